@@ -0,0 +1,90 @@
+//! Hot spots of the path from downloaded candles to the plots.
+//!
+//! ```bash
+//! cargo bench --bench data_path
+//! ```
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use netstrat::{
+    data::Data,
+    download,
+    netstrat::{aggregate, axis::Axis, indicators::math, synthetic},
+    sources::binance::Interval,
+    widgets::{candle_boxes, volume_bars, VolumeKind},
+};
+
+const CANDLES: usize = 500_000;
+const MINUTE: i64 = 60_000;
+const HOUR: i64 = 60 * MINUTE;
+const PERIOD: usize = 14;
+
+fn merge(c: &mut Criterion) {
+    let older = synthetic::klines(1, 0, MINUTE, CANDLES);
+    let newer = synthetic::klines(2, CANDLES as i64 * MINUTE, MINUTE, CANDLES);
+
+    c.bench_function("merge 2x500k pages", |b| {
+        b.iter_batched(
+            || newer.clone(),
+            |mut klines| {
+                download::insert_page(&mut klines, black_box(&older));
+                klines
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn extents(c: &mut Criterion) {
+    let klines = synthetic::klines(1, 0, MINUTE, CANDLES);
+
+    c.bench_function("extents 500k", |b| {
+        b.iter_batched(
+            || klines.clone(),
+            |klines| Data::new(black_box(klines)),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn projections(c: &mut Criterion) {
+    let klines = synthetic::klines(1, 0, MINUTE, CANDLES);
+    let step = Axis::Time.step(Some(Interval::Minute), &klines);
+
+    c.bench_function("volume bars 500k", |b| {
+        b.iter(|| volume_bars(black_box(&klines), VolumeKind::Base, step))
+    });
+    c.bench_function("candle boxes 500k", |b| {
+        b.iter(|| candle_boxes(black_box(&klines), &Axis::Time, step))
+    });
+}
+
+fn resample(c: &mut Criterion) {
+    let klines = synthetic::klines(1, 0, MINUTE, CANDLES);
+
+    c.bench_function("resample 500k 1m to 1h", |b| {
+        b.iter(|| aggregate::resample(black_box(&klines), HOUR))
+    });
+}
+
+fn indicators(c: &mut Criterion) {
+    let closes = Data::new(synthetic::klines(1, 0, MINUTE, CANDLES)).closes();
+
+    c.bench_function("sma 500k", |b| {
+        b.iter(|| math::sma(black_box(&closes), PERIOD))
+    });
+    c.bench_function("ema 500k", |b| {
+        b.iter(|| math::ema(black_box(&closes), PERIOD))
+    });
+    c.bench_function("rsi 500k", |b| {
+        b.iter(|| math::rsi(black_box(&closes), PERIOD))
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = merge, extents, projections, resample, indicators
+}
+criterion_main!(benches);
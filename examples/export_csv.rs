@@ -0,0 +1,35 @@
+//! Headless export of the last day of minute klines.
+//!
+//! ```bash
+//! cargo run --example export_csv --no-default-features -- BTCUSDT
+//! ```
+use std::path::Path;
+
+use netstrat::{download::download, export, props::Props, sources::binance::Client};
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+
+    let symbol = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "BTCUSDT".to_string());
+    let props = Props::default();
+
+    let klines = match download(&Client::default(), &symbol, &props).await {
+        Ok(klines) => klines,
+        Err(err) => {
+            eprintln!("failed to download klines: {}", err.chain());
+            std::process::exit(1);
+        }
+    };
+
+    let name = export::file_name(&symbol, &props, export::TimeFormat::Millis);
+    match export::to_csv(Path::new(&name), &klines, export::Columns::All) {
+        Ok(path) => println!("exported {} klines to {}", klines.len(), path.display()),
+        Err(err) => {
+            eprintln!("failed to export klines: {}", err.chain());
+            std::process::exit(1);
+        }
+    }
+}
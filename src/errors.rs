@@ -0,0 +1,203 @@
+use std::{error::Error as StdError, io, num::ParseFloatError, path::Path, path::PathBuf};
+
+use crossbeam::channel::SendError;
+use quick_error::{quick_error, Context};
+
+use crate::netstrat::bounds::Bounds;
+
+quick_error! {
+    /// Error of the data layer: fetching, decoding, validating and exporting data.
+    #[derive(Debug)]
+    pub enum Error {
+        Http(url: String, err: reqwest::Error) {
+            display("request to {} failed", url)
+            source(err)
+            context(url: &'a str, err: reqwest::Error) -> (url.to_string(), err)
+        }
+        RateLimited(url: String, retry_after: Option<u64>) {
+            display("rate limited on {}, retry after: {:?}s", url, retry_after)
+        }
+        Banned(url: String, retry_after: Option<u64>) {
+            display("ip banned on {}, retry after: {:?}s", url, retry_after)
+        }
+        Api(url: String, code: i64, msg: String) {
+            display("request to {} rejected with code {}: {}", url, code, msg)
+        }
+        InvalidSymbol(url: String, symbol: String) {
+            display("request to {} rejected, invalid symbol {}", url, symbol)
+        }
+        Status(url: String, status: u16) {
+            display("request to {} failed with status {}", url, status)
+        }
+        Influx(url: String, status: u16, msg: String) {
+            display("write to {} failed with status {}: {}", url, status, msg)
+        }
+        Decode(symbol: String, bounds: Bounds, err: serde_json::Error) {
+            display("failed to decode data for {} on page {:?}", symbol, bounds)
+            source(err)
+            context(ctx: (&'a str, Bounds), err: serde_json::Error)
+                -> (ctx.0.to_string(), ctx.1, err)
+        }
+        Response(url: String, err: serde_json::Error) {
+            display("failed to decode response from {}", url)
+            source(err)
+            context(url: &'a str, err: serde_json::Error) -> (url.to_string(), err)
+        }
+        InvalidValue(symbol: String, bounds: Bounds, err: ParseFloatError) {
+            display("invalid value in data for {} on page {:?}", symbol, bounds)
+            source(err)
+            context(ctx: (&'a str, Bounds), err: ParseFloatError)
+                -> (ctx.0.to_string(), ctx.1, err)
+        }
+        Validation(msg: String) {
+            display("validation failed: {}", msg)
+        }
+        ChannelSend(topic: String) {
+            display("failed to send message to {} channel", topic)
+        }
+        Io(path: PathBuf, err: io::Error) {
+            display("failed to access {:?}", path)
+            source(err)
+            context(path: &'a Path, err: io::Error) -> (path.to_path_buf(), err)
+        }
+        Csv(path: PathBuf, err: csv::Error) {
+            display("failed to process csv {:?}", path)
+            source(err)
+            context(path: &'a Path, err: csv::Error) -> (path.to_path_buf(), err)
+        }
+        Json(path: PathBuf, err: serde_json::Error) {
+            display("failed to process json {:?}", path)
+            source(err)
+            context(path: &'a Path, err: serde_json::Error) -> (path.to_path_buf(), err)
+        }
+        Bind(addr: String, err: io::Error) {
+            display("failed to listen on {}", addr)
+            source(err)
+        }
+        Script(line: Option<usize>, msg: String) {
+            display("script error{}: {}", line.map(|l| format!(" at line {l}")).unwrap_or_default(), msg)
+        }
+        Expression(pos: usize, msg: String) {
+            display("expression error at {}: {}", pos, msg)
+        }
+        View(msg: String) {
+            display("invalid view: {}", msg)
+        }
+        ViewVersion(version: u64) {
+            display("unsupported view version {}", version)
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Short message suitable to be shown to the user.
+    pub fn user_message(&self) -> String {
+        match self {
+            Error::Http(_, err) if err.is_connect() || err.is_timeout() => {
+                "network unreachable".to_string()
+            }
+            Error::Http(_, err) => format!("request failed: {err}"),
+            Error::RateLimited(_, Some(secs)) => {
+                format!("Binance rate limit hit, retry in {secs}s")
+            }
+            Error::RateLimited(_, None) | Error::Api(_, -1003, _) => {
+                "Binance rate limit hit".to_string()
+            }
+            Error::Banned(_, Some(secs)) => {
+                format!("Binance banned this IP for too many requests, retry in {secs}s")
+            }
+            Error::Banned(_, None) => "Binance banned this IP for too many requests".to_string(),
+            Error::InvalidSymbol(_, symbol) => format!("Binance rejected symbol {symbol}"),
+            Error::Api(_, -1121, _) => "Binance doesn't know the symbol".to_string(),
+            Error::Api(_, -1120, _) => "Binance doesn't support the interval".to_string(),
+            Error::Api(_, code, msg) => format!("Binance rejected the request ({code}): {msg}"),
+            Error::Status(_, status) => format!("Binance responded with status {status}"),
+            Error::Influx(_, 401 | 403, _) => "InfluxDB rejected the token".to_string(),
+            Error::Influx(_, status, msg) => {
+                format!("InfluxDB responded with status {status}: {msg}")
+            }
+            Error::Decode(symbol, _, _) | Error::InvalidValue(symbol, _, _) => {
+                format!("Binance returned unexpected data for {symbol}")
+            }
+            Error::Response(_, _) => "Binance returned unexpected response".to_string(),
+            Error::Validation(msg) => msg.clone(),
+            Error::ChannelSend(topic) => format!("internal error: {topic} channel is closed"),
+            Error::Io(path, _) | Error::Csv(path, _) | Error::Json(path, _) => {
+                format!("failed to access {path:?}")
+            }
+            Error::Bind(addr, err) => format!("failed to start server on {addr}: {err}"),
+            Error::Script(_, _) | Error::Expression(_, _) | Error::View(_) => self.to_string(),
+            Error::ViewVersion(version) => {
+                format!("unsupported view version {version}, it was shared from a newer netstrat")
+            }
+        }
+    }
+
+    /// Error message with all the underlying causes.
+    pub fn chain(&self) -> String {
+        let mut msg = self.to_string();
+        let mut source = self.source();
+        while let Some(err) = source {
+            msg = format!("{msg}: {err}");
+            source = err.source();
+        }
+
+        msg
+    }
+}
+
+impl<'a, T> From<Context<&'a str, SendError<T>>> for Error {
+    fn from(ctx: Context<&'a str, SendError<T>>) -> Self {
+        Error::ChannelSend(ctx.0.to_string())
+    }
+}
+
+#[cfg(test)]
+mod errors_tests {
+    use super::*;
+
+    #[test]
+    fn test_chain() {
+        let err = serde_json::from_str::<Vec<i64>>("[1,").unwrap_err();
+        let err = Error::Decode("BTCUSDT".to_string(), Bounds(0, 10), err);
+
+        assert!(err.chain().starts_with(&err.to_string()));
+        assert!(err.chain().len() > err.to_string().len());
+        assert_eq!(
+            err.user_message(),
+            "Binance returned unexpected data for BTCUSDT"
+        );
+    }
+
+    #[test]
+    fn test_api_user_message() {
+        let api = |code: i64, msg: &str| Error::Api("url".to_string(), code, msg.to_string());
+
+        assert_eq!(
+            api(-1121, "Invalid symbol.").user_message(),
+            "Binance doesn't know the symbol"
+        );
+        assert_eq!(
+            api(-1120, "Invalid interval.").user_message(),
+            "Binance doesn't support the interval"
+        );
+        assert_eq!(
+            api(-1003, "Too many requests.").user_message(),
+            "Binance rate limit hit"
+        );
+        assert_eq!(
+            api(-1102, "Mandatory parameter 'symbol' was not sent.").user_message(),
+            "Binance rejected the request (-1102): Mandatory parameter 'symbol' was not sent."
+        );
+        assert_eq!(
+            Error::Banned("url".to_string(), Some(120)).user_message(),
+            "Binance banned this IP for too many requests, retry in 120s"
+        );
+        assert_eq!(
+            Error::InvalidSymbol("url".to_string(), "FOO".to_string()).user_message(),
+            "Binance rejected symbol FOO"
+        );
+    }
+}
@@ -0,0 +1,10 @@
+pub mod errors;
+pub mod netstrat;
+pub mod network;
+pub mod sources;
+#[cfg(feature = "gui")]
+pub mod widgets;
+#[cfg(feature = "gui")]
+pub mod windows;
+
+pub use crate::netstrat::{bounds, data, download, export, props};
@@ -1,46 +1,274 @@
 use std::collections::HashMap;
-use std::time::SystemTime;
+use std::time::{Instant, SystemTime};
 
+use chrono::Utc;
+use clap::Parser;
 use crossbeam::channel::unbounded;
 use eframe::{run_native, App, CreationContext, NativeOptions};
-use egui::{CentralPanel, Context, Layout, TopBottomPanel};
-use tokio;
-use tracing::{info, trace};
+use egui::{Align2, CentralPanel, Context, Key, Layout, TopBottomPanel, Window};
+use tracing::{error, info, trace};
 
-use widgets::Theme;
-use windows::{AppWindow, SymbolsGraph};
+use netstrat::netstrat::{
+    actions::{Actions, Shortcut},
+    cache::Cache,
+    channels,
+    download::DownloadConfig,
+    export::{ConfirmLimits, ExportOptions},
+    influx::Influx,
+    journal::JournalConfig,
+    last_used::LastUsed,
+    launch::Args,
+    logging::Logging,
+    memory::CandlesBudget,
+    notify::Notifier,
+    props::{CandlesBand, Props},
+    scheduler::Jobs,
+    session::{Session, SessionFile, SNAPSHOT_PERIOD},
+    timezone::Timezone,
+    watchlist::Watchlist,
+};
+use netstrat::network::{webhook::Webhook, ws_server::Broadcaster};
+use netstrat::sources::binance::{set_weight_budget, Client, CHILD_WEIGHT_BUDGET};
+use netstrat::widgets::{Palette, StatusBar, Theme, Toast, Toasts};
+use netstrat::windows::Watchlist as WatchlistWindow;
+use netstrat::windows::{AppWindow, Network, Scheduler, Settings, SymbolsGraph};
 
-mod netstrat;
-mod network;
-mod sources;
-mod widgets;
-mod windows;
+const ACTION_PALETTE: &str = "palette";
+const ACTION_FULLSCREEN: &str = "fullscreen";
+const ACTION_SAVE_SESSION: &str = "session.save";
 
 struct TemplateApp {
     windows: Vec<Box<dyn AppWindow>>,
+    settings: Settings,
     theme: Theme,
+    toasts: Toasts,
+    status: StatusBar,
+    actions: Actions,
+    palette: Palette,
+    session_file: SessionFile,
+    /// Snapshot of a session which didn't exit cleanly, offered to restore.
+    unclean_session: Option<Session>,
+    last_snapshot: Option<Instant>,
+    /// Whether the app was opened from another instance, see [`Args::child`].
+    child: bool,
+    /// Whether only the chart plots are shown.
+    fullscreen: bool,
 }
 
 impl TemplateApp {
-    fn new(_ctx: &CreationContext<'_>) -> Self {
+    fn new(ctx: &CreationContext<'_>, args: Args, logging: Logging) -> Self {
         info!("Creating app...");
 
-        let (s, r) = unbounded();
+        let (s, r) = channels::latest();
+        let (s_toasts, r_toasts) = unbounded();
+        let (s_status, r_status) = unbounded();
+        let broadcaster = Broadcaster::default();
+        let budget = CandlesBudget::default();
+        let cache = Cache::default();
+        let notifier = Notifier::default();
+        let session_file = SessionFile::default();
+        let confirm_limits = ConfirmLimits::default();
+        let timezone = Timezone::default();
+        let export_options = ExportOptions::new(timezone.clone());
+        let download_config = DownloadConfig::default();
+        let influx = Influx::default();
+        let webhook = Webhook::default();
+        let last_used = LastUsed::default();
+        let band = CandlesBand::default();
+        let journal = JournalConfig::default();
+        let jobs = Jobs::default();
+        if !args.child {
+            jobs.spawn(Client::default(), export_options.clone());
+        }
+        let watchlist = Watchlist::default();
+        let actions = Actions::default();
+        actions.register(
+            ACTION_PALETTE,
+            "command palette",
+            Some(Shortcut::command(Key::P)),
+        );
+        actions.register(
+            ACTION_FULLSCREEN,
+            "toggle fullscreen",
+            Some(Shortcut::key(Key::F)),
+        );
+        actions.register(ACTION_SAVE_SESSION, "save session", None);
 
         let mut visibility_map = HashMap::new();
         visibility_map.insert("debug".to_string(), false);
 
+        let mut graph = SymbolsGraph::new(
+            s.clone(),
+            r,
+            s_toasts.clone(),
+            s_status,
+            broadcaster.clone(),
+            budget.clone(),
+            cache.clone(),
+            notifier.clone(),
+            confirm_limits.clone(),
+            export_options.clone(),
+            timezone.clone(),
+            download_config.clone(),
+            influx.clone(),
+            webhook.clone(),
+            last_used.clone(),
+            band.clone(),
+            journal.clone(),
+            watchlist.clone(),
+            actions.clone(),
+            ctx.storage,
+            true,
+        );
+        if let Some(symbol) = args.symbol.clone() {
+            let props = args.props(Utc::now().naive_utc()).unwrap_or_else(|err| {
+                error!("invalid launch flags, using defaults: {}", err.chain());
+                let toast = Toast::error(format!("{}, using defaults", err.user_message()));
+                if let Err(err) = s_toasts.send(toast) {
+                    error!("failed to send toast: {err}");
+                }
+                Props::default()
+            });
+            graph.open(symbol, props);
+        }
+        if let Some(view) = &args.view {
+            graph.open_view(view);
+        }
+        if let Some(path) = &args.import {
+            graph.import_file(&ctx.egui_ctx, path);
+        }
+
         Self {
-            windows: vec![Box::new(SymbolsGraph::new(s, r, true))],
+            windows: vec![
+                Box::new(graph),
+                Box::new(Network::new(false, webhook.clone(), actions.clone())),
+                Box::new(Scheduler::new(
+                    false,
+                    jobs,
+                    actions.clone(),
+                    s_toasts.clone(),
+                    ctx.storage,
+                )),
+                Box::new(WatchlistWindow::new(
+                    false,
+                    watchlist,
+                    s,
+                    actions.clone(),
+                    ctx.storage,
+                )),
+            ],
+            settings: Settings::new(
+                false,
+                broadcaster.clone(),
+                budget,
+                cache,
+                notifier,
+                confirm_limits,
+                export_options,
+                timezone,
+                download_config,
+                influx,
+                webhook,
+                last_used,
+                band,
+                journal,
+                logging,
+                actions.clone(),
+                s_toasts,
+                ctx.storage,
+            ),
             theme: Theme::new(),
+            toasts: Toasts::new(r_toasts),
+            status: StatusBar::new(r_status, broadcaster),
+            actions,
+            palette: Default::default(),
+            unclean_session: session_file.load().filter(|s| !s.clean && !args.child),
+            session_file,
+            last_snapshot: None,
+            child: args.child,
+            fullscreen: false,
         }
     }
-}
 
-impl App for TemplateApp {
-    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
-        let start = SystemTime::now();
+    /// F toggles fullscreen through its action, escape leaves it when no text is edited.
+    /// egui has no function keys, so F11 can't be used.
+    fn fullscreen_toggled(&self, ctx: &Context) -> bool {
+        let escape =
+            self.fullscreen && !ctx.wants_keyboard_input() && ctx.input().key_pressed(Key::Escape);
+        self.actions.take(ACTION_FULLSCREEN) || escape
+    }
+
+    fn handle_actions(&mut self) {
+        if self.actions.take(ACTION_PALETTE) {
+            self.palette.open();
+        }
+        if self.actions.take(ACTION_SAVE_SESSION) {
+            info!("saving session");
+            self.write_snapshot(false);
+        }
+    }
+
+    fn write_snapshot(&mut self, clean: bool) {
+        if self.child {
+            return;
+        }
+        let mut session = Session {
+            clean,
+            ..Default::default()
+        };
+        self.windows.iter().for_each(|w| w.snapshot(&mut session));
+        self.settings.snapshot(&mut session);
 
+        if let Err(err) = self.session_file.store(&session) {
+            error!("failed to write session snapshot: {}", err.chain());
+        }
+        self.last_snapshot = Some(Instant::now());
+    }
+
+    /// Offers to restore the unclean session, snapshots are written once it is answered.
+    fn session_ui(&mut self, ctx: &Context) {
+        let Some(session) = &self.unclean_session else {
+            if self
+                .last_snapshot
+                .is_none_or(|t| t.elapsed() >= SNAPSHOT_PERIOD)
+            {
+                self.write_snapshot(false);
+            }
+            return;
+        };
+
+        let mut restore = None;
+        Window::new("restore session")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("netstrat did not exit cleanly last time.");
+                if let Some(view) = &session.view {
+                    ui.label(format!("{} {} was open.", view.symbol, view.interval));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("restore").clicked() {
+                        restore = Some(true);
+                    }
+                    if ui.button("discard").clicked() {
+                        restore = Some(false);
+                    }
+                });
+            });
+
+        if let Some(restore) = restore {
+            if let Some(session) = self.unclean_session.take() {
+                if restore {
+                    info!("restoring session: {session:?}");
+                    self.windows.iter_mut().for_each(|w| w.restore(&session));
+                    self.settings.restore(&session);
+                }
+            }
+        }
+    }
+
+    fn panels(&mut self, ctx: &Context) {
         TopBottomPanel::top("header").show(ctx, |ui| {
             ui.with_layout(Layout::left_to_right(), |ui| {
                 ui.add(&mut self.theme);
@@ -48,12 +276,50 @@ impl App for TemplateApp {
                 self.windows.iter_mut().for_each(|w| {
                     w.as_mut().toggle_btn(ui);
                 });
+                self.settings.toggle_btn(ui);
+            });
+        });
+
+        TopBottomPanel::bottom("status bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                self.status.ui(ui);
+                ui.separator();
+                self.settings.status_ui(ui);
             });
         });
 
         CentralPanel::default().show(ctx, |ui| {
             self.windows.iter_mut().for_each(|w| w.show(ui));
+            self.settings.show(ui);
         });
+    }
+}
+
+impl App for TemplateApp {
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        let start = SystemTime::now();
+
+        self.actions.handle_keys(ctx);
+        self.handle_actions();
+        self.windows.iter_mut().for_each(|w| w.tick(ctx));
+        self.settings.tick(ctx);
+
+        if self.fullscreen_toggled(ctx) {
+            self.fullscreen = !self.fullscreen;
+        }
+        if self.fullscreen {
+            CentralPanel::default().show(ctx, |ui| {
+                if !self.windows.iter_mut().any(|w| w.fullscreen(ui)) {
+                    ui.label("nothing to show, press F to leave fullscreen");
+                }
+            });
+        } else {
+            self.panels(ctx);
+        }
+
+        self.palette.show(ctx, &self.actions);
+        self.toasts.show(ctx);
+        self.session_ui(ctx);
 
         trace!(
             "time elapsed per frame: {:?}",
@@ -62,15 +328,31 @@ impl App for TemplateApp {
                 .expect("failed to compute duration_since")
         );
     }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        if self.child {
+            return;
+        }
+        self.windows.iter_mut().for_each(|w| w.save(storage));
+        self.settings.save(storage);
+    }
+
+    fn on_exit(&mut self, _gl: &eframe::glow::Context) {
+        self.write_snapshot(true);
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt::init();
+    let logging = Logging::init();
+    let args = Args::parse();
+    if args.child {
+        set_weight_budget(CHILD_WEIGHT_BUDGET);
+    }
 
     run_native(
         "netstrat",
         NativeOptions::default(),
-        Box::new(|cc| Box::new(TemplateApp::new(cc))),
+        Box::new(|cc| Box::new(TemplateApp::new(cc, args, logging))),
     );
 }
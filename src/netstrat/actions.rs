@@ -0,0 +1,273 @@
+//! Registry of the app actions, listed in the command palette and run by their shortcuts.
+//!
+//! Windows register their actions when they are built and take the triggered ones on every
+//! frame, shortcuts are only bound here so the palette shows the keys which run them.
+
+use std::sync::{Arc, RwLock};
+
+use egui::{Context, Event, Key, Modifiers};
+use tracing::{debug, error};
+
+/// Keys running an action.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shortcut {
+    Key(Modifiers, Key),
+    /// Typed text, for keys egui has no [`Key`] of, e.g. `/`.
+    Text(&'static str),
+}
+
+impl Shortcut {
+    pub const fn key(key: Key) -> Self {
+        Shortcut::Key(Modifiers::NONE, key)
+    }
+
+    /// Key with ctrl, or cmd on mac.
+    pub const fn command(key: Key) -> Self {
+        Shortcut::Key(Modifiers::COMMAND, key)
+    }
+
+    /// Name of the keys, e.g. `Ctrl+P`.
+    pub fn label(&self) -> String {
+        match self {
+            Shortcut::Key(modifiers, key) => {
+                let mut label = String::new();
+                if modifiers.command || modifiers.ctrl {
+                    label.push_str("Ctrl+");
+                }
+                if modifiers.alt {
+                    label.push_str("Alt+");
+                }
+                if modifiers.shift {
+                    label.push_str("Shift+");
+                }
+                let key = format!("{key:?}");
+                label.push_str(key.strip_prefix("Num").unwrap_or(&key));
+                label
+            }
+            Shortcut::Text(text) => text.to_string(),
+        }
+    }
+
+    /// Keys without modifiers type text, they run actions only while no text is edited.
+    fn is_plain(&self) -> bool {
+        match self {
+            Shortcut::Key(modifiers, _) => modifiers.is_none() || modifiers.shift_only(),
+            Shortcut::Text(_) => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Action {
+    /// Unique id, e.g. `window.settings`.
+    pub id: String,
+    pub name: String,
+    pub shortcut: Option<Shortcut>,
+    /// Disabled actions are listed but can't run.
+    pub enabled: bool,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    actions: Vec<Action>,
+    /// Ids of the actions run and not yet taken by their owners.
+    triggered: Vec<String>,
+}
+
+/// Handle to the registry, clones share it.
+#[derive(Debug, Clone, Default)]
+pub struct Actions {
+    registry: Arc<RwLock<Registry>>,
+}
+
+impl Actions {
+    /// Adds the action or replaces the one with the same id. A shortcut bound to another
+    /// action is dropped.
+    pub fn register(&self, id: &str, name: &str, shortcut: Option<Shortcut>) {
+        let mut registry = self.registry.write().unwrap();
+        let shortcut = shortcut.filter(|s| {
+            let taken = registry
+                .actions
+                .iter()
+                .find(|a| a.id != id && a.shortcut == Some(*s));
+            if let Some(taken) = taken {
+                error!("shortcut {} of {id} is taken by {}", s.label(), taken.id);
+            }
+            taken.is_none()
+        });
+        let action = Action {
+            id: id.to_string(),
+            name: name.to_string(),
+            shortcut,
+            enabled: true,
+        };
+        match registry.actions.iter_mut().find(|a| a.id == id) {
+            Some(a) => *a = action,
+            None => registry.actions.push(action),
+        }
+    }
+
+    pub fn set_enabled(&self, id: &str, enabled: bool) {
+        let mut registry = self.registry.write().unwrap();
+        if let Some(a) = registry.actions.iter_mut().find(|a| a.id == id) {
+            a.enabled = enabled;
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<Action> {
+        let registry = self.registry.read().unwrap();
+        registry.actions.iter().find(|a| a.id == id).cloned()
+    }
+
+    /// Runs the action once its owner takes it, returns false when it is unknown or
+    /// disabled.
+    pub fn trigger(&self, id: &str) -> bool {
+        let mut registry = self.registry.write().unwrap();
+        let enabled = registry.actions.iter().any(|a| a.id == id && a.enabled);
+        if enabled && !registry.triggered.iter().any(|t| t == id) {
+            debug!("triggered action {id}");
+            registry.triggered.push(id.to_string());
+        }
+        enabled
+    }
+
+    /// Returns true once after the action was triggered.
+    pub fn take(&self, id: &str) -> bool {
+        let mut registry = self.registry.write().unwrap();
+        let len = registry.triggered.len();
+        registry.triggered.retain(|t| t != id);
+        registry.triggered.len() != len
+    }
+
+    /// Triggers the actions of the shortcuts pressed on this frame. Keys without modifiers
+    /// are left to text edits.
+    pub fn handle_keys(&self, ctx: &Context) {
+        let editing = ctx.wants_keyboard_input();
+        let shortcuts: Vec<(String, Shortcut)> = {
+            let registry = self.registry.read().unwrap();
+            registry
+                .actions
+                .iter()
+                .filter_map(|a| Some((a.id.clone(), a.shortcut?)))
+                .filter(|(_, s)| !editing || !s.is_plain())
+                .collect()
+        };
+
+        let mut input = ctx.input_mut();
+        shortcuts.into_iter().for_each(|(id, shortcut)| {
+            let pressed = match shortcut {
+                Shortcut::Key(modifiers, key) => input.consume_key(modifiers, key),
+                Shortcut::Text(text) => input
+                    .events
+                    .iter()
+                    .any(|e| matches!(e, Event::Text(t) if t == text)),
+            };
+            if pressed {
+                self.trigger(&id);
+            }
+        });
+    }
+
+    /// Up to `limit` actions whose names contain the letters of the query in order, the
+    /// closest matches first. All actions are listed for an empty query.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<Action> {
+        let registry = self.registry.read().unwrap();
+        let mut found: Vec<_> = registry
+            .actions
+            .iter()
+            .filter_map(|a| Some((fuzzy_score(query, &a.name)?, a)))
+            .collect();
+        found.sort_by_key(|(score, a)| (*score, a.name.len()));
+
+        found
+            .into_iter()
+            .take(limit)
+            .map(|(_, a)| a.clone())
+            .collect()
+    }
+}
+
+/// Letters of `text` skipped to match the query case-insensitively in order, matches at
+/// word starts skip nothing.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut pos = 0;
+    let mut score = 0;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let at = pos + text[pos..].iter().position(|c| *c == q)?;
+        let word_start = at == 0 || !text[at - 1].is_alphanumeric();
+        if !word_start {
+            score += at - pos;
+        }
+        pos = at + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod actions_tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger_and_take() {
+        let actions = Actions::default();
+        actions.register("window.settings", "toggle settings", None);
+        actions.register("export", "export", Some(Shortcut::command(Key::E)));
+
+        assert!(actions.trigger("window.settings"));
+        assert!(actions.trigger("window.settings"));
+        assert!(!actions.trigger("unknown"));
+        assert!(actions.take("window.settings"));
+        assert!(!actions.take("window.settings"));
+
+        actions.set_enabled("export", false);
+        assert!(!actions.trigger("export"));
+        assert!(!actions.take("export"));
+        assert!(!actions.get("export").unwrap().enabled);
+    }
+
+    #[test]
+    fn test_shortcuts_are_unique() {
+        let actions = Actions::default();
+        actions.register("a", "a", Some(Shortcut::key(Key::F)));
+        actions.register("b", "b", Some(Shortcut::key(Key::F)));
+        actions.register("a", "a again", Some(Shortcut::key(Key::F)));
+
+        assert_eq!(actions.get("b").unwrap().shortcut, None);
+        assert_eq!(
+            actions.get("a").unwrap().shortcut,
+            Some(Shortcut::key(Key::F))
+        );
+        assert_eq!(actions.get("a").unwrap().name, "a again");
+        assert_eq!(Shortcut::command(Key::P).label(), "Ctrl+P");
+        assert_eq!(Shortcut::key(Key::Num1).label(), "1");
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let actions = Actions::default();
+        [
+            ("window.settings", "toggle settings window"),
+            ("interval.1h", "interval 1h"),
+            ("interval.1m", "interval 1m"),
+            ("session.save", "save session"),
+        ]
+        .into_iter()
+        .for_each(|(id, name)| actions.register(id, name, None));
+
+        let ids = |query| {
+            actions
+                .search(query, 10)
+                .into_iter()
+                .map(|a| a.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids("set"), vec!["window.settings"]);
+        assert_eq!(ids("se"), vec!["window.settings", "session.save"]);
+        assert_eq!(ids("int 1h"), vec!["interval.1h"]);
+        assert_eq!(ids("sv ses"), vec!["session.save"]);
+        assert_eq!(ids("xyz"), Vec::<String>::new());
+        assert_eq!(ids("").len(), 4);
+    }
+}
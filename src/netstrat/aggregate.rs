@@ -0,0 +1,300 @@
+//! Candles built locally from trades for intervals Binance has no klines for,
+//! and wider candles resampled from loaded ones.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::sources::binance::{Kline, Trade};
+
+use super::data::Data;
+
+/// What to do with buckets without trades.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmptyBuckets {
+    #[default]
+    Skip,
+    /// Emit zero volume candle at the previous close.
+    CarryForward,
+}
+
+/// Buckets trades sorted by time into candles of `bucket_millis`.
+///
+/// Buckets are aligned to the multiples of `bucket_millis` since epoch like Binance klines are.
+/// Candles closing in the future are marked as forming.
+pub fn aggregate(trades: &[Trade], bucket_millis: i64, empty: EmptyBuckets) -> Data {
+    let mut klines: Vec<Kline> = vec![];
+    if bucket_millis <= 0 {
+        return Data::new(klines);
+    }
+
+    trades.iter().for_each(|t| {
+        let t_open = t.time.div_euclid(bucket_millis) * bucket_millis;
+        match klines.last_mut() {
+            Some(k) if k.t_open == t_open => add_trade(k, t),
+            last => {
+                if let (EmptyBuckets::CarryForward, Some(prev)) = (empty, last.copied()) {
+                    let mut gap_open = prev.t_open + bucket_millis;
+                    while gap_open < t_open {
+                        klines.push(flat(prev.close, gap_open, bucket_millis));
+                        gap_open += bucket_millis;
+                    }
+                }
+
+                let mut k = flat(t.price, t_open, bucket_millis);
+                add_trade(&mut k, t);
+                klines.push(k);
+            }
+        }
+    });
+
+    let now = Utc::now().timestamp_millis();
+    klines.iter_mut().for_each(|k| k.closed = k.t_close < now);
+
+    Data::new(klines)
+}
+
+/// Merges klines sorted by time into candles of `bucket_millis` aligned like in [`aggregate`].
+///
+/// Klines as wide as the bucket or wider are kept as they are. A candle is forming if any of its klines is.
+pub fn resample(klines: &[Kline], bucket_millis: i64) -> Data {
+    let mut resampled: Vec<Kline> = vec![];
+    klines.iter().for_each(|k| {
+        if bucket_millis <= 0 || k.t_close - k.t_open + 1 >= bucket_millis {
+            resampled.push(*k);
+            return;
+        }
+
+        let t_open = k.t_open.div_euclid(bucket_millis) * bucket_millis;
+        match resampled.last_mut() {
+            Some(r) if r.t_open == t_open => {
+                r.high = r.high.max(k.high);
+                r.low = r.low.min(k.low);
+                r.close = k.close;
+                r.volume += k.volume;
+                r.quote_asset_volume = sum(r.quote_asset_volume, k.quote_asset_volume);
+                r.number_of_trades = r
+                    .number_of_trades
+                    .zip(k.number_of_trades)
+                    .map(|(a, b)| a + b);
+                r.taker_buy_base_asset_volume =
+                    sum(r.taker_buy_base_asset_volume, k.taker_buy_base_asset_volume);
+                r.taker_buy_quote_asset_volume = sum(
+                    r.taker_buy_quote_asset_volume,
+                    k.taker_buy_quote_asset_volume,
+                );
+                r.closed &= k.closed;
+            }
+            _ => resampled.push(Kline {
+                t_open,
+                t_close: t_open + bucket_millis - 1,
+                ..*k
+            }),
+        }
+    });
+
+    Data::new(resampled)
+}
+
+/// Sum of optional volumes, unknown if either is.
+fn sum(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    a.zip(b).map(|(a, b)| a + b)
+}
+
+fn flat(price: f32, t_open: i64, bucket_millis: i64) -> Kline {
+    Kline {
+        t_open,
+        t_close: t_open + bucket_millis - 1,
+        open: price,
+        high: price,
+        low: price,
+        close: price,
+        quote_asset_volume: Some(0.0),
+        number_of_trades: Some(0),
+        taker_buy_base_asset_volume: Some(0.0),
+        taker_buy_quote_asset_volume: Some(0.0),
+        ..Default::default()
+    }
+}
+
+fn add_trade(k: &mut Kline, t: &Trade) {
+    k.high = k.high.max(t.price);
+    k.low = k.low.min(t.price);
+    k.close = t.price;
+    k.volume += t.qty;
+    add(&mut k.quote_asset_volume, t.qty * t.price);
+    k.number_of_trades = Some(k.number_of_trades.unwrap_or_default() + 1);
+    if !t.is_buyer_maker {
+        add(&mut k.taker_buy_base_asset_volume, t.qty);
+        add(&mut k.taker_buy_quote_asset_volume, t.qty * t.price);
+    }
+}
+
+fn add(sum: &mut Option<f32>, v: f32) {
+    *sum = Some(sum.unwrap_or_default() + v);
+}
+
+#[cfg(test)]
+mod aggregate_tests {
+    use crate::{netstrat::bounds::Bounds, sources::binance::mock::MockBinance};
+
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/agg_trades.json");
+
+    fn candle(t_open: i64, ohlc: [f32; 4], volume: f32, trades: i64) -> Kline {
+        Kline {
+            t_open,
+            t_close: t_open + 999,
+            open: ohlc[0],
+            high: ohlc[1],
+            low: ohlc[2],
+            close: ohlc[3],
+            volume,
+            number_of_trades: Some(trades),
+            ..Default::default()
+        }
+    }
+
+    fn assert_candles(actual: &[Kline], expected: &[Kline]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        actual.iter().zip(expected).for_each(|(a, e)| {
+            assert_eq!(
+                (a.t_open, a.t_close, a.open, a.high, a.low, a.close),
+                (e.t_open, e.t_close, e.open, e.high, e.low, e.close)
+            );
+            assert!((a.volume - e.volume).abs() < 1e-4, "{a:?} != {e:?}");
+            assert_eq!(a.number_of_trades, e.number_of_trades);
+        });
+    }
+
+    async fn fixture_trades() -> Vec<Trade> {
+        let mock = MockBinance::start().await;
+        mock.agg_trades_raw(1_000, FIXTURE).await;
+
+        mock.client()
+            .agg_trades("BTCUSDT", Bounds(1_000, 5_000))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_fixture_skip_empty() {
+        let data = aggregate(&fixture_trades().await, 1_000, EmptyBuckets::Skip);
+
+        assert_candles(
+            &data.vals,
+            &[
+                candle(1_000, [100.0, 102.0, 99.5, 101.0], 3.5, 4),
+                candle(2_000, [101.5, 101.5, 101.5, 101.5], 0.25, 1),
+                candle(4_000, [103.0, 104.0, 103.0, 104.0], 1.5, 2),
+            ],
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_fixture_carry_forward() {
+        let data = aggregate(&fixture_trades().await, 1_000, EmptyBuckets::CarryForward);
+
+        assert_candles(
+            &data.vals,
+            &[
+                candle(1_000, [100.0, 102.0, 99.5, 101.0], 3.5, 4),
+                candle(2_000, [101.5, 101.5, 101.5, 101.5], 0.25, 1),
+                candle(3_000, [101.5, 101.5, 101.5, 101.5], 0.0, 0),
+                candle(4_000, [103.0, 104.0, 103.0, 104.0], 1.5, 2),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_aggregate_no_trades() {
+        assert!(aggregate(&[], 1_000, EmptyBuckets::CarryForward)
+            .vals
+            .is_empty());
+    }
+
+    #[test]
+    fn test_taker_buy_volume() {
+        let trades = [
+            Trade {
+                price: 10.0,
+                qty: 1.0,
+                is_buyer_maker: false,
+                ..Default::default()
+            },
+            Trade {
+                price: 20.0,
+                qty: 2.0,
+                is_buyer_maker: true,
+                ..Default::default()
+            },
+        ];
+        let k = aggregate(&trades, 5_000, EmptyBuckets::Skip).vals[0];
+
+        assert_eq!(k.quote_asset_volume, Some(50.0));
+        assert_eq!(k.taker_buy_base_asset_volume, Some(1.0));
+        assert_eq!(k.taker_buy_quote_asset_volume, Some(10.0));
+        assert_eq!(k.t_close, 4_999);
+        assert!(k.closed);
+    }
+
+    #[test]
+    fn test_resample() {
+        let klines: Vec<Kline> = (0..5)
+            .map(|i| Kline {
+                t_open: 1_000 + i * 1_000,
+                t_close: 1_999 + i * 1_000,
+                open: i as f32,
+                high: 10.0 + i as f32,
+                low: i as f32 - 1.0,
+                close: i as f32 + 0.5,
+                volume: 1.0,
+                number_of_trades: Some(1),
+                closed: i < 4,
+                ..Default::default()
+            })
+            .collect();
+
+        let data = resample(&klines, 2_000);
+
+        assert_candles(
+            &data.vals,
+            &[
+                Kline {
+                    t_close: 1_999,
+                    ..candle(0, [0.0, 10.0, -1.0, 0.5], 1.0, 1)
+                },
+                Kline {
+                    t_close: 3_999,
+                    ..candle(2_000, [1.0, 12.0, 0.0, 2.5], 2.0, 2)
+                },
+                Kline {
+                    t_close: 5_999,
+                    ..candle(4_000, [3.0, 14.0, 2.0, 4.5], 2.0, 2)
+                },
+            ],
+        );
+        assert!(data.vals[1].closed);
+        assert!(!data.vals[2].closed);
+        assert_eq!(resample(&klines, 1_000).vals, klines);
+    }
+
+    #[test]
+    fn test_forming_bucket() {
+        let now = Utc::now().timestamp_millis();
+        let trades = [
+            Trade {
+                time: now - 60_000,
+                ..Default::default()
+            },
+            Trade {
+                time: now,
+                ..Default::default()
+            },
+        ];
+        let data = aggregate(&trades, 60_000, EmptyBuckets::Skip);
+
+        assert!(data.vals[0].closed);
+        assert!(!data.vals[1].closed);
+    }
+}
@@ -0,0 +1,347 @@
+//! What the x coordinate of the plots stands for.
+
+use std::sync::Arc;
+
+use crate::sources::binance::{Interval, Kline};
+
+use super::data::Data;
+
+/// Share of its slot a candle body or volume bar fills, the rest is the gap between them.
+const SLOT_FILL: f64 = 0.9;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Axis {
+    /// Time in millis.
+    #[default]
+    Time,
+    /// Index of an item covering the time range at the index, e.g. a renko brick.
+    /// Item `i` spans `i..i+1` on the plot.
+    Index(Arc<Vec<[i64; 2]>>),
+}
+
+impl Axis {
+    /// Candles placed one after another by index leaving out time gaps between them,
+    /// with the axis mapping the indices back to the candle times.
+    pub fn compress(data: &Data) -> (Data, Axis) {
+        let ranges = data.vals.iter().map(|k| [k.t_open, k.t_close]).collect();
+        let vals = data
+            .vals
+            .iter()
+            .enumerate()
+            .map(|(i, k)| Kline {
+                t_open: i as i64,
+                t_close: i as i64 + 1,
+                ..*k
+            })
+            .collect();
+
+        (Data::new(vals), Axis::Index(Arc::new(ranges)))
+    }
+
+    /// Nominal width of a candle of `interval` on this axis. Without a known interval,
+    /// e.g. for an imported file of irregular candles, it is the smallest gap between the
+    /// candles so none of them overlap.
+    pub fn step(&self, interval: Option<Interval>, klines: &[Kline]) -> f64 {
+        match (self, interval) {
+            (Axis::Time, Some(interval)) => interval.millis() as f64,
+            (Axis::Time, None) => smallest_gap(klines),
+            (Axis::Index(_), _) => 1.0,
+        }
+    }
+
+    /// Time range of the item at `x`.
+    pub fn range(&self, x: f64) -> Option<[i64; 2]> {
+        match self {
+            Axis::Time if x.is_finite() => Some([x as i64, x as i64]),
+            Axis::Time => None,
+            Axis::Index(ranges) if x >= 0.0 => ranges.get(x as usize).copied(),
+            Axis::Index(_) => None,
+        }
+    }
+
+    /// Plot coordinate of the time `ts`. Time within an item maps linearly onto its span,
+    /// time within a gap maps to the start of the next item and time beyond the items
+    /// is extrapolated by the width of the outermost one.
+    pub fn to_x(&self, ts: f64) -> f64 {
+        let ranges = match self {
+            Axis::Time => return ts,
+            Axis::Index(ranges) => ranges,
+        };
+        let (Some(first), Some(last)) = (ranges.first(), ranges.last()) else {
+            return 0.0;
+        };
+        if ts < first[0] as f64 {
+            return (ts - first[0] as f64) / width(first);
+        }
+        if ts > last[1] as f64 {
+            return ranges.len() as f64 + (ts - last[1] as f64) / width(last);
+        }
+
+        let idx = ranges.partition_point(|r| r[0] as f64 <= ts) - 1;
+        let r = &ranges[idx];
+        idx as f64 + ((ts - r[0] as f64) / width(r)).min(1.0)
+    }
+
+    /// Plot coordinate of a point placed at `x` on the time axis by the [`center`] of a
+    /// candle with the `step`, e.g. of an indicator. Points within the slot of a candle keep
+    /// their place in its item, so centers land on the centers of the items, other times
+    /// map as with [`Axis::to_x`].
+    pub fn slot_x(&self, x: f64, step: f64) -> f64 {
+        let Axis::Index(ranges) = self else {
+            return x;
+        };
+        let idx = ranges.partition_point(|r| r[0] as f64 <= x);
+        match idx.checked_sub(1).map(|i| (i, ranges[i][0] as f64)) {
+            Some((i, open)) if x < open + step => i as f64 + (x - open) / step,
+            _ => self.to_x(x),
+        }
+    }
+
+    /// Time at the plot coordinate `x`, inverse of [`Axis::to_x`].
+    pub fn to_ts(&self, x: f64) -> f64 {
+        let ranges = match self {
+            Axis::Time => return x,
+            Axis::Index(ranges) => ranges,
+        };
+        let (Some(first), Some(last)) = (ranges.first(), ranges.last()) else {
+            return 0.0;
+        };
+        if x < 0.0 {
+            return first[0] as f64 + x * width(first);
+        }
+        if x >= ranges.len() as f64 {
+            return last[1] as f64 + (x - ranges.len() as f64) * width(last);
+        }
+
+        let r = &ranges[x as usize];
+        r[0] as f64 + x.fract() * width(r)
+    }
+
+    /// Timestamp of the candle spanning the time `ts` in the data on this axis:
+    /// the time itself on the time axis, index of the item spanning it otherwise.
+    pub fn locate(&self, ts: i64) -> Option<i64> {
+        match self {
+            Axis::Time => Some(ts),
+            Axis::Index(ranges) => {
+                let idx = ranges.partition_point(|r| r[0] <= ts).checked_sub(1)?;
+                (ts <= ranges[idx][1]).then_some(idx as i64)
+            }
+        }
+    }
+
+    /// Time at `x` for axis labels.
+    pub fn format(&self, x: f64) -> String {
+        match self {
+            Axis::Index(ranges) if ranges.is_empty() => String::new(),
+            _ => Data::format_ts(self.to_ts(x)),
+        }
+    }
+
+    /// Time or the time range at `x` for tooltips.
+    pub fn describe(&self, x: f64) -> String {
+        match self.range(x) {
+            Some([from, to]) if from != to => format!(
+                "{} - {}",
+                Data::format_ts(from as f64),
+                Data::format_ts(to as f64)
+            ),
+            Some([ts, _]) => Data::format_ts(ts as f64),
+            None => String::new(),
+        }
+    }
+}
+
+/// Center and width of the candle `k` on an axis with the nominal `step`. Every pane
+/// places candles with it, so bodies and bars line up over gaps and a partial last candle.
+pub fn slot(k: &Kline, step: f64) -> (f64, f64) {
+    (k.t_open as f64 + step / 2.0, step * SLOT_FILL)
+}
+
+/// X of the candle `k`, the center of its [`slot`]. Points, markers and snapping use it so
+/// they line up with the bodies and bars.
+pub fn center(k: &Kline, step: f64) -> f64 {
+    slot(k, step).0
+}
+
+/// Smallest gap between the opens of the candles, or the span of a single one, in millis.
+/// It is the step of candles whose interval isn't at hand.
+pub fn smallest_gap(klines: &[Kline]) -> f64 {
+    klines
+        .windows(2)
+        .map(|w| w[1].t_open - w[0].t_open)
+        .filter(|gap| *gap > 0)
+        .min()
+        .or_else(|| klines.first().map(|k| k.t_close - k.t_open + 1))
+        .unwrap_or(1)
+        .max(1) as f64
+}
+
+/// Span of the item in millis, at least one.
+fn width(range: &[i64; 2]) -> f64 {
+    (range[1] - range[0]).max(1) as f64
+}
+
+#[cfg(test)]
+mod axis_tests {
+    use crate::{netstrat::labels, sources::binance::mock};
+
+    use super::*;
+
+    #[test]
+    fn test_index_ranges() {
+        let axis = Axis::Index(Arc::new(vec![[0, 59_000], [60_000, 60_000]]));
+
+        assert_eq!(axis.range(0.5), Some([0, 59_000]));
+        assert_eq!(axis.range(1.0), Some([60_000, 60_000]));
+        assert_eq!(axis.range(2.5), None);
+        assert_eq!(axis.range(-0.5), None);
+        assert_eq!(
+            axis.describe(0.5),
+            "1970-01-01 00:00:00 - 1970-01-01 00:00:59"
+        );
+        assert_eq!(axis.describe(1.5), "1970-01-01 00:01:00");
+        assert_eq!(Axis::Index(Default::default()).format(7.0), "");
+    }
+
+    #[test]
+    fn test_compress() {
+        let klines = [[0, 59_999], [60_000, 119_999], [600_000, 659_999]]
+            .into_iter()
+            .map(|[t_open, t_close]| Kline {
+                t_open,
+                t_close,
+                close: t_open as f32,
+                ..Default::default()
+            })
+            .collect();
+
+        let (data, axis) = Axis::compress(&Data::new(klines));
+
+        assert_eq!(
+            data.vals
+                .iter()
+                .map(|k| (k.t_open, k.t_close, k.close))
+                .collect::<Vec<_>>(),
+            vec![(0, 1, 0.0), (1, 2, 60_000.0), (2, 3, 600_000.0)]
+        );
+        assert_eq!(axis.range(2.5), Some([600_000, 659_999]));
+    }
+
+    #[test]
+    fn test_index_mapping() {
+        let axis = Axis::Index(Arc::new(vec![[0, 100], [1000, 1100]]));
+
+        // mids of the items land on mids of their spans
+        assert_eq!(axis.to_x(50.0), 0.5);
+        assert_eq!(axis.to_x(1050.0), 1.5);
+        // gap collapses to the start of the next item
+        assert_eq!(axis.to_x(500.0), 1.0);
+        // extrapolated by the width of the outer items
+        assert_eq!(axis.to_x(-50.0), -0.5);
+        assert_eq!(axis.to_x(1300.0), 4.0);
+
+        [-0.5, 0.0, 0.25, 1.0, 1.75, 2.0, 4.0]
+            .into_iter()
+            .for_each(|x| assert_eq!(axis.to_x(axis.to_ts(x)), x));
+    }
+
+    #[test]
+    fn test_centers_in_slots() {
+        // imported candles without close times are centered like the others
+        let mut klines = mock::klines(0, 60_000, 3);
+        klines.iter_mut().for_each(|k| k.t_close = k.t_open);
+        let data = Data::new(klines.clone());
+        let (compressed, axis) = Axis::compress(&data);
+
+        klines.iter().zip(&compressed.vals).for_each(|(k, c)| {
+            assert_eq!(data.center(k), slot(k, 60_000.0).0);
+            assert_eq!(
+                axis.slot_x(data.center(k), data.step()),
+                compressed.center(c)
+            );
+        });
+        assert_eq!(data.center(&klines[2]), 150_000.0);
+        assert_eq!(axis.slot_x(200_000.0, data.step()), axis.to_x(200_000.0));
+        assert_eq!(Axis::Time.slot_x(90_000.0, data.step()), 90_000.0);
+    }
+
+    #[test]
+    fn test_locate() {
+        let axis = Axis::Index(Arc::new(vec![[0, 99], [1000, 1099]]));
+
+        assert_eq!(axis.locate(99), Some(0));
+        assert_eq!(axis.locate(1000), Some(1));
+        assert_eq!(axis.locate(500), None);
+        assert_eq!(axis.locate(-1), None);
+        assert_eq!(axis.locate(2000), None);
+        assert_eq!(Axis::Time.locate(500), Some(500));
+    }
+
+    #[test]
+    fn test_time() {
+        assert_eq!(Axis::Time.to_x(60_000.0), 60_000.0);
+        assert_eq!(Axis::Time.to_ts(60_000.0), 60_000.0);
+        assert_eq!(Axis::Time.format(60_000.0), "1970-01-01 00:01:00");
+        assert_eq!(Axis::Time.describe(60_000.0), "1970-01-01 00:01:00");
+    }
+
+    #[test]
+    fn test_invalid_coordinates() {
+        let (_, axis) = Axis::compress(&Data::new(vec![Kline {
+            t_open: 0,
+            t_close: 999,
+            ..Default::default()
+        }]));
+
+        for axis in [Axis::Time, axis] {
+            for x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+                assert_eq!(axis.range(x), None);
+            }
+            for x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e300, -1e300] {
+                assert_eq!(axis.format(x), labels::INVALID);
+                assert!(matches!(axis.describe(x).as_str(), "" | labels::INVALID));
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_of_every_interval() {
+        // irregular close times don't change the width
+        let mut klines = mock::klines(0, 60_000, 3);
+        klines[1].t_close += 1234;
+
+        let steps: Vec<f64> = Interval::ALL
+            .into_iter()
+            .map(|i| Axis::Time.step(Some(i), &klines))
+            .collect();
+
+        assert_eq!(
+            steps,
+            vec![
+                1_000.0,
+                5_000.0,
+                60_000.0,
+                300_000.0,
+                900_000.0,
+                3_600_000.0,
+                14_400_000.0,
+                86_400_000.0,
+                604_800_000.0
+            ]
+        );
+        let index = Axis::Index(Arc::new(vec![]));
+        assert!(Interval::ALL
+            .into_iter()
+            .all(|i| index.step(Some(i), &klines) == 1.0));
+    }
+
+    #[test]
+    fn test_step_without_interval() {
+        let mut klines = mock::klines(0, 60_000, 2);
+        klines.extend(mock::klines(150_000, 30_000, 2));
+
+        assert_eq!(Axis::Time.step(None, &klines), 30_000.0);
+        assert_eq!(Axis::Time.step(None, &klines[..1]), 60_000.0);
+        assert_eq!(Axis::Time.step(None, &[]), 1.0);
+    }
+}
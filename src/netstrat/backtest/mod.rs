@@ -0,0 +1,482 @@
+//! Bar by bar backtests of strategies over loaded data.
+//!
+//! To add a strategy implement [`Strategy`] and add it to [`builtin`].
+
+mod strategies;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use quick_error::ResultExt;
+use serde::Serialize;
+use tracing::info;
+
+use crate::{errors::Result, sources::binance::Kline};
+
+use super::{
+    data::Data,
+    indicators::{math, IndicatorOutput, Series, SignalKind, SignalPoint, SignalSeries, SubPanel},
+};
+
+pub use self::strategies::{RsiReversion, SmaCross};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Opens a long position, reversing a short one.
+    Buy,
+    /// Opens a short position, reversing a long one.
+    Sell,
+    /// Closes the open position.
+    Close,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Side {
+    Long,
+    Short,
+}
+
+impl Side {
+    fn sign(&self) -> f64 {
+        match self {
+            Side::Long => 1.0,
+            Side::Short => -1.0,
+        }
+    }
+}
+
+/// What a strategy sees on a candle, there is no access to later candles.
+pub struct Context<'a> {
+    /// Candles up to and including the current one.
+    pub candles: &'a [Kline],
+    /// Side of the open position.
+    pub position: Option<Side>,
+}
+
+impl Context<'_> {
+    pub fn candle(&self) -> &Kline {
+        &self.candles[self.candles.len() - 1]
+    }
+}
+
+pub trait Strategy {
+    /// Name including current params, e.g. `SMA cross(10, 30)`.
+    fn name(&self) -> String;
+
+    /// Draws params controls returning true if any of the params changed.
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool;
+
+    /// Drops state kept from a previous run.
+    fn reset(&mut self) {}
+
+    /// Called on close of every candle, the signal is filled at the close price.
+    fn on_candle(&mut self, ctx: &Context) -> Option<Signal>;
+}
+
+/// Strategies available in the app.
+pub fn builtin() -> Vec<Box<dyn Strategy>> {
+    vec![
+        Box::new(SmaCross::default()),
+        Box::new(RsiReversion::default()),
+    ]
+}
+
+/// Size of every opened position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sizing {
+    /// Percent of the current equity.
+    Equity(f64),
+    /// Amount in quote asset.
+    Fixed(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// Starting balance in quote asset.
+    pub balance: f64,
+    /// Fee charged on the notional of every fill in basis points.
+    pub fee_bps: f64,
+    pub sizing: Sizing,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            balance: 10_000.0,
+            fee_bps: 10.0,
+            sizing: Sizing::Equity(100.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Trade {
+    pub side: Side,
+    pub entry_ts: i64,
+    pub entry_price: f64,
+    pub exit_ts: i64,
+    pub exit_price: f64,
+    pub qty: f64,
+    /// Profit after fees of both fills.
+    pub pnl: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub strategy: String,
+    /// Equity marked to the close of every candle as `[time, equity]`.
+    pub equity: Vec<[f64; 2]>,
+    pub trades: Vec<Trade>,
+}
+
+impl Report {
+    /// Share of trades with positive pnl.
+    pub fn win_rate(&self) -> Option<f64> {
+        if self.trades.is_empty() {
+            return None;
+        }
+
+        let wins = self.trades.iter().filter(|t| t.pnl > 0.0).count();
+        Some(wins as f64 / self.trades.len() as f64)
+    }
+
+    /// Gross profit over gross loss, `None` without losing trades.
+    pub fn profit_factor(&self) -> Option<f64> {
+        let profit: f64 = self.trades.iter().map(|t| t.pnl.max(0.0)).sum();
+        let loss: f64 = self.trades.iter().map(|t| (-t.pnl).max(0.0)).sum();
+
+        (loss > 0.0).then(|| profit / loss)
+    }
+
+    /// Deepest relative drop of the equity, e.g. `-0.1` for 10%.
+    pub fn max_drawdown(&self) -> f64 {
+        let vals: Vec<f64> = self.equity.iter().map(|p| p[1]).collect();
+        math::max_drawdown(&vals, None).map_or(0.0, |(_, _, depth)| depth)
+    }
+
+    pub fn final_equity(&self) -> Option<f64> {
+        self.equity.last().map(|p| p[1])
+    }
+
+    /// Equity curve in a panel of its own with entries and exits as signals on the candles.
+    pub fn output(&self) -> IndicatorOutput {
+        let signal = |buy: bool, ts: i64, price: f64, label: String| SignalPoint {
+            ts,
+            kind: match buy {
+                true => SignalKind::Buy,
+                false => SignalKind::Sell,
+            },
+            label,
+            price: Some(price),
+        };
+        let equity = Series {
+            name: format!("equity {}", self.strategy),
+            points: self.equity.clone(),
+            color: None,
+        };
+
+        IndicatorOutput {
+            signals: vec![SignalSeries {
+                name: format!("backtest {}", self.strategy),
+                points: self
+                    .trades
+                    .iter()
+                    .flat_map(|t| {
+                        let long = t.side == Side::Long;
+                        let side = format!("{:?}", t.side).to_lowercase();
+                        [
+                            signal(long, t.entry_ts, t.entry_price, format!("{side} entry")),
+                            signal(
+                                !long,
+                                t.exit_ts,
+                                t.exit_price,
+                                format!("{side} exit, pnl {:.2}", t.pnl),
+                            ),
+                        ]
+                    })
+                    .collect(),
+            }],
+            sub_panels: vec![SubPanel {
+                name: equity.name.clone(),
+                series: vec![equity],
+                label: Some(self.summary()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "trades: {}, win rate: {}, profit factor: {}, max drawdown: {:.2}%",
+            self.trades.len(),
+            self.win_rate()
+                .map_or("-".to_string(), |r| format!("{:.1}%", r * 100.0)),
+            self.profit_factor()
+                .map_or("-".to_string(), |f| format!("{f:.2}")),
+            self.max_drawdown() * 100.0,
+        )
+    }
+}
+
+struct Position {
+    side: Side,
+    qty: f64,
+    entry_ts: i64,
+    entry_price: f64,
+    entry_fee: f64,
+}
+
+impl Position {
+    fn pnl(&self, price: f64) -> f64 {
+        self.side.sign() * (price - self.entry_price) * self.qty
+    }
+}
+
+/// Runs the strategy over the candles from the first one, a position still open
+/// on the last candle is closed at its close.
+pub fn run(strategy: &mut dyn Strategy, data: &Data, settings: &Settings) -> Report {
+    strategy.reset();
+    let fee = |notional: f64| notional * settings.fee_bps / 10_000.0;
+    let mut balance = settings.balance;
+    let mut position: Option<Position> = None;
+    let mut report = Report {
+        strategy: strategy.name(),
+        ..Default::default()
+    };
+
+    let close = |position: Position, balance: &mut f64, ts: i64, price: f64| {
+        let exit_fee = fee(price * position.qty);
+        *balance += position.pnl(price) - exit_fee;
+        Trade {
+            side: position.side,
+            entry_ts: position.entry_ts,
+            entry_price: position.entry_price,
+            exit_ts: ts,
+            exit_price: price,
+            qty: position.qty,
+            pnl: position.pnl(price) - position.entry_fee - exit_fee,
+        }
+    };
+
+    for idx in 0..data.vals.len() {
+        let k = &data.vals[idx];
+        let (ts, price) = (data.center(k) as i64, k.close as f64);
+        let ctx = Context {
+            candles: &data.vals[..=idx],
+            position: position.as_ref().map(|p| p.side),
+        };
+        let side = match strategy.on_candle(&ctx) {
+            Some(Signal::Buy) => Some(Side::Long),
+            Some(Signal::Sell) => Some(Side::Short),
+            Some(Signal::Close) => None,
+            None => position.as_ref().map(|p| p.side),
+        };
+
+        if position.as_ref().map(|p| p.side) != side {
+            if let Some(p) = position.take() {
+                report.trades.push(close(p, &mut balance, ts, price));
+            }
+            if let Some(side) = side {
+                let notional = match settings.sizing {
+                    Sizing::Equity(pct) => balance * pct / 100.0,
+                    Sizing::Fixed(amount) => amount,
+                };
+                if notional > 0.0 && price > 0.0 {
+                    let entry_fee = fee(notional);
+                    balance -= entry_fee;
+                    position = Some(Position {
+                        side,
+                        qty: notional / price,
+                        entry_ts: ts,
+                        entry_price: price,
+                        entry_fee,
+                    });
+                }
+            }
+        }
+
+        let open_pnl = position.as_ref().map_or(0.0, |p| p.pnl(price));
+        report.equity.push([ts as f64, balance + open_pnl]);
+    }
+
+    if let (Some(p), Some(k)) = (position.take(), data.vals.last()) {
+        report.trades.push(close(
+            p,
+            &mut balance,
+            data.center(k) as i64,
+            k.close as f64,
+        ));
+        if let Some(last) = report.equity.last_mut() {
+            last[1] = balance;
+        }
+    }
+
+    report
+}
+
+/// Default export file name for trades of the strategy on the symbol.
+pub fn file_name(symbol: &str, strategy: &str) -> String {
+    let strategy: String = strategy
+        .chars()
+        .map(|c| match c.is_alphanumeric() {
+            true => c.to_ascii_lowercase(),
+            false => '_',
+        })
+        .collect();
+
+    format!("{symbol}_backtest_{}.csv", strategy.trim_matches('_'))
+}
+
+/// Writes trades to csv file returning its absolute path.
+pub fn to_csv(path: &Path, trades: &[Trade]) -> Result<PathBuf> {
+    let f = File::create(path).context(path)?;
+    let abs_path = path.canonicalize().context(path)?;
+    info!("Saving trades to file: {abs_path:?}");
+
+    let mut wtr = csv::Writer::from_writer(f);
+    for t in trades {
+        wtr.serialize(t).context(path)?;
+    }
+    wtr.flush().context(path)?;
+
+    Ok(abs_path)
+}
+
+#[cfg(test)]
+mod backtest_tests {
+    use super::*;
+
+    /// Signals given by index of the candle.
+    struct Scripted(Vec<(usize, Signal)>);
+
+    impl Strategy for Scripted {
+        fn name(&self) -> String {
+            "scripted".to_string()
+        }
+
+        #[cfg(feature = "gui")]
+        fn params_ui(&mut self, _: &mut egui::Ui) -> bool {
+            false
+        }
+
+        fn on_candle(&mut self, ctx: &Context) -> Option<Signal> {
+            let idx = ctx.candles.len() - 1;
+            self.0.iter().find(|(i, _)| *i == idx).map(|(_, s)| *s)
+        }
+    }
+
+    fn data(closes: &[f32]) -> Data {
+        Data::new(
+            closes
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: i as i64 * 10 + 10,
+                    open: *c,
+                    high: *c,
+                    low: *c,
+                    close: *c,
+                    closed: true,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn settings(fee_bps: f64) -> Settings {
+        Settings {
+            balance: 1000.0,
+            fee_bps,
+            sizing: Sizing::Fixed(100.0),
+        }
+    }
+
+    #[test]
+    fn test_long_and_short() {
+        let mut strategy = Scripted(vec![
+            (0, Signal::Buy),
+            (1, Signal::Sell),
+            (2, Signal::Close),
+        ]);
+
+        let report = run(
+            &mut strategy,
+            &data(&[10.0, 20.0, 15.0, 30.0]),
+            &settings(0.0),
+        );
+
+        assert_eq!(report.trades.len(), 2);
+        assert_eq!(report.trades[0].side, Side::Long);
+        assert_eq!(report.trades[0].pnl, 100.0);
+        assert_eq!(report.trades[1].side, Side::Short);
+        assert_eq!(report.trades[1].pnl, 25.0);
+        assert_eq!(
+            report.equity.iter().map(|p| p[1]).collect::<Vec<_>>(),
+            vec![1000.0, 1100.0, 1125.0, 1125.0]
+        );
+        assert_eq!(report.win_rate(), Some(1.0));
+        assert_eq!(report.profit_factor(), None);
+    }
+
+    #[test]
+    fn test_fees_and_open_position_closed_at_end() {
+        let mut strategy = Scripted(vec![(0, Signal::Buy)]);
+
+        let report = run(&mut strategy, &data(&[10.0, 5.0]), &settings(100.0));
+
+        // entry fee 1 on 100, exit fee 0.5 on 50
+        assert_eq!(report.trades.len(), 1);
+        assert_eq!(report.trades[0].exit_price, 5.0);
+        assert_eq!(report.trades[0].pnl, -51.5);
+        assert_eq!(report.final_equity(), Some(948.5));
+        assert_eq!(report.win_rate(), Some(0.0));
+    }
+
+    #[test]
+    fn test_stats() {
+        let report = Report {
+            equity: vec![[0.0, 100.0], [1.0, 120.0], [2.0, 90.0], [3.0, 130.0]],
+            trades: [30.0, -10.0, 10.0]
+                .into_iter()
+                .map(|pnl| Trade {
+                    side: Side::Long,
+                    entry_ts: 0,
+                    entry_price: 1.0,
+                    exit_ts: 1,
+                    exit_price: 1.0,
+                    qty: 1.0,
+                    pnl,
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(report.profit_factor(), Some(4.0));
+        assert_eq!(report.win_rate(), Some(2.0 / 3.0));
+        assert_eq!(report.max_drawdown(), -0.25);
+        let signals = &report.output().signals[0];
+        assert_eq!(signals.points.len(), 6);
+        assert_eq!(signals.points[1].label, "long exit, pnl 30.00");
+    }
+
+    #[test]
+    fn test_export() {
+        let mut strategy = Scripted(vec![(0, Signal::Buy), (1, Signal::Close)]);
+        let report = run(&mut strategy, &data(&[10.0, 20.0]), &settings(0.0));
+        let path = std::env::temp_dir().join(format!(
+            "netstrat_{}",
+            file_name("BTCUSDT", &report.strategy)
+        ));
+
+        let abs_path = to_csv(&path, &report.trades).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&abs_path).unwrap(),
+            "side,entry_ts,entry_price,exit_ts,exit_price,qty,pnl\nLong,5,10.0,15,20.0,10.0,100.0\n"
+        );
+        std::fs::remove_file(abs_path).unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+use super::{Context, Side, Signal, Strategy};
+
+/// Long when the fast sma of closes crosses above the slow one, short when it crosses below.
+pub struct SmaCross {
+    pub fast: usize,
+    pub slow: usize,
+}
+
+impl Default for SmaCross {
+    fn default() -> Self {
+        Self { fast: 10, slow: 30 }
+    }
+}
+
+/// Mean of the last `n` closes ending `back` candles before the last one.
+fn sma(ctx: &Context, n: usize, back: usize) -> Option<f64> {
+    if n == 0 {
+        return None;
+    }
+    let end = ctx.candles.len().checked_sub(back)?;
+    let start = end.checked_sub(n)?;
+
+    Some(
+        ctx.candles[start..end]
+            .iter()
+            .map(|k| k.close as f64)
+            .sum::<f64>()
+            / n as f64,
+    )
+}
+
+impl Strategy for SmaCross {
+    fn name(&self) -> String {
+        format!("SMA cross({}, {})", self.fast, self.slow)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = ui
+            .add(
+                egui::DragValue::new(&mut self.fast)
+                    .clamp_range(1..=1000)
+                    .prefix("fast: "),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut self.slow)
+                    .clamp_range(1..=1000)
+                    .prefix("slow: "),
+            )
+            .changed();
+
+        changed
+    }
+
+    fn on_candle(&mut self, ctx: &Context) -> Option<Signal> {
+        let prev = sma(ctx, self.fast, 1)? - sma(ctx, self.slow, 1)?;
+        let curr = sma(ctx, self.fast, 0)? - sma(ctx, self.slow, 0)?;
+
+        match (prev <= 0.0 && curr > 0.0, prev >= 0.0 && curr < 0.0) {
+            (true, _) => Some(Signal::Buy),
+            (_, true) => Some(Signal::Sell),
+            _ => None,
+        }
+    }
+}
+
+/// Buys when RSI of closes drops below the lower bound and closes once it gets back above the middle.
+pub struct RsiReversion {
+    pub period: usize,
+    pub lower: f64,
+    pub exit: f64,
+    /// Wilder's averages of gains and losses with the last close and count of changes seen.
+    state: Option<(f64, f64, f64, usize)>,
+}
+
+impl Default for RsiReversion {
+    fn default() -> Self {
+        Self {
+            period: 14,
+            lower: 30.0,
+            exit: 50.0,
+            state: None,
+        }
+    }
+}
+
+impl RsiReversion {
+    /// Updates averages with the last close, same as [`crate::netstrat::indicators::math::rsi`].
+    fn rsi(&mut self, close: f64) -> Option<f64> {
+        let n = self.period.max(1) as f64;
+        let (gain, loss, prev, count) = match self.state {
+            None => {
+                self.state = Some((0.0, 0.0, close, 0));
+                return None;
+            }
+            Some(state) => state,
+        };
+
+        let change = close - prev;
+        let (up, down) = (change.max(0.0), (-change).max(0.0));
+        let count = count + 1;
+        let (gain, loss) = match count as f64 <= n {
+            // sums until the averages are seeded
+            true => (gain + up, loss + down),
+            false => ((gain * (n - 1.0) + up) / n, (loss * (n - 1.0) + down) / n),
+        };
+        let (gain, loss) = match count as f64 == n {
+            true => (gain / n, loss / n),
+            false => (gain, loss),
+        };
+        self.state = Some((gain, loss, close, count));
+
+        if (count as f64) < n {
+            return None;
+        }
+
+        Some(match loss == 0.0 {
+            true => 100.0,
+            false => 100.0 - 100.0 / (1.0 + gain / loss),
+        })
+    }
+}
+
+impl Strategy for RsiReversion {
+    fn name(&self) -> String {
+        format!(
+            "RSI reversion({}, {}, {})",
+            self.period, self.lower, self.exit
+        )
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = ui
+            .add(
+                egui::DragValue::new(&mut self.period)
+                    .clamp_range(1..=1000)
+                    .prefix("period: "),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut self.lower)
+                    .clamp_range(0.0..=100.0)
+                    .prefix("buy below: "),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut self.exit)
+                    .clamp_range(0.0..=100.0)
+                    .prefix("close above: "),
+            )
+            .changed();
+
+        changed
+    }
+
+    fn reset(&mut self) {
+        self.state = None;
+    }
+
+    fn on_candle(&mut self, ctx: &Context) -> Option<Signal> {
+        let rsi = self.rsi(ctx.candle().close as f64)?;
+
+        match ctx.position {
+            None if rsi < self.lower => Some(Signal::Buy),
+            Some(Side::Long) if rsi > self.exit => Some(Signal::Close),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod strategies_tests {
+    use crate::{
+        netstrat::{data::Data, indicators::math},
+        sources::binance::Kline,
+    };
+
+    use super::*;
+
+    fn data(closes: &[f64]) -> Data {
+        Data::new(
+            closes
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: i as i64 * 10 + 10,
+                    close: *c as f32,
+                    closed: true,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn signals(strategy: &mut dyn Strategy, data: &Data) -> Vec<(usize, Signal)> {
+        strategy.reset();
+        (0..data.vals.len())
+            .filter_map(|idx| {
+                let ctx = Context {
+                    candles: &data.vals[..=idx],
+                    position: None,
+                };
+                Some((idx, strategy.on_candle(&ctx)?))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sma_cross() {
+        let data = data(&[5.0, 4.0, 3.0, 2.0, 6.0, 7.0, 1.0]);
+        let mut strategy = SmaCross { fast: 1, slow: 3 };
+
+        assert_eq!(
+            signals(&mut strategy, &data),
+            vec![(4, Signal::Buy), (6, Signal::Sell)]
+        );
+    }
+
+    #[test]
+    fn test_rsi_matches_math() {
+        let closes = [10.0, 11.0, 10.5, 10.0, 9.0, 9.5, 8.0, 8.5, 9.0, 10.0];
+        let expected = math::rsi(&closes, 3);
+        let mut strategy = RsiReversion {
+            period: 3,
+            ..Default::default()
+        };
+
+        let rsi: Vec<Option<f64>> = closes.iter().map(|c| strategy.rsi(*c)).collect();
+
+        rsi.iter().zip(expected).for_each(|(a, b)| match (a, b) {
+            (Some(a), Some(b)) => assert!((a - b).abs() < 1e-9),
+            (a, b) => assert_eq!(*a, b),
+        });
+    }
+
+    #[test]
+    fn test_rsi_reversion() {
+        let data = data(&[10.0, 9.0, 8.0, 7.0, 8.0, 9.0]);
+        let mut strategy = RsiReversion {
+            period: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(signals(&mut strategy, &data)[0], (2, Signal::Buy));
+    }
+}
@@ -1,6 +1,7 @@
 use std::cmp::{max, min, Ordering};
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Ord)]
+/// Ordered by start then end.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Bounds(pub i64, pub i64);
 
 impl Bounds {
@@ -26,25 +27,25 @@ impl Bounds {
         Some(Bounds(max(self.0, other.0), min(self.1, other.1)))
     }
 
+    /// Distance between the edges; zero for a single point.
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         (self.1 - self.0) as usize
     }
 
     pub fn subtract(&self, other: &Bounds) -> Option<BoundsSet> {
         if !self.intersects(other) {
-            return Some(BoundsSet::new(vec![self.clone()]));
+            return Some(BoundsSet::new(vec![*self]));
         }
 
         if other.contains(self) {
             return None;
         }
 
-        if self < other {
-            return Some(BoundsSet::new(vec![Bounds(self.0, other.0 - 1)]));
-        }
-
-        if self > other {
-            return Some(BoundsSet::new(vec![Bounds(other.1 + 1, self.1)]));
+        match self.position(other) {
+            Ordering::Less => return Some(BoundsSet::new(vec![Bounds(self.0, other.0 - 1)])),
+            Ordering::Greater => return Some(BoundsSet::new(vec![Bounds(other.1 + 1, self.1)])),
+            Ordering::Equal => {}
         }
 
         if self.contains(other) {
@@ -57,7 +58,7 @@ impl Bounds {
                 res = res.concat(&right_b);
             }
 
-            if res.len() == 0 {
+            if res.is_empty() {
                 return None;
             }
 
@@ -67,6 +68,18 @@ impl Bounds {
         None
     }
 
+    /// Whether the bounds lie left or right of the other on the axis, equal when one
+    /// contains the other.
+    fn position(&self, other: &Bounds) -> Ordering {
+        if self.1 <= other.0 || (self.1 <= other.1 && self.0 <= other.0) {
+            return Ordering::Less;
+        } else if other.1 <= self.0 || (other.1 <= self.1 && other.0 <= self.0) {
+            return Ordering::Greater;
+        }
+
+        Ordering::Equal
+    }
+
     fn contains(&self, other: &Bounds) -> bool {
         self.0 <= other.0 && other.1 <= self.1
     }
@@ -82,18 +95,6 @@ impl Bounds {
     }
 }
 
-impl PartialOrd for Bounds {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        if self.1 <= other.0 || (self.1 <= other.1 && self.0 <= other.0) {
-            return Some(Ordering::Less);
-        } else if other.1 <= self.0 || (other.1 <= self.1 && other.0 <= self.0) {
-            return Some(Ordering::Greater);
-        }
-
-        Some(Ordering::Equal)
-    }
-}
-
 #[cfg(test)]
 mod bounds_tests {
     use super::*;
@@ -138,6 +139,7 @@ mod bounds_tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_intersects() {
         // containment
         assert_eq!(Bounds(3, 5).intersects(&Bounds(3, 4)), true);
@@ -244,6 +246,10 @@ impl BoundsSet {
         self.vals.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.vals.is_empty()
+    }
+
     pub fn concat(&self, other: &Self) -> Self {
         let mut vals = self.vals.clone();
         vals.extend_from_slice(&other.vals);
@@ -265,6 +271,14 @@ impl BoundsSet {
         Some(self.vals.first()?.0)
     }
 
+    /// Range from the earliest start to the latest end.
+    pub fn hull(&self) -> Option<Bounds> {
+        self.vals
+            .iter()
+            .copied()
+            .reduce(|a, b| Bounds(a.0.min(b.0), a.1.max(b.1)))
+    }
+
     /// Concats, sorts and unions 2 bounds sequences.
     pub fn merge(&self, other: &BoundsSet) -> Self {
         let mut new_vals = self.concat(other).vals;
@@ -274,7 +288,7 @@ impl BoundsSet {
         Self {
             vals: new_vals.iter().fold(Vec::new(), |mut acc, v| {
                 if acc.is_empty() {
-                    acc.push(v.clone());
+                    acc.push(*v);
 
                     return acc;
                 }
@@ -283,7 +297,7 @@ impl BoundsSet {
                 if let Some(union) = last.union(v) {
                     *last = union;
                 } else {
-                    acc.push(v.clone());
+                    acc.push(*v);
                 }
 
                 acc
@@ -293,7 +307,7 @@ impl BoundsSet {
 
     /// Computes self - other difference.
     pub fn subtract(&self, other: &BoundsSet) -> Option<BoundsSet> {
-        if other.len() == 0 {
+        if other.is_empty() {
             return Some(self.clone());
         }
 
@@ -308,7 +322,7 @@ impl BoundsSet {
             res = curr_vals;
         });
 
-        if res.len() == 0 {
+        if res.is_empty() {
             return None;
         }
 
@@ -412,4 +426,55 @@ mod bounds_sequence_tests {
             ])),
         );
     }
+
+    #[test]
+    fn test_bounds_set_hull() {
+        assert_eq!(BoundsSet::default().hull(), None);
+        assert_eq!(
+            BoundsSet::new(vec![Bounds(10, 20), Bounds(0, 5), Bounds(15, 30)]).hull(),
+            Some(Bounds(0, 30))
+        );
+    }
+}
+
+#[cfg(test)]
+mod bounds_prop_tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const MAX: i64 = 60;
+
+    fn bounds() -> impl Strategy<Value = Bounds> {
+        (0..MAX, 0..MAX).prop_map(|(a, b)| Bounds(a.min(b), a.max(b)))
+    }
+
+    fn bounds_set() -> impl Strategy<Value = BoundsSet> {
+        prop::collection::vec(bounds(), 0..5).prop_map(BoundsSet::new)
+    }
+
+    fn contains(set: &BoundsSet, p: i64) -> bool {
+        set.vals.iter().any(|b| b.0 <= p && p <= b.1)
+    }
+
+    proptest! {
+        #[test]
+        fn merge_is_union(a in bounds_set(), b in bounds_set()) {
+            let merged = a.merge(&b);
+
+            for p in -1..=MAX {
+                prop_assert_eq!(contains(&merged, p), contains(&a, p) || contains(&b, p));
+            }
+            prop_assert!(merged.vals.windows(2).all(|w| w[0].1 + 1 < w[1].0));
+        }
+
+        #[test]
+        fn subtract_is_difference(a in bounds_set(), b in bounds_set()) {
+            let diff = a.subtract(&b).unwrap_or_default();
+
+            for p in -1..=MAX {
+                prop_assert_eq!(contains(&diff, p), contains(&a, p) && !contains(&b, p));
+            }
+        }
+    }
 }
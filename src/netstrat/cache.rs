@@ -0,0 +1,368 @@
+//! On-disk cache of downloaded pages.
+//!
+//! Every page is a csv file `<dir>/<symbol>/<interval>_<start>_<end>.csv` with all the
+//! export columns. Pages with forming candles are not cached. Files are written under a
+//! temporary name and renamed when complete, so pruning and status never see partial files.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::SystemTime,
+};
+
+use quick_error::ResultExt;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::{
+    errors::{Error, Result},
+    sources::binance::Kline,
+};
+
+use super::{
+    aggregate::EmptyBuckets,
+    bounds::Bounds,
+    export::{self, Columns},
+    props::Props,
+};
+
+pub const DEFAULT_DIR: &str = "cache";
+pub const DEFAULT_MAX_BYTES: u64 = 512 * 1024 * 1024;
+
+const EXTENSION: &str = "csv";
+const TMP_EXTENSION: &str = "tmp";
+
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub dir: PathBuf,
+    pub max_bytes: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from(DEFAULT_DIR),
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+}
+
+/// Size of the cache and number of cached pages per symbol.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheStatus {
+    pub bytes: u64,
+    pub entries: BTreeMap<String, usize>,
+}
+
+struct Entry {
+    path: PathBuf,
+    symbol: String,
+    bytes: u64,
+    modified: SystemTime,
+}
+
+/// Handle to the cache, clones share the config.
+#[derive(Debug, Clone, Default)]
+pub struct Cache {
+    config: Arc<RwLock<CacheConfig>>,
+}
+
+impl Cache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(config)),
+        }
+    }
+
+    pub fn config(&self) -> CacheConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: CacheConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    fn path(&self, symbol: &str, props: &Props, page: Bounds) -> PathBuf {
+        let interval = match (props.interval.is_aggregated(), props.empty_buckets) {
+            (true, EmptyBuckets::CarryForward) => format!("{}-filled", props.interval.as_str()),
+            _ => props.interval.as_str().to_string(),
+        };
+
+        self.config()
+            .dir
+            .join(symbol)
+            .join(format!("{interval}_{}_{}", page.0, page.1))
+            .with_extension(EXTENSION)
+    }
+
+    /// Cached klines of the page, `None` when missing or unreadable.
+    pub fn get(&self, symbol: &str, props: &Props, page: Bounds) -> Option<Vec<Kline>> {
+        let path = self.path(symbol, props, page);
+        if !path.exists() {
+            return None;
+        }
+
+        match export::from_csv(&path) {
+            Ok(klines) => {
+                debug!("Read page {page:?} of {symbol} from cache.");
+                Some(klines)
+            }
+            Err(err) => {
+                debug!("Ignoring broken cache entry: {}.", err.chain());
+                None
+            }
+        }
+    }
+
+    /// Saves klines of the page unless there is a forming candle among them.
+    pub fn put(&self, symbol: &str, props: &Props, page: Bounds, klines: &[Kline]) -> Result<()> {
+        if klines.iter().any(|k| !k.closed) {
+            return Ok(());
+        }
+
+        let path = self.path(symbol, props, page);
+        let dir = path.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dir).context(dir)?;
+
+        let tmp = path.with_extension(format!(
+            "{}.{}.{TMP_EXTENSION}",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        if let Err(err) = export::to_csv(&tmp, klines, Columns::All) {
+            fs::remove_file(&tmp).ok();
+            return Err(err);
+        }
+        fs::rename(&tmp, &path).context(path.as_path())?;
+
+        Ok(())
+    }
+
+    /// Complete entries, files being written are skipped.
+    fn entries(&self) -> Result<Vec<Entry>> {
+        let dir = self.config().dir;
+        let mut entries = vec![];
+        if !dir.exists() {
+            return Ok(entries);
+        }
+
+        for symbol_dir in fs::read_dir(&dir).context(dir.as_path())? {
+            let symbol_dir = symbol_dir.context(dir.as_path())?.path();
+            if !symbol_dir.is_dir() {
+                continue;
+            }
+            let symbol = symbol_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            for file in fs::read_dir(&symbol_dir).context(symbol_dir.as_path())? {
+                let path = file.context(symbol_dir.as_path())?.path();
+                if path.extension().is_none_or(|e| e != EXTENSION) {
+                    continue;
+                }
+                let meta = match fs::metadata(&path) {
+                    Ok(meta) => meta,
+                    // removed concurrently
+                    Err(_) => continue,
+                };
+
+                entries.push(Entry {
+                    symbol: symbol.clone(),
+                    bytes: meta.len(),
+                    modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    path,
+                });
+            }
+        }
+
+        Ok(entries)
+    }
+
+    pub fn status(&self) -> Result<CacheStatus> {
+        let mut status = CacheStatus::default();
+        self.entries()?.iter().for_each(|e| {
+            status.bytes += e.bytes;
+            *status.entries.entry(e.symbol.clone()).or_default() += 1;
+        });
+
+        Ok(status)
+    }
+
+    /// Deletes the oldest entries until the cache fits the max size. Returns number of deleted entries.
+    pub fn prune(&self) -> Result<usize> {
+        let max_bytes = self.config().max_bytes;
+        let mut entries = self.entries()?;
+        let mut bytes: u64 = entries.iter().map(|e| e.bytes).sum();
+        entries.sort_by_key(|e| e.modified);
+
+        let mut deleted = 0;
+        for e in entries {
+            if bytes <= max_bytes {
+                break;
+            }
+
+            match fs::remove_file(&e.path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(Error::Io(e.path, err))
+                }
+                _ => {
+                    bytes -= e.bytes;
+                    deleted += 1;
+                }
+            }
+        }
+
+        if deleted > 0 {
+            info!("Pruned {deleted} cache entries, {bytes} bytes left.");
+        }
+
+        Ok(deleted)
+    }
+
+    /// Removes cached pages of the symbol or all of them when it's `None`.
+    /// Files other than cached pages are left in place.
+    pub fn clear(&self, symbol: Option<&str>) -> Result<()> {
+        let entries = self.entries()?;
+        for e in entries.iter() {
+            if symbol.is_some_and(|s| s != e.symbol) {
+                continue;
+            }
+
+            match fs::remove_file(&e.path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(Error::Io(e.path.clone(), err))
+                }
+                _ => {}
+            }
+            if let Some(dir) = e.path.parent() {
+                // fails until the dir is empty
+                fs::remove_dir(dir).ok();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    fn cache(name: &str, max_bytes: u64) -> Cache {
+        let dir =
+            std::env::temp_dir().join(format!("netstrat_cache_{name}_{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+
+        Cache::new(CacheConfig { dir, max_bytes })
+    }
+
+    fn klines(closed: bool) -> Vec<Kline> {
+        vec![Kline {
+            t_open: 0,
+            t_close: 59_999,
+            close: 1.5,
+            volume: 2.0,
+            number_of_trades: Some(3),
+            closed,
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn test_put_and_get() {
+        let cache = cache("put_get", DEFAULT_MAX_BYTES);
+        let props = Props::default();
+
+        assert!(cache.get("BTCUSDT", &props, Bounds(0, 60_000)).is_none());
+        cache
+            .put("BTCUSDT", &props, Bounds(0, 60_000), &klines(true))
+            .unwrap();
+
+        assert_eq!(
+            cache.get("BTCUSDT", &props, Bounds(0, 60_000)),
+            Some(klines(true))
+        );
+        cache.clear(None).unwrap();
+    }
+
+    #[test]
+    fn test_forming_not_cached() {
+        let cache = cache("forming", DEFAULT_MAX_BYTES);
+        let props = Props::default();
+
+        cache
+            .put("BTCUSDT", &props, Bounds(0, 60_000), &klines(false))
+            .unwrap();
+
+        assert!(cache.get("BTCUSDT", &props, Bounds(0, 60_000)).is_none());
+    }
+
+    #[test]
+    fn test_status_and_clear_symbol() {
+        let cache = cache("status", DEFAULT_MAX_BYTES);
+        let props = Props::default();
+        cache
+            .put("BTCUSDT", &props, Bounds(0, 1), &klines(true))
+            .unwrap();
+        cache
+            .put("BTCUSDT", &props, Bounds(1, 2), &klines(true))
+            .unwrap();
+        cache
+            .put("ETHUSDT", &props, Bounds(0, 1), &klines(true))
+            .unwrap();
+
+        let status = cache.status().unwrap();
+        assert_eq!(
+            status.entries,
+            BTreeMap::from([("BTCUSDT".to_string(), 2), ("ETHUSDT".to_string(), 1)])
+        );
+        assert!(status.bytes > 0);
+
+        cache.clear(Some("BTCUSDT")).unwrap();
+        assert_eq!(
+            cache.status().unwrap().entries,
+            BTreeMap::from([("ETHUSDT".to_string(), 1)])
+        );
+        cache.clear(None).unwrap();
+        assert_eq!(cache.status().unwrap(), CacheStatus::default());
+    }
+
+    #[test]
+    fn test_prune_oldest_skipping_partial_files() {
+        let cache = cache("prune", 0);
+        let props = Props::default();
+        cache
+            .put("BTCUSDT", &props, Bounds(0, 1), &klines(true))
+            .unwrap();
+        let entry_bytes = cache.status().unwrap().bytes;
+        thread::sleep(Duration::from_millis(20));
+        cache
+            .put("BTCUSDT", &props, Bounds(1, 2), &klines(true))
+            .unwrap();
+
+        let partial = cache.config().dir.join("BTCUSDT").join("1m_2_3.1.tmp");
+        fs::write(&partial, "t_open").unwrap();
+
+        cache.set_config(CacheConfig {
+            max_bytes: entry_bytes,
+            ..cache.config()
+        });
+        assert_eq!(cache.prune().unwrap(), 1);
+        assert!(cache.get("BTCUSDT", &props, Bounds(0, 1)).is_none());
+        assert!(cache.get("BTCUSDT", &props, Bounds(1, 2)).is_some());
+        assert!(partial.exists());
+
+        cache.clear(None).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+//! Bounded channels between the windows, each with the policy applied once it is full,
+//! so a subscriber which stopped receiving can't make a queue grow.
+
+use std::time::Duration;
+
+use crossbeam::channel::{bounded, Receiver, SendTimeoutError, Sender, TrySendError};
+
+/// Channel keeping only the newest value, e.g. for the selected symbol or props where older
+/// ones are stale once a newer one is sent. The sender keeps a receiving end to drop them,
+/// so it never sees the channel disconnect and values nobody receives are dropped silently.
+pub fn latest<T>() -> (LatestSender<T>, Receiver<T>) {
+    let (s, r) = bounded(1);
+    (LatestSender { s, r: r.clone() }, r)
+}
+
+/// Channel of `cap` values which blocks senders up to `timeout` when full, for requests which
+/// must not be dropped silently.
+pub fn with_timeout<T>(cap: usize, timeout: Duration) -> (TimeoutSender<T>, Receiver<T>) {
+    let (s, r) = bounded(cap);
+    (TimeoutSender { s, timeout }, r)
+}
+
+pub struct LatestSender<T> {
+    s: Sender<T>,
+    /// Receiving end used to drop the unreceived value.
+    r: Receiver<T>,
+}
+
+impl<T> Clone for LatestSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            s: self.s.clone(),
+            r: self.r.clone(),
+        }
+    }
+}
+
+impl<T> LatestSender<T> {
+    /// Sends the value replacing the one not received yet.
+    pub fn send(&self, mut value: T) {
+        while let Err(TrySendError::Full(v)) = self.s.try_send(value) {
+            self.r.try_recv().ok();
+            value = v;
+        }
+    }
+}
+
+pub struct TimeoutSender<T> {
+    s: Sender<T>,
+    timeout: Duration,
+}
+
+impl<T> Clone for TimeoutSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            s: self.s.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<T> TimeoutSender<T> {
+    /// Sends the value waiting for room up to the timeout.
+    pub fn send(&self, value: T) -> Result<(), SendTimeoutError<T>> {
+        self.s.send_timeout(value, self.timeout)
+    }
+}
+
+#[cfg(test)]
+mod channels_tests {
+    use super::*;
+
+    #[test]
+    fn test_latest_wins() {
+        let (s, r) = latest();
+
+        (0..10_000).for_each(|i| s.clone().send(i));
+
+        assert_eq!(r.len(), 1);
+        assert_eq!(r.try_recv(), Ok(9_999));
+        assert!(r.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_timeout_when_full() {
+        let (s, r) = with_timeout(2, Duration::from_millis(10));
+
+        s.send(1).unwrap();
+        s.send(2).unwrap();
+        assert!(matches!(s.send(3), Err(SendTimeoutError::Timeout(3))));
+        assert_eq!(r.len(), 2);
+
+        assert_eq!(r.recv(), Ok(1));
+        s.send(3).unwrap();
+        assert_eq!(r.try_iter().collect::<Vec<_>>(), vec![2, 3]);
+    }
+}
@@ -0,0 +1,236 @@
+//! Pairwise correlations of log returns of several symbols over the same range. Closes of a
+//! pair are joined on candle open times, candles only one of the symbols has are left out.
+
+use crate::sources::binance::Kline;
+
+use super::axis;
+
+/// Returns a pair needs for its correlation to be shown.
+pub const MIN_RETURNS: usize = 20;
+
+/// Correlation of a pair, unknown when the symbols have too few candles in common.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    /// Candles both symbols have.
+    pub overlap: usize,
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Matrix {
+    pub symbols: Vec<String>,
+    /// Cells of every pair, symmetric with ones on the diagonal.
+    pub cells: Vec<Vec<Cell>>,
+}
+
+impl Matrix {
+    /// Correlations of the symbols with their candles, given in the same order.
+    pub fn new(symbols: Vec<String>, klines: &[Vec<Kline>], min_returns: usize) -> Self {
+        let n = symbols.len();
+        let mut cells = vec![
+            vec![
+                Cell {
+                    overlap: 0,
+                    value: None
+                };
+                n
+            ];
+            n
+        ];
+        (0..n).for_each(|i| {
+            cells[i][i] = Cell {
+                overlap: klines[i].len(),
+                value: Some(1.0),
+            };
+            (i + 1..n).for_each(|j| {
+                let joined = join(&klines[i], &klines[j]);
+                let (a, b): (Vec<_>, Vec<_>) = joined.iter().copied().unzip();
+                let (a, b) = (log_returns(&a), log_returns(&b));
+                let value = match a.len() >= min_returns.max(2) {
+                    true => pearson(&a, &b),
+                    false => None,
+                };
+                let cell = Cell {
+                    overlap: joined.len(),
+                    value,
+                };
+                cells[i][j] = cell;
+                cells[j][i] = cell;
+            });
+        });
+
+        Self { symbols, cells }
+    }
+
+    /// Mean correlation of the symbol with the others, pairs without one are left out.
+    pub fn average(&self, idx: usize) -> Option<f64> {
+        let values: Vec<f64> = self.cells[idx]
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != idx)
+            .filter_map(|(_, c)| c.value)
+            .collect();
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+    }
+
+    /// Positions of the symbols by their average correlation, the highest first and those
+    /// without one last.
+    pub fn by_average(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.symbols.len()).collect();
+        order.sort_by(|a, b| {
+            let (a, b) = (self.average(*a), self.average(*b));
+            b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        order
+    }
+}
+
+/// Closes of both symbols at the open times they have in common, both sorted by open time.
+pub fn join(a: &[Kline], b: &[Kline]) -> Vec<(f64, f64)> {
+    let mut joined = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].t_open.cmp(&b[j].t_open) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                joined.push((a[i].close as f64, b[j].close as f64));
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    joined
+}
+
+/// Closes of `b` at the open times `a` has too, scaled to start at the first of these
+/// closes of `a` so both are drawn over the same candles. Points are at the centers of the
+/// candles of `a`.
+pub fn rebased(a: &[Kline], b: &[Kline]) -> Vec<[f64; 2]> {
+    let step = axis::smallest_gap(a);
+    let mut points = vec![];
+    let mut scale = None;
+    let mut j = 0;
+    for k in a {
+        while j < b.len() && b[j].t_open < k.t_open {
+            j += 1;
+        }
+        let Some(other) = b.get(j).filter(|o| o.t_open == k.t_open) else {
+            continue;
+        };
+        if other.close == 0.0 {
+            continue;
+        }
+        let scale = *scale.get_or_insert(k.close as f64 / other.close as f64);
+        points.push([axis::center(k, step), other.close as f64 * scale]);
+    }
+
+    points
+}
+
+/// Logarithms of the ratios of consecutive closes, returns of closes which aren't positive
+/// are zero.
+pub fn log_returns(closes: &[f64]) -> Vec<f64> {
+    closes
+        .windows(2)
+        .map(|w| match w[0] > 0.0 && w[1] > 0.0 {
+            true => (w[1] / w[0]).ln(),
+            false => 0.0,
+        })
+        .collect()
+}
+
+/// Pearson correlation coefficient, unknown for fewer than two values or values which
+/// don't vary.
+pub fn pearson(x: &[f64], y: &[f64]) -> Option<f64> {
+    let n = x.len().min(y.len());
+    if n < 2 {
+        return None;
+    }
+    let (x, y) = (&x[..n], &y[..n]);
+    let mean = |v: &[f64]| v.iter().sum::<f64>() / n as f64;
+    let (mx, my) = (mean(x), mean(y));
+
+    let (mut cov, mut vx, mut vy) = (0.0, 0.0, 0.0);
+    x.iter().zip(y).for_each(|(x, y)| {
+        let (dx, dy) = (x - mx, y - my);
+        cov += dx * dy;
+        vx += dx * dx;
+        vy += dy * dy;
+    });
+    if vx == 0.0 || vy == 0.0 {
+        return None;
+    }
+
+    Some((cov / (vx * vy).sqrt()).clamp(-1.0, 1.0))
+}
+
+#[cfg(test)]
+mod correlation_tests {
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+
+    fn klines(closes: &[f32], from: usize) -> Vec<Kline> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(idx, close)| Kline {
+                t_open: (from + idx) as i64 * MINUTE,
+                close: *close,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pearson() {
+        let x = [1.0, 2.0, 3.0, 4.0];
+        assert!((pearson(&x, &[2.0, 4.0, 6.0, 8.0]).unwrap() - 1.0).abs() < 1e-12);
+        assert!((pearson(&x, &[8.0, 6.0, 4.0, 2.0]).unwrap() + 1.0).abs() < 1e-12);
+        assert_eq!(pearson(&x, &[1.0, 1.0, 1.0, 1.0]), None);
+        assert_eq!(pearson(&[1.0], &[1.0]), None);
+        assert!(pearson(&x, &[1.0, 3.0, 2.0, 4.0]).unwrap() < 1.0);
+    }
+
+    #[test]
+    fn test_join_on_open_times() {
+        let a = klines(&[1.0, 2.0, 3.0, 4.0], 0);
+        let b = klines(&[10.0, 20.0, 30.0], 2);
+
+        assert_eq!(join(&a, &b), vec![(3.0, 10.0), (4.0, 20.0)]);
+        let points: Vec<f64> = rebased(&a, &b).into_iter().map(|[_, v]| v).collect();
+        assert_eq!(points, vec![3.0, 6.0]);
+        assert_eq!(log_returns(&[1.0, 2.0, 0.0]), vec![2f64.ln(), 0.0]);
+    }
+
+    #[test]
+    fn test_matrix() {
+        let up: Vec<f32> = (0..30).map(|i| 100.0 + (i % 7) as f32).collect();
+        let down: Vec<f32> = (0..30).map(|i| 100.0 - (i % 7) as f32).collect();
+        let series = vec![
+            klines(&up, 0),
+            klines(&up, 0),
+            klines(&down, 0),
+            klines(&up, 25),
+        ];
+        let symbols = ["A", "B", "C", "D"].map(String::from).to_vec();
+
+        let matrix = Matrix::new(symbols, &series, 10);
+
+        assert_eq!(matrix.cells[0][0].value, Some(1.0));
+        assert!((matrix.cells[0][1].value.unwrap() - 1.0).abs() < 1e-9);
+        assert!(matrix.cells[0][2].value.unwrap() < -0.9);
+        assert_eq!(matrix.cells[2][0], matrix.cells[0][2]);
+        assert_eq!(
+            matrix.cells[0][3],
+            Cell {
+                overlap: 5,
+                value: None
+            }
+        );
+        assert_eq!(matrix.average(3), None);
+        assert_eq!(matrix.by_average(), vec![0, 1, 2, 3]);
+    }
+}
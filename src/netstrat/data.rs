@@ -1,11 +1,11 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, ops::Range};
 
-use chrono::{DateTime, NaiveDateTime, Utc};
-use egui::Color32;
-use tracing::{debug, info};
+use tracing::info;
 
 use crate::sources::binance::Kline;
 
+use super::{axis, labels};
+
 #[derive(Default, Clone)]
 pub struct Data {
     pub vals: Vec<Kline>,
@@ -14,10 +14,16 @@ pub struct Data {
     max_y: f64,
     min_y: f64,
     max_vol: f64,
+    /// Width of the candle slots, see [`axis::slot`].
+    step: f64,
 }
 
 impl Data {
     pub fn new(vals: Vec<Kline>) -> Self {
+        if vals.is_empty() {
+            return Self::default();
+        }
+
         let max_y = vals
             .iter()
             .max_by(|l, r| {
@@ -56,6 +62,7 @@ impl Data {
 
         let max_x = vals.last().unwrap().t_close as f64;
         let min_x = vals.first().unwrap().t_open as f64;
+        let step = axis::smallest_gap(&vals);
 
         info!(
             "Computed data props: max_x: {max_x},  min_x: {min_x}, max_y: {max_y}, min_y: {min_y}, max_vol: {max_vol}."
@@ -68,6 +75,7 @@ impl Data {
             max_y,
             min_y,
             max_vol,
+            step,
         }
     }
 
@@ -91,18 +99,228 @@ impl Data {
         self.max_vol
     }
 
-    pub fn format_ts(ts: f64) -> String {
-        let secs = (ts / 1000f64) as i64;
-        let naive = NaiveDateTime::from_timestamp(secs, 0);
-        let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// X of the candle, the center of its slot where the bodies and volume bars are drawn.
+    pub fn center(&self, k: &Kline) -> f64 {
+        axis::center(k, self.step)
+    }
+
+    /// Data without the forming candle.
+    pub fn closed(&self) -> Data {
+        Data::new(self.vals.iter().filter(|k| k.closed).copied().collect())
+    }
 
-        datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+    /// Data of the first `n` candles, e.g. ones shown during a replay.
+    pub fn up_to(&self, n: usize) -> Data {
+        Data::new(self.vals[..n.min(self.vals.len())].to_vec())
     }
 
-    pub fn k_color(k: &Kline) -> Color32 {
-        match k.open > k.close {
-            true => Color32::LIGHT_RED,
-            false => Color32::LIGHT_GREEN,
+    /// Data as it was at `ts`, candles opened later are left out and the one spanning `ts`
+    /// is forming. There are no ticks to cut it at, so only its open is known and it is flat
+    /// without volume.
+    pub fn as_of(&self, ts: i64) -> Data {
+        let mut vals = self.vals[..count_to(&self.vals, ts)].to_vec();
+        if let Some(last) = vals.last_mut().filter(|k| ts < k.t_close) {
+            *last = Kline {
+                high: last.open,
+                low: last.open,
+                close: last.open,
+                volume: 0.0,
+                quote_asset_volume: last.quote_asset_volume.map(|_| 0.0),
+                number_of_trades: last.number_of_trades.map(|_| 0),
+                taker_buy_base_asset_volume: last.taker_buy_base_asset_volume.map(|_| 0.0),
+                taker_buy_quote_asset_volume: last.taker_buy_quote_asset_volume.map(|_| 0.0),
+                closed: false,
+                ..*last
+            };
         }
+
+        Data::new(vals)
+    }
+
+    /// Candles overlapping the time range from `t0` to `t1` in millis, see [`range_of`].
+    pub fn slice_range(&self, t0: i64, t1: i64) -> &[Kline] {
+        &self.vals[range_of(&self.vals, t0, t1)]
+    }
+
+    /// Candles overlapping the time range with their index in the data.
+    pub fn iter_range(&self, t0: i64, t1: i64) -> impl Iterator<Item = (usize, &Kline)> {
+        let range = range_of(&self.vals, t0, t1);
+        range.clone().zip(&self.vals[range])
+    }
+
+    /// Index of the candle spanning the time `ts`, see [`index_of`].
+    pub fn index_of(&self, ts: i64) -> Option<usize> {
+        index_of(&self.vals, ts)
+    }
+
+    pub fn closes(&self) -> Vec<f64> {
+        self.vals.iter().map(|k| k.close as f64).collect()
+    }
+
+    pub fn volumes(&self) -> Vec<f64> {
+        self.vals.iter().map(|k| k.volume as f64).collect()
+    }
+
+    pub fn highs(&self) -> Vec<f64> {
+        self.vals.iter().map(|k| k.high as f64).collect()
+    }
+
+    pub fn lows(&self) -> Vec<f64> {
+        self.vals.iter().map(|k| k.low as f64).collect()
+    }
+
+    pub fn format_ts(ts: f64) -> String {
+        labels::format_ts(ts)
+    }
+}
+
+/// Positions of the candles sorted by open time overlapping the time range from `t0` to
+/// `t1` in millis, both included. Candles partly within the range count.
+pub fn range_of(klines: &[Kline], t0: i64, t1: i64) -> Range<usize> {
+    let from = klines.partition_point(|k| k.t_close < t0);
+    let to = klines.partition_point(|k| k.t_open <= t1);
+
+    from..to.max(from)
+}
+
+/// Count of the candles sorted by open time opened at or before `ts`.
+pub fn count_to(klines: &[Kline], ts: i64) -> usize {
+    klines.partition_point(|k| k.t_open <= ts)
+}
+
+/// Position of the candle spanning the time `ts` in candles sorted by open time, none
+/// before, after or between the candles.
+pub fn index_of(klines: &[Kline], ts: i64) -> Option<usize> {
+    let idx = count_to(klines, ts).checked_sub(1)?;
+
+    (ts <= klines[idx].t_close).then_some(idx)
+}
+
+#[cfg(test)]
+mod data_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_format_ts() {
+        assert_eq!(Data::format_ts(0.0), "1970-01-01 00:00:00");
+        assert_eq!(Data::format_ts(999.0), "1970-01-01 00:00:00");
+        assert_eq!(Data::format_ts(1659277217000.0), "2022-07-31 14:20:17");
+        assert_eq!(Data::format_ts(-1000.0), "1969-12-31 23:59:59");
+    }
+
+    #[test]
+    fn test_closed() {
+        let k = |t_open, closed| Kline {
+            t_open,
+            t_close: t_open + 1,
+            closed,
+            ..Default::default()
+        };
+        let data = Data::new(vec![k(0, true), k(2, true), k(4, false)]);
+
+        let closed = data.closed();
+        assert_eq!(closed.vals.len(), 2);
+        assert_eq!(closed.max_x(), 3.0);
+    }
+
+    #[test]
+    fn test_up_to() {
+        let k = |t_open| Kline {
+            t_open,
+            t_close: t_open + 1,
+            ..Default::default()
+        };
+        let data = Data::new(vec![k(0), k(2), k(4)]);
+
+        assert_eq!(data.up_to(2).max_x(), 3.0);
+        assert_eq!(data.up_to(10).vals.len(), 3);
+    }
+
+    #[test]
+    fn test_as_of() {
+        let data = Data::new(mock::klines(0, 60_000, 3));
+
+        let mid = data.as_of(90_000);
+        assert_eq!(mid.vals.len(), 2);
+        assert!(mid.vals[0].closed);
+        assert!(!mid.vals[1].closed);
+        assert_eq!(mid.closed().vals.len(), 1);
+
+        // nothing of the forming candle after the cutoff is known
+        let forming = mid.vals[1];
+        let open = data.vals[1].open;
+        assert_eq!(
+            [forming.open, forming.high, forming.low, forming.close],
+            [open; 4]
+        );
+        assert_eq!(forming.volume, 0.0);
+        assert!(mid.max_y() <= data.vals[0].high.max(open) as f64);
+
+        // a cutoff at the last millisecond of a candle keeps it closed
+        assert!(data.as_of(120_000 - 1).vals.iter().all(|k| k.closed));
+        assert_eq!(data.as_of(-1).vals.len(), 0);
+        assert_eq!(data.as_of(i64::MAX).vals, data.vals);
+    }
+
+    #[test]
+    fn test_new_empty() {
+        let data = Data::new(vec![]);
+
+        assert!(data.vals.is_empty());
+        assert_eq!(data.max_x(), 0.0);
+    }
+
+    #[test]
+    fn test_format_ts_out_of_range() {
+        assert_eq!(Data::format_ts(f64::MAX), labels::INVALID);
+        assert_eq!(Data::format_ts(f64::MIN), labels::INVALID);
+        assert_eq!(Data::format_ts(f64::NAN), labels::INVALID);
+    }
+
+    #[test]
+    fn test_slice_range() {
+        // a gap after the second candle
+        let mut klines = mock::klines(0, 60_000, 2);
+        klines.extend(mock::klines(180_000, 60_000, 2));
+        let data = Data::new(klines.clone());
+        let slice = |t0, t1| data.slice_range(t0, t1);
+
+        assert_eq!(slice(0, 300_000 - 1), &klines[..]);
+        // boundaries of a candle are within it
+        assert_eq!(slice(60_000 - 1, 60_000), &klines[..2]);
+        assert_eq!(slice(60_000, 120_000 - 1), &klines[1..2]);
+        assert_eq!(slice(120_000 - 1, 180_000), &klines[1..3]);
+        // within the gap, before and after the candles and reversed
+        assert!(slice(120_000, 180_000 - 1).is_empty());
+        assert!(slice(-60_000, -1).is_empty());
+        assert!(slice(300_000, 360_000).is_empty());
+        assert!(slice(180_000, 0).is_empty());
+        assert!(Data::default().slice_range(0, 60_000).is_empty());
+
+        let indices: Vec<usize> = data.iter_range(60_000, 180_000).map(|(i, _)| i).collect();
+        assert_eq!(indices, vec![1, 2]);
+        assert_eq!(Data::default().iter_range(0, 1).count(), 0);
+    }
+
+    #[test]
+    fn test_index_of() {
+        let mut klines = mock::klines(0, 60_000, 2);
+        klines.extend(mock::klines(180_000, 60_000, 1));
+        let data = Data::new(klines);
+
+        assert_eq!(data.index_of(0), Some(0));
+        assert_eq!(data.index_of(60_000 - 1), Some(0));
+        assert_eq!(data.index_of(60_000), Some(1));
+        assert_eq!(data.index_of(240_000 - 1), Some(2));
+        assert_eq!(data.index_of(-1), None);
+        assert_eq!(data.index_of(150_000), None);
+        assert_eq!(data.index_of(240_000), None);
+        assert_eq!(Data::default().index_of(0), None);
     }
 }
@@ -0,0 +1,106 @@
+//! Values published only once they stopped changing, with repeats of the last published
+//! one dropped.
+
+use std::time::{Duration, Instant};
+
+pub struct Debounce<T> {
+    delay: Duration,
+    /// Latest value with the time it was pushed at.
+    pending: Option<(T, Instant)>,
+    last: Option<T>,
+}
+
+impl<T: Clone + PartialEq> Debounce<T> {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: None,
+            last: None,
+        }
+    }
+
+    /// Replaces the pending value restarting the delay.
+    pub fn push(&mut self, value: T, now: Instant) {
+        self.pending = Some((value, now));
+    }
+
+    /// Marks the value as published elsewhere so it is not repeated.
+    pub fn seen(&mut self, value: T) {
+        self.last = Some(value);
+    }
+
+    /// Forgets the last published value so it is published again, e.g. once it failed.
+    pub fn forget(&mut self) {
+        self.last = None;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Pending value once the delay passed since it was pushed, unless it equals the last
+    /// published one.
+    pub fn poll(&mut self, now: Instant) -> Option<T> {
+        match &self.pending {
+            Some((_, pushed)) if now.duration_since(*pushed) >= self.delay => {}
+            _ => return None,
+        }
+
+        let (value, _) = self.pending.take()?;
+        if self.last.as_ref() == Some(&value) {
+            return None;
+        }
+        self.last = Some(value.clone());
+
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod debounce_tests {
+    use super::*;
+
+    const DELAY: Duration = Duration::from_millis(300);
+
+    #[test]
+    fn test_trailing_value() {
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let mut debounce = Debounce::new(DELAY);
+
+        let emitted: Vec<_> = (0..10)
+            .filter_map(|i| {
+                debounce.push(i, at(i * 50));
+                debounce.poll(at(i * 50 + 10))
+            })
+            .collect();
+        assert!(emitted.is_empty());
+        assert!(debounce.is_pending());
+
+        assert_eq!(debounce.poll(at(9 * 50 + 299)), None);
+        assert_eq!(debounce.poll(at(9 * 50 + 300)), Some(9));
+        assert_eq!(debounce.poll(at(2000)), None);
+        assert!(!debounce.is_pending());
+    }
+
+    #[test]
+    fn test_repeats_dropped() {
+        let start = Instant::now();
+        let mut debounce = Debounce::new(DELAY);
+
+        debounce.push(1, start);
+        assert_eq!(debounce.poll(start + DELAY), Some(1));
+        debounce.push(1, start + DELAY);
+        assert_eq!(debounce.poll(start + DELAY * 2), None);
+
+        debounce.seen(2);
+        debounce.push(2, start);
+        assert_eq!(debounce.poll(start + DELAY), None);
+        debounce.push(1, start);
+        assert_eq!(debounce.poll(start + DELAY), Some(1));
+
+        debounce.forget();
+        debounce.push(1, start);
+        assert_eq!(debounce.poll(start + DELAY), Some(1));
+    }
+}
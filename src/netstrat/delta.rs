@@ -0,0 +1,81 @@
+//! Order flow delta: taker buy volume minus taker sell volume per candle.
+
+use crate::sources::binance::Kline;
+
+use super::data::Data;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Delta {
+    /// Candle mid time in plot coordinates.
+    pub x: f64,
+    pub buy: f64,
+    pub sell: f64,
+    pub delta: f64,
+    /// Sum of deltas from the first candle up to this one.
+    pub cumulative: f64,
+}
+
+/// Deltas of candles having taker buy volume, candles without it are skipped.
+pub fn deltas(data: &Data) -> Vec<Delta> {
+    let mut cumulative = 0.0;
+    data.vals
+        .iter()
+        .filter_map(|k: &Kline| {
+            let buy = k.taker_buy_base_asset_volume? as f64;
+            let sell = k.volume as f64 - buy;
+            cumulative += buy - sell;
+
+            Some(Delta {
+                x: data.center(k),
+                buy,
+                sell,
+                delta: buy - sell,
+                cumulative,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod delta_tests {
+    use super::*;
+
+    fn kline(t_open: i64, volume: f32, taker_buy: Option<f32>) -> Kline {
+        Kline {
+            t_open,
+            t_close: t_open + 10,
+            volume,
+            taker_buy_base_asset_volume: taker_buy,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deltas() {
+        let data = Data::new(vec![
+            kline(0, 10.0, Some(7.0)),
+            kline(10, 4.0, None),
+            kline(20, 6.0, Some(1.0)),
+        ]);
+
+        assert_eq!(
+            deltas(&data),
+            vec![
+                Delta {
+                    x: 5.0,
+                    buy: 7.0,
+                    sell: 3.0,
+                    delta: 4.0,
+                    cumulative: 4.0,
+                },
+                Delta {
+                    x: 25.0,
+                    buy: 1.0,
+                    sell: 5.0,
+                    delta: -4.0,
+                    cumulative: 0.0,
+                },
+            ]
+        );
+    }
+}
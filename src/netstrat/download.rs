@@ -0,0 +1,187 @@
+use std::{
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use tracing::{error, info};
+
+use crate::{
+    errors::Result,
+    netstrat::bounds::Bounds,
+    sources::binance::{Client, Kline},
+};
+
+use super::{aggregate::aggregate, cache::Cache, props::Props, state::State};
+
+pub const CONCURRENCY_RANGE: RangeInclusive<usize> = 1..=16;
+
+/// Download settings of the charts, clones share the values.
+#[derive(Clone, Debug)]
+pub struct DownloadConfig {
+    /// Pages of a download requested at once.
+    concurrency: Arc<AtomicUsize>,
+    /// Whether a newly selected symbol loads the range of the previous one.
+    load_selected: Arc<AtomicBool>,
+}
+
+impl Default for DownloadConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: Arc::new(AtomicUsize::new(4)),
+            load_selected: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
+
+impl DownloadConfig {
+    pub fn concurrency(&self) -> usize {
+        self.concurrency.load(Ordering::Relaxed)
+    }
+
+    pub fn set_concurrency(&self, pages: usize) {
+        self.concurrency.store(
+            pages.clamp(*CONCURRENCY_RANGE.start(), *CONCURRENCY_RANGE.end()),
+            Ordering::Relaxed,
+        );
+    }
+
+    pub fn load_selected(&self) -> bool {
+        self.load_selected.load(Ordering::Relaxed)
+    }
+
+    pub fn set_load_selected(&self, load: bool) {
+        self.load_selected.store(load, Ordering::Relaxed);
+    }
+}
+
+/// Downloads klines of the page. Intervals without klines are aggregated from trades.
+/// Pages found in the cache are not downloaded, downloaded ones are cached.
+pub async fn page(
+    client: &Client,
+    cache: Option<&Cache>,
+    symbol: &str,
+    props: &Props,
+    page: Bounds,
+    limit: usize,
+) -> Result<Vec<Kline>> {
+    if let Some(klines) = cache.and_then(|c| c.get(symbol, props, page)) {
+        return Ok(klines);
+    }
+
+    let klines = match props.interval.is_aggregated() {
+        true => {
+            let trades = client.agg_trades(symbol, page).await?;
+            aggregate(&trades, props.interval.millis(), props.empty_buckets).vals
+        }
+        false => {
+            client
+                .kline(symbol.to_string(), props.interval, page, limit)
+                .await?
+        }
+    };
+
+    if let Some(cache) = cache {
+        // failing cache must not fail the download
+        if let Err(err) = cache.put(symbol, props, page, &klines) {
+            error!("Failed to cache page: {}.", err.chain());
+        }
+    }
+
+    Ok(klines)
+}
+
+/// Downloads klines for the props page by page without the gui.
+pub async fn download(client: &Client, symbol: &str, props: &Props) -> Result<Vec<Kline>> {
+    let mut state = State::default();
+    state.apply_props(props);
+
+    let mut klines = vec![];
+    if state.loading.pages.is_empty() {
+        info!("Nothing to download for props: {props:?}.");
+        return Ok(klines);
+    }
+
+    while let Some((page, limit)) = state.loading.take_page() {
+        klines.extend(self::page(client, None, symbol, props, page, limit).await?);
+        state.loading.page_loaded();
+    }
+
+    Ok(klines)
+}
+
+/// Inserts klines of a page, loaded in any order, keeping all of them sorted by open time.
+/// Candles of the page replace the ones with the same open time.
+pub fn insert_page(klines: &mut Vec<Kline>, page: &[Kline]) {
+    let (Some(first), Some(last)) = (page.first(), page.last()) else {
+        return;
+    };
+
+    let from = klines.partition_point(|k| k.t_open < first.t_open);
+    let to = klines.partition_point(|k| k.t_open <= last.t_open);
+    if from == to {
+        klines.splice(from..from, page.iter().copied());
+        return;
+    }
+
+    let mut overlapped = klines.drain(from..to).peekable();
+    let mut merged = Vec::with_capacity(page.len() + overlapped.len());
+    for k in page {
+        while let Some(old) = overlapped.next_if(|old| old.t_open <= k.t_open) {
+            if old.t_open < k.t_open {
+                merged.push(old);
+            }
+        }
+        merged.push(*k);
+    }
+    merged.extend(overlapped);
+    klines.splice(from..from, merged);
+}
+
+#[cfg(test)]
+mod download_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_pages_inserted_in_order() {
+        let all = mock::klines(0, 10, 9);
+        let mut klines = vec![];
+
+        [&all[6..9], &all[0..3], &all[3..6], &[]]
+            .into_iter()
+            .for_each(|page| insert_page(&mut klines, page));
+
+        assert_eq!(klines, all);
+    }
+
+    #[test]
+    fn test_overlapping_page_replaces_candles() {
+        let all = mock::klines(0, 10, 9);
+        let mut klines = [&all[0..2], &all[4..9]].concat();
+        let mut page = all[1..6].to_vec();
+        page.iter_mut().for_each(|k| k.close += 1.0);
+
+        insert_page(&mut klines, &page);
+
+        assert_eq!(
+            klines.iter().map(|k| k.t_open).collect::<Vec<_>>(),
+            all.iter().map(|k| k.t_open).collect::<Vec<_>>()
+        );
+        assert_eq!(klines[1..6], page[..]);
+        assert_eq!(klines[6..], all[6..]);
+    }
+
+    #[test]
+    fn test_concurrency_clamped() {
+        let config = DownloadConfig::default();
+
+        config.clone().set_concurrency(0);
+        assert_eq!(config.concurrency(), 1);
+        config.set_concurrency(100);
+        assert_eq!(config.concurrency(), 16);
+    }
+}
@@ -0,0 +1,240 @@
+//! Events like macro releases imported from csv and shown for any symbol.
+
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use quick_error::ResultExt;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Result;
+
+/// Category of events without one.
+const DEFAULT_CATEGORY: &str = "other";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    /// Time in millis.
+    pub ts: i64,
+    pub label: String,
+    pub category: String,
+}
+
+/// Events read from a file with the rows which couldn't be read.
+#[derive(Debug, Default)]
+pub struct Import {
+    pub events: Vec<Event>,
+    /// Messages about the bad rows.
+    pub errors: Vec<String>,
+}
+
+/// Imported events with the categories hidden by the user.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Events {
+    events: Vec<Event>,
+    hidden: BTreeSet<String>,
+}
+
+impl Events {
+    /// Adds events sorted by time, the ones already present are skipped.
+    pub fn add(&mut self, events: Vec<Event>) {
+        events.into_iter().for_each(|e| {
+            if !self.events.contains(&e) {
+                self.events.push(e);
+            }
+        });
+        self.events.sort_by_key(|e| e.ts);
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.hidden.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Sorted categories of all the events.
+    pub fn categories(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .map(|e| e.category.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn is_shown(&self, category: &str) -> bool {
+        !self.hidden.contains(category)
+    }
+
+    pub fn set_shown(&mut self, category: &str, shown: bool) {
+        match shown {
+            true => self.hidden.remove(category),
+            false => self.hidden.insert(category.to_string()),
+        };
+    }
+
+    /// Events of the shown categories within `[start, end]`.
+    pub fn visible(&self, start: i64, end: i64) -> impl Iterator<Item = &Event> {
+        self.events
+            .iter()
+            .filter(move |e| start <= e.ts && e.ts <= end && self.is_shown(&e.category))
+    }
+}
+
+/// Parses epoch millis or an ISO date, date time or RFC 3339 time, naive times are in UTC.
+pub fn parse_ts(s: &str) -> Option<i64> {
+    let s = s.trim();
+    if let Ok(millis) = s.parse::<i64>() {
+        return Some(millis);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.timestamp_millis());
+    }
+
+    [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+    })
+    .map(|dt| dt.and_utc().timestamp_millis())
+}
+
+/// Reads `timestamp,label,category` rows with a header, category is optional.
+pub fn from_csv(path: &Path) -> Result<Import> {
+    let f = std::fs::File::open(path).context(path)?;
+    from_reader(f, path)
+}
+
+fn from_reader(rdr: impl Read, path: &Path) -> Result<Import> {
+    let mut rdr = csv::ReaderBuilder::new().flexible(true).from_reader(rdr);
+    let mut import = Import::default();
+
+    for (idx, row) in rdr.records().enumerate() {
+        // the header is the first line
+        let line = idx + 2;
+        let row = match row {
+            Ok(row) => row,
+            Err(err) if err.is_io_error() => Err(err).context(path)?,
+            Err(err) => {
+                import.errors.push(format!("line {line}: {err}"));
+                continue;
+            }
+        };
+
+        let (ts, label) = match (row.get(0), row.get(1)) {
+            (Some(ts), Some(label)) if !label.trim().is_empty() => (ts, label.trim()),
+            _ => {
+                import.errors.push(format!("line {line}: no label"));
+                continue;
+            }
+        };
+        let ts = match parse_ts(ts) {
+            Some(ts) => ts,
+            None => {
+                import
+                    .errors
+                    .push(format!("line {line}: invalid timestamp {ts:?}"));
+                continue;
+            }
+        };
+
+        import.events.push(Event {
+            ts,
+            label: label.to_string(),
+            category: match row.get(2).map(str::trim) {
+                Some(c) if !c.is_empty() => c.to_string(),
+                _ => DEFAULT_CATEGORY.to_string(),
+            },
+        });
+    }
+
+    Ok(import)
+}
+
+#[cfg(test)]
+mod events_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ts() {
+        assert_eq!(parse_ts("1656633600000"), Some(1_656_633_600_000));
+        assert_eq!(parse_ts("2022-07-01T00:00:00Z"), Some(1_656_633_600_000));
+        assert_eq!(
+            parse_ts("2022-07-01T02:00:00+02:00"),
+            Some(1_656_633_600_000)
+        );
+        assert_eq!(parse_ts("2022-07-01 00:00:00"), Some(1_656_633_600_000));
+        assert_eq!(parse_ts("2022-07-01 00:01"), Some(1_656_633_660_000));
+        assert_eq!(parse_ts("2022-07-01"), Some(1_656_633_600_000));
+        assert_eq!(parse_ts("yesterday"), None);
+    }
+
+    #[test]
+    fn test_bad_rows_are_reported() {
+        let csv = "timestamp,label,category\n\
+            2022-07-01,CPI print,macro\n\
+            soon,FOMC,macro\n\
+            1656720000000,exchange outage\n\
+            1656720000000\n";
+
+        let import = from_reader(csv.as_bytes(), Path::new("events.csv")).unwrap();
+
+        assert_eq!(
+            import.events,
+            vec![
+                Event {
+                    ts: 1_656_633_600_000,
+                    label: "CPI print".to_string(),
+                    category: "macro".to_string(),
+                },
+                Event {
+                    ts: 1_656_720_000_000,
+                    label: "exchange outage".to_string(),
+                    category: DEFAULT_CATEGORY.to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            import.errors,
+            vec![
+                "line 3: invalid timestamp \"soon\"".to_string(),
+                "line 5: no label".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visible_events() {
+        let mut events = Events::default();
+        let event = |ts, category: &str| Event {
+            ts,
+            label: format!("{category} {ts}"),
+            category: category.to_string(),
+        };
+        events.add(vec![
+            event(30, "macro"),
+            event(10, "macro"),
+            event(20, "crypto"),
+        ]);
+        events.add(vec![event(10, "macro")]);
+
+        assert_eq!(events.categories(), vec!["crypto", "macro"]);
+        let ts = |events: &Events| events.visible(0, 25).map(|e| e.ts).collect::<Vec<_>>();
+        assert_eq!(ts(&events), vec![10, 20]);
+
+        events.set_shown("macro", false);
+        assert_eq!(ts(&events), vec![20]);
+    }
+}
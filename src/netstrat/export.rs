@@ -0,0 +1,1213 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+use quick_error::ResultExt;
+use serde::{Deserialize, Serialize, Serializer};
+use tracing::{info, warn};
+
+use crate::errors::Result;
+use crate::sources::binance::Kline;
+
+use super::{
+    axis,
+    indicators::IndicatorOutput,
+    manifest::{HashWriter, Written},
+    pages::Pages,
+    props::Props,
+    stats::Stats,
+    timezone::Timezone,
+    xlsx::{self, Cell, Workbook},
+};
+
+/// Average size of a csv row with the [`Columns::Basic`] and [`Columns::All`] columns.
+const BASIC_ROW_BYTES: usize = 75;
+const ALL_ROW_BYTES: usize = 120;
+/// Average size of an xlsx row with the [`Columns::Basic`] and [`Columns::All`] columns.
+const XLSX_BASIC_ROW_BYTES: usize = 60;
+const XLSX_ALL_ROW_BYTES: usize = 100;
+/// Candles of an xlsx sheet below its header, more are split over more sheets.
+pub const XLSX_SHEET_ROWS: usize = xlsx::MAX_ROWS - 1;
+/// Average size of a line protocol point with all the fields.
+const LINE_BYTES: usize = 250;
+/// Average size of a pretty json object with all the fields.
+const JSON_ROW_BYTES: usize = 400;
+/// Copied json rows above it are warned about as they make a large clipboard.
+pub const JSON_WARN_ROWS: usize = 5_000;
+
+/// Default export file name for the symbol and props with the times in the format.
+pub fn file_name(symbol: &str, props: &Props, times: TimeFormat) -> String {
+    format!(
+        "{}_{}_{}_{:?}.csv",
+        symbol,
+        times.file_ts(props.start_time().timestamp_millis()),
+        times.file_ts(props.end_time().timestamp_millis()),
+        props.interval,
+    )
+}
+
+/// How the open and close times and the times of the file name are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeFormat {
+    /// Epoch millis.
+    #[default]
+    Millis,
+    /// Epoch seconds with the millis as decimals.
+    Seconds,
+    /// ISO 8601 date in the timezone `utc_offset` minutes from UTC, the one of the app in
+    /// [`ExportOptions::times`].
+    Iso { utc_offset: i32 },
+}
+
+impl TimeFormat {
+    /// Formats of the options with ISO ones in UTC.
+    pub const ALL: [TimeFormat; 3] = [
+        TimeFormat::Millis,
+        TimeFormat::Seconds,
+        TimeFormat::Iso { utc_offset: 0 },
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimeFormat::Millis => "epoch millis",
+            TimeFormat::Seconds => "epoch seconds",
+            TimeFormat::Iso { .. } => "ISO 8601",
+        }
+    }
+
+    /// Time of the UTC millis, e.g. `2022-07-01T02:00:00.000+02:00`.
+    pub fn format(&self, ts: i64) -> String {
+        match self {
+            TimeFormat::Millis => ts.to_string(),
+            TimeFormat::Seconds => {
+                let sign = if ts < 0 { "-" } else { "" };
+                format!("{sign}{}.{:03}", ts.abs() / 1000, ts.abs() % 1000)
+            }
+            TimeFormat::Iso { .. } => self
+                .local(ts)
+                .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Millis, true))
+                .unwrap_or_else(|| ts.to_string()),
+        }
+    }
+
+    /// Time of the UTC millis without separators, to the second for ISO dates, e.g.
+    /// `20220701T020000+0200`.
+    pub fn file_ts(&self, ts: i64) -> String {
+        match (self, self.local(ts)) {
+            (TimeFormat::Millis, _) => ts.to_string(),
+            (TimeFormat::Seconds, _) => ts.div_euclid(1000).to_string(),
+            (TimeFormat::Iso { utc_offset: 0 }, Some(dt)) => {
+                dt.format("%Y%m%dT%H%M%SZ").to_string()
+            }
+            (TimeFormat::Iso { .. }, Some(dt)) => dt.format("%Y%m%dT%H%M%S%z").to_string(),
+            (TimeFormat::Iso { .. }, None) => ts.to_string(),
+        }
+    }
+
+    /// Cell of the time, a date shown in the timezone for ISO dates.
+    fn cell(&self, ts: i64) -> Cell<'static> {
+        match self {
+            TimeFormat::Millis => Cell::Number(ts as f64),
+            TimeFormat::Seconds => Cell::Number(ts as f64 / 1000.0),
+            TimeFormat::Iso { utc_offset } => Cell::Date(ts + *utc_offset as i64 * 60_000),
+        }
+    }
+
+    fn local(&self, ts: i64) -> Option<DateTime<FixedOffset>> {
+        let utc_offset = match self {
+            TimeFormat::Iso { utc_offset } => *utc_offset,
+            _ => 0,
+        };
+        let offset = FixedOffset::east_opt(utc_offset * 60)?;
+
+        DateTime::from_timestamp_millis(ts).map(|dt| dt.with_timezone(&offset))
+    }
+}
+
+/// Export preferences of the settings. Clones share them.
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions {
+    times: Arc<Mutex<TimeFormat>>,
+    /// Whether a [`super::manifest::Manifest`] is written next to exported files.
+    manifest: Arc<AtomicBool>,
+    timezone: Timezone,
+}
+
+impl ExportOptions {
+    pub fn new(timezone: Timezone) -> Self {
+        Self {
+            timezone,
+            ..Default::default()
+        }
+    }
+
+    /// Format of the times, ISO ones in the timezone of the app.
+    pub fn times(&self) -> TimeFormat {
+        match self.times.lock().map(|f| *f).unwrap_or_default() {
+            TimeFormat::Iso { .. } => TimeFormat::Iso {
+                utc_offset: self.timezone.utc_offset(),
+            },
+            times => times,
+        }
+    }
+
+    pub fn set_times(&self, format: TimeFormat) {
+        if let Ok(mut f) = self.times.lock() {
+            *f = format;
+        }
+    }
+
+    pub fn manifest(&self) -> bool {
+        self.manifest.load(Ordering::Relaxed)
+    }
+
+    pub fn set_manifest(&self, manifest: bool) {
+        self.manifest.store(manifest, Ordering::Relaxed);
+    }
+}
+
+/// Columns written to csv.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Columns {
+    /// Prices, base volume, times and closed flag.
+    Basic,
+    /// Basic columns with quote volume, number of trades and taker buy volumes.
+    #[default]
+    All,
+}
+
+/// How an export is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv(Columns),
+    /// Excel workbook with a sheet of candles per symbol and optionally one of their stats.
+    Xlsx {
+        columns: Columns,
+        stats: bool,
+    },
+    /// InfluxDB line protocol file.
+    LineProtocol,
+    /// Points written to the InfluxDB of the settings.
+    Influx,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::Csv(Columns::default())
+    }
+}
+
+impl Format {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Csv(_) => "csv",
+            Format::Xlsx { .. } => "xlsx",
+            Format::LineProtocol => "line protocol",
+            Format::Influx => "InfluxDB",
+        }
+    }
+
+    /// Whether a manifest can be written next to the file, see [`to_xlsx`] for workbooks.
+    pub fn manifest(&self) -> bool {
+        matches!(self, Format::Csv(_) | Format::LineProtocol)
+    }
+
+    /// Extension of the written file, none when nothing is written locally.
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Format::Csv(_) => Some("csv"),
+            Format::Xlsx { .. } => Some("xlsx"),
+            Format::LineProtocol => Some("lp"),
+            Format::Influx => None,
+        }
+    }
+
+    fn row_bytes(&self) -> usize {
+        match self {
+            Format::Csv(Columns::Basic) => BASIC_ROW_BYTES,
+            Format::Csv(Columns::All) => ALL_ROW_BYTES,
+            Format::Xlsx {
+                columns: Columns::Basic,
+                ..
+            } => XLSX_BASIC_ROW_BYTES,
+            Format::Xlsx {
+                columns: Columns::All,
+                ..
+            } => XLSX_ALL_ROW_BYTES,
+            Format::LineProtocol | Format::Influx => LINE_BYTES,
+        }
+    }
+}
+
+/// Expected size of an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Estimate {
+    /// Requests to download the candles.
+    pub pages: usize,
+    pub rows: usize,
+    pub bytes: usize,
+}
+
+/// Estimates the size of an export of the props in the format.
+pub fn estimate(props: &Props, format: Format) -> Estimate {
+    let rows = props.candles();
+    let pages = Pages::new(
+        props.bounds.clone(),
+        props.interval.millis() as usize,
+        props.limit,
+    )
+    .map_or(0, |p| p.len());
+    Estimate {
+        pages,
+        rows,
+        bytes: rows * format.row_bytes(),
+    }
+}
+
+/// Exports over any of the limits are confirmed before they start.
+/// Clones share the limits.
+#[derive(Clone, Debug)]
+pub struct ConfirmLimits {
+    pages: Arc<AtomicUsize>,
+    rows: Arc<AtomicUsize>,
+}
+
+impl Default for ConfirmLimits {
+    fn default() -> Self {
+        Self {
+            pages: Arc::new(AtomicUsize::new(50)),
+            rows: Arc::new(AtomicUsize::new(100_000)),
+        }
+    }
+}
+
+impl ConfirmLimits {
+    pub fn pages(&self) -> usize {
+        self.pages.load(Ordering::Relaxed)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    pub fn set(&self, pages: usize, rows: usize) {
+        self.pages.store(pages, Ordering::Relaxed);
+        self.rows.store(rows, Ordering::Relaxed);
+    }
+
+    pub fn exceeded(&self, estimate: &Estimate) -> bool {
+        estimate.pages > self.pages() || estimate.rows > self.rows()
+    }
+}
+
+/// Indicators enabled on the chart listed in the export options with whether their values
+/// are written as columns. Clones share the list.
+#[derive(Clone, Debug, Default)]
+pub struct IndicatorColumns {
+    /// Ids as in [`super::indicators::Indicators::enabled_ids`] with names and checked state.
+    listed: Arc<Mutex<Vec<(String, String, bool)>>>,
+}
+
+impl IndicatorColumns {
+    /// Lists the indicators by id and name, ones listed before keep their checked state and
+    /// new ones are checked.
+    pub fn set_listed(&self, indicators: Vec<(String, String)>) {
+        let Ok(mut listed) = self.listed.lock() else {
+            return;
+        };
+        *listed = indicators
+            .into_iter()
+            .map(|(id, name)| {
+                let checked = listed.iter().find(|l| l.0 == id).is_none_or(|l| l.2);
+                (id, name, checked)
+            })
+            .collect();
+    }
+
+    pub fn listed(&self) -> Vec<(String, String, bool)> {
+        self.listed.lock().map(|l| l.clone()).unwrap_or_default()
+    }
+
+    pub fn set_checked(&self, id: &str, checked: bool) {
+        if let Ok(mut listed) = self.listed.lock() {
+            listed
+                .iter_mut()
+                .filter(|l| l.0 == id)
+                .for_each(|l| l.2 = checked);
+        }
+    }
+
+    /// Ids of the checked indicators.
+    pub fn checked(&self) -> Vec<String> {
+        self.listed()
+            .into_iter()
+            .filter(|l| l.2)
+            .map(|l| l.0)
+            .collect()
+    }
+}
+
+/// Format picked in the export options, the checked symbols of the list are exported in it
+/// too. Clones share it.
+#[derive(Clone, Debug, Default)]
+pub struct ChosenFormat {
+    format: Arc<Mutex<Format>>,
+}
+
+impl ChosenFormat {
+    pub fn get(&self) -> Format {
+        self.format.lock().map(|f| *f).unwrap_or_default()
+    }
+
+    pub fn set(&self, format: Format) {
+        if let Ok(mut f) = self.format.lock() {
+            *f = format;
+        }
+    }
+}
+
+/// Indicator values of every exported candle, empty where not available.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub values: Vec<Option<f64>>,
+}
+
+/// Columns of the values shown in the tooltip of the outputs or of their lines when they have
+/// none, named after the series so that the same params give the same names.
+pub fn indicator_columns(klines: &[Kline], outputs: &[IndicatorOutput]) -> Vec<Column> {
+    let step = axis::smallest_gap(klines);
+    let mut columns: Vec<Column> = vec![];
+    outputs
+        .iter()
+        .flat_map(|o| match o.values.is_empty() {
+            true => o.overlay.iter().chain(&o.panel).chain(&o.dots).collect(),
+            false => o.values.iter().collect::<Vec<_>>(),
+        })
+        .for_each(|s| {
+            let base = column_name(&s.name);
+            let name = (1..)
+                .map(|n| match n {
+                    1 => base.clone(),
+                    n => format!("{base}_{n}"),
+                })
+                .find(|name| columns.iter().all(|c| &c.name != name))
+                .unwrap_or(base);
+            let values = klines
+                .iter()
+                .map(|k| {
+                    let x = axis::center(k, step);
+                    let idx = s.points.binary_search_by(|p| p[0].total_cmp(&x));
+                    idx.ok().map(|idx| s.points[idx][1])
+                })
+                .collect();
+            columns.push(Column { name, values });
+        });
+
+    columns
+}
+
+/// Series name as a lowercase column name, e.g. `sma_50` for `SMA(50)`.
+fn column_name(series: &str) -> String {
+    series
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Time written in the format.
+struct Time(i64, TimeFormat);
+
+impl Serialize for Time {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.1 {
+            TimeFormat::Millis => serializer.serialize_i64(self.0),
+            format => serializer.serialize_str(&format.format(self.0)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BasicRow {
+    t_open: Time,
+    open: f32,
+    high: f32,
+    low: f32,
+    close: f32,
+    volume: f32,
+    t_close: Time,
+    closed: bool,
+}
+
+impl BasicRow {
+    fn new(k: &Kline, times: TimeFormat) -> Self {
+        Self {
+            t_open: Time(k.t_open, times),
+            open: k.open,
+            high: k.high,
+            low: k.low,
+            close: k.close,
+            volume: k.volume,
+            t_close: Time(k.t_close, times),
+            closed: k.closed,
+        }
+    }
+}
+
+/// Kline with its times in a format.
+#[derive(Serialize)]
+struct AllRow {
+    t_open: Time,
+    open: f32,
+    high: f32,
+    low: f32,
+    close: f32,
+    volume: f32,
+    t_close: Time,
+    quote_asset_volume: Option<f32>,
+    number_of_trades: Option<i64>,
+    taker_buy_base_asset_volume: Option<f32>,
+    taker_buy_quote_asset_volume: Option<f32>,
+    closed: bool,
+}
+
+impl AllRow {
+    fn new(k: &Kline, times: TimeFormat) -> Self {
+        Self {
+            t_open: Time(k.t_open, times),
+            open: k.open,
+            high: k.high,
+            low: k.low,
+            close: k.close,
+            volume: k.volume,
+            t_close: Time(k.t_close, times),
+            quote_asset_volume: k.quote_asset_volume,
+            number_of_trades: k.number_of_trades,
+            taker_buy_base_asset_volume: k.taker_buy_base_asset_volume,
+            taker_buy_quote_asset_volume: k.taker_buy_quote_asset_volume,
+            closed: k.closed,
+        }
+    }
+}
+
+/// Names of the csv columns of the candles.
+fn kline_header(columns: Columns) -> csv::Result<Vec<String>> {
+    let k = Kline::default();
+    match columns {
+        Columns::Basic => header(&BasicRow::new(&k, TimeFormat::Millis)),
+        Columns::All => header(&AllRow::new(&k, TimeFormat::Millis)),
+    }
+}
+
+/// Writes klines to csv file with times in millis returning its absolute path.
+pub fn to_csv(path: &Path, klines: &[Kline], columns: Columns) -> Result<PathBuf> {
+    to_csv_with_indicators(path, klines, columns, TimeFormat::Millis, &[]).map(|w| w.path)
+}
+
+/// Writes klines to csv file with the times in the format and the indicator columns after
+/// the candle ones, returning its absolute path and hash.
+pub fn to_csv_with_indicators(
+    path: &Path,
+    klines: &[Kline],
+    columns: Columns,
+    times: TimeFormat,
+    indicators: &[Column],
+) -> Result<Written> {
+    let f = File::create(path).context(path)?;
+    let abs_path = path.canonicalize().context(path)?;
+    info!("Saving to file: {abs_path:?}");
+
+    let mut hashed = HashWriter::new(f);
+    write_csv(&mut hashed, klines, columns, times, indicators, true).context(path)?;
+
+    Ok(Written {
+        path: abs_path,
+        sha256: hashed.sha256(),
+    })
+}
+
+/// Appends klines to csv file with the times in the format, writing the header first when
+/// the file is new or empty. Returns its absolute path.
+pub fn append_csv(
+    path: &Path,
+    klines: &[Kline],
+    columns: Columns,
+    times: TimeFormat,
+) -> Result<PathBuf> {
+    let f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context(path)?;
+    let abs_path = path.canonicalize().context(path)?;
+    info!("Appending to file: {abs_path:?}");
+
+    let empty = f.metadata().context(path)?.len() == 0;
+    write_csv(f, klines, columns, times, &[], empty).context(path)?;
+
+    Ok(abs_path)
+}
+
+fn write_csv<W: Write>(
+    w: W,
+    klines: &[Kline],
+    columns: Columns,
+    times: TimeFormat,
+    indicators: &[Column],
+    with_header: bool,
+) -> csv::Result<()> {
+    let mut wtr = csv::WriterBuilder::new().has_headers(false).from_writer(w);
+    if with_header {
+        let header = kline_header(columns)?;
+        wtr.write_record(header.iter().chain(indicators.iter().map(|c| &c.name)))?;
+    }
+    for (i, k) in klines.iter().enumerate() {
+        let values: Vec<Option<f64>> = indicators.iter().map(|c| c.values[i]).collect();
+        match columns {
+            Columns::Basic => wtr.serialize((BasicRow::new(k, times), values)),
+            Columns::All => wtr.serialize((AllRow::new(k, times), values)),
+        }?;
+    }
+    wtr.flush()?;
+
+    Ok(())
+}
+
+/// Candles of a symbol with their indicator columns written to sheets of a workbook.
+pub struct XlsxSheet<'a> {
+    pub symbol: &'a str,
+    pub klines: &'a [Kline],
+    pub indicators: &'a [Column],
+}
+
+/// Sheets the rows are split over in an xlsx export.
+pub fn xlsx_sheets(rows: usize) -> usize {
+    rows.div_ceil(XLSX_SHEET_ROWS).max(1)
+}
+
+/// Writes a workbook with a sheet of every symbol, split over more sheets beyond
+/// [`XLSX_SHEET_ROWS`], and a sheet of their stats if `stats`. Times are numbers for epoch
+/// formats and dates for ISO ones. Returns its absolute path.
+///
+/// Workbooks get no manifest: saving one seeks back to patch its zip entries, so its bytes
+/// can't be hashed as they are written.
+pub fn to_xlsx(
+    path: &Path,
+    sheets: &[XlsxSheet],
+    columns: Columns,
+    times: TimeFormat,
+    stats: bool,
+) -> Result<PathBuf> {
+    let header = kline_header(columns).context(path)?;
+    let widths: Vec<f64> = header
+        .iter()
+        .map(|name| match name.starts_with("t_") {
+            true => 24.0,
+            false => 12.0,
+        })
+        .collect();
+    let mut wb = Workbook::new();
+    for sheet in sheets {
+        let parts = xlsx_sheets(sheet.klines.len());
+        if parts > 1 {
+            warn!(
+                "{} candles of {} are split over {parts} sheets",
+                sheet.klines.len(),
+                sheet.symbol
+            );
+        }
+        for part in 0..parts {
+            let name = match part {
+                0 => sheet.symbol.to_string(),
+                n => format!("{} {}", sheet.symbol, n + 1),
+            };
+            wb.add_sheet(&name, &widths).context(path)?;
+            let names = header
+                .iter()
+                .chain(sheet.indicators.iter().map(|c| &c.name));
+            wb.write_header(&names.collect::<Vec<_>>()).context(path)?;
+
+            let from = part * XLSX_SHEET_ROWS;
+            let rows = sheet.klines.iter().enumerate().skip(from);
+            for (i, k) in rows.take(XLSX_SHEET_ROWS) {
+                let mut cells = kline_cells(k, columns, times);
+                cells.extend(
+                    sheet
+                        .indicators
+                        .iter()
+                        .map(|c| c.values[i].map_or(Cell::Empty, Cell::Number)),
+                );
+                wb.write_row(&cells).context(path)?;
+            }
+        }
+    }
+    if stats {
+        stats_sheet(&mut wb, sheets, times).context(path)?;
+    }
+    wb.save(path).context(path)?;
+    let abs_path = path.canonicalize().context(path)?;
+    info!("Saved to file: {abs_path:?}");
+
+    Ok(abs_path)
+}
+
+/// Cells of the kline in the order of the csv columns.
+fn kline_cells(k: &Kline, columns: Columns, times: TimeFormat) -> Vec<Cell<'static>> {
+    let optional = |v: Option<f32>| v.map_or(Cell::Empty, |v| Cell::Number(v as f64));
+    let mut cells = vec![
+        times.cell(k.t_open),
+        Cell::Number(k.open as f64),
+        Cell::Number(k.high as f64),
+        Cell::Number(k.low as f64),
+        Cell::Number(k.close as f64),
+        Cell::Number(k.volume as f64),
+        times.cell(k.t_close),
+    ];
+    if columns == Columns::All {
+        cells.extend([
+            optional(k.quote_asset_volume),
+            k.number_of_trades
+                .map_or(Cell::Empty, |n| Cell::Number(n as f64)),
+            optional(k.taker_buy_base_asset_volume),
+            optional(k.taker_buy_quote_asset_volume),
+        ]);
+    }
+    cells.push(Cell::Bool(k.closed));
+
+    cells
+}
+
+/// Sheet with a row of the stats of every symbol.
+fn stats_sheet(wb: &mut Workbook, sheets: &[XlsxSheet], times: TimeFormat) -> std::io::Result<()> {
+    let header = [
+        "symbol",
+        "candles",
+        "from",
+        "to",
+        "open",
+        "close",
+        "change %",
+        "high",
+        "low",
+        "range %",
+        "volume",
+        "up candles",
+        "volatility %",
+    ];
+    let widths: Vec<f64> = header
+        .iter()
+        .map(|name| match *name {
+            "from" | "to" => 24.0,
+            _ => 12.0,
+        })
+        .collect();
+    wb.add_sheet("stats", &widths)?;
+    wb.write_header(&header)?;
+    for sheet in sheets {
+        let Some(s) = Stats::of(sheet.klines) else {
+            wb.write_row(&[Cell::Text(sheet.symbol), Cell::Number(0.0)])?;
+            continue;
+        };
+        wb.write_row(&[
+            Cell::Text(sheet.symbol),
+            Cell::Number(s.candles as f64),
+            times.cell(s.bounds.0),
+            times.cell(s.bounds.1),
+            Cell::Number(s.open),
+            Cell::Number(s.close),
+            Cell::Number(s.change),
+            Cell::Number(s.high),
+            Cell::Number(s.low),
+            Cell::Number(s.range),
+            Cell::Number(s.volume),
+            Cell::Number(s.up as f64),
+            s.volatility.map_or(Cell::Empty, Cell::Number),
+        ])?;
+    }
+
+    Ok(())
+}
+
+/// Names of the csv columns a row is written to.
+fn header<T: Serialize>(row: &T) -> csv::Result<Vec<String>> {
+    let mut written = vec![];
+    let mut wtr = csv::Writer::from_writer(&mut written);
+    wtr.serialize(row)?;
+    wtr.flush()?;
+    drop(wtr);
+    let mut rdr = csv::Reader::from_reader(written.as_slice());
+
+    Ok(rdr.headers()?.iter().map(String::from).collect())
+}
+
+/// Klines as a json array of the rows of an export with all the columns, times are ISO
+/// dates in place of millis.
+pub fn to_json(klines: &[Kline]) -> serde_json::Result<String> {
+    let rows = klines
+        .iter()
+        .map(|k| {
+            let mut row = serde_json::to_value(k)?;
+            if let Some(row) = row.as_object_mut() {
+                [("t_open", k.t_open), ("t_close", k.t_close)]
+                    .into_iter()
+                    .for_each(|(name, ts)| {
+                        let iso = TimeFormat::Iso { utc_offset: 0 }.format(ts);
+                        row.insert(name.to_string(), iso.into());
+                    });
+            }
+            Ok(row)
+        })
+        .collect::<serde_json::Result<Vec<_>>>()?;
+
+    serde_json::to_string_pretty(&rows)
+}
+
+/// Expected size of the json of that many rows.
+pub fn json_bytes(rows: usize) -> usize {
+    rows * JSON_ROW_BYTES
+}
+
+/// Reads klines written with any of the [`Columns`] and times in millis. Missing optional columns are read as `None`.
+pub fn from_csv(path: &Path) -> Result<Vec<Kline>> {
+    let mut rdr = csv::Reader::from_path(path).context(path)?;
+
+    rdr.deserialize()
+        .map(|row| Ok(row.context(path)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod export_tests {
+
+    use crate::{
+        netstrat::{
+            bounds::{Bounds, BoundsSet},
+            data::Data,
+            download::download,
+            indicators::{Bollinger, Indicators, Rsi, Series, Sma},
+        },
+        sources::binance::{
+            mock::{self, MockBinance},
+            Interval,
+        },
+    };
+
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+    const ISO_UTC: TimeFormat = TimeFormat::Iso { utc_offset: 0 };
+
+    fn props(start: i64, end: i64, limit: usize) -> Props {
+        let dt_start = DateTime::from_timestamp_millis(start).unwrap().naive_utc();
+        let dt_end = DateTime::from_timestamp_millis(end).unwrap().naive_utc();
+
+        Props {
+            date_start: dt_start.date(),
+            date_end: dt_end.date(),
+            time_start: dt_start.time(),
+            time_end: dt_end.time(),
+            bounds: BoundsSet::new(vec![Bounds(start, end)]),
+            interval: Interval::Minute,
+            limit,
+            empty_buckets: Default::default(),
+        }
+    }
+
+    async fn serve_pages(mock: &MockBinance, pages: usize, per_page: usize) -> Vec<Kline> {
+        let all = mock::klines(0, MINUTE, pages * per_page);
+        for chunk in all.chunks(per_page) {
+            mock.klines_page(chunk[0].t_open, chunk).await;
+        }
+
+        all
+    }
+
+    #[tokio::test]
+    async fn test_multi_page_download() {
+        let mock = MockBinance::start().await;
+        let expected = serve_pages(&mock, 3, 2).await;
+
+        let props = props(0, 6 * MINUTE, 2);
+        let klines = download(&mock.client(), "BTCUSDT", &props).await.unwrap();
+
+        assert_eq!(klines, expected);
+        assert_eq!(mock.received_requests().await.len(), 3);
+        mock.received_requests().await.iter().for_each(|r| {
+            assert_eq!(mock::query_value(r, "limit").unwrap(), "2");
+        });
+
+        let data = Data::new(klines);
+        assert_eq!(data.vals.len(), 6);
+        assert_eq!(data.min_x(), 0.0);
+        assert_eq!(data.max_x(), (6 * MINUTE - 1) as f64);
+        assert_eq!(data.max_y(), 107.0);
+        assert_eq!(data.min_y(), 99.0);
+    }
+
+    #[tokio::test]
+    async fn test_export_csv_matches_fixture() {
+        let mock = MockBinance::start().await;
+        serve_pages(&mock, 3, 2).await;
+
+        let props = props(0, 6 * MINUTE, 2);
+        let klines = download(&mock.client(), "BTCUSDT", &props).await.unwrap();
+
+        let path = std::env::temp_dir().join(file_name("BTCUSDT", &props, TimeFormat::Millis));
+        let abs_path = to_csv(&path, &klines, Columns::All).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&abs_path).unwrap(),
+            include_str!("../../tests/fixtures/klines.csv")
+        );
+        assert_eq!(
+            file_name("BTCUSDT", &props, TimeFormat::Millis),
+            "BTCUSDT_0_360000_Minute.csv"
+        );
+        assert_eq!(from_csv(&abs_path).unwrap(), klines);
+
+        std::fs::remove_file(abs_path).unwrap();
+    }
+
+    #[test]
+    fn test_indicator_columns_match_fixture() {
+        let klines = mock::klines(0, MINUTE, 4);
+        let mut indicators = Indicators::new(vec![
+            Box::new(Sma { period: 2 }),
+            Box::new(Rsi { period: 2 }),
+            Box::new(Bollinger::default()),
+        ]);
+        indicators.set_enabled_ids(&[
+            "sma".to_string(),
+            "rsi".to_string(),
+            "bollinger".to_string(),
+        ]);
+
+        let outputs = indicators.evaluate(
+            &Data::new(klines.clone()),
+            &["sma".to_string(), "rsi".to_string()],
+        );
+        let columns = indicator_columns(&klines, &outputs);
+
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["sma_2", "rsi_2"]);
+        let path = std::env::temp_dir().join("netstrat_indicator_columns.csv");
+        let written =
+            to_csv_with_indicators(&path, &klines, Columns::Basic, TimeFormat::Millis, &columns)
+                .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            include_str!("../../tests/fixtures/klines_indicators.csv")
+        );
+        // hashed while written
+        assert_eq!(
+            written.sha256,
+            crate::netstrat::manifest::sha256(include_bytes!(
+                "../../tests/fixtures/klines_indicators.csv"
+            ))
+        );
+        // candle columns are read back as before
+        assert_eq!(from_csv(&path).unwrap()[3].close, klines[3].close);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_xlsx_workbook() {
+        let klines = mock::klines(1_656_633_600_000, MINUTE, 3);
+        let columns = vec![Column {
+            name: "sma_2".to_string(),
+            values: vec![None, Some(101.5), Some(102.5)],
+        }];
+        let sheets = [
+            XlsxSheet {
+                symbol: "BTCUSDT",
+                klines: &klines,
+                indicators: &columns,
+            },
+            XlsxSheet {
+                symbol: "ETHUSDT",
+                klines: &[],
+                indicators: &[],
+            },
+        ];
+        let path = std::env::temp_dir().join("netstrat_export.xlsx");
+
+        let abs_path = to_xlsx(&path, &sheets, Columns::Basic, ISO_UTC, true).unwrap();
+
+        let book = xlsx::xlsx_tests::entry(&abs_path, "xl/workbook.xml");
+        assert!(book.contains(
+            r#"<sheet name="BTCUSDT" sheetId="1" r:id="rId1"/><sheet name="ETHUSDT" sheetId="2" r:id="rId2"/><sheet name="stats" sheetId="3" r:id="rId3"/>"#
+        ));
+        let content = xlsx::xlsx_tests::entry(&abs_path, "xl/worksheets/sheet1.xml");
+        assert!(content.contains(r#"<c r="I1" s="1" t="inlineStr"><is><t>sma_2</t></is></c>"#));
+        // 2022-07-01 00:01 as a date and the close as a number
+        assert!(content.contains(r#"<c r="A3" s="2"><v>44743.00069444445</v></c>"#));
+        assert!(content.contains(r#"<c r="E3"><v>102</v></c>"#));
+        assert!(content.contains(r#"<c r="I3"><v>101.5</v></c>"#));
+        // nothing past the closed flag while the indicator warms up
+        assert!(content.contains(r#"<c r="H2" t="b"><v>1</v></c></row>"#));
+
+        std::fs::remove_file(abs_path).unwrap();
+    }
+
+    #[test]
+    fn test_xlsx_sheets() {
+        assert_eq!(xlsx_sheets(0), 1);
+        assert_eq!(xlsx_sheets(XLSX_SHEET_ROWS), 1);
+        assert_eq!(xlsx_sheets(XLSX_SHEET_ROWS + 1), 2);
+    }
+
+    #[test]
+    fn test_column_names() {
+        assert_eq!(column_name("SMA(50)"), "sma_50");
+        assert_eq!(column_name("BB(20, 2.5) upper"), "bb_20_2_5_upper");
+        assert_eq!(
+            column_name("cumulative log returns"),
+            "cumulative_log_returns"
+        );
+
+        let klines = mock::klines(0, MINUTE, 2);
+        let series = Series {
+            name: "Tenkan".to_string(),
+            points: vec![[(MINUTE + MINUTE / 2) as f64, 1.0]],
+            ..Default::default()
+        };
+        let output = IndicatorOutput {
+            values: vec![series.clone(), series],
+            ..Default::default()
+        };
+
+        let columns = indicator_columns(&klines, &[output]);
+
+        assert_eq!(columns[0].name, "tenkan");
+        assert_eq!(columns[1].name, "tenkan_2");
+        assert_eq!(columns[0].values, vec![None, Some(1.0)]);
+    }
+
+    #[test]
+    fn test_basic_columns_load_without_optional_fields() {
+        let klines = mock::klines(0, MINUTE, 2);
+        let path = std::env::temp_dir().join("netstrat_basic_columns.csv");
+        to_csv(&path, &klines, Columns::Basic).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("t_open,open,high,low,close,volume,t_close,closed\n"));
+
+        let loaded = from_csv(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].close, klines[1].close);
+        assert!(loaded[1].closed);
+        assert_eq!(loaded[1].quote_asset_volume, None);
+        assert_eq!(loaded[1].number_of_trades, None);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_load_data_saved_before_optional_fields() {
+        let path = std::env::temp_dir().join("netstrat_old_columns.csv");
+        std::fs::write(
+            &path,
+            "t_open,open,high,low,close,volume,t_close\n0,1.0,2.0,0.5,1.5,10.0,59999\n",
+        )
+        .unwrap();
+
+        let loaded = from_csv(&path).unwrap();
+        assert_eq!(loaded[0].volume, 10.0);
+        assert_eq!(loaded[0].taker_buy_base_asset_volume, None);
+        assert!(loaded[0].closed);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_time_formats() {
+        // 2022-07-01 00:01:59.999 UTC
+        let ts = 1_656_633_719_999;
+        let plus_two = TimeFormat::Iso { utc_offset: 120 };
+        let minus_half = TimeFormat::Iso { utc_offset: -30 };
+
+        assert_eq!(TimeFormat::Millis.format(ts), "1656633719999");
+        assert_eq!(TimeFormat::Seconds.format(ts), "1656633719.999");
+        assert_eq!(TimeFormat::Seconds.format(-1), "-0.001");
+        assert_eq!(ISO_UTC.format(ts), "2022-07-01T00:01:59.999Z");
+        assert_eq!(plus_two.format(ts), "2022-07-01T02:01:59.999+02:00");
+        assert_eq!(minus_half.format(ts), "2022-06-30T23:31:59.999-00:30");
+
+        assert_eq!(TimeFormat::Seconds.file_ts(ts), "1656633719");
+        assert_eq!(ISO_UTC.file_ts(ts), "20220701T000159Z");
+        assert_eq!(plus_two.file_ts(ts), "20220701T020159+0200");
+
+        // the date cell shows the time in the timezone
+        assert_eq!(plus_two.cell(0), Cell::Date(2 * 60 * MINUTE));
+        assert_eq!(TimeFormat::Seconds.cell(1500), Cell::Number(1.5));
+    }
+
+    #[test]
+    fn test_iso_times_in_app_timezone() {
+        let timezone = Timezone::default();
+        let options = ExportOptions::new(timezone.clone());
+        options.set_times(TimeFormat::Iso { utc_offset: 0 });
+
+        timezone.set_utc_offset(330);
+        assert_eq!(options.times(), TimeFormat::Iso { utc_offset: 330 });
+        options.set_times(TimeFormat::Millis);
+        assert_eq!(options.times(), TimeFormat::Millis);
+    }
+
+    #[test]
+    fn test_csv_times_in_format() {
+        let klines = mock::klines(1_656_633_600_000, MINUTE, 2);
+        let path = std::env::temp_dir().join("netstrat_csv_times.csv");
+
+        let times = TimeFormat::Iso { utc_offset: 60 };
+        to_csv_with_indicators(&path, &klines, Columns::All, times, &[]).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let mut lines = content.lines();
+        assert_eq!(
+            lines.next(),
+            include_str!("../../tests/fixtures/klines.csv")
+                .lines()
+                .next()
+        );
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("2022-07-01T01:00:00.000+01:00,"));
+        assert!(lines
+            .next()
+            .unwrap()
+            .contains(",2022-07-01T01:01:59.999+01:00,"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_json_rows_match_csv_columns() {
+        let klines = mock::klines(1_656_633_600_000, MINUTE, 2);
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&klines).unwrap()).unwrap();
+
+        let rows = json.as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1]["t_open"], "2022-07-01T00:01:00.000Z");
+        assert_eq!(rows[1]["t_close"], "2022-07-01T00:01:59.999Z");
+        assert_eq!(rows[1]["close"], klines[1].close as f64);
+        let header = include_str!("../../tests/fixtures/klines.csv")
+            .lines()
+            .next()
+            .unwrap();
+        assert_eq!(
+            header.split(',').collect::<Vec<_>>(),
+            rows[1]
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(to_json(&[]).unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_estimate() {
+        let day = props(0, 24 * 60 * MINUTE, 1000);
+
+        let estimate = estimate(&day, Format::Csv(Columns::Basic));
+
+        assert_eq!(estimate.rows, 1440);
+        assert_eq!(estimate.pages, 2);
+        assert_eq!(estimate.bytes, 1440 * BASIC_ROW_BYTES);
+        let limits = ConfirmLimits::default();
+        assert!(!limits.exceeded(&estimate));
+        limits.clone().set(1, 100_000);
+        assert!(limits.exceeded(&estimate));
+
+        let xlsx = Format::Xlsx {
+            columns: Columns::All,
+            stats: false,
+        };
+        assert_eq!(super::estimate(&day, xlsx).bytes, 1440 * XLSX_ALL_ROW_BYTES);
+
+        let lines = super::estimate(&day, Format::Influx);
+        assert_eq!(lines.bytes, 1440 * LINE_BYTES);
+    }
+
+    #[test]
+    fn test_row_bytes() {
+        let dir = std::env::temp_dir().join("netstrat_row_bytes");
+        std::fs::create_dir_all(&dir).unwrap();
+        let klines = mock::klines(1_656_633_600_000, MINUTE, 100);
+
+        [
+            (Columns::Basic, BASIC_ROW_BYTES),
+            (Columns::All, ALL_ROW_BYTES),
+        ]
+        .into_iter()
+        .for_each(|(columns, expected)| {
+            let path = to_csv(&dir.join("rows.csv"), &klines, columns).unwrap();
+            let per_row = std::fs::metadata(path).unwrap().len() as usize / klines.len();
+            assert!(per_row.abs_diff(expected) < expected / 3, "{per_row}");
+        });
+
+        // compressed sizes of prices that vary like real ones do over enough rows to
+        // outweigh the parts every workbook has
+        let mut varied = mock::klines(1_656_633_600_000, MINUTE, 2_000);
+        varied.iter_mut().enumerate().for_each(|(i, k)| {
+            let jitter = |n: usize| ((i * 7_919 + n * 104_729) % 10_007) as f32 / 10_007.0;
+            k.open = 20_000.0 + jitter(1) * 100.0;
+            k.high = k.open + jitter(2) * 10.0;
+            k.low = k.open - jitter(3) * 10.0;
+            k.close = k.open + jitter(4) * 5.0;
+            k.volume = jitter(5) * 50.0;
+            k.quote_asset_volume = Some(jitter(6) * 1e6);
+            k.number_of_trades = Some((jitter(7) * 1_000.0) as i64);
+            k.taker_buy_base_asset_volume = Some(jitter(8) * 20.0);
+            k.taker_buy_quote_asset_volume = Some(jitter(9) * 4e5);
+        });
+        [
+            (Columns::Basic, XLSX_BASIC_ROW_BYTES),
+            (Columns::All, XLSX_ALL_ROW_BYTES),
+        ]
+        .into_iter()
+        .for_each(|(columns, expected)| {
+            let sheet = XlsxSheet {
+                symbol: "BTCUSDT",
+                klines: &varied,
+                indicators: &[],
+            };
+            let path = to_xlsx(&dir.join("rows.xlsx"), &[sheet], columns, ISO_UTC, false).unwrap();
+            let per_row = std::fs::metadata(path).unwrap().len() as usize / varied.len();
+            assert!(per_row.abs_diff(expected) < expected / 3, "{per_row}");
+        });
+
+        let path = crate::netstrat::influx::to_file(
+            &dir.join("rows.lp"),
+            "BTCUSDT",
+            Interval::Minute,
+            &klines,
+        )
+        .unwrap()
+        .path;
+        let per_line = std::fs::metadata(path).unwrap().len() as usize / klines.len();
+        assert!(per_line.abs_diff(LINE_BYTES) < LINE_BYTES / 3, "{per_line}");
+    }
+
+    #[test]
+    fn test_export_to_missing_dir() {
+        let path = std::env::temp_dir()
+            .join("netstrat_missing_dir")
+            .join("out.csv");
+        let err = to_csv(&path, &[], Columns::All).unwrap_err();
+
+        assert!(matches!(err, crate::errors::Error::Io(p, _) if p == path));
+    }
+}
@@ -0,0 +1,442 @@
+//! Expressions over candle series, a lightweight alternative to scripts.
+//!
+//! Supported are the series `open`, `high`, `low`, `close` and `volume`, numbers,
+//! `+ - * /`, parentheses and the functions `sma(x, n)`, `ema(x, n)` and `abs(x)`,
+//! e.g. `(close - sma(close, 20)) / sma(close, 20)`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{Error, Result};
+
+use super::{
+    data::Data,
+    indicators::{math, IndicatorOutput, Series, SubPanel},
+};
+
+const SERIES: [&str; 5] = ["open", "high", "low", "close", "volume"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Func {
+    Sma,
+    Ema,
+    Abs,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Num(f64),
+    Series(&'static str),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(Func, Vec<Expr>),
+}
+
+/// Parses the source reporting the char position of the offending token on failure.
+pub fn parse(src: &str) -> Result<Expr> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        idx: 0,
+        end: src.chars().count(),
+    };
+    let expr = parser.expr()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some((pos, token)) => Err(Error::Expression(*pos, format!("unexpected {token:?}"))),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(char),
+}
+
+fn tokenize(src: &str) -> Result<Vec<(usize, Token)>> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let num = text
+                .parse()
+                .map_err(|_| Error::Expression(start, format!("invalid number {text}")))?;
+            tokens.push((start, Token::Num(num)));
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((start, Token::Ident(chars[start..i].iter().collect())));
+        } else if "+-*/(),".contains(c) {
+            i += 1;
+            tokens.push((start, Token::Op(c)));
+        } else {
+            return Err(Error::Expression(start, format!("unexpected {c:?}")));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    idx: usize,
+    /// Position reported for errors at the end of the source.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.idx)
+    }
+
+    fn next(&mut self) -> Result<(usize, Token)> {
+        let token = self
+            .tokens
+            .get(self.idx)
+            .cloned()
+            .ok_or_else(|| Error::Expression(self.end, "unexpected end".to_string()))?;
+        self.idx += 1;
+
+        Ok(token)
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        let found = matches!(self.peek(), Some((_, Token::Op(c))) if *c == op);
+        if found {
+            self.idx += 1;
+        }
+
+        found
+    }
+
+    fn expect(&mut self, op: char) -> Result<()> {
+        match self.next()? {
+            (_, Token::Op(c)) if c == op => Ok(()),
+            (pos, token) => Err(Error::Expression(
+                pos,
+                format!("expected '{op}', found {token:?}"),
+            )),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Some((_, Token::Op(c))) if *c == '+' || *c == '-' => *c,
+                _ => return Ok(lhs),
+            };
+            self.idx += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some((_, Token::Op(c))) if *c == '*' || *c == '/' => *c,
+                _ => return Ok(lhs),
+            };
+            self.idx += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        match self.eat('-') {
+            true => Ok(Expr::Neg(Box::new(self.unary()?))),
+            false => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        match self.next()? {
+            (_, Token::Num(n)) => Ok(Expr::Num(n)),
+            (_, Token::Op('(')) => {
+                let expr = self.expr()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            (pos, Token::Ident(name)) if self.eat('(') => self.call(pos, &name),
+            (pos, Token::Ident(name)) => SERIES
+                .iter()
+                .find(|s| **s == name)
+                .map(|s| Expr::Series(s))
+                .ok_or_else(|| Error::Expression(pos, format!("unknown series {name}"))),
+            (pos, token) => Err(Error::Expression(pos, format!("unexpected {token:?}"))),
+        }
+    }
+
+    /// Arguments of the function `name` after its opening parenthesis.
+    fn call(&mut self, pos: usize, name: &str) -> Result<Expr> {
+        let func = match name {
+            "sma" => Func::Sma,
+            "ema" => Func::Ema,
+            "abs" => Func::Abs,
+            _ => return Err(Error::Expression(pos, format!("unknown function {name}"))),
+        };
+
+        let mut args = vec![self.expr()?];
+        while self.eat(',') {
+            let arg_pos = self.peek().map_or(self.end, |(p, _)| *p);
+            args.push(self.expr()?);
+            if func != Func::Abs
+                && !matches!(args.last(), Some(Expr::Num(n)) if *n >= 1.0 && n.fract() == 0.0)
+            {
+                return Err(Error::Expression(
+                    arg_pos,
+                    format!("{name} period should be a positive integer"),
+                ));
+            }
+        }
+        self.expect(')')?;
+
+        let arity = match func {
+            Func::Abs => 1,
+            Func::Sma | Func::Ema => 2,
+        };
+        match args.len() == arity {
+            true => Ok(Expr::Call(func, args)),
+            false => Err(Error::Expression(
+                pos,
+                format!("{name} takes {arity} arguments, got {}", args.len()),
+            )),
+        }
+    }
+}
+
+enum Value {
+    Scalar(f64),
+    Series(Vec<Option<f64>>),
+}
+
+impl Expr {
+    /// Values aligned with the data candles, `None` for not yet available or not finite ones.
+    pub fn eval(&self, data: &Data) -> Vec<Option<f64>> {
+        match self.value(data) {
+            Value::Scalar(v) => vec![Some(v).filter(|v| v.is_finite()); data.vals.len()],
+            Value::Series(vals) => vals
+                .into_iter()
+                .map(|v| v.filter(|v| v.is_finite()))
+                .collect(),
+        }
+    }
+
+    fn value(&self, data: &Data) -> Value {
+        match self {
+            Expr::Num(n) => Value::Scalar(*n),
+            Expr::Series(name) => Value::Series(
+                data.vals
+                    .iter()
+                    .map(|k| {
+                        Some(match *name {
+                            "open" => k.open,
+                            "high" => k.high,
+                            "low" => k.low,
+                            "volume" => k.volume,
+                            _ => k.close,
+                        } as f64)
+                    })
+                    .collect(),
+            ),
+            Expr::Neg(e) => map(e.value(data), |v| -v),
+            Expr::Binary(op, lhs, rhs) => {
+                let f = |a: f64, b: f64| match op {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    _ => a / b,
+                };
+                match (lhs.value(data), rhs.value(data)) {
+                    (Value::Scalar(a), Value::Scalar(b)) => Value::Scalar(f(a, b)),
+                    (Value::Scalar(a), Value::Series(b)) => {
+                        Value::Series(b.iter().map(|b| Some(f(a, (*b)?))).collect())
+                    }
+                    (Value::Series(a), Value::Scalar(b)) => {
+                        Value::Series(a.iter().map(|a| Some(f((*a)?, b))).collect())
+                    }
+                    (Value::Series(a), Value::Series(b)) => Value::Series(
+                        a.iter()
+                            .zip(&b)
+                            .map(|(a, b)| Some(f((*a)?, (*b)?)))
+                            .collect(),
+                    ),
+                }
+            }
+            Expr::Call(Func::Abs, args) => map(args[0].value(data), f64::abs),
+            Expr::Call(func, args) => {
+                let n = match args[1] {
+                    Expr::Num(n) => n as usize,
+                    _ => 0,
+                };
+                let vals = args[0].eval(data);
+                Value::Series(math::after_warm_up(&vals, |v| match func {
+                    Func::Sma => math::sma(v, n),
+                    _ => math::ema(v, n),
+                }))
+            }
+        }
+    }
+}
+
+fn map(value: Value, f: impl Fn(f64) -> f64) -> Value {
+    match value {
+        Value::Scalar(v) => Value::Scalar(f(v)),
+        Value::Series(vals) => Value::Series(vals.iter().map(|v| Some(f((*v)?))).collect()),
+    }
+}
+
+/// Where the expression is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Target {
+    #[default]
+    Overlay,
+    SubPanel,
+}
+
+/// Expression saved by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Definition {
+    pub name: String,
+    pub source: String,
+    /// Line color as rgb.
+    pub color: [u8; 3],
+    pub target: Target,
+    pub enabled: bool,
+}
+
+impl Default for Definition {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            source: "sma(close, 20)".to_string(),
+            color: [255, 165, 0],
+            target: Default::default(),
+            enabled: false,
+        }
+    }
+}
+
+impl Definition {
+    pub fn compute(&self, data: &Data) -> Result<IndicatorOutput> {
+        let vals = parse(&self.source)?.eval(data);
+        let series = Series {
+            color: Some(self.color),
+            ..Series::from_values(&self.name, data, &vals)
+        };
+
+        Ok(match self.target {
+            Target::Overlay => IndicatorOutput {
+                overlay: vec![series.clone()],
+                values: vec![series],
+                ..Default::default()
+            },
+            Target::SubPanel => IndicatorOutput {
+                sub_panels: vec![SubPanel {
+                    name: self.name.clone(),
+                    series: vec![series.clone()],
+                    ..Default::default()
+                }],
+                values: vec![series],
+                ..Default::default()
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod expression_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    fn data() -> Data {
+        Data::new(
+            [1.0, 2.0, 3.0, 4.0]
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: i as i64 * 10 + 9,
+                    open: *c - 1.0,
+                    close: *c,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn eval(src: &str) -> Vec<Option<f64>> {
+        parse(src).unwrap().eval(&data())
+    }
+
+    fn error_pos(src: &str) -> usize {
+        match parse(src) {
+            Err(Error::Expression(pos, _)) => pos,
+            res => panic!("expected error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_precedence() {
+        assert_eq!(
+            eval("-close + 2 * (open - 1) / 2"),
+            vec![Some(-2.0), Some(-2.0), Some(-2.0), Some(-2.0)]
+        );
+        assert_eq!(eval("abs(open - 2)")[0], Some(2.0));
+        assert_eq!(eval("1 / (close - 1)")[0], None);
+    }
+
+    #[test]
+    fn test_nested_functions() {
+        assert_eq!(
+            eval("sma(close, 2)"),
+            vec![None, Some(1.5), Some(2.5), Some(3.5)]
+        );
+        assert_eq!(
+            eval("sma(sma(close, 2), 2)"),
+            vec![None, None, Some(2.0), Some(3.0)]
+        );
+    }
+
+    #[test]
+    fn test_error_positions() {
+        assert_eq!(error_pos("close +"), 7);
+        assert_eq!(error_pos("close + price"), 8);
+        assert_eq!(error_pos("sma(close, 2.5)"), 11);
+        assert_eq!(error_pos("abs(close, 2)"), 0);
+        assert_eq!(error_pos("(close"), 6);
+        assert_eq!(error_pos("close # 2"), 6);
+        assert_eq!(error_pos("close 2"), 6);
+    }
+
+    #[test]
+    fn test_targets() {
+        let def = Definition {
+            name: "spread".to_string(),
+            source: "close - open".to_string(),
+            target: Target::SubPanel,
+            ..Default::default()
+        };
+
+        let out = def.compute(&data()).unwrap();
+
+        assert!(out.overlay.is_empty());
+        assert_eq!(out.sub_panels[0].series[0].points.len(), 4);
+        assert_eq!(out.sub_panels[0].series[0].color, Some(def.color));
+    }
+}
@@ -0,0 +1,471 @@
+//! Candles read back from csv files, e.g. ones written by an export.
+//!
+//! The delimiter, header and timestamp format are detected: rows are split on the delimiter
+//! most common in the first line, a first row starting with a timestamp is data and columns
+//! are then taken in the order of the export, timestamps are epoch millis or seconds or ISO
+//! dates.
+
+use std::io::Read;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDateTime};
+use quick_error::ResultExt;
+use tracing::warn;
+
+use crate::errors::{Error, Result};
+use crate::sources::binance::{Interval, Kline};
+
+use super::{
+    events,
+    manifest::{self, Manifest},
+};
+
+const DELIMITERS: [u8; 3] = [b',', b';', b'\t'];
+/// Columns in the order of an export with all columns, a file without a header is read in it.
+const COLUMNS: [&str; 12] = [
+    "t_open",
+    "open",
+    "high",
+    "low",
+    "close",
+    "volume",
+    "t_close",
+    "quote_asset_volume",
+    "number_of_trades",
+    "taker_buy_base_asset_volume",
+    "taker_buy_quote_asset_volume",
+    "closed",
+];
+/// Columns of an export with the basic columns, in their order.
+const BASIC_COLUMNS: [&str; 8] = [
+    "t_open", "open", "high", "low", "close", "volume", "t_close", "closed",
+];
+/// Columns without which a row isn't a candle.
+const REQUIRED: [&str; 6] = ["t_open", "open", "high", "low", "close", "volume"];
+/// Epoch timestamps below it are in seconds, it is in 1973 in millis and 5138 in seconds.
+const MAX_SECS: i64 = 100_000_000_000;
+/// Date of an ISO time in a file name, followed by `Z` or the UTC offset.
+const FILE_DATE: &str = "%Y%m%dT%H%M%S";
+
+/// Candles read from a file with the rows which couldn't be read.
+#[derive(Debug, Default)]
+pub struct Import {
+    /// Sorted by open time, the last of rows with the same open time is kept.
+    pub klines: Vec<Kline>,
+    /// Messages about the bad rows.
+    pub errors: Vec<String>,
+    /// Symbol and interval of an export file name.
+    pub symbol: Option<String>,
+    pub interval: Option<Interval>,
+    /// Manifest written next to the file by an export.
+    pub manifest: Option<Manifest>,
+    /// Hex SHA-256 of the file.
+    pub sha256: String,
+}
+
+impl Import {
+    /// Interval of the file name or the smallest gap between the candles if it is a known one.
+    pub fn interval(&self) -> Option<Interval> {
+        self.interval.or_else(|| {
+            let gap = self
+                .klines
+                .windows(2)
+                .map(|w| w[1].t_open - w[0].t_open)
+                .min()?;
+            Interval::ALL.into_iter().find(|i| i.millis() == gap)
+        })
+    }
+
+    /// Whether the file is the one the manifest was written for, none without a manifest.
+    pub fn checksum_matches(&self) -> Option<bool> {
+        self.manifest.as_ref().map(|m| m.sha256 == self.sha256)
+    }
+}
+
+/// Reads candles of a csv file, rows which fail to parse are skipped and reported.
+pub fn from_csv(path: &Path) -> Result<Import> {
+    let f = std::fs::File::open(path).context(path)?;
+    let mut import = from_reader(f, path)?;
+    if let Some((symbol, interval)) = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .and_then(parse_file_name)
+    {
+        import.symbol = Some(symbol);
+        import.interval = Some(interval);
+    }
+    match Manifest::read(path) {
+        Ok(manifest) => import.manifest = manifest,
+        Err(err) => warn!("failed to read manifest: {}", err.chain()),
+    }
+
+    Ok(import)
+}
+
+/// Symbol and interval of an export file name, see [`super::export::file_name`].
+pub fn parse_file_name(name: &str) -> Option<(String, Interval)> {
+    let stem = name.strip_suffix(".csv")?;
+    let mut parts = stem.rsplitn(4, '_');
+    let interval = parts.next()?;
+    let end = parts.next()?;
+    let start = parts.next()?;
+    let symbol = parts.next()?;
+    if symbol.is_empty() || parse_file_ts(start).is_none() || parse_file_ts(end).is_none() {
+        return None;
+    }
+
+    Interval::ALL
+        .into_iter()
+        .find(|i| format!("{i:?}") == interval)
+        .map(|i| (symbol.to_string(), i))
+}
+
+/// Epoch time or compact ISO date of an export file name, see
+/// [`super::export::TimeFormat::file_ts`].
+fn parse_file_ts(s: &str) -> Option<i64> {
+    if let Ok(ts) = s.parse::<i64>() {
+        return Some(ts);
+    }
+    match s.strip_suffix('Z') {
+        Some(utc) => NaiveDateTime::parse_from_str(utc, FILE_DATE)
+            .ok()
+            .map(|dt| dt.and_utc().timestamp_millis()),
+        None => DateTime::parse_from_str(s, &format!("{FILE_DATE}%z"))
+            .ok()
+            .map(|dt| dt.timestamp_millis()),
+    }
+}
+
+fn from_reader(mut rdr: impl Read, path: &Path) -> Result<Import> {
+    let mut content = String::new();
+    rdr.read_to_string(&mut content).context(path)?;
+
+    let first_line = content.lines().next().unwrap_or_default();
+    let delimiter = DELIMITERS
+        .into_iter()
+        .max_by_key(|d| first_line.bytes().filter(|b| b == d).count())
+        .unwrap_or(b',');
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let mut records = rdr.records().enumerate().peekable();
+
+    let header = match records.peek() {
+        Some((_, Ok(first))) if first.get(0).and_then(parse_ts).is_none() => {
+            let names: Vec<String> = first.iter().map(|n| n.trim().to_lowercase()).collect();
+            records.next();
+            names
+        }
+        Some((_, Ok(first))) if first.len() <= BASIC_COLUMNS.len() => {
+            BASIC_COLUMNS.map(String::from).to_vec()
+        }
+        _ => COLUMNS.map(String::from).to_vec(),
+    };
+    let column = |name: &str| header.iter().position(|n| n == name);
+    if let Some(missing) = REQUIRED.iter().find(|c| column(c).is_none()) {
+        return Err(Error::Validation(format!(
+            "{} has no {missing} column",
+            path.display()
+        )));
+    }
+    let columns: Vec<_> = COLUMNS.iter().map(|c| column(c)).collect();
+
+    let mut import = Import {
+        sha256: manifest::sha256(content.as_bytes()),
+        ..Default::default()
+    };
+    for (idx, row) in records {
+        let line = idx + 1;
+        let row = match row {
+            Ok(row) => row,
+            Err(err) if err.is_io_error() => Err(err).context(path)?,
+            Err(err) => {
+                import.errors.push(format!("line {line}: {err}"));
+                continue;
+            }
+        };
+        match parse_row(&row, &columns) {
+            Ok(k) => import.klines.push(k),
+            Err(err) => import.errors.push(format!("line {line}: {err}")),
+        }
+    }
+
+    import.klines.sort_by_key(|k| k.t_open);
+    import.klines.reverse();
+    import.klines.dedup_by_key(|k| k.t_open);
+    import.klines.reverse();
+
+    Ok(import)
+}
+
+/// Epoch millis or seconds, with the millis as decimals or not, or an ISO date.
+pub fn parse_ts(s: &str) -> Option<i64> {
+    let s = s.trim();
+    match s.parse::<i64>() {
+        Ok(secs) if secs.abs() < MAX_SECS => secs.checked_mul(1000),
+        Ok(_) => events::parse_ts(s),
+        Err(_) => parse_decimal_secs(s).or_else(|| events::parse_ts(s)),
+    }
+}
+
+/// Epoch seconds with up to three decimals, e.g. `1656633719.999`.
+fn parse_decimal_secs(s: &str) -> Option<i64> {
+    let (secs, decimals) = s.split_once('.')?;
+    if decimals.is_empty() || decimals.len() > 3 || !decimals.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let millis = format!("{decimals:0<3}").parse::<i64>().ok()?;
+    let whole = secs.parse::<i64>().ok()?.checked_mul(1000)?;
+
+    Some(match secs.starts_with('-') {
+        true => whole - millis,
+        false => whole + millis,
+    })
+}
+
+/// Candle of a row with the fields at the positions of [`COLUMNS`].
+fn parse_row(
+    row: &csv::StringRecord,
+    columns: &[Option<usize>],
+) -> std::result::Result<Kline, String> {
+    let field = |idx: usize| {
+        columns[idx]
+            .and_then(|c| row.get(c))
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+    };
+    let ts = |idx: usize| match field(idx) {
+        Some(f) => parse_ts(f)
+            .map(Some)
+            .ok_or(format!("invalid timestamp {f:?}")),
+        None => Ok(None),
+    };
+    let number = |idx: usize| match field(idx) {
+        Some(f) => f
+            .parse::<f32>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .map(Some)
+            .ok_or(format!("invalid {} {f:?}", COLUMNS[idx])),
+        None => Ok(None),
+    };
+    let required = |idx: usize| number(idx)?.ok_or(format!("no {}", COLUMNS[idx]));
+
+    let t_open = ts(0)?.ok_or("no t_open")?;
+    Ok(Kline {
+        t_open,
+        open: required(1)?,
+        high: required(2)?,
+        low: required(3)?,
+        close: required(4)?,
+        volume: required(5)?,
+        t_close: ts(6)?.unwrap_or(t_open),
+        quote_asset_volume: number(7)?,
+        number_of_trades: match field(8) {
+            Some(f) => Some(
+                f.parse()
+                    .map_err(|_| format!("invalid number_of_trades {f:?}"))?,
+            ),
+            None => None,
+        },
+        taker_buy_base_asset_volume: number(9)?,
+        taker_buy_quote_asset_volume: number(10)?,
+        closed: match field(11) {
+            Some(f) => f.parse().map_err(|_| format!("invalid closed {f:?}"))?,
+            None => true,
+        },
+    })
+}
+
+#[cfg(test)]
+mod import_tests {
+    use crate::{
+        netstrat::{
+            export::{self, Columns, TimeFormat},
+            props::Props,
+        },
+        sources::binance::mock,
+    };
+
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+
+    fn read(csv: &str) -> Import {
+        from_reader(csv.as_bytes(), Path::new("klines.csv")).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let klines = mock::klines(1_656_633_600_000, MINUTE, 5);
+        let dir = std::env::temp_dir().join("netstrat_import");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let times = [
+            TimeFormat::Millis,
+            TimeFormat::Seconds,
+            TimeFormat::Iso { utc_offset: 0 },
+            TimeFormat::Iso { utc_offset: 330 },
+            TimeFormat::Iso { utc_offset: -480 },
+        ];
+        times.into_iter().for_each(|times| {
+            [Columns::All, Columns::Basic]
+                .into_iter()
+                .for_each(|columns| {
+                    let name = export::file_name("BTCUSDT", &Props::default(), times);
+                    let path = export::to_csv_with_indicators(
+                        &dir.join(name),
+                        &klines,
+                        columns,
+                        times,
+                        &[],
+                    )
+                    .unwrap()
+                    .path;
+
+                    let import = from_csv(&path).unwrap();
+
+                    assert!(import.errors.is_empty(), "{times:?}");
+                    assert_eq!(import.symbol.as_deref(), Some("BTCUSDT"), "{times:?}");
+                    assert_eq!(import.interval, Some(Props::default().interval));
+                    assert_eq!(import.klines.len(), klines.len());
+                    assert_eq!(import.klines[4].close, klines[4].close);
+                    assert_eq!(import.klines[4].t_close, klines[4].t_close, "{times:?}");
+                    match columns {
+                        Columns::All => assert_eq!(import.klines, klines),
+                        Columns::Basic => assert_eq!(import.klines[4].number_of_trades, None),
+                    }
+                    std::fs::remove_file(path).unwrap();
+                });
+        });
+    }
+
+    #[test]
+    fn test_manifest_read_with_file() {
+        let klines = mock::klines(0, MINUTE, 3);
+        let dir = std::env::temp_dir().join("netstrat_import_manifest");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("BTCUSDT_0_180000_Minute.csv");
+        let written =
+            export::to_csv_with_indicators(&path, &klines, Columns::All, TimeFormat::Millis, &[])
+                .unwrap();
+        let manifest = Manifest::new(
+            "BTCUSDT",
+            Interval::Minute,
+            &Props::default(),
+            &klines,
+            manifest::Source::import("a.csv"),
+        )
+        .of(&written);
+        let manifest_path = manifest.write(&path).unwrap();
+
+        let import = from_csv(&path).unwrap();
+        assert_eq!(import.manifest, Some(manifest));
+        assert_eq!(import.checksum_matches(), Some(true));
+
+        // a changed file no longer matches
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, content.replace(",true", ",false")).unwrap();
+        assert_eq!(from_csv(&path).unwrap().checksum_matches(), Some(false));
+
+        std::fs::remove_file(manifest_path).unwrap();
+        assert_eq!(from_csv(&path).unwrap().checksum_matches(), None);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_decimal_seconds() {
+        assert_eq!(parse_ts("1656633719.999"), Some(1_656_633_719_999));
+        assert_eq!(parse_ts("1656633719.5"), Some(1_656_633_719_500));
+        assert_eq!(parse_ts("-0.001"), Some(-1));
+        assert_eq!(parse_ts("1.2345"), None);
+        assert_eq!(parse_ts("1."), None);
+    }
+
+    #[test]
+    fn test_detected_format() {
+        let semicolons = read(
+            "2022-07-01 00:00:00;1.0;2.0;0.5;1.5;10.0;2022-07-01 00:00:59\n\
+             1656633660;1.5;2.5;1.0;2.0;11.0;1656633719\n",
+        );
+        assert!(semicolons.errors.is_empty());
+        assert_eq!(
+            semicolons
+                .klines
+                .iter()
+                .map(|k| k.t_open)
+                .collect::<Vec<_>>(),
+            vec![1_656_633_600_000, 1_656_633_660_000]
+        );
+        assert_eq!(semicolons.klines[1].t_close, 1_656_633_719_000);
+        assert_eq!(semicolons.interval(), Some(Interval::Minute));
+
+        let tabs = read("close\tt_open\topen\thigh\tlow\tvolume\n1.5\t0\t1.0\t2.0\t0.5\t10.0\n");
+        assert!(tabs.errors.is_empty());
+        assert_eq!(tabs.klines[0].close, 1.5);
+        assert_eq!(tabs.klines[0].volume, 10.0);
+        assert!(tabs.klines[0].closed);
+    }
+
+    #[test]
+    fn test_bad_rows_are_counted() {
+        let import = read(
+            "t_open,open,high,low,close,volume,t_close\n\
+             0,1.0,2.0,0.5,1.5,10.0,59999\n\
+             soon,1.0,2.0,0.5,1.5,10.0,59999\n\
+             60000,1.0,2.0,0.5,NaN,10.0,119999\n\
+             120000,1.0,2.0\n\
+             60000,1.0,2.0,0.5,1.5,10.0,119999\n",
+        );
+
+        assert_eq!(import.klines.len(), 2);
+        assert_eq!(
+            import.errors,
+            vec![
+                "line 3: invalid timestamp \"soon\"",
+                "line 4: invalid close \"NaN\"",
+                "line 5: no low",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicates_keep_last() {
+        let import = read(
+            "t_open,open,high,low,close,volume,t_close\n\
+             60000,1.0,2.0,0.5,1.5,10.0,119999\n\
+             0,1.0,2.0,0.5,1.5,10.0,59999\n\
+             60000,1.0,2.0,0.5,1.7,10.0,119999\n",
+        );
+
+        assert_eq!(import.klines.len(), 2);
+        assert_eq!(import.klines[1].close, 1.7);
+    }
+
+    #[test]
+    fn test_missing_column() {
+        let err = from_reader("t_open,close\n0,1.0\n".as_bytes(), Path::new("a.csv")).unwrap_err();
+
+        assert_eq!(err.user_message(), "a.csv has no open column");
+    }
+
+    #[test]
+    fn test_parse_file_name() {
+        assert_eq!(
+            parse_file_name("BTC_USDT_0_360_FiveMinutes.csv"),
+            Some(("BTC_USDT".to_string(), Interval::FiveMinutes))
+        );
+        assert_eq!(
+            parse_file_name("BTCUSDT_20220701T000000Z_20220701T053000+0530_Minute.csv"),
+            Some(("BTCUSDT".to_string(), Interval::Minute))
+        );
+        assert_eq!(
+            parse_file_name("BTCUSDT_20220701T000000_20220701T000000Z_Minute.csv"),
+            None
+        );
+        assert_eq!(parse_file_name("BTCUSDT_0_360_Fortnight.csv"), None);
+        assert_eq!(parse_file_name("prices.csv"), None);
+    }
+}
@@ -0,0 +1,263 @@
+//! Volumes far above the ones before them.
+
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{Glyph, Indicator, IndicatorOutput, Series, Tone};
+
+/// Gold fill of the highlighted volume bars.
+const COLOR: [u8; 3] = [255, 215, 0];
+
+/// Volumes above `mean + k·std` of the `window` volumes before them, highlighted on the
+/// volume pane and marked over their candles.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolumeAnomalies {
+    pub window: usize,
+    pub k: f64,
+    /// Whether the quote volume is checked instead of the base one.
+    pub quote: bool,
+    /// Flags of the last computation, candles appended since only compute theirs.
+    #[serde(skip)]
+    flags: RefCell<AnomalyFlags>,
+}
+
+impl Default for VolumeAnomalies {
+    fn default() -> Self {
+        let flags = AnomalyFlags::default();
+        Self {
+            window: flags.window(),
+            k: flags.k(),
+            quote: false,
+            flags: RefCell::new(flags),
+        }
+    }
+}
+
+impl Indicator for VolumeAnomalies {
+    fn id(&self) -> &'static str {
+        "volume_anomalies"
+    }
+
+    fn name(&self) -> String {
+        format!("Volume anomalies({}, {})", self.window, self.k)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = ui
+            .add(
+                egui::DragValue::new(&mut self.window)
+                    .clamp_range(2..=1000)
+                    .prefix("window: "),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut self.k)
+                    .speed(0.1)
+                    .clamp_range(0.1..=10.0)
+                    .prefix("k: "),
+            )
+            .on_hover_text("volumes above mean + k·std of the ones before them")
+            .changed();
+        changed |= ui.checkbox(&mut self.quote, "quote volume").changed();
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let volumes: Vec<f64> = data
+            .vals
+            .iter()
+            .map(|k| match self.quote {
+                true => k.quote_asset_volume.unwrap_or_default() as f64,
+                false => k.volume as f64,
+            })
+            .collect();
+        let mut flags = self.flags.borrow_mut();
+        flags.set_params(self.window, self.k);
+        let flagged: Vec<_> = data
+            .vals
+            .iter()
+            .zip(&volumes)
+            .zip(flags.update(&volumes))
+            .filter(|(_, flagged)| **flagged)
+            .map(|((k, v), _)| (data.center(k), *v, k.high as f64))
+            .collect();
+
+        IndicatorOutput {
+            volume_highlights: vec![Series {
+                name: "anomalies".to_string(),
+                points: flagged.iter().map(|(x, v, _)| [*x, *v]).collect(),
+                color: Some(COLOR),
+            }],
+            glyphs: flagged
+                .iter()
+                .map(|(x, _, high)| Glyph {
+                    name: "volume anomaly".to_string(),
+                    point: [*x, *high],
+                    tone: Tone::Neutral,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Flags volumes exceeding `mean + k·std` of the `window` volumes before them, the first
+/// `window` ones are never flagged. Kept across updates so appended or refreshed candles
+/// only recompute the flags from the first volume that changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnomalyFlags {
+    window: usize,
+    k: f64,
+    volumes: Vec<f64>,
+    flags: Vec<bool>,
+}
+
+impl Default for AnomalyFlags {
+    fn default() -> Self {
+        Self::new(20, 3.0)
+    }
+}
+
+impl AnomalyFlags {
+    pub fn new(window: usize, k: f64) -> Self {
+        Self {
+            window,
+            k,
+            volumes: vec![],
+            flags: vec![],
+        }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn k(&self) -> f64 {
+        self.k
+    }
+
+    /// Changes the parameters, the flags are recomputed on the next update.
+    pub fn set_params(&mut self, window: usize, k: f64) {
+        if (window, k) != (self.window, self.k) {
+            self.window = window;
+            self.k = k;
+            self.volumes.clear();
+            self.flags.clear();
+        }
+    }
+
+    /// Flags of the volumes after the same prefix is kept and the rest recomputed.
+    pub fn update(&mut self, volumes: &[f64]) -> &[bool] {
+        let same = self
+            .volumes
+            .iter()
+            .zip(volumes)
+            .take_while(|(a, b)| a == b)
+            .count();
+        self.volumes.truncate(same);
+        self.flags.truncate(same);
+        self.volumes.extend_from_slice(&volumes[same..]);
+        self.flags.extend((same..volumes.len()).map(|i| {
+            i.checked_sub(self.window)
+                .filter(|_| self.window >= 2)
+                .is_some_and(|from| exceeds(&volumes[from..i], volumes[i], self.k))
+        }));
+
+        &self.flags
+    }
+
+    /// Flags of the volumes set on the last update.
+    pub fn flags(&self) -> &[bool] {
+        &self.flags
+    }
+}
+
+/// Whether `v` is more than `k` sample standard deviations above the mean of `window`.
+fn exceeds(window: &[f64], v: f64, k: f64) -> bool {
+    let n = window.len() as f64;
+    let mean = window.iter().sum::<f64>() / n;
+    let std = (window.iter().map(|w| (w - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+
+    v > mean + k * std
+}
+
+#[cfg(test)]
+mod anomalies_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_flags_beyond_threshold() {
+        // mean 10 and sample std 2 over the window of three
+        let volumes = [8.0, 10.0, 12.0, 15.0, 10.0, 12.0, 40.0];
+        let mut anomalies = AnomalyFlags::new(3, 2.0);
+
+        let flags = anomalies.update(&volumes).to_vec();
+
+        // 15 is above 10 + 2·2, 40 above everything before it
+        assert_eq!(flags, vec![false, false, false, true, false, false, true]);
+
+        anomalies.set_params(3, 3.0);
+        assert!(anomalies.flags().is_empty());
+        assert!(!anomalies.update(&volumes)[3]);
+    }
+
+    #[test]
+    fn test_recomputed_from_changed_volume() {
+        let mut anomalies = AnomalyFlags::new(2, 1.0);
+        anomalies.update(&[10.0, 12.0, 11.0]);
+        assert_eq!(anomalies.flags(), [false, false, false]);
+
+        // the forming candle grows and another one is appended
+        let flags = anomalies.update(&[10.0, 12.0, 30.0, 12.0]).to_vec();
+        assert_eq!(flags, vec![false, false, true, false]);
+
+        // fewer candles after a reload
+        assert_eq!(anomalies.update(&[10.0]), [false]);
+        assert_eq!(anomalies.update(&[]), [false; 0]);
+    }
+
+    #[test]
+    fn test_highlighted_and_marked() {
+        let mut klines = mock::klines(0, 60_000, 4);
+        klines
+            .iter_mut()
+            .zip([8.0, 10.0, 12.0, 15.0])
+            .for_each(|(k, v)| k.volume = v);
+        let indicator = VolumeAnomalies {
+            window: 3,
+            k: 2.0,
+            ..Default::default()
+        };
+
+        let out = indicator.compute(&Data::new(klines.clone()));
+
+        let center = 180_000.0 + 30_000.0;
+        assert_eq!(out.volume_highlights[0].points, vec![[center, 15.0]]);
+        assert_eq!(out.glyphs.len(), 1);
+        assert_eq!(out.glyphs[0].point, [center, klines[3].high as f64]);
+
+        // a spike appended live is flagged from the kept flags
+        klines.extend(mock::klines(240_000, 60_000, 1));
+        klines[4].volume = 100.0;
+        let out = indicator.compute(&Data::new(klines));
+        assert_eq!(out.volume_highlights[0].points.len(), 2);
+        assert_eq!(indicator.flags.borrow().flags().len(), 5);
+    }
+}
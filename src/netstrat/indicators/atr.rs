@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Band, Indicator, IndicatorOutput, Series};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AtrDisplay {
+    /// ATR line in the indicators panel.
+    #[default]
+    Panel,
+    /// Band of `close ± k·ATR` over the candles.
+    Band,
+}
+
+/// Average true range with Wilder's smoothing.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Atr {
+    pub period: usize,
+    pub display: AtrDisplay,
+    /// Band width in ATRs.
+    pub k: f64,
+}
+
+impl Default for Atr {
+    fn default() -> Self {
+        Self {
+            period: 14,
+            display: Default::default(),
+            k: 2.0,
+        }
+    }
+}
+
+impl Indicator for Atr {
+    fn id(&self) -> &'static str {
+        "atr"
+    }
+
+    fn name(&self) -> String {
+        format!("ATR({})", self.period)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = super::moving_average::period_ui(ui, &mut self.period);
+        changed |= ui
+            .selectable_value(&mut self.display, AtrDisplay::Panel, "panel")
+            .changed();
+        changed |= ui
+            .selectable_value(&mut self.display, AtrDisplay::Band, "band")
+            .changed();
+        if self.display == AtrDisplay::Band {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut self.k)
+                        .speed(0.1)
+                        .clamp_range(0.1..=10.0)
+                        .prefix("k: "),
+                )
+                .changed();
+        }
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let vals = math::atr(&data.highs(), &data.lows(), &data.closes(), self.period);
+        let atr = Series::from_values(self.name(), data, &vals);
+
+        match self.display {
+            AtrDisplay::Panel => IndicatorOutput {
+                panel: vec![atr.clone()],
+                values: vec![atr],
+                ..Default::default()
+            },
+            AtrDisplay::Band => {
+                let closes = data.closes().into_iter().map(Some).collect();
+                let (_, upper, lower) = math::around(closes, &vals, self.k);
+                let name = format!("{} ±{}", self.name(), self.k);
+
+                IndicatorOutput {
+                    bands: vec![Band {
+                        upper: Series::from_values(format!("{name} upper"), data, &upper),
+                        lower: Series::from_values(format!("{name} lower"), data, &lower),
+                        name,
+                        ..Default::default()
+                    }],
+                    values: vec![atr],
+                    ..Default::default()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod atr_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    fn data() -> Data {
+        Data::new(
+            (0..4)
+                .map(|i| Kline {
+                    t_open: i * 10,
+                    t_close: i * 10 + 10,
+                    high: 11.0,
+                    low: 9.0,
+                    close: 10.0,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_band_around_close() {
+        let atr = Atr {
+            period: 2,
+            display: AtrDisplay::Band,
+            k: 1.5,
+        };
+
+        let out = atr.compute(&data());
+
+        assert!(out.panel.is_empty());
+        assert_eq!(out.bands[0].upper.points, vec![[25.0, 13.0], [35.0, 13.0]]);
+        assert_eq!(out.bands[0].lower.points, vec![[25.0, 7.0], [35.0, 7.0]]);
+        assert_eq!(out.values[0].points, vec![[25.0, 2.0], [35.0, 2.0]]);
+    }
+}
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Band, Indicator, IndicatorOutput, Series};
+
+/// Band of closes `k` standard deviations around their simple moving average.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bollinger {
+    pub period: usize,
+    /// Band width in standard deviations.
+    pub k: f64,
+}
+
+impl Default for Bollinger {
+    fn default() -> Self {
+        Self { period: 20, k: 2.0 }
+    }
+}
+
+impl Indicator for Bollinger {
+    fn id(&self) -> &'static str {
+        "bollinger"
+    }
+
+    fn name(&self) -> String {
+        format!("BB({}, {})", self.period, self.k)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = super::moving_average::period_ui(ui, &mut self.period);
+        changed |= k_ui(ui, &mut self.k);
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        channel(
+            self.name(),
+            data,
+            math::bollinger(&data.closes(), self.period, self.k),
+        )
+    }
+}
+
+/// Band of highs and lows `k` average true ranges around the exponential moving average of
+/// closes.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keltner {
+    pub period: usize,
+    pub atr_period: usize,
+    /// Band width in ATRs.
+    pub k: f64,
+}
+
+impl Default for Keltner {
+    fn default() -> Self {
+        Self {
+            period: 20,
+            atr_period: 10,
+            k: 2.0,
+        }
+    }
+}
+
+impl Indicator for Keltner {
+    fn id(&self) -> &'static str {
+        "keltner"
+    }
+
+    fn name(&self) -> String {
+        format!("KC({}, {}, {})", self.period, self.atr_period, self.k)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = super::moving_average::period_ui(ui, &mut self.period);
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut self.atr_period)
+                    .clamp_range(1..=1000)
+                    .prefix("ATR period: "),
+            )
+            .changed();
+        changed |= k_ui(ui, &mut self.k);
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        channel(
+            self.name(),
+            data,
+            math::keltner(
+                &data.highs(),
+                &data.lows(),
+                &data.closes(),
+                self.period,
+                self.atr_period,
+                self.k,
+            ),
+        )
+    }
+}
+
+/// Band between the highest high and the lowest low of the last candles.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Donchian {
+    pub period: usize,
+}
+
+impl Default for Donchian {
+    fn default() -> Self {
+        Self { period: 20 }
+    }
+}
+
+impl Indicator for Donchian {
+    fn id(&self) -> &'static str {
+        "donchian"
+    }
+
+    fn name(&self) -> String {
+        format!("DC({})", self.period)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        super::moving_average::period_ui(ui, &mut self.period)
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        channel(
+            self.name(),
+            data,
+            math::donchian(&data.highs(), &data.lows(), self.period),
+        )
+    }
+}
+
+#[cfg(feature = "gui")]
+fn k_ui(ui: &mut egui::Ui, k: &mut f64) -> bool {
+    ui.add(
+        egui::DragValue::new(k)
+            .speed(0.1)
+            .clamp_range(0.1..=10.0)
+            .prefix("k: "),
+    )
+    .changed()
+}
+
+/// Filled band with the middle line, all three values shown when hovered.
+fn channel(name: String, data: &Data, (mid, upper, lower): math::Bands) -> IndicatorOutput {
+    let mid = Series::from_values(format!("{name} middle"), data, &mid);
+    let upper = Series::from_values(format!("{name} upper"), data, &upper);
+    let lower = Series::from_values(format!("{name} lower"), data, &lower);
+
+    IndicatorOutput {
+        bands: vec![Band {
+            upper: upper.clone(),
+            lower: lower.clone(),
+            name,
+            ..Default::default()
+        }],
+        overlay: vec![mid.clone()],
+        values: vec![upper, mid, lower],
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod channels_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    fn data() -> Data {
+        Data::new(
+            [
+                (11.0, 9.0, 10.0),
+                (12.0, 10.0, 11.0),
+                (13.0, 11.0, 12.0),
+                (12.0, 10.0, 11.0),
+            ]
+            .iter()
+            .enumerate()
+            .map(|(i, (high, low, close))| Kline {
+                t_open: i as i64 * 10,
+                t_close: (i as i64 + 1) * 10 - 1,
+                high: *high,
+                low: *low,
+                close: *close,
+                ..Default::default()
+            })
+            .collect(),
+        )
+    }
+
+    fn ys(series: &Series) -> Vec<f64> {
+        series
+            .points
+            .iter()
+            .map(|p| (p[1] * 1e6).round() / 1e6)
+            .collect()
+    }
+
+    #[test]
+    fn test_keltner() {
+        let keltner = Keltner {
+            period: 2,
+            atr_period: 2,
+            k: 1.5,
+        };
+
+        let out = keltner.compute(&data());
+
+        // ema 11.5 and 11.1666.., atr 2 once warmed up
+        assert_eq!(ys(&out.bands[0].upper), vec![14.5, 14.166667]);
+        assert_eq!(ys(&out.bands[0].lower), vec![8.5, 8.166667]);
+        assert_eq!(ys(&out.overlay[0]), vec![10.5, 11.5, 11.166667]);
+        assert_eq!(out.values.len(), 3);
+        assert_eq!(out.bands[0].name, "KC(2, 2, 1.5)");
+    }
+
+    #[test]
+    fn test_donchian() {
+        let out = Donchian { period: 3 }.compute(&data());
+
+        assert_eq!(ys(&out.bands[0].upper), vec![13.0, 13.0]);
+        assert_eq!(ys(&out.bands[0].lower), vec![9.0, 10.0]);
+        assert_eq!(ys(&out.overlay[0]), vec![11.0, 11.5]);
+        assert_eq!(
+            out.values
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["DC(3) upper", "DC(3) middle", "DC(3) lower"]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Indicator, IndicatorOutput, Series, SubPanel};
+
+/// Drop of closes from their maximum in percent with the deepest drop highlighted.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Drawdown {
+    /// Candles the maximum is taken over, all loaded ones when `None`.
+    pub window: Option<usize>,
+}
+
+impl Indicator for Drawdown {
+    fn id(&self) -> &'static str {
+        "drawdown"
+    }
+
+    fn name(&self) -> String {
+        match self.window {
+            Some(window) => format!("Drawdown({window})"),
+            None => "Drawdown".to_string(),
+        }
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut rolling = self.window.is_some();
+        let mut changed = ui.checkbox(&mut rolling, "rolling").changed();
+        if changed {
+            self.window = rolling.then_some(100);
+        }
+        if let Some(window) = &mut self.window {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(window)
+                        .clamp_range(1..=10_000)
+                        .prefix("window: "),
+                )
+                .changed();
+        }
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let closes = data.closes();
+        let vals: Vec<Option<f64>> = math::drawdown(&closes, self.window)
+            .into_iter()
+            .map(|v| v.map(|v| v * 100.0))
+            .collect();
+        let series = Series::from_values(self.name(), data, &vals);
+
+        let (highlights, label) = match math::max_drawdown(&closes, self.window) {
+            Some((peak, trough, depth)) => {
+                let mut segment = vec![None; vals.len()];
+                segment[peak..=trough].copy_from_slice(&vals[peak..=trough]);
+                let duration = data.vals[trough].t_open - data.vals[peak].t_open;
+
+                (
+                    vec![Series::from_values("max drawdown", data, &segment)],
+                    Some(format!(
+                        "max drawdown: {:.2}% over {}",
+                        depth * 100.0,
+                        format_duration(Duration::milliseconds(duration))
+                    )),
+                )
+            }
+            None => (vec![], None),
+        };
+
+        IndicatorOutput {
+            values: vec![series.clone()],
+            sub_panels: vec![SubPanel {
+                name: self.name(),
+                series: vec![series],
+                highlights,
+                label,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+}
+
+/// Two most significant units of the duration, e.g. `3d 4h`.
+fn format_duration(d: Duration) -> String {
+    let parts = [
+        (d.num_days(), "d"),
+        (d.num_hours() % 24, "h"),
+        (d.num_minutes() % 60, "m"),
+        (d.num_seconds() % 60, "s"),
+    ];
+    let start = parts.iter().position(|(v, _)| *v > 0).unwrap_or(3);
+
+    parts[start..]
+        .iter()
+        .take(2)
+        .filter(|(v, _)| *v > 0)
+        .map(|(v, unit)| format!("{v}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod drawdown_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    #[test]
+    fn test_max_drawdown_segment_and_label() {
+        let hour = 60 * 60 * 1000;
+        let data = Data::new(
+            [10.0, 8.0, 12.0, 9.0, 6.0, 11.0]
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Kline {
+                    t_open: i as i64 * hour,
+                    t_close: (i as i64 + 1) * hour - 1,
+                    close: *c,
+                    ..Default::default()
+                })
+                .collect(),
+        );
+
+        let out = Drawdown::default().compute(&data);
+        let panel = &out.sub_panels[0];
+
+        assert_eq!(
+            panel.highlights[0]
+                .points
+                .iter()
+                .map(|p| p[1])
+                .collect::<Vec<_>>(),
+            vec![0.0, -25.0, -50.0]
+        );
+        assert_eq!(
+            panel.label.as_deref(),
+            Some("max drawdown: -50.00% over 2h")
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::hours(76)), "3d 4h");
+        assert_eq!(format_duration(Duration::days(2)), "2d");
+        assert_eq!(format_duration(Duration::seconds(30)), "30s");
+    }
+}
@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Band, Indicator, IndicatorOutput, Series, Tone};
+
+const SENKOU_A: &str = "Senkou A";
+const SENKOU_B: &str = "Senkou B";
+
+/// Ichimoku cloud with the spans shifted forward and the lagging line shifted back
+/// by the `kijun` period.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ichimoku {
+    pub tenkan: usize,
+    pub kijun: usize,
+    pub senkou: usize,
+}
+
+impl Default for Ichimoku {
+    fn default() -> Self {
+        Self {
+            tenkan: 9,
+            kijun: 26,
+            senkou: 52,
+        }
+    }
+}
+
+impl Indicator for Ichimoku {
+    fn id(&self) -> &'static str {
+        "ichimoku"
+    }
+
+    fn name(&self) -> String {
+        format!("Ichimoku({}, {}, {})", self.tenkan, self.kijun, self.senkou)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        [
+            (&mut self.tenkan, "tenkan: "),
+            (&mut self.kijun, "kijun: "),
+            (&mut self.senkou, "senkou: "),
+        ]
+        .into_iter()
+        .for_each(|(v, prefix)| {
+            changed |= ui
+                .add(egui::DragValue::new(v).clamp_range(1..=1000).prefix(prefix))
+                .changed();
+        });
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let (highs, lows) = (data.highs(), data.lows());
+        let tenkan = math::midpoint(&highs, &lows, self.tenkan);
+        let kijun = math::midpoint(&highs, &lows, self.kijun);
+        let senkou_a: Vec<Option<f64>> = tenkan
+            .iter()
+            .zip(&kijun)
+            .map(|(t, k)| Some(((*t)? + (*k)?) / 2.0))
+            .collect();
+        let senkou_b = math::midpoint(&highs, &lows, self.senkou);
+        let closes: Vec<Option<f64>> = data.closes().into_iter().map(Some).collect();
+
+        let shift = self.kijun as i64;
+        let senkou_a = Series::from_shifted(SENKOU_A, data, &senkou_a, shift);
+        let senkou_b = Series::from_shifted(SENKOU_B, data, &senkou_b, shift);
+        let lines = vec![
+            Series::from_values("Tenkan", data, &tenkan),
+            Series::from_values("Kijun", data, &kijun),
+            Series::from_shifted("Chikou", data, &closes, -shift),
+        ];
+
+        let mut values = lines.clone();
+        values.extend([senkou_a.clone(), senkou_b.clone()]);
+
+        IndicatorOutput {
+            overlay: lines,
+            bands: cloud(&senkou_a, &senkou_b),
+            values,
+            ..Default::default()
+        }
+    }
+}
+
+/// Splits area between the spans into bands toned by the span on top.
+/// Bands meet at the interpolated crossing points.
+fn cloud(a: &Series, b: &Series) -> Vec<Band> {
+    // span B has the longest warm-up, align span A with it
+    let a = &a.points[a.points.len().saturating_sub(b.points.len())..];
+    let b = &b.points[..];
+
+    let tone = |a: &[f64; 2], b: &[f64; 2]| match a[1] >= b[1] {
+        true => Tone::Bullish,
+        false => Tone::Bearish,
+    };
+    let mut bands: Vec<Band> = vec![];
+    for i in 0..a.len().min(b.len()) {
+        let curr = tone(&a[i], &b[i]);
+        match bands.last_mut() {
+            Some(band) if band.tone == curr => {
+                band.upper.points.push(a[i]);
+                band.lower.points.push(b[i]);
+            }
+            last => {
+                let mut band = Band {
+                    name: "Kumo".to_string(),
+                    upper: Series {
+                        name: SENKOU_A.to_string(),
+                        points: vec![],
+                        color: None,
+                    },
+                    lower: Series {
+                        name: SENKOU_B.to_string(),
+                        points: vec![],
+                        color: None,
+                    },
+                    tone: curr,
+                };
+                if let Some(prev) = last {
+                    let cross = crossing(a[i - 1], a[i], b[i - 1], b[i]);
+                    prev.upper.points.push(cross);
+                    prev.lower.points.push(cross);
+                    band.upper.points.push(cross);
+                    band.lower.points.push(cross);
+                }
+                band.upper.points.push(a[i]);
+                band.lower.points.push(b[i]);
+                bands.push(band);
+            }
+        }
+    }
+
+    bands
+}
+
+/// Intersection of segments `a0-a1` and `b0-b1` sharing x coordinates.
+fn crossing(a0: [f64; 2], a1: [f64; 2], b0: [f64; 2], b1: [f64; 2]) -> [f64; 2] {
+    let (d0, d1) = (a0[1] - b0[1], a1[1] - b1[1]);
+    let t = match d0 - d1 == 0.0 {
+        true => 0.0,
+        false => d0 / (d0 - d1),
+    };
+
+    [a0[0] + t * (a1[0] - a0[0]), a0[1] + t * (a1[1] - a0[1])]
+}
+
+#[cfg(test)]
+mod ichimoku_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    fn data(closes: &[f32]) -> Data {
+        Data::new(
+            closes
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: i as i64 * 10 + 9,
+                    high: *c + 1.0,
+                    low: *c - 1.0,
+                    close: *c,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn ichimoku() -> Ichimoku {
+        Ichimoku {
+            tenkan: 2,
+            kijun: 3,
+            senkou: 4,
+        }
+    }
+
+    #[test]
+    fn test_lines_and_shifts() {
+        let d = data(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        let out = ichimoku().compute(&d);
+
+        // tenkan: midpoint of 2 candles
+        assert_eq!(out.overlay[0].points[0], [15.0, 1.5]);
+        // chikou: close drawn 3 candles back
+        assert_eq!(out.overlay[2].points[0], [-25.0, 1.0]);
+        let senkou_b = out.values.iter().find(|s| s.name == SENKOU_B).unwrap();
+        // senkou b: midpoint of first 4 candles drawn 3 candles forward
+        assert_eq!(senkou_b.points[0], [65.0, 2.5]);
+        assert_eq!(senkou_b.points.last().unwrap()[0], d.max_x() - 4.0 + 30.0);
+        assert_eq!(out.values.len(), 5);
+    }
+
+    #[test]
+    fn test_cloud_tones() {
+        let a = Series {
+            name: SENKOU_A.to_string(),
+            points: vec![[0.0, 2.0], [10.0, 2.0], [20.0, 0.0]],
+            color: None,
+        };
+        let b = Series {
+            name: SENKOU_B.to_string(),
+            points: vec![[0.0, 1.0], [10.0, 1.0], [20.0, 1.0]],
+            color: None,
+        };
+
+        let bands = cloud(&a, &b);
+
+        assert_eq!(
+            bands.iter().map(|b| b.tone).collect::<Vec<_>>(),
+            vec![Tone::Bullish, Tone::Bearish]
+        );
+        assert_eq!(bands[0].upper.points.last(), Some(&[15.0, 1.0]));
+        assert_eq!(bands[1].lower.points[0], [15.0, 1.0]);
+    }
+}
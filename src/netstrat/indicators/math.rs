@@ -0,0 +1,905 @@
+//! Windowed computations shared by indicators.
+//!
+//! Every function returns a vector aligned with its input
+//! where `None` marks values not yet available due to warm-up.
+
+/// Simple moving average over `n` values.
+pub fn sma(vals: &[f64], n: usize) -> Vec<Option<f64>> {
+    let mut res = vec![None; vals.len()];
+    if n == 0 || vals.len() < n {
+        return res;
+    }
+
+    let mut sum: f64 = vals[..n].iter().sum();
+    res[n - 1] = Some(sum / n as f64);
+    for i in n..vals.len() {
+        sum += vals[i] - vals[i - n];
+        res[i] = Some(sum / n as f64);
+    }
+
+    res
+}
+
+/// Exponential moving average over `n` values seeded with the sma of the first `n` values.
+pub fn ema(vals: &[f64], n: usize) -> Vec<Option<f64>> {
+    smoothed(vals, n, 2.0 / (n as f64 + 1.0))
+}
+
+/// Wilder's moving average (RMA) over `n` values seeded with the sma of the first `n` values.
+pub fn rma(vals: &[f64], n: usize) -> Vec<Option<f64>> {
+    smoothed(vals, n, 1.0 / n as f64)
+}
+
+/// Relative strength index over `n` values using Wilder's smoothing.
+pub fn rsi(vals: &[f64], n: usize) -> Vec<Option<f64>> {
+    let mut res = vec![None; vals.len()];
+    if n == 0 || vals.len() <= n {
+        return res;
+    }
+
+    let (gains, losses): (Vec<f64>, Vec<f64>) = vals
+        .windows(2)
+        .map(|w| {
+            let change = w[1] - w[0];
+            (change.max(0.0), (-change).max(0.0))
+        })
+        .unzip();
+
+    let avg_gains = rma(&gains, n);
+    let avg_losses = rma(&losses, n);
+    for i in 0..gains.len() {
+        if let (Some(gain), Some(loss)) = (avg_gains[i], avg_losses[i]) {
+            res[i + 1] = Some(match loss == 0.0 {
+                true => 100.0,
+                false => 100.0 - 100.0 / (1.0 + gain / loss),
+            });
+        }
+    }
+
+    res
+}
+
+/// Highest of the last `n` values.
+pub fn highest(vals: &[f64], n: usize) -> Vec<Option<f64>> {
+    rolling(vals, n, |w| w.iter().copied().fold(f64::MIN, f64::max))
+}
+
+/// Lowest of the last `n` values.
+pub fn lowest(vals: &[f64], n: usize) -> Vec<Option<f64>> {
+    rolling(vals, n, |w| w.iter().copied().fold(f64::MAX, f64::min))
+}
+
+/// Log returns of neighbour values, the first one is not available.
+pub fn log_returns(vals: &[f64]) -> Vec<Option<f64>> {
+    let mut res = vec![None; vals.len().min(1)];
+    res.extend(vals.windows(2).map(|w| Some((w[1] / w[0]).ln())));
+
+    res
+}
+
+/// Simple returns of neighbour values, the first one and returns from zero are not available.
+pub fn simple_returns(vals: &[f64]) -> Vec<Option<f64>> {
+    let mut res = vec![None; vals.len().min(1)];
+    res.extend(
+        vals.windows(2)
+            .map(|w| (w[0] != 0.0).then(|| w[1] / w[0] - 1.0)),
+    );
+
+    res
+}
+
+/// Returns of values since the first one, as sums of log returns when `log`.
+pub fn cumulative_returns(vals: &[f64], log: bool) -> Vec<Option<f64>> {
+    let Some(first) = vals.first().copied().filter(|v| *v > 0.0) else {
+        return vec![None; vals.len()];
+    };
+
+    vals.iter()
+        .map(|v| match log {
+            true => (*v > 0.0).then(|| (v / first).ln()),
+            false => Some(v / first - 1.0),
+        })
+        .collect()
+}
+
+/// Cumulative returns in percent started over at the first point at or after `x`, left as
+/// they are when there is no such point.
+pub fn rebase_returns(points: &[[f64; 2]], x: f64, log: bool) -> Vec<[f64; 2]> {
+    let base = match points.iter().find(|p| p[0] >= x) {
+        Some(p) if log || p[1] > -100.0 => p[1],
+        _ => return points.to_vec(),
+    };
+
+    points
+        .iter()
+        .map(|[px, py]| match log {
+            true => [*px, py - base],
+            false => [*px, ((100.0 + py) / (100.0 + base) - 1.0) * 100.0],
+        })
+        .collect()
+}
+
+/// Sample standard deviation of the last `n` values.
+pub fn stdev(vals: &[f64], n: usize) -> Vec<Option<f64>> {
+    if n < 2 {
+        return vec![None; vals.len()];
+    }
+
+    rolling(vals, n, |w| {
+        let mean = w.iter().sum::<f64>() / n as f64;
+        (w.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+    })
+}
+
+/// Middle, upper and lower values of a band.
+pub type Bands = (Vec<Option<f64>>, Vec<Option<f64>>, Vec<Option<f64>>);
+
+/// Band `k` widths apart from the middle values, not available where either is missing.
+pub fn around(mid: Vec<Option<f64>>, width: &[Option<f64>], k: f64) -> Bands {
+    let shifted = |sign: f64| -> Vec<Option<f64>> {
+        mid.iter()
+            .zip(width)
+            .map(|(m, w)| Some((*m)? + sign * k * (*w)?))
+            .collect()
+    };
+    let (upper, lower) = (shifted(1.0), shifted(-1.0));
+
+    (mid, upper, lower)
+}
+
+/// Bollinger bands as the sma of the last `n` values `k` sample standard deviations apart.
+pub fn bollinger(vals: &[f64], n: usize, k: f64) -> Bands {
+    around(sma(vals, n), &stdev(vals, n), k)
+}
+
+/// Keltner channels as the ema of `n` closes `k` ATRs over `atr_n` candles apart.
+pub fn keltner(
+    highs: &[f64],
+    lows: &[f64],
+    closes: &[f64],
+    n: usize,
+    atr_n: usize,
+    k: f64,
+) -> Bands {
+    around(ema(closes, n), &atr(highs, lows, closes, atr_n), k)
+}
+
+/// Donchian channels as the highest high and the lowest low of the last `n` candles with
+/// the midline halfway between.
+pub fn donchian(highs: &[f64], lows: &[f64], n: usize) -> Bands {
+    let (upper, lower) = (highest(highs, n), lowest(lows, n));
+    let mid = upper
+        .iter()
+        .zip(&lower)
+        .map(|(u, l)| Some(((*u)? + (*l)?) / 2.0))
+        .collect();
+
+    (mid, upper, lower)
+}
+
+/// Distance of values from the sma of the last `n` values in sample standard deviations of
+/// the same window, not available for values which don't vary.
+pub fn zscore(vals: &[f64], n: usize) -> Vec<Option<f64>> {
+    let (mid, dev) = (sma(vals, n), stdev(vals, n));
+
+    vals.iter()
+        .zip(mid.iter().zip(&dev))
+        .map(|(v, (m, d))| {
+            let (m, d) = ((*m)?, (*d)?);
+            (d > 0.0).then(|| (v - m) / d)
+        })
+        .collect()
+}
+
+/// Maximum of all the values so far.
+pub fn running_max(vals: &[f64]) -> Vec<Option<f64>> {
+    let mut max = f64::MIN;
+    vals.iter()
+        .map(|v| {
+            max = max.max(*v);
+            Some(max)
+        })
+        .collect()
+}
+
+/// Relative drop of values from their maximum of the last `n` values
+/// or of all the values so far when `n` is `None`, e.g. `-0.1` for 10% below it.
+pub fn drawdown(vals: &[f64], n: Option<usize>) -> Vec<Option<f64>> {
+    let max = match n {
+        Some(n) => highest(vals, n),
+        None => running_max(vals),
+    };
+
+    vals.iter()
+        .zip(max)
+        .map(|(v, max)| max.filter(|m| *m != 0.0).map(|m| v / m - 1.0))
+        .collect()
+}
+
+/// Deepest drawdown as indices of its peak and trough with its depth.
+pub fn max_drawdown(vals: &[f64], n: Option<usize>) -> Option<(usize, usize, f64)> {
+    let (trough, depth) = drawdown(vals, n)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, d)| Some((i, d?)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .filter(|(_, d)| *d < 0.0)?;
+
+    let from = n.map_or(0, |n| (trough + 1).saturating_sub(n));
+    let peak_val = vals[from..=trough].iter().copied().fold(f64::MIN, f64::max);
+    let peak = (from..=trough).rev().find(|i| vals[*i] == peak_val)?;
+
+    Some((peak, trough, depth))
+}
+
+/// Middle of the highest high and the lowest low of the last `n` candles.
+pub fn midpoint(highs: &[f64], lows: &[f64], n: usize) -> Vec<Option<f64>> {
+    highest(highs, n)
+        .iter()
+        .zip(lowest(lows, n))
+        .map(|(h, l)| Some(((*h)? + l?) / 2.0))
+        .collect()
+}
+
+/// Stochastic oscillator `(%K, %D)` where raw %K over `k` candles is smoothed
+/// with the sma over `smooth` values and %D is the sma of %K over `d` values.
+/// Raw %K of a flat range is 50.
+pub fn stochastic(
+    highs: &[f64],
+    lows: &[f64],
+    closes: &[f64],
+    k: usize,
+    smooth: usize,
+    d: usize,
+) -> (Vec<Option<f64>>, Vec<Option<f64>>) {
+    let hh = highest(highs, k);
+    let ll = lowest(lows, k);
+    let raw: Vec<Option<f64>> = closes
+        .iter()
+        .zip(hh.iter().zip(&ll))
+        .map(|(c, (h, l))| {
+            let (h, l) = ((*h)?, (*l)?);
+            Some(match h - l == 0.0 {
+                true => 50.0,
+                false => 100.0 * (c - l) / (h - l),
+            })
+        })
+        .collect();
+
+    let k = after_warm_up(&raw, |v| sma(v, smooth));
+    let d = after_warm_up(&k, |v| sma(v, d));
+
+    (k, d)
+}
+
+/// Indices where `a` crosses `b` with true for crossing upwards.
+pub fn crossovers(a: &[Option<f64>], b: &[Option<f64>]) -> Vec<(usize, bool)> {
+    let diffs: Vec<Option<f64>> = a.iter().zip(b).map(|(a, b)| Some((*a)? - (*b)?)).collect();
+
+    (1..diffs.len())
+        .filter_map(|i| {
+            let (prev, curr) = (diffs[i - 1]?, diffs[i]?);
+            match (prev <= 0.0 && curr > 0.0, prev >= 0.0 && curr < 0.0) {
+                (true, _) => Some((i, true)),
+                (_, true) => Some((i, false)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// On-balance volume starting from zero at the first value.
+pub fn obv(closes: &[f64], volumes: &[f64]) -> Vec<Option<f64>> {
+    let mut sum = 0.0;
+    closes
+        .iter()
+        .zip(volumes)
+        .enumerate()
+        .map(|(i, (c, v))| {
+            if let Some(prev) = i.checked_sub(1).map(|i| closes[i]) {
+                sum += match c.total_cmp(&prev) {
+                    std::cmp::Ordering::Greater => *v,
+                    std::cmp::Ordering::Less => -v,
+                    std::cmp::Ordering::Equal => 0.0,
+                };
+            }
+            Some(sum)
+        })
+        .collect()
+}
+
+/// Least squares slope of the points, `None` for less than 2 distinct x.
+pub fn slope(points: &[[f64; 2]]) -> Option<f64> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p[0]).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p[1]).sum::<f64>() / n;
+    let (cov, var) = points.iter().fold((0.0, 0.0), |(cov, var), p| {
+        let dx = p[0] - mean_x;
+        (cov + dx * (p[1] - mean_y), var + dx * dx)
+    });
+
+    (var > 0.0).then(|| cov / var)
+}
+
+/// True range of every candle but the first one which has no previous close.
+pub fn true_range(highs: &[f64], lows: &[f64], closes: &[f64]) -> Vec<Option<f64>> {
+    let len = highs.len().min(lows.len()).min(closes.len());
+    (0..len)
+        .map(|i| {
+            let prev_close = closes[i.checked_sub(1)?];
+            Some(
+                (highs[i] - lows[i])
+                    .max((highs[i] - prev_close).abs())
+                    .max((lows[i] - prev_close).abs()),
+            )
+        })
+        .collect()
+}
+
+/// Average true range over `n` values using Wilder's smoothing.
+pub fn atr(highs: &[f64], lows: &[f64], closes: &[f64], n: usize) -> Vec<Option<f64>> {
+    let tr = true_range(highs, lows, closes);
+    let mut res = vec![None; tr.len()];
+    if tr.len() <= 1 {
+        return res;
+    }
+
+    let ranges: Vec<f64> = tr[1..].iter().flatten().copied().collect();
+    rma(&ranges, n)
+        .into_iter()
+        .enumerate()
+        .for_each(|(i, v)| res[i + 1] = v);
+
+    res
+}
+
+/// SuperTrend line over highs and lows `k` ATRs over `n` candles around their midpoint,
+/// with `true` while trending up. The lower line only rises and the upper one only falls
+/// until the close crosses them, the trend flips when the close crosses the line followed.
+pub fn supertrend(
+    highs: &[f64],
+    lows: &[f64],
+    closes: &[f64],
+    n: usize,
+    k: f64,
+) -> Vec<Option<(f64, bool)>> {
+    let atr = atr(highs, lows, closes, n);
+    let mut res = vec![None; atr.len()];
+    let mut prev: Option<(f64, f64, bool)> = None;
+    for i in 0..atr.len() {
+        let Some(atr) = atr[i] else {
+            continue;
+        };
+        let mid = (highs[i] + lows[i]) / 2.0;
+        let (upper, lower) = (mid + k * atr, mid - k * atr);
+        let (upper, lower, up) = match prev {
+            None => (upper, lower, false),
+            Some((prev_upper, prev_lower, up)) => {
+                let prev_close = closes[i - 1];
+                let upper = match upper < prev_upper || prev_close > prev_upper {
+                    true => upper,
+                    false => prev_upper,
+                };
+                let lower = match lower > prev_lower || prev_close < prev_lower {
+                    true => lower,
+                    false => prev_lower,
+                };
+                let up = match up {
+                    true => closes[i] >= lower,
+                    false => closes[i] > upper,
+                };
+                (upper, lower, up)
+            }
+        };
+        res[i] = Some((if up { lower } else { upper }, up));
+        prev = Some((upper, lower, up));
+    }
+
+    res
+}
+
+/// Parabolic SAR following TA-Lib with `true` while long, the first candle has no value.
+///
+/// The trend starts short if the second candle moves down more than up and long otherwise,
+/// from the extreme of the first candle. The acceleration factor starts at `start` and grows
+/// by `step` with every new extreme up to `max`. The SAR never enters the range of the last
+/// two candles and on a reversal it restarts from the extreme of the previous trend.
+pub fn parabolic_sar(
+    highs: &[f64],
+    lows: &[f64],
+    start: f64,
+    step: f64,
+    max: f64,
+) -> Vec<Option<(f64, bool)>> {
+    let len = highs.len().min(lows.len());
+    let mut res = vec![None; len];
+    if len < 2 {
+        return res;
+    }
+
+    let (down, up) = (lows[0] - lows[1], highs[1] - highs[0]);
+    let mut long = !(down > up && down > 0.0);
+    let (mut sar, mut ep) = match long {
+        true => (lows[0], highs[1]),
+        false => (highs[0], lows[1]),
+    };
+    let mut af = start;
+    for i in 1..len {
+        let (prev_high, prev_low) = (highs[i - 1], lows[i - 1]);
+        let (high, low) = (highs[i], lows[i]);
+        let reversed = match long {
+            true => low <= sar,
+            false => high >= sar,
+        };
+        if reversed {
+            long = !long;
+            sar = match long {
+                true => ep.min(prev_low).min(low),
+                false => ep.max(prev_high).max(high),
+            };
+            af = start;
+            ep = if long { high } else { low };
+        } else if (long && high > ep) || (!long && low < ep) {
+            ep = if long { high } else { low };
+            af = (af + step).min(max);
+        }
+        res[i] = Some((sar, long));
+
+        sar += af * (ep - sar);
+        sar = match long {
+            true => sar.min(prev_low).min(low),
+            false => sar.max(prev_high).max(high),
+        };
+    }
+
+    res
+}
+
+fn rolling(vals: &[f64], n: usize, f: impl Fn(&[f64]) -> f64) -> Vec<Option<f64>> {
+    let mut res = vec![None; vals.len()];
+    if n == 0 {
+        return res;
+    }
+
+    vals.windows(n)
+        .enumerate()
+        .for_each(|(i, w)| res[i + n - 1] = Some(f(w)));
+
+    res
+}
+
+/// Applies `f` to values after the leading `None`s keeping the result aligned.
+pub fn after_warm_up(
+    vals: &[Option<f64>],
+    f: impl Fn(&[f64]) -> Vec<Option<f64>>,
+) -> Vec<Option<f64>> {
+    let start = vals.iter().position(|v| v.is_some()).unwrap_or(vals.len());
+    let defined: Vec<f64> = vals[start..]
+        .iter()
+        .map(|v| v.unwrap_or_default())
+        .collect();
+
+    let mut res = vec![None; start];
+    res.extend(f(&defined));
+
+    res
+}
+
+fn smoothed(vals: &[f64], n: usize, alpha: f64) -> Vec<Option<f64>> {
+    let mut res = vec![None; vals.len()];
+    if n == 0 || vals.len() < n {
+        return res;
+    }
+
+    let mut prev = vals[..n].iter().sum::<f64>() / n as f64;
+    res[n - 1] = Some(prev);
+    for i in n..vals.len() {
+        prev = alpha * vals[i] + (1.0 - alpha) * prev;
+        res[i] = Some(prev);
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod math_tests {
+    use super::*;
+
+    fn assert_close(actual: &[Option<f64>], expected: &[Option<f64>]) {
+        assert_eq!(actual.len(), expected.len());
+        actual.iter().zip(expected).for_each(|(a, e)| match (a, e) {
+            (Some(a), Some(e)) => assert!((a - e).abs() < 1e-2, "{actual:?} != {expected:?}"),
+            (None, None) => {}
+            _ => panic!("{actual:?} != {expected:?}"),
+        });
+    }
+
+    #[test]
+    fn test_returns() {
+        let closes = [100.0, 110.0, 99.0, 0.0, 5.0];
+        assert_close(
+            &simple_returns(&closes),
+            &[None, Some(0.1), Some(-0.1), Some(-1.0), None],
+        );
+        assert_close(
+            &cumulative_returns(&closes[..3], false),
+            &[Some(0.0), Some(0.1), Some(-0.01)],
+        );
+        assert_close(
+            &cumulative_returns(&closes[..3], true),
+            &[Some(0.0), Some(1.1f64.ln()), Some(0.99f64.ln())],
+        );
+        assert_close(&cumulative_returns(&[0.0, 1.0], false), &[None, None]);
+
+        // 10% up then 10% down is -1% overall and -10% since the second candle
+        let points = [[0.0, 0.0], [1.0, 10.0], [2.0, -1.0]];
+        let rebased = rebase_returns(&points, 0.5, false);
+        assert!((rebased[2][1] + 10.0).abs() < 1e-9);
+        assert!((rebased[1][1]).abs() < 1e-9);
+        assert_eq!(rebase_returns(&points, 0.5, true)[2], [2.0, -11.0]);
+        assert_eq!(rebase_returns(&points, 3.0, false), points.to_vec());
+    }
+
+    #[test]
+    fn test_zscore_agrees_with_bollinger() {
+        let closes = [10.0, 12.0, 11.0, 15.0, 9.0, 9.0, 9.0, 13.0];
+        let (n, k) = (4, 2.0);
+        let (mid, upper, _) = bollinger(&closes, n, k);
+        let z = zscore(&closes, n);
+
+        assert_eq!(z.iter().position(|v| v.is_some()), Some(n - 1));
+        closes.iter().enumerate().skip(n - 1).for_each(|(i, c)| {
+            let (mid, upper) = (mid[i].unwrap(), upper[i].unwrap());
+            match z[i] {
+                // a close at z sits at z / k of the way from the middle to the upper band
+                Some(z) => assert!((mid + z / k * (upper - mid) - c).abs() < 1e-9),
+                None => assert_eq!(upper, mid),
+            }
+        });
+        assert_close(&zscore(&[1.0, 1.0, 1.0], 2), &[None, None, None]);
+        assert_close(&zscore(&[1.0, 3.0], 2), &[None, Some(2f64.sqrt() / 2.0)]);
+    }
+
+    #[test]
+    fn test_keltner() {
+        let (highs, lows) = ([11.0, 12.0, 13.0, 12.0], [9.0, 10.0, 11.0, 10.0]);
+        let closes = [10.0, 11.0, 12.0, 11.0];
+        let (mid, upper, lower) = keltner(&highs, &lows, &closes, 2, 2, 2.0);
+
+        // ema 10.5, 11.5, 11.1666.. and atr 2, 2, 2
+        assert_close(
+            &mid,
+            &[None, Some(10.5), Some(11.5), Some(11.0 + 1.0 / 6.0)],
+        );
+        assert_close(&upper, &[None, None, Some(15.5), Some(15.0 + 1.0 / 6.0)]);
+        assert_close(&lower, &[None, None, Some(7.5), Some(7.0 + 1.0 / 6.0)]);
+    }
+
+    #[test]
+    fn test_donchian() {
+        let highs = [11.0, 14.0, 12.0, 13.0];
+        let lows = [9.0, 10.0, 8.0, 12.0];
+        let (mid, upper, lower) = donchian(&highs, &lows, 2);
+
+        assert_close(&upper, &[None, Some(14.0), Some(14.0), Some(13.0)]);
+        assert_close(&lower, &[None, Some(9.0), Some(8.0), Some(8.0)]);
+        assert_close(&mid, &[None, Some(11.5), Some(11.0), Some(10.5)]);
+    }
+
+    #[test]
+    fn test_supertrend() {
+        let highs = [
+            10.0, 11.0, 12.0, 14.0, 16.0, 15.0, 13.0, 11.0, 10.0, 11.0, 13.0, 15.0,
+        ];
+        let lows = [
+            9.0, 10.0, 11.0, 12.0, 14.0, 13.0, 11.0, 9.0, 8.0, 9.0, 11.0, 13.0,
+        ];
+        let closes = [
+            9.5, 10.5, 11.5, 13.5, 15.5, 13.5, 11.5, 9.5, 9.0, 10.5, 12.5, 14.5,
+        ];
+
+        let st = supertrend(&highs, &lows, &closes, 3, 1.0);
+
+        // reference values of the TradingView definition starting in a downtrend
+        let lines: Vec<Option<f64>> = st.iter().map(|v| v.map(|v| v.0)).collect();
+        assert_close(
+            &lines,
+            &[
+                None,
+                None,
+                None,
+                Some(14.833333),
+                Some(12.944444),
+                Some(12.944444),
+                Some(14.302469),
+                Some(12.368313),
+                Some(11.245542),
+                Some(11.245542),
+                Some(9.724204),
+                Some(11.649469),
+            ],
+        );
+        let ups: Vec<bool> = st.iter().flatten().map(|v| v.1).collect();
+        assert_eq!(
+            ups,
+            vec![false, true, true, false, false, false, false, true, true]
+        );
+        assert!(supertrend(&highs, &lows, &closes, 20, 1.0)
+            .iter()
+            .all(|v| v.is_none()));
+    }
+
+    #[test]
+    fn test_parabolic_sar() {
+        let highs = [
+            10.0, 11.0, 12.0, 14.0, 16.0, 15.0, 13.0, 11.0, 10.0, 11.0, 13.0, 15.0,
+        ];
+        let lows = [
+            9.0, 10.0, 11.0, 12.0, 14.0, 13.0, 11.0, 9.0, 8.0, 9.0, 11.0, 13.0,
+        ];
+
+        let sar = parabolic_sar(&highs, &lows, 0.02, 0.02, 0.2);
+
+        // reference values of TA-Lib SAR, the second and third ones are held at the low
+        // of the first two candles
+        let vals: Vec<Option<f64>> = sar.iter().map(|v| v.map(|v| v.0)).collect();
+        assert_close(
+            &vals,
+            &[
+                None,
+                Some(9.0),
+                Some(9.0),
+                Some(9.12),
+                Some(9.4128),
+                Some(9.939776),
+                Some(10.424594),
+                Some(16.0),
+                Some(15.86),
+                Some(15.5456),
+                Some(15.243776),
+                Some(8.0),
+            ],
+        );
+        let longs: Vec<bool> = sar.iter().flatten().map(|v| v.1).collect();
+        assert_eq!(longs, [vec![true; 6], vec![false; 4], vec![true]].concat());
+
+        // a falling second candle starts short from the first high
+        let sar = parabolic_sar(&[10.0, 9.0, 8.0], &[9.0, 8.0, 7.0], 0.02, 0.02, 0.2);
+        assert_eq!(sar, vec![None, Some((10.0, false)), Some((10.0, false))]);
+    }
+
+    #[test]
+    fn test_log_returns_and_stdev() {
+        let returns = log_returns(&[1.0, std::f64::consts::E, 1.0]);
+        assert_close(&returns, &[None, Some(1.0), Some(-1.0)]);
+
+        // mean 5, squared deviations 9 + 1 + 1 + 9 over n - 1
+        assert_close(
+            &stdev(&[2.0, 4.0, 6.0, 8.0], 4),
+            &[None, None, None, Some((20.0f64 / 3.0).sqrt())],
+        );
+        assert_close(&stdev(&[1.0, 2.0], 1), &[None, None]);
+    }
+
+    #[test]
+    fn test_drawdown() {
+        let vals = [10.0, 8.0, 12.0, 9.0, 6.0, 11.0];
+
+        assert_close(
+            &drawdown(&vals, None),
+            &[
+                Some(0.0),
+                Some(-0.2),
+                Some(0.0),
+                Some(-0.25),
+                Some(-0.5),
+                Some(-1.0 / 12.0),
+            ],
+        );
+        assert_close(
+            &drawdown(&vals, Some(2)),
+            &[
+                None,
+                Some(-0.2),
+                Some(0.0),
+                Some(-0.25),
+                Some(-1.0 / 3.0),
+                Some(0.0),
+            ],
+        );
+        assert_eq!(max_drawdown(&vals, None), Some((2, 4, -0.5)));
+        let (peak, trough, depth) = max_drawdown(&vals, Some(2)).unwrap();
+        assert_eq!((peak, trough), (3, 4));
+        assert!((depth + 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(max_drawdown(&[1.0, 2.0], None), None);
+    }
+
+    #[test]
+    fn test_sma() {
+        assert_close(
+            &sma(&[1.0, 2.0, 3.0, 4.0, 5.0], 3),
+            &[None, None, Some(2.0), Some(3.0), Some(4.0)],
+        );
+        assert_close(&sma(&[1.0, 2.0], 3), &[None, None]);
+        assert_close(&sma(&[1.0, 2.0], 0), &[None, None]);
+    }
+
+    #[test]
+    fn test_ema() {
+        assert_close(
+            &ema(&[1.0, 2.0, 3.0, 4.0, 5.0], 3),
+            &[None, None, Some(2.0), Some(3.0), Some(4.0)],
+        );
+        assert_close(
+            &ema(&[2.0, 4.0, 6.0, 2.0], 2),
+            &[None, Some(3.0), Some(5.0), Some(3.0)],
+        );
+    }
+
+    #[test]
+    fn test_rsi() {
+        // closes from the StockCharts RSI example computed without intermediate rounding
+        let closes = [
+            44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03,
+            45.61, 46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64,
+        ];
+        let res = rsi(&closes, 14);
+
+        assert!(res[..14].iter().all(|v| v.is_none()));
+        assert_close(
+            &res[14..],
+            &[
+                Some(70.46),
+                Some(66.25),
+                Some(66.48),
+                Some(69.35),
+                Some(66.29),
+                Some(57.92),
+            ],
+        );
+    }
+
+    const HIGHS: [f64; 10] = [
+        48.70, 48.72, 48.90, 48.87, 48.82, 49.05, 49.20, 49.35, 49.92, 50.19,
+    ];
+    const LOWS: [f64; 10] = [
+        47.79, 48.14, 48.39, 48.37, 48.24, 48.64, 48.94, 48.86, 49.50, 49.87,
+    ];
+    const CLOSES: [f64; 10] = [
+        48.16, 48.61, 48.75, 48.63, 48.74, 49.03, 49.07, 49.32, 49.91, 50.13,
+    ];
+
+    #[test]
+    fn test_true_range() {
+        assert_close(
+            &true_range(&HIGHS, &LOWS, &CLOSES),
+            &[
+                None,
+                Some(0.58),
+                Some(0.51),
+                Some(0.50),
+                Some(0.58),
+                Some(0.41),
+                Some(0.26),
+                Some(0.49),
+                Some(0.60),
+                Some(0.32),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_atr() {
+        // rounded to 4 digits from Wilder's formula atr = (prev * (n - 1) + tr) / n
+        let expected = [0.53, 0.5467, 0.5011, 0.4207, 0.4438, 0.4959, 0.4373];
+        let res = atr(&HIGHS, &LOWS, &CLOSES, 3);
+
+        assert!(res[..3].iter().all(|v| v.is_none()));
+        res[3..].iter().zip(expected).for_each(|(a, e)| {
+            assert!((a.unwrap() - e).abs() < 1e-4, "{res:?}");
+        });
+        assert_close(&atr(&HIGHS[..1], &LOWS[..1], &CLOSES[..1], 3), &[None]);
+    }
+
+    fn rounded(vals: &[Option<f64>]) -> Vec<Option<f64>> {
+        vals.iter()
+            .map(|v| v.map(|v| (v * 1e4).round() / 1e4))
+            .collect()
+    }
+
+    #[test]
+    fn test_highest_lowest() {
+        let vals = [1.0, 3.0, 2.0, 0.0];
+
+        assert_eq!(
+            highest(&vals, 2),
+            vec![None, Some(3.0), Some(3.0), Some(2.0)]
+        );
+        assert_eq!(
+            lowest(&vals, 2),
+            vec![None, Some(1.0), Some(2.0), Some(0.0)]
+        );
+        assert_eq!(highest(&vals, 5), vec![None; 4]);
+    }
+
+    #[test]
+    fn test_stochastic() {
+        let highs = [10.0, 11.0, 12.0, 11.0, 10.0, 11.0, 13.0, 14.0, 13.0, 12.0];
+        let lows = [8.0, 9.0, 10.0, 9.0, 8.0, 9.0, 10.0, 12.0, 11.0, 10.0];
+        let closes = [9.0, 10.5, 11.0, 9.5, 8.5, 10.5, 12.5, 13.5, 11.5, 10.5];
+
+        let (k, d) = stochastic(&highs, &lows, &closes, 3, 2, 2);
+
+        assert_eq!(
+            rounded(&k),
+            vec![
+                None,
+                None,
+                None,
+                Some(45.8333),
+                Some(14.5833),
+                Some(47.9167),
+                Some(86.6667),
+                Some(90.0),
+                Some(63.75),
+                Some(25.0)
+            ]
+        );
+        assert_eq!(
+            rounded(&d),
+            vec![
+                None,
+                None,
+                None,
+                None,
+                Some(30.2083),
+                Some(31.25),
+                Some(67.2917),
+                Some(88.3333),
+                Some(76.875),
+                Some(44.375)
+            ]
+        );
+        assert_eq!(crossovers(&k, &d), vec![(5, true), (8, false)]);
+    }
+
+    #[test]
+    fn test_stochastic_flat() {
+        let (k, _) = stochastic(&[1.0; 3], &[1.0; 3], &[1.0; 3], 2, 1, 1);
+
+        assert_eq!(k, vec![None, Some(50.0), Some(50.0)]);
+    }
+
+    #[test]
+    fn test_obv() {
+        assert_close(
+            &obv(
+                &[10.0, 11.0, 10.5, 10.5, 12.0, 11.0],
+                &[100.0, 200.0, 150.0, 50.0, 300.0, 120.0],
+            ),
+            &[
+                Some(0.0),
+                Some(200.0),
+                Some(50.0),
+                Some(50.0),
+                Some(350.0),
+                Some(230.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_slope() {
+        assert_eq!(slope(&[[0.0, 1.0], [1.0, 3.0], [2.0, 5.0]]), Some(2.0));
+        assert_eq!(slope(&[[1.0, 1.0]]), None);
+        assert_eq!(slope(&[]), None);
+    }
+
+    #[test]
+    fn test_rsi_only_gains() {
+        assert_close(
+            &rsi(&[1.0, 2.0, 3.0, 4.0], 2),
+            &[None, None, Some(100.0), Some(100.0)],
+        );
+    }
+}
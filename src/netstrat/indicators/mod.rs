@@ -0,0 +1,760 @@
+//! Indicators computed over loaded data.
+//!
+//! To add an indicator implement [`Indicator`] and add it to [`builtin`].
+
+pub mod anomalies;
+mod atr;
+mod channels;
+mod drawdown;
+mod ichimoku;
+pub mod math;
+mod moving_average;
+mod obv;
+mod parabolic_sar;
+mod patterns;
+mod pivots;
+mod returns;
+mod rsi;
+mod sessions;
+mod stochastic;
+mod supertrend;
+pub mod timeframe;
+mod volatility;
+mod zscore;
+
+pub use self::anomalies::VolumeAnomalies;
+pub use self::atr::{Atr, AtrDisplay};
+pub use self::channels::{Bollinger, Donchian, Keltner};
+pub use self::drawdown::Drawdown;
+pub use self::ichimoku::Ichimoku;
+pub use self::moving_average::{Ema, Sma};
+pub use self::obv::Obv;
+pub use self::parabolic_sar::ParabolicSar;
+pub use self::patterns::{Pattern, Patterns, Thresholds};
+pub use self::pivots::Pivots;
+pub use self::returns::{ReturnKind, Returns};
+pub use self::rsi::Rsi;
+pub use self::sessions::{Session, SessionLevels};
+pub use self::stochastic::Stochastic;
+pub use self::supertrend::SuperTrend;
+pub use self::volatility::Volatility;
+pub use self::zscore::ZScore;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::sources::binance::Interval;
+
+use super::{bounds::Bounds, data::Data};
+
+/// Named series of `[x, y]` points in plot coordinates.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub points: Vec<[f64; 2]>,
+    /// Line color as rgb, picked automatically when `None`.
+    pub color: Option<[u8; 3]>,
+}
+
+impl Series {
+    /// Builds series from values aligned with data candles skipping missing ones.
+    pub fn from_values(name: impl Into<String>, data: &Data, vals: &[Option<f64>]) -> Self {
+        Self::from_shifted(name, data, vals, 0)
+    }
+
+    /// Builds series from values aligned with data candles drawn `shift` candles later,
+    /// or earlier for negative `shift`. Points may go beyond the data.
+    pub fn from_shifted(
+        name: impl Into<String>,
+        data: &Data,
+        vals: &[Option<f64>],
+        shift: i64,
+    ) -> Self {
+        let offset = shift as f64 * data.step();
+        Self {
+            name: name.into(),
+            color: None,
+            points: data
+                .vals
+                .iter()
+                .zip(vals)
+                .filter_map(|(k, v)| Some([data.center(k) + offset, (*v)?]))
+                .collect(),
+        }
+    }
+}
+
+/// Color of the band or level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tone {
+    #[default]
+    Neutral,
+    Bullish,
+    Bearish,
+}
+
+/// Area between two series.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Band {
+    pub name: String,
+    pub upper: Series,
+    pub lower: Series,
+    pub tone: Tone,
+}
+
+/// How cumulative returns in percent are started over at the left edge of the view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rebase {
+    Simple,
+    Log,
+}
+
+/// Study drawn in its own panel under the candles.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubPanel {
+    pub name: String,
+    pub series: Vec<Series>,
+    /// Values drawn as bars, green above zero and red below.
+    pub bars: Vec<Series>,
+    /// Series are cumulative returns in percent rebased to the first visible point when set.
+    pub rebase: Option<Rebase>,
+    /// Values of dashed horizontal lines.
+    pub guides: Vec<f64>,
+    /// Parts of the series drawn highlighted.
+    pub highlights: Vec<Series>,
+    /// Note shown in the corner of the panel.
+    pub label: Option<String>,
+}
+
+/// Horizontal line at `y` within the `x` range.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Level {
+    pub name: String,
+    pub x: [f64; 2],
+    pub y: f64,
+    pub tone: Tone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerKind {
+    Buy,
+    Sell,
+}
+
+/// Signal mark at `[x, y]` in plot coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Marker {
+    pub kind: MarkerKind,
+    pub point: [f64; 2],
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SignalKind {
+    Buy,
+    Sell,
+    #[default]
+    Neutral,
+}
+
+/// Signal at the candle containing `ts`, drawn at its low for buys and at its high
+/// otherwise when there is no `price`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignalPoint {
+    /// Time in millis.
+    pub ts: i64,
+    pub kind: SignalKind,
+    /// Shown when the signal is hovered.
+    pub label: String,
+    pub price: Option<f64>,
+}
+
+/// Signals of a single producer, e.g. a strategy or a script, replaced as a whole by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignalSeries {
+    pub name: String,
+    pub points: Vec<SignalPoint>,
+}
+
+/// Named mark of a single candle, drawn below it for bullish tone and above otherwise.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Glyph {
+    pub name: String,
+    pub point: [f64; 2],
+    pub tone: Tone,
+}
+
+/// Short state of an indicator shown in the chart header, e.g. the current trend.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Header {
+    pub text: String,
+    pub tone: Tone,
+}
+
+/// Horizontal guide lines of a panel indicator, zones outside of them
+/// up to the indicator extremes are shaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Guides {
+    pub lower: f64,
+    pub upper: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Price and indicator series expected to move in the same direction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Divergence {
+    pub price: Series,
+    pub indicator: Series,
+}
+
+impl Divergence {
+    /// Describes slopes of price and indicator within `visible` when their directions differ.
+    pub fn hint(&self, visible: Bounds) -> Option<String> {
+        let within = |s: &Series| -> Vec<[f64; 2]> {
+            s.points
+                .iter()
+                .filter(|p| visible.0 as f64 <= p[0] && p[0] <= visible.1 as f64)
+                .copied()
+                .collect()
+        };
+        let price = math::slope(&within(&self.price))?;
+        let indicator = math::slope(&within(&self.indicator))?;
+        if price == 0.0 || indicator == 0.0 || price.signum() == indicator.signum() {
+            return None;
+        }
+
+        let direction = |slope: f64| match slope > 0.0 {
+            true => "rising",
+            false => "falling",
+        };
+        Some(format!(
+            "{} divergence: price {}, {} {}",
+            self.indicator.name,
+            direction(price),
+            self.indicator.name,
+            direction(indicator)
+        ))
+    }
+}
+
+/// Result of indicator computation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndicatorOutput {
+    /// Lines drawn over the candles.
+    pub overlay: Vec<Series>,
+    /// Series drawn as dots over the candles.
+    pub dots: Vec<Series>,
+    /// Bands drawn over the candles.
+    pub bands: Vec<Band>,
+    /// Lines drawn in a separate panel under the candles.
+    pub panel: Vec<Series>,
+    /// Buy and sell marks drawn over the candles.
+    pub markers: Vec<Marker>,
+    /// Values shown in the candle tooltip.
+    pub values: Vec<Series>,
+    /// Guide lines of the panel.
+    pub guides: Vec<Guides>,
+    /// Marks drawn in the panel.
+    pub panel_markers: Vec<Marker>,
+    /// Pairs checked for divergence within the visible range.
+    pub divergences: Vec<Divergence>,
+    /// Labeled levels drawn over the candles.
+    pub levels: Vec<Level>,
+    /// Studies drawn in panels of their own.
+    pub sub_panels: Vec<SubPanel>,
+    /// Marks of candle patterns drawn over the candles.
+    pub glyphs: Vec<Glyph>,
+    /// Signals drawn over the candles.
+    pub signals: Vec<SignalSeries>,
+    /// Labels shown in the chart header.
+    pub headers: Vec<Header>,
+    /// Candles whose volume bars are highlighted, at their middle and volume, the
+    /// volume pane counts the visible ones by the series name.
+    pub volume_highlights: Vec<Series>,
+}
+
+impl IndicatorOutput {
+    pub fn extend(&mut self, other: &IndicatorOutput) {
+        self.overlay.extend(other.overlay.iter().cloned());
+        self.dots.extend(other.dots.iter().cloned());
+        self.bands.extend(other.bands.iter().cloned());
+        self.panel.extend(other.panel.iter().cloned());
+        self.markers.extend(other.markers.iter().cloned());
+        self.values.extend(other.values.iter().cloned());
+        self.guides.extend(other.guides.iter().cloned());
+        self.panel_markers
+            .extend(other.panel_markers.iter().cloned());
+        self.divergences.extend(other.divergences.iter().cloned());
+        self.levels.extend(other.levels.iter().cloned());
+        self.sub_panels.extend(other.sub_panels.iter().cloned());
+        self.glyphs.extend(other.glyphs.iter().cloned());
+        self.signals.extend(other.signals.iter().cloned());
+        self.headers.extend(other.headers.iter().cloned());
+        self.volume_highlights
+            .extend(other.volume_highlights.iter().cloned());
+    }
+
+    /// Moves everything drawn to the x coordinates given by `f`, e.g. when the time axis
+    /// is compressed. Divergences are checked within the visible time and signals are placed
+    /// by time so they are left as they are.
+    pub fn map_x(&mut self, f: impl Fn(f64) -> f64) {
+        let series = |s: &mut Series| s.points.iter_mut().for_each(|p| p[0] = f(p[0]));
+        self.overlay
+            .iter_mut()
+            .chain(self.dots.iter_mut())
+            .chain(self.panel.iter_mut())
+            .chain(self.values.iter_mut())
+            .chain(self.volume_highlights.iter_mut())
+            .chain(
+                self.bands
+                    .iter_mut()
+                    .flat_map(|b| [&mut b.upper, &mut b.lower]),
+            )
+            .chain(self.sub_panels.iter_mut().flat_map(|p| {
+                p.series
+                    .iter_mut()
+                    .chain(p.highlights.iter_mut())
+                    .chain(p.bars.iter_mut())
+            }))
+            .for_each(series);
+        self.markers
+            .iter_mut()
+            .chain(self.panel_markers.iter_mut())
+            .for_each(|m| m.point[0] = f(m.point[0]));
+        self.glyphs
+            .iter_mut()
+            .for_each(|g| g.point[0] = f(g.point[0]));
+        self.levels
+            .iter_mut()
+            .for_each(|l| l.x = [f(l.x[0]), f(l.x[1])]);
+    }
+}
+
+pub trait Indicator {
+    /// Stable identifier used to persist enabled indicators.
+    fn id(&self) -> &'static str;
+
+    /// Name including current params, e.g. `SMA(20)`.
+    fn name(&self) -> String;
+
+    /// Current params, used to share the indicator as it is set up.
+    fn params(&self) -> serde_json::Value;
+
+    /// Restores params returned by [`Indicator::params`], missing ones are set to defaults.
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()>;
+
+    /// Draws params controls returning true if any of the params changed.
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool;
+
+    /// Timezone of the app in minutes from UTC, for indicators grouping candles in calendar
+    /// sessions.
+    fn set_utc_offset(&mut self, _minutes: i32) {}
+
+    fn compute(&self, data: &Data) -> IndicatorOutput;
+}
+
+fn to_params<T: Serialize>(indicator: &T) -> serde_json::Value {
+    serde_json::to_value(indicator).unwrap_or_default()
+}
+
+fn from_params<T: DeserializeOwned>(
+    indicator: &mut T,
+    params: serde_json::Value,
+) -> serde_json::Result<()> {
+    *indicator = serde_json::from_value(params)?;
+    Ok(())
+}
+
+/// Enabled indicator with its params as shared in a view.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Setup {
+    /// Id as returned by [`Indicators::enabled_ids`].
+    pub id: String,
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+/// Indicators available in the app.
+pub fn builtin() -> Vec<Box<dyn Indicator>> {
+    vec![
+        Box::new(Sma::default()),
+        Box::new(Ema::default()),
+        Box::new(Rsi::default()),
+        Box::new(Atr::default()),
+        Box::new(Bollinger::default()),
+        Box::new(Keltner::default()),
+        Box::new(Donchian::default()),
+        Box::new(SuperTrend::default()),
+        Box::new(ParabolicSar::default()),
+        Box::new(Stochastic::default()),
+        Box::new(Obv::default()),
+        Box::new(Ichimoku::default()),
+        Box::new(Pivots::default()),
+        Box::new(SessionLevels::default()),
+        Box::new(Volatility::default()),
+        Box::new(ZScore::default()),
+        Box::new(Drawdown::default()),
+        Box::new(Returns::default()),
+        Box::new(Patterns::default()),
+        Box::new(VolumeAnomalies::default()),
+    ]
+}
+
+struct Entry {
+    indicator: Box<dyn Indicator>,
+    enabled: bool,
+    /// Timeframe the indicator is computed on, the chart one if `None`.
+    timeframe: Option<Interval>,
+    output: IndicatorOutput,
+}
+
+impl Entry {
+    /// Output over the data, computed on the timeframe of the entry and projected back.
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        match self
+            .timeframe
+            .and_then(|t| Some((t, timeframe::resample(data, t)?)))
+        {
+            Some((t, higher)) => {
+                timeframe::project(&self.indicator.compute(&higher), &higher, data, t.as_str())
+            }
+            None => self.indicator.compute(data),
+        }
+    }
+
+    /// Id with the timeframe appended after `@` if set.
+    fn id(&self) -> String {
+        match self.timeframe {
+            Some(t) => format!("{}@{}", self.indicator.id(), t.as_str()),
+            None => self.indicator.id().to_string(),
+        }
+    }
+}
+
+/// Registry of indicators with their enabled state and last computed output.
+pub struct Indicators {
+    entries: Vec<Entry>,
+}
+
+impl Default for Indicators {
+    fn default() -> Self {
+        Self::new(builtin())
+    }
+}
+
+impl Indicators {
+    pub fn new(indicators: Vec<Box<dyn Indicator>>) -> Self {
+        Self {
+            entries: indicators
+                .into_iter()
+                .map(|indicator| Entry {
+                    indicator,
+                    enabled: false,
+                    timeframe: None,
+                    output: Default::default(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn set_utc_offset(&mut self, minutes: i32) {
+        self.entries
+            .iter_mut()
+            .for_each(|e| e.indicator.set_utc_offset(minutes));
+    }
+
+    /// Recomputes outputs of enabled indicators.
+    pub fn compute(&mut self, data: &Data) {
+        self.entries.iter_mut().for_each(|e| {
+            e.output = match e.enabled && !data.vals.is_empty() {
+                true => e.compute(data),
+                false => Default::default(),
+            }
+        });
+    }
+
+    pub fn set_enabled(&mut self, idx: usize, enabled: bool) {
+        if let Some(e) = self.entries.get_mut(idx) {
+            e.enabled = enabled;
+        }
+    }
+
+    /// Sets the timeframe the indicator is computed on, the chart one if `None`.
+    pub fn set_timeframe(&mut self, idx: usize, timeframe: Option<Interval>) {
+        if let Some(e) = self.entries.get_mut(idx) {
+            e.timeframe = timeframe;
+        }
+    }
+
+    /// Ids of enabled indicators, ones on another timeframe are followed by `@` and the timeframe.
+    pub fn enabled_ids(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| e.enabled)
+            .map(Entry::id)
+            .collect()
+    }
+
+    /// Ids of enabled indicators as in [`Indicators::enabled_ids`] with their names.
+    pub fn enabled_names(&self) -> Vec<(String, String)> {
+        self.entries
+            .iter()
+            .filter(|e| e.enabled)
+            .map(|e| (e.id(), e.indicator.name()))
+            .collect()
+    }
+
+    /// Enables indicators with given ids and disables the rest, unknown ids are ignored.
+    pub fn set_enabled_ids(&mut self, ids: &[String]) {
+        self.entries.iter_mut().for_each(|e| {
+            let found = ids.iter().find_map(|id| {
+                let (id, timeframe) = match id.split_once('@') {
+                    Some((id, timeframe)) => (id, timeframe::parse(timeframe)),
+                    None => (id.as_str(), None),
+                };
+                (id == e.indicator.id()).then_some(timeframe)
+            });
+            e.enabled = found.is_some();
+            e.timeframe = found.flatten();
+        });
+    }
+
+    /// Enabled indicators with their params.
+    pub fn setups(&self) -> Vec<Setup> {
+        self.entries
+            .iter()
+            .filter(|e| e.enabled)
+            .map(|e| Setup {
+                id: e.id(),
+                params: e.indicator.params(),
+            })
+            .collect()
+    }
+
+    /// Enables indicators of the setups with their params and disables the rest. Params
+    /// which don't fit the indicator are left as they are, ids of failed ones are returned.
+    pub fn set_setups(&mut self, setups: &[Setup]) -> Vec<String> {
+        let ids: Vec<String> = setups.iter().map(|s| s.id.clone()).collect();
+        self.set_enabled_ids(&ids);
+
+        setups
+            .iter()
+            .filter(|s| !s.params.is_null())
+            .filter_map(|s| {
+                let id = s.id.split('@').next().unwrap_or_default();
+                let e = self.entries.iter_mut().find(|e| e.indicator.id() == id)?;
+                e.indicator
+                    .set_params(s.params.clone())
+                    .err()
+                    .map(|_| s.id.clone())
+            })
+            .collect()
+    }
+
+    /// Outputs of the enabled indicators with the ids computed over other data than the
+    /// shown one, the shown outputs are kept.
+    pub fn evaluate(&self, data: &Data, ids: &[String]) -> Vec<IndicatorOutput> {
+        self.entries
+            .iter()
+            .filter(|e| e.enabled && !data.vals.is_empty() && ids.contains(&e.id()))
+            .map(|e| e.compute(data))
+            .collect()
+    }
+
+    /// Outputs of enabled indicators.
+    pub fn outputs(&self) -> impl Iterator<Item = &IndicatorOutput> {
+        self.entries.iter().filter(|e| e.enabled).map(|e| &e.output)
+    }
+
+    /// Draws enable toggle and params of every indicator returning true if anything changed.
+    #[cfg(feature = "gui")]
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        self.entries.iter_mut().for_each(|e| {
+            ui.horizontal(|ui| {
+                changed |= ui.checkbox(&mut e.enabled, e.indicator.name()).changed();
+                ui.add_enabled_ui(e.enabled, |ui| {
+                    changed |= e.indicator.params_ui(ui);
+                    let timeframe = e.timeframe;
+                    egui::ComboBox::from_id_source(("timeframe", e.indicator.id()))
+                        .selected_text(timeframe.map_or("chart", |t| t.as_str()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut e.timeframe, None, "chart");
+                            timeframe::TIMEFRAMES.into_iter().for_each(|t| {
+                                ui.selectable_value(&mut e.timeframe, Some(t), t.as_str());
+                            });
+                        });
+                    changed |= timeframe != e.timeframe;
+                });
+            });
+        });
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod indicators_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    fn data(closes: &[f32]) -> Data {
+        Data::new(
+            closes
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: i as i64 * 10 + 10,
+                    open: *c,
+                    high: *c,
+                    low: *c,
+                    close: *c,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_series_from_values() {
+        let d = data(&[1.0, 2.0, 3.0]);
+
+        assert_eq!(
+            Series::from_values("s", &d, &[None, Some(1.5), Some(2.5)]).points,
+            vec![[15.0, 1.5], [25.0, 2.5]]
+        );
+    }
+
+    #[test]
+    fn test_map_x() {
+        let series = Series {
+            points: vec![[10.0, 1.0]],
+            ..Default::default()
+        };
+        let mut output = IndicatorOutput {
+            overlay: vec![series.clone()],
+            sub_panels: vec![SubPanel {
+                highlights: vec![series.clone()],
+                ..Default::default()
+            }],
+            levels: vec![Level {
+                name: "l".to_string(),
+                x: [0.0, 10.0],
+                y: 1.0,
+                tone: Tone::Neutral,
+            }],
+            divergences: vec![Divergence {
+                price: series.clone(),
+                indicator: series,
+            }],
+            ..Default::default()
+        };
+
+        output.map_x(|x| x / 10.0);
+
+        assert_eq!(output.overlay[0].points, vec![[1.0, 1.0]]);
+        assert_eq!(output.sub_panels[0].highlights[0].points, vec![[1.0, 1.0]]);
+        assert_eq!(output.levels[0].x, [0.0, 1.0]);
+        assert_eq!(output.divergences[0].price.points, vec![[10.0, 1.0]]);
+    }
+
+    #[test]
+    fn test_only_enabled_computed() {
+        let d = data(&[1.0, 2.0, 3.0, 4.0]);
+        let mut indicators = Indicators::new(vec![
+            Box::new(Sma { period: 2 }),
+            Box::new(Ema { period: 2 }),
+        ]);
+
+        indicators.set_enabled(1, true);
+        indicators.compute(&d);
+
+        let outputs: Vec<&IndicatorOutput> = indicators.outputs().collect();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].overlay[0].name, "EMA(2)");
+        assert_eq!(outputs[0].overlay[0].points.len(), 3);
+    }
+
+    #[test]
+    fn test_enabled_ids() {
+        let mut indicators = Indicators::default();
+
+        indicators.set_enabled_ids(&["rsi".to_string(), "obv".to_string(), "gone".to_string()]);
+
+        assert_eq!(indicators.enabled_ids(), vec!["rsi", "obv"]);
+    }
+
+    #[test]
+    fn test_timeframe_ids() {
+        let mut indicators = Indicators::default();
+
+        indicators.set_enabled_ids(&["sma@1d".to_string(), "rsi@2w".to_string()]);
+
+        assert_eq!(indicators.enabled_ids(), vec!["sma@1d", "rsi"]);
+    }
+
+    #[test]
+    fn test_computed_on_timeframe() {
+        let hour = 60 * 60 * 1000;
+        let d = Data::new(crate::sources::binance::mock::klines(0, hour, 48));
+        let mut indicators = Indicators::new(vec![Box::new(Sma { period: 1 })]);
+        indicators.set_enabled(0, true);
+        indicators.set_timeframe(0, Some(Interval::Day));
+
+        indicators.compute(&d);
+
+        let outputs: Vec<&IndicatorOutput> = indicators.outputs().collect();
+        assert_eq!(outputs[0].overlay[0].name, "SMA(1) 1d");
+        assert_eq!(outputs[0].overlay[0].points.len(), 25);
+    }
+
+    #[test]
+    fn test_setups() {
+        let mut indicators = Indicators::default();
+        indicators.set_enabled_ids(&["sma@1d".to_string(), "stochastic".to_string()]);
+        indicators.entries[0]
+            .indicator
+            .set_params(serde_json::json!({ "period": 50 }))
+            .unwrap();
+        let setups = indicators.setups();
+
+        let mut restored = Indicators::default();
+        let failed = restored.set_setups(
+            &[
+                setups,
+                vec![Setup {
+                    id: "rsi".to_string(),
+                    params: serde_json::json!({ "period": "long" }),
+                }],
+            ]
+            .concat(),
+        );
+
+        assert_eq!(failed, vec!["rsi"]);
+        assert_eq!(restored.enabled_ids(), vec!["sma@1d", "rsi", "stochastic"]);
+        assert_eq!(restored.entries[0].indicator.name(), "SMA(50)");
+        assert_eq!(restored.entries[2].indicator.name(), "RSI(14)");
+    }
+
+    #[test]
+    fn test_missing_params_default() {
+        let mut stochastic = Stochastic::default();
+
+        stochastic
+            .set_params(serde_json::json!({ "k": 5 }))
+            .unwrap();
+
+        assert_eq!((stochastic.k, stochastic.d), (5, Stochastic::default().d));
+    }
+
+    #[test]
+    fn test_builtin_ids_unique() {
+        let mut ids: Vec<&str> = builtin().iter().map(|i| i.id()).collect();
+        ids.sort();
+        ids.dedup();
+
+        assert_eq!(ids.len(), builtin().len());
+    }
+}
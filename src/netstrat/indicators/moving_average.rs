@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Indicator, IndicatorOutput, Series};
+
+/// Simple moving average of close prices.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sma {
+    pub period: usize,
+}
+
+impl Default for Sma {
+    fn default() -> Self {
+        Self { period: 20 }
+    }
+}
+
+impl Indicator for Sma {
+    fn id(&self) -> &'static str {
+        "sma"
+    }
+
+    fn name(&self) -> String {
+        format!("SMA({})", self.period)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        period_ui(ui, &mut self.period)
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let vals = math::sma(&data.closes(), self.period);
+
+        IndicatorOutput {
+            overlay: vec![Series::from_values(self.name(), data, &vals)],
+            ..Default::default()
+        }
+    }
+}
+
+/// Exponential moving average of close prices.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ema {
+    pub period: usize,
+}
+
+impl Default for Ema {
+    fn default() -> Self {
+        Self { period: 20 }
+    }
+}
+
+impl Indicator for Ema {
+    fn id(&self) -> &'static str {
+        "ema"
+    }
+
+    fn name(&self) -> String {
+        format!("EMA({})", self.period)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        period_ui(ui, &mut self.period)
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let vals = math::ema(&data.closes(), self.period);
+
+        IndicatorOutput {
+            overlay: vec![Series::from_values(self.name(), data, &vals)],
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+pub(super) fn period_ui(ui: &mut egui::Ui, period: &mut usize) -> bool {
+    ui.add(
+        egui::DragValue::new(period)
+            .clamp_range(1..=1000)
+            .prefix("period: "),
+    )
+    .changed()
+}
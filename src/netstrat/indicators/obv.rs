@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Divergence, Indicator, IndicatorOutput, Series};
+
+/// On-balance volume with optional sma of itself.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Obv {
+    /// Period of the sma drawn over OBV, `None` to hide it.
+    pub sma: Option<usize>,
+}
+
+impl Indicator for Obv {
+    fn id(&self) -> &'static str {
+        "obv"
+    }
+
+    fn name(&self) -> String {
+        "OBV".to_string()
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut enabled = self.sma.is_some();
+        let mut changed = ui.checkbox(&mut enabled, "sma").changed();
+        let mut period = self.sma.unwrap_or(20);
+        if enabled {
+            changed |= super::moving_average::period_ui(ui, &mut period);
+        }
+        self.sma = enabled.then_some(period);
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let obv = math::obv(&data.closes(), &data.volumes());
+        let mut panel = vec![Series::from_values(self.name(), data, &obv)];
+        if let Some(period) = self.sma {
+            let vals: Vec<f64> = obv.iter().flatten().copied().collect();
+            panel.push(Series::from_values(
+                format!("OBV SMA({period})"),
+                data,
+                &math::sma(&vals, period),
+            ));
+        }
+
+        let closes: Vec<Option<f64>> = data.closes().into_iter().map(Some).collect();
+
+        IndicatorOutput {
+            divergences: vec![Divergence {
+                price: Series::from_values("close", data, &closes),
+                indicator: panel[0].clone(),
+            }],
+            panel,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod obv_tests {
+    use crate::{netstrat::bounds::Bounds, sources::binance::Kline};
+
+    use super::*;
+
+    fn data(closes: &[f32], volumes: &[f32]) -> Data {
+        Data::new(
+            closes
+                .iter()
+                .zip(volumes)
+                .enumerate()
+                .map(|(i, (c, v))| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: i as i64 * 10 + 10,
+                    close: *c,
+                    volume: *v,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_obv_with_sma() {
+        let d = data(&[10.0, 11.0, 10.5, 12.0], &[100.0, 200.0, 150.0, 300.0]);
+
+        let out = Obv { sma: Some(2) }.compute(&d);
+
+        assert_eq!(
+            out.panel[0].points,
+            vec![[5.0, 0.0], [15.0, 200.0], [25.0, 50.0], [35.0, 350.0]]
+        );
+        assert_eq!(
+            out.panel[1].points,
+            vec![[15.0, 100.0], [25.0, 125.0], [35.0, 200.0]]
+        );
+    }
+
+    #[test]
+    fn test_divergence_hint() {
+        // price rises on shrinking up volume and heavy down volume
+        let d = data(
+            &[10.0, 12.0, 11.0, 13.0, 12.5],
+            &[1.0, 10.0, 50.0, 10.0, 50.0],
+        );
+        let out = Obv::default().compute(&d);
+
+        assert_eq!(
+            out.divergences[0].hint(Bounds(0, 50)),
+            Some("OBV divergence: price rising, OBV falling".to_string())
+        );
+        assert_eq!(out.divergences[0].hint(Bounds(0, 20)), None);
+    }
+}
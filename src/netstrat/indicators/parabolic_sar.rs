@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Indicator, IndicatorOutput, Series};
+
+/// Parabolic stop and reverse as dots below the candles while long and above them while short.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParabolicSar {
+    /// Acceleration factor at the start of a trend.
+    pub start: f64,
+    /// Growth of the acceleration factor with every new extreme.
+    pub step: f64,
+    pub max: f64,
+}
+
+impl Default for ParabolicSar {
+    fn default() -> Self {
+        Self {
+            start: 0.02,
+            step: 0.02,
+            max: 0.2,
+        }
+    }
+}
+
+impl Indicator for ParabolicSar {
+    fn id(&self) -> &'static str {
+        "parabolic_sar"
+    }
+
+    fn name(&self) -> String {
+        format!("SAR({}, {}, {})", self.start, self.step, self.max)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        [
+            (&mut self.start, "start: "),
+            (&mut self.step, "step: "),
+            (&mut self.max, "max: "),
+        ]
+        .into_iter()
+        .for_each(|(v, prefix)| {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(v)
+                        .speed(0.01)
+                        .clamp_range(0.001..=1.0)
+                        .prefix(prefix),
+                )
+                .changed();
+        });
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let sar: Vec<Option<f64>> =
+            math::parabolic_sar(&data.highs(), &data.lows(), self.start, self.step, self.max)
+                .into_iter()
+                .map(|v| v.map(|(sar, _)| sar))
+                .collect();
+        let sar = Series::from_values(self.name(), data, &sar);
+
+        IndicatorOutput {
+            dots: vec![sar.clone()],
+            values: vec![sar],
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod parabolic_sar_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    #[test]
+    fn test_dots_after_first_candle() {
+        let data = Data::new(
+            [(10.0, 9.0), (9.0, 8.0), (8.0, 7.0)]
+                .iter()
+                .enumerate()
+                .map(|(i, (high, low))| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: (i as i64 + 1) * 10 - 1,
+                    high: *high,
+                    low: *low,
+                    ..Default::default()
+                })
+                .collect(),
+        );
+
+        let out = ParabolicSar::default().compute(&data);
+
+        assert_eq!(out.dots[0].points, vec![[15.0, 10.0], [25.0, 10.0]]);
+        assert_eq!(out.values[0].name, "SAR(0.02, 0.02, 0.2)");
+        assert!(out.overlay.is_empty());
+    }
+}
@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{netstrat::data::Data, sources::binance::Kline};
+
+use super::{Glyph, Indicator, IndicatorOutput, Tone};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    BullishEngulfing,
+    BearishEngulfing,
+    Doji,
+    Hammer,
+    ShootingStar,
+}
+
+pub const PATTERNS: [Pattern; 5] = [
+    Pattern::BullishEngulfing,
+    Pattern::BearishEngulfing,
+    Pattern::Doji,
+    Pattern::Hammer,
+    Pattern::ShootingStar,
+];
+
+impl Pattern {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pattern::BullishEngulfing => "bullish engulfing",
+            Pattern::BearishEngulfing => "bearish engulfing",
+            Pattern::Doji => "doji",
+            Pattern::Hammer => "hammer",
+            Pattern::ShootingStar => "shooting star",
+        }
+    }
+
+    pub fn tone(&self) -> Tone {
+        match self {
+            Pattern::BullishEngulfing | Pattern::Hammer => Tone::Bullish,
+            Pattern::BearishEngulfing | Pattern::ShootingStar => Tone::Bearish,
+            Pattern::Doji => Tone::Neutral,
+        }
+    }
+}
+
+/// Body and wick ratios the patterns are matched with.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Thresholds {
+    /// Largest body of a doji relative to the candle range.
+    pub doji_body: f64,
+    /// Smallest long wick of a hammer or shooting star relative to the body.
+    pub long_wick: f64,
+    /// Largest short wick of a hammer or shooting star relative to the body.
+    pub short_wick: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            doji_body: 0.1,
+            long_wick: 2.0,
+            short_wick: 0.5,
+        }
+    }
+}
+
+struct Shape {
+    body: f64,
+    upper: f64,
+    lower: f64,
+    range: f64,
+    bullish: bool,
+    bearish: bool,
+}
+
+impl From<&Kline> for Shape {
+    fn from(k: &Kline) -> Self {
+        let (open, close) = (k.open as f64, k.close as f64);
+        Self {
+            body: (close - open).abs(),
+            upper: k.high as f64 - open.max(close),
+            lower: open.min(close) - k.low as f64,
+            range: (k.high - k.low) as f64,
+            bullish: close > open,
+            bearish: close < open,
+        }
+    }
+}
+
+impl Thresholds {
+    fn is_doji(&self, s: &Shape) -> bool {
+        s.range > 0.0 && s.body <= self.doji_body * s.range
+    }
+
+    /// Long lower wick, short upper one and a body which is not a doji.
+    fn is_hammer(&self, s: &Shape) -> bool {
+        !self.is_doji(s)
+            && s.body > 0.0
+            && s.lower >= self.long_wick * s.body
+            && s.upper <= self.short_wick * s.body
+    }
+
+    fn is_shooting_star(&self, s: &Shape) -> bool {
+        !self.is_doji(s)
+            && s.body > 0.0
+            && s.upper >= self.long_wick * s.body
+            && s.lower <= self.short_wick * s.body
+    }
+}
+
+/// Whether the body of `cur` covers the opposite colored body of `prev` and is larger.
+fn engulfs(prev: &Kline, cur: &Kline, bullish: bool) -> bool {
+    let (p, c) = (Shape::from(prev), Shape::from(cur));
+    let (lo, hi) = (cur.open.min(cur.close), cur.open.max(cur.close));
+    let covers = lo <= prev.open.min(prev.close) && hi >= prev.open.max(prev.close);
+    let colors = match bullish {
+        true => p.bearish && c.bullish,
+        false => p.bullish && c.bearish,
+    };
+
+    colors && covers && c.body > p.body
+}
+
+/// Indices of candles matching the `patterns`, in the order of candles and then of the patterns.
+/// Candles are matched by their shape only, the preceding trend is not considered.
+pub fn detect(data: &Data, thresholds: &Thresholds, patterns: &[Pattern]) -> Vec<(usize, Pattern)> {
+    data.vals
+        .iter()
+        .enumerate()
+        .flat_map(|(i, k)| {
+            let shape = Shape::from(k);
+            let prev = i.checked_sub(1).map(|p| &data.vals[p]);
+            patterns
+                .iter()
+                .filter(move |p| match p {
+                    Pattern::BullishEngulfing => prev.is_some_and(|prev| engulfs(prev, k, true)),
+                    Pattern::BearishEngulfing => prev.is_some_and(|prev| engulfs(prev, k, false)),
+                    Pattern::Doji => thresholds.is_doji(&shape),
+                    Pattern::Hammer => thresholds.is_hammer(&shape),
+                    Pattern::ShootingStar => thresholds.is_shooting_star(&shape),
+                })
+                .map(move |p| (i, *p))
+        })
+        .collect()
+}
+
+/// Marks candles matching the enabled classic patterns.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Patterns {
+    pub thresholds: Thresholds,
+    /// Detected patterns in the order of [`PATTERNS`].
+    pub enabled: [bool; 5],
+}
+
+impl Default for Patterns {
+    fn default() -> Self {
+        Self {
+            thresholds: Default::default(),
+            enabled: [true; 5],
+        }
+    }
+}
+
+impl Indicator for Patterns {
+    fn id(&self) -> &'static str {
+        "patterns"
+    }
+
+    fn name(&self) -> String {
+        "Patterns".to_string()
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        ui.menu_button("config", |ui| {
+            PATTERNS
+                .iter()
+                .zip(self.enabled.iter_mut())
+                .for_each(|(p, enabled)| {
+                    changed |= ui.checkbox(enabled, p.name()).changed();
+                });
+            ui.separator();
+            let t = &mut self.thresholds;
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut t.doji_body)
+                        .clamp_range(0.0..=1.0)
+                        .speed(0.01)
+                        .prefix("doji body / range: "),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut t.long_wick)
+                        .clamp_range(0.0..=20.0)
+                        .speed(0.1)
+                        .prefix("long wick / body: "),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut t.short_wick)
+                        .clamp_range(0.0..=20.0)
+                        .speed(0.1)
+                        .prefix("short wick / body: "),
+                )
+                .changed();
+        });
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let patterns: Vec<Pattern> = PATTERNS
+            .iter()
+            .zip(self.enabled)
+            .filter(|(_, enabled)| *enabled)
+            .map(|(p, _)| *p)
+            .collect();
+
+        IndicatorOutput {
+            glyphs: detect(data, &self.thresholds, &patterns)
+                .into_iter()
+                .map(|(i, p)| {
+                    let k = &data.vals[i];
+                    let y = match p.tone() {
+                        Tone::Bullish => k.low,
+                        Tone::Bearish | Tone::Neutral => k.high,
+                    };
+                    Glyph {
+                        name: p.name().to_string(),
+                        point: [data.center(k), y as f64],
+                        tone: p.tone(),
+                    }
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod patterns_tests {
+    use super::*;
+
+    fn data(ohlc: &[[f32; 4]]) -> Data {
+        Data::new(
+            ohlc.iter()
+                .enumerate()
+                .map(|(i, [open, high, low, close])| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: i as i64 * 10 + 9,
+                    open: *open,
+                    high: *high,
+                    low: *low,
+                    close: *close,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn detect_all(ohlc: &[[f32; 4]]) -> Vec<(usize, Pattern)> {
+        detect(&data(ohlc), &Thresholds::default(), &PATTERNS)
+    }
+
+    #[test]
+    fn test_engulfing() {
+        let matches = detect_all(&[
+            [12.0, 12.5, 10.5, 11.0],
+            // bullish body 10.5..12.5 covers 11..12
+            [10.5, 13.0, 10.0, 12.5],
+            // bearish body 10..13 covers 10.5..12.5
+            [13.0, 13.5, 9.5, 10.0],
+            // covers only part of the previous body
+            [10.5, 12.0, 10.0, 11.5],
+        ]);
+
+        assert_eq!(
+            matches,
+            vec![
+                (1, Pattern::BullishEngulfing),
+                (2, Pattern::BearishEngulfing)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_candle_patterns() {
+        let matches = detect_all(&[
+            // body 0.1 of range 2
+            [10.0, 11.0, 9.0, 10.1],
+            // lower wick 3, body 1, upper wick 0.2
+            [12.0, 13.2, 9.0, 13.0],
+            // upper wick 3, body 1, lower wick 0
+            [11.0, 15.0, 10.0, 10.0],
+            // plain candle not covering the previous body
+            [10.5, 12.5, 10.0, 12.0],
+        ]);
+
+        assert_eq!(
+            matches,
+            vec![
+                (0, Pattern::Doji),
+                (1, Pattern::Hammer),
+                (2, Pattern::ShootingStar),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let data = data(&[[10.0, 11.0, 9.0, 10.3]]);
+        let patterns = [Pattern::Doji];
+
+        assert!(detect(&data, &Thresholds::default(), &patterns).is_empty());
+        let loose = Thresholds {
+            doji_body: 0.2,
+            ..Default::default()
+        };
+        assert_eq!(detect(&data, &loose, &patterns), vec![(0, Pattern::Doji)]);
+    }
+
+    #[test]
+    fn test_glyphs_above_and_below() {
+        let output = Patterns::default()
+            .compute(&data(&[[12.0, 13.2, 9.0, 13.0], [11.0, 15.0, 10.0, 10.0]]));
+
+        assert_eq!(
+            output.glyphs.iter().map(|g| g.point).collect::<Vec<_>>(),
+            vec![[5.0, 9.0], [15.0, 15.0]]
+        );
+        assert_eq!(output.glyphs[0].name, "hammer");
+    }
+}
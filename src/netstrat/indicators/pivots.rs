@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{
+    sessions::{sessions, Ohlc, Session},
+    Indicator, IndicatorOutput, Level, Tone,
+};
+
+pub const LEVELS: [&str; 7] = ["R3", "R2", "R1", "P", "S1", "S2", "S3"];
+
+/// Classic pivots from high, low and close in the order of [`LEVELS`].
+pub fn classic(high: f64, low: f64, close: f64) -> [f64; 7] {
+    let p = (high + low + close) / 3.0;
+    [
+        high + 2.0 * (p - low),
+        p + (high - low),
+        2.0 * p - low,
+        p,
+        2.0 * p - high,
+        p - (high - low),
+        low - 2.0 * (high - p),
+    ]
+}
+
+/// Classic pivot levels of every session computed from the previous one.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pivots {
+    pub session: Session,
+    /// Minutes from UTC of the timezone of the app sessions are grouped in.
+    #[serde(skip)]
+    pub utc_offset: i32,
+    /// Shown levels in the order of [`LEVELS`].
+    pub shown: [bool; 7],
+}
+
+impl Default for Pivots {
+    fn default() -> Self {
+        Self {
+            session: Default::default(),
+            utc_offset: 0,
+            shown: [true; 7],
+        }
+    }
+}
+
+impl Indicator for Pivots {
+    fn id(&self) -> &'static str {
+        "pivots"
+    }
+
+    fn name(&self) -> String {
+        match self.session {
+            Session::Daily => "Pivots(daily)".to_string(),
+            Session::Weekly => "Pivots(weekly)".to_string(),
+        }
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = ui
+            .selectable_value(&mut self.session, Session::Daily, "daily")
+            .changed();
+        changed |= ui
+            .selectable_value(&mut self.session, Session::Weekly, "weekly")
+            .changed();
+        LEVELS
+            .iter()
+            .zip(self.shown.iter_mut())
+            .for_each(|(l, shown)| {
+                changed |= ui.checkbox(shown, *l).changed();
+            });
+
+        changed
+    }
+
+    fn set_utc_offset(&mut self, minutes: i32) {
+        self.utc_offset = minutes;
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let sessions = sessions(data, self.session, self.utc_offset);
+        let mut levels = vec![];
+        sessions.windows(2).for_each(|pair| {
+            let (prev, (start, _)) = (&data.vals[pair[0].1.clone()], &pair[1]);
+            let Some(Ohlc {
+                high, low, close, ..
+            }) = Ohlc::of(prev)
+            else {
+                return;
+            };
+
+            LEVELS
+                .iter()
+                .zip(classic(high, low, close))
+                .zip(self.shown)
+                .filter(|(_, shown)| *shown)
+                .for_each(|((name, y), _)| {
+                    levels.push(Level {
+                        name: name.to_string(),
+                        x: [*start as f64, (*start + self.session.millis() - 1) as f64],
+                        y,
+                        tone: match name.chars().next() {
+                            Some('R') => Tone::Bearish,
+                            Some('S') => Tone::Bullish,
+                            _ => Tone::Neutral,
+                        },
+                    })
+                });
+        });
+
+        IndicatorOutput {
+            levels,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod pivots_tests {
+    use crate::sources::binance::Kline;
+
+    use super::{
+        super::sessions::{DAY_MILLIS, HOUR_MILLIS},
+        *,
+    };
+
+    fn kline(t_open: i64, high: f32, low: f32, close: f32) -> Kline {
+        Kline {
+            t_open,
+            t_close: t_open + HOUR_MILLIS - 1,
+            high,
+            low,
+            close,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_classic() {
+        assert_eq!(
+            classic(12.0, 6.0, 9.0),
+            [18.0, 15.0, 12.0, 9.0, 6.0, 3.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_levels_of_previous_session() {
+        let data = Data::new(vec![
+            kline(0, 12.0, 8.0, 10.0),
+            kline(HOUR_MILLIS, 11.0, 6.0, 9.0),
+            kline(DAY_MILLIS, 100.0, 1.0, 50.0),
+        ]);
+        let pivots = Pivots {
+            shown: [false, false, true, true, true, false, false],
+            ..Default::default()
+        };
+
+        let levels = pivots.compute(&data).levels;
+
+        assert_eq!(
+            levels
+                .iter()
+                .map(|l| (l.name.as_str(), l.y))
+                .collect::<Vec<_>>(),
+            vec![("R1", 12.0), ("P", 9.0), ("S1", 6.0)]
+        );
+        assert_eq!(
+            levels[0].x,
+            [DAY_MILLIS as f64, (2 * DAY_MILLIS - 1) as f64]
+        );
+        assert_eq!(levels[0].tone, Tone::Bearish);
+    }
+}
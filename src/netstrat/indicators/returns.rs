@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Indicator, IndicatorOutput, Rebase, Series, SubPanel};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReturnKind {
+    #[default]
+    Simple,
+    Log,
+}
+
+/// Returns of closes in percent, per candle as bars and cumulative since the first candle as
+/// a line.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Returns {
+    pub kind: ReturnKind,
+    /// Cumulative returns start over at the left edge of the view.
+    pub rebase: bool,
+}
+
+impl Indicator for Returns {
+    fn id(&self) -> &'static str {
+        "returns"
+    }
+
+    fn name(&self) -> String {
+        match self.kind {
+            ReturnKind::Simple => "Returns".to_string(),
+            ReturnKind::Log => "Log returns".to_string(),
+        }
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = ui
+            .selectable_value(&mut self.kind, ReturnKind::Simple, "simple")
+            .changed();
+        changed |= ui
+            .selectable_value(&mut self.kind, ReturnKind::Log, "log")
+            .changed();
+        changed |= ui
+            .checkbox(&mut self.rebase, "rebase to visible start")
+            .changed();
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let closes = data.closes();
+        let log = self.kind == ReturnKind::Log;
+        let percent = |vals: Vec<Option<f64>>| -> Vec<Option<f64>> {
+            vals.into_iter().map(|v| v.map(|v| v * 100.0)).collect()
+        };
+        let per_candle = match self.kind {
+            ReturnKind::Simple => math::simple_returns(&closes),
+            ReturnKind::Log => math::log_returns(&closes),
+        };
+        let per_candle = Series::from_values(self.name(), data, &percent(per_candle));
+        let cumulative = Series::from_values(
+            format!("cumulative {}", self.name().to_lowercase()),
+            data,
+            &percent(math::cumulative_returns(&closes, log)),
+        );
+
+        IndicatorOutput {
+            values: vec![per_candle.clone(), cumulative.clone()],
+            sub_panels: vec![SubPanel {
+                name: self.name(),
+                series: vec![cumulative],
+                bars: vec![per_candle],
+                rebase: self.rebase.then_some(match self.kind {
+                    ReturnKind::Simple => Rebase::Simple,
+                    ReturnKind::Log => Rebase::Log,
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod returns_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    fn data() -> Data {
+        Data::new(
+            [100.0, 125.0, 100.0, 150.0]
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: (i as i64 + 1) * 10 - 1,
+                    close: *c,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn ys(series: &Series) -> Vec<f64> {
+        series
+            .points
+            .iter()
+            .map(|p| (p[1] * 1e6).round() / 1e6)
+            .collect()
+    }
+
+    #[test]
+    fn test_simple_returns() {
+        let out = Returns::default().compute(&data());
+        let panel = &out.sub_panels[0];
+
+        assert_eq!(ys(&panel.bars[0]), vec![25.0, -20.0, 50.0]);
+        assert_eq!(ys(&panel.series[0]), vec![0.0, 25.0, 0.0, 50.0]);
+        assert_eq!(panel.bars[0].points[0][0], 15.0);
+        assert_eq!(panel.rebase, None);
+        assert_eq!(out.values.len(), 2);
+    }
+
+    #[test]
+    fn test_log_returns_rebased() {
+        let returns = Returns {
+            kind: ReturnKind::Log,
+            rebase: true,
+        };
+        let out = returns.compute(&data());
+        let panel = &out.sub_panels[0];
+
+        // log returns add up to the cumulative one
+        let sum: f64 = panel.bars[0].points.iter().map(|p| p[1]).sum();
+        let last = panel.series[0].points[3][1];
+        assert!((sum - last).abs() < 1e-9);
+        assert!((last - 1.5f64.ln() * 100.0).abs() < 1e-9);
+        assert_eq!(panel.rebase, Some(Rebase::Log));
+        assert_eq!(panel.name, "Log returns");
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Indicator, IndicatorOutput, Series};
+
+/// Relative strength index of close prices.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rsi {
+    pub period: usize,
+}
+
+impl Default for Rsi {
+    fn default() -> Self {
+        Self { period: 14 }
+    }
+}
+
+impl Indicator for Rsi {
+    fn id(&self) -> &'static str {
+        "rsi"
+    }
+
+    fn name(&self) -> String {
+        format!("RSI({})", self.period)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        super::moving_average::period_ui(ui, &mut self.period)
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let vals = math::rsi(&data.closes(), self.period);
+
+        IndicatorOutput {
+            panel: vec![Series::from_values(self.name(), data, &vals)],
+            ..Default::default()
+        }
+    }
+}
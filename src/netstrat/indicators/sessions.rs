@@ -0,0 +1,319 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{netstrat::data::Data, sources::binance::Kline};
+
+use super::{Indicator, IndicatorOutput, Level, Tone};
+
+pub(super) const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+const MINUTE_MILLIS: i64 = 60 * 1000;
+#[cfg(test)]
+pub(super) const HOUR_MILLIS: i64 = 60 * MINUTE_MILLIS;
+/// Epoch started on Thursday, weeks start on Monday.
+const WEEK_START_OFFSET: i64 = 3 * DAY_MILLIS;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Session {
+    #[default]
+    Daily,
+    Weekly,
+}
+
+impl Session {
+    pub fn millis(&self) -> i64 {
+        match self {
+            Session::Daily => DAY_MILLIS,
+            Session::Weekly => 7 * DAY_MILLIS,
+        }
+    }
+
+    /// Start of the session containing `ts` in UTC millis, sessions are calendar
+    /// days or weeks in the timezone `utc_offset` minutes from UTC.
+    pub fn start(&self, ts: i64, utc_offset: i32) -> i64 {
+        let shift = utc_offset as i64 * MINUTE_MILLIS
+            + match self {
+                Session::Daily => 0,
+                Session::Weekly => WEEK_START_OFFSET,
+            };
+
+        (ts + shift).div_euclid(self.millis()) * self.millis() - shift
+    }
+}
+
+/// Ranges of candles of every session with the session start.
+pub fn sessions(data: &Data, session: Session, utc_offset: i32) -> Vec<(i64, Range<usize>)> {
+    let mut res: Vec<(i64, Range<usize>)> = vec![];
+    data.vals.iter().enumerate().for_each(|(i, k)| {
+        let start = session.start(k.t_open, utc_offset);
+        match res.last_mut() {
+            Some((last, range)) if *last == start => range.end = i + 1,
+            _ => res.push((start, i..i + 1)),
+        }
+    });
+
+    res
+}
+
+/// Open, high, low and close of the candles of a session.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ohlc {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+impl Ohlc {
+    pub fn of(klines: &[Kline]) -> Option<Self> {
+        Some(Self {
+            open: klines.first()?.open as f64,
+            high: klines
+                .iter()
+                .map(|k| k.high as f64)
+                .fold(f64::MIN, f64::max),
+            low: klines.iter().map(|k| k.low as f64).fold(f64::MAX, f64::min),
+            close: klines.last()?.close as f64,
+        })
+    }
+}
+
+/// Reference lines of the latest session from the previous day and week and the current
+/// day open, spanning the current day or week.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionLevels {
+    /// Minutes from UTC of the timezone of the app sessions are grouped in.
+    #[serde(skip)]
+    pub utc_offset: i32,
+    pub prev_high_low: bool,
+    pub prev_close: bool,
+    pub open: bool,
+    /// High and low of the previous week.
+    pub weekly: bool,
+}
+
+impl Default for SessionLevels {
+    fn default() -> Self {
+        Self {
+            utc_offset: 0,
+            prev_high_low: true,
+            prev_close: false,
+            open: true,
+            weekly: false,
+        }
+    }
+}
+
+impl Indicator for SessionLevels {
+    fn id(&self) -> &'static str {
+        "session_levels"
+    }
+
+    fn name(&self) -> String {
+        "Session levels".to_string()
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        [
+            (&mut self.prev_high_low, "prev day high/low"),
+            (&mut self.prev_close, "prev day close"),
+            (&mut self.open, "today open"),
+            (&mut self.weekly, "prev week high/low"),
+        ]
+        .into_iter()
+        .for_each(|(shown, label)| {
+            changed |= ui.checkbox(shown, label).changed();
+        });
+
+        changed
+    }
+
+    fn set_utc_offset(&mut self, minutes: i32) {
+        self.utc_offset = minutes;
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let mut levels = vec![];
+        let mut push = |name: &str, session: Session, start: i64, y: f64, tone: Tone| {
+            levels.push(Level {
+                name: name.to_string(),
+                x: [start as f64, (start + session.millis() - 1) as f64],
+                y,
+                tone,
+            })
+        };
+
+        let days = sessions(data, Session::Daily, self.utc_offset);
+        let ohlc = |range: &Range<usize>| Ohlc::of(&data.vals[range.clone()]);
+        if let Some((start, today)) = days.last() {
+            let prev = days.len().checked_sub(2).and_then(|i| ohlc(&days[i].1));
+            if let Some(prev) = prev.filter(|_| self.prev_high_low) {
+                push(
+                    "prev high",
+                    Session::Daily,
+                    *start,
+                    prev.high,
+                    Tone::Bearish,
+                );
+                push("prev low", Session::Daily, *start, prev.low, Tone::Bullish);
+            }
+            if let Some(prev) = prev.filter(|_| self.prev_close) {
+                push(
+                    "prev close",
+                    Session::Daily,
+                    *start,
+                    prev.close,
+                    Tone::Neutral,
+                );
+            }
+            if let Some(today) = ohlc(today).filter(|_| self.open) {
+                push("open", Session::Daily, *start, today.open, Tone::Neutral);
+            }
+        }
+
+        let weeks = sessions(data, Session::Weekly, self.utc_offset);
+        if let [.., (_, prev), (start, _)] = weeks.as_slice() {
+            if let Some(prev) = ohlc(prev).filter(|_| self.weekly) {
+                push(
+                    "prev week high",
+                    Session::Weekly,
+                    *start,
+                    prev.high,
+                    Tone::Bearish,
+                );
+                push(
+                    "prev week low",
+                    Session::Weekly,
+                    *start,
+                    prev.low,
+                    Tone::Bullish,
+                );
+            }
+        }
+
+        IndicatorOutput {
+            levels,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod sessions_tests {
+    use super::*;
+
+    fn kline(t_open: i64, open: f32, high: f32, low: f32, close: f32) -> Kline {
+        Kline {
+            t_open,
+            t_close: t_open + HOUR_MILLIS - 1,
+            open,
+            high,
+            low,
+            close,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sessions_in_timezone() {
+        // 22:00 and 23:00 of day 0, 00:00 of day 1 in UTC
+        let data = Data::new(
+            (22..25)
+                .map(|h| kline(h * HOUR_MILLIS, 1.0, 1.0, 1.0, 1.0))
+                .collect(),
+        );
+
+        let utc = sessions(&data, Session::Daily, 0);
+        assert_eq!(utc, vec![(0, 0..2), (DAY_MILLIS, 2..3)]);
+        // all of them are on day 1 at UTC+2
+        let plus_two = sessions(&data, Session::Daily, 120);
+        assert_eq!(plus_two, vec![(DAY_MILLIS - 2 * HOUR_MILLIS, 0..3)]);
+    }
+
+    #[test]
+    fn test_weeks_start_on_monday() {
+        // 1970-01-05 was Monday
+        let monday = 4 * DAY_MILLIS;
+
+        assert_eq!(
+            Session::Weekly.start(monday - 1, 0),
+            monday - 7 * DAY_MILLIS
+        );
+        assert_eq!(Session::Weekly.start(monday + DAY_MILLIS, 0), monday);
+    }
+
+    #[test]
+    fn test_levels_over_current_day() {
+        let data = Data::new(vec![
+            kline(0, 10.0, 12.0, 8.0, 10.0),
+            kline(HOUR_MILLIS, 10.0, 11.0, 6.0, 9.0),
+            kline(DAY_MILLIS, 9.5, 100.0, 1.0, 50.0),
+            kline(DAY_MILLIS + HOUR_MILLIS, 50.0, 60.0, 40.0, 45.0),
+        ]);
+        let levels = SessionLevels {
+            prev_close: true,
+            ..Default::default()
+        };
+
+        let levels = levels.compute(&data).levels;
+
+        assert_eq!(
+            levels
+                .iter()
+                .map(|l| (l.name.as_str(), l.y))
+                .collect::<Vec<_>>(),
+            vec![
+                ("prev high", 12.0),
+                ("prev low", 6.0),
+                ("prev close", 9.0),
+                ("open", 9.5)
+            ]
+        );
+        assert!(levels
+            .iter()
+            .all(|l| l.x == [DAY_MILLIS as f64, (2 * DAY_MILLIS - 1) as f64]));
+    }
+
+    #[test]
+    fn test_weekly_levels_need_previous_week() {
+        let monday = 4 * DAY_MILLIS;
+        let mut levels = SessionLevels {
+            weekly: true,
+            ..Default::default()
+        };
+        let data = Data::new(vec![kline(monday, 5.0, 6.0, 4.0, 5.0)]);
+
+        // the only day has an open but no previous day or week
+        let names: Vec<String> = levels
+            .compute(&data)
+            .levels
+            .into_iter()
+            .map(|l| l.name)
+            .collect();
+        assert_eq!(names, vec!["open"]);
+
+        levels.open = false;
+        let data = Data::new(vec![
+            kline(monday - DAY_MILLIS, 5.0, 7.0, 3.0, 5.0),
+            kline(monday, 5.0, 6.0, 4.0, 5.0),
+        ]);
+        let weekly = levels.compute(&data).levels;
+        assert_eq!(weekly.len(), 4);
+        assert_eq!((weekly[2].y, weekly[3].y), (7.0, 3.0));
+        assert_eq!(
+            weekly[2].x,
+            [monday as f64, (monday + 7 * DAY_MILLIS - 1) as f64]
+        );
+    }
+}
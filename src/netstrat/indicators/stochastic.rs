@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Guides, Indicator, IndicatorOutput, Marker, MarkerKind, Series};
+
+/// Stochastic oscillator %K and %D lines.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stochastic {
+    pub k: usize,
+    pub smooth: usize,
+    pub d: usize,
+    /// Marks %K crossing %D on the panel.
+    pub crossovers: bool,
+}
+
+impl Default for Stochastic {
+    fn default() -> Self {
+        Self {
+            k: 14,
+            smooth: 3,
+            d: 3,
+            crossovers: false,
+        }
+    }
+}
+
+impl Indicator for Stochastic {
+    fn id(&self) -> &'static str {
+        "stochastic"
+    }
+
+    fn name(&self) -> String {
+        format!("Stoch({}, {}, {})", self.k, self.smooth, self.d)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        [
+            (&mut self.k, "k: "),
+            (&mut self.smooth, "smooth: "),
+            (&mut self.d, "d: "),
+        ]
+        .into_iter()
+        .for_each(|(v, prefix)| {
+            changed |= ui
+                .add(egui::DragValue::new(v).clamp_range(1..=1000).prefix(prefix))
+                .changed();
+        });
+        changed |= ui.checkbox(&mut self.crossovers, "crossovers").changed();
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let (k, d) = math::stochastic(
+            &data.highs(),
+            &data.lows(),
+            &data.closes(),
+            self.k,
+            self.smooth,
+            self.d,
+        );
+
+        let panel_markers = match self.crossovers {
+            true => math::crossovers(&k, &d)
+                .into_iter()
+                .filter_map(|(i, up)| {
+                    let c = data.vals.get(i)?;
+                    Some(Marker {
+                        kind: match up {
+                            true => MarkerKind::Buy,
+                            false => MarkerKind::Sell,
+                        },
+                        point: [data.center(c), k[i]?],
+                    })
+                })
+                .collect(),
+            false => vec![],
+        };
+
+        IndicatorOutput {
+            panel: vec![
+                Series::from_values(format!("{} %K", self.name()), data, &k),
+                Series::from_values(format!("{} %D", self.name()), data, &d),
+            ],
+            guides: vec![Guides {
+                lower: 20.0,
+                upper: 80.0,
+                min: 0.0,
+                max: 100.0,
+            }],
+            panel_markers,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod stochastic_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    #[test]
+    fn test_crossover_markers() {
+        let closes = [1.0, 2.0, 3.0, 2.0, 1.0, 2.0, 3.0];
+        let data = Data::new(
+            closes
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: i as i64 * 10 + 10,
+                    high: 3.0,
+                    low: 1.0,
+                    close: *c,
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        let mut stoch = Stochastic {
+            k: 1,
+            smooth: 1,
+            d: 2,
+            crossovers: false,
+        };
+
+        assert!(stoch.compute(&data).panel_markers.is_empty());
+
+        stoch.crossovers = true;
+        let kinds: Vec<MarkerKind> = stoch
+            .compute(&data)
+            .panel_markers
+            .iter()
+            .map(|m| m.kind)
+            .collect();
+        assert_eq!(kinds, vec![MarkerKind::Sell, MarkerKind::Buy]);
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{
+    math, Header, Indicator, IndicatorOutput, Series, SignalKind, SignalPoint, SignalSeries, Tone,
+};
+
+const UP_COLOR: [u8; 3] = [144, 238, 144];
+const DOWN_COLOR: [u8; 3] = [255, 128, 128];
+
+/// Stepped trailing line below the candles in an uptrend and above them in a downtrend,
+/// with flips of the trend as signals.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SuperTrend {
+    pub period: usize,
+    /// Distance of the line from the candle midpoint in ATRs.
+    pub k: f64,
+}
+
+impl Default for SuperTrend {
+    fn default() -> Self {
+        Self { period: 10, k: 3.0 }
+    }
+}
+
+impl Indicator for SuperTrend {
+    fn id(&self) -> &'static str {
+        "supertrend"
+    }
+
+    fn name(&self) -> String {
+        format!("ST({}, {})", self.period, self.k)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = ui
+            .add(
+                egui::DragValue::new(&mut self.period)
+                    .clamp_range(1..=1000)
+                    .prefix("ATR period: "),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut self.k)
+                    .speed(0.1)
+                    .clamp_range(0.1..=10.0)
+                    .prefix("k: "),
+            )
+            .changed();
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let st = math::supertrend(
+            &data.highs(),
+            &data.lows(),
+            &data.closes(),
+            self.period,
+            self.k,
+        );
+        let name = self.name();
+        let line: Vec<Option<f64>> = st.iter().map(|v| v.map(|(line, _)| line)).collect();
+
+        // a line per run of the same trend so it's colored by the trend and broken at flips
+        let mut overlay: Vec<Series> = vec![];
+        let mut signals = vec![];
+        let mut prev_up = None;
+        st.iter()
+            .zip(&data.vals)
+            .enumerate()
+            .for_each(|(i, (v, k))| {
+                let Some((_, up)) = *v else {
+                    return;
+                };
+                if prev_up != Some(up) {
+                    let (trend, color) = match up {
+                        true => ("up", UP_COLOR),
+                        false => ("down", DOWN_COLOR),
+                    };
+                    let mut run = vec![None; st.len()];
+                    run[i..]
+                        .iter_mut()
+                        .zip(&st[i..])
+                        .take_while(|(_, v)| v.is_some_and(|(_, u)| u == up))
+                        .for_each(|(r, v)| *r = v.map(|(line, _)| line));
+                    overlay.push(Series {
+                        color: Some(color),
+                        ..Series::from_values(format!("{name} {trend}"), data, &run)
+                    });
+                    if prev_up.is_some() {
+                        signals.push(SignalPoint {
+                            ts: k.t_open,
+                            kind: match up {
+                                true => SignalKind::Buy,
+                                false => SignalKind::Sell,
+                            },
+                            label: format!("{name} flipped {trend}"),
+                            price: None,
+                        });
+                    }
+                }
+                prev_up = Some(up);
+            });
+
+        IndicatorOutput {
+            overlay,
+            values: vec![Series::from_values(name.clone(), data, &line)],
+            signals: vec![SignalSeries {
+                name: name.clone(),
+                points: signals,
+            }],
+            headers: prev_up
+                .map(|up| Header {
+                    text: format!("{name} {}", if up { "up" } else { "down" }),
+                    tone: match up {
+                        true => Tone::Bullish,
+                        false => Tone::Bearish,
+                    },
+                })
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod supertrend_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    fn data() -> Data {
+        let highs = [10.0, 11.0, 12.0, 14.0, 16.0, 15.0, 13.0, 11.0, 10.0];
+        let lows = [9.0, 10.0, 11.0, 12.0, 14.0, 13.0, 11.0, 9.0, 8.0];
+        let closes = [9.5, 10.5, 11.5, 13.5, 15.5, 13.5, 11.5, 9.5, 9.0];
+        Data::new(
+            (0..closes.len())
+                .map(|i| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: (i as i64 + 1) * 10 - 1,
+                    high: highs[i],
+                    low: lows[i],
+                    close: closes[i],
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_runs_and_flips() {
+        let st = SuperTrend { period: 3, k: 1.0 };
+
+        let out = st.compute(&data());
+
+        let runs: Vec<(&str, usize)> = out
+            .overlay
+            .iter()
+            .map(|s| (s.name.as_str(), s.points.len()))
+            .collect();
+        assert_eq!(
+            runs,
+            vec![
+                ("ST(3, 1) down", 1),
+                ("ST(3, 1) up", 2),
+                ("ST(3, 1) down", 3)
+            ]
+        );
+        assert_eq!(out.overlay[1].color, Some(UP_COLOR));
+        assert_eq!(out.values[0].points.len(), 6);
+
+        let flips: Vec<(i64, SignalKind)> = out.signals[0]
+            .points
+            .iter()
+            .map(|p| (p.ts, p.kind))
+            .collect();
+        assert_eq!(flips, vec![(40, SignalKind::Buy), (60, SignalKind::Sell)]);
+        assert_eq!(
+            out.headers,
+            vec![Header {
+                text: "ST(3, 1) down".to_string(),
+                tone: Tone::Bearish,
+            }]
+        );
+    }
+}
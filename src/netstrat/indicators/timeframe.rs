@@ -0,0 +1,274 @@
+//! Indicators computed over a wider timeframe and drawn over the chart candles.
+
+use crate::{
+    netstrat::{aggregate, data::Data},
+    sources::binance::{Interval, Kline},
+};
+
+use super::{
+    Band, Divergence, Glyph, Header, IndicatorOutput, Level, Marker, Series, SignalPoint,
+    SignalSeries, SubPanel,
+};
+
+/// Timeframes an indicator can be computed on besides the chart one.
+pub const TIMEFRAMES: [Interval; 3] = [Interval::Minute, Interval::Hour, Interval::Day];
+
+/// Timeframe by its name, e.g. `1d`.
+pub fn parse(s: &str) -> Option<Interval> {
+    TIMEFRAMES.into_iter().find(|i| i.as_str() == s)
+}
+
+/// Candles of `data` resampled to `timeframe`, `None` if they are as wide already.
+pub fn resample(data: &Data, timeframe: Interval) -> Option<Data> {
+    let width = data.vals.first().map(|k| k.t_close - k.t_open + 1)?;
+    (timeframe.millis() > width).then(|| aggregate::resample(&data.vals, timeframe.millis()))
+}
+
+/// Projects output computed over the `higher` candles onto the `lower` ones as steps.
+///
+/// A value of a higher candle shows up from the lower candle closing with it on, so nothing
+/// is drawn before the higher candle is closed. Points are moved to the lower candle
+/// the higher one becomes known at and names get the `label` appended.
+pub fn project(
+    output: &IndicatorOutput,
+    higher: &Data,
+    lower: &Data,
+    label: &str,
+) -> IndicatorOutput {
+    let steps = |s: &Series| steps(s, higher, lower, label);
+    let known_at = |x: f64| known_at(x, higher, lower);
+
+    IndicatorOutput {
+        overlay: output.overlay.iter().map(steps).collect(),
+        dots: output.dots.iter().map(steps).collect(),
+        bands: output
+            .bands
+            .iter()
+            .map(|b| Band {
+                name: format!("{} {label}", b.name),
+                upper: steps(&b.upper),
+                lower: steps(&b.lower),
+                tone: b.tone,
+            })
+            .collect(),
+        panel: output.panel.iter().map(steps).collect(),
+        markers: output
+            .markers
+            .iter()
+            .filter_map(|m| {
+                let k = known_at(m.point[0])?;
+                Some(Marker {
+                    point: [lower.center(k), m.point[1]],
+                    ..*m
+                })
+            })
+            .collect(),
+        values: output.values.iter().map(steps).collect(),
+        guides: output.guides.clone(),
+        panel_markers: output
+            .panel_markers
+            .iter()
+            .filter_map(|m| {
+                let k = known_at(m.point[0])?;
+                Some(Marker {
+                    point: [lower.center(k), m.point[1]],
+                    ..*m
+                })
+            })
+            .collect(),
+        divergences: output
+            .divergences
+            .iter()
+            .map(|d| Divergence {
+                price: steps(&d.price),
+                indicator: steps(&d.indicator),
+            })
+            .collect(),
+        levels: output
+            .levels
+            .iter()
+            .filter_map(|l| {
+                let from = lower.center(known_at(l.x[0])?);
+                let to = known_at(l.x[1]).map_or(lower.max_x(), |k| lower.center(k));
+                Some(Level {
+                    name: format!("{} {label}", l.name),
+                    x: [from, to],
+                    ..l.clone()
+                })
+            })
+            .collect(),
+        sub_panels: output
+            .sub_panels
+            .iter()
+            .map(|p| SubPanel {
+                name: format!("{} {label}", p.name),
+                series: p.series.iter().map(steps).collect(),
+                highlights: p.highlights.iter().map(steps).collect(),
+                bars: p.bars.iter().map(steps).collect(),
+                label: p.label.clone(),
+                rebase: p.rebase,
+                guides: p.guides.clone(),
+            })
+            .collect(),
+        glyphs: output
+            .glyphs
+            .iter()
+            .filter_map(|g| {
+                let k = known_at(g.point[0])?;
+                Some(Glyph {
+                    point: [lower.center(k), g.point[1]],
+                    ..g.clone()
+                })
+            })
+            .collect(),
+        signals: output
+            .signals
+            .iter()
+            .map(|s| SignalSeries {
+                name: format!("{} {label}", s.name),
+                points: s
+                    .points
+                    .iter()
+                    .filter_map(|p| {
+                        let k = known_at(p.ts as f64)?;
+                        Some(SignalPoint {
+                            ts: k.t_open,
+                            ..p.clone()
+                        })
+                    })
+                    .collect(),
+            })
+            .collect(),
+        headers: output
+            .headers
+            .iter()
+            .map(|h| Header {
+                text: format!("{} {label}", h.text),
+                ..h.clone()
+            })
+            .collect(),
+        volume_highlights: output
+            .volume_highlights
+            .iter()
+            .map(|s| Series {
+                points: s
+                    .points
+                    .iter()
+                    .filter_map(|p| Some([lower.center(known_at(p[0])?), p[1]]))
+                    .collect(),
+                ..s.clone()
+            })
+            .collect(),
+    }
+}
+
+/// Value of the series at every lower candle, the last one at or before the middle
+/// of the last higher candle closed by then.
+fn steps(series: &Series, higher: &Data, lower: &Data, label: &str) -> Series {
+    Series {
+        name: format!("{} {label}", series.name),
+        color: series.color,
+        points: lower
+            .vals
+            .iter()
+            .filter_map(|k| {
+                let closed = higher.vals.partition_point(|h| h.t_close <= k.t_close);
+                let x = higher.center(&higher.vals[closed.checked_sub(1)?]);
+                let idx = series.points.partition_point(|p| p[0] <= x);
+                Some([lower.center(k), series.points[idx.checked_sub(1)?][1]])
+            })
+            .collect(),
+    }
+}
+
+/// Lower candle closing with the higher candle at `x`.
+fn known_at<'a>(x: f64, higher: &Data, lower: &'a Data) -> Option<&'a Kline> {
+    let h = &higher.vals[higher
+        .vals
+        .partition_point(|h| h.t_open as f64 <= x)
+        .checked_sub(1)?];
+    lower.vals.iter().find(|k| k.t_close >= h.t_close)
+}
+
+#[cfg(test)]
+mod timeframe_tests {
+    use crate::{
+        netstrat::indicators::{Indicator, MarkerKind, Sma},
+        sources::binance::mock,
+    };
+
+    use super::*;
+
+    const HOUR: i64 = 60 * 60 * 1000;
+
+    #[test]
+    fn test_no_lookahead() {
+        let lower = Data::new(mock::klines(0, HOUR, 72));
+        let higher = resample(&lower, Interval::Day).unwrap();
+        let output = Sma { period: 1 }.compute(&higher);
+
+        let projected = project(&output, &higher, &lower, "1d");
+        let series = &projected.overlay[0];
+
+        assert_eq!(series.name, "SMA(1) 1d");
+        // nothing until the first day closes with its last hour
+        assert_eq!(series.points[0][0], lower.center(&lower.vals[23]));
+        let at = |hour: usize| {
+            series
+                .points
+                .iter()
+                .find(|p| p[0] == lower.center(&lower.vals[hour]))
+                .map(|p| p[1])
+        };
+        // close of the first day is the close of its last hour
+        assert_eq!(at(23), Some(lower.vals[23].close as f64));
+        assert_eq!(at(46), Some(lower.vals[23].close as f64));
+        assert_eq!(at(47), Some(lower.vals[47].close as f64));
+        assert_eq!(at(71), Some(lower.vals[71].close as f64));
+    }
+
+    #[test]
+    fn test_forming_higher_candle_not_shown() {
+        let lower = Data::new(mock::klines(0, HOUR, 30));
+        let higher = resample(&lower, Interval::Day).unwrap();
+        let output = Sma { period: 1 }.compute(&higher);
+
+        let projected = project(&output, &higher, &lower, "1d");
+
+        assert!(projected.overlay[0]
+            .points
+            .iter()
+            .all(|p| p[1] == lower.vals[23].close as f64));
+    }
+
+    #[test]
+    fn test_markers_moved_to_close() {
+        let lower = Data::new(mock::klines(0, HOUR, 48));
+        let higher = resample(&lower, Interval::Day).unwrap();
+        let output = IndicatorOutput {
+            markers: vec![Marker {
+                kind: MarkerKind::Buy,
+                point: [higher.center(&higher.vals[0]), 1.0],
+            }],
+            ..Default::default()
+        };
+
+        let projected = project(&output, &higher, &lower, "1d");
+
+        assert_eq!(
+            projected.markers[0].point,
+            [lower.center(&lower.vals[23]), 1.0]
+        );
+    }
+
+    #[test]
+    fn test_resample_only_wider() {
+        let data = Data::new(mock::klines(0, HOUR, 48));
+
+        assert!(resample(&data, Interval::Minute).is_none());
+        assert!(resample(&data, Interval::Hour).is_none());
+        assert_eq!(resample(&data, Interval::Day).unwrap().vals.len(), 2);
+        assert_eq!(parse("1d"), Some(Interval::Day));
+        assert_eq!(parse("5s"), None);
+    }
+}
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Indicator, IndicatorOutput, Series, SubPanel};
+
+const YEAR_MILLIS: f64 = 365.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// Annualized standard deviation of log returns of closes.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Volatility {
+    pub window: usize,
+}
+
+impl Default for Volatility {
+    fn default() -> Self {
+        Self { window: 20 }
+    }
+}
+
+impl Indicator for Volatility {
+    fn id(&self) -> &'static str {
+        "volatility"
+    }
+
+    fn name(&self) -> String {
+        format!("Volatility({})", self.window)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.add(
+            egui::DragValue::new(&mut self.window)
+                .clamp_range(2..=1000)
+                .prefix("window: "),
+        )
+        .changed()
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let vals = volatility(data, self.window);
+        let series = Series::from_values(self.name(), data, &vals);
+
+        IndicatorOutput {
+            values: vec![series.clone()],
+            sub_panels: vec![SubPanel {
+                name: self.name(),
+                series: vec![series],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+}
+
+/// Realized volatility in percent annualized by the candle interval.
+pub fn volatility(data: &Data, window: usize) -> Vec<Option<f64>> {
+    let interval = match data.vals.first() {
+        Some(k) => (k.t_close - k.t_open + 1) as f64,
+        None => return vec![],
+    };
+    let per_year = (YEAR_MILLIS / interval).sqrt();
+
+    math::log_returns(&data.closes())
+        .into_iter()
+        .skip(1)
+        .collect::<Option<Vec<f64>>>()
+        .map_or(vec![None; data.vals.len()], |returns| {
+            let mut res = vec![None];
+            res.extend(
+                math::stdev(&returns, window)
+                    .into_iter()
+                    .map(|v| v.map(|v| v * per_year * 100.0)),
+            );
+            res
+        })
+}
+
+#[cfg(test)]
+mod volatility_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    #[test]
+    fn test_annualized_by_interval() {
+        let day = 24 * 60 * 60 * 1000;
+        let e = std::f64::consts::E;
+        let data = Data::new(
+            [1.0, e, 1.0, e]
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Kline {
+                    t_open: i as i64 * day,
+                    t_close: (i as i64 + 1) * day - 1,
+                    close: *c as f32,
+                    ..Default::default()
+                })
+                .collect(),
+        );
+
+        let vals = volatility(&data, 3);
+
+        // returns 1, -1, 1: mean 1/3, sample stdev sqrt(4/3)
+        let expected = (4.0f64 / 3.0).sqrt() * 365f64.sqrt() * 100.0;
+        assert_eq!(vals[..3], [None, None, None]);
+        assert!((vals[3].unwrap() - expected).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+use crate::netstrat::data::Data;
+
+use super::{math, Indicator, IndicatorOutput, Marker, MarkerKind, Series, SubPanel};
+
+/// Guide lines of the panel in standard deviations.
+const GUIDES: f64 = 2.0;
+
+/// Distance of closes from their simple moving average in standard deviations of the same
+/// window, the way Bollinger bands measure it.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZScore {
+    pub period: usize,
+    /// Readings beyond it are marked on the candles, nothing is marked when `None`.
+    pub extremes: Option<f64>,
+}
+
+impl Default for ZScore {
+    fn default() -> Self {
+        Self {
+            period: 20,
+            extremes: None,
+        }
+    }
+}
+
+impl Indicator for ZScore {
+    fn id(&self) -> &'static str {
+        "zscore"
+    }
+
+    fn name(&self) -> String {
+        format!("Z-score({})", self.period)
+    }
+
+    fn params(&self) -> serde_json::Value {
+        super::to_params(self)
+    }
+
+    fn set_params(&mut self, params: serde_json::Value) -> serde_json::Result<()> {
+        super::from_params(self, params)
+    }
+
+    #[cfg(feature = "gui")]
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = super::moving_average::period_ui(ui, &mut self.period);
+        let mut marked = self.extremes.is_some();
+        if ui.checkbox(&mut marked, "mark extremes").changed() {
+            self.extremes = marked.then_some(GUIDES);
+            changed = true;
+        }
+        if let Some(threshold) = &mut self.extremes {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(threshold)
+                        .speed(0.1)
+                        .clamp_range(0.1..=10.0)
+                        .prefix("beyond: "),
+                )
+                .changed();
+        }
+
+        changed
+    }
+
+    fn compute(&self, data: &Data) -> IndicatorOutput {
+        let vals = math::zscore(&data.closes(), self.period);
+        let series = Series::from_values(self.name(), data, &vals);
+
+        IndicatorOutput {
+            values: vec![series.clone()],
+            markers: self
+                .extremes
+                .map(|threshold| extremes(data, &vals, threshold))
+                .unwrap_or_default(),
+            sub_panels: vec![SubPanel {
+                name: self.name(),
+                series: vec![series],
+                guides: vec![-GUIDES, GUIDES],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+}
+
+/// Sell marks on closes where the reading goes above `threshold` and buy marks where it goes
+/// below `-threshold`, readings staying beyond it aren't marked again.
+fn extremes(data: &Data, vals: &[Option<f64>], threshold: f64) -> Vec<Marker> {
+    let side = |v: &Option<f64>| match v {
+        Some(v) if *v > threshold => Some(MarkerKind::Sell),
+        Some(v) if *v < -threshold => Some(MarkerKind::Buy),
+        _ => None,
+    };
+
+    vals.iter()
+        .enumerate()
+        .filter_map(|(i, v)| {
+            let kind = side(v)?;
+            let previous = i.checked_sub(1).and_then(|p| side(&vals[p]));
+            let k = &data.vals[i];
+            (previous != Some(kind)).then_some(Marker {
+                kind,
+                point: [data.center(k), k.close as f64],
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod zscore_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    #[test]
+    fn test_extremes_marked_once() {
+        let closes = [10.0, 10.0, 11.0, 10.0, 10.0, 20.0, 30.0, 10.0, 0.0];
+        let data = Data::new(
+            closes
+                .iter()
+                .enumerate()
+                .map(|(i, c)| Kline {
+                    t_open: i as i64 * 10,
+                    t_close: (i as i64 + 1) * 10 - 1,
+                    close: *c,
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        let zscore = ZScore {
+            period: 4,
+            extremes: Some(1.1),
+        };
+
+        let out = zscore.compute(&data);
+
+        let marks: Vec<_> = out.markers.iter().map(|m| (m.kind, m.point[1])).collect();
+        assert_eq!(
+            marks,
+            vec![(MarkerKind::Sell, 20.0), (MarkerKind::Buy, 0.0)]
+        );
+        assert_eq!(out.sub_panels[0].guides, vec![-2.0, 2.0]);
+        assert!(ZScore::default().compute(&data).markers.is_empty());
+    }
+}
@@ -0,0 +1,400 @@
+//! Candles as InfluxDB line protocol, written to a file or to the write endpoint of
+//! InfluxDB v2 in batches.
+//!
+//! Every candle is a point of the `klines` measurement tagged with its symbol and interval,
+//! timestamped with its open time in nanoseconds.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use quick_error::ResultExt;
+use reqwest::{header::RETRY_AFTER, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::errors::{Error, Result};
+use crate::sources::binance::{Interval, Kline};
+
+use super::manifest::{HashWriter, Written};
+
+pub const MEASUREMENT: &str = "klines";
+pub const DEFAULT_URL: &str = "http://localhost:8086";
+pub const DEFAULT_BATCH: usize = 5000;
+const PATH_WRITE: &str = "/api/v2/write";
+const MAX_RETRIES: usize = 3;
+const DEFAULT_RETRY_AFTER_SECS: u64 = 1;
+const MAX_RETRY_AFTER_SECS: u64 = 60;
+
+/// Endpoint and credentials of an InfluxDB v2 to write to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InfluxConfig {
+    pub url: String,
+    pub org: String,
+    pub bucket: String,
+    pub token: String,
+    /// Points written per request.
+    pub batch: usize,
+}
+
+impl Default for InfluxConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_URL.to_string(),
+            org: Default::default(),
+            bucket: Default::default(),
+            token: Default::default(),
+            batch: DEFAULT_BATCH,
+        }
+    }
+}
+
+/// Handle to the InfluxDB settings, clones share the config.
+#[derive(Debug, Clone, Default)]
+pub struct Influx {
+    config: Arc<RwLock<InfluxConfig>>,
+}
+
+impl Influx {
+    pub fn config(&self) -> InfluxConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: InfluxConfig) {
+        *self.config.write().unwrap() = config;
+    }
+}
+
+/// Outcome of a write, batches which failed don't stop the others.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub points: usize,
+    pub failed: usize,
+    /// Message of the last batch which failed.
+    pub error: Option<String>,
+}
+
+/// Escapes commas, equal signs and spaces of a tag value, line breaks can't be written
+/// and are replaced by spaces.
+pub fn escape_tag(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            ',' | '=' | ' ' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push_str("\\ "),
+            '\\' => escaped.push_str("\\\\"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Point of the candle, optional fields are left out when missing.
+pub fn line(symbol: &str, interval: Interval, k: &Kline) -> String {
+    let mut fields = format!(
+        "open={},high={},low={},close={},volume={}",
+        k.open, k.high, k.low, k.close, k.volume
+    );
+    let optional = [
+        ("quote_asset_volume", k.quote_asset_volume),
+        ("taker_buy_base_asset_volume", k.taker_buy_base_asset_volume),
+        (
+            "taker_buy_quote_asset_volume",
+            k.taker_buy_quote_asset_volume,
+        ),
+    ];
+    optional.into_iter().for_each(|(name, v)| {
+        if let Some(v) = v {
+            fields.push_str(&format!(",{name}={v}"));
+        }
+    });
+    if let Some(trades) = k.number_of_trades {
+        fields.push_str(&format!(",number_of_trades={trades}i"));
+    }
+    fields.push_str(&format!(",closed={}", k.closed));
+
+    format!(
+        "{MEASUREMENT},symbol={},interval={} {fields} {}",
+        escape_tag(symbol),
+        interval.as_str(),
+        k.t_open as i128 * 1_000_000,
+    )
+}
+
+/// Writes points of the klines to a file returning its absolute path and hash.
+pub fn to_file(path: &Path, symbol: &str, interval: Interval, klines: &[Kline]) -> Result<Written> {
+    let f = File::create(path).context(path)?;
+    let abs_path = path.canonicalize().context(path)?;
+    info!("Saving line protocol to file: {abs_path:?}");
+
+    let mut hashed = HashWriter::new(f);
+    write_lines(&mut hashed, symbol, interval, klines).context(path)?;
+
+    Ok(Written {
+        path: abs_path,
+        sha256: hashed.sha256(),
+    })
+}
+
+/// Appends points of the klines to a file returning its absolute path.
+pub fn append_to_file(
+    path: &Path,
+    symbol: &str,
+    interval: Interval,
+    klines: &[Kline],
+) -> Result<PathBuf> {
+    let f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context(path)?;
+    let abs_path = path.canonicalize().context(path)?;
+    info!("Appending line protocol to file: {abs_path:?}");
+
+    write_lines(f, symbol, interval, klines).context(path)?;
+
+    Ok(abs_path)
+}
+
+fn write_lines(
+    w: impl Write,
+    symbol: &str,
+    interval: Interval,
+    klines: &[Kline],
+) -> std::io::Result<()> {
+    let mut wtr = BufWriter::new(w);
+    for k in klines {
+        writeln!(wtr, "{}", line(symbol, interval, k))?;
+    }
+    wtr.flush()
+}
+
+/// Writes points of the klines in batches of the config, retrying batches rejected with 429
+/// or 5xx.
+pub async fn write(
+    config: &InfluxConfig,
+    symbol: &str,
+    interval: Interval,
+    klines: &[Kline],
+) -> Result<Summary> {
+    let url = Url::parse(&config.url)
+        .and_then(|u| u.join(PATH_WRITE))
+        .map_err(|_| Error::Validation(format!("invalid InfluxDB url {:?}", config.url)))?;
+    if config.bucket.trim().is_empty() {
+        return Err(Error::Validation("InfluxDB bucket is not set".to_string()));
+    }
+
+    let client = reqwest::Client::new();
+    let mut summary = Summary::default();
+    for batch in klines.chunks(config.batch.max(1)) {
+        let body = batch
+            .iter()
+            .map(|k| line(symbol, interval, k))
+            .collect::<Vec<_>>()
+            .join("\n");
+        match write_batch(&client, &url, config, body).await {
+            Ok(()) => summary.points += batch.len(),
+            Err(err) => {
+                warn!(
+                    "failed to write batch of {} points: {}",
+                    batch.len(),
+                    err.chain()
+                );
+                summary.failed += batch.len();
+                summary.error = Some(err.user_message());
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+async fn write_batch(
+    client: &reqwest::Client,
+    url: &Url,
+    config: &InfluxConfig,
+    body: String,
+) -> Result<()> {
+    let mut attempt = 0;
+    loop {
+        let resp = client
+            .post(url.clone())
+            .query(&[
+                ("org", config.org.as_str()),
+                ("bucket", config.bucket.as_str()),
+                ("precision", "ns"),
+            ])
+            .header("Authorization", format!("Token {}", config.token))
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body(body.clone())
+            .send()
+            .await
+            .context(url.as_str())?;
+
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+        if retryable && attempt < MAX_RETRIES {
+            attempt += 1;
+            let secs = resp
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_RETRY_AFTER_SECS)
+                .min(MAX_RETRY_AFTER_SECS);
+            warn!("InfluxDB responded with {status}. Retrying in {secs}s. Attempt: {attempt}.");
+            tokio::time::sleep(Duration::from_secs(secs)).await;
+            continue;
+        }
+
+        let msg = resp.text().await.unwrap_or_default();
+        return Err(Error::Influx(url.to_string(), status.as_u16(), msg));
+    }
+}
+
+#[cfg(test)]
+mod influx_tests {
+    use wiremock::{
+        matchers::{header, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_escape_tag() {
+        assert_eq!(escape_tag("BTCUSDT"), "BTCUSDT");
+        assert_eq!(escape_tag("BTC USDT"), "BTC\\ USDT");
+        assert_eq!(escape_tag("a,b=c"), "a\\,b\\=c");
+        assert_eq!(escape_tag("a\\b"), "a\\\\b");
+        assert_eq!(escape_tag("a\nb"), "a\\ b");
+        assert_eq!(escape_tag("币安"), "币安");
+    }
+
+    #[test]
+    fn test_line() {
+        let mut k = Kline {
+            t_open: 1_656_633_600_000,
+            open: 1.5,
+            high: 2.0,
+            low: 1.0,
+            close: 1.75,
+            volume: 10.0,
+            t_close: 1_656_633_659_999,
+            number_of_trades: Some(42),
+            closed: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            line("BTC,USDT x", Interval::Hour, &k),
+            "klines,symbol=BTC\\,USDT\\ x,interval=1h \
+             open=1.5,high=2,low=1,close=1.75,volume=10,number_of_trades=42i,closed=true \
+             1656633600000000000"
+        );
+
+        k.number_of_trades = None;
+        k.quote_asset_volume = Some(17.5);
+        assert_eq!(
+            line("BTCUSDT", Interval::Minute, &k),
+            "klines,symbol=BTCUSDT,interval=1m \
+             open=1.5,high=2,low=1,close=1.75,volume=10,quote_asset_volume=17.5,closed=true \
+             1656633600000000000"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_batches_with_retry() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(PATH_WRITE))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(PATH_WRITE))
+            .and(query_param("bucket", "candles"))
+            .and(query_param("precision", "ns"))
+            .and(header("Authorization", "Token secret"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        let config = InfluxConfig {
+            url: server.uri(),
+            org: "org".to_string(),
+            bucket: "candles".to_string(),
+            token: "secret".to_string(),
+            batch: 2,
+        };
+
+        let summary = write(
+            &config,
+            "BTCUSDT",
+            Interval::Minute,
+            &mock::klines(0, 60_000, 5),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            summary,
+            Summary {
+                points: 5,
+                failed: 0,
+                error: None
+            }
+        );
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(
+            String::from_utf8_lossy(&requests[3].body).lines().count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_batches_are_counted() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("unauthorized access"))
+            .mount(&server)
+            .await;
+        let config = InfluxConfig {
+            url: server.uri(),
+            bucket: "candles".to_string(),
+            batch: 2,
+            ..Default::default()
+        };
+
+        let summary = write(
+            &config,
+            "BTCUSDT",
+            Interval::Minute,
+            &mock::klines(0, 60_000, 3),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(summary.points, 0);
+        assert_eq!(summary.failed, 3);
+        assert_eq!(
+            summary.error.as_deref(),
+            Some("InfluxDB rejected the token")
+        );
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,201 @@
+//! Notes of all symbols listed as a trade journal, with the P&L of the notes of trades.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use quick_error::ResultExt;
+use serde::Serialize;
+use tracing::info;
+
+use crate::errors::Result;
+
+use super::notes::{Direction, Notes};
+
+pub const DEFAULT_FEE_BPS: f64 = 10.0;
+pub const FILE_STEM: &str = "journal";
+
+/// Journal settings, clones share the values.
+#[derive(Clone, Debug)]
+pub struct JournalConfig {
+    /// Fee charged on the notional of both fills in basis points.
+    fee_bps: Arc<RwLock<f64>>,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            fee_bps: Arc::new(RwLock::new(DEFAULT_FEE_BPS)),
+        }
+    }
+}
+
+impl JournalConfig {
+    pub fn fee_bps(&self) -> f64 {
+        *self.fee_bps.read().unwrap()
+    }
+
+    pub fn set_fee_bps(&self, fee_bps: f64) {
+        *self.fee_bps.write().unwrap() = fee_bps.max(0.0);
+    }
+}
+
+/// Note of the journal with the symbol it belongs to.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Row {
+    pub symbol: String,
+    /// Position of the note among the notes of the symbol.
+    #[serde(skip)]
+    pub idx: usize,
+    /// Time in millis.
+    pub ts: i64,
+    pub price: f64,
+    pub direction: Option<Direction>,
+    pub entry: Option<f64>,
+    pub exit: Option<f64>,
+    pub size: Option<f64>,
+    /// Profit after fees of both fills.
+    pub pnl: Option<f64>,
+    pub text: String,
+}
+
+/// Notes of all symbols ordered by time, the oldest first.
+pub fn rows(notes: &Notes, fee_bps: f64) -> Vec<Row> {
+    let mut rows: Vec<_> = notes
+        .iter()
+        .flat_map(|(symbol, notes)| {
+            notes.iter().enumerate().map(move |(idx, n)| Row {
+                symbol: symbol.to_string(),
+                idx,
+                ts: n.ts,
+                price: n.price,
+                direction: n.direction,
+                entry: n.entry,
+                exit: n.exit,
+                size: n.size,
+                pnl: n.pnl(fee_bps),
+                text: n.text.clone(),
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| (a.ts, &a.symbol).cmp(&(b.ts, &b.symbol)));
+
+    rows
+}
+
+/// Sum of the known P&L of the rows.
+pub fn total_pnl(rows: &[Row]) -> f64 {
+    rows.iter().filter_map(|r| r.pnl).sum()
+}
+
+pub fn file_name(extension: &str) -> String {
+    format!("{FILE_STEM}.{extension}")
+}
+
+/// Writes rows to csv file returning its absolute path.
+pub fn to_csv(path: &Path, rows: &[Row]) -> Result<PathBuf> {
+    let f = File::create(path).context(path)?;
+    let abs_path = path.canonicalize().context(path)?;
+    info!("Saving journal to file: {abs_path:?}");
+
+    let mut wtr = csv::Writer::from_writer(f);
+    for r in rows {
+        wtr.serialize(r).context(path)?;
+    }
+    wtr.flush().context(path)?;
+
+    Ok(abs_path)
+}
+
+/// Writes rows to json file returning its absolute path.
+pub fn to_json(path: &Path, rows: &[Row]) -> Result<PathBuf> {
+    let f = File::create(path).context(path)?;
+    let abs_path = path.canonicalize().context(path)?;
+    info!("Saving journal to file: {abs_path:?}");
+
+    serde_json::to_writer_pretty(f, rows).context(path)?;
+
+    Ok(abs_path)
+}
+
+#[cfg(test)]
+mod journal_tests {
+    use crate::netstrat::notes::Note;
+
+    use super::*;
+
+    fn notes() -> Notes {
+        let mut notes = Notes::default();
+        notes.set(
+            "ETHUSDT",
+            vec![
+                Note {
+                    ts: 3_000,
+                    text: "breakout".to_string(),
+                    direction: Some(Direction::Long),
+                    entry: Some(100.0),
+                    exit: Some(110.0),
+                    size: Some(1.0),
+                    ..Default::default()
+                },
+                Note {
+                    ts: 1_000,
+                    text: "CPI print".to_string(),
+                    ..Default::default()
+                },
+            ],
+        );
+        notes.set(
+            "BTCUSDT",
+            vec![Note {
+                ts: 2_000,
+                text: "fade".to_string(),
+                direction: Some(Direction::Short),
+                entry: Some(50.0),
+                exit: Some(60.0),
+                size: Some(0.5),
+                ..Default::default()
+            }],
+        );
+        notes
+    }
+
+    #[test]
+    fn test_rows_across_symbols_by_time() {
+        let rows = rows(&notes(), 0.0);
+
+        let order: Vec<_> = rows.iter().map(|r| (r.symbol.as_str(), r.idx)).collect();
+        assert_eq!(order, vec![("ETHUSDT", 1), ("BTCUSDT", 0), ("ETHUSDT", 0)]);
+        assert_eq!(rows[0].pnl, None);
+        assert_eq!(rows[1].pnl, Some(-5.0));
+        assert_eq!(rows[2].pnl, Some(10.0));
+        assert_eq!(total_pnl(&rows), 5.0);
+    }
+
+    #[test]
+    fn test_export() {
+        let dir = std::env::temp_dir();
+        let csv_path = dir.join(format!("netstrat_{}", file_name("csv")));
+        let json_path = dir.join(format!("netstrat_{}", file_name("json")));
+        let rows = rows(&notes(), 0.0);
+
+        let abs_csv = to_csv(&csv_path, &rows).unwrap();
+        let csv = std::fs::read_to_string(&abs_csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "symbol,ts,price,direction,entry,exit,size,pnl,text"
+        );
+        assert_eq!(lines[1], "ETHUSDT,1000,0.0,,,,,,CPI print");
+        assert_eq!(lines[2], "BTCUSDT,2000,0.0,short,50.0,60.0,0.5,-5.0,fade");
+
+        let abs_json = to_json(&json_path, &rows).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&abs_json).unwrap()).unwrap();
+        assert_eq!(json[2]["direction"], "long");
+        assert_eq!(json[2]["pnl"], 10.0);
+
+        std::fs::remove_file(abs_csv).unwrap();
+        std::fs::remove_file(abs_json).unwrap();
+    }
+}
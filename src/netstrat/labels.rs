@@ -0,0 +1,112 @@
+//! Timestamps formatted for plot labels. The same ticks and hovered points are labeled on
+//! every frame, so the formatted strings are cached per second they show.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use chrono::DateTime;
+
+const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// Labels kept before the cache is cleared, far more than the ticks of all plots.
+const CAPACITY: usize = 4096;
+/// Label of times out of the range of dates, e.g. of plot coordinates dragged far out.
+pub const INVALID: &str = "—";
+
+thread_local! {
+    static CACHE: RefCell<Labels> = RefCell::new(Labels::default());
+}
+
+#[derive(Default)]
+struct Labels {
+    by_secs: HashMap<i64, String>,
+    hits: usize,
+    misses: usize,
+}
+
+impl Labels {
+    fn get(&mut self, secs: i64) -> String {
+        if let Some(label) = self.by_secs.get(&secs) {
+            self.hits += 1;
+            return label.clone();
+        }
+
+        self.misses += 1;
+        if self.by_secs.len() >= CAPACITY {
+            self.by_secs.clear();
+        }
+        let label = format_secs(secs);
+        self.by_secs.insert(secs, label.clone());
+        label
+    }
+}
+
+/// Time in millis as a label, [`INVALID`] when out of range or not a number.
+pub fn format_ts(ts: f64) -> String {
+    if !ts.is_finite() {
+        return INVALID.to_string();
+    }
+    // saturates far out of range which is then rejected as any other invalid date
+    let secs = (ts / 1000f64).floor() as i64;
+    CACHE.with(|c| c.borrow_mut().get(secs))
+}
+
+/// Labels of the thread taken from the cache and formatted.
+pub fn stats() -> (usize, usize) {
+    CACHE.with(|c| {
+        let c = c.borrow();
+        (c.hits, c.misses)
+    })
+}
+
+fn format_secs(secs: i64) -> String {
+    match DateTime::from_timestamp(secs, 0) {
+        Some(datetime) => datetime.format(FORMAT).to_string(),
+        None => INVALID.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod labels_tests {
+    use super::*;
+
+    #[test]
+    fn test_cached() {
+        let (hits, misses) = stats();
+
+        (0..3).for_each(|_| {
+            assert_eq!(format_ts(60_500.0), "1970-01-01 00:01:00");
+            assert_eq!(format_ts(61_000.0), "1970-01-01 00:01:01");
+        });
+        assert_eq!(format_ts(f64::MAX), INVALID);
+
+        assert_eq!(stats(), (hits + 4, misses + 3));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(format_ts(f64::NAN), INVALID);
+        assert_eq!(format_ts(f64::INFINITY), INVALID);
+        assert_eq!(format_ts(f64::NEG_INFINITY), INVALID);
+        assert_eq!(format_ts(-1e20), INVALID);
+        assert_eq!(format_ts(1e20), INVALID);
+    }
+
+    #[test]
+    fn test_extreme() {
+        assert_eq!(format_ts(-1.0), "1969-12-31 23:59:59");
+        assert_eq!(format_ts(-62_135_596_800_000.0), "0001-01-01 00:00:00");
+        assert_eq!(format_ts(253_402_300_799_999.0), "9999-12-31 23:59:59");
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut labels = Labels::default();
+
+        (0..CAPACITY as i64 + 1).for_each(|secs| {
+            labels.get(secs);
+        });
+
+        assert_eq!(labels.by_secs.len(), 1);
+        assert_eq!(labels.get(0), "1970-01-01 00:00:00");
+        assert_eq!(labels.misses, CAPACITY + 2);
+    }
+}
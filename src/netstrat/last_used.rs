@@ -0,0 +1,182 @@
+//! Interval and range length last loaded for each symbol, a selected symbol starts with them
+//! ending at the time it is selected.
+
+use std::sync::{Arc, RwLock};
+
+use chrono::{Duration, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::sources::binance::Interval;
+
+use super::props::Props;
+
+/// Symbols remembered, the least recently loaded is forgotten first.
+pub const DEFAULT_CAP: usize = 100;
+
+/// Interval and range length of a symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub symbol: String,
+    /// Name of the interval, e.g. `1h`.
+    pub interval: String,
+    pub span_millis: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LastUsedConfig {
+    pub enabled: bool,
+    pub cap: usize,
+    /// The most recently loaded last.
+    pub entries: Vec<Entry>,
+}
+
+impl Default for LastUsedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cap: DEFAULT_CAP,
+            entries: Default::default(),
+        }
+    }
+}
+
+/// Handle to the remembered symbols, clones share them.
+#[derive(Debug, Clone, Default)]
+pub struct LastUsed {
+    config: Arc<RwLock<LastUsedConfig>>,
+}
+
+impl LastUsed {
+    pub fn config(&self) -> LastUsedConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: LastUsedConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.read().unwrap().enabled
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.config.write().unwrap().enabled = enabled;
+    }
+
+    /// Remembers the interval and range length of the props as the most recent ones of the
+    /// symbol, forgetting the least recent symbol beyond the cap.
+    pub fn remember(&self, symbol: &str, props: &Props) {
+        let mut config = self.config.write().unwrap();
+        if !config.enabled || symbol.is_empty() {
+            return;
+        }
+
+        config.entries.retain(|e| e.symbol != symbol);
+        config.entries.push(Entry {
+            symbol: symbol.to_string(),
+            interval: props.interval.as_str().to_string(),
+            span_millis: (props.end_time() - props.start_time()).num_milliseconds(),
+        });
+        let excess = config.entries.len().saturating_sub(config.cap.max(1));
+        config.entries.drain(..excess);
+    }
+
+    /// Props of the remembered interval and range length of the symbol ending at `now`,
+    /// other props are taken from `base`.
+    pub fn props(&self, symbol: &str, base: &Props, now: NaiveDateTime) -> Option<Props> {
+        let config = self.config.read().unwrap();
+        if !config.enabled {
+            return None;
+        }
+
+        let entry = config.entries.iter().rev().find(|e| e.symbol == symbol)?;
+        let interval = Interval::parse(&entry.interval)?;
+        let end = now.with_nanosecond(0).unwrap_or(now);
+        let start = end.checked_sub_signed(Duration::milliseconds(entry.span_millis))?;
+
+        Some(Props {
+            limit: base.limit,
+            ..Props::new(start, end, interval, base.empty_buckets)
+        })
+    }
+}
+
+#[cfg(test)]
+mod last_used_tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2022, 7, 1)
+            .unwrap()
+            .and_hms_milli_opt(12, 0, 0, 500)
+            .unwrap()
+    }
+
+    fn props(interval: Interval, hours: i64) -> Props {
+        let start = now() - Duration::hours(hours);
+        Props::new(start, now(), interval, Default::default())
+    }
+
+    #[test]
+    fn test_props_end_now() {
+        let last_used = LastUsed::default();
+        last_used.remember("BTCUSDT", &props(Interval::Day, 24 * 30));
+        last_used.remember("DOGEUSDT", &props(Interval::FiveMinutes, 6));
+
+        let later = now() + Duration::days(2);
+        let btc = last_used
+            .props("BTCUSDT", &Props::default(), later)
+            .unwrap();
+        assert_eq!(btc.interval, Interval::Day);
+        assert_eq!(
+            btc.end_time().naive_utc(),
+            later.with_nanosecond(0).unwrap()
+        );
+        assert_eq!(btc.end_time() - btc.start_time(), Duration::days(30));
+
+        let doge = last_used
+            .props("DOGEUSDT", &Props::default(), later)
+            .unwrap();
+        assert_eq!(doge.interval, Interval::FiveMinutes);
+        assert_eq!(doge.candles(), 6 * 12);
+
+        assert_eq!(last_used.props("ETHUSDT", &Props::default(), later), None);
+    }
+
+    #[test]
+    fn test_least_recent_forgotten() {
+        let last_used = LastUsed::default();
+        last_used.set_config(LastUsedConfig {
+            cap: 2,
+            ..Default::default()
+        });
+
+        last_used.remember("A", &props(Interval::Hour, 1));
+        last_used.remember("B", &props(Interval::Hour, 2));
+        last_used.remember("A", &props(Interval::Hour, 3));
+        last_used.remember("C", &props(Interval::Hour, 4));
+
+        let symbols: Vec<_> = last_used
+            .config()
+            .entries
+            .into_iter()
+            .map(|e| (e.symbol, e.span_millis / 3_600_000))
+            .collect();
+        assert_eq!(symbols, vec![("A".to_string(), 3), ("C".to_string(), 4)]);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let last_used = LastUsed::default();
+        last_used.remember("A", &props(Interval::Hour, 1));
+
+        last_used.set_enabled(false);
+        last_used.remember("B", &props(Interval::Hour, 1));
+
+        assert_eq!(last_used.props("A", &Props::default(), now()), None);
+        assert_eq!(last_used.config().entries.len(), 1);
+    }
+}
@@ -0,0 +1,242 @@
+//! Command line flags to open the app with a symbol and its candles loaded.
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::{Child, Command},
+};
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use clap::Parser;
+use quick_error::ResultExt;
+
+use crate::{
+    errors::{Error, Result},
+    sources::binance::Interval,
+};
+
+use super::{aggregate::EmptyBuckets, props::Props, view::View};
+
+const DATETIME_FORMATS: [&str; 3] = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"];
+
+#[derive(Debug, Clone, Default, Parser)]
+#[command(name = "netstrat", about = "Charts and strategies on Binance data")]
+pub struct Args {
+    /// Symbol to open, e.g. ETHUSDT.
+    #[arg(long)]
+    pub symbol: Option<String>,
+    /// Candle interval: 1s, 5s, 1m, 5m, 15m, 1h, 4h, 1d or 1w.
+    #[arg(long, requires = "symbol")]
+    pub interval: Option<String>,
+    /// Range up to now, e.g. 30m, 12h, 7d or 2w.
+    #[arg(long, requires = "symbol", conflicts_with_all = ["start", "end"])]
+    pub last: Option<String>,
+    /// Start of the range in UTC, e.g. 2024-01-31 or "2024-01-31 12:00".
+    #[arg(long, requires = "symbol")]
+    pub start: Option<String>,
+    /// End of the range in UTC, now if omitted.
+    #[arg(long, requires = "start")]
+    pub end: Option<String>,
+    /// Shared view to open, as copied from the view menu.
+    #[arg(long, conflicts_with = "symbol")]
+    pub view: Option<String>,
+    /// Candles csv to open, e.g. one written by the export.
+    #[arg(long, conflicts_with_all = ["symbol", "view"])]
+    pub import: Option<PathBuf>,
+    /// Opened from another instance, which alone runs the scheduled jobs, writes the
+    /// session snapshots and saves the app state. Its requests get a smaller part of the
+    /// weight budget of the api.
+    #[arg(long, hide = true)]
+    pub child: bool,
+}
+
+impl Args {
+    /// Props of the flags, ones not given are taken from the defaults.
+    pub fn props(&self, now: NaiveDateTime) -> Result<Props> {
+        let defaults = Props::default();
+        let interval = match &self.interval {
+            Some(s) => Interval::parse(s)
+                .ok_or_else(|| Error::Validation(format!("unknown interval {s:?}")))?,
+            None => defaults.interval,
+        };
+        let (start, end) = match (&self.last, &self.start, &self.end) {
+            (Some(last), _, _) => {
+                let start = now
+                    .checked_sub_signed(parse_duration(last)?)
+                    .ok_or_else(|| {
+                        Error::Validation(format!("duration {last:?} reaches too far back"))
+                    })?;
+                (start, now)
+            }
+            (None, Some(start), end) => (
+                parse_datetime(start)?,
+                end.as_deref().map_or(Ok(now), parse_datetime)?,
+            ),
+            (None, None, _) => (
+                defaults.start_time().naive_utc(),
+                defaults.end_time().naive_utc(),
+            ),
+        };
+
+        // checked and snapped like a range of the chooser
+        Props::checked(start, end, interval, EmptyBuckets::default(), false, true)
+            .map_err(|err| Error::Validation(format!("range start {start} to end {end}: {err}")))
+    }
+}
+
+/// Starts another instance of the app showing the view in a window of its own.
+pub fn spawn(view: &View) -> Result<Child> {
+    spawn_with(["--view".into(), view.encode().into()])
+}
+
+/// Starts another instance of the app showing the symbol with the default range.
+pub fn spawn_symbol(symbol: &str) -> Result<Child> {
+    spawn_with(["--symbol".into(), symbol.into()])
+}
+
+/// Starts another instance of the app showing candles of the csv in a window of its own.
+pub fn spawn_import(path: &Path) -> Result<Child> {
+    spawn_with(["--import".into(), path.as_os_str().to_owned()])
+}
+
+fn spawn_with(args: [OsString; 2]) -> Result<Child> {
+    let exe = std::env::current_exe().context(Path::new("netstrat"))?;
+    Command::new(&exe)
+        .args(args)
+        .arg("--child")
+        .spawn()
+        .context(exe.as_path())
+        .map_err(Error::from)
+}
+
+/// Duration as a count followed by a unit, e.g. `7d`.
+fn parse_duration(s: &str) -> Result<Duration> {
+    let invalid = || Error::Validation(format!("invalid duration {s:?}, expected e.g. 7d"));
+    let unit = s.chars().last().ok_or_else(invalid)?;
+    let count: i64 = s[..s.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+    let duration = match unit {
+        's' => Duration::try_seconds(count),
+        'm' => Duration::try_minutes(count),
+        'h' => Duration::try_hours(count),
+        'd' => Duration::try_days(count),
+        'w' => Duration::try_weeks(count),
+        _ => None,
+    };
+
+    duration
+        .filter(|d| *d > Duration::zero())
+        .ok_or_else(invalid)
+}
+
+/// Date with an optional time, midnight if the time is omitted.
+fn parse_datetime(s: &str) -> Result<NaiveDateTime> {
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| Error::Validation(format!("invalid date {s:?}, expected e.g. 2024-01-31")))
+}
+
+#[cfg(test)]
+mod launch_tests {
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        parse_datetime("2024-02-01 12:00").unwrap()
+    }
+
+    fn args(flags: &[&str]) -> Args {
+        Args::try_parse_from([&["netstrat"], flags].concat()).unwrap()
+    }
+
+    #[test]
+    fn test_last() {
+        let props = args(&["--symbol", "ETHUSDT", "--interval", "1h", "--last", "7d"])
+            .props(now())
+            .unwrap();
+
+        assert_eq!(props.interval, Interval::Hour);
+        assert_eq!(props.start_time().naive_utc(), now() - Duration::days(7));
+        assert_eq!(props.end_time().naive_utc(), now());
+        assert_eq!(props, props.snapped());
+    }
+
+    #[test]
+    fn test_start_end() {
+        let props = args(&[
+            "--symbol",
+            "ETHUSDT",
+            "--start",
+            "2024-01-01",
+            "--end",
+            "2024-01-02T06:30:00",
+        ])
+        .props(now())
+        .unwrap();
+        assert_eq!(props.interval, Interval::Minute);
+        assert_eq!(
+            props.start_time().naive_utc(),
+            parse_datetime("2024-01-01 00:00").unwrap()
+        );
+        assert_eq!(
+            props.end_time().naive_utc(),
+            parse_datetime("2024-01-02 06:30").unwrap()
+        );
+
+        let open_ended = args(&["--symbol", "ETHUSDT", "--start", "2024-01-31 12:00"])
+            .props(now())
+            .unwrap();
+        assert_eq!(open_ended.end_time().naive_utc(), now());
+    }
+
+    #[test]
+    fn test_child() {
+        assert!(!args(&[]).child);
+        assert!(args(&["--symbol", "ETHUSDT", "--child"]).child);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let invalid = |flags: &[&str]| {
+            args(&[&["--symbol", "ETHUSDT"], flags].concat())
+                .props(now())
+                .unwrap_err()
+                .user_message()
+        };
+
+        assert_eq!(invalid(&["--interval", "2w"]), "unknown interval \"2w\"");
+        assert!(invalid(&["--last", "7x"]).starts_with("invalid duration"));
+        assert!(invalid(&["--last", "0d"]).starts_with("invalid duration"));
+        assert!(invalid(&["--last", "100000000d"]).ends_with("reaches too far back"));
+        assert!(invalid(&["--last", "20000000w"]).ends_with("reaches too far back"));
+        assert!(invalid(&["--start", "31.01.2024"]).starts_with("invalid date"));
+        assert!(invalid(&["--start", "2024-03-01"]).starts_with("range start"));
+
+        let parse = |flags: &[&str]| Args::try_parse_from([&["netstrat"], flags].concat());
+        assert!(parse(&[
+            "--symbol",
+            "ETHUSDT",
+            "--last",
+            "7d",
+            "--start",
+            "2024-01-01"
+        ])
+        .is_err());
+        assert!(parse(&["--symbol", "ETHUSDT", "--end", "2024-01-01"]).is_err());
+        assert!(parse(&["--last", "7d"]).is_err());
+        assert!(parse(&["--symbol", "ETHUSDT", "--view", "eyJ2ZXJzaW9uIjoxfQ"]).is_err());
+        assert!(parse(&["--view", "eyJ2ZXJzaW9uIjoxfQ"]).is_ok());
+        assert!(parse(&["--symbol", "ETHUSDT", "--import", "a.csv"]).is_err());
+        assert_eq!(
+            parse(&["--import", "a.csv"]).unwrap().import,
+            Some(PathBuf::from("a.csv"))
+        );
+        assert!(parse(&[]).unwrap().symbol.is_none());
+    }
+}
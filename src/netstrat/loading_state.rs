@@ -1,20 +1,19 @@
-use chrono::{DateTime, Duration, Utc};
 use tracing::info;
 
-use crate::{
-    netstrat::bounds::{Bounds, BoundsSet},
-    sources::binance::Interval,
-};
+use crate::netstrat::bounds::{Bounds, BoundsSet};
 
-use super::{
-    pages::{Page, Pages},
-    props::Props,
-};
+use super::pages::Pages;
 
+/// Pages of a download handed out to any number of requests in flight.
 #[derive(Default, Debug, Clone)]
 pub struct LoadingState {
     pub pages: Pages,
     pub has_error: bool,
+    /// Index of the next page to request.
+    next: usize,
+    /// Pages finished either way.
+    finished: usize,
+    failed: Vec<Bounds>,
 }
 
 impl LoadingState {
@@ -27,19 +26,81 @@ impl LoadingState {
         })
     }
 
-    pub fn left_edge(&self) -> i64 {
-        self.pages.page().0
+    /// Bounds and candles limit of the next page to request.
+    pub fn take_page(&mut self) -> Option<(Bounds, usize)> {
+        let page = self.pages.get(self.next)?;
+        let limit = self.pages.page_size(self.next);
+        self.next += 1;
+
+        Some((Bounds(page.0, page.1), limit))
+    }
+
+    pub fn page_loaded(&mut self) {
+        self.finished += 1;
+    }
+
+    /// Marks the page failed, the other pages are loaded regardless.
+    pub fn page_failed(&mut self, page: Bounds) {
+        self.finished += 1;
+        self.failed.push(page);
+        self.has_error = true;
+    }
+
+    /// Pages requested and not finished yet.
+    pub fn in_flight(&self) -> usize {
+        self.next - self.finished
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished >= self.pages.len()
     }
 
-    pub fn turn_page(&mut self) -> Option<Page> {
-        self.pages.next()
+    /// Ranges of the failed pages.
+    pub fn failed(&self) -> Option<BoundsSet> {
+        (!self.failed.is_empty()).then(|| BoundsSet::new(self.failed.clone()))
     }
 
-    pub fn progress(&mut self) -> f32 {
-        if self.pages.len() == 0 {
+    pub fn progress(&self) -> f32 {
+        if self.pages.is_empty() {
             return 1.0;
         }
 
-        self.pages.turned_pages as f32 / self.pages.len() as f32
+        self.finished as f32 / self.pages.len() as f32
+    }
+}
+
+#[cfg(test)]
+mod loading_state_tests {
+    use super::*;
+
+    #[test]
+    fn test_pages_in_flight() {
+        let bounds = BoundsSet::new(vec![Bounds(0, 150)]);
+        let mut loading = LoadingState::new(&bounds, 1, 50).unwrap();
+
+        let taken: Vec<_> = (0..4).filter_map(|_| loading.take_page()).collect();
+        assert_eq!(
+            taken,
+            vec![
+                (Bounds(0, 50), 50),
+                (Bounds(50, 100), 50),
+                (Bounds(100, 150), 50)
+            ]
+        );
+        assert_eq!(loading.in_flight(), 3);
+
+        loading.page_loaded();
+        loading.page_failed(Bounds(50, 100));
+        assert!(!loading.is_finished());
+        assert!(loading.has_error);
+        assert_eq!(loading.in_flight(), 1);
+
+        loading.page_loaded();
+        assert!(loading.is_finished());
+        assert_eq!(loading.progress(), 1.0);
+        assert_eq!(
+            loading.failed(),
+            Some(BoundsSet::new(vec![Bounds(50, 100)]))
+        );
     }
 }
@@ -0,0 +1,336 @@
+//! Tracing subscriber with a filter changed at runtime and an optional log file rotated
+//! by size.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::Subscriber;
+use tracing_subscriber::{
+    fmt::{self, MakeWriter},
+    layer::SubscriberExt,
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Registry,
+};
+
+use crate::errors::{Error, Result};
+
+pub const DEFAULT_FILTER: &str = "info";
+const DEFAULT_PATH: &str = "logs/netstrat.log";
+/// Size of the log file beyond which it is rotated.
+const MAX_BYTES: u64 = 10 * 1024 * 1024;
+/// Rotated files kept next to the log file, `netstrat.log.1` being the newest.
+const KEEP: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Directives of an `EnvFilter`, e.g. `info,netstrat::sources::binance=debug`.
+    pub filter: String,
+    /// Whether logs are also written to the log file.
+    pub file: bool,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            filter: DEFAULT_FILTER.to_string(),
+            file: false,
+        }
+    }
+}
+
+/// Handle of the installed subscriber, clones share the filter and the log file.
+#[derive(Clone)]
+pub struct Logging {
+    handle: reload::Handle<EnvFilter, Registry>,
+    filter: Arc<Mutex<String>>,
+    /// Whether the filter came from `RUST_LOG`, it is kept over the saved one then.
+    from_env: bool,
+    file: LogFile,
+}
+
+impl Logging {
+    /// Installs the subscriber logging to stdout and, once enabled, to the log file, with
+    /// the filter of `RUST_LOG` or the default one.
+    pub fn init() -> Self {
+        let env = std::env::var(EnvFilter::DEFAULT_ENV)
+            .ok()
+            .filter(|f| EnvFilter::try_new(f).is_ok());
+
+        let (logging, subscriber) =
+            Self::new(env.as_deref(), LogFile::new(PathBuf::from(DEFAULT_PATH)));
+        if let Err(err) = subscriber.try_init() {
+            eprintln!("failed to install the tracing subscriber: {err}");
+        }
+
+        logging
+    }
+
+    /// Handle with the subscriber it changes, which is to be installed.
+    fn new(env: Option<&str>, file: LogFile) -> (Self, impl Subscriber + Send + Sync) {
+        let filter = env.unwrap_or(DEFAULT_FILTER).to_string();
+        let (layer, handle) = reload::Layer::new(EnvFilter::new(&filter));
+        let subscriber = tracing_subscriber::registry()
+            .with(layer)
+            .with(fmt::layer())
+            .with(fmt::layer().with_ansi(false).with_writer(file.clone()));
+
+        let logging = Self {
+            handle,
+            filter: Arc::new(Mutex::new(filter)),
+            from_env: env.is_some(),
+            file,
+        };
+        (logging, subscriber)
+    }
+
+    pub fn filter(&self) -> String {
+        self.filter.lock().unwrap().clone()
+    }
+
+    /// Replaces the filter of all outputs, the previous one stays on invalid directives.
+    pub fn set_filter(&self, filter: &str) -> Result<()> {
+        let filter = filter.trim();
+        let parsed = EnvFilter::try_new(filter)
+            .map_err(|err| Error::Validation(format!("invalid log filter {filter:?}: {err}")))?;
+        self.handle
+            .reload(parsed)
+            .map_err(|err| Error::Validation(format!("failed to change log filter: {err}")))?;
+        *self.filter.lock().unwrap() = filter.to_string();
+
+        Ok(())
+    }
+
+    pub fn from_env(&self) -> bool {
+        self.from_env
+    }
+
+    pub fn config(&self) -> LogConfig {
+        LogConfig {
+            filter: self.filter(),
+            file: self.file.enabled(),
+        }
+    }
+
+    /// Applies the saved config, keeping the filter of `RUST_LOG` if it was set.
+    pub fn set_config(&self, config: &LogConfig) -> Result<()> {
+        self.file.set_enabled(config.file);
+        match self.from_env {
+            true => Ok(()),
+            false => self.set_filter(&config.filter),
+        }
+    }
+
+    pub fn file(&self) -> &LogFile {
+        &self.file
+    }
+}
+
+/// File logs are appended to while it is enabled, rotated once it grows over
+/// [`MAX_BYTES`]. Clones share it.
+#[derive(Clone)]
+pub struct LogFile {
+    inner: Arc<Mutex<Rotating>>,
+    enabled: Arc<AtomicBool>,
+}
+
+impl LogFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self::with_limits(path, MAX_BYTES, KEEP)
+    }
+
+    fn with_limits(path: PathBuf, max_bytes: u64, keep: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Rotating {
+                path,
+                max_bytes,
+                keep,
+                file: None,
+                written: 0,
+            })),
+            enabled: Default::default(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Starts or stops writing, the file is closed when stopped.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.inner.lock().unwrap().file = None;
+        }
+    }
+
+    /// Absolute path of the file when it exists, as configured otherwise.
+    pub fn path(&self) -> PathBuf {
+        let path = self.inner.lock().unwrap().path.clone();
+        path.canonicalize().unwrap_or(path)
+    }
+}
+
+impl Write for LogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.enabled() {
+            self.inner.lock().unwrap().write(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.inner.lock().unwrap().file {
+            Some(f) => f.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogFile {
+    type Writer = LogFile;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+struct Rotating {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: Option<File>,
+    /// Bytes in the open file.
+    written: u64,
+}
+
+impl Rotating {
+    fn write(&mut self, buf: &[u8]) -> io::Result<()> {
+        if self.file.is_none() {
+            self.open()?;
+        }
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        if let Some(f) = &mut self.file {
+            f.write_all(buf)?;
+            self.written += buf.len() as u64;
+        }
+
+        Ok(())
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.written = f.metadata()?.len();
+        self.file = Some(f);
+
+        Ok(())
+    }
+
+    /// Shifts the rotated files dropping the oldest and starts a new file.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        for i in (1..self.keep).rev() {
+            let from = rotated(&self.path, i);
+            if from.exists() {
+                fs::rename(from, rotated(&self.path, i + 1))?;
+            }
+        }
+        match self.keep {
+            0 => fs::remove_file(&self.path)?,
+            _ => fs::rename(&self.path, rotated(&self.path, 1))?,
+        }
+
+        self.open()
+    }
+}
+
+/// Path of the `n`th rotated file, e.g. `netstrat.log.2`.
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{n}"));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod logging_tests {
+    use super::*;
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("netstrat_logging_{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_rotate_by_size() {
+        let path = dir("rotate").join("netstrat.log");
+        let mut file = LogFile::with_limits(path.clone(), 10, 2);
+
+        file.write_all(b"ignored while disabled").unwrap();
+        assert!(!path.exists());
+
+        file.set_enabled(true);
+        let lines = [
+            "aaaa\n", "bbbb\n", "cccc\n", "dddd\n", "eeee\n", "ffff\n", "gggg\n",
+        ];
+        for line in lines {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+
+        let read = |p: &Path| fs::read_to_string(p).unwrap();
+        // the oldest lines were dropped with the third rotation
+        assert_eq!(read(&path), "gggg\n");
+        assert_eq!(read(&rotated(&path, 1)), "eeee\nffff\n");
+        assert_eq!(read(&rotated(&path, 2)), "cccc\ndddd\n");
+        assert!(!rotated(&path, 3).exists());
+        assert!(file.path().is_absolute());
+    }
+
+    #[test]
+    fn test_reload_filter() {
+        let path = dir("filter").join("netstrat.log");
+        let (logging, subscriber) = Logging::new(None, LogFile::new(path));
+        let _default = tracing::subscriber::set_default(subscriber);
+        assert_eq!(logging.filter(), DEFAULT_FILTER);
+
+        logging
+            .set_filter(" info,netstrat::sources::binance=debug ")
+            .unwrap();
+        assert_eq!(logging.filter(), "info,netstrat::sources::binance=debug");
+
+        let err = logging.set_filter("netstrat=loud").unwrap_err();
+        assert!(err.user_message().contains("invalid log filter"));
+        assert_eq!(logging.filter(), "info,netstrat::sources::binance=debug");
+
+        logging
+            .set_config(&LogConfig {
+                filter: "warn".to_string(),
+                file: true,
+            })
+            .unwrap();
+        assert_eq!(
+            logging.config(),
+            LogConfig {
+                filter: "warn".to_string(),
+                file: true
+            }
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Sidecar describing an exported file, written next to it as `<file>.meta.json`. Csv and
+//! line protocol exports get one, xlsx ones don't as their zip is patched by seeking back
+//! while it is saved and can't be hashed as it is written.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{SecondsFormat, Utc};
+use quick_error::ResultExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use crate::errors::Result;
+use crate::sources::binance::{Client, Interval, Kline};
+
+use super::props::Props;
+
+const EXTENSION: &str = "meta.json";
+
+/// File written by an export with the SHA-256 of its bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Written {
+    /// Absolute path of the file.
+    pub path: PathBuf,
+    pub sha256: String,
+}
+
+/// Writer hashing the bytes as they are written to `W`.
+pub struct HashWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> HashWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// Hex SHA-256 of the bytes written.
+    pub fn sha256(self) -> String {
+        format!("{:x}", self.hasher.finalize())
+    }
+}
+
+impl<W: Write> Write for HashWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Hex SHA-256 of the bytes.
+pub fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Where candles of an export came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Source {
+    /// E.g. `binance spot` or `csv import`.
+    pub name: String,
+    /// Api the candles were downloaded from, none for imported ones.
+    pub base_url: Option<String>,
+}
+
+impl Source {
+    pub fn binance_spot(client: &Client) -> Self {
+        Self {
+            name: "binance spot".to_string(),
+            base_url: Some(client.base_url().to_string()),
+        }
+    }
+
+    /// Candles imported from the file.
+    pub fn import(file: &str) -> Self {
+        Self {
+            name: format!("csv import of {file}"),
+            base_url: None,
+        }
+    }
+}
+
+/// What was exported, from where and when, with the hash of the written file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Name of the data file.
+    pub file: String,
+    pub symbol: String,
+    pub interval: String,
+    /// Range of the props in UTC millis.
+    pub requested: [i64; 2],
+    /// First open to last close of the exported candles, none without candles.
+    pub effective: Option<[i64; 2]>,
+    pub rows: usize,
+    pub source: Source,
+    pub app_version: String,
+    /// ISO date of the export.
+    pub exported_at: String,
+    pub sha256: String,
+}
+
+impl Manifest {
+    /// Manifest of the candles before they are written, see [`Manifest::of`].
+    pub fn new(
+        symbol: &str,
+        interval: Interval,
+        props: &Props,
+        klines: &[Kline],
+        source: Source,
+    ) -> Self {
+        let requested = props.bounds.hull().unwrap_or_default();
+        Self {
+            file: String::new(),
+            symbol: symbol.to_string(),
+            interval: format!("{interval:?}"),
+            requested: [requested.0, requested.1],
+            effective: klines
+                .first()
+                .zip(klines.last())
+                .map(|(first, last)| [first.t_open, last.t_close]),
+            rows: klines.len(),
+            source,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: String::new(),
+            sha256: String::new(),
+        }
+    }
+
+    /// Manifest of the file the candles were written to, exported now.
+    pub fn of(self, written: &Written) -> Self {
+        Self {
+            file: written
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            exported_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            sha256: written.sha256.clone(),
+            ..self
+        }
+    }
+
+    /// Writes the manifest next to the data file returning its absolute path.
+    pub fn write(&self, data: &Path) -> Result<PathBuf> {
+        let path = path(data);
+        let f = File::create(&path).context(path.as_path())?;
+        let abs_path = path.canonicalize().context(path.as_path())?;
+        info!("Saving manifest to file: {abs_path:?}");
+
+        serde_json::to_writer_pretty(f, self).context(path.as_path())?;
+
+        Ok(abs_path)
+    }
+
+    /// Manifest next to the data file, none when there is no such file.
+    pub fn read(data: &Path) -> Result<Option<Self>> {
+        let path = path(data);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let f = File::open(&path).context(path.as_path())?;
+
+        Ok(Some(serde_json::from_reader(f).context(path.as_path())?))
+    }
+}
+
+/// Path of the manifest of the data file.
+pub fn path(data: &Path) -> PathBuf {
+    let mut name = data.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{EXTENSION}"));
+    data.with_file_name(name)
+}
+
+#[cfg(test)]
+mod manifest_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_hash_while_writing() {
+        let mut written = vec![];
+        let mut wtr = HashWriter::new(&mut written);
+        wtr.write_all(b"ab").unwrap();
+        wtr.write_all(b"c").unwrap();
+
+        let hash = wtr.sha256();
+        assert_eq!(
+            hash,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(hash, sha256(b"abc"));
+        assert_eq!(written, b"abc");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = std::env::temp_dir().join("netstrat_manifest");
+        std::fs::create_dir_all(&dir).unwrap();
+        let data = dir.join("BTCUSDT_0_180000_Minute.csv");
+        let klines = mock::klines(0, 60_000, 3);
+        let written = Written {
+            path: data.clone(),
+            sha256: sha256(b""),
+        };
+        let props = Props::default();
+
+        let manifest = Manifest::new(
+            "BTCUSDT",
+            Interval::Minute,
+            &props,
+            &klines,
+            Source::binance_spot(&Client::default()),
+        )
+        .of(&written);
+        let path = manifest.write(&data).unwrap();
+
+        assert!(path.ends_with("BTCUSDT_0_180000_Minute.csv.meta.json"));
+        assert_eq!(manifest.file, "BTCUSDT_0_180000_Minute.csv");
+        assert_eq!(manifest.effective, Some([0, 180_000 - 1]));
+        assert_eq!(manifest.rows, 3);
+        assert_eq!(
+            manifest.source.base_url.as_deref(),
+            Some("https://api.binance.com")
+        );
+        assert_eq!(Manifest::read(&data).unwrap(), Some(manifest));
+
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(Manifest::read(&data).unwrap(), None);
+    }
+}
@@ -0,0 +1,201 @@
+//! Bookkeeping of candles held in memory and eviction of the least recently viewed ones.
+
+use std::{
+    cmp::Reverse,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use crate::sources::binance::Kline;
+
+use super::bounds::{Bounds, BoundsSet};
+
+pub const DEFAULT_CAP: usize = 1_000_000;
+
+/// Views older than that are forgotten.
+const MAX_VIEWS: usize = 256;
+
+/// Total number of candles held by all graphs and the cap for it.
+/// Clones share the counters.
+#[derive(Clone, Debug)]
+pub struct CandlesBudget {
+    resident: Arc<AtomicUsize>,
+    cap: Arc<AtomicUsize>,
+}
+
+impl Default for CandlesBudget {
+    fn default() -> Self {
+        Self {
+            resident: Default::default(),
+            cap: Arc::new(AtomicUsize::new(DEFAULT_CAP)),
+        }
+    }
+}
+
+impl CandlesBudget {
+    pub fn resident(&self) -> usize {
+        self.resident.load(Ordering::Relaxed)
+    }
+
+    pub fn cap(&self) -> usize {
+        self.cap.load(Ordering::Relaxed)
+    }
+
+    pub fn set_cap(&self, cap: usize) {
+        self.cap.store(cap, Ordering::Relaxed)
+    }
+
+    /// Replaces `old` count of some holder with the `new` one.
+    pub fn track(&self, old: usize, new: usize) {
+        match new >= old {
+            true => self.resident.fetch_add(new - old, Ordering::Relaxed),
+            false => self.resident.fetch_sub(old - new, Ordering::Relaxed),
+        };
+    }
+
+    /// Number of candles over the cap.
+    pub fn excess(&self) -> usize {
+        self.resident().saturating_sub(self.cap())
+    }
+}
+
+/// Visible ranges in the order they were viewed.
+#[derive(Default, Debug, Clone)]
+pub struct Views {
+    vals: Vec<(Bounds, u64)>,
+    tick: u64,
+}
+
+impl Views {
+    pub fn touch(&mut self, visible: Bounds) {
+        if visible.len() == 0 || self.vals.last().is_some_and(|(b, _)| *b == visible) {
+            return;
+        }
+
+        self.tick += 1;
+        self.vals.push((visible, self.tick));
+        if self.vals.len() > MAX_VIEWS {
+            self.vals.remove(0);
+        }
+    }
+
+    /// Tick of the latest view containing the candle, 0 for never viewed.
+    fn last_viewed(&self, k: &Kline) -> u64 {
+        let candle = Bounds(k.t_open, k.t_close);
+        self.vals
+            .iter()
+            .rev()
+            .find(|(b, _)| b.intersect(&candle).is_some())
+            .map_or(0, |(_, tick)| *tick)
+    }
+}
+
+/// Drops up to `excess` candles which are not `visible`, least recently viewed
+/// and then farthest from the visible range first. Remaining klines are sorted by time.
+/// Returns evicted ranges.
+pub fn evict(klines: &mut Vec<Kline>, views: &Views, visible: Bounds, excess: usize) -> BoundsSet {
+    klines.sort_by_key(|k| k.t_open);
+
+    let mut candidates: Vec<(usize, u64, i64)> = klines
+        .iter()
+        .enumerate()
+        .filter(|(_, k)| Bounds(k.t_open, k.t_close).intersect(&visible).is_none())
+        .map(|(i, k)| {
+            let distance = (k.t_open - visible.1).max(visible.0 - k.t_close);
+            (i, views.last_viewed(k), distance)
+        })
+        .collect();
+    candidates.sort_by_key(|(_, viewed, distance)| (*viewed, Reverse(*distance)));
+
+    let mut evicted = vec![false; klines.len()];
+    candidates
+        .iter()
+        .take(excess)
+        .for_each(|(i, _, _)| evicted[*i] = true);
+
+    let mut ranges: Vec<Bounds> = vec![];
+    let mut prev_evicted = false;
+    klines.iter().zip(&evicted).for_each(|(k, e)| {
+        if *e {
+            match (prev_evicted, ranges.last_mut()) {
+                (true, Some(last)) => last.1 = k.t_close,
+                _ => ranges.push(Bounds(k.t_open, k.t_close)),
+            }
+        }
+        prev_evicted = *e;
+    });
+
+    let mut flags = evicted.iter();
+    klines.retain(|_| !flags.next().unwrap());
+
+    BoundsSet::new(ranges)
+}
+
+#[cfg(test)]
+mod memory_tests {
+    use super::*;
+
+    fn klines(n: i64) -> Vec<Kline> {
+        (0..n)
+            .rev()
+            .map(|i| Kline {
+                t_open: i * 10,
+                t_close: i * 10 + 9,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_budget() {
+        let budget = CandlesBudget::default();
+        budget.set_cap(10);
+        let other = budget.clone();
+
+        budget.track(0, 8);
+        other.track(0, 5);
+        assert_eq!(budget.resident(), 13);
+        assert_eq!(budget.excess(), 3);
+
+        other.track(5, 1);
+        assert_eq!(budget.excess(), 0);
+    }
+
+    #[test]
+    fn test_evict_keeps_visible() {
+        let mut vals = klines(10);
+
+        let evicted = evict(&mut vals, &Views::default(), Bounds(0, 99), 5);
+
+        assert!(evicted.is_empty());
+        assert_eq!(vals.len(), 10);
+    }
+
+    #[test]
+    fn test_evict_farthest_never_viewed() {
+        let mut vals = klines(10);
+
+        let evicted = evict(&mut vals, &Views::default(), Bounds(0, 19), 3);
+
+        assert_eq!(evicted, BoundsSet::new(vec![Bounds(70, 99)]));
+        assert_eq!(vals.first().unwrap().t_open, 0);
+        assert_eq!(vals.last().unwrap().t_open, 60);
+    }
+
+    #[test]
+    fn test_evict_least_recently_viewed() {
+        let mut vals = klines(10);
+        let mut views = Views::default();
+        views.touch(Bounds(80, 99));
+        views.touch(Bounds(20, 39));
+        views.touch(Bounds(0, 19));
+
+        let evicted = evict(&mut vals, &views, Bounds(0, 19), 6);
+
+        // never viewed middle goes first, then the older view of the right edge
+        assert_eq!(evicted, BoundsSet::new(vec![Bounds(40, 99)]));
+        assert_eq!(vals.len(), 4);
+    }
+}
@@ -1,6 +1,51 @@
+#[cfg(feature = "gui")]
+pub mod actions;
+pub mod aggregate;
+pub mod axis;
+pub mod backtest;
 pub mod bounds;
+pub mod cache;
+pub mod channels;
+pub mod correlation;
 pub mod data;
+pub mod debounce;
+pub mod delta;
+pub mod download;
+pub mod events;
+pub mod export;
+pub mod expression;
+pub mod import;
+pub mod indicators;
+pub mod influx;
+pub mod journal;
+pub mod labels;
+pub mod last_used;
+pub mod launch;
 pub mod loading_state;
+pub mod logging;
+pub mod manifest;
+pub mod memory;
+pub mod notes;
+#[cfg(feature = "gui")]
+pub mod notify;
 pub mod pages;
+pub mod portfolio;
 pub mod props;
+pub mod quality;
+pub mod refresh;
+pub mod renko;
+pub mod replay;
+pub mod scheduler;
+pub mod scripting;
+pub mod selection;
+pub mod session;
+pub mod snap;
+pub mod spread;
 pub mod state;
+pub mod stats;
+pub mod symbol_index;
+pub mod synthetic;
+pub mod timezone;
+pub mod view;
+pub mod watchlist;
+pub mod xlsx;
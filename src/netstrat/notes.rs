@@ -0,0 +1,220 @@
+//! Text notes pinned to chart coordinates, kept per symbol. Notes of trades also carry
+//! their direction, prices and size for the journal.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use quick_error::ResultExt;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::errors::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Long,
+    Short,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Long => "long",
+            Direction::Short => "short",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Note {
+    /// Time in millis.
+    pub ts: i64,
+    pub price: f64,
+    pub text: String,
+    pub direction: Option<Direction>,
+    pub entry: Option<f64>,
+    pub exit: Option<f64>,
+    /// Quantity in base asset.
+    pub size: Option<f64>,
+}
+
+impl Note {
+    /// Profit in quote asset after the fee of both fills, known once the note has a
+    /// direction, both prices and a size.
+    pub fn pnl(&self, fee_bps: f64) -> Option<f64> {
+        let (entry, exit, size) = (self.entry?, self.exit?, self.size?);
+        let gross = match self.direction? {
+            Direction::Long => (exit - entry) * size,
+            Direction::Short => (entry - exit) * size,
+        };
+        let fees = (entry + exit) * size.abs() * fee_bps / 10_000.0;
+
+        Some(gross - fees)
+    }
+}
+
+/// Notes of all symbols.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Notes {
+    by_symbol: BTreeMap<String, Vec<Note>>,
+}
+
+impl Notes {
+    /// Symbols with their notes in symbol order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[Note])> {
+        self.by_symbol
+            .iter()
+            .map(|(symbol, notes)| (symbol.as_str(), notes.as_slice()))
+    }
+
+    pub fn get(&self, symbol: &str) -> &[Note] {
+        self.by_symbol.get(symbol).map_or(&[], |n| n.as_slice())
+    }
+
+    pub fn note_mut(&mut self, symbol: &str, idx: usize) -> Option<&mut Note> {
+        self.by_symbol.get_mut(symbol)?.get_mut(idx)
+    }
+
+    /// Removes the note, symbols left without notes are dropped.
+    pub fn remove(&mut self, symbol: &str, idx: usize) {
+        let mut notes = self.get(symbol).to_vec();
+        if idx < notes.len() {
+            notes.remove(idx);
+            self.set(symbol, notes);
+        }
+    }
+
+    /// Replaces notes of the symbol, symbols without notes are dropped.
+    pub fn set(&mut self, symbol: &str, notes: Vec<Note>) {
+        match notes.is_empty() {
+            true => self.by_symbol.remove(symbol),
+            false => self.by_symbol.insert(symbol.to_string(), notes),
+        };
+    }
+}
+
+/// Default export file name for notes of the symbol.
+pub fn file_name(symbol: &str, extension: &str) -> String {
+    format!("{symbol}_notes.{extension}")
+}
+
+/// Writes notes to csv file returning its absolute path.
+pub fn to_csv(path: &Path, notes: &[Note]) -> Result<PathBuf> {
+    let f = File::create(path).context(path)?;
+    let abs_path = path.canonicalize().context(path)?;
+    info!("Saving notes to file: {abs_path:?}");
+
+    let mut wtr = csv::Writer::from_writer(f);
+    for n in notes {
+        wtr.serialize(n).context(path)?;
+    }
+    wtr.flush().context(path)?;
+
+    Ok(abs_path)
+}
+
+/// Writes notes to json file returning its absolute path.
+pub fn to_json(path: &Path, notes: &[Note]) -> Result<PathBuf> {
+    let f = File::create(path).context(path)?;
+    let abs_path = path.canonicalize().context(path)?;
+    info!("Saving notes to file: {abs_path:?}");
+
+    serde_json::to_writer_pretty(f, notes).context(path)?;
+
+    Ok(abs_path)
+}
+
+#[cfg(test)]
+mod notes_tests {
+    use super::*;
+
+    fn notes() -> Vec<Note> {
+        vec![
+            Note {
+                ts: 1_000,
+                price: 10.5,
+                text: "CPI print".to_string(),
+                ..Default::default()
+            },
+            Note {
+                ts: 2_000,
+                price: 9.0,
+                text: "exchange outage, \"maintenance\"".to_string(),
+                direction: Some(Direction::Short),
+                entry: Some(9.5),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_set_per_symbol() {
+        let mut all = Notes::default();
+        all.set("BTCUSDT", notes());
+
+        assert_eq!(all.get("BTCUSDT"), notes().as_slice());
+        assert!(all.get("ETHUSDT").is_empty());
+
+        all.note_mut("BTCUSDT", 1).unwrap().exit = Some(9.0);
+        assert_eq!(all.get("BTCUSDT")[1].exit, Some(9.0));
+        assert!(all.note_mut("BTCUSDT", 2).is_none());
+
+        all.remove("BTCUSDT", 0);
+        assert_eq!(all.get("BTCUSDT").len(), 1);
+        all.set("BTCUSDT", vec![]);
+        assert_eq!(all, Notes::default());
+    }
+
+    #[test]
+    fn test_export() {
+        let dir = std::env::temp_dir();
+        let csv_path = dir.join(format!("netstrat_{}", file_name("BTCUSDT", "csv")));
+        let json_path = dir.join(format!("netstrat_{}", file_name("BTCUSDT", "json")));
+
+        let abs_csv = to_csv(&csv_path, &notes()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&abs_csv).unwrap(),
+            "ts,price,text,direction,entry,exit,size\n\
+             1000,10.5,CPI print,,,,\n\
+             2000,9.0,\"exchange outage, \"\"maintenance\"\"\",short,9.5,,\n"
+        );
+
+        let abs_json = to_json(&json_path, &notes()).unwrap();
+        let loaded: Vec<Note> =
+            serde_json::from_str(&std::fs::read_to_string(&abs_json).unwrap()).unwrap();
+        assert_eq!(loaded, notes());
+
+        std::fs::remove_file(abs_csv).unwrap();
+        std::fs::remove_file(abs_json).unwrap();
+    }
+
+    #[test]
+    fn test_pnl() {
+        let mut note = Note {
+            direction: Some(Direction::Long),
+            entry: Some(100.0),
+            exit: Some(110.0),
+            size: Some(2.0),
+            ..Default::default()
+        };
+        assert_eq!(note.pnl(0.0), Some(20.0));
+        // 10 bps of 200 and 220
+        assert!((note.pnl(10.0).unwrap() - 19.58).abs() < 1e-9);
+
+        note.direction = Some(Direction::Short);
+        assert_eq!(note.pnl(0.0), Some(-20.0));
+
+        note.exit = None;
+        assert_eq!(note.pnl(0.0), None);
+    }
+
+    #[test]
+    fn test_notes_without_journal_fields_load() {
+        let note: Note = serde_json::from_str(r#"{"ts":1,"price":2.0,"text":"a"}"#).unwrap();
+        assert_eq!(note.direction, None);
+        assert_eq!(note.text, "a");
+    }
+}
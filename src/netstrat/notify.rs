@@ -0,0 +1,157 @@
+//! Desktop notifications about long downloads and exports finishing.
+
+use std::{
+    sync::{Arc, RwLock},
+    thread,
+    time::Duration,
+};
+
+use notify_rust::Notification;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+
+use super::{bounds::Bounds, data::Data};
+
+#[cfg(target_os = "macos")]
+const SOUND: &str = "Glass";
+#[cfg(target_os = "windows")]
+const SOUND: &str = "Default";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const SOUND: &str = "complete";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    pub enabled: bool,
+    pub sound: bool,
+    /// Tasks running shorter are finished silently.
+    pub threshold_secs: u64,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sound: false,
+            threshold_secs: 30,
+        }
+    }
+}
+
+/// Task which finished or failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finished {
+    /// What was done, e.g. `download`.
+    pub task: &'static str,
+    pub symbol: String,
+    pub range: Bounds,
+    pub rows: usize,
+    pub error: Option<String>,
+}
+
+impl Finished {
+    fn summary(&self) -> String {
+        match self.error {
+            Some(_) => format!("{} of {} failed", self.task, self.symbol),
+            None => format!("{} of {} finished", self.task, self.symbol),
+        }
+    }
+
+    fn body(&self) -> String {
+        let range = format!(
+            "{} - {}",
+            Data::format_ts(self.range.0 as f64),
+            Data::format_ts(self.range.1 as f64)
+        );
+        match &self.error {
+            Some(err) => format!("{range}\n{err}"),
+            None => format!("{range}\n{} rows", self.rows),
+        }
+    }
+}
+
+/// Shared handle sending notifications, cloned into the tasks so they notify on completion
+/// without waiting for the ui to be drawn.
+#[derive(Clone, Default)]
+pub struct Notifier {
+    config: Arc<RwLock<NotifyConfig>>,
+}
+
+impl Notifier {
+    pub fn config(&self) -> NotifyConfig {
+        self.config.read().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    pub fn set_config(&self, config: NotifyConfig) {
+        if let Ok(mut c) = self.config.write() {
+            *c = config;
+        }
+    }
+
+    fn should_notify(&self, elapsed: Duration) -> bool {
+        let config = self.config();
+        config.enabled && elapsed >= Duration::from_secs(config.threshold_secs)
+    }
+
+    /// Notifies about the task if it ran longer than the threshold, the notification is
+    /// shown from a thread of its own.
+    pub fn finished(&self, finished: Finished, elapsed: Duration) {
+        if !self.should_notify(elapsed) {
+            return;
+        }
+
+        debug!("notifying after {elapsed:?}: {finished:?}");
+        let sound = self.config().sound;
+        thread::spawn(move || {
+            let mut notification = Notification::new();
+            notification
+                .appname("netstrat")
+                .summary(&finished.summary())
+                .body(&finished.body());
+            if sound {
+                notification.sound_name(SOUND);
+            }
+            if let Err(err) = notification.show() {
+                error!("failed to show notification: {err}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod notify_tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold() {
+        let notifier = Notifier::default();
+        assert!(!notifier.should_notify(Duration::from_secs(29)));
+        assert!(notifier.should_notify(Duration::from_secs(30)));
+
+        notifier.set_config(NotifyConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        assert!(!notifier.clone().should_notify(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_message() {
+        let mut finished = Finished {
+            task: "export",
+            symbol: "BTCUSDT".to_string(),
+            range: Bounds(0, 60_000),
+            rows: 1440,
+            error: None,
+        };
+        assert_eq!(finished.summary(), "export of BTCUSDT finished");
+        assert_eq!(
+            finished.body(),
+            "1970-01-01 00:00:00 - 1970-01-01 00:01:00\n1440 rows"
+        );
+
+        finished.error = Some("network unreachable".to_string());
+        assert_eq!(finished.summary(), "export of BTCUSDT failed");
+        assert!(finished.body().ends_with("network unreachable"));
+    }
+}
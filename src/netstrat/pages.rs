@@ -7,8 +7,6 @@ pub struct Page(pub i64, pub i64);
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct Pages {
-    pub curr_page_idx: usize,
-    pub turned_pages: usize,
     vals: Vec<Page>,
     step: usize,
 }
@@ -28,7 +26,7 @@ impl Pages {
 
         let mut vals = vec![];
         bounds.vals().iter_mut().for_each(|b| {
-            if b.len() <= limit*step as usize  {
+            if b.len() <= limit*step  {
                 debug!("Not iterating inside bounds due to its size being less than limit. Taking it to page as a whole. Bounds: {b:?}. Step: {step}.");
                 vals.push(Page(b.0, b.1));
                 return ;
@@ -53,34 +51,26 @@ impl Pages {
 
         info!("Computed pages: {vals:?}.");
 
-        Some(Self {
-            vals,
-            step,
-            ..Default::default()
-        })
+        Some(Self { vals, step })
     }
 
     pub fn len(&self) -> usize {
         self.vals.len()
     }
 
-    pub fn next(&mut self) -> Option<Page> {
-        self.turned_pages += 1;
-        if let Some(page) = self.vals.get(self.turned_pages) {
-            self.curr_page_idx += 1;
-            return Some(page.clone());
-        }
-
-        None
+    pub fn is_empty(&self) -> bool {
+        self.vals.is_empty()
     }
 
-    pub fn page(&self) -> Page {
-        self.vals[self.curr_page_idx].clone()
+    pub fn get(&self, idx: usize) -> Option<Page> {
+        self.vals.get(idx).cloned()
     }
 
-    pub fn page_size(&self) -> usize {
-        let page = self.page();
-        ((page.1 - page.0) / self.step as i64) as usize
+    /// Candles of the page at index, a partial candle counts as a whole one.
+    pub fn page_size(&self, idx: usize) -> usize {
+        self.vals.get(idx).map_or(0, |page| {
+            ((page.1 - page.0).max(1) as usize).div_ceil(self.step)
+        })
     }
 }
 
@@ -99,7 +89,6 @@ mod pages_tests {
             Pages {
                 vals: vec![Page(0, 50), Page(60, 110), Page(110, 150)],
                 step: 1,
-                ..Default::default()
             }
         );
 
@@ -110,8 +99,41 @@ mod pages_tests {
             Pages {
                 vals: vec![Page(0, 50), Page(60, 110), Page(110, 150)],
                 step: 2,
-                ..Default::default()
             }
         );
     }
 }
+
+#[cfg(test)]
+mod pages_prop_tests {
+    use proptest::prelude::*;
+
+    use crate::netstrat::bounds::Bounds;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn pages_cover_bounds(
+            start in 0..1_000_000i64,
+            len in 1..1_000_000i64,
+            step in 1..10_000usize,
+            limit in 1..1_000usize,
+        ) {
+            let bounds = BoundsSet::new(vec![Bounds(start, start + len)]);
+            let pages = Pages::new(bounds.clone(), step, limit).unwrap();
+
+            let union = (0..pages.len())
+                .map(|idx| pages.get(idx).unwrap())
+                .fold(BoundsSet::default(), |acc, p| acc.merge_single(Bounds(p.0, p.1)));
+            prop_assert_eq!(union, bounds);
+
+            for idx in 0..pages.len() {
+                let page = pages.get(idx).unwrap();
+                let size = pages.page_size(idx);
+                prop_assert!(size >= 1 && size <= limit);
+                prop_assert!(size as i64 * step as i64 >= page.1 - page.0);
+            }
+        }
+    }
+}
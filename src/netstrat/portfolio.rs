@@ -0,0 +1,225 @@
+//! Hypothetical positions marked to the latest known prices, either the last close loaded in
+//! the chart or the last price of the 24 hour ticker.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    netstrat::indicators::{Marker, MarkerKind},
+    sources::binance::Interval,
+};
+
+use super::notes::Direction;
+
+/// Closes of a sparkline at most.
+pub const SPARKLINE_POINTS: i64 = 120;
+
+/// Price and time in millis a position was entered or left at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fill {
+    pub ts: i64,
+    pub price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub symbol: String,
+    pub direction: Direction,
+    /// Quantity in base asset.
+    pub size: f64,
+    pub entry: Fill,
+    /// Fill of a closed position, which is marked to its price from then on.
+    pub exit: Option<Fill>,
+}
+
+impl Position {
+    /// Price the position is marked to, the exit price once closed.
+    pub fn mark(&self, quote: Option<&Quote>) -> Option<f64> {
+        self.exit.map(|e| e.price).or(quote.map(|q| q.price))
+    }
+
+    /// Notional at the price in quote asset.
+    pub fn value(&self, price: f64) -> f64 {
+        self.size * price
+    }
+
+    /// Profit at the price in quote asset.
+    pub fn pnl(&self, price: f64) -> f64 {
+        match self.direction {
+            Direction::Long => (price - self.entry.price) * self.size,
+            Direction::Short => (self.entry.price - price) * self.size,
+        }
+    }
+
+    /// Buy and sell marks of the entry and the exit.
+    pub fn markers(&self) -> Vec<Marker> {
+        let (enter, leave) = match self.direction {
+            Direction::Long => (MarkerKind::Buy, MarkerKind::Sell),
+            Direction::Short => (MarkerKind::Sell, MarkerKind::Buy),
+        };
+        let mut markers = vec![Marker {
+            kind: enter,
+            point: [self.entry.ts as f64, self.entry.price],
+        }];
+        if let Some(exit) = self.exit {
+            markers.push(Marker {
+                kind: leave,
+                point: [exit.ts as f64, exit.price],
+            });
+        }
+
+        markers
+    }
+
+    /// Interval with at most `SPARKLINE_POINTS` candles from the entry up to `now` or the
+    /// exit, the longest one when none is short enough.
+    pub fn sparkline_interval(&self, now: i64) -> Interval {
+        let end = self.exit.map_or(now, |e| e.ts);
+        let span = (end - self.entry.ts).max(0);
+        Interval::ALL
+            .into_iter()
+            .filter(|i| !i.is_aggregated())
+            .find(|i| span / i.millis() <= SPARKLINE_POINTS)
+            .unwrap_or(Interval::Day)
+    }
+}
+
+/// Where the price of a symbol comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// Last close of the candles loaded in the chart.
+    Chart,
+    Ticker,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quote {
+    pub price: f64,
+    /// Time of the price in millis.
+    pub ts: i64,
+    pub source: Source,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub positions: Vec<Position>,
+}
+
+impl Portfolio {
+    /// Marks of the positions of the symbol.
+    pub fn markers(&self, symbol: &str) -> Vec<Marker> {
+        self.positions
+            .iter()
+            .filter(|p| p.symbol == symbol)
+            .flat_map(|p| p.markers())
+            .collect()
+    }
+
+    /// Sums of value and profit of the positions with a known price.
+    pub fn totals(&self, quotes: &BTreeMap<String, Quote>) -> (f64, f64) {
+        self.positions
+            .iter()
+            .filter_map(|p| Some((p, p.mark(quotes.get(&p.symbol))?)))
+            .fold((0.0, 0.0), |(value, pnl), (p, price)| {
+                (value + p.value(price), pnl + p.pnl(price))
+            })
+    }
+}
+
+#[cfg(test)]
+mod portfolio_tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60 * 1000;
+
+    fn position(symbol: &str, direction: Direction, exit: Option<Fill>) -> Position {
+        Position {
+            symbol: symbol.to_string(),
+            direction,
+            size: 2.0,
+            entry: Fill {
+                ts: 0,
+                price: 100.0,
+            },
+            exit,
+        }
+    }
+
+    fn quote(price: f64) -> Quote {
+        Quote {
+            price,
+            ts: DAY,
+            source: Source::Ticker,
+        }
+    }
+
+    #[test]
+    fn test_marked_to_quote_or_exit() {
+        let long = position("BTCUSDT", Direction::Long, None);
+        let short = position("ETHUSDT", Direction::Short, None);
+        let closed = position(
+            "BTCUSDT",
+            Direction::Long,
+            Some(Fill {
+                ts: DAY,
+                price: 90.0,
+            }),
+        );
+
+        assert_eq!(long.mark(None), None);
+        assert_eq!(long.pnl(long.mark(Some(&quote(110.0))).unwrap()), 20.0);
+        assert_eq!(short.pnl(110.0), -20.0);
+        assert_eq!(closed.mark(Some(&quote(110.0))), Some(90.0));
+        assert_eq!(closed.value(90.0), 180.0);
+
+        let portfolio = Portfolio {
+            positions: vec![long, short, closed],
+        };
+        let quotes = BTreeMap::from([("BTCUSDT".to_string(), quote(110.0))]);
+        assert_eq!(portfolio.totals(&quotes), (400.0, 0.0));
+    }
+
+    #[test]
+    fn test_markers() {
+        let portfolio = Portfolio {
+            positions: vec![
+                position(
+                    "BTCUSDT",
+                    Direction::Short,
+                    Some(Fill {
+                        ts: DAY,
+                        price: 90.0,
+                    }),
+                ),
+                position("ETHUSDT", Direction::Long, None),
+            ],
+        };
+
+        let markers = portfolio.markers("BTCUSDT");
+
+        assert_eq!(
+            markers,
+            vec![
+                Marker {
+                    kind: MarkerKind::Sell,
+                    point: [0.0, 100.0],
+                },
+                Marker {
+                    kind: MarkerKind::Buy,
+                    point: [DAY as f64, 90.0],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sparkline_interval() {
+        let p = position("BTCUSDT", Direction::Long, None);
+
+        assert_eq!(p.sparkline_interval(60 * 60 * 1000), Interval::Minute);
+        assert_eq!(p.sparkline_interval(DAY), Interval::FifteenMinutes);
+        assert_eq!(p.sparkline_interval(30 * DAY), Interval::Day);
+        assert_eq!(p.sparkline_interval(1000 * DAY), Interval::Day);
+    }
+}
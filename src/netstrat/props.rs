@@ -1,54 +1,263 @@
-use chrono::{Date, DateTime, Duration, NaiveTime, Timelike, Utc};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
 
 use crate::{
-    netstrat::bounds::{Bounds, BoundsSet},
+    netstrat::{
+        aggregate::EmptyBuckets,
+        bounds::{Bounds, BoundsSet},
+    },
     sources::binance::Interval,
 };
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Props {
-    pub date_start: Date<Utc>,
-    pub date_end: Date<Utc>,
+    pub date_start: NaiveDate,
+    pub date_end: NaiveDate,
     pub time_start: NaiveTime,
     pub time_end: NaiveTime,
     pub bounds: BoundsSet,
     pub interval: Interval,
     pub limit: usize,
+    /// Used for intervals aggregated from trades.
+    pub empty_buckets: EmptyBuckets,
 }
 
 impl Props {
+    /// Props of the range between `start` and `end` in UTC, validity is not checked.
+    pub fn new(
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        interval: Interval,
+        empty_buckets: EmptyBuckets,
+    ) -> Self {
+        Self {
+            date_start: start.date(),
+            date_end: end.date(),
+            time_start: start.time(),
+            time_end: end.time(),
+            bounds: BoundsSet::new(vec![Bounds(
+                start.and_utc().timestamp_millis(),
+                end.and_utc().timestamp_millis(),
+            )]),
+            interval,
+            limit: 1000,
+            empty_buckets,
+        }
+    }
+
+    /// Props of the sessions from `time_start` to `time_end` of the next day, one starting
+    /// on every day from `date_start` up to the day before `date_end`. Validity is not
+    /// checked.
+    pub fn overnight(
+        date_start: NaiveDate,
+        date_end: NaiveDate,
+        time_start: NaiveTime,
+        time_end: NaiveTime,
+        interval: Interval,
+        empty_buckets: EmptyBuckets,
+    ) -> Self {
+        let nights = date_start
+            .iter_days()
+            .take_while(|d| *d < date_end)
+            .filter_map(|d| {
+                let start = d.and_time(time_start).and_utc();
+                let end = d.succ_opt()?.and_time(time_end).and_utc();
+                Some(Bounds(start.timestamp_millis(), end.timestamp_millis()))
+            })
+            .collect();
+
+        Self {
+            date_start,
+            date_end,
+            time_start,
+            time_end,
+            bounds: BoundsSet::new(nights),
+            interval,
+            limit: 1000,
+            empty_buckets,
+        }
+    }
+
+    /// Props of the range as entered in the chooser or on the command line, or why it is
+    /// invalid. With `overnight` a start time after the end time stands for sessions
+    /// crossing midnight, with `snap` the bounds are widened to the interval.
+    pub fn checked(
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        interval: Interval,
+        empty_buckets: EmptyBuckets,
+        overnight: bool,
+        snap: bool,
+    ) -> Result<Props, &'static str> {
+        let props = match overnight && start.time() > end.time() {
+            true if start.date() >= end.date() => {
+                return Err("overnight sessions end on the next day, pick a later end date")
+            }
+            true => Props::overnight(
+                start.date(),
+                end.date(),
+                start.time(),
+                end.time(),
+                interval,
+                empty_buckets,
+            ),
+            false if start.date() == end.date() && start.time() > end.time() => {
+                return Err("start must be before end, or turn on overnight sessions")
+            }
+            false => Props::new(start, end, interval, empty_buckets),
+        };
+        if !props.is_valid() {
+            return Err("start must be before end");
+        }
+
+        Ok(match snap {
+            true => props.snapped(),
+            false => props,
+        })
+    }
+
     pub fn start_time(&self) -> DateTime<Utc> {
-        self.date_start.and_hms(
-            self.time_start.hour(),
-            self.time_start.minute(),
-            self.time_start.second(),
-        )
+        self.date_start.and_time(self.time_start).and_utc()
     }
 
     pub fn end_time(&self) -> DateTime<Utc> {
-        self.date_end.and_hms(
-            self.time_end.hour(),
-            self.time_end.minute(),
-            self.time_end.second(),
-        )
+        self.date_end.and_time(self.time_end).and_utc()
     }
 
     pub fn is_valid(&self) -> bool {
         self.start_time() < self.end_time()
     }
+
+    /// Estimated number of candles in the bounds, fewer are returned for intervals
+    /// without trading.
+    pub fn candles(&self) -> usize {
+        let step = self.interval.millis() as usize;
+        self.bounds
+            .vals()
+            .iter()
+            .map(|b| b.len().div_ceil(step))
+            .sum()
+    }
+
+    /// Props with the bounds widened to boundaries of the interval, candles of the exchange
+    /// start at them so the bounds match the candles returned.
+    pub fn snapped(&self) -> Props {
+        let step = self.interval.millis();
+        let bounds = self
+            .bounds
+            .vals()
+            .into_iter()
+            .map(|b| {
+                let start = self.interval.open(b.0);
+                let end = self.interval.open(b.1);
+                Bounds(start, if end < b.1 { end + step } else { end })
+            })
+            .collect();
+
+        Props {
+            // sessions widened into each other are loaded as one
+            bounds: BoundsSet::default().merge(&BoundsSet::new(bounds)),
+            ..self.clone()
+        }
+    }
+
+    /// Interval of the same range with a number of candles within the band when there are
+    /// fewer or more at the current one. The finest one is picked for large ranges and the
+    /// coarsest one for small ranges, intervals built from trades aren't suggested.
+    pub fn suggest_interval(&self, band: &CandlesBand) -> Option<Interval> {
+        let candles = self.candles();
+        let with = |interval: Interval| Props {
+            interval,
+            ..self.clone()
+        };
+        let kline_intervals = Interval::ALL.into_iter().filter(|i| !i.is_aggregated());
+        if candles > band.max() {
+            kline_intervals
+                .filter(|i| i.millis() > self.interval.millis())
+                .find(|i| with(*i).candles() <= band.max())
+        } else if candles < band.min() {
+            kline_intervals
+                .rev()
+                .filter(|i| i.millis() < self.interval.millis())
+                .find(|i| with(*i).candles() >= band.min())
+        } else {
+            None
+        }
+    }
+
+    /// Props of `interval` ending at the same time with the start moved to keep about the
+    /// same number of candles.
+    pub fn with_interval(&self, interval: Interval) -> Props {
+        let end = self.end_time();
+        let span = (end - self.start_time()).num_milliseconds();
+        let candles = (span / self.interval.millis()).max(1);
+        let start = DateTime::from_timestamp_millis(
+            end.timestamp_millis()
+                .saturating_sub(candles.saturating_mul(interval.millis())),
+        )
+        .unwrap_or(DateTime::<Utc>::MIN_UTC);
+
+        Props {
+            limit: self.limit,
+            ..Props::new(
+                start.naive_utc(),
+                end.naive_utc(),
+                interval,
+                self.empty_buckets,
+            )
+        }
+    }
+}
+
+/// Candles count of a request beyond which another interval is suggested.
+/// Clones share the band.
+#[derive(Clone, Debug)]
+pub struct CandlesBand {
+    min: Arc<AtomicUsize>,
+    max: Arc<AtomicUsize>,
+}
+
+impl Default for CandlesBand {
+    fn default() -> Self {
+        Self {
+            min: Arc::new(AtomicUsize::new(50)),
+            max: Arc::new(AtomicUsize::new(200_000)),
+        }
+    }
+}
+
+impl CandlesBand {
+    pub fn min(&self) -> usize {
+        self.min.load(Ordering::Relaxed)
+    }
+
+    pub fn max(&self) -> usize {
+        self.max.load(Ordering::Relaxed)
+    }
+
+    pub fn set(&self, min: usize, max: usize) {
+        self.min.store(min, Ordering::Relaxed);
+        self.max.store(max.max(min), Ordering::Relaxed);
+    }
 }
 
 impl Default for Props {
     fn default() -> Self {
         let now = Utc::now();
         let mut p = Self {
-            date_start: now.date() - Duration::days(1),
-            date_end: now.date(),
-            time_start: NaiveTime::from_hms(0, 0, 0),
-            time_end: NaiveTime::from_hms(now.hour(), now.minute(), now.second()),
+            date_start: now.date_naive() - Duration::days(1),
+            date_end: now.date_naive(),
+            time_start: NaiveTime::MIN,
+            time_end: NaiveTime::from_hms_opt(now.hour(), now.minute(), now.second())
+                .unwrap_or(NaiveTime::MIN),
             interval: Interval::Minute,
             bounds: BoundsSet::default(),
             limit: 1000,
+            empty_buckets: EmptyBuckets::default(),
         };
 
         p.bounds = BoundsSet::new(vec![Bounds(
@@ -59,3 +268,269 @@ impl Default for Props {
         p
     }
 }
+
+#[cfg(test)]
+mod props_tests {
+    use super::*;
+
+    fn props(
+        date_start: NaiveDate,
+        time_start: NaiveTime,
+        date_end: NaiveDate,
+        time_end: NaiveTime,
+    ) -> Props {
+        Props {
+            date_start,
+            date_end,
+            time_start,
+            time_end,
+            bounds: BoundsSet::default(),
+            interval: Interval::Minute,
+            limit: 1000,
+            empty_buckets: EmptyBuckets::default(),
+        }
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn time(h: u32, m: u32, s: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, s).unwrap()
+    }
+
+    #[test]
+    fn test_start_end_time() {
+        let p = props(
+            date(1970, 1, 1),
+            time(0, 0, 0),
+            date(1970, 1, 2),
+            time(0, 0, 0),
+        );
+        assert_eq!(p.start_time().timestamp_millis(), 0);
+        assert_eq!(p.end_time().timestamp_millis(), 24 * 60 * 60 * 1000);
+
+        // last second of the day
+        let p = props(
+            date(2022, 7, 31),
+            time(23, 59, 59),
+            date(2022, 8, 1),
+            time(0, 0, 0),
+        );
+        assert_eq!(
+            p.end_time().timestamp_millis() - p.start_time().timestamp_millis(),
+            1000
+        );
+
+        // leap day
+        let p = props(
+            date(2020, 2, 28),
+            time(12, 0, 0),
+            date(2020, 3, 1),
+            time(12, 0, 0),
+        );
+        assert_eq!(
+            p.end_time().signed_duration_since(p.start_time()),
+            Duration::days(2)
+        );
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let d = date(2022, 1, 1);
+
+        assert!(props(d, time(0, 0, 0), d, time(0, 0, 1)).is_valid());
+        assert!(!props(d, time(0, 0, 1), d, time(0, 0, 1)).is_valid());
+        assert!(!props(d, time(0, 0, 2), d, time(0, 0, 1)).is_valid());
+        assert!(props(d, time(23, 59, 59), date(2022, 1, 2), time(0, 0, 0)).is_valid());
+        assert!(!props(date(2022, 1, 2), time(0, 0, 0), d, time(23, 59, 59)).is_valid());
+    }
+
+    #[test]
+    fn test_candles() {
+        let d = date(2022, 1, 1);
+        let mut p = props(d, time(0, 0, 0), d, time(1, 0, 30));
+        p.bounds = BoundsSet::new(vec![Bounds(0, 3_630_000), Bounds(4_000_000, 4_060_000)]);
+
+        assert_eq!(p.candles(), 61 + 1);
+    }
+
+    #[test]
+    fn test_with_interval() {
+        let d = date(2022, 1, 2);
+        let p = props(d, time(0, 0, 0), d, time(10, 0, 0));
+
+        let hourly = p.with_interval(Interval::FourHours);
+        assert_eq!(hourly.interval, Interval::FourHours);
+        assert_eq!(hourly.end_time(), p.end_time());
+        assert_eq!(hourly.start_time(), p.end_time() - Duration::hours(4 * 600));
+        assert_eq!(hourly.candles(), 600);
+
+        let back = hourly.with_interval(Interval::Minute);
+        assert_eq!(back.start_time(), p.start_time());
+        assert_eq!(
+            back.bounds,
+            Props::new(
+                p.start_time().naive_utc(),
+                p.end_time().naive_utc(),
+                Interval::Minute,
+                p.empty_buckets,
+            )
+            .bounds
+        );
+    }
+
+    #[test]
+    fn test_snapped() {
+        let d = date(2022, 1, 1);
+        let hour = 60 * 60 * 1000;
+        let mut p = Props::new(
+            d.and_time(time(10, 7, 0)),
+            d.and_time(time(14, 52, 0)),
+            Interval::Hour,
+            EmptyBuckets::default(),
+        );
+        let day = p.start_time().timestamp_millis() - 10 * hour - 7 * 60_000;
+
+        assert_eq!(
+            p.snapped().bounds,
+            BoundsSet::new(vec![Bounds(day + 10 * hour, day + 15 * hour)])
+        );
+        assert_eq!(p.snapped().start_time(), p.start_time());
+
+        p.bounds = BoundsSet::new(vec![Bounds(day, day + 2 * hour)]);
+        assert_eq!(p.snapped().bounds, p.bounds);
+
+        // weeks of the Saturday open on the Mondays around it
+        p.interval = Interval::Week;
+        assert_eq!(
+            p.snapped().bounds,
+            BoundsSet::new(vec![Bounds(day - 5 * 24 * hour, day + 2 * 24 * hour)])
+        );
+    }
+
+    #[test]
+    fn test_overnight() {
+        let hour = 60 * 60 * 1000;
+        let p = Props::overnight(
+            date(1970, 1, 1),
+            date(1970, 1, 4),
+            time(22, 0, 0),
+            time(6, 0, 0),
+            Interval::Hour,
+            EmptyBuckets::default(),
+        );
+
+        assert!(p.is_valid());
+        assert_eq!(
+            p.bounds,
+            BoundsSet::new(vec![
+                Bounds(22 * hour, 30 * hour),
+                Bounds(46 * hour, 54 * hour),
+                Bounds(70 * hour, 78 * hour),
+            ])
+        );
+        assert_eq!(p.candles(), 24);
+        assert_eq!(p.bounds.hull(), Some(Bounds(22 * hour, 78 * hour)));
+        assert_eq!(p.end_time().timestamp_millis(), 78 * hour);
+
+        let mut daily = p.clone();
+        daily.interval = Interval::Day;
+        assert_eq!(
+            daily.snapped().bounds,
+            BoundsSet::new(vec![Bounds(0, 96 * hour)])
+        );
+    }
+
+    #[test]
+    fn test_suggest_interval() {
+        let band = CandlesBand::default();
+        let d = date(2020, 1, 1);
+        let mut p = Props::new(
+            d.and_time(time(0, 0, 0)),
+            date(2022, 1, 1).and_time(time(0, 0, 0)),
+            Interval::Minute,
+            EmptyBuckets::default(),
+        );
+        assert!(p.candles() > 1_000_000);
+        assert_eq!(p.suggest_interval(&band), Some(Interval::FifteenMinutes));
+
+        p.interval = Interval::Hour;
+        assert_eq!(p.suggest_interval(&band), None);
+
+        let mut p = Props::new(
+            d.and_time(time(0, 0, 0)),
+            d.and_time(time(2, 0, 0)),
+            Interval::Day,
+            EmptyBuckets::default(),
+        );
+        assert_eq!(p.suggest_interval(&band), Some(Interval::Minute));
+
+        band.set(5, 10);
+        assert_eq!(p.suggest_interval(&band), Some(Interval::FifteenMinutes));
+        p.interval = Interval::Second;
+        assert_eq!(p.suggest_interval(&band), Some(Interval::FifteenMinutes));
+    }
+
+    #[test]
+    fn test_default_bounds() {
+        let p = Props::default();
+
+        assert!(p.is_valid());
+        assert_eq!(
+            p.bounds,
+            BoundsSet::new(vec![Bounds(
+                p.start_time().timestamp_millis(),
+                p.end_time().timestamp_millis()
+            )])
+        );
+    }
+}
+
+#[cfg(test)]
+mod props_prop_tests {
+    use chrono::NaiveDateTime;
+    use proptest::prelude::*;
+
+    use crate::netstrat::state::State;
+
+    use super::*;
+
+    /// Times from 2017 to 2030 at second precision.
+    fn datetime() -> impl Strategy<Value = NaiveDateTime> {
+        (1_483_228_800..1_893_456_000i64)
+            .prop_map(|secs| DateTime::from_timestamp(secs, 0).unwrap().naive_utc())
+    }
+
+    proptest! {
+        #[test]
+        fn valid_when_start_before_end(start in datetime(), end in datetime()) {
+            let props = Props::new(start, end, Interval::Minute, EmptyBuckets::default());
+
+            prop_assert_eq!(props.is_valid(), props.start_time() < props.end_time());
+            prop_assert_eq!(props.is_valid(), start < end);
+        }
+
+        #[test]
+        fn valid_props_are_paged(
+            start in datetime(),
+            secs in 1..3 * 86_400i64,
+            limit in 1..1_000usize,
+        ) {
+            let end = start + Duration::seconds(secs);
+            let mut props = Props::new(start, end, Interval::Minute, EmptyBuckets::default());
+            props.limit = limit;
+            prop_assert!(props.is_valid());
+            prop_assert!(props.candles() >= 1);
+
+            let mut state = State::default();
+            state.apply_props(&props);
+            let mut pages = 0;
+            while let Some((_, page_limit)) = state.loading.take_page() {
+                prop_assert!(page_limit >= 1 && page_limit <= limit);
+                pages += 1;
+            }
+            prop_assert!(pages >= 1);
+        }
+    }
+}
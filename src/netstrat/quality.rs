@@ -0,0 +1,254 @@
+//! Checks of loaded candles for gaps, duplicates and anomalies.
+
+use crate::sources::binance::Kline;
+
+use super::{
+    bounds::{Bounds, BoundsSet},
+    data::Data,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    /// Missing candles within a loaded range.
+    Gap,
+    /// Several candles opening at the same time.
+    Duplicate,
+    ZeroVolume,
+    /// High below the body or low above it.
+    InvalidRange,
+    /// Close to close return too far from the mean in standard deviations.
+    ExtremeReturn,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 5] = [
+        Kind::Gap,
+        Kind::Duplicate,
+        Kind::ZeroVolume,
+        Kind::InvalidRange,
+        Kind::ExtremeReturn,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Gap => "gap",
+            Kind::Duplicate => "duplicate",
+            Kind::ZeroVolume => "zero volume",
+            Kind::InvalidRange => "invalid range",
+            Kind::ExtremeReturn => "extreme return",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub kind: Kind,
+    /// Time range the finding covers.
+    pub bounds: Bounds,
+    pub detail: String,
+}
+
+impl Finding {
+    pub fn describe(&self) -> String {
+        let from = Data::format_ts(self.bounds.0 as f64);
+        match self.bounds.0 == self.bounds.1 {
+            true => format!("{}: {from}, {}", self.kind.as_str(), self.detail),
+            false => format!(
+                "{}: {from} - {}, {}",
+                self.kind.as_str(),
+                Data::format_ts(self.bounds.1 as f64),
+                self.detail
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    /// Findings sorted by time.
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    pub fn count(&self, kind: Kind) -> usize {
+        self.findings.iter().filter(|f| f.kind == kind).count()
+    }
+
+    /// Merged time ranges of the findings to download again.
+    pub fn affected(&self) -> BoundsSet {
+        self.findings
+            .iter()
+            .fold(BoundsSet::default(), |set, f| set.merge_single(f.bounds))
+    }
+}
+
+/// Checks `klines` which are expected to cover the `loaded` ranges, gaps are looked for
+/// only within them. Returns beyond `z_score` standard deviations from the mean return
+/// are reported as extreme.
+pub fn check(klines: &[Kline], loaded: &BoundsSet, z_score: f64) -> Report {
+    let mut sorted = klines.to_vec();
+    sorted.sort_by_key(|k| k.t_open);
+
+    let mut findings = vec![];
+    sorted
+        .chunk_by(|a, b| a.t_open == b.t_open)
+        .filter(|same| same.len() > 1)
+        .for_each(|same| {
+            findings.push(Finding {
+                kind: Kind::Duplicate,
+                bounds: Bounds(same[0].t_open, same[0].t_close),
+                detail: format!("{} candles", same.len()),
+            })
+        });
+    sorted.dedup_by_key(|k| k.t_open);
+
+    sorted.windows(2).for_each(|w| {
+        let gap = Bounds(w[0].t_close + 1, w[1].t_open - 1);
+        let within = loaded.vals().iter().any(|b| b.0 <= gap.0 && gap.1 <= b.1);
+        if gap.0 <= gap.1 && within {
+            findings.push(Finding {
+                kind: Kind::Gap,
+                bounds: gap,
+                detail: format!("{} missing", missing(&w[0], gap)),
+            });
+        }
+    });
+
+    sorted.iter().for_each(|k| {
+        let bounds = Bounds(k.t_open, k.t_close);
+        if k.volume == 0.0 {
+            findings.push(Finding {
+                kind: Kind::ZeroVolume,
+                bounds,
+                detail: format!("close {}", k.close),
+            });
+        }
+        if k.high < k.open.max(k.close) || k.low > k.open.min(k.close) {
+            findings.push(Finding {
+                kind: Kind::InvalidRange,
+                bounds,
+                detail: format!("o {} h {} l {} c {}", k.open, k.high, k.low, k.close),
+            });
+        }
+    });
+
+    let returns: Vec<(&Kline, f64)> = sorted
+        .windows(2)
+        .filter(|w| w[0].close != 0.0)
+        .map(|w| (&w[1], w[1].close as f64 / w[0].close as f64 - 1.0))
+        .collect();
+    let n = returns.len() as f64;
+    let mean = returns.iter().map(|(_, r)| r).sum::<f64>() / n;
+    let std = (returns.iter().map(|(_, r)| (r - mean).powi(2)).sum::<f64>() / n).sqrt();
+    if std > 0.0 {
+        returns.iter().for_each(|(k, r)| {
+            let z = (r - mean) / std;
+            if z.abs() > z_score {
+                findings.push(Finding {
+                    kind: Kind::ExtremeReturn,
+                    bounds: Bounds(k.t_open, k.t_close),
+                    detail: format!("{:+.2}%, z {z:.1}", r * 100.0),
+                });
+            }
+        });
+    }
+
+    findings.sort_by_key(|f| (f.bounds.0, f.kind));
+    Report { findings }
+}
+
+/// Number of candles as wide as `prev` fitting into the gap.
+fn missing(prev: &Kline, gap: Bounds) -> i64 {
+    let width = (prev.t_close - prev.t_open + 1).max(1);
+    (gap.1 - gap.0 + 1 + width - 1) / width
+}
+
+#[cfg(test)]
+mod quality_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+
+    fn loaded(klines: &[Kline]) -> BoundsSet {
+        BoundsSet::new(vec![Bounds(
+            klines[0].t_open,
+            klines[klines.len() - 1].t_close,
+        )])
+    }
+
+    #[test]
+    fn test_clean_data() {
+        let klines = mock::klines(0, MINUTE, 50);
+
+        assert!(check(&klines, &loaded(&klines), 4.0).findings.is_empty());
+    }
+
+    #[test]
+    fn test_gaps_and_duplicates() {
+        let mut klines = mock::klines(0, MINUTE, 10);
+        let all = loaded(&klines);
+        klines.drain(3..5);
+        klines.push(klines[0]);
+
+        let report = check(&klines, &all, 4.0);
+
+        assert_eq!(report.count(Kind::Duplicate), 1);
+        assert_eq!(report.count(Kind::Gap), 1);
+        let gap = report
+            .findings
+            .iter()
+            .find(|f| f.kind == Kind::Gap)
+            .unwrap();
+        assert_eq!(gap.bounds, Bounds(3 * MINUTE, 5 * MINUTE - 1));
+        assert_eq!(gap.detail, "2 missing");
+        assert_eq!(
+            report.affected().vals(),
+            vec![Bounds(0, MINUTE - 1), Bounds(3 * MINUTE, 5 * MINUTE - 1)]
+        );
+    }
+
+    #[test]
+    fn test_gap_outside_loaded_ranges() {
+        let klines = [
+            mock::klines(0, MINUTE, 3),
+            mock::klines(10 * MINUTE, MINUTE, 3),
+        ]
+        .concat();
+        let loaded = BoundsSet::new(vec![
+            Bounds(0, 3 * MINUTE - 1),
+            Bounds(10 * MINUTE, 13 * MINUTE - 1),
+        ]);
+
+        assert_eq!(check(&klines, &loaded, 4.0).count(Kind::Gap), 0);
+    }
+
+    #[test]
+    fn test_anomalies() {
+        let mut klines = mock::klines(0, MINUTE, 50);
+        klines[5].volume = 0.0;
+        klines[6].high = klines[6].close - 1.0;
+        klines[20].close *= 3.0;
+        klines[20].high = klines[20].close;
+
+        let report = check(&klines, &loaded(&klines), 2.0);
+
+        assert_eq!(report.count(Kind::ZeroVolume), 1);
+        assert_eq!(report.count(Kind::InvalidRange), 1);
+        let extreme: Vec<Bounds> = report
+            .findings
+            .iter()
+            .filter(|f| f.kind == Kind::ExtremeReturn)
+            .map(|f| f.bounds)
+            .collect();
+        // the jump up and the drop back
+        assert_eq!(
+            extreme,
+            vec![
+                Bounds(20 * MINUTE, 21 * MINUTE - 1),
+                Bounds(21 * MINUTE, 22 * MINUTE - 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Reloading the last candles once the candle of the interval closes.
+
+use crate::sources::binance::{Interval, Kline};
+
+use super::bounds::Bounds;
+
+/// Delay after the interval boundary giving the exchange time to close the candle.
+const CLOSE_DELAY_MILLIS: i64 = 2_000;
+const BACKOFF_MIN_MILLIS: i64 = 5_000;
+const BACKOFF_MAX_MILLIS: i64 = 5 * 60 * 1000;
+
+/// Schedule of refreshes in millis since epoch, kept in sync with the symbol and interval.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schedule {
+    synced: Option<(String, Interval)>,
+    next: Option<i64>,
+    failures: u32,
+}
+
+impl Schedule {
+    /// Schedules the refresh after the next boundary if the symbol or interval changed.
+    pub fn sync(&mut self, symbol: &str, interval: Interval, now: i64) {
+        let key = (symbol.to_string(), interval);
+        if self.synced.as_ref() == Some(&key) {
+            return;
+        }
+
+        self.synced = Some(key);
+        self.failures = 0;
+        self.next = Some(after_close(now, interval));
+    }
+
+    /// Drops the schedule, the next sync schedules it again.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_due(&self, now: i64) -> bool {
+        self.next.is_some_and(|next| now >= next)
+    }
+
+    /// Millis left until the next refresh.
+    pub fn remaining(&self, now: i64) -> Option<i64> {
+        self.next.map(|next| (next - now).max(0))
+    }
+
+    /// Candles of the last closed and the current interval to request.
+    pub fn tail(&self, now: i64) -> Option<Bounds> {
+        let (_, interval) = self.synced.as_ref()?;
+        Some(Bounds(interval.open(now) - interval.millis(), now))
+    }
+
+    /// Schedules the refresh after the next boundary.
+    pub fn succeeded(&mut self, now: i64) {
+        self.failures = 0;
+        self.next = self
+            .synced
+            .as_ref()
+            .map(|(_, interval)| after_close(now, *interval));
+    }
+
+    /// Refreshes on the next check whether the interval closed or not.
+    pub fn force(&mut self, now: i64) {
+        if self.synced.is_some() {
+            self.failures = 0;
+            self.next = Some(now);
+        }
+    }
+
+    /// Retries with the delay doubling on every failure in a row.
+    pub fn failed(&mut self, now: i64) {
+        self.failures += 1;
+        let delay = BACKOFF_MIN_MILLIS
+            .saturating_mul(1 << (self.failures - 1).min(16))
+            .min(BACKOFF_MAX_MILLIS);
+        self.next = Some(now + delay);
+    }
+}
+
+fn after_close(now: i64, interval: Interval) -> i64 {
+    interval.open(now) + interval.millis() + CLOSE_DELAY_MILLIS
+}
+
+/// Millis left until the candle forming at `now` closes, candles opening every interval
+/// from `last_open`, the open time of the last loaded one.
+pub fn close_remaining(last_open: i64, interval_millis: i64, now: i64) -> i64 {
+    let close = last_open + ((now - last_open).div_euclid(interval_millis) + 1) * interval_millis;
+    close - now
+}
+
+/// Remaining time as `m:ss`, with hours when longer.
+pub fn countdown(millis: i64) -> String {
+    let secs = (millis.max(0) + 999) / 1000;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    match h {
+        0 => format!("{m}:{s:02}"),
+        _ => format!("{h}:{m:02}:{s:02}"),
+    }
+}
+
+/// Replaces candles with fresh ones of the same open time and appends the new ones.
+pub fn merge(klines: &mut Vec<Kline>, fresh: &[Kline]) {
+    fresh.iter().for_each(
+        |f| match klines.iter_mut().rfind(|k| k.t_open == f.t_open) {
+            Some(k) => *k = *f,
+            None => klines.push(*f),
+        },
+    );
+}
+
+/// Fresh candles which closed since the klines were loaded, to be called before merging.
+pub fn newly_closed(klines: &[Kline], fresh: &[Kline]) -> Vec<Kline> {
+    fresh
+        .iter()
+        .filter(|f| f.closed)
+        .filter(|f| {
+            !klines
+                .iter()
+                .rev()
+                .any(|k| k.t_open == f.t_open && k.closed)
+        })
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod refresh_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+
+    #[test]
+    fn test_after_boundary() {
+        let mut schedule = Schedule::default();
+        schedule.sync("BTCUSDT", Interval::Minute, 10 * MINUTE + 30_000);
+
+        assert!(!schedule.is_due(11 * MINUTE));
+        assert!(schedule.is_due(11 * MINUTE + CLOSE_DELAY_MILLIS));
+        assert_eq!(schedule.remaining(11 * MINUTE), Some(CLOSE_DELAY_MILLIS));
+        assert_eq!(
+            schedule.tail(11 * MINUTE + CLOSE_DELAY_MILLIS),
+            Some(Bounds(10 * MINUTE, 11 * MINUTE + CLOSE_DELAY_MILLIS))
+        );
+
+        schedule.succeeded(11 * MINUTE + CLOSE_DELAY_MILLIS);
+        assert_eq!(
+            schedule.remaining(11 * MINUTE + CLOSE_DELAY_MILLIS),
+            Some(MINUTE)
+        );
+    }
+
+    #[test]
+    fn test_force() {
+        let mut schedule = Schedule::default();
+        schedule.force(0);
+        assert!(!schedule.is_due(0));
+
+        schedule.sync("BTCUSDT", Interval::Minute, 10_000);
+        schedule.failed(10_000);
+        schedule.force(20_000);
+        assert!(schedule.is_due(20_000));
+        schedule.failed(20_000);
+        assert_eq!(schedule.remaining(20_000), Some(BACKOFF_MIN_MILLIS));
+    }
+
+    #[test]
+    fn test_close_remaining() {
+        // the last candle is forming
+        assert_eq!(close_remaining(MINUTE, MINUTE, MINUTE + 15_000), 45_000);
+        assert_eq!(close_remaining(MINUTE, MINUTE, MINUTE), MINUTE);
+        assert_eq!(close_remaining(MINUTE, MINUTE, 2 * MINUTE - 1), 1);
+        // the last candle closed and the next one isn't loaded yet
+        assert_eq!(close_remaining(MINUTE, MINUTE, 3 * MINUTE + 50_000), 10_000);
+    }
+
+    #[test]
+    fn test_countdown() {
+        assert_eq!(countdown(0), "0:00");
+        assert_eq!(countdown(1), "0:01");
+        assert_eq!(countdown(61_000), "1:01");
+        assert_eq!(countdown(3_600_000 + 5_000), "1:00:05");
+        assert_eq!(countdown(-5), "0:00");
+    }
+
+    #[test]
+    fn test_resync_on_change() {
+        let mut schedule = Schedule::default();
+        schedule.sync("BTCUSDT", Interval::Minute, 0);
+        schedule.failed(0);
+        schedule.sync("BTCUSDT", Interval::Minute, 30_000);
+        assert_eq!(schedule.remaining(0), Some(BACKOFF_MIN_MILLIS));
+
+        let hour = 60 * MINUTE;
+        schedule.sync("BTCUSDT", Interval::Hour, 30_000);
+        assert_eq!(
+            schedule.remaining(30_000),
+            Some(hour - 30_000 + CLOSE_DELAY_MILLIS)
+        );
+
+        schedule.sync("ETHUSDT", Interval::Hour, 40_000);
+        assert_eq!(
+            schedule.remaining(40_000),
+            Some(hour - 40_000 + CLOSE_DELAY_MILLIS)
+        );
+    }
+
+    #[test]
+    fn test_backoff() {
+        let mut schedule = Schedule::default();
+        schedule.sync("BTCUSDT", Interval::Minute, 0);
+
+        let delays: Vec<i64> = (0..10)
+            .map(|_| {
+                schedule.failed(0);
+                schedule.remaining(0).unwrap()
+            })
+            .collect();
+
+        assert_eq!(&delays[..4], &[5_000, 10_000, 20_000, 40_000]);
+        assert_eq!(delays[9], BACKOFF_MAX_MILLIS);
+
+        schedule.succeeded(0);
+        schedule.failed(0);
+        assert_eq!(schedule.remaining(0), Some(BACKOFF_MIN_MILLIS));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut klines = mock::klines(0, MINUTE, 3);
+        let mut fresh = mock::klines(2 * MINUTE, MINUTE, 2);
+        fresh[0].close = 1.0;
+
+        merge(&mut klines, &fresh);
+
+        assert_eq!(klines.len(), 4);
+        assert_eq!(klines[2].close, 1.0);
+        assert_eq!(klines[3].t_open, 3 * MINUTE);
+    }
+
+    #[test]
+    fn test_newly_closed() {
+        let mut klines = mock::klines(0, MINUTE, 3);
+        klines[2].closed = false;
+        let mut fresh = mock::klines(MINUTE, MINUTE, 3);
+        fresh[2].closed = false;
+
+        let closed = newly_closed(&klines, &fresh);
+
+        assert_eq!(
+            closed.iter().map(|k| k.t_open).collect::<Vec<_>>(),
+            vec![2 * MINUTE]
+        );
+    }
+}
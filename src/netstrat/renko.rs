@@ -0,0 +1,218 @@
+//! Renko bricks built from close prices.
+
+use serde::{Deserialize, Serialize};
+
+use crate::sources::binance::Kline;
+
+use super::{data::Data, indicators::math};
+
+/// How the height of the bricks is picked.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BrickSize {
+    /// Price distance.
+    Absolute(f64),
+    /// Last value of the average true range over the period.
+    Atr(usize),
+}
+
+impl Default for BrickSize {
+    fn default() -> Self {
+        BrickSize::Atr(14)
+    }
+}
+
+impl BrickSize {
+    /// Brick height for the data, `None` when it can't be computed or is not positive.
+    pub fn resolve(&self, data: &Data) -> Option<f64> {
+        let size = match self {
+            BrickSize::Absolute(size) => Some(*size),
+            BrickSize::Atr(period) => {
+                math::atr(&data.highs(), &data.lows(), &data.closes(), *period)
+                    .into_iter()
+                    .rev()
+                    .find_map(|v| v)
+            }
+        };
+
+        size.filter(|s| *s > 0.0 && s.is_finite())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Brick {
+    pub open: f64,
+    pub close: f64,
+    /// Open time of the first candle since the previous brick.
+    pub t_open: i64,
+    /// Close time of the candle which completed the brick.
+    pub t_close: i64,
+    pub volume: f64,
+}
+
+impl Brick {
+    pub fn is_up(&self) -> bool {
+        self.close > self.open
+    }
+}
+
+/// Bricks of `size` from closes starting at the first close. A new brick is added once
+/// the close moves a whole brick beyond the top or the bottom of the last one, so reversals
+/// take two bricks of movement. Volume of candles is summed into the brick they complete,
+/// a candle completing several bricks splits its volume evenly between them.
+pub fn bricks(klines: &[Kline], size: f64) -> Vec<Brick> {
+    let mut bricks: Vec<Brick> = vec![];
+    let (first, rest) = match klines.split_first() {
+        Some(split) if size > 0.0 => split,
+        _ => return bricks,
+    };
+
+    let (mut low, mut high) = (first.close as f64, first.close as f64);
+    let (mut from, mut volume) = (first.t_open, first.volume as f64);
+    rest.iter().for_each(|k| {
+        let close = k.close as f64;
+        let mut formed = vec![];
+        while close >= high + size {
+            formed.push((high, high + size));
+            (low, high) = (high, high + size);
+        }
+        while close <= low - size {
+            formed.push((low, low - size));
+            (low, high) = (low - size, low);
+        }
+
+        if formed.is_empty() {
+            volume += k.volume as f64;
+            return;
+        }
+
+        let share = k.volume as f64 / formed.len() as f64;
+        formed.iter().enumerate().for_each(|(i, (open, close))| {
+            bricks.push(Brick {
+                open: *open,
+                close: *close,
+                t_open: from,
+                t_close: k.t_close,
+                volume: share + if i == 0 { volume } else { 0.0 },
+            });
+        });
+        (from, volume) = (k.t_close + 1, 0.0);
+    });
+
+    bricks
+}
+
+/// Bricks as candles spanning their index on the time axis, brick `i` covers `i..i+1`.
+pub fn to_klines(bricks: &[Brick]) -> Vec<Kline> {
+    bricks
+        .iter()
+        .enumerate()
+        .map(|(i, b)| Kline {
+            t_open: i as i64,
+            t_close: i as i64 + 1,
+            open: b.open as f32,
+            close: b.close as f32,
+            high: b.open.max(b.close) as f32,
+            low: b.open.min(b.close) as f32,
+            volume: b.volume as f32,
+            closed: true,
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod renko_tests {
+    use super::*;
+
+    fn klines(closes: &[f32]) -> Vec<Kline> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, c)| Kline {
+                t_open: i as i64 * 10,
+                t_close: i as i64 * 10 + 9,
+                close: *c,
+                high: *c,
+                low: *c,
+                volume: 1.0,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn shape(bricks: &[Brick]) -> Vec<(f64, f64)> {
+        bricks.iter().map(|b| (b.open, b.close)).collect()
+    }
+
+    #[test]
+    fn test_bricks_up_and_reversal() {
+        let bricks = bricks(&klines(&[10.0, 11.5, 12.0, 11.0, 10.5, 9.5, 8.9]), 1.0);
+
+        // reversal from 11..12 needs a close at 10 or lower
+        assert_eq!(
+            shape(&bricks),
+            vec![(10.0, 11.0), (11.0, 12.0), (11.0, 10.0), (10.0, 9.0)]
+        );
+        assert_eq!(
+            bricks
+                .iter()
+                .map(|b| [b.t_open, b.t_close])
+                .collect::<Vec<_>>(),
+            vec![[0, 19], [20, 29], [30, 59], [60, 69]]
+        );
+        assert_eq!(
+            bricks.iter().map(|b| b.volume).collect::<Vec<_>>(),
+            vec![2.0, 1.0, 3.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_gap_forms_several_bricks() {
+        let bricks = bricks(&klines(&[100.0, 100.5, 103.2]), 1.0);
+
+        assert_eq!(
+            shape(&bricks),
+            vec![(100.0, 101.0), (101.0, 102.0), (102.0, 103.0)]
+        );
+        assert!(bricks.iter().all(|b| b.t_close == 29 && b.is_up()));
+        assert_eq!(
+            bricks.iter().map(|b| b.volume).collect::<Vec<_>>(),
+            vec![2.0 + 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0]
+        );
+    }
+
+    #[test]
+    fn test_no_bricks() {
+        assert!(bricks(&klines(&[10.0, 10.5, 9.5]), 1.0).is_empty());
+        assert!(bricks(&klines(&[10.0, 20.0]), 0.0).is_empty());
+        assert!(bricks(&[], 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_to_klines() {
+        let klines = to_klines(&bricks(&klines(&[10.0, 12.0, 9.0]), 1.0));
+
+        assert_eq!(
+            klines
+                .iter()
+                .map(|k| (k.t_open, k.open, k.close, k.high, k.low))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 10.0, 11.0, 11.0, 10.0),
+                (1, 11.0, 12.0, 12.0, 11.0),
+                (2, 11.0, 10.0, 11.0, 10.0),
+                (3, 10.0, 9.0, 10.0, 9.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_size() {
+        let data = Data::new(klines(&[10.0, 12.0, 9.0]));
+
+        assert_eq!(BrickSize::Absolute(2.0).resolve(&data), Some(2.0));
+        assert_eq!(BrickSize::Absolute(0.0).resolve(&data), None);
+        assert!(BrickSize::Atr(2).resolve(&data).is_some());
+        assert_eq!(BrickSize::Atr(10).resolve(&data), None);
+    }
+}
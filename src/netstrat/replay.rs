@@ -0,0 +1,186 @@
+//! Stepping through loaded candles one by one, everything is computed only from
+//! the candles up to the replay cursor.
+
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDateTime;
+
+use super::data::{self, Data};
+
+/// Format of timestamps entered to jump to, the one of [`Data::format_ts`].
+const TS_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub struct Replay {
+    /// Count of candles shown, the last shown one is at `cursor - 1`.
+    cursor: usize,
+    /// Count of loaded candles.
+    len: usize,
+    playing: bool,
+    /// Candles stepped per second while playing.
+    pub speed: f64,
+    last_step: Instant,
+}
+
+impl Replay {
+    /// Starts with the candles up to the one containing `ts`, at least one is shown.
+    pub fn new(data: &Data, ts: i64) -> Self {
+        Self {
+            cursor: cursor_at(data, ts),
+            len: data.vals.len(),
+            playing: false,
+            speed: 2.0,
+            last_step: Instant::now(),
+        }
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.len
+    }
+
+    pub fn play(&mut self) {
+        self.playing = !self.is_finished();
+        self.last_step = Instant::now();
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Shows one more candle, returns whether there was one.
+    pub fn step_forward(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+
+        self.cursor += 1;
+        true
+    }
+
+    /// Hides the last shown candle keeping the first one, returns whether it was hidden.
+    pub fn step_back(&mut self) -> bool {
+        if self.cursor <= 1 {
+            return false;
+        }
+
+        self.cursor -= 1;
+        true
+    }
+
+    pub fn jump_to(&mut self, data: &Data, ts: i64) {
+        self.cursor = cursor_at(data, ts);
+        self.len = data.vals.len();
+    }
+
+    /// Steps forward while playing once a step is due at `now`, pausing at the end.
+    /// Returns whether the cursor moved.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if !self.playing || self.speed <= 0.0 {
+            return false;
+        }
+
+        let step = Duration::from_secs_f64(1.0 / self.speed);
+        if now.duration_since(self.last_step) < step {
+            return false;
+        }
+
+        self.last_step = now;
+        let stepped = self.step_forward();
+        if self.is_finished() {
+            self.playing = false;
+        }
+
+        stepped
+    }
+}
+
+/// Count of candles opened at or before `ts`, at least one unless there are none.
+fn cursor_at(data: &Data, ts: i64) -> usize {
+    data::count_to(&data.vals, ts).max(1).min(data.vals.len())
+}
+
+/// Parses a timestamp in millis from the format candle times are shown in.
+pub fn parse_ts(s: &str) -> Option<i64> {
+    NaiveDateTime::parse_from_str(s.trim(), TS_FORMAT)
+        .ok()
+        .map(|dt| dt.and_utc().timestamp_millis())
+}
+
+#[cfg(test)]
+mod replay_tests {
+    use crate::sources::binance::Kline;
+
+    use super::*;
+
+    fn data(len: i64) -> Data {
+        Data::new(
+            (0..len)
+                .map(|i| Kline {
+                    t_open: i * 10,
+                    t_close: i * 10 + 9,
+                    closed: true,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_starts_at_candle_containing_ts() {
+        let data = data(5);
+
+        assert_eq!(Replay::new(&data, 25).cursor(), 3);
+        assert_eq!(Replay::new(&data, 20).cursor(), 3);
+        assert_eq!(Replay::new(&data, -5).cursor(), 1);
+        assert_eq!(Replay::new(&data, 1000).cursor(), 5);
+    }
+
+    #[test]
+    fn test_steps() {
+        let data = data(3);
+        let mut replay = Replay::new(&data, 0);
+
+        assert!(!replay.step_back());
+        assert!(replay.step_forward());
+        assert!(replay.step_forward());
+        assert!(replay.is_finished());
+        assert!(!replay.step_forward());
+        assert!(replay.step_back());
+        assert_eq!(replay.cursor(), 2);
+
+        replay.jump_to(&data, 5);
+        assert_eq!(replay.cursor(), 1);
+    }
+
+    #[test]
+    fn test_tick_pauses_at_end() {
+        let data = data(2);
+        let mut replay = Replay::new(&data, 0);
+        replay.speed = 10.0;
+        let now = Instant::now();
+
+        assert!(!replay.tick(now + Duration::from_secs(1)));
+        replay.play();
+        assert!(!replay.tick(replay.last_step));
+        assert!(replay.tick(replay.last_step + Duration::from_millis(100)));
+        assert_eq!(replay.cursor(), 2);
+        assert!(!replay.is_playing());
+    }
+
+    #[test]
+    fn test_parse_ts() {
+        assert_eq!(parse_ts("2022-07-31 14:20:17"), Some(1659277217000));
+        assert_eq!(
+            parse_ts(&Data::format_ts(1659277217000.0)),
+            Some(1659277217000)
+        );
+        assert_eq!(parse_ts("2022-07-31"), None);
+    }
+}
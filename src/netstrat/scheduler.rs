@@ -0,0 +1,568 @@
+//! Recurring exports appending the candles closed since the last successful run of a job
+//! to a file per symbol, run in the background while the app runs.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use quick_error::ResultExt;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::{
+    errors::{Error, Result},
+    sources::binance::{Client, Interval, Kline},
+};
+
+use super::{
+    aggregate::EmptyBuckets,
+    bounds::Bounds,
+    download,
+    export::{self, Columns, ExportOptions, TimeFormat},
+    import, influx,
+    props::Props,
+};
+
+/// Period the runner checks for due jobs.
+const TICK: Duration = Duration::from_secs(5);
+/// Runs kept in the history.
+const HISTORY_LEN: usize = 200;
+/// Bytes read from the end of a file to find its last candle.
+const TAIL_BYTES: u64 = 4096;
+
+/// How the candles of a job are appended.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobFormat {
+    #[default]
+    Csv,
+    /// Csv with the basic columns only.
+    CsvBasic,
+    LineProtocol,
+}
+
+impl JobFormat {
+    pub const ALL: [JobFormat; 3] = [JobFormat::Csv, JobFormat::CsvBasic, JobFormat::LineProtocol];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobFormat::Csv => "csv",
+            JobFormat::CsvBasic => "csv basic",
+            JobFormat::LineProtocol => "line protocol",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            JobFormat::Csv | JobFormat::CsvBasic => "csv",
+            JobFormat::LineProtocol => "lp",
+        }
+    }
+}
+
+/// Export of the symbols run every `cadence_mins` minutes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Job {
+    pub id: u64,
+    pub symbols: Vec<String>,
+    /// Name of the interval, e.g. `1m`.
+    pub interval: String,
+    pub format: JobFormat,
+    /// Directory the files of the symbols are appended to.
+    pub destination: String,
+    pub cadence_mins: u32,
+    pub enabled: bool,
+    /// Close time in millis of the last candle requested by a successful run.
+    pub synced_to: Option<i64>,
+    /// Time in millis the last run started.
+    pub last_run: Option<i64>,
+}
+
+impl Default for Job {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            symbols: vec![],
+            interval: Interval::Minute.as_str().to_string(),
+            format: Default::default(),
+            destination: ".".to_string(),
+            cadence_mins: 60,
+            enabled: true,
+            synced_to: None,
+            last_run: None,
+        }
+    }
+}
+
+impl Job {
+    pub fn interval(&self) -> Option<Interval> {
+        Interval::parse(&self.interval)
+    }
+
+    fn cadence_millis(&self) -> i64 {
+        self.cadence_mins.max(1) as i64 * 60 * 1000
+    }
+
+    /// File the candles of the symbol are appended to, e.g. `BTCUSDT_1m.csv`.
+    pub fn path(&self, symbol: &str) -> PathBuf {
+        Path::new(&self.destination).join(format!(
+            "{symbol}_{}.{}",
+            self.interval,
+            self.format.extension()
+        ))
+    }
+
+    /// Time in millis of the next run, none when it never ran.
+    pub fn next_run(&self) -> Option<i64> {
+        self.last_run.map(|last| last + self.cadence_millis())
+    }
+
+    pub fn is_due(&self, now: i64) -> bool {
+        self.enabled && self.next_run().is_none_or(|next| now >= next)
+    }
+
+    /// Candles closed since the last successful run, or within the last cadence on the
+    /// first one. None when no candle closed since.
+    pub fn bounds(&self, now: i64) -> Option<Bounds> {
+        let interval = self.interval()?;
+        let end = interval.open(now) - 1;
+        let start = match self.synced_to {
+            Some(synced) => synced + 1,
+            None => interval.open(now - self.cadence_millis()),
+        };
+
+        (start < end).then_some(Bounds(start, end))
+    }
+}
+
+/// Outcome of a run of a job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub job: u64,
+    /// Time in millis the run started.
+    pub at: i64,
+    /// Candles appended to the files of all symbols.
+    pub appended: usize,
+    /// Failures of the run, prefixed by the symbol they happened for.
+    pub errors: Vec<String>,
+    /// Close time of the last candle requested, set when all symbols succeeded.
+    pub synced_to: Option<i64>,
+}
+
+impl Run {
+    pub fn succeeded(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Open time in millis of the last candle of the file, none when it has no candles.
+fn last_open(path: &Path, format: JobFormat) -> Result<Option<i64>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut f = File::open(path).context(path)?;
+    let len = f.metadata().context(path)?.len();
+    f.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))
+        .context(path)?;
+    let mut tail = vec![];
+    f.read_to_end(&mut tail).context(path)?;
+    let tail = String::from_utf8_lossy(&tail);
+
+    let Some(last) = tail.lines().rev().find(|l| !l.trim().is_empty()) else {
+        return Ok(None);
+    };
+    Ok(match format {
+        JobFormat::Csv | JobFormat::CsvBasic => last.split(',').next().and_then(import::parse_ts),
+        JobFormat::LineProtocol => last
+            .split_whitespace()
+            .last()
+            .and_then(|ns| ns.parse::<i128>().ok())
+            .map(|ns| (ns / 1_000_000) as i64),
+    })
+}
+
+/// Appends the klines opened after the last candle of the file, so candles of runs
+/// overlapping the previous one are not written twice. Returns how many were appended.
+pub fn append(
+    path: &Path,
+    symbol: &str,
+    interval: Interval,
+    klines: &[Kline],
+    format: JobFormat,
+    times: TimeFormat,
+) -> Result<usize> {
+    let last = last_open(path, format)?;
+    let fresh: Vec<Kline> = klines
+        .iter()
+        .filter(|k| last.is_none_or(|last| k.t_open > last))
+        .copied()
+        .collect();
+    if fresh.is_empty() {
+        return Ok(0);
+    }
+
+    match format {
+        JobFormat::Csv => export::append_csv(path, &fresh, Columns::All, times),
+        JobFormat::CsvBasic => export::append_csv(path, &fresh, Columns::Basic, times),
+        JobFormat::LineProtocol => influx::append_to_file(path, symbol, interval, &fresh),
+    }?;
+
+    Ok(fresh.len())
+}
+
+/// Downloads the candles closed since the last successful run of the job and appends them
+/// to the files of its symbols.
+pub async fn run(client: &Client, job: &Job, times: TimeFormat, now: i64) -> Run {
+    let mut run = Run {
+        job: job.id,
+        at: now,
+        appended: 0,
+        errors: vec![],
+        synced_to: None,
+    };
+    let Some(interval) = job.interval() else {
+        run.errors
+            .push(format!("unknown interval {}", job.interval));
+        return run;
+    };
+    let Some(bounds) = job.bounds(now) else {
+        run.synced_to = job.synced_to;
+        return run;
+    };
+    let dir = Path::new(&job.destination);
+    if let Err(err) = fs::create_dir_all(dir).context(dir).map_err(Error::from) {
+        run.errors.push(err.user_message());
+        return run;
+    }
+
+    let (Some(start), Some(end)) = (
+        DateTime::from_timestamp_millis(bounds.0),
+        DateTime::from_timestamp_millis(bounds.1),
+    ) else {
+        run.errors.push(format!("invalid range {bounds:?}"));
+        return run;
+    };
+    let props = Props::new(
+        start.naive_utc(),
+        end.naive_utc(),
+        interval,
+        EmptyBuckets::Skip,
+    );
+    for symbol in &job.symbols {
+        let appended = download::download(client, symbol, &props)
+            .await
+            .and_then(|klines| {
+                let closed: Vec<Kline> = klines
+                    .into_iter()
+                    .filter(|k| k.closed && k.t_close <= bounds.1)
+                    .collect();
+                append(
+                    &job.path(symbol),
+                    symbol,
+                    interval,
+                    &closed,
+                    job.format,
+                    times,
+                )
+            });
+        match appended {
+            Ok(n) => run.appended += n,
+            Err(err) => {
+                error!("scheduled export of {symbol} failed: {}", err.chain());
+                run.errors.push(format!("{symbol}: {}", err.user_message()));
+            }
+        }
+    }
+    if run.succeeded() {
+        run.synced_to = Some(bounds.1);
+    }
+    info!(
+        "job {} appended {} candles with {} errors",
+        job.id,
+        run.appended,
+        run.errors.len()
+    );
+
+    run
+}
+
+#[derive(Default)]
+struct State {
+    jobs: Vec<Job>,
+    /// Latest runs, the newest last.
+    history: VecDeque<Run>,
+    /// Runs finished since the start.
+    runs: usize,
+    running: HashSet<u64>,
+    /// Jobs to run on the next tick whether they are due or not.
+    requested: HashSet<u64>,
+}
+
+/// Jobs with their history, clones share them. Runs happen on a task of its own started
+/// by [`Jobs::spawn`].
+#[derive(Clone, Default)]
+pub struct Jobs {
+    state: Arc<Mutex<State>>,
+}
+
+impl Jobs {
+    pub fn jobs(&self) -> Vec<Job> {
+        self.state.lock().unwrap().jobs.clone()
+    }
+
+    pub fn set_jobs(&self, jobs: Vec<Job>) {
+        self.state.lock().unwrap().jobs = jobs;
+    }
+
+    /// Adds the job with a new id returning it.
+    pub fn add(&self, job: Job) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let id = state.jobs.iter().map(|j| j.id).max().unwrap_or_default() + 1;
+        state.jobs.push(Job { id, ..job });
+
+        id
+    }
+
+    pub fn remove(&self, id: u64) {
+        self.state.lock().unwrap().jobs.retain(|j| j.id != id);
+    }
+
+    pub fn set_enabled(&self, id: u64, enabled: bool) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(job) = state.jobs.iter_mut().find(|j| j.id == id) {
+            job.enabled = enabled;
+        }
+    }
+
+    /// Runs the job on the next tick.
+    pub fn run_now(&self, id: u64) {
+        self.state.lock().unwrap().requested.insert(id);
+    }
+
+    pub fn is_running(&self, id: u64) -> bool {
+        self.state.lock().unwrap().running.contains(&id)
+    }
+
+    /// Latest runs, the newest first.
+    pub fn history(&self) -> Vec<Run> {
+        let state = self.state.lock().unwrap();
+        state.history.iter().rev().cloned().collect()
+    }
+
+    /// Runs finished since the start.
+    pub fn runs(&self) -> usize {
+        self.state.lock().unwrap().runs
+    }
+
+    /// Due and requested jobs which are not running, marked as running since now.
+    pub fn take_due(&self, now: i64) -> Vec<Job> {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        let mut due = vec![];
+        for job in state.jobs.iter_mut() {
+            let requested = state.requested.remove(&job.id);
+            if state.running.contains(&job.id) || !(requested || job.is_due(now)) {
+                continue;
+            }
+            job.last_run = Some(now);
+            state.running.insert(job.id);
+            due.push(job.clone());
+        }
+        state.requested.clear();
+
+        due
+    }
+
+    /// Records the run and the candles its job is synced to.
+    pub fn finish(&self, run: Run) {
+        let mut state = self.state.lock().unwrap();
+        state.running.remove(&run.job);
+        if let Some(job) = state.jobs.iter_mut().find(|j| j.id == run.job) {
+            job.synced_to = run.synced_to.or(job.synced_to);
+        }
+        state.history.push_back(run);
+        if state.history.len() > HISTORY_LEN {
+            state.history.pop_front();
+        }
+        state.runs += 1;
+    }
+
+    /// Starts the task running due jobs one after another, with the times of the options.
+    pub fn spawn(&self, client: Client, options: ExportOptions) {
+        let jobs = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(TICK);
+            loop {
+                ticker.tick().await;
+                let now = Utc::now().timestamp_millis();
+                for job in jobs.take_due(now) {
+                    jobs.finish(run(&client, &job, options.times(), now).await);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod scheduler_tests {
+    use crate::sources::binance::mock::{self, MockBinance};
+
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+    /// 2022-07-01 00:00 UTC, recent enough for epoch millis not to be read as seconds.
+    const START: i64 = 1_656_633_600_000;
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("netstrat_scheduler_{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn job(dir: &Path) -> Job {
+        Job {
+            id: 1,
+            symbols: vec!["BTCUSDT".to_string()],
+            destination: dir.to_string_lossy().to_string(),
+            cadence_mins: 5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_due_every_cadence() {
+        let mut job = job(Path::new("."));
+        assert!(job.is_due(START));
+
+        job.last_run = Some(START);
+        assert!(!job.is_due(START + 5 * MINUTE - 1));
+        assert!(job.is_due(START + 5 * MINUTE));
+
+        job.enabled = false;
+        assert!(!job.is_due(START + 10 * MINUTE));
+    }
+
+    #[test]
+    fn test_bounds_since_last_success() {
+        let mut job = job(Path::new("."));
+        let now = START + 10 * MINUTE + 30_000;
+
+        // the last cadence on the first run, up to the last closed candle
+        assert_eq!(
+            job.bounds(now),
+            Some(Bounds(START + 5 * MINUTE, START + 10 * MINUTE - 1))
+        );
+
+        job.synced_to = Some(START + 8 * MINUTE - 1);
+        assert_eq!(
+            job.bounds(now),
+            Some(Bounds(START + 8 * MINUTE, START + 10 * MINUTE - 1))
+        );
+
+        job.synced_to = Some(START + 10 * MINUTE - 1);
+        assert_eq!(job.bounds(now), None);
+    }
+
+    #[test]
+    fn test_append_skips_written_candles() {
+        let dir = dir("append");
+        fs::create_dir_all(&dir).unwrap();
+        let klines = mock::klines(START, MINUTE, 5);
+
+        for format in JobFormat::ALL {
+            for times in [TimeFormat::Millis, TimeFormat::Iso { utc_offset: 120 }] {
+                let path = dir.join(format!("BTCUSDT.{}", format.extension()));
+                let _ = fs::remove_file(&path);
+                let append = |klines: &[Kline]| {
+                    append(&path, "BTCUSDT", Interval::Minute, klines, format, times).unwrap()
+                };
+
+                assert_eq!(append(&klines[..3]), 3);
+                // overlaps the candles written before
+                assert_eq!(append(&klines[1..]), 2);
+                assert_eq!(append(&klines), 0);
+
+                let content = fs::read_to_string(&path).unwrap();
+                let header = matches!(format, JobFormat::Csv | JobFormat::CsvBasic);
+                assert_eq!(content.lines().count(), 5 + header as usize, "{format:?}");
+                assert_eq!(
+                    last_open(&path, format).unwrap(),
+                    Some(START + 4 * MINUTE),
+                    "{format:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_due_jobs_are_taken_once() {
+        let jobs = Jobs::default();
+        let id = jobs.add(job(Path::new(".")));
+        let other = jobs.add(Job {
+            enabled: false,
+            ..job(Path::new("."))
+        });
+        assert_eq!((id, other), (1, 2));
+
+        let due = jobs.take_due(START);
+        assert_eq!(due.iter().map(|j| j.id).collect::<Vec<_>>(), vec![id]);
+        assert!(jobs.is_running(id));
+        assert!(jobs.take_due(START + 10 * MINUTE).is_empty());
+
+        jobs.finish(Run {
+            job: id,
+            at: START,
+            appended: 3,
+            errors: vec![],
+            synced_to: Some(START - 1),
+        });
+        assert!(!jobs.is_running(id));
+        assert_eq!(jobs.jobs()[0].synced_to, Some(START - 1));
+        assert_eq!(jobs.jobs()[0].last_run, Some(START));
+        assert_eq!(jobs.runs(), 1);
+        assert!(jobs.take_due(START + MINUTE).is_empty());
+
+        // failures keep the candles synced to
+        jobs.run_now(other);
+        assert_eq!(jobs.take_due(START + MINUTE)[0].id, other);
+        jobs.finish(Run {
+            job: id,
+            at: START + MINUTE,
+            appended: 0,
+            errors: vec!["BTCUSDT: network unreachable".to_string()],
+            synced_to: None,
+        });
+        assert_eq!(jobs.jobs()[0].synced_to, Some(START - 1));
+        assert!(!jobs.history()[0].succeeded());
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_runs_append_once() {
+        let mock = MockBinance::start().await;
+        let klines = mock::klines(START, MINUTE, 10);
+        mock.klines_page(START + 5 * MINUTE, &klines[5..]).await;
+        let dir = dir("run");
+        let mut job = job(&dir);
+        let now = START + 10 * MINUTE + 30_000;
+
+        let first = run(&mock.client(), &job, TimeFormat::Millis, now).await;
+        assert!(first.succeeded(), "{:?}", first.errors);
+        assert_eq!(first.appended, 5);
+        assert_eq!(first.synced_to, Some(START + 10 * MINUTE - 1));
+
+        // the previous run is considered failed, so the same candles are requested again
+        job.last_run = Some(now);
+        let second = run(&mock.client(), &job, TimeFormat::Millis, now).await;
+        assert!(second.succeeded());
+        assert_eq!(second.appended, 0);
+
+        let content = fs::read_to_string(job.path("BTCUSDT")).unwrap();
+        assert_eq!(content.lines().count(), 1 + 5);
+    }
+}
@@ -0,0 +1,466 @@
+//! User scripts computing indicators and signals with [rhai](https://rhai.rs).
+//!
+//! Scripts see candles as arrays `open`, `high`, `low`, `close`, `volume` and `time`
+//! (candle open time in millis), can use `sma(arr, n)`, `ema(arr, n)` and `rsi(arr, n)`
+//! and return a map or an array of maps describing the output:
+//!
+//! ```rhai
+//! let fast = sma(close, 5);
+//! [
+//!     #{ name: "fast", overlay: fast },          // line over the candles
+//!     #{ name: "rsi", panel: rsi(close, 14) },   // line in the indicators panel
+//!     #{ buy: [10, 42], sell: [30] },            // marks at candle indices
+//!     #{ name: "breakout", signals: [            // labeled signals at candle indices
+//!         #{ index: 42, kind: "buy", label: "range high", price: 101.5 },
+//!     ] },
+//! ]
+//! ```
+//!
+//! Value arrays are aligned with candles, `()` marks a missing value.
+//! Signal `kind` is `buy`, `sell` or anything else for neutral ones, `label` and `price` are optional.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use quick_error::ResultExt;
+use rhai::{
+    module_resolvers::DummyModuleResolver, Array, Dynamic, Engine, EvalAltResult, Map, Scope,
+};
+use tracing::debug;
+
+use crate::{
+    errors::{Error, Result},
+    sources::binance::Kline,
+};
+
+use super::{
+    data::Data,
+    indicators::{
+        math, IndicatorOutput, Marker, MarkerKind, Series, SignalKind, SignalPoint, SignalSeries,
+    },
+};
+
+/// Directory scripts are saved to.
+pub const SCRIPTS_DIR: &str = "scripts";
+
+/// Maximum time a script is allowed to run.
+pub const TIME_LIMIT: Duration = Duration::from_millis(500);
+
+const EXTENSION: &str = "rhai";
+const MAX_ARRAY_SIZE: usize = 1_000_000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    pub name: String,
+    pub source: String,
+}
+
+impl Script {
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            source: source.into(),
+        }
+    }
+
+    fn path(&self, dir: &Path) -> PathBuf {
+        dir.join(&self.name).with_extension(EXTENSION)
+    }
+
+    /// Writes script to `dir` returning path of the file.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(dir).context(dir)?;
+        let path = self.path(dir);
+        fs::write(&path, &self.source).context(path.as_path())?;
+
+        Ok(path)
+    }
+
+    pub fn remove(&self, dir: &Path) -> Result<()> {
+        let path = self.path(dir);
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(Error::Io(path, err)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Loads scripts saved in `dir` sorted by name. Missing directory has no scripts.
+pub fn load(dir: &Path) -> Result<Vec<Script>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut scripts = vec![];
+    for entry in fs::read_dir(dir).context(dir)? {
+        let path = entry.context(dir)?.path();
+        if path.extension().is_none_or(|ext| ext != EXTENSION) {
+            continue;
+        }
+        if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+            let source = fs::read_to_string(&path).context(path.as_path())?;
+            scripts.push(Script::new(name, source));
+        }
+    }
+    scripts.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(scripts)
+}
+
+/// Runs script over the data. Execution is terminated once `time_limit` is exceeded.
+pub fn run(source: &str, data: &Data, time_limit: Duration) -> Result<IndicatorOutput> {
+    let engine = engine(time_limit);
+
+    let ast = engine
+        .compile(source)
+        .map_err(|err| Error::Script(err.1.line(), err.0.to_string()))?;
+
+    let res = engine
+        .eval_ast_with_scope::<Dynamic>(&mut scope(data), &ast)
+        .map_err(|err| {
+            let line = err.position().line();
+            match *err {
+                EvalAltResult::ErrorTerminated(_, _) => Error::Script(
+                    line,
+                    format!("time limit of {}ms exceeded", time_limit.as_millis()),
+                ),
+                err => Error::Script(line, err.to_string()),
+            }
+        })?;
+
+    output(data, res)
+}
+
+fn engine(time_limit: Duration) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .set_max_array_size(MAX_ARRAY_SIZE)
+        .set_max_string_size(MAX_ARRAY_SIZE)
+        .on_print(|s| debug!("script: {s}"))
+        .on_debug(|s, _, pos| debug!("script at {pos}: {s}"));
+
+    let started = Instant::now();
+    engine.on_progress(move |_| (started.elapsed() > time_limit).then_some(Dynamic::UNIT));
+
+    engine
+        .register_fn("sma", |vals: Array, n: i64| windowed(vals, n, math::sma))
+        .register_fn("ema", |vals: Array, n: i64| windowed(vals, n, math::ema))
+        .register_fn("rsi", |vals: Array, n: i64| windowed(vals, n, math::rsi));
+
+    engine
+}
+
+fn windowed(vals: Array, n: i64, f: fn(&[f64], usize) -> Vec<Option<f64>>) -> Array {
+    let vals: Vec<f64> = vals
+        .iter()
+        .map(|v| value(v).flatten().unwrap_or(f64::NAN))
+        .collect();
+
+    f(&vals, n.max(0) as usize)
+        .into_iter()
+        .map(|v| v.map_or(Dynamic::UNIT, Dynamic::from_float))
+        .collect()
+}
+
+fn scope(data: &Data) -> Scope<'static> {
+    let floats = |f: fn(&Kline) -> f32| -> Array {
+        data.vals
+            .iter()
+            .map(|k| Dynamic::from_float(f(k) as f64))
+            .collect()
+    };
+
+    let mut scope = Scope::new();
+    scope
+        .push_constant("open", floats(|k| k.open))
+        .push_constant("high", floats(|k| k.high))
+        .push_constant("low", floats(|k| k.low))
+        .push_constant("close", floats(|k| k.close))
+        .push_constant("volume", floats(|k| k.volume))
+        .push_constant(
+            "time",
+            data.vals
+                .iter()
+                .map(|k| Dynamic::from_int(k.t_open))
+                .collect::<Array>(),
+        );
+
+    scope
+}
+
+/// Number value, `Some(None)` for unit and `None` for anything else.
+fn value(v: &Dynamic) -> Option<Option<f64>> {
+    if v.is_unit() {
+        return Some(None);
+    }
+
+    v.as_float()
+        .ok()
+        .or_else(|| v.as_int().ok().map(|i| i as f64))
+        .map(Some)
+}
+
+fn output(data: &Data, res: Dynamic) -> Result<IndicatorOutput> {
+    let mut out = IndicatorOutput::default();
+    if res.is_unit() {
+        return Ok(out);
+    }
+
+    let items = match res.is_array() {
+        true => res.into_array().unwrap_or_default(),
+        false => vec![res],
+    };
+
+    for (i, item) in items.into_iter().enumerate() {
+        let map = item
+            .try_cast::<Map>()
+            .ok_or_else(|| Error::Script(None, format!("output item {i} is not a map")))?;
+        let name = map
+            .get("name")
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| format!("script {i}"));
+
+        if let Some(vals) = map.get("overlay") {
+            out.overlay.push(series(data, &name, vals)?);
+        }
+        if let Some(vals) = map.get("panel") {
+            out.panel.push(series(data, &name, vals)?);
+        }
+        if let Some(idxs) = map.get("buy") {
+            out.markers.extend(markers(data, MarkerKind::Buy, idxs)?);
+        }
+        if let Some(idxs) = map.get("sell") {
+            out.markers.extend(markers(data, MarkerKind::Sell, idxs)?);
+        }
+        if let Some(items) = map.get("signals") {
+            out.signals.push(signals(data, &name, items)?);
+        }
+    }
+
+    Ok(out)
+}
+
+fn array(key: &str, v: &Dynamic) -> Result<Array> {
+    v.clone()
+        .into_array()
+        .map_err(|_| Error::Script(None, format!("{key} must be an array")))
+}
+
+fn series(data: &Data, name: &str, vals: &Dynamic) -> Result<Series> {
+    let vals = array(name, vals)?;
+    if vals.len() != data.vals.len() {
+        return Err(Error::Script(
+            None,
+            format!(
+                "{name} has {} values while there are {} candles",
+                vals.len(),
+                data.vals.len()
+            ),
+        ));
+    }
+
+    let vals = vals
+        .iter()
+        .map(|v| {
+            value(v).ok_or_else(|| Error::Script(None, format!("{name} has non number value {v}")))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Series::from_values(name, data, &vals))
+}
+
+fn markers(data: &Data, kind: MarkerKind, idxs: &Dynamic) -> Result<Vec<Marker>> {
+    let key = match kind {
+        MarkerKind::Buy => "buy",
+        MarkerKind::Sell => "sell",
+    };
+
+    array(key, idxs)?
+        .iter()
+        .map(|idx| {
+            let k = idx
+                .as_int()
+                .ok()
+                .and_then(|i| data.vals.get(usize::try_from(i).ok()?))
+                .ok_or_else(|| {
+                    Error::Script(None, format!("{key} has invalid candle index {idx}"))
+                })?;
+
+            Ok(Marker {
+                kind,
+                point: [
+                    data.center(k),
+                    match kind {
+                        MarkerKind::Buy => k.low as f64,
+                        MarkerKind::Sell => k.high as f64,
+                    },
+                ],
+            })
+        })
+        .collect()
+}
+
+fn signals(data: &Data, name: &str, items: &Dynamic) -> Result<SignalSeries> {
+    let points = array("signals", items)?
+        .into_iter()
+        .map(|item| {
+            let map = item.try_cast::<Map>().ok_or_else(|| {
+                Error::Script(None, format!("{name} has a signal which is not a map"))
+            })?;
+            let k = map
+                .get("index")
+                .and_then(|idx| data.vals.get(usize::try_from(idx.as_int().ok()?).ok()?))
+                .ok_or_else(|| {
+                    Error::Script(
+                        None,
+                        format!("{name} has a signal without a valid candle index"),
+                    )
+                })?;
+            let price = match map.get("price") {
+                Some(p) => value(p).ok_or_else(|| {
+                    Error::Script(None, format!("{name} has non number price {p}"))
+                })?,
+                None => None,
+            };
+
+            Ok(SignalPoint {
+                ts: k.t_open,
+                kind: match map.get("kind").map(|k| k.to_string()).as_deref() {
+                    Some("buy") => SignalKind::Buy,
+                    Some("sell") => SignalKind::Sell,
+                    _ => SignalKind::Neutral,
+                },
+                label: map.get("label").map(|l| l.to_string()).unwrap_or_default(),
+                price,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(SignalSeries {
+        name: name.to_string(),
+        points,
+    })
+}
+
+#[cfg(test)]
+mod scripting_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    fn data() -> Data {
+        Data::new(mock::klines(0, 10, 5))
+    }
+
+    #[test]
+    fn test_overlay_panel_and_markers() {
+        let out = run(
+            r#"[
+                #{ name: "sma", overlay: sma(close, 2) },
+                #{ name: "diff", panel: close.map(|c, i| c - open[i]) },
+                #{ buy: [1], sell: [3] },
+            ]"#,
+            &data(),
+            TIME_LIMIT,
+        )
+        .unwrap();
+
+        assert_eq!(out.overlay[0].name, "sma");
+        assert_eq!(out.overlay[0].points.len(), 4);
+        assert_eq!(out.overlay[0].points[0], [15.0, 101.5]);
+        assert_eq!(out.panel[0].points.len(), 5);
+        assert!(out.panel[0].points.iter().all(|p| p[1] == 1.0));
+        assert_eq!(
+            out.markers,
+            vec![
+                Marker {
+                    kind: MarkerKind::Buy,
+                    point: [15.0, 100.0],
+                },
+                Marker {
+                    kind: MarkerKind::Sell,
+                    point: [35.0, 105.0],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signals() {
+        let out = run(
+            r#"#{ name: "s", signals: [
+                #{ index: 1, kind: "sell", label: "top", price: 7 },
+                #{ index: 2 },
+            ] }"#,
+            &data(),
+            TIME_LIMIT,
+        )
+        .unwrap();
+
+        assert_eq!(
+            out.signals,
+            vec![SignalSeries {
+                name: "s".to_string(),
+                points: vec![
+                    SignalPoint {
+                        ts: 10,
+                        kind: SignalKind::Sell,
+                        label: "top".to_string(),
+                        price: Some(7.0),
+                    },
+                    SignalPoint {
+                        ts: 20,
+                        ..Default::default()
+                    },
+                ],
+            }]
+        );
+
+        let err = run("#{ signals: [#{ index: 9 }] }", &data(), TIME_LIMIT).unwrap_err();
+        assert!(err.to_string().contains("candle index"), "{err}");
+    }
+
+    #[test]
+    fn test_errors_have_lines() {
+        let err = run("let x = 1;\nlet y = ;", &data(), TIME_LIMIT).unwrap_err();
+        assert!(matches!(err, Error::Script(Some(2), _)), "{err}");
+
+        let err = run("let x = 1;\n\nundefined_fn(x)", &data(), TIME_LIMIT).unwrap_err();
+        assert!(matches!(err, Error::Script(Some(3), _)), "{err}");
+    }
+
+    #[test]
+    fn test_invalid_output() {
+        let err = run("#{ overlay: [1.0] }", &data(), TIME_LIMIT).unwrap_err();
+        assert!(err.to_string().contains("5 candles"), "{err}");
+
+        let err = run("#{ buy: [10] }", &data(), TIME_LIMIT).unwrap_err();
+        assert!(err.to_string().contains("invalid candle index"), "{err}");
+    }
+
+    #[test]
+    fn test_infinite_loop_terminated() {
+        let started = Instant::now();
+        let err = run("loop {}", &data(), Duration::from_millis(50)).unwrap_err();
+
+        assert!(err.to_string().contains("time limit"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("netstrat_scripts_{}", std::process::id()));
+        let script = Script::new("fast", "#{ overlay: sma(close, 3) }");
+
+        script.save(&dir).unwrap();
+        assert_eq!(load(&dir).unwrap(), vec![script.clone()]);
+
+        script.remove(&dir).unwrap();
+        assert!(load(&dir).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
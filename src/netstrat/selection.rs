@@ -0,0 +1,54 @@
+//! Symbol selected to be shown on the chart.
+
+/// Where a selection comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// Symbol clicked in the list.
+    List,
+    /// Reload of the shown symbol requested with the button.
+    Reload,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub symbol: String,
+    /// Whether the symbol is loaded again even if it is already shown.
+    pub force: bool,
+    pub source: Source,
+}
+
+impl Selection {
+    pub fn new(symbol: impl Into<String>, source: Source) -> Self {
+        Self {
+            symbol: symbol.into(),
+            force: false,
+            source,
+        }
+    }
+
+    /// Selection loading the symbol again.
+    pub fn reload(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            force: true,
+            source: Source::Reload,
+        }
+    }
+
+    /// Whether the selection changes nothing while `shown` is on the chart.
+    pub fn is_redundant(&self, shown: &str) -> bool {
+        !self.force && self.symbol == shown
+    }
+}
+
+#[cfg(test)]
+mod selection_tests {
+    use super::*;
+
+    #[test]
+    fn test_redundant() {
+        assert!(Selection::new("BTCUSDT", Source::List).is_redundant("BTCUSDT"));
+        assert!(!Selection::new("ETHUSDT", Source::List).is_redundant("BTCUSDT"));
+        assert!(!Selection::reload("BTCUSDT").is_redundant("BTCUSDT"));
+    }
+}
@@ -0,0 +1,130 @@
+//! Snapshots of the open windows and the chart written while the app runs, to restore them
+//! after a crash. A snapshot is marked clean on exit, so only unclean ones are offered.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use quick_error::ResultExt;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::errors::Result;
+
+use super::view::View;
+
+pub const SNAPSHOT_PERIOD: Duration = Duration::from_secs(30);
+const FILE_NAME: &str = "netstrat_session.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Whether the app exited after writing the snapshot.
+    pub clean: bool,
+    /// Visibility of the windows by name.
+    pub windows: BTreeMap<String, bool>,
+    /// Symbol with its props, indicators and chart mode.
+    pub view: Option<View>,
+}
+
+impl Session {
+    pub fn set_visible(&mut self, window: &str, visible: bool) {
+        self.windows.insert(window.to_string(), visible);
+    }
+
+    pub fn visible(&self, window: &str) -> Option<bool> {
+        self.windows.get(window).copied()
+    }
+}
+
+/// File the snapshots are written to, in the temp dir by default.
+#[derive(Debug, Clone)]
+pub struct SessionFile {
+    path: PathBuf,
+}
+
+impl Default for SessionFile {
+    fn default() -> Self {
+        Self::new(std::env::temp_dir().join(FILE_NAME))
+    }
+}
+
+impl SessionFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Last written snapshot, `None` if there is none or it can't be read.
+    pub fn load(&self) -> Option<Session> {
+        let bytes = fs::read(&self.path).ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(session) => Some(session),
+            Err(err) => {
+                debug!("ignoring corrupt session snapshot {:?}: {err}", self.path);
+                None
+            }
+        }
+    }
+
+    /// Writes the snapshot to a temporary file renamed over the previous one, so a crash
+    /// while writing leaves the previous snapshot in place.
+    pub fn store(&self, session: &Session) -> Result<()> {
+        let path = self.path.as_path();
+        let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+        let json = serde_json::to_vec(session).context(path)?;
+        fs::write(&tmp, json).context(tmp.as_path())?;
+        fs::rename(&tmp, path).context(path)?;
+
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod session_tests {
+    use super::*;
+
+    fn file(name: &str) -> SessionFile {
+        let dir = std::env::temp_dir().join("netstrat_session_tests");
+        fs::create_dir_all(&dir).unwrap();
+        let file = SessionFile::new(dir.join(name));
+        fs::remove_file(file.path()).ok();
+        file
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let file = file("roundtrip.json");
+        assert_eq!(file.load(), None);
+
+        let mut session = Session {
+            view: Some(View {
+                symbol: "BTCUSDT".to_string(),
+                interval: "1h".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        session.set_visible("settings", true);
+        file.store(&session).unwrap();
+
+        let loaded = file.load().unwrap();
+        assert_eq!(loaded, session);
+        assert_eq!(loaded.visible("settings"), Some(true));
+        assert_eq!(loaded.visible("scripts"), None);
+    }
+
+    #[test]
+    fn test_corrupt_ignored() {
+        let file = file("corrupt.json");
+        fs::write(file.path(), r#"{"clean": fal"#).unwrap();
+
+        assert_eq!(file.load(), None);
+    }
+}
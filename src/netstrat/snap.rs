@@ -0,0 +1,122 @@
+//! Snapping of pointer positions to candle prices.
+
+use crate::sources::binance::Kline;
+
+use super::axis;
+
+/// Pointer position over the candles with the candle under it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Hover {
+    /// Pointer position in plot coordinates, `y` is snapped if snapping is on.
+    pub point: [f64; 2],
+    pub candle: Option<Kline>,
+    pub snapped: bool,
+}
+
+/// Candle whose time range contains `x`, klines should be sorted by time.
+pub fn candle_at(klines: &[Kline], x: f64) -> Option<&Kline> {
+    let idx = klines.partition_point(|k| (k.t_close as f64) < x);
+    klines.get(idx).filter(|k| k.t_open as f64 <= x)
+}
+
+/// Open, high, low or close of the candle closest to `y`.
+pub fn nearest_ohlc(k: &Kline, y: f64) -> f64 {
+    [k.open, k.high, k.low, k.close]
+        .into_iter()
+        .map(|v| v as f64)
+        .min_by(|a, b| (a - y).abs().total_cmp(&(b - y).abs()))
+        .unwrap_or(y)
+}
+
+/// Hover at `point` with `y` snapped to the closest price of the candle under it.
+pub fn hover(klines: &[Kline], point: [f64; 2], snap: bool) -> Hover {
+    let candle = candle_at(klines, point[0]).copied();
+    match (snap, candle) {
+        (true, Some(k)) => Hover {
+            point: [point[0], nearest_ohlc(&k, point[1])],
+            candle,
+            snapped: true,
+        },
+        _ => Hover {
+            point,
+            candle,
+            snapped: false,
+        },
+    }
+}
+
+/// Moves an anchor point to the closest candle high or low within `radius`, given in plot
+/// units per axis so callers can derive it from a pixel radius, at the center of the candle
+/// slot of `step` width. Far points are left as is.
+pub fn snap_anchor(klines: &[Kline], point: [f64; 2], radius: [f64; 2], step: f64) -> [f64; 2] {
+    let from = klines.partition_point(|k| (k.t_close as f64) < point[0] - radius[0]);
+    klines[from..]
+        .iter()
+        .take_while(|k| k.t_open as f64 <= point[0] + radius[0])
+        .flat_map(|k| {
+            let x = axis::center(k, step);
+            [[x, k.high as f64], [x, k.low as f64]]
+        })
+        .map(|p| {
+            let (dx, dy) = ((p[0] - point[0]) / radius[0], (p[1] - point[1]) / radius[1]);
+            (p, dx * dx + dy * dy)
+        })
+        .filter(|(_, dist)| *dist <= 1.0)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(point, |(p, _)| p)
+}
+
+#[cfg(test)]
+mod snap_tests {
+    use super::*;
+
+    fn klines() -> Vec<Kline> {
+        (0..3)
+            .map(|i| Kline {
+                t_open: i * 10,
+                t_close: i * 10 + 9,
+                open: 10.0 + i as f32,
+                high: 15.0 + i as f32,
+                low: 5.0 + i as f32,
+                close: 12.0 + i as f32,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_candle_at() {
+        let klines = klines();
+
+        assert_eq!(candle_at(&klines, 12.0).map(|k| k.t_open), Some(10));
+        assert_eq!(candle_at(&klines, 9.5), None);
+        assert_eq!(candle_at(&klines, 40.0), None);
+    }
+
+    #[test]
+    fn test_hover_snaps_to_nearest_ohlc() {
+        let klines = klines();
+
+        let snapped = hover(&klines, [12.0, 12.2], true);
+        assert_eq!(snapped.point, [12.0, 13.0]);
+        assert!(snapped.snapped);
+
+        let raw = hover(&klines, [12.0, 12.2], false);
+        assert_eq!(raw.point, [12.0, 12.2]);
+        assert_eq!(raw.candle, Some(klines[1]));
+    }
+
+    #[test]
+    fn test_snap_anchor_within_radius() {
+        let klines = klines();
+
+        assert_eq!(
+            snap_anchor(&klines, [13.0, 15.5], [5.0, 1.0], 10.0),
+            [15.0, 16.0]
+        );
+        assert_eq!(
+            snap_anchor(&klines, [13.0, 10.0], [5.0, 1.0], 10.0),
+            [13.0, 10.0]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Closes of the same instrument from two sources joined by time and the spread between them.
+
+use std::{iter::Peekable, slice};
+
+use crate::sources::binance::Kline;
+
+use super::indicators::{IndicatorOutput, Series, SubPanel};
+
+/// What to do with buckets one of the sources has no candle for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Missing {
+    /// Leave the bucket out.
+    #[default]
+    Omit,
+    /// Take the last close of the source, buckets before its first candle are left out.
+    ForwardFill,
+}
+
+/// Closes of both sources in a bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aligned {
+    pub t_open: i64,
+    pub t_close: i64,
+    pub a: f64,
+    pub b: f64,
+}
+
+impl Aligned {
+    pub fn spread(&self) -> f64 {
+        self.a - self.b
+    }
+
+    /// Spread relative to the close of `b` in percent.
+    pub fn spread_pct(&self) -> Option<f64> {
+        (self.b != 0.0).then(|| self.spread() / self.b * 100.0)
+    }
+}
+
+/// Joins candles of two sources sorted by time on open times aligned to `bucket_millis`,
+/// the last candle of a source within a bucket is taken.
+pub fn join(a: &[Kline], b: &[Kline], bucket_millis: i64, missing: Missing) -> Vec<Aligned> {
+    if bucket_millis <= 0 {
+        return vec![];
+    }
+
+    let bucket = |k: &Kline| k.t_open.div_euclid(bucket_millis) * bucket_millis;
+    let mut buckets: Vec<i64> = a.iter().chain(b).map(bucket).collect();
+    buckets.sort_unstable();
+    buckets.dedup();
+
+    let (mut a, mut b) = (a.iter().peekable(), b.iter().peekable());
+    let (mut last_a, mut last_b) = (None, None);
+    buckets
+        .into_iter()
+        .filter_map(|t_open| {
+            let close_in = |iter: &mut Peekable<slice::Iter<Kline>>| {
+                let mut close = None;
+                while let Some(k) = iter.next_if(|k| bucket(k) <= t_open) {
+                    close = Some(k.close as f64);
+                }
+                close
+            };
+            let (close_a, close_b) = (close_in(&mut a), close_in(&mut b));
+            last_a = close_a.or(last_a);
+            last_b = close_b.or(last_b);
+
+            let (a, b) = match missing {
+                Missing::Omit => (close_a?, close_b?),
+                Missing::ForwardFill => (last_a?, last_b?),
+            };
+            Some(Aligned {
+                t_open,
+                t_close: t_open + bucket_millis - 1,
+                a,
+                b,
+            })
+        })
+        .collect()
+}
+
+/// Spread and spread in percent as panels named after the sources, with closes of both
+/// sources drawn over the candles when `overlay` is set.
+pub fn output(aligned: &[Aligned], names: [&str; 2], overlay: bool) -> IndicatorOutput {
+    let [a, b] = names;
+    // centers of the bucket slots, where the candles of the bucket width are drawn
+    let mid = |j: &Aligned| j.t_open as f64 + (j.t_close - j.t_open + 1) as f64 / 2.0;
+    let series = |name: String, f: &dyn Fn(&Aligned) -> Option<f64>| Series {
+        name,
+        points: aligned
+            .iter()
+            .filter_map(|j| Some([mid(j), f(j)?]))
+            .collect(),
+        color: None,
+    };
+    let panel = |name: String, s: Series| SubPanel {
+        name,
+        series: vec![s],
+        ..Default::default()
+    };
+
+    IndicatorOutput {
+        sub_panels: vec![
+            panel(
+                format!("spread {a} - {b}"),
+                series("spread".to_string(), &|j| Some(j.spread())),
+            ),
+            panel(
+                format!("spread {a} - {b} %"),
+                series("spread %".to_string(), &Aligned::spread_pct),
+            ),
+        ],
+        overlay: match overlay {
+            true => vec![
+                series(format!("{a} close"), &|j| Some(j.a)),
+                series(format!("{b} close"), &|j| Some(j.b)),
+            ],
+            false => vec![],
+        },
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod spread_tests {
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+
+    fn klines(closes: &[(i64, f32)]) -> Vec<Kline> {
+        closes
+            .iter()
+            .map(|(t_open, close)| Kline {
+                t_open: *t_open,
+                t_close: t_open + MINUTE - 1,
+                close: *close,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn closes(aligned: &[Aligned]) -> Vec<(i64, f64, f64)> {
+        aligned.iter().map(|j| (j.t_open, j.a, j.b)).collect()
+    }
+
+    #[test]
+    fn test_join_on_aligned_buckets() {
+        // the second source opens its candles a few millis late
+        let a = klines(&[(0, 10.0), (MINUTE, 11.0)]);
+        let b = klines(&[(5, 9.0), (MINUTE + 3, 10.0)]);
+
+        let aligned = join(&a, &b, MINUTE, Missing::Omit);
+
+        assert_eq!(closes(&aligned), vec![(0, 10.0, 9.0), (MINUTE, 11.0, 10.0)]);
+        assert_eq!(aligned[1].t_close, 2 * MINUTE - 1);
+        assert_eq!(aligned[0].spread(), 1.0);
+        assert!((aligned[1].spread_pct().unwrap() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_missing_buckets() {
+        let a = klines(&[(0, 10.0), (MINUTE, 11.0), (2 * MINUTE, 12.0)]);
+        let b = klines(&[(MINUTE, 10.0), (3 * MINUTE, 13.0)]);
+
+        assert_eq!(
+            closes(&join(&a, &b, MINUTE, Missing::Omit)),
+            vec![(MINUTE, 11.0, 10.0)]
+        );
+        assert_eq!(
+            closes(&join(&a, &b, MINUTE, Missing::ForwardFill)),
+            vec![
+                (MINUTE, 11.0, 10.0),
+                (2 * MINUTE, 12.0, 10.0),
+                (3 * MINUTE, 12.0, 13.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_last_candle_in_bucket() {
+        let a = klines(&[(0, 10.0), (30_000, 10.5)]);
+        let b = klines(&[(0, 9.0)]);
+
+        assert_eq!(
+            closes(&join(&a, &b, MINUTE, Missing::Omit)),
+            vec![(0, 10.5, 9.0)]
+        );
+    }
+
+    #[test]
+    fn test_output() {
+        let aligned = join(
+            &klines(&[(0, 10.0)]),
+            &klines(&[(0, 0.0)]),
+            MINUTE,
+            Missing::Omit,
+        );
+
+        let out = output(&aligned, ["binance", "kraken"], true);
+
+        assert_eq!(out.sub_panels[0].name, "spread binance - kraken");
+        assert_eq!(out.sub_panels[0].series[0].points, vec![[30_000.0, 10.0]]);
+        // percent of zero close is left out
+        assert!(out.sub_panels[1].series[0].points.is_empty());
+        assert_eq!(out.overlay.len(), 2);
+        assert!(output(&aligned, ["binance", "kraken"], false)
+            .overlay
+            .is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 use tracing::info;
 
-use crate::{netstrat::bounds::BoundsSet, sources::binance::Interval};
+use crate::netstrat::bounds::{Bounds, BoundsSet};
 
 use super::{loading_state::LoadingState, props::Props};
 
@@ -26,7 +26,8 @@ impl State {
         let to_load = subtract_res.unwrap();
         info!("Computed difference to load: {to_load:?}.");
 
-        let loading_res = LoadingState::new(&to_load, State::step(props.interval), props.limit);
+        let loading_res =
+            LoadingState::new(&to_load, props.interval.millis() as usize, props.limit);
         if loading_res.is_none() {
             info!("Failed to initialize loading state.");
             return;
@@ -42,15 +43,44 @@ impl State {
         self.props = props.clone();
     }
 
-    pub fn report_loading_error(&mut self) {
-        self.loading.has_error = true;
+    pub fn report_loading_error(&mut self, page: Bounds) {
+        self.loading.page_failed(page);
     }
 
-    fn step(i: Interval) -> usize {
-        match i {
-            Interval::Minute => 60 * 1000,
-            Interval::Hour => 60 * 60 * 1000,
-            Interval::Day => 60 * 60 * 24 * 1000,
-        }
+    /// Forgets evicted ranges so they are downloaded again when requested.
+    pub fn evict(&mut self, evicted: &BoundsSet) {
+        self.bounds = self.bounds.subtract(evicted).unwrap_or_default();
+    }
+
+    /// Marks a range loaded outside of the paged download, e.g. a refreshed tail.
+    pub fn mark_loaded(&mut self, bounds: Bounds) {
+        self.bounds = self.bounds.merge_single(bounds);
+    }
+
+    pub fn bounds(&self) -> &BoundsSet {
+        &self.bounds
+    }
+}
+
+#[cfg(test)]
+mod state_tests {
+    use super::*;
+
+    #[test]
+    fn test_evicted_range_is_loaded_again() {
+        let mut state = State::default();
+        let mut props = Props {
+            bounds: BoundsSet::new(vec![Bounds(0, 999_999)]),
+            ..Default::default()
+        };
+        state.apply_props(&props);
+
+        state.evict(&BoundsSet::new(vec![Bounds(600_000, 999_999)]));
+        assert_eq!(state.bounds(), &BoundsSet::new(vec![Bounds(0, 599_999)]));
+
+        props.bounds = BoundsSet::new(vec![Bounds(500_000, 999_999)]);
+        state.apply_props(&props);
+        assert!(!state.loading.pages.is_empty());
+        assert_eq!(state.bounds(), &BoundsSet::new(vec![Bounds(0, 999_999)]));
     }
 }
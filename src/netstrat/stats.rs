@@ -0,0 +1,110 @@
+//! Summary statistics of a run of candles.
+
+use crate::sources::binance::Kline;
+
+use super::{bounds::Bounds, data, indicators::math};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub candles: usize,
+    /// From the first open to the last close in millis.
+    pub bounds: Bounds,
+    pub open: f64,
+    pub close: f64,
+    pub high: f64,
+    pub low: f64,
+    /// Change from the first open to the last close in percent.
+    pub change: f64,
+    /// Distance of the high from the low in percent of the low.
+    pub range: f64,
+    pub volume: f64,
+    /// Candles closing above their open.
+    pub up: usize,
+    /// Sample standard deviation of close to close log returns in percent, `None` for
+    /// less than three candles.
+    pub volatility: Option<f64>,
+}
+
+impl Stats {
+    pub fn of(klines: &[Kline]) -> Option<Self> {
+        let (first, last) = (klines.first()?, klines.last()?);
+        let (open, close) = (first.open as f64, last.close as f64);
+        let high = klines
+            .iter()
+            .map(|k| k.high as f64)
+            .fold(f64::MIN, f64::max);
+        let low = klines.iter().map(|k| k.low as f64).fold(f64::MAX, f64::min);
+        let closes: Vec<f64> = klines.iter().map(|k| k.close as f64).collect();
+        let returns: Vec<f64> = math::log_returns(&closes).into_iter().flatten().collect();
+
+        Some(Self {
+            candles: klines.len(),
+            bounds: Bounds(first.t_open, last.t_close),
+            open,
+            close,
+            high,
+            low,
+            change: (close / open - 1.0) * 100.0,
+            range: (high / low - 1.0) * 100.0,
+            volume: klines.iter().map(|k| k.volume as f64).sum(),
+            up: klines.iter().filter(|k| k.close > k.open).count(),
+            volatility: math::stdev(&returns, returns.len())
+                .last()
+                .copied()
+                .flatten()
+                .map(|v| v * 100.0),
+        })
+    }
+
+    pub fn mean_volume(&self) -> f64 {
+        self.volume / self.candles as f64
+    }
+}
+
+/// Candles within the bounds, both sorted by time.
+pub fn within(klines: &[Kline], bounds: Bounds) -> &[Kline] {
+    &klines[data::range_of(klines, bounds.0, bounds.1)]
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let mut klines = mock::klines(0, 60_000, 3);
+        klines
+            .iter_mut()
+            .zip([(100.0, 110.0), (110.0, 99.0), (99.0, 125.0)])
+            .for_each(|(k, (open, close))| {
+                k.open = open;
+                k.close = close;
+            });
+
+        let stats = Stats::of(&klines).unwrap();
+
+        assert_eq!(stats.candles, 3);
+        assert_eq!(stats.bounds, Bounds(0, 180_000 - 1));
+        assert_eq!(stats.change, 25.0);
+        assert_eq!((stats.high, stats.low), (104.0, 99.0));
+        assert_eq!(stats.up, 2);
+        assert_eq!(stats.volume, 10.0 + 11.0 + 12.0);
+        assert_eq!(stats.mean_volume(), 11.0);
+        assert!(stats.volatility.is_some());
+
+        assert_eq!(Stats::of(&klines[..2]).unwrap().volatility, None);
+        assert_eq!(Stats::of(&[]), None);
+    }
+
+    #[test]
+    fn test_within() {
+        let klines = mock::klines(0, 60_000, 5);
+
+        assert_eq!(within(&klines, Bounds(60_000, 180_000 - 1)), &klines[1..3]);
+        // candles partly inside count
+        assert_eq!(within(&klines, Bounds(90_000, 150_000)), &klines[1..3]);
+        assert!(within(&klines, Bounds(600_000, 700_000)).is_empty());
+    }
+}
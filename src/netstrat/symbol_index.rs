@@ -0,0 +1,118 @@
+//! Case-insensitive symbol search over thousands of symbols, matched against names
+//! uppercased once when the index is built.
+
+use std::collections::BTreeSet;
+
+#[derive(Debug, Default, Clone)]
+pub struct SymbolIndex {
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    upper: String,
+    symbol: String,
+    favorite: bool,
+}
+
+impl SymbolIndex {
+    pub fn new<'a>(
+        symbols: impl IntoIterator<Item = &'a str>,
+        favorites: &BTreeSet<String>,
+    ) -> Self {
+        Self {
+            entries: symbols
+                .into_iter()
+                .map(|s| Entry {
+                    upper: s.to_uppercase(),
+                    symbol: s.to_string(),
+                    favorite: favorites.contains(s),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Up to `limit` symbols containing `query`: exact matches first, then prefix and other
+    /// matches, favorites and shorter names first within each.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&str> {
+        let query = query.trim().to_uppercase();
+        if query.is_empty() {
+            return vec![];
+        }
+
+        let mut found: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|e| {
+                let at = e.upper.find(&query)?;
+                let rank = match (at, e.upper.len() == query.len()) {
+                    (_, true) => 0,
+                    (0, false) => 1,
+                    _ => 2,
+                };
+                Some(((rank, !e.favorite, e.upper.len(), e.symbol.as_str()), e))
+            })
+            .collect();
+        if found.len() > limit && limit > 0 {
+            found.select_nth_unstable_by(limit - 1, |a, b| a.0.cmp(&b.0));
+            found.truncate(limit);
+        }
+        found.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        found
+            .into_iter()
+            .take(limit)
+            .map(|(_, e)| e.symbol.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod symbol_index_tests {
+    use super::*;
+
+    fn index(favorites: &[&str]) -> SymbolIndex {
+        let favorites = favorites.iter().map(|s| s.to_string()).collect();
+        SymbolIndex::new(
+            [
+                "BTCUSDT", "ETHBTC", "ETHUSDT", "ETHUSDC", "USDTETH", "ETH", "SOLUSDT",
+            ],
+            &favorites,
+        )
+    }
+
+    #[test]
+    fn test_ranked() {
+        assert_eq!(index(&[]).search("ethu", 10), vec!["ETHUSDC", "ETHUSDT"]);
+        assert_eq!(
+            index(&["ETHUSDT"]).search("eth", 10),
+            vec!["ETH", "ETHUSDT", "ETHBTC", "ETHUSDC", "USDTETH"]
+        );
+        assert_eq!(index(&[]).search(" usdt", 2), vec!["USDTETH", "BTCUSDT"]);
+    }
+
+    #[test]
+    fn test_no_matches() {
+        assert!(index(&[]).search("", 10).is_empty());
+        assert!(index(&[]).search("doge", 10).is_empty());
+        assert!(index(&[]).search("eth", 0).is_empty());
+    }
+
+    #[test]
+    fn test_many_symbols() {
+        let names: Vec<String> = (0..5_000).map(|i| format!("S{i}USDT")).collect();
+        let index = SymbolIndex::new(names.iter().map(|s| s.as_str()), &BTreeSet::new());
+
+        assert_eq!(index.len(), 5_000);
+        assert_eq!(index.search("s4999u", 5), vec!["S4999USDT"]);
+        assert_eq!(index.search("s1", 3), vec!["S1USDT", "S10USDT", "S11USDT"]);
+    }
+}
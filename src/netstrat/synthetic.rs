@@ -0,0 +1,53 @@
+//! Random walk candles for benchmarks, the same seed gives the same candles.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::sources::binance::Kline;
+
+/// Generates `n` closed candles of `step` millis each starting at `start`.
+pub fn klines(seed: u64, start: i64, step: i64, n: usize) -> Vec<Kline> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut close = 100.0f32;
+
+    (0..n)
+        .map(|i| {
+            let t_open = start + i as i64 * step;
+            let open = close;
+            close = (open * (1.0 + rng.gen_range(-0.01..0.01))).max(0.01);
+            let volume = rng.gen_range(1.0..100.0f32);
+            Kline {
+                t_open,
+                open,
+                high: open.max(close) * (1.0 + rng.gen_range(0.0..0.005)),
+                low: open.min(close) * (1.0 - rng.gen_range(0.0..0.005)),
+                close,
+                volume,
+                t_close: t_open + step - 1,
+                quote_asset_volume: Some(volume * close),
+                number_of_trades: Some(rng.gen_range(1..500)),
+                taker_buy_base_asset_volume: Some(volume / 2.0),
+                taker_buy_quote_asset_volume: Some(volume * close / 2.0),
+                closed: true,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod synthetic_tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic() {
+        let klines = klines(7, 0, 60_000, 1_000);
+
+        assert_eq!(klines, super::klines(7, 0, 60_000, 1_000));
+        assert_ne!(klines, super::klines(8, 0, 60_000, 1_000));
+        assert!(klines
+            .windows(2)
+            .all(|w| w[1].t_open == w[0].t_close + 1 && w[1].open == w[0].close));
+        assert!(klines
+            .iter()
+            .all(|k| k.low <= k.open.min(k.close) && k.high >= k.open.max(k.close)));
+    }
+}
@@ -0,0 +1,31 @@
+//! Timezone of the app. ISO times of exports are written in it and the calendar sessions
+//! of pivots and session levels are grouped in it.
+
+use std::{
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc,
+    },
+};
+
+/// Offsets from UTC in minutes of the timezones in use, UTC-12 to UTC+14.
+pub const UTC_OFFSET_RANGE: RangeInclusive<i32> = -12 * 60..=14 * 60;
+
+/// Offset from UTC in minutes, clones share it.
+#[derive(Debug, Clone, Default)]
+pub struct Timezone {
+    utc_offset: Arc<AtomicI32>,
+}
+
+impl Timezone {
+    pub fn utc_offset(&self) -> i32 {
+        self.utc_offset.load(Ordering::Relaxed)
+    }
+
+    /// Sets the offset clamped to [`UTC_OFFSET_RANGE`].
+    pub fn set_utc_offset(&self, minutes: i32) {
+        let minutes = minutes.clamp(*UTC_OFFSET_RANGE.start(), *UTC_OFFSET_RANGE.end());
+        self.utc_offset.store(minutes, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,174 @@
+//! Chart view shared as a compact string to open the same chart in another instance.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::{Error, Result},
+    sources::binance::Interval,
+};
+
+use super::{
+    aggregate::EmptyBuckets,
+    bounds::{Bounds, BoundsSet},
+    indicators::Setup,
+    props::Props,
+    renko::BrickSize,
+};
+
+/// Version of the format written, strings of older versions are read with missing fields
+/// set to defaults.
+pub const VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct View {
+    pub symbol: String,
+    /// Candle interval as in [`Interval::as_str`].
+    pub interval: String,
+    /// Loaded time range in millis.
+    pub start: i64,
+    pub end: i64,
+    pub limit: Option<usize>,
+    pub empty_buckets: EmptyBuckets,
+    /// Time range in view in millis.
+    pub visible: Option<[i64; 2]>,
+    /// Brick size when shown as renko.
+    pub renko: Option<BrickSize>,
+    pub compress: bool,
+    pub indicators: Vec<Setup>,
+}
+
+#[derive(Serialize)]
+struct Versioned<'a> {
+    version: u64,
+    #[serde(flatten)]
+    view: &'a View,
+}
+
+impl View {
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(&Versioned {
+            version: VERSION,
+            view: self,
+        })
+        .unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Reads a string made by [`View::encode`], whitespace around it is ignored.
+    pub fn decode(s: &str) -> Result<View> {
+        let json = URL_SAFE_NO_PAD
+            .decode(s.trim())
+            .map_err(|_| Error::View("not a view string".to_string()))?;
+        let value: serde_json::Value =
+            serde_json::from_slice(&json).map_err(|err| Error::View(err.to_string()))?;
+        match value.get("version").and_then(|v| v.as_u64()) {
+            Some(version) if (1..=VERSION).contains(&version) => {}
+            Some(version) => return Err(Error::ViewVersion(version)),
+            None => return Err(Error::View("version is missing".to_string())),
+        }
+
+        serde_json::from_value(value).map_err(|err| Error::View(err.to_string()))
+    }
+
+    /// Props to download the candles of the view with.
+    pub fn props(&self) -> Result<Props> {
+        let interval = Interval::parse(&self.interval)
+            .ok_or_else(|| Error::View(format!("unknown interval {:?}", self.interval)))?;
+        let (start, end) = match (
+            DateTime::from_timestamp_millis(self.start),
+            DateTime::from_timestamp_millis(self.end),
+        ) {
+            (Some(start), Some(end)) if start < end => (start.naive_utc(), end.naive_utc()),
+            _ => {
+                return Err(Error::View(format!(
+                    "invalid time range {} - {}",
+                    self.start, self.end
+                )))
+            }
+        };
+
+        let defaults = Props::default();
+        Ok(Props {
+            date_start: start.date(),
+            time_start: start.time(),
+            date_end: end.date(),
+            time_end: end.time(),
+            bounds: BoundsSet::new(vec![Bounds(self.start, self.end)]),
+            interval,
+            limit: self.limit.unwrap_or(defaults.limit),
+            empty_buckets: self.empty_buckets,
+        })
+    }
+}
+
+#[cfg(test)]
+mod view_tests {
+    use super::*;
+
+    fn view() -> View {
+        View {
+            symbol: "BTCUSDT".to_string(),
+            interval: "1h".to_string(),
+            start: 1_700_000_000_000,
+            end: 1_700_086_400_000,
+            limit: Some(500),
+            visible: Some([1_700_040_000_000, 1_700_080_000_000]),
+            renko: Some(BrickSize::Absolute(25.0)),
+            indicators: vec![Setup {
+                id: "sma@1d".to_string(),
+                params: serde_json::json!({ "period": 50 }),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let encoded = view().encode();
+
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_".contains(c)));
+        assert_eq!(View::decode(&format!(" {encoded}\n")).unwrap(), view());
+
+        let props = view().props().unwrap();
+        assert_eq!(props.interval, Interval::Hour);
+        assert_eq!(props.start_time().timestamp_millis(), view().start);
+        assert_eq!(props.end_time().timestamp_millis(), view().end);
+        assert_eq!(props.limit, 500);
+    }
+
+    #[test]
+    fn test_missing_fields_default() {
+        let encoded = URL_SAFE_NO_PAD.encode(r#"{"version":1,"symbol":"ETHUSDT"}"#);
+
+        let view = View::decode(&encoded).unwrap();
+
+        assert_eq!(view.symbol, "ETHUSDT");
+        assert_eq!(view.renko, None);
+        assert!(view.indicators.is_empty());
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let newer = URL_SAFE_NO_PAD.encode(r#"{"version":2,"symbol":"ETHUSDT"}"#);
+        let err = View::decode(&newer).unwrap_err();
+        assert!(matches!(err, Error::ViewVersion(2)));
+        assert!(err.user_message().contains("newer netstrat"));
+
+        assert!(matches!(View::decode("not base64!"), Err(Error::View(_))));
+        assert!(matches!(
+            View::decode(&URL_SAFE_NO_PAD.encode("{}")),
+            Err(Error::View(_))
+        ));
+        assert!(View {
+            interval: "2w".to_string(),
+            ..view()
+        }
+        .props()
+        .is_err());
+    }
+}
@@ -0,0 +1,54 @@
+//! Symbols watched with their 24 hour tickers.
+
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+};
+
+/// Watched symbols, clones share them.
+#[derive(Clone, Default)]
+pub struct Watchlist {
+    symbols: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl Watchlist {
+    pub fn symbols(&self) -> Vec<String> {
+        self.symbols.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn set_symbols(&self, symbols: Vec<String>) {
+        *self.symbols.lock().unwrap() = symbols.into_iter().collect();
+    }
+
+    /// Watches the symbols, returning how many weren't watched yet.
+    pub fn extend(&self, symbols: impl IntoIterator<Item = String>) -> usize {
+        let mut watched = self.symbols.lock().unwrap();
+        symbols
+            .into_iter()
+            .filter(|s| watched.insert(s.clone()))
+            .count()
+    }
+
+    pub fn remove(&self, symbol: &str) {
+        self.symbols.lock().unwrap().remove(symbol);
+    }
+}
+
+#[cfg(test)]
+mod watchlist_tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_between_clones() {
+        let watchlist = Watchlist::default();
+        let other = watchlist.clone();
+
+        let added = watchlist.extend(["ETHUSDT".to_string(), "BTCUSDT".to_string()]);
+        assert_eq!(added, 2);
+        assert_eq!(other.extend(["BTCUSDT".to_string()]), 0);
+        assert_eq!(other.symbols(), vec!["BTCUSDT", "ETHUSDT"]);
+
+        other.remove("BTCUSDT");
+        assert_eq!(watchlist.symbols(), vec!["ETHUSDT"]);
+    }
+}
@@ -0,0 +1,228 @@
+//! Xlsx workbooks written row by row with `rust_xlsxwriter` in constant memory mode: rows
+//! of a sheet go to a temporary file as they are written rather than staying in memory.
+
+use std::io;
+use std::path::Path;
+
+use rust_xlsxwriter::{Format, Worksheet, XlsxError};
+
+/// Rows of a sheet including the header.
+pub const MAX_ROWS: usize = 1_048_576;
+/// Longest sheet name Excel opens.
+const MAX_NAME_CHARS: usize = 31;
+/// Days from the epoch of Excel dates to the Unix one.
+const UNIX_EPOCH_DAYS: f64 = 25_569.0;
+const DAY_MILLIS: f64 = 86_400_000.0;
+
+/// Value of a cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cell<'a> {
+    /// Number, left empty when not finite.
+    Number(f64),
+    Text(&'a str),
+    Bool(bool),
+    /// UTC millis shown as a date and time.
+    Date(i64),
+    Empty,
+}
+
+/// Workbook whose sheets and rows are added in order, saved once they all are.
+pub struct Workbook {
+    book: rust_xlsxwriter::Workbook,
+    bold: Format,
+    date: Format,
+    sheets: Vec<String>,
+    /// Rows written to the last sheet, `None` before the first one is added.
+    rows: Option<usize>,
+}
+
+impl Default for Workbook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Workbook {
+    pub fn new() -> Self {
+        Self {
+            book: rust_xlsxwriter::Workbook::new(),
+            bold: Format::new().set_bold(),
+            date: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss.000"),
+            sheets: vec![],
+            rows: None,
+        }
+    }
+
+    /// Starts a sheet with the widths of its first columns in characters, the previous one
+    /// is done. Names are cut to what Excel opens and made unique.
+    pub fn add_sheet(&mut self, name: &str, widths: &[f64]) -> io::Result<()> {
+        let name = self.unique_name(name);
+        let sheet = self.book.add_worksheet_with_constant_memory();
+        sheet.set_name(&name).map_err(other)?;
+        for (i, w) in widths.iter().enumerate() {
+            sheet.set_column_width(i as u16, *w).map_err(other)?;
+        }
+        self.sheets.push(name);
+        self.rows = Some(0);
+
+        Ok(())
+    }
+
+    /// Row of bold column names.
+    pub fn write_header<S: AsRef<str>>(&mut self, names: &[S]) -> io::Result<()> {
+        let cells: Vec<Cell> = names.iter().map(|n| Cell::Text(n.as_ref())).collect();
+        self.row(&cells, true)
+    }
+
+    pub fn write_row(&mut self, cells: &[Cell]) -> io::Result<()> {
+        self.row(cells, false)
+    }
+
+    /// Rows written to the current sheet.
+    pub fn rows(&self) -> usize {
+        self.rows.unwrap_or_default()
+    }
+
+    /// Writes the workbook to `path`.
+    pub fn save(mut self, path: &Path) -> io::Result<()> {
+        if self.sheets.is_empty() {
+            self.add_sheet("Sheet1", &[])?;
+        }
+
+        self.book.save(path).map_err(other)
+    }
+
+    fn row(&mut self, cells: &[Cell], bold: bool) -> io::Result<()> {
+        let r = match self.rows {
+            Some(rows) if rows < MAX_ROWS => rows,
+            Some(_) => return Err(invalid("sheet is full")),
+            None => return Err(invalid("no sheet to write to")),
+        };
+
+        let sheet = self
+            .book
+            .worksheet_from_index(self.sheets.len() - 1)
+            .map_err(other)?;
+        let style = bold.then_some(&self.bold);
+        for (i, cell) in cells.iter().enumerate() {
+            write_cell(sheet, r as u32, i as u16, cell, &self.date, style).map_err(other)?;
+        }
+        self.rows = Some(r + 1);
+
+        Ok(())
+    }
+
+    fn unique_name(&self, name: &str) -> String {
+        let name: String = name
+            .chars()
+            .map(|c| match c {
+                '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+                c => c,
+            })
+            .collect();
+        let taken = |n: &str| self.sheets.iter().any(|s| s.eq_ignore_ascii_case(n));
+        (1..)
+            .map(|n| {
+                let suffix = match n {
+                    1 => String::new(),
+                    n => format!(" ({n})"),
+                };
+                let base: String = name.chars().take(MAX_NAME_CHARS - suffix.len()).collect();
+                format!("{base}{suffix}")
+            })
+            .find(|n| !taken(n))
+            .unwrap_or(name)
+    }
+}
+
+/// Writes the cell in `style` if any, dates without one in `date`.
+fn write_cell(
+    sheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    cell: &Cell,
+    date: &Format,
+    style: Option<&Format>,
+) -> Result<(), XlsxError> {
+    match (cell, style) {
+        (Cell::Number(v), None) if v.is_finite() => sheet.write_number(row, col, *v),
+        (Cell::Number(v), Some(s)) if v.is_finite() => {
+            sheet.write_number_with_format(row, col, *v, s)
+        }
+        (Cell::Text(t), None) => sheet.write_string(row, col, *t),
+        (Cell::Text(t), Some(s)) => sheet.write_string_with_format(row, col, *t, s),
+        (Cell::Bool(b), None) => sheet.write_boolean(row, col, *b),
+        (Cell::Bool(b), Some(s)) => sheet.write_boolean_with_format(row, col, *b, s),
+        (Cell::Date(ts), _) => {
+            let serial = *ts as f64 / DAY_MILLIS + UNIX_EPOCH_DAYS;
+            sheet.write_number_with_format(row, col, serial, date)
+        }
+        (Cell::Number(_) | Cell::Empty, _) => return Ok(()),
+    }
+    .map(|_| ())
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+fn other(err: XlsxError) -> io::Error {
+    match err {
+        XlsxError::IoError(err) => err,
+        err => io::Error::other(err),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod xlsx_tests {
+    use std::io::Read;
+
+    use super::*;
+
+    /// Content of the entry of a saved workbook.
+    pub(crate) fn entry(path: &Path, name: &str) -> String {
+        let mut zip = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+        let mut xml = String::new();
+        zip.by_name(name).unwrap().read_to_string(&mut xml).unwrap();
+        xml
+    }
+
+    #[test]
+    fn test_workbook() {
+        let mut wb = Workbook::new();
+        wb.add_sheet("BTC/USDT", &[20.0]).unwrap();
+        wb.write_header(&["time", "close", "closed"]).unwrap();
+        wb.write_row(&[Cell::Date(86_400_000), Cell::Number(1.5), Cell::Bool(true)])
+            .unwrap();
+        wb.write_row(&[Cell::Text("a<b"), Cell::Number(f64::NAN), Cell::Empty])
+            .unwrap();
+        assert_eq!(wb.rows(), 3);
+        wb.add_sheet("btc/usdt", &[]).unwrap();
+
+        let path = std::env::temp_dir().join("netstrat_xlsx_workbook.xlsx");
+        wb.save(&path).unwrap();
+
+        let sheet = entry(&path, "xl/worksheets/sheet1.xml");
+        assert!(sheet.contains(r#"<c r="A1" s="1" t="inlineStr"><is><t>time</t></is></c>"#));
+        // the day after the epoch as a serial date
+        assert!(sheet.contains(r#"<c r="A2" s="2"><v>25570</v></c>"#));
+        assert!(sheet.contains(r#"<c r="B2"><v>1.5</v></c><c r="C2" t="b"><v>1</v></c>"#));
+        assert!(sheet.contains(r#"<c r="A3" t="inlineStr"><is><t>a&lt;b</t></is></c></row>"#));
+        let book = entry(&path, "xl/workbook.xml");
+        assert!(book.contains(r#"<sheet name="BTC_USDT" sheetId="1" r:id="rId1"/>"#));
+        assert!(book.contains(r#"<sheet name="btc_usdt (2)" sheetId="2" r:id="rId2"/>"#));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_full_sheet() {
+        let mut wb = Workbook::new();
+        assert!(wb.write_row(&[]).is_err());
+
+        wb.add_sheet(&"x".repeat(40), &[]).unwrap();
+        wb.rows = Some(MAX_ROWS);
+        assert!(wb.write_row(&[Cell::Empty]).is_err());
+        assert_eq!(wb.sheets[0].len(), MAX_NAME_CHARS);
+    }
+}
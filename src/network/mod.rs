@@ -1 +1,3 @@
 pub mod rest;
+pub mod webhook;
+pub mod ws_server;
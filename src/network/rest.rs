@@ -5,6 +5,12 @@ pub struct Rest {
     c: reqwest::Client,
 }
 
+impl Default for Rest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Rest {
     pub fn new() -> Rest {
         Rest {
@@ -0,0 +1,369 @@
+//! Webhook receiving a json POST when a download finishes and when a candle closes while
+//! auto refresh is on.
+//!
+//! ```json
+//! {"type":"fetch","symbol":"BTCUSDT","interval":"1m","start":1656633600000,"end":1656720000000,"candles":1440,"error":null}
+//! {"type":"candle","symbol":"BTCUSDT","interval":"1m","kline":{"t_open":1656633600000,"open":19900.5,...}}
+//! {"type":"test","sent_at":1656633600000}
+//! ```
+//!
+//! Deliveries are queued and posted one at a time from a thread of its own, failed ones are
+//! retried with a doubling delay.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, RwLock},
+    thread,
+    time::Duration,
+};
+
+use chrono::Utc;
+use crossbeam::channel::{bounded, Receiver, Sender, TrySendError};
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, warn};
+
+use crate::sources::binance::Kline;
+
+/// Deliveries waiting to be posted, newer ones are dropped while it is full.
+const QUEUE: usize = 256;
+/// Deliveries kept in the log.
+const LOG_LEN: usize = 50;
+const MAX_ATTEMPTS: u32 = 5;
+const BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    pub url: String,
+}
+
+/// Body of a delivery.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Payload {
+    /// Download of the range in millis finished, with the message of a failure.
+    Fetch {
+        symbol: String,
+        interval: String,
+        start: i64,
+        end: i64,
+        candles: usize,
+        error: Option<String>,
+    },
+    /// Candle closed while auto refresh was on.
+    Candle {
+        symbol: String,
+        interval: String,
+        kline: Kline,
+    },
+    /// Sent from settings to check the url.
+    Test { sent_at: i64 },
+}
+
+impl Payload {
+    fn kind(&self) -> &'static str {
+        match self {
+            Payload::Fetch { .. } => "fetch",
+            Payload::Candle { .. } => "candle",
+            Payload::Test { .. } => "test",
+        }
+    }
+}
+
+/// Outcome of a delivery for the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    /// Time in millis of the last attempt.
+    pub at: i64,
+    pub kind: &'static str,
+    pub url: String,
+    pub attempts: u32,
+    /// Status the url responded with, or why nothing was delivered.
+    pub result: std::result::Result<u16, String>,
+}
+
+struct Queued {
+    url: String,
+    kind: &'static str,
+    body: String,
+}
+
+/// Handle queueing deliveries, clones share the config, the queue and the log. The thread
+/// posting deliveries starts with the first one.
+#[derive(Clone)]
+pub struct Webhook {
+    config: Arc<RwLock<WebhookConfig>>,
+    queue: Arc<Mutex<Option<Sender<Queued>>>>,
+    log: Arc<Mutex<VecDeque<Delivery>>>,
+    backoff: Duration,
+}
+
+impl Default for Webhook {
+    fn default() -> Self {
+        Self {
+            config: Default::default(),
+            queue: Default::default(),
+            log: Default::default(),
+            backoff: BACKOFF,
+        }
+    }
+}
+
+impl Webhook {
+    pub fn config(&self) -> WebhookConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: WebhookConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    /// Latest deliveries, the newest first.
+    pub fn log(&self) -> Vec<Delivery> {
+        self.log.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    /// Queues the payload when the webhook is enabled.
+    pub fn send(&self, payload: &Payload) {
+        let config = self.config();
+        if config.enabled {
+            self.enqueue(&config.url, payload);
+        }
+    }
+
+    /// Queues a test event whether the webhook is enabled or not.
+    pub fn send_test(&self) {
+        let payload = Payload::Test {
+            sent_at: Utc::now().timestamp_millis(),
+        };
+        self.enqueue(&self.config().url, &payload);
+    }
+
+    fn enqueue(&self, url: &str, payload: &Payload) {
+        let url = url.trim();
+        if reqwest::Url::parse(url).is_err() {
+            self.record(payload.kind(), url, 0, Err(format!("invalid url {url:?}")));
+            return;
+        }
+        let body = match serde_json::to_string(payload) {
+            Ok(body) => body,
+            Err(err) => {
+                error!("failed to serialize payload {payload:?}: {err}");
+                return;
+            }
+        };
+
+        let queued = Queued {
+            url: url.to_string(),
+            kind: payload.kind(),
+            body,
+        };
+        let mut queue = self.queue.lock().unwrap();
+        let sender = queue.get_or_insert_with(|| {
+            let (s, r) = bounded(QUEUE);
+            let webhook = self.clone();
+            thread::spawn(move || webhook.deliver_all(r));
+            s
+        });
+        match sender.try_send(queued) {
+            Ok(()) => {}
+            Err(TrySendError::Full(q)) => {
+                self.record(q.kind, &q.url, 0, Err("queue full, dropped".to_string()))
+            }
+            Err(TrySendError::Disconnected(q)) => {
+                *queue = None;
+                self.record(q.kind, &q.url, 0, Err("delivery stopped".to_string()))
+            }
+        }
+    }
+
+    fn record(&self, kind: &'static str, url: &str, attempts: u32, result: Result<u16, String>) {
+        let mut log = self.log.lock().unwrap();
+        if log.len() >= LOG_LEN {
+            log.pop_front();
+        }
+        log.push_back(Delivery {
+            at: Utc::now().timestamp_millis(),
+            kind,
+            url: url.to_string(),
+            attempts,
+            result,
+        });
+    }
+
+    fn deliver_all(&self, queue: Receiver<Queued>) {
+        let rt = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt,
+            Err(err) => {
+                error!("failed to start webhook runtime: {err}");
+                return;
+            }
+        };
+        let client = reqwest::Client::new();
+        for queued in queue.iter() {
+            rt.block_on(self.deliver(&client, &queued));
+        }
+    }
+
+    /// Posts the delivery retrying network errors, 429 and 5xx.
+    async fn deliver(&self, client: &reqwest::Client, queued: &Queued) {
+        let mut delay = self.backoff;
+        let mut attempt = 0;
+        let result = loop {
+            attempt += 1;
+            let res = client
+                .post(&queued.url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(queued.body.clone())
+                .timeout(TIMEOUT)
+                .send()
+                .await;
+            let (result, retry) = match res {
+                Ok(resp) if resp.status().is_success() => break Ok(resp.status().as_u16()),
+                Ok(resp) => {
+                    let status = resp.status();
+                    let retry = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                    (Err(format!("status {}", status.as_u16())), retry)
+                }
+                Err(err) => (Err(err.to_string()), true),
+            };
+            if !retry || attempt >= MAX_ATTEMPTS {
+                break result;
+            }
+
+            warn!(
+                "webhook {} delivery failed: {:?}. Retrying in {delay:?}. Attempt: {attempt}.",
+                queued.kind, result
+            );
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_BACKOFF);
+        };
+
+        debug!("webhook {} delivered: {result:?}", queued.kind);
+        self.record(queued.kind, &queued.url, attempt, result);
+    }
+}
+
+#[cfg(test)]
+mod webhook_tests {
+    use std::time::Instant;
+
+    use wiremock::{
+        matchers::{body_partial_json, method},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    fn webhook(url: &str) -> Webhook {
+        let webhook = Webhook {
+            backoff: Duration::from_millis(1),
+            ..Default::default()
+        };
+        webhook.set_config(WebhookConfig {
+            enabled: true,
+            url: url.to_string(),
+        });
+        webhook
+    }
+
+    fn wait_for_log(webhook: &Webhook, len: usize) -> Vec<Delivery> {
+        let started = Instant::now();
+        while webhook.log().len() < len && started.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        webhook.log()
+    }
+
+    #[test]
+    fn test_payload_json() {
+        let kline = mock::klines(0, 60_000, 1)[0];
+        let json = serde_json::to_value(Payload::Candle {
+            symbol: "BTCUSDT".to_string(),
+            interval: "1m".to_string(),
+            kline,
+        })
+        .unwrap();
+
+        assert_eq!(json["type"], "candle");
+        assert_eq!(json["interval"], "1m");
+        assert_eq!(json["kline"]["t_close"], 59_999);
+    }
+
+    #[tokio::test]
+    async fn test_retried_with_backoff() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"type": "fetch"})))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        let webhook = webhook(&server.uri());
+
+        webhook.send(&Payload::Fetch {
+            symbol: "BTCUSDT".to_string(),
+            interval: "1m".to_string(),
+            start: 0,
+            end: 60_000,
+            candles: 1,
+            error: None,
+        });
+        let log = tokio::task::spawn_blocking(move || wait_for_log(&webhook, 1))
+            .await
+            .unwrap();
+
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].kind, "fetch");
+        assert_eq!(log[0].attempts, 3);
+        assert_eq!(log[0].result, Ok(200));
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let webhook = webhook(&server.uri());
+
+        webhook.send_test();
+        let log = tokio::task::spawn_blocking(move || wait_for_log(&webhook, 1))
+            .await
+            .unwrap();
+
+        assert_eq!(log[0].kind, "test");
+        assert_eq!(log[0].attempts, 1);
+        assert_eq!(log[0].result, Err("status 404".to_string()));
+    }
+
+    #[test]
+    fn test_disabled_and_invalid() {
+        let webhook = webhook("not a url");
+
+        webhook.send_test();
+        assert_eq!(
+            webhook.log()[0].result,
+            Err("invalid url \"not a url\"".to_string())
+        );
+
+        webhook.set_config(WebhookConfig::default());
+        webhook.send(&Payload::Test { sent_at: 0 });
+        assert_eq!(webhook.log().len(), 1);
+    }
+}
@@ -0,0 +1,283 @@
+//! Websocket server republishing what the app is looking at to external consumers.
+//!
+//! Every message is a json object with the `type` field:
+//!
+//! ```json
+//! {"type":"symbol","symbol":"BTCUSDT"}
+//! {"type":"range","symbol":"BTCUSDT","interval":"1m","start":1656633600000,"end":1656720000000,"candles":1440}
+//! {"type":"candle","symbol":"BTCUSDT","kline":{"t_open":1656633600000,"open":19900.5,...}}
+//! ```
+//!
+//! `kline` has the same fields as the csv export.
+
+use std::{
+    net::{SocketAddr, TcpListener as StdTcpListener},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{broadcast, watch},
+    task::JoinHandle,
+};
+use tokio_tungstenite::tungstenite;
+use tracing::{debug, error, info};
+
+use crate::{
+    errors::{Error, Result},
+    sources::binance::Kline,
+};
+
+pub const DEFAULT_PORT: u16 = 9001;
+
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// Symbol was selected.
+    Symbol { symbol: String },
+    /// Data download for the range in millis finished.
+    Range {
+        symbol: String,
+        interval: String,
+        start: i64,
+        end: i64,
+        candles: usize,
+    },
+    /// Candle was updated by live data.
+    Candle { symbol: String, kline: Kline },
+}
+
+/// Handle publishing messages to every connected client.
+/// Publishing without running server or connected clients is a no-op.
+#[derive(Clone)]
+pub struct Broadcaster {
+    sender: broadcast::Sender<String>,
+    connections: Arc<AtomicUsize>,
+    /// Whether a server publishing the messages is running.
+    listening: Arc<AtomicBool>,
+}
+
+impl Default for Broadcaster {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+
+        Self {
+            sender,
+            connections: Default::default(),
+            listening: Default::default(),
+        }
+    }
+}
+
+impl Broadcaster {
+    pub fn publish(&self, msg: &Message) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+
+        match serde_json::to_string(msg) {
+            Ok(json) => {
+                // only fails when there are no receivers left
+                self.sender.send(json).ok();
+            }
+            Err(err) => error!("failed to serialize message {msg:?}: {err}"),
+        }
+    }
+
+    /// Number of currently connected clients.
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::Relaxed)
+    }
+
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::Relaxed)
+    }
+}
+
+/// Running server which is stopped together with all its connections on drop.
+pub struct Server {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+    broadcaster: Broadcaster,
+    // dropping the sender closes connections
+    _stop: watch::Sender<()>,
+}
+
+impl Server {
+    /// Starts server on localhost. Must be called within tokio runtime.
+    pub fn start(port: u16, broadcaster: Broadcaster) -> Result<Self> {
+        let addr = format!("127.0.0.1:{port}");
+        let listener = StdTcpListener::bind(&addr)
+            .and_then(|l| {
+                l.set_nonblocking(true)?;
+                TcpListener::from_std(l)
+            })
+            .map_err(|err| Error::Bind(addr.clone(), err))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|err| Error::Bind(addr, err))?;
+
+        info!("websocket server listening on {addr}");
+
+        let (stop_pub, stop_sub) = watch::channel(());
+        broadcaster.listening.store(true, Ordering::Relaxed);
+        Ok(Self {
+            addr,
+            handle: tokio::spawn(accept(listener, broadcaster.clone(), stop_sub)),
+            broadcaster,
+            _stop: stop_pub,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        info!("stopping websocket server on {}", self.addr);
+        self.handle.abort();
+        self.broadcaster.listening.store(false, Ordering::Relaxed);
+    }
+}
+
+async fn accept(listener: TcpListener, broadcaster: Broadcaster, stop: watch::Receiver<()>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let broadcaster = broadcaster.clone();
+                let stop = stop.clone();
+                tokio::spawn(async move {
+                    let msgs = broadcaster.sender.subscribe();
+                    broadcaster.connections.fetch_add(1, Ordering::Relaxed);
+                    if let Err(err) = serve(stream, msgs, stop).await {
+                        debug!("websocket connection with {peer} closed: {err}");
+                    }
+                    broadcaster.connections.fetch_sub(1, Ordering::Relaxed);
+                });
+            }
+            Err(err) => error!("failed to accept websocket connection: {err}"),
+        }
+    }
+}
+
+async fn serve(
+    stream: TcpStream,
+    mut msgs: broadcast::Receiver<String>,
+    mut stop: watch::Receiver<()>,
+) -> tungstenite::Result<()> {
+    let mut ws = tokio_tungstenite::accept_async(stream).await?;
+
+    loop {
+        tokio::select! {
+            _ = stop.changed() => return ws.close(None).await,
+            msg = msgs.recv() => match msg {
+                Ok(json) => ws.send(tungstenite::Message::Text(json)).await?,
+                Err(broadcast::error::RecvError::Lagged(n)) => debug!("client lagged by {n} messages"),
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            incoming = ws.next() => match incoming {
+                Some(Ok(tungstenite::Message::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(err),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod ws_server_tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    async fn wait_connections(broadcaster: &Broadcaster, n: usize) {
+        for _ in 0..100 {
+            if broadcaster.connections() == n {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!(
+            "expected {n} connections, got {}",
+            broadcaster.connections()
+        );
+    }
+
+    #[test]
+    fn test_message_schema() {
+        let msg = Message::Range {
+            symbol: "BTCUSDT".to_string(),
+            interval: "1m".to_string(),
+            start: 0,
+            end: 60_000,
+            candles: 1,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&msg).unwrap(),
+            r#"{"type":"range","symbol":"BTCUSDT","interval":"1m","start":0,"end":60000,"candles":1}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_to_clients() {
+        let broadcaster = Broadcaster::default();
+        let server = Server::start(0, broadcaster.clone()).unwrap();
+
+        let url = format!("ws://{}", server.addr());
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        wait_connections(&broadcaster, 1).await;
+
+        broadcaster.publish(&Message::Symbol {
+            symbol: "BTCUSDT".to_string(),
+        });
+
+        let msg = ws.next().await.unwrap().unwrap();
+        assert_eq!(
+            msg.into_text().unwrap(),
+            r#"{"type":"symbol","symbol":"BTCUSDT"}"#
+        );
+
+        ws.close(None).await.unwrap();
+        wait_connections(&broadcaster, 0).await;
+    }
+
+    #[tokio::test]
+    async fn test_stop_closes_connections() {
+        let broadcaster = Broadcaster::default();
+        let server = Server::start(0, broadcaster.clone()).unwrap();
+
+        let url = format!("ws://{}", server.addr());
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        wait_connections(&broadcaster, 1).await;
+        assert!(broadcaster.is_listening());
+
+        drop(server);
+        assert!(!broadcaster.is_listening());
+
+        assert!(matches!(
+            ws.next().await,
+            Some(Ok(tungstenite::Message::Close(_))) | None
+        ));
+        wait_connections(&broadcaster, 0).await;
+    }
+
+    #[tokio::test]
+    async fn test_port_in_use() {
+        let server = Server::start(0, Broadcaster::default()).unwrap();
+
+        let err = Server::start(server.addr().port(), Broadcaster::default())
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::Bind(_, _)), "{err}");
+    }
+}
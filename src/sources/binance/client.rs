@@ -1,83 +1,157 @@
-use std::cmp::Ordering;
+use std::cmp::{min, Ordering};
+use std::num::ParseFloatError;
+use std::sync::OnceLock;
+use std::time::Duration;
 
+use chrono::Utc;
+use quick_error::ResultExt;
+use reqwest::{header::RETRY_AFTER, StatusCode};
 use serde::{Deserialize, Serialize};
-use serde_json;
+use tracing::warn;
 
+use crate::errors::{Error, Result};
+use crate::netstrat::bounds::Bounds;
 use crate::network::rest::Rest;
 use crate::sources::binance::interval::Interval;
 
-use super::errors::ClientError;
+use super::limiter::WeightLimiter;
 
-#[derive(Clone, Debug, Default)]
-pub struct Client {}
+/// Clones share the connection pool and the request weight limiter.
+#[derive(Clone, Debug)]
+pub struct Client {
+    base_url: String,
+    max_retries: usize,
+    rest: Rest,
+    limiter: WeightLimiter,
+}
 
 const BASE_URL: &str = "https://api.binance.com";
 const PATH_KLINE: &str = "/api/v3/klines";
 const PATH_INFO: &str = "/api/v3/exchangeInfo";
+const PATH_AGG_TRADES: &str = "/api/v3/aggTrades";
+const PATH_TICKER: &str = "/api/v3/ticker/24hr";
+const AGG_TRADES_LIMIT: usize = 1000;
+/// Binance rejects aggTrades requests spanning longer than an hour.
+const AGG_TRADES_WINDOW_MILLIS: i64 = 60 * 60 * 1000;
+const MAX_RETRIES: usize = 3;
+const DEFAULT_RETRY_AFTER_SECS: u64 = 1;
+const MAX_RETRY_AFTER_SECS: u64 = 60;
+/// Request weights of the endpoints, see the api docs.
+const WEIGHT_KLINES: u32 = 2;
+const WEIGHT_AGG_TRADES: u32 = 2;
+const WEIGHT_INFO: u32 = 20;
+/// Code of the api errors for symbols Binance doesn't list.
+const CODE_INVALID_SYMBOL: i64 = -1121;
+/// Header with the weight used by the ip in the current minute.
+const USED_WEIGHT: &str = "x-mbx-used-weight-1m";
+/// Query parameters left out of urls in errors and logs.
+const SECRET_PARAMS: [&str; 2] = ["signature", "apiKey"];
+
+/// Clients of the api share one connection pool and one limiter, the limit is per ip.
+impl Default for Client {
+    fn default() -> Self {
+        static SHARED: OnceLock<Client> = OnceLock::new();
+        SHARED.get_or_init(|| Self::new(BASE_URL)).clone()
+    }
+}
 
 #[derive(Debug, Deserialize, Default)]
 pub struct Info {
     pub symbols: Vec<Symbol>,
 }
 
+/// Error body Binance responds with, e.g. `{"code":-1121,"msg":"Invalid symbol."}`.
+#[derive(Deserialize)]
+struct ApiError {
+    code: i64,
+    msg: String,
+}
+
 #[derive(Deserialize)]
 struct KlineData(
-    i64,    // Open time
-    String, // Open
-    String, // High
-    String, // Low
-    String, // Close
-    String, // Volume
-    i64,    // Close time
-    String, // Quote asset volume
-    i64,    // Number of trades
-    String, // Taker buy base asset volume
-    String, // Taker buy quote asset volume
-    String, // Ignore
+    i64,                        // Open time
+    String,                     // Open
+    String,                     // High
+    String,                     // Low
+    String,                     // Close
+    String,                     // Volume
+    i64,                        // Close time
+    String,                     // Quote asset volume
+    i64,                        // Number of trades
+    String,                     // Taker buy base asset volume
+    String,                     // Taker buy quote asset volume
+    #[allow(dead_code)] String, // Ignore
 );
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Deserialize)]
+struct AggTradeData {
+    #[serde(rename = "a")]
+    id: i64,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    qty: String,
+    #[serde(rename = "T")]
+    time: i64,
+    #[serde(rename = "m")]
+    is_buyer_maker: bool,
+}
+
+#[derive(Deserialize)]
+struct TickerData {
+    symbol: String,
+    #[serde(rename = "lastPrice")]
+    last_price: String,
+    #[serde(rename = "priceChangePercent")]
+    price_change_percent: String,
+    #[serde(rename = "openTime")]
+    open_time: i64,
+    #[serde(rename = "closeTime")]
+    close_time: i64,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, PartialEq)]
 pub struct Symbol {
     pub symbol: String,
     pub status: String,
 
     #[serde(rename = "baseAsset")]
-    base_asset: String,
+    pub base_asset: String,
 
     #[serde(rename = "baseAssetPrecision")]
-    base_asset_precision: usize,
+    pub base_asset_precision: usize,
 
     #[serde(rename = "quoteAsset")]
-    quote_asset: String,
+    pub quote_asset: String,
 
     #[serde(rename = "quotePrecision")]
-    quote_precision: usize,
+    pub quote_precision: usize,
 
     #[serde(rename = "quoteAssetPrecision")]
-    quote_asset_precision: usize,
+    pub quote_asset_precision: usize,
 
     #[serde(rename = "baseCommissionPrecision")]
-    base_commission_precision: usize,
+    pub base_commission_precision: usize,
 
     #[serde(rename = "quoteCommissionPrecision")]
-    quote_commission_precision: usize,
+    pub quote_commission_precision: usize,
 
     #[serde(rename = "icebergAllowed")]
-    iceberg_allowed: bool,
+    pub iceberg_allowed: bool,
 
     #[serde(rename = "ocoAllowed")]
-    oco_allowed: bool,
+    pub oco_allowed: bool,
 
     #[serde(rename = "quoteOrderQtyMarketAllowed")]
-    quote_order_qty_market_allowed: bool,
+    pub quote_order_qty_market_allowed: bool,
 
     #[serde(rename = "allowTrailingStop")]
-    allow_trailing_stop: bool,
+    pub allow_trailing_stop: bool,
 
     #[serde(rename = "isSpotTradingAllowed")]
-    is_spot_trading_allowed: bool,
+    pub is_spot_trading_allowed: bool,
 
     #[serde(rename = "isMarginTradingAllowed")]
-    is_margin_trading_allowed: bool,
+    pub is_margin_trading_allowed: bool,
 }
 
 impl Symbol {
@@ -86,7 +160,9 @@ impl Symbol {
     }
 }
 
-#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize)]
+/// Candle. Fields after `t_close` are optional so that data saved
+/// before they were introduced still loads.
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct Kline {
     pub t_open: i64,
     pub open: f32,
@@ -95,30 +171,89 @@ pub struct Kline {
     pub close: f32,
     pub volume: f32,
     pub t_close: i64,
-    pub quote_asset_volume: f32,
-    pub number_of_trades: i64,
-    pub taker_buy_base_asset_volume: f32,
-    pub taker_buy_quote_asset_volume: f32,
+    #[serde(default)]
+    pub quote_asset_volume: Option<f32>,
+    #[serde(default)]
+    pub number_of_trades: Option<i64>,
+    #[serde(default)]
+    pub taker_buy_base_asset_volume: Option<f32>,
+    #[serde(default)]
+    pub taker_buy_quote_asset_volume: Option<f32>,
+    /// False for the candle which is still forming.
+    #[serde(default = "default_closed")]
+    pub closed: bool,
+}
+
+fn default_closed() -> bool {
+    true
 }
 
 impl Kline {
-    fn from_kline_data(data: KlineData) -> Self {
-        Kline {
+    /// Parses kline considering it forming if it closes after `now`.
+    fn from_kline_data(data: KlineData, now: i64) -> std::result::Result<Self, ParseFloatError> {
+        Ok(Kline {
             t_open: data.0,
-            open: data.1.parse::<f32>().unwrap(),
-            high: data.2.parse::<f32>().unwrap(),
-            low: data.3.parse::<f32>().unwrap(),
-            close: data.4.parse::<f32>().unwrap(),
-            volume: data.5.parse::<f32>().unwrap(),
+            open: data.1.parse::<f32>()?,
+            high: data.2.parse::<f32>()?,
+            low: data.3.parse::<f32>()?,
+            close: data.4.parse::<f32>()?,
+            volume: data.5.parse::<f32>()?,
             t_close: data.6,
-            quote_asset_volume: data.7.parse::<f32>().unwrap(),
-            number_of_trades: data.8,
-            taker_buy_base_asset_volume: data.9.parse::<f32>().unwrap(),
-            taker_buy_quote_asset_volume: data.10.parse::<f32>().unwrap(),
-        }
+            quote_asset_volume: Some(data.7.parse::<f32>()?),
+            number_of_trades: Some(data.8),
+            taker_buy_base_asset_volume: Some(data.9.parse::<f32>()?),
+            taker_buy_quote_asset_volume: Some(data.10.parse::<f32>()?),
+            closed: data.6 < now,
+        })
     }
 }
 
+/// Aggregated trade: trades filled at the same time, price and side.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct Trade {
+    pub id: i64,
+    pub price: f32,
+    pub qty: f32,
+    pub time: i64,
+    pub is_buyer_maker: bool,
+}
+
+/// Price statistics of a symbol over the last 24 hours.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct Ticker {
+    pub last_price: f64,
+    pub price_change_percent: f64,
+    /// Time of the last price in millis.
+    pub ts: i64,
+}
+
+impl Ticker {
+    fn from_ticker_data(data: &TickerData) -> std::result::Result<Self, ParseFloatError> {
+        Ok(Ticker {
+            last_price: data.last_price.parse::<f64>()?,
+            price_change_percent: data.price_change_percent.parse::<f64>()?,
+            ts: data.close_time,
+        })
+    }
+}
+
+impl Trade {
+    fn from_agg_trade_data(data: AggTradeData) -> std::result::Result<Self, ParseFloatError> {
+        Ok(Trade {
+            id: data.id,
+            price: data.price.parse::<f32>()?,
+            qty: data.qty.parse::<f32>()?,
+            time: data.time,
+            is_buyer_maker: data.is_buyer_maker,
+        })
+    }
+}
+
+enum TradesFrom {
+    Window(i64, i64),
+    Id(i64),
+}
+
 impl Ord for Kline {
     fn cmp(&self, other: &Self) -> Ordering {
         if self.close < other.close {
@@ -137,39 +272,586 @@ impl Eq for Kline {}
 
 impl PartialOrd for Kline {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.close.partial_cmp(&other.close)
+        Some(self.cmp(other))
     }
 }
 
 impl Client {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            max_retries: MAX_RETRIES,
+            rest: Rest::new(),
+            limiter: Default::default(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Fetches klines for the page starting at the left edge of `page`.
+    ///
+    /// Rate limited requests are retried after the delay requested by the server.
     pub async fn kline(
+        &self,
         symbol: String,
         interval: Interval,
-        start_time: i64,
+        page: Bounds,
+        limit: usize,
+    ) -> Result<Vec<Kline>> {
+        self.retrying(|| self.kline_once(&symbol, interval, page, limit))
+            .await
+    }
+
+    /// Fetches all aggregated trades within `page` with the end excluded.
+    ///
+    /// Page is requested in windows of at most an hour, each window is followed by trade ids
+    /// until there are no more trades in it.
+    pub async fn agg_trades(&self, symbol: &str, page: Bounds) -> Result<Vec<Trade>> {
+        let mut trades = vec![];
+        let mut start = page.0;
+        while start < page.1 {
+            let end = min(page.1, start + AGG_TRADES_WINDOW_MILLIS);
+            let mut from = TradesFrom::Window(start, end - 1);
+            loop {
+                let batch = self
+                    .retrying(|| self.agg_trades_once(symbol, page, &from))
+                    .await?;
+                let full = batch.len() == AGG_TRADES_LIMIT;
+                let last_id = batch.last().map(|t| t.id);
+                let in_window = batch.iter().take_while(|t| t.time < end).count();
+                let passed_window = in_window < batch.len();
+                trades.extend(batch.into_iter().take(in_window));
+
+                match last_id {
+                    Some(id) if full && !passed_window => from = TradesFrom::Id(id + 1),
+                    _ => break,
+                }
+            }
+            start = end;
+        }
+
+        Ok(trades)
+    }
+
+    async fn retrying<T, F, Fut>(&self, f: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match f().await {
+                Err(Error::RateLimited(url, retry_after)) if attempt < self.max_retries => {
+                    attempt += 1;
+                    let secs = min(
+                        retry_after.unwrap_or(DEFAULT_RETRY_AFTER_SECS),
+                        MAX_RETRY_AFTER_SECS,
+                    );
+                    warn!("Rate limited on {url}. Retrying in {secs}s. Attempt: {attempt}.");
+                    tokio::time::sleep(Duration::from_secs(secs)).await;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    async fn kline_once(
+        &self,
+        symbol: &str,
+        interval: Interval,
+        page: Bounds,
         limit: usize,
-    ) -> Result<Vec<Kline>, ClientError> {
-        let url = format!("{}{}", BASE_URL, PATH_KLINE);
+    ) -> Result<Vec<Kline>> {
+        let url = format!("{}{}", self.base_url, PATH_KLINE);
         let params = &[
-            ("symbol", symbol.as_str()),
+            ("symbol", symbol),
             ("interval", interval.as_str()),
-            ("startTime", &start_time.to_string()),
+            ("startTime", &page.0.to_string()),
             ("limit", &limit.to_string()),
         ];
-        let resp = Rest::new().get_with_params(&url, params).await?;
-        let json_str = &resp.text().await?;
-        let res = serde_json::from_str::<Vec<KlineData>>(json_str)?;
-
-        Ok(res
-            .into_iter()
-            .map(|data| Kline::from_kline_data(data))
-            .collect())
-    }
-
-    pub async fn info() -> Info {
-        let url = format!("{}{}", BASE_URL, PATH_INFO);
-        let resp = Rest::new().get(&url).await.unwrap();
-        let json_str = &resp.text().await.unwrap();
-        let res: Info = serde_json::from_str(json_str).unwrap();
-        res
+        self.limiter.acquire(WEIGHT_KLINES).await;
+        let resp = self
+            .rest
+            .get_with_params(&url, params)
+            .await
+            .context(url.as_str())?;
+        let json_str = &self.body(resp, Some(symbol)).await?;
+
+        Client::parse_klines(symbol, page, json_str)
+    }
+
+    async fn agg_trades_once(
+        &self,
+        symbol: &str,
+        page: Bounds,
+        from: &TradesFrom,
+    ) -> Result<Vec<Trade>> {
+        let url = format!("{}{}", self.base_url, PATH_AGG_TRADES);
+        let limit = AGG_TRADES_LIMIT.to_string();
+        let mut params = vec![("symbol", symbol.to_string()), ("limit", limit)];
+        match from {
+            TradesFrom::Window(start, end) => {
+                params.push(("startTime", start.to_string()));
+                params.push(("endTime", end.to_string()));
+            }
+            TradesFrom::Id(id) => params.push(("fromId", id.to_string())),
+        }
+        let params: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.limiter.acquire(WEIGHT_AGG_TRADES).await;
+
+        let resp = self
+            .rest
+            .get_with_params(&url, &params)
+            .await
+            .context(url.as_str())?;
+        let json_str = &self.body(resp, Some(symbol)).await?;
+
+        Client::parse_agg_trades(symbol, page, json_str)
+    }
+
+    /// Fetches 24 hour tickers of the symbols in a single request.
+    pub async fn tickers(&self, symbols: &[String]) -> Result<Vec<(String, Ticker)>> {
+        self.retrying(|| self.tickers_once(symbols)).await
+    }
+
+    async fn tickers_once(&self, symbols: &[String]) -> Result<Vec<(String, Ticker)>> {
+        let url = format!("{}{}", self.base_url, PATH_TICKER);
+        let weight = match symbols.len() {
+            0..=20 => 2,
+            21..=100 => 40,
+            _ => 80,
+        };
+        let symbols = serde_json::to_string(symbols).context(url.as_str())?;
+        self.limiter.acquire(weight).await;
+        let resp = self
+            .rest
+            .get_with_params(&url, &[("symbols", symbols.as_str())])
+            .await
+            .context(url.as_str())?;
+        let json_str = &self.body(resp, None).await?;
+
+        Client::parse_tickers(&url, json_str)
+    }
+
+    pub async fn info(&self) -> Result<Info> {
+        let url = format!("{}{}", self.base_url, PATH_INFO);
+        self.limiter.acquire(WEIGHT_INFO).await;
+        let resp = self.rest.get(&url).await.context(url.as_str())?;
+        let json_str = &self.body(resp, None).await?;
+        let res: Info = serde_json::from_str(json_str).context(url.as_str())?;
+        Ok(res)
+    }
+
+    /// Body of a successful response, otherwise the error Binance responded with. Invalid
+    /// symbol errors name `symbol`, the one requested if only one was.
+    async fn body(&self, resp: reqwest::Response, symbol: Option<&str>) -> Result<String> {
+        let url = redacted(resp.url());
+        let status = resp.status();
+        if let Some(used) = resp
+            .headers()
+            .get(USED_WEIGHT)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u32>().ok())
+        {
+            self.limiter.observe(used);
+        }
+        if let StatusCode::TOO_MANY_REQUESTS | StatusCode::IM_A_TEAPOT = status {
+            let retry_after = resp
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok());
+
+            // retrying while banned only prolongs the ban
+            return Err(match status {
+                StatusCode::IM_A_TEAPOT => Error::Banned(url, retry_after),
+                _ => Error::RateLimited(url, retry_after),
+            });
+        }
+
+        let body = resp.text().await.context(url.as_str())?;
+        if status.is_success() {
+            return Ok(body);
+        }
+
+        let err = match (serde_json::from_str::<ApiError>(&body), symbol) {
+            (Ok(api), Some(symbol)) if api.code == CODE_INVALID_SYMBOL => {
+                Error::InvalidSymbol(url, symbol.to_string())
+            }
+            (Ok(api), _) => Error::Api(url, api.code, api.msg),
+            (Err(_), _) => Error::Status(url, status.as_u16()),
+        };
+        warn!("Binance request failed: {err}.");
+        Err(err)
+    }
+
+    fn parse_klines(symbol: &str, page: Bounds, json_str: &str) -> Result<Vec<Kline>> {
+        let res = serde_json::from_str::<Vec<KlineData>>(json_str).context((symbol, page))?;
+        let now = Utc::now().timestamp_millis();
+
+        res.into_iter()
+            .map(|data| Ok(Kline::from_kline_data(data, now).context((symbol, page))?))
+            .collect()
+    }
+
+    fn parse_tickers(url: &str, json_str: &str) -> Result<Vec<(String, Ticker)>> {
+        let res = serde_json::from_str::<Vec<TickerData>>(json_str).context(url)?;
+
+        res.into_iter()
+            .map(|data| {
+                let window = Bounds(data.open_time, data.close_time);
+                let ticker =
+                    Ticker::from_ticker_data(&data).context((data.symbol.as_str(), window))?;
+                Ok((data.symbol, ticker))
+            })
+            .collect()
+    }
+
+    fn parse_agg_trades(symbol: &str, page: Bounds, json_str: &str) -> Result<Vec<Trade>> {
+        let res = serde_json::from_str::<Vec<AggTradeData>>(json_str).context((symbol, page))?;
+
+        res.into_iter()
+            .map(|data| Ok(Trade::from_agg_trade_data(data).context((symbol, page))?))
+            .collect()
+    }
+}
+
+/// Url with the secret query parameters left out.
+fn redacted(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| !SECRET_PARAMS.contains(&k.as_ref()))
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    url.to_string()
+}
+
+#[cfg(test)]
+mod client_tests {
+    use super::super::mock::{self, MockBinance};
+    use super::*;
+
+    const KLINE_ROW: &str = r#"[1659225600000,"23634.00","23645.10","23622.04","23630.55","86.45",1659225659999,"2043245.29",1582,"43.71","1033042.49","0"]"#;
+
+    #[test]
+    fn test_parse_klines() {
+        let json_str = format!("[{KLINE_ROW},{KLINE_ROW}]");
+        let klines = Client::parse_klines("BTCUSDT", Bounds(0, 1), &json_str).unwrap();
+
+        assert_eq!(klines.len(), 2);
+        assert_eq!(klines[0].t_open, 1659225600000);
+        assert_eq!(klines[0].t_close, 1659225659999);
+        assert_eq!(klines[0].number_of_trades, Some(1582));
+        assert_eq!(
+            klines[0].quote_asset_volume,
+            Some("2043245.29".parse().unwrap())
+        );
+        assert_eq!(klines[0].taker_buy_base_asset_volume, Some(43.71));
+        assert_eq!(
+            klines[0].taker_buy_quote_asset_volume,
+            Some("1033042.49".parse().unwrap())
+        );
+        assert_eq!(klines[0].close, 23630.55);
+        assert!(klines[0].closed);
+    }
+
+    #[test]
+    fn test_parse_klines_forming() {
+        let t_open = Utc::now().timestamp_millis();
+        let json_str = format!(
+            "[{}]",
+            KLINE_ROW
+                .replace("1659225600000", &t_open.to_string())
+                .replace("1659225659999", &(t_open + 59_999).to_string())
+        );
+        let klines = Client::parse_klines("BTCUSDT", Bounds(0, 1), &json_str).unwrap();
+
+        assert!(!klines[0].closed);
+    }
+
+    #[test]
+    fn test_parse_klines_decode_error_has_page() {
+        let page = Bounds(1659225600000, 1659285600000);
+        let err = Client::parse_klines("BTCUSDT", page, "[[1659225600000,").unwrap_err();
+
+        match err {
+            Error::Decode(symbol, bounds, _) => {
+                assert_eq!(symbol, "BTCUSDT");
+                assert_eq!(bounds, page);
+            }
+            _ => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_klines_invalid_value_has_page() {
+        let page = Bounds(10, 20);
+        let json_str = format!("[{}]", KLINE_ROW.replace("23645.10", "abc"));
+        let err = Client::parse_klines("ETHUSDT", page, &json_str).unwrap_err();
+
+        match err {
+            Error::InvalidValue(symbol, bounds, _) => {
+                assert_eq!(symbol, "ETHUSDT");
+                assert_eq!(bounds, page);
+            }
+            _ => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kline_retries_rate_limited() {
+        let mock = MockBinance::start().await;
+        let klines = mock::klines(0, 60_000, 3);
+        mock.rate_limited(0, 2).await;
+        mock.klines_page(0, &klines).await;
+
+        let res = mock
+            .client()
+            .kline(
+                "BTCUSDT".to_string(),
+                Interval::Minute,
+                Bounds(0, 180_000),
+                3,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res, klines);
+        assert_eq!(mock.received_requests().await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_kline_gives_up_when_rate_limited() {
+        let mock = MockBinance::start().await;
+        mock.rate_limited(0, (MAX_RETRIES + 1) as u64).await;
+        mock.klines_page(0, &mock::klines(0, 60_000, 3)).await;
+
+        let err = mock
+            .client()
+            .kline(
+                "BTCUSDT".to_string(),
+                Interval::Minute,
+                Bounds(0, 180_000),
+                3,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, Error::RateLimited(_, Some(0))));
+        assert_eq!(mock.received_requests().await.len(), MAX_RETRIES + 1);
+    }
+
+    #[tokio::test]
+    async fn test_kline_api_error() {
+        let mock = MockBinance::start().await;
+        mock.klines_error(400, r#"{"code":-1121,"msg":"Invalid symbol."}"#)
+            .await;
+
+        let err = mock
+            .client()
+            .kline("NOPE".to_string(), Interval::Minute, Bounds(0, 60_000), 1)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(&err, Error::InvalidSymbol(url, symbol)
+            if url.contains("symbol=NOPE") && symbol == "NOPE"));
+        assert_eq!(err.user_message(), "Binance rejected symbol NOPE");
+        assert_eq!(mock.received_requests().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_kline_error_without_body() {
+        let mock = MockBinance::start().await;
+        mock.klines_error(502, "Bad Gateway").await;
+
+        let err = mock
+            .client()
+            .kline(
+                "BTCUSDT".to_string(),
+                Interval::Minute,
+                Bounds(0, 60_000),
+                1,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Status(_, 502)));
+    }
+
+    #[tokio::test]
+    async fn test_kline_banned_not_retried() {
+        let mock = MockBinance::start().await;
+        mock.klines_error(418, "").await;
+
+        let err = mock
+            .client()
+            .kline(
+                "BTCUSDT".to_string(),
+                Interval::Minute,
+                Bounds(0, 60_000),
+                1,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Banned(_, None)));
+        assert_eq!(mock.received_requests().await.len(), 1);
+    }
+
+    #[test]
+    fn test_redacted() {
+        let url = reqwest::Url::parse(
+            "https://api.binance.com/api/v3/order?symbol=BTCUSDT&apiKey=k&signature=s",
+        )
+        .unwrap();
+        assert_eq!(
+            redacted(&url),
+            "https://api.binance.com/api/v3/order?symbol=BTCUSDT"
+        );
+
+        let url = reqwest::Url::parse("https://api.binance.com/api/v3/order?signature=s").unwrap();
+        assert_eq!(redacted(&url), "https://api.binance.com/api/v3/order");
+    }
+
+    #[tokio::test]
+    async fn test_kline_request_params() {
+        let mock = MockBinance::start().await;
+        mock.klines_page(120_000, &mock::klines(120_000, 60_000, 2))
+            .await;
+
+        mock.client()
+            .kline(
+                "ETHUSDT".to_string(),
+                Interval::Minute,
+                Bounds(120_000, 240_000),
+                2,
+            )
+            .await
+            .unwrap();
+
+        let requests = mock.received_requests().await;
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            mock::query_value(&requests[0], "symbol").unwrap(),
+            "ETHUSDT"
+        );
+        assert_eq!(mock::query_value(&requests[0], "interval").unwrap(), "1m");
+        assert_eq!(
+            mock::query_value(&requests[0], "startTime").unwrap(),
+            "120000"
+        );
+        assert_eq!(mock::query_value(&requests[0], "limit").unwrap(), "2");
+    }
+
+    #[tokio::test]
+    async fn test_tickers() {
+        let mock = MockBinance::start().await;
+        mock.tickers(&[("BTCUSDT", 65_000.5), ("ETHUSDT", 3_200.0)])
+            .await;
+        let symbols = ["BTCUSDT", "ETHUSDT"].map(String::from);
+
+        let tickers = mock.client().tickers(&symbols).await.unwrap();
+
+        assert_eq!(
+            tickers,
+            vec![
+                (
+                    "BTCUSDT".to_string(),
+                    Ticker {
+                        last_price: 65_000.5,
+                        price_change_percent: 1.5,
+                        ts: 86_400_000
+                    }
+                ),
+                (
+                    "ETHUSDT".to_string(),
+                    Ticker {
+                        last_price: 3_200.0,
+                        price_change_percent: 1.5,
+                        ts: 86_400_000
+                    }
+                ),
+            ]
+        );
+        let requests = mock.received_requests().await;
+        assert_eq!(
+            mock::query_value(&requests[0], "symbols").unwrap(),
+            r#"["BTCUSDT","ETHUSDT"]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_kline_malformed_rows() {
+        let mock = MockBinance::start().await;
+        mock.klines_raw(0, r#"[[0,"1.0","2.0"]]"#).await;
+
+        let err = mock
+            .client()
+            .kline(
+                "BTCUSDT".to_string(),
+                Interval::Minute,
+                Bounds(0, 60_000),
+                1,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Decode(_, Bounds(0, 60_000), _)));
+    }
+
+    #[tokio::test]
+    async fn test_agg_trades_follows_ids_until_window_end() {
+        let mock = MockBinance::start().await;
+        let first = mock::trades(1, 0, 1, AGG_TRADES_LIMIT);
+        // second batch crosses the page end at 1500
+        let second = mock::trades(AGG_TRADES_LIMIT as i64 + 1, 1000, 1, 1000);
+        mock.agg_trades_window(0, &first).await;
+        mock.agg_trades_from_id(AGG_TRADES_LIMIT as i64 + 1, &second)
+            .await;
+
+        let trades = mock
+            .client()
+            .agg_trades("BTCUSDT", Bounds(0, 1500))
+            .await
+            .unwrap();
+
+        assert_eq!(trades.len(), 1500);
+        assert_eq!(trades.last().unwrap().time, 1499);
+        assert!(trades.windows(2).all(|w| w[0].id + 1 == w[1].id));
+
+        let requests = mock.received_requests().await;
+        assert_eq!(requests.len(), 2);
+        assert_eq!(mock::query_value(&requests[0], "endTime").unwrap(), "1499");
+    }
+
+    #[tokio::test]
+    async fn test_agg_trades_splits_page_to_hour_windows() {
+        let mock = MockBinance::start().await;
+        let hour = AGG_TRADES_WINDOW_MILLIS;
+        mock.agg_trades_window(0, &mock::trades(1, 0, 1000, 2))
+            .await;
+        mock.agg_trades_window(hour, &mock::trades(3, hour, 1000, 2))
+            .await;
+
+        let trades = mock
+            .client()
+            .agg_trades("BTCUSDT", Bounds(0, hour + 5000))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            trades.iter().map(|t| t.id).collect::<Vec<_>>(),
+            [1, 2, 3, 4]
+        );
+        assert_eq!(mock.received_requests().await.len(), 2);
     }
 }
@@ -1,18 +1,81 @@
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+/// The epoch is on a Thursday, weekly candles open on Mondays from 4 days after it.
+const WEEK_OPEN_MILLIS: i64 = 4 * DAY_MILLIS;
+
 #[derive(Debug, Clone, Copy)]
 pub enum Interval {
+    Second,
+    FiveSeconds,
     Minute,
+    FiveMinutes,
+    FifteenMinutes,
     Hour,
+    FourHours,
     Day,
+    Week,
 }
 
 impl Interval {
-    pub fn as_str(&self) -> &str {
+    pub const ALL: [Interval; 9] = [
+        Interval::Second,
+        Interval::FiveSeconds,
+        Interval::Minute,
+        Interval::FiveMinutes,
+        Interval::FifteenMinutes,
+        Interval::Hour,
+        Interval::FourHours,
+        Interval::Day,
+        Interval::Week,
+    ];
+
+    /// Interval by its name, e.g. `1m`.
+    pub fn parse(s: &str) -> Option<Interval> {
+        Self::ALL.into_iter().find(|i| i.as_str() == s)
+    }
+
+    pub fn as_str(&self) -> &'static str {
         match self {
+            Interval::Second => "1s",
+            Interval::FiveSeconds => "5s",
             Interval::Minute => "1m",
+            Interval::FiveMinutes => "5m",
+            Interval::FifteenMinutes => "15m",
             Interval::Hour => "1h",
+            Interval::FourHours => "4h",
             Interval::Day => "1d",
+            Interval::Week => "1w",
         }
     }
+
+    pub fn millis(&self) -> i64 {
+        match self {
+            Interval::Second => 1000,
+            Interval::FiveSeconds => 5 * 1000,
+            Interval::Minute => 60 * 1000,
+            Interval::FiveMinutes => 5 * 60 * 1000,
+            Interval::FifteenMinutes => 15 * 60 * 1000,
+            Interval::Hour => 60 * 60 * 1000,
+            Interval::FourHours => 4 * 60 * 60 * 1000,
+            Interval::Day => DAY_MILLIS,
+            Interval::Week => 7 * DAY_MILLIS,
+        }
+    }
+
+    /// Open time of the candle containing `ts`, candles open every interval since the
+    /// epoch except weekly ones which open on Mondays.
+    pub fn open(&self, ts: i64) -> i64 {
+        let offset = match self {
+            Interval::Week => WEEK_OPEN_MILLIS,
+            _ => 0,
+        };
+
+        (ts - offset).div_euclid(self.millis()) * self.millis() + offset
+    }
+
+    /// Intervals without kline endpoint which are aggregated locally from trades.
+    pub fn is_aggregated(&self) -> bool {
+        matches!(self, Interval::Second | Interval::FiveSeconds)
+    }
 }
 
 impl PartialEq for Interval {
@@ -20,3 +83,24 @@ impl PartialEq for Interval {
         format!("{:?}", self) == format!("{:?}", other)
     }
 }
+
+#[cfg(test)]
+mod interval_tests {
+    use super::*;
+
+    #[test]
+    fn test_open() {
+        // 2022-07-01 was a Friday
+        let friday = 1_656_633_600_000 + 5 * 60 * 60 * 1000;
+        assert_eq!(Interval::Hour.open(friday + 59_999), friday);
+        assert_eq!(Interval::Day.open(friday), 1_656_633_600_000);
+        // opened on Monday 2022-06-27
+        assert_eq!(Interval::Week.open(friday), 1_656_288_000_000);
+        assert_eq!(Interval::Week.open(1_656_288_000_000), 1_656_288_000_000);
+        assert_eq!(
+            Interval::Week.open(1_656_288_000_000 - 1),
+            1_655_683_200_000
+        );
+        assert_eq!(Interval::parse("1w"), Some(Interval::Week));
+    }
+}
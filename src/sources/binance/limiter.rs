@@ -0,0 +1,125 @@
+//! Request weight spent on the Binance api per minute. Clients sharing a limiter wait for
+//! the next minute once the budget is spent instead of being rejected with 429, repeated
+//! ones get the ip banned with 418.
+
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use tracing::debug;
+
+/// Part of the 6000 weight per minute of the api used, leaving room for other tools on the
+/// same ip.
+const BUDGET: u32 = 4800;
+const WINDOW: Duration = Duration::from_secs(60);
+/// Budget of an instance opened from another one. The used weight the server reports counts
+/// the requests of both, so the child stops early and leaves the rest to its parent.
+pub const CHILD_WEIGHT_BUDGET: u32 = BUDGET / 4;
+
+/// Budget of the limiters made with [`WeightLimiter::default`].
+static DEFAULT_BUDGET: AtomicU32 = AtomicU32::new(BUDGET);
+
+/// Sets the budget of the default limiters, before the first client of the instance is made.
+pub fn set_weight_budget(budget: u32) {
+    DEFAULT_BUDGET.store(budget, Ordering::Relaxed);
+}
+
+#[derive(Clone, Debug)]
+pub struct WeightLimiter {
+    budget: u32,
+    window: Duration,
+    state: Arc<Mutex<Window>>,
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    /// Time of the first request of the window.
+    started: Option<Instant>,
+    used: u32,
+}
+
+impl Default for WeightLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUDGET.load(Ordering::Relaxed), WINDOW)
+    }
+}
+
+impl WeightLimiter {
+    pub fn new(budget: u32, window: Duration) -> Self {
+        Self {
+            budget,
+            window,
+            state: Default::default(),
+        }
+    }
+
+    /// Waits until the weight fits in the budget of the current window and spends it.
+    pub async fn acquire(&self, weight: u32) {
+        while let Some(wait) = self.try_acquire(weight, Instant::now()) {
+            debug!("request weight budget spent, waiting {wait:?}");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Spends the weight when it fits, otherwise how long until the window restarts.
+    fn try_acquire(&self, weight: u32, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        let started = match state.started {
+            Some(started) if now.saturating_duration_since(started) < self.window => started,
+            _ => {
+                *state = Window {
+                    started: Some(now),
+                    used: 0,
+                };
+                now
+            }
+        };
+        // a request heavier than the budget goes alone in a window
+        if state.used == 0 || state.used + weight <= self.budget {
+            state.used += weight;
+            return None;
+        }
+
+        Some(self.window - now.saturating_duration_since(started))
+    }
+
+    /// Weight the server reports as used in the current minute, which includes requests of
+    /// other clients on the same ip.
+    pub fn observe(&self, used: u32) {
+        let mut state = self.state.lock().unwrap();
+        state.used = state.used.max(used);
+    }
+}
+
+#[cfg(test)]
+mod limiter_tests {
+    use super::*;
+
+    #[test]
+    fn test_waits_for_next_window() {
+        let limiter = WeightLimiter::new(10, WINDOW);
+        let start = Instant::now();
+
+        assert_eq!(limiter.try_acquire(4, start), None);
+        assert_eq!(limiter.try_acquire(6, start), None);
+        let at = start + Duration::from_secs(15);
+        assert_eq!(limiter.try_acquire(2, at), Some(Duration::from_secs(45)));
+
+        assert_eq!(limiter.try_acquire(2, start + WINDOW), None);
+        limiter.observe(9);
+        assert!(limiter.try_acquire(2, start + WINDOW).is_some());
+    }
+
+    #[test]
+    fn test_heavy_request_goes_alone() {
+        let limiter = WeightLimiter::new(10, WINDOW);
+        let start = Instant::now();
+
+        assert_eq!(limiter.try_acquire(40, start), None);
+        assert!(limiter.try_acquire(1, start).is_some());
+    }
+}
@@ -0,0 +1,213 @@
+//! Mock Binance server serving canned responses for the data pipeline tests.
+
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, Request, ResponseTemplate,
+};
+
+use super::{Client, Kline, Trade};
+
+pub struct MockBinance {
+    server: MockServer,
+}
+
+impl MockBinance {
+    pub async fn start() -> Self {
+        Self {
+            server: MockServer::start().await,
+        }
+    }
+
+    pub fn client(&self) -> Client {
+        Client::new(self.server.uri())
+    }
+
+    /// Serves `klines` for the klines request starting at `start_time`.
+    pub async fn klines_page(&self, start_time: i64, klines: &[Kline]) {
+        let body = Value::Array(klines.iter().map(kline_json).collect());
+        self.klines_response(start_time, ResponseTemplate::new(200).set_body_json(body))
+            .await;
+    }
+
+    /// Serves raw `body` for the klines request starting at `start_time`.
+    pub async fn klines_raw(&self, start_time: i64, body: &str) {
+        self.klines_response(
+            start_time,
+            ResponseTemplate::new(200).set_body_string(body.to_string()),
+        )
+        .await;
+    }
+
+    /// Serves `trades` for the aggTrades request starting at `start_time`.
+    pub async fn agg_trades_window(&self, start_time: i64, trades: &[Trade]) {
+        self.agg_trades_response(("startTime", start_time), trades)
+            .await;
+    }
+
+    /// Serves `trades` for the aggTrades request continuing from trade `id`.
+    pub async fn agg_trades_from_id(&self, id: i64, trades: &[Trade]) {
+        self.agg_trades_response(("fromId", id), trades).await;
+    }
+
+    /// Serves raw `body` for the aggTrades request starting at `start_time`.
+    pub async fn agg_trades_raw(&self, start_time: i64, body: &str) {
+        Mock::given(method("GET"))
+            .and(path("/api/v3/aggTrades"))
+            .and(query_param("startTime", start_time.to_string().as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body.to_string()))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Responds with 429 and Retry-After header for the first `times` klines requests.
+    pub async fn rate_limited(&self, retry_after_secs: u64, times: u64) {
+        Mock::given(method("GET"))
+            .and(path("/api/v3/klines"))
+            .respond_with(
+                ResponseTemplate::new(429)
+                    .insert_header("Retry-After", retry_after_secs.to_string().as_str()),
+            )
+            .up_to_n_times(times)
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Responds with `status` and raw `body` to every klines request.
+    pub async fn klines_error(&self, status: u16, body: &str) {
+        Mock::given(method("GET"))
+            .and(path("/api/v3/klines"))
+            .respond_with(ResponseTemplate::new(status).set_body_string(body.to_string()))
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Serves 24 hour tickers with the last prices of the symbols.
+    pub async fn tickers(&self, prices: &[(&str, f64)]) {
+        let body = Value::Array(
+            prices
+                .iter()
+                .map(|(symbol, price)| {
+                    json!({
+                        "symbol": symbol,
+                        "lastPrice": price.to_string(),
+                        "priceChangePercent": "1.5",
+                        "openTime": 0,
+                        "closeTime": 86_400_000
+                    })
+                })
+                .collect(),
+        );
+        Mock::given(method("GET"))
+            .and(path("/api/v3/ticker/24hr"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&self.server)
+            .await;
+    }
+
+    pub async fn received_requests(&self) -> Vec<Request> {
+        self.server.received_requests().await.unwrap_or_default()
+    }
+
+    async fn agg_trades_response(&self, param: (&str, i64), trades: &[Trade]) {
+        let body = Value::Array(trades.iter().map(trade_json).collect());
+        Mock::given(method("GET"))
+            .and(path("/api/v3/aggTrades"))
+            .and(query_param(param.0, param.1.to_string().as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&self.server)
+            .await;
+    }
+
+    async fn klines_response(&self, start_time: i64, resp: ResponseTemplate) {
+        Mock::given(method("GET"))
+            .and(path("/api/v3/klines"))
+            .and(query_param("startTime", start_time.to_string().as_str()))
+            .respond_with(resp)
+            .mount(&self.server)
+            .await;
+    }
+}
+
+/// Returns query parameter value of the request.
+pub fn query_value(req: &Request, key: &str) -> Option<String> {
+    req.url
+        .query_pairs()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.to_string())
+}
+
+/// Generates `n` candles of `step` millis each starting at `start`.
+pub fn klines(start: i64, step: i64, n: usize) -> Vec<Kline> {
+    (0..n)
+        .map(|i| {
+            let t_open = start + i as i64 * step;
+            let open = 100.0 + i as f32;
+            Kline {
+                t_open,
+                open,
+                high: open + 2.0,
+                low: open - 1.0,
+                close: open + 1.0,
+                volume: 10.0 + i as f32,
+                t_close: t_open + step - 1,
+                quote_asset_volume: Some(1000.0),
+                number_of_trades: Some(5),
+                taker_buy_base_asset_volume: Some(4.0),
+                taker_buy_quote_asset_volume: Some(400.0),
+                closed: true,
+            }
+        })
+        .collect()
+}
+
+/// Kline in Binance wire format.
+pub fn kline_json(k: &Kline) -> Value {
+    json!([
+        k.t_open,
+        k.open.to_string(),
+        k.high.to_string(),
+        k.low.to_string(),
+        k.close.to_string(),
+        k.volume.to_string(),
+        k.t_close,
+        k.quote_asset_volume.unwrap_or_default().to_string(),
+        k.number_of_trades.unwrap_or_default(),
+        k.taker_buy_base_asset_volume
+            .unwrap_or_default()
+            .to_string(),
+        k.taker_buy_quote_asset_volume
+            .unwrap_or_default()
+            .to_string(),
+        "0"
+    ])
+}
+
+/// Generates `n` trades with ids from `first_id` one every `step` millis starting at `start`.
+pub fn trades(first_id: i64, start: i64, step: i64, n: usize) -> Vec<Trade> {
+    (0..n)
+        .map(|i| Trade {
+            id: first_id + i as i64,
+            price: 100.0 + i as f32,
+            qty: 1.0,
+            time: start + i as i64 * step,
+            is_buyer_maker: i % 2 == 0,
+        })
+        .collect()
+}
+
+/// Aggregated trade in Binance wire format.
+pub fn trade_json(t: &Trade) -> Value {
+    json!({
+        "a": t.id,
+        "p": t.price.to_string(),
+        "q": t.qty.to_string(),
+        "f": t.id,
+        "l": t.id,
+        "T": t.time,
+        "m": t.is_buyer_maker,
+        "M": true
+    })
+}
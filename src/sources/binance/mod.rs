@@ -1,7 +1,9 @@
 mod client;
 mod interval;
+mod limiter;
+#[cfg(test)]
+pub mod mock;
 
 pub use self::client::*;
 pub use self::interval::*;
-
-pub mod errors;
+pub use self::limiter::{set_weight_budget, CHILD_WEIGHT_BUDGET};
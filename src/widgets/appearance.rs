@@ -0,0 +1,130 @@
+use std::ops::RangeInclusive;
+
+use egui::{
+    plot::{Legend, Line, Plot, Value, Values},
+    Context, FontId, RichText, TextStyle, Ui,
+};
+use serde::{Deserialize, Serialize};
+
+/// Text style of the axis labels, tooltips and legends of the plots.
+const PLOT_TEXT: &str = "plot";
+
+pub const SCALE_RANGE: RangeInclusive<f32> = 0.5..=4.0;
+pub const PLOT_FONT_RANGE: RangeInclusive<f32> = 6.0..=32.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    /// Points per pixel replacing the native one of the display.
+    pub ui_scale: Option<f32>,
+    pub plot_font_size: f32,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            ui_scale: None,
+            plot_font_size: 12.0,
+        }
+    }
+}
+
+impl Appearance {
+    /// Values out of range moved to the closest allowed.
+    pub fn clamped(self) -> Self {
+        Self {
+            ui_scale: self
+                .ui_scale
+                .map(|s| s.clamp(*SCALE_RANGE.start(), *SCALE_RANGE.end())),
+            plot_font_size: self
+                .plot_font_size
+                .clamp(*PLOT_FONT_RANGE.start(), *PLOT_FONT_RANGE.end()),
+        }
+    }
+
+    /// Sets the scale and plot text of the context when they differ, `native_scale` is used
+    /// without an override.
+    pub fn apply(&self, ctx: &Context, native_scale: f32) {
+        let scale = self.ui_scale.unwrap_or(native_scale);
+        if (ctx.pixels_per_point() - scale).abs() > f32::EPSILON {
+            ctx.set_pixels_per_point(scale);
+        }
+
+        let name = TextStyle::Name(PLOT_TEXT.into());
+        let font = FontId::proportional(self.plot_font_size);
+        if ctx.style().text_styles.get(&name) != Some(&font) {
+            let mut style = (*ctx.style()).clone();
+            style.text_styles.insert(name, font);
+            ctx.set_style(style);
+        }
+    }
+
+    /// Sample text and plot drawn with the appearance, relative to the applied scale.
+    pub fn preview_ui(&self, ui: &mut Ui) {
+        let body = TextStyle::Body.resolve(ui.style()).size;
+        let scale = self.ui_scale.unwrap_or_else(|| ui.ctx().pixels_per_point());
+        ui.label(RichText::new("ui text").size(body * scale / ui.ctx().pixels_per_point()));
+
+        set_plot_text(ui, self.plot_font_size);
+        let line = Line::new(Values::from_values_iter(
+            (0..50).map(|i| Value::new(i as f64, (i as f64 / 5.0).sin())),
+        ))
+        .name("close");
+        Plot::new("appearance preview")
+            .height(120.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .legend(Legend::default())
+            .show(ui, |plot_ui| plot_ui.line(line));
+    }
+}
+
+/// Draws plot labels of the ui with the plot text style set by [`Appearance::apply`].
+pub fn plot_text(ui: &mut Ui) {
+    if let Some(font) = ui
+        .style()
+        .text_styles
+        .get(&TextStyle::Name(PLOT_TEXT.into()))
+    {
+        set_plot_text(ui, font.size);
+    }
+}
+
+/// Plots draw axis labels with the monospace style, tooltips and legends with the body one.
+fn set_plot_text(ui: &mut Ui, size: f32) {
+    let text_styles = &mut ui.style_mut().text_styles;
+    text_styles.insert(TextStyle::Body, FontId::proportional(size));
+    text_styles.insert(TextStyle::Monospace, FontId::monospace(size));
+}
+
+#[cfg(test)]
+mod appearance_tests {
+    use super::*;
+
+    #[test]
+    fn test_clamped() {
+        let appearance = Appearance {
+            ui_scale: Some(10.0),
+            plot_font_size: 1.0,
+        }
+        .clamped();
+        assert_eq!(appearance.ui_scale, Some(4.0));
+        assert_eq!(appearance.plot_font_size, 6.0);
+
+        assert_eq!(Appearance::default().clamped(), Appearance::default());
+    }
+
+    #[test]
+    fn test_apply() {
+        let ctx = Context::default();
+        let appearance = Appearance {
+            ui_scale: None,
+            plot_font_size: 20.0,
+        };
+
+        appearance.apply(&ctx, 1.0);
+
+        let font = ctx.style().text_styles[&TextStyle::Name(PLOT_TEXT.into())].clone();
+        assert_eq!(font, FontId::proportional(20.0));
+    }
+}
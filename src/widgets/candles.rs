@@ -1,54 +1,163 @@
+use std::sync::Arc;
+
 use chrono::{DateTime, Utc};
-use crossbeam::channel::{unbounded, Sender};
 use egui::{
-    plot::{BoxElem, BoxPlot, BoxSpread, LinkedAxisGroup, Plot, PlotBounds},
-    Color32, Id, Response, Stroke, Vec2, Widget,
+    plot::{
+        BoxElem, BoxPlot, BoxSpread, HLine, Legend, LineStyle, LinkedAxisGroup, MarkerShape, Plot,
+        Points, Polygon, Text, VLine, Value, Values,
+    },
+    Align2, Color32, Key, Pos2, Rect, Response, RichText, Stroke, TextEdit, Ui, Vec2, Widget,
+    Window,
+};
+use tracing::{error, info};
+
+use crate::{
+    netstrat::{
+        axis::{self, Axis},
+        bounds::Bounds,
+        channels::{self, LatestSender},
+        data::Data,
+        events::Event,
+        export,
+        indicators::{Band, Glyph, IndicatorOutput, Level, Marker, MarkerKind, Series},
+        notes::Note,
+        snap::{self, Hover},
+        stats,
+    },
+    sources::binance::{Interval, Kline},
 };
-use serde::{Deserialize, Serialize};
-use tracing::{debug, enabled, error, info};
 
-use crate::netstrat::{bounds::Bounds, data::Data};
+use super::{appearance::plot_text, indicator_panel, signals::Signals};
 
 const BOUNDS_SEND_DELAY_MILLIS: i64 = 300;
+/// Distance in points from a note marker at which the note is hovered.
+const NOTE_HOVER_RADIUS: f32 = 8.0;
+/// Distance in points from a signal marker at which its label is shown.
+const SIGNAL_HOVER_RADIUS: f32 = 6.0;
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-struct CachedPlotData {
-    auto_bounds: bool,
-    min_auto_bounds: PlotBounds,
+/// What to do with the range selected on the plot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeAction {
+    Zoom,
+    Stats,
+    Export,
 }
 
 pub struct Candles {
-    data: Data,
+    /// Candles shared with the other panes.
+    data: Arc<Data>,
     val: Vec<BoxElem>,
+    overlays: Vec<Series>,
+    dots: Vec<Series>,
+    bands: Vec<Band>,
+    /// Extent of the overlays which may be projected beyond the candles.
+    x_range: (f64, f64),
+    /// Price extent of the dots which may lie beyond the candles.
+    y_range: (f64, f64),
+    markers: Vec<Marker>,
+    glyphs: Vec<Glyph>,
+    signals: Signals,
+    /// Names of the signal series taken from the indicators output.
+    output_signals: Vec<String>,
+    levels: Vec<Level>,
+    values: Vec<Series>,
     axes_group: LinkedAxisGroup,
-    bounds_pub: Sender<Bounds>,
+    /// What x of the data stands for. Notes, events and the span are kept in time
+    /// and placed through it.
+    axis: Axis,
+    /// Interval of the data, the candles are as wide as it on the time axis. None when it
+    /// is unknown, see [`Axis::step`].
+    interval: Option<Interval>,
+    /// Whether the axis or interval changed since the elements were built.
+    axis_changed: bool,
+    bounds_pub: LatestSender<Bounds>,
     incremental_drag_diff: f32,
     last_time_drag_happened: DateTime<Utc>,
     drag_happened: bool,
     bounds: Bounds,
     enabled: bool,
+    /// Whether the crosshair snaps to prices of the hovered candle.
+    snap: bool,
+    hover: Option<Hover>,
+    rect: Rect,
+    x_view: [f64; 2],
+    /// Hover at the point the context menu was opened at.
+    menu_at: Option<Hover>,
+    /// Prices of the levels added from the context menu.
+    user_levels: Vec<f64>,
+    /// Time span marked with vertical lines.
+    span: Option<[f64; 2]>,
+    notes: Vec<Note>,
+    notes_changed: bool,
+    /// Whether note texts are shown without hovering the markers.
+    note_texts: bool,
+    hovered_note: Option<usize>,
+    /// Note hovered when the context menu was opened.
+    menu_note: Option<usize>,
+    /// Note being edited and where its editor shows up.
+    editing: Option<(usize, Pos2)>,
+    events: Vec<(Event, Color32)>,
+    /// Time range selected with shift-drag, snapped to the candles within it.
+    selection: Option<Bounds>,
+    /// Time the shift-drag selecting a range started at.
+    selecting: Option<f64>,
+    /// Where the actions of the selection show up.
+    selection_menu: Option<Pos2>,
+    range_action: Option<(RangeAction, Bounds)>,
 }
 
 impl Default for Candles {
     fn default() -> Self {
-        let (s_bounds, _) = unbounded();
+        let (s_bounds, _) = channels::latest();
 
         Self {
             data: Default::default(),
             val: Default::default(),
+            overlays: Default::default(),
+            dots: Default::default(),
+            bands: Default::default(),
+            x_range: (f64::INFINITY, f64::NEG_INFINITY),
+            y_range: (f64::INFINITY, f64::NEG_INFINITY),
+            markers: Default::default(),
+            glyphs: Default::default(),
+            signals: Default::default(),
+            output_signals: Default::default(),
+            levels: Default::default(),
+            values: Default::default(),
             axes_group: LinkedAxisGroup::new(false, false),
+            axis: Axis::Time,
+            interval: Some(Interval::Minute),
+            axis_changed: false,
             bounds_pub: s_bounds,
             last_time_drag_happened: Utc::now(),
             drag_happened: Default::default(),
             bounds: Bounds(0, 0),
             incremental_drag_diff: 0.0,
             enabled: true,
+            snap: false,
+            hover: None,
+            menu_at: None,
+            rect: Rect::NOTHING,
+            x_view: [0.0, 0.0],
+            user_levels: Default::default(),
+            span: None,
+            notes: Default::default(),
+            notes_changed: false,
+            note_texts: false,
+            hovered_note: None,
+            menu_note: None,
+            editing: None,
+            events: Default::default(),
+            selection: None,
+            selecting: None,
+            selection_menu: None,
+            range_action: None,
         }
     }
 }
 
 impl Candles {
-    pub fn new(axes_group: LinkedAxisGroup, bounds_pub: Sender<Bounds>) -> Self {
+    pub fn new(axes_group: LinkedAxisGroup, bounds_pub: LatestSender<Bounds>) -> Self {
         Self {
             axes_group,
             bounds_pub,
@@ -56,46 +165,316 @@ impl Candles {
         }
     }
 
-    pub fn set_data(&mut self, data: Data) {
-        let val: Vec<BoxElem> = data
-            .vals
-            .iter()
-            .map(|k| -> BoxElem {
-                BoxElem::new(
-                    (k.t_open + k.t_close) as f64 / 2.0,
-                    BoxSpread::new(
-                        k.low as f64,
-                        {
-                            match k.open > k.close {
-                                true => k.close as f64,
-                                false => k.open as f64,
-                            }
-                        },
-                        k.open as f64, // we don't need to see median for candle
-                        {
-                            match k.open > k.close {
-                                true => k.open as f64,
-                                false => k.close as f64,
-                            }
-                        },
-                        k.high as f64,
-                    ),
-                )
-                .name(Data::format_ts(k.t_close as f64))
-                .stroke(Stroke::new(1.0, Data::k_color(k)))
-                .fill(Data::k_color(k))
-                .whisker_width(0.0)
-                .box_width((k.t_open - k.t_close) as f64 * 0.9)
-            })
-            .collect();
+    pub fn set_axes_group(&mut self, axes_group: LinkedAxisGroup) {
+        self.axes_group = axes_group;
+    }
+
+    /// Axis of the data set next.
+    pub fn set_axis(&mut self, axis: Axis) {
+        self.axis_changed |= self.axis != axis;
+        self.axis = axis;
+    }
+
+    /// Interval of the data set next, none when it is unknown.
+    pub fn set_interval(&mut self, interval: Option<Interval>) {
+        self.axis_changed |= self.interval != interval;
+        self.interval = interval;
+    }
+
+    pub fn axis(&self) -> &Axis {
+        &self.axis
+    }
+
+    /// Screen rect and x range of the plot on the last drawn frame.
+    pub fn x_view(&self) -> (Rect, [f64; 2]) {
+        (self.rect, self.x_view)
+    }
+
+    /// Requests data for the visible range once it stops changing.
+    pub fn moved(&mut self) {
+        self.drag_happened = true;
+        self.last_time_drag_happened = Utc::now();
+    }
+
+    /// Rebuilds the elements unless the same data is set again on the same axis.
+    pub fn set_data(&mut self, data: Arc<Data>) {
+        if Arc::ptr_eq(&self.data, &data) && !self.axis_changed {
+            return;
+        }
+        self.axis_changed = false;
 
+        let val = candle_boxes(
+            &data.vals,
+            &self.axis,
+            self.axis.step(self.interval, &data.vals),
+        );
+
+        self.signals.place(&data, &self.axis);
         self.data = data;
         self.val = val;
     }
 
+    /// Takes everything drawn over the candles from the output, the panel series are ignored.
+    pub fn set_indicators(&mut self, output: IndicatorOutput) {
+        self.x_range = output
+            .overlay
+            .iter()
+            .chain(output.bands.iter().flat_map(|b| [&b.upper, &b.lower]))
+            .flat_map(|s| &s.points)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+                (min.min(p[0]), max.max(p[0]))
+            });
+        self.y_range = output
+            .dots
+            .iter()
+            .flat_map(|s| &s.points)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+                (min.min(p[1]), max.max(p[1]))
+            });
+        self.overlays = output.overlay;
+        self.dots = output.dots;
+        self.bands = output.bands;
+        self.markers = output.markers;
+        self.glyphs = output.glyphs;
+        self.output_signals.iter().for_each(|name| {
+            self.signals.remove(name);
+        });
+        self.output_signals = output.signals.iter().map(|s| s.name.clone()).collect();
+        output.signals.into_iter().for_each(|s| self.signals.add(s));
+        self.signals.place(&self.data, &self.axis);
+        self.levels = output.levels;
+        self.values = output.values;
+    }
+
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled
     }
+
+    pub fn set_snap(&mut self, snap: bool) {
+        self.snap = snap
+    }
+
+    /// Marks the time span, e.g. the one visible in another pane.
+    pub fn set_span(&mut self, span: Option<[f64; 2]>) {
+        self.span = span
+    }
+
+    /// Pointer position with the candle under it on the last drawn frame.
+    pub fn hovered(&self) -> Option<Hover> {
+        self.hover
+    }
+
+    /// Events drawn as vertical lines of their colors.
+    pub fn set_events(&mut self, events: Vec<(Event, Color32)>) {
+        self.events = events;
+    }
+
+    pub fn set_notes(&mut self, notes: Vec<Note>) {
+        self.notes = notes;
+        self.editing = None;
+        self.hovered_note = None;
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// Returns true once after notes were added, edited or deleted.
+    pub fn take_notes_changed(&mut self) -> bool {
+        std::mem::take(&mut self.notes_changed)
+    }
+
+    pub fn set_note_texts(&mut self, shown: bool) {
+        self.note_texts = shown
+    }
+
+    fn remove_note(&mut self, idx: usize) {
+        self.notes.remove(idx);
+        self.notes_changed = true;
+        self.editing = None;
+        self.hovered_note = None;
+    }
+
+    /// Editor of the note opened from the context menu, empty notes are dropped once done.
+    fn note_editor(&mut self, ctx: &egui::Context) {
+        let (idx, pos) = match self.editing {
+            Some(editing) if editing.0 < self.notes.len() => editing,
+            _ => return,
+        };
+
+        let (mut done, mut delete) = (false, false);
+        Window::new("note")
+            .collapsible(false)
+            .resizable(false)
+            .default_pos(pos)
+            .show(ctx, |ui| {
+                let resp = ui.add(TextEdit::singleline(&mut self.notes[idx].text));
+                self.notes_changed |= resp.changed();
+                done = resp.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+                ui.horizontal(|ui| {
+                    done |= ui.button("done").clicked();
+                    delete = ui.button("delete").clicked();
+                });
+            });
+
+        if delete || (done && self.notes[idx].text.trim().is_empty()) {
+            self.remove_note(idx);
+        } else if done {
+            self.editing = None;
+        }
+    }
+
+    /// Actions for the point the context menu was opened at.
+    fn context_menu_ui(&mut self, ui: &mut egui::Ui) {
+        let hover = match self.menu_at {
+            Some(hover) => hover,
+            None => {
+                ui.close_menu();
+                return;
+            }
+        };
+        let [x, y] = hover.point;
+
+        let mut copied = None;
+        if ui.button("copy time").clicked() {
+            copied = Some(Data::format_ts(
+                self.axis.to_ts(hover.candle.map_or(x, |k| k.t_open as f64)),
+            ));
+        }
+        if ui.button("copy price").clicked() {
+            copied = Some(y.to_string());
+        }
+        if let Some(k) = hover.candle {
+            copied = copied.or(copy_json_ui(ui, "copy candle as json", &[k]));
+        }
+        let Bounds(from, to) = self.bounds;
+        let visible = self.data.slice_range(from, to);
+        if visible.len() > 1 {
+            let text = format!("copy {} visible candles as json", visible.len());
+            copied = copied.or(copy_json_ui(ui, &text, visible));
+        }
+        ui.separator();
+        if ui.button("add level here").clicked() {
+            self.user_levels.push(y);
+            ui.close_menu();
+        }
+        if !self.user_levels.is_empty() && ui.button("remove levels").clicked() {
+            self.user_levels.clear();
+            ui.close_menu();
+        }
+        ui.separator();
+        let pos = ui.min_rect().left_top();
+        if ui.button("add note here").clicked() {
+            self.notes.push(Note {
+                ts: self.axis.to_ts(x) as i64,
+                price: y,
+                ..Default::default()
+            });
+            self.editing = Some((self.notes.len() - 1, pos));
+            ui.close_menu();
+        }
+        if let Some(idx) = self.menu_note.filter(|idx| *idx < self.notes.len()) {
+            if ui.button("edit note").clicked() {
+                self.editing = Some((idx, pos));
+                ui.close_menu();
+            }
+            if ui.button("delete note").clicked() {
+                self.remove_note(idx);
+                ui.close_menu();
+            }
+        }
+
+        if let Some(text) = copied {
+            ui.output().copied_text = text;
+            ui.close_menu();
+        }
+    }
+
+    /// Time range shown on the last drawn frame.
+    pub fn visible(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// Range selected with shift-drag from the open of its first candle to the close of
+    /// the last one.
+    pub fn selection(&self) -> Option<Bounds> {
+        self.selection
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+        self.selecting = None;
+        self.selection_menu = None;
+    }
+
+    /// Returns the action picked for the selection once.
+    pub fn take_range_action(&mut self) -> Option<(RangeAction, Bounds)> {
+        self.range_action.take()
+    }
+
+    /// Selects the candles between the drag start and `ts`, the actions show up once the
+    /// drag is released.
+    fn select(&mut self, resp: &Response, ts: Option<f64>) {
+        if resp.drag_started() {
+            self.selecting = ts;
+            self.selection_menu = None;
+        }
+        let from = match self.selecting {
+            Some(from) => from,
+            None => return,
+        };
+        if let Some(ts) = ts {
+            self.selection = snap_range(&self.data.vals, from, ts);
+        }
+        if resp.drag_released() {
+            self.selecting = None;
+            self.selection_menu = self.selection.and(resp.hover_pos());
+        }
+    }
+
+    /// Actions for the selected range.
+    fn selection_menu(&mut self, ctx: &egui::Context) {
+        let (selection, pos) = match (self.selection, self.selection_menu) {
+            (Some(selection), Some(pos)) => (selection, pos),
+            _ => return,
+        };
+
+        let mut picked = None;
+        let mut clear = false;
+        Window::new("selection")
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .fixed_pos(pos)
+            .show(ctx, |ui| {
+                let candles = stats::within(&self.data.vals, selection).len();
+                ui.label(format!(
+                    "{} - {}, {candles} candles",
+                    Data::format_ts(selection.0 as f64),
+                    Data::format_ts(selection.1 as f64),
+                ));
+                [
+                    (RangeAction::Zoom, "zoom to selection"),
+                    (RangeAction::Stats, "stats for selection"),
+                    (RangeAction::Export, "export selection"),
+                ]
+                .into_iter()
+                .for_each(|(action, text)| {
+                    if ui.button(text).clicked() {
+                        picked = Some(action);
+                    }
+                });
+                clear = ui.button("clear").clicked();
+            });
+
+        if let Some(action) = picked {
+            self.range_action = Some((action, selection));
+            self.selection_menu = None;
+        }
+        if clear {
+            self.clear_selection();
+        }
+    }
 }
 
 impl Widget for &mut Candles {
@@ -106,55 +485,201 @@ impl Widget for &mut Candles {
                 .num_milliseconds()
                 > BOUNDS_SEND_DELAY_MILLIS
         {
-            let msg = self.bounds.clone();
-            let send_res = self.bounds_pub.send(msg.clone());
-            match send_res {
-                Ok(_) => info!("sent bounds: {msg:?}"),
-                Err(err) => error!("failed to send bounds: {err}"),
-            }
+            let msg = self.bounds;
+            self.bounds_pub.send(msg);
+            info!("sent bounds: {msg:?}");
 
             self.drag_happened = false;
         }
+        let (label_axis, x_axis, tooltip_axis) =
+            (self.axis.clone(), self.axis.clone(), self.axis.clone());
+        if self.selection.is_some() && ui.input().key_pressed(Key::Escape) {
+            self.clear_selection();
+        }
+        // shift-drag selects a range instead of panning
+        let shift = ui.input().modifiers.shift;
         ui.add_enabled_ui(self.enabled, |ui| {
-            Plot::new("candles")
+            plot_text(ui);
+            let plot = Plot::new("candles")
                 .link_axis(self.axes_group.clone())
-                .label_formatter(|_, v| -> String { format!("{}", Data::format_ts(v.x)) })
-                .x_axis_formatter(|v, _range| Data::format_ts(v))
-                .include_x(self.data.max_x())
-                .include_x(self.data.min_x())
+                .label_formatter(move |_, v| -> String { label_axis.format(v.x) })
+                .x_axis_formatter(move |v, _range| x_axis.format(v))
+                .include_x(self.data.max_x().max(self.x_range.1))
+                .include_x(self.data.min_x().min(self.x_range.0))
                 .set_margin_fraction(Vec2::new(0.05, 0.05))
-                .include_y(self.data.max_y())
-                .include_y(self.data.min_y())
+                .include_y(self.data.max_y().max(self.y_range.1))
+                .include_y(self.data.min_y().min(self.y_range.0))
+                .legend(Legend::default())
+                // wheel is handled by the graph, ctrl+wheel zooms prices
+                .allow_scroll(false)
+                .allow_drag(!shift && self.selecting.is_none())
+                .show_y(!self.hover.is_some_and(|h| h.snapped))
                 .show(ui, |plot_ui| {
+                    // alt temporarily disables snapping
+                    let snap = self.snap && !plot_ui.ctx().input().modifiers.alt;
+                    self.hover = plot_ui
+                        .pointer_coordinate()
+                        .map(|p| snap::hover(&self.data.vals, [p.x, p.y], snap));
+                    if let Some(hover) = self.hover.filter(|h| h.snapped) {
+                        let y = hover.point[1];
+                        plot_ui.hline(
+                            HLine::new(y)
+                                .color(Color32::GRAY)
+                                .style(LineStyle::dashed_dense()),
+                        );
+                        plot_ui.text(
+                            Text::new(
+                                Value::new(plot_ui.plot_bounds().max()[0], y),
+                                RichText::new(format!("{y:.8}")).small(),
+                            )
+                            .anchor(Align2::RIGHT_BOTTOM)
+                            .color(Color32::GRAY),
+                        );
+                    }
+
+                    let data = self.data.clone();
+                    let step = self.axis.step(self.interval, &data.vals);
+                    let values = self.values.clone();
+                    let glyphs = self.glyphs.clone();
+                    self.bands.iter().for_each(|b| {
+                        indicator_panel::band(b)
+                            .into_iter()
+                            .for_each(|p| plot_ui.polygon(p));
+                        plot_ui.line(indicator_panel::line(&b.upper));
+                        plot_ui.line(indicator_panel::line(&b.lower));
+                    });
+                    self.overlays
+                        .iter()
+                        .for_each(|s| plot_ui.line(indicator_panel::line(s)));
+                    self.dots
+                        .iter()
+                        .for_each(|s| plot_ui.points(indicator_panel::scatter(s)));
+                    self.user_levels.iter().for_each(|y| {
+                        plot_ui.hline(HLine::new(*y).name(format!("level {y}")));
+                    });
+                    self.levels.iter().for_each(|l| {
+                        let (line, label) = indicator_panel::level(l);
+                        plot_ui.line(line);
+                        plot_ui.text(label);
+                    });
+                    if let Some(Bounds(from, to)) = self.selection {
+                        let [min, max] = [plot_ui.plot_bounds().min(), plot_ui.plot_bounds().max()];
+                        let [from, to] = [from, to].map(|ts| self.axis.to_x(ts as f64));
+                        plot_ui.polygon(
+                            Polygon::new(Values::from_values(vec![
+                                Value::new(from, min[1]),
+                                Value::new(to, min[1]),
+                                Value::new(to, max[1]),
+                                Value::new(from, max[1]),
+                            ]))
+                            .color(Color32::LIGHT_BLUE)
+                            .fill_alpha(0.1)
+                            .width(0.0),
+                        );
+                    }
+                    self.span.iter().flatten().for_each(|ts| {
+                        plot_ui.vline(
+                            VLine::new(self.axis.to_x(*ts))
+                                .color(Color32::LIGHT_BLUE)
+                                .width(2.0),
+                        );
+                    });
+                    [MarkerKind::Buy, MarkerKind::Sell]
+                        .into_iter()
+                        .for_each(|kind| plot_ui.points(markers(&self.markers, kind)));
+                    indicator_panel::glyphs(&self.glyphs)
+                        .into_iter()
+                        .for_each(|p| plot_ui.points(p));
+                    let bounds = plot_ui.plot_bounds();
+                    self.signals
+                        .points([bounds.min()[0], bounds.max()[0]])
+                        .into_iter()
+                        .for_each(|p| plot_ui.points(p));
+
+                    let pointer = plot_ui
+                        .pointer_coordinate()
+                        .map(|p| plot_ui.screen_from_plot(p));
+                    self.hovered_note = self.notes.iter().position(|n| {
+                        let pos = plot_ui.screen_from_plot(note_value(n, &self.axis));
+                        pointer.is_some_and(|p| p.distance(pos) <= NOTE_HOVER_RADIUS)
+                    });
+                    if let Some(pos) = pointer {
+                        let left =
+                            plot_ui.plot_from_screen(pos - Vec2::new(SIGNAL_HOVER_RADIUS, 0.0));
+                        let right =
+                            plot_ui.plot_from_screen(pos + Vec2::new(SIGNAL_HOVER_RADIUS, 0.0));
+                        let hovered = self.signals.within([left.x, right.x]).iter().find(|s| {
+                            let at = plot_ui.screen_from_plot(Value::new(s.point[0], s.point[1]));
+                            pos.distance(at) <= SIGNAL_HOVER_RADIUS
+                        });
+                        if let Some(s) = hovered {
+                            plot_ui.text(
+                                Text::new(
+                                    Value::new(s.point[0], s.point[1]),
+                                    RichText::new(format!("  {}", s.label)).small(),
+                                )
+                                .anchor(Align2::LEFT_CENTER)
+                                .color(Color32::WHITE),
+                            );
+                        }
+                    }
+                    plot_ui.points(notes(&self.notes, &self.axis));
+                    self.notes
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _)| self.note_texts || self.hovered_note == Some(*idx))
+                        .for_each(|(_, n)| {
+                            plot_ui.text(
+                                Text::new(
+                                    note_value(n, &self.axis),
+                                    RichText::new(format!("  {}", n.text)).small(),
+                                )
+                                .anchor(Align2::LEFT_CENTER)
+                                .color(Color32::GOLD),
+                            );
+                        });
+
                     plot_ui.box_plot(
                         BoxPlot::new(self.val.clone())
-                            .element_formatter(Box::new(|el, _| -> String {
-                                format!(
-                                    "open: {:.8}\nclose: {:.8}\nhigh: {:.8}\nlow: {:.8}\n{}",
-                                    {
-                                        match el.fill == Color32::LIGHT_RED {
-                                            true => el.spread.quartile3,
-                                            false => el.spread.quartile1,
-                                        }
-                                    },
-                                    {
-                                        match el.fill == Color32::LIGHT_RED {
-                                            true => el.spread.quartile1,
-                                            false => el.spread.quartile3,
-                                        }
-                                    },
-                                    el.spread.upper_whisker,
-                                    el.spread.lower_whisker,
-                                    Data::format_ts(el.argument),
-                                )
+                            .element_formatter(Box::new(move |el, _| -> String {
+                                match data.vals.binary_search_by(|k| {
+                                    axis::slot(k, step).0.total_cmp(&el.argument)
+                                }) {
+                                    Ok(idx) => tooltip(
+                                        &data.vals[idx],
+                                        el.argument,
+                                        &values,
+                                        &glyphs,
+                                        &tooltip_axis,
+                                    ),
+                                    Err(_) => tooltip_axis.describe(el.argument),
+                                }
                             }))
                             .vertical(),
                     );
 
+                    let top = plot_ui.plot_bounds().max()[1];
+                    self.events.iter().for_each(|(e, color)| {
+                        let x = self.axis.to_x(e.ts as f64);
+                        plot_ui.vline(VLine::new(x).color(*color).style(LineStyle::dashed_loose()));
+                        plot_ui.text(
+                            Text::new(
+                                Value::new(x, top),
+                                RichText::new(format!(" {}", e.label)).small(),
+                            )
+                            .anchor(Align2::LEFT_TOP)
+                            .color(*color),
+                        );
+                    });
+
                     let plot_bounds = plot_ui.plot_bounds();
-                    self.bounds = Bounds(plot_bounds.min()[0] as i64, plot_bounds.max()[0] as i64);
+                    self.x_view = [plot_bounds.min()[0], plot_bounds.max()[0]];
+                    let [from, to] = self.x_view.map(|x| self.axis.to_ts(x));
+                    self.bounds = Bounds(from as i64, to as i64);
 
-                    let drag_diff = plot_ui.pointer_coordinate_drag_delta().x;
+                    // measured in time so the compressed axis needs the same drag to download
+                    let drag_diff = plot_ui.pointer_coordinate_drag_delta().x as f64;
+                    let drag_diff = (self.axis.to_ts(self.x_view[0] + drag_diff) - from) as f32;
                     if drag_diff.abs() > 0.0 {
                         self.incremental_drag_diff += drag_diff;
 
@@ -167,8 +692,204 @@ impl Widget for &mut Candles {
                     }
 
                     plot_ui.ctx().request_repaint();
-                })
+
+                    plot_ui.pointer_coordinate().map(|p| self.axis.to_ts(p.x))
+                });
+            let resp = plot.response;
+
+            self.rect = resp.rect;
+            if shift || self.selecting.is_some() {
+                self.select(&resp, plot.inner);
+            }
+            self.selection_menu(ui.ctx());
+            if resp.secondary_clicked() {
+                self.menu_at = self.hover;
+                self.menu_note = self.hovered_note;
+            }
+            self.note_editor(ui.ctx());
+            resp.context_menu(|ui| self.context_menu_ui(ui))
+        })
+        .inner
+    }
+}
+
+/// Button copying the klines as a json array of export rows, warns about the size of the
+/// clipboard above [`export::JSON_WARN_ROWS`]. Returns the json once clicked.
+pub fn copy_json_ui(ui: &mut Ui, text: &str, klines: &[Kline]) -> Option<String> {
+    let clicked = ui.button(text).clicked();
+    if klines.len() > export::JSON_WARN_ROWS {
+        let mb = export::json_bytes(klines.len()) as f64 / (1024.0 * 1024.0);
+        ui.label(
+            RichText::new(format!("~{mb:.1} MB, large clipboards may slow other apps"))
+                .small()
+                .color(Color32::GOLD),
+        );
+    }
+    if !clicked {
+        return None;
+    }
+
+    match export::to_json(klines) {
+        Ok(json) => Some(json),
+        Err(err) => {
+            error!("failed to serialize candles: {err}");
+            None
+        }
+    }
+}
+
+/// Range from the open of the first candle to the close of the last one between the times
+/// in either order.
+fn snap_range(klines: &[Kline], a: f64, b: f64) -> Option<Bounds> {
+    let (from, to) = (a.min(b) as i64, a.max(b) as i64);
+    let within = stats::within(klines, Bounds(from, to));
+
+    Some(Bounds(within.first()?.t_open, within.last()?.t_close))
+}
+
+/// Box plot elements of the candles in their slots of `step` width, labeled with what x
+/// stands for on the axis.
+pub fn candle_boxes(klines: &[Kline], axis: &Axis, step: f64) -> Vec<BoxElem> {
+    klines
+        .iter()
+        .map(|k| -> BoxElem {
+            let (x, width) = axis::slot(k, step);
+            BoxElem::new(
+                x,
+                BoxSpread::new(
+                    k.low as f64,
+                    {
+                        match k.open > k.close {
+                            true => k.close as f64,
+                            false => k.open as f64,
+                        }
+                    },
+                    k.open as f64, // we don't need to see median for candle
+                    {
+                        match k.open > k.close {
+                            true => k.open as f64,
+                            false => k.close as f64,
+                        }
+                    },
+                    k.high as f64,
+                ),
+            )
+            .name({
+                let time = match axis {
+                    Axis::Time => Data::format_ts(k.t_close as f64),
+                    Axis::Index(_) => axis.describe(x),
+                };
+                match k.closed {
+                    true => time,
+                    false => format!("{time} (forming)"),
+                }
+            })
+            .stroke(Stroke::new(1.0, k_color(k)))
+            .fill(k_color(k))
+            .whisker_width(0.0)
+            .box_width(width)
         })
-        .response
+        .collect()
+}
+
+/// Candle color, forming candle is dimmed.
+fn k_color(k: &Kline) -> Color32 {
+    let color = match k.open > k.close {
+        true => Color32::LIGHT_RED,
+        false => Color32::LIGHT_GREEN,
+    };
+
+    match k.closed {
+        true => color,
+        false => color.linear_multiply(0.4),
+    }
+}
+
+/// Tooltip of the candle with the values and patterns of the indicators at its center `x`.
+fn tooltip(k: &Kline, x: f64, values: &[Series], glyphs: &[Glyph], axis: &Axis) -> String {
+    let mut lines = vec![
+        format!("open: {:.8}", k.open),
+        format!("close: {:.8}", k.close),
+        format!("high: {:.8}", k.high),
+        format!("low: {:.8}", k.low),
+        format!("volume: {:.8}", k.volume),
+    ];
+    if let Some(v) = k.quote_asset_volume {
+        lines.push(format!("quote volume: {v:.8}"));
+    }
+    if let Some(v) = k.taker_buy_base_asset_volume {
+        lines.push(format!("taker buy volume: {v:.8}"));
+    }
+    if let Some(n) = k.number_of_trades {
+        lines.push(format!("trades: {n}"));
+    }
+    values.iter().for_each(|s| {
+        if let Ok(idx) = s.points.binary_search_by(|p| p[0].total_cmp(&x)) {
+            lines.push(format!("{}: {:.8}", s.name, s.points[idx][1]));
+        }
+    });
+    glyphs
+        .iter()
+        .filter(|g| g.point[0] == x)
+        .for_each(|g| lines.push(format!("pattern: {}", g.name)));
+    lines.push(axis.describe(x));
+
+    lines.join("\n")
+}
+
+fn note_value(note: &Note, axis: &Axis) -> Value {
+    Value::new(axis.to_x(note.ts as f64), note.price)
+}
+
+fn notes(notes: &[Note], axis: &Axis) -> Points {
+    Points::new(Values::from_values_iter(
+        notes.iter().map(|n| note_value(n, axis)),
+    ))
+    .name("notes")
+    .shape(MarkerShape::Diamond)
+    .color(Color32::GOLD)
+    .filled(true)
+    .radius(5.0)
+}
+
+fn markers(markers: &[Marker], kind: MarkerKind) -> Points {
+    let (name, shape, color) = match kind {
+        MarkerKind::Buy => ("buy", MarkerShape::Up, Color32::GREEN),
+        MarkerKind::Sell => ("sell", MarkerShape::Down, Color32::RED),
+    };
+
+    Points::new(Values::from_values_iter(
+        markers
+            .iter()
+            .filter(|m| m.kind == kind)
+            .map(|m| Value::new(m.point[0], m.point[1])),
+    ))
+    .name(name)
+    .shape(shape)
+    .color(color)
+    .filled(true)
+    .radius(6.0)
+}
+
+#[cfg(test)]
+mod candles_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_snap_range() {
+        let klines = mock::klines(0, 60_000, 5);
+
+        // dragged backwards from the middle of the fourth candle into the second one
+        assert_eq!(
+            snap_range(&klines, 200_000.0, 70_000.0),
+            Some(Bounds(60_000, 240_000 - 1))
+        );
+        assert_eq!(
+            snap_range(&klines, 10_000.0, 10_000.0),
+            Some(Bounds(0, 60_000 - 1))
+        );
+        assert_eq!(snap_range(&klines, 400_000.0, 500_000.0), None);
     }
 }
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use crossbeam::channel::Receiver;
+use egui::{plot::LinkedAxisGroup, Color32, ComboBox, Ui};
+use egui_extras::{Size, StripBuilder};
+
+use crate::{
+    netstrat::{
+        aggregate,
+        bounds::Bounds,
+        channels,
+        data::Data,
+        events::Event,
+        indicators::{IndicatorOutput, Indicators},
+        notes::Note,
+    },
+    sources::binance::{Interval, Kline},
+};
+
+use super::{candles::Candles, volume::Volume};
+
+/// Candles and volume of the loaded data resampled to a wider interval with indicators of their own.
+///
+/// Plots are linked only with each other, the span visible in the main panes is marked over the candles.
+pub struct ContextPane {
+    interval: Interval,
+    candles: Candles,
+    volume: Volume,
+    indicators: Indicators,
+    data: Arc<Data>,
+    /// Drags of the candles, dropped as the pane shows loaded data only.
+    bounds_sub: Receiver<Bounds>,
+}
+
+impl Default for ContextPane {
+    fn default() -> Self {
+        let axes_group = LinkedAxisGroup::new(true, false);
+        let (s_bounds, r_bounds) = channels::latest();
+
+        Self {
+            interval: Interval::Day,
+            candles: Candles::new(axes_group.clone(), s_bounds),
+            volume: Volume::new(axes_group),
+            indicators: Default::default(),
+            data: Default::default(),
+            bounds_sub: r_bounds,
+        }
+    }
+}
+
+impl ContextPane {
+    pub fn set_klines(&mut self, klines: &[Kline]) {
+        self.data = Arc::new(aggregate::resample(klines, self.interval.millis()));
+        self.candles.set_interval(Some(self.interval));
+        self.volume.set_interval(Some(self.interval));
+        self.candles.set_data(self.data.clone());
+        self.volume.set_data(self.data.clone());
+        self.apply_indicators();
+    }
+
+    pub fn set_events(&mut self, events: Vec<(Event, Color32)>) {
+        self.candles.set_events(events);
+    }
+
+    pub fn set_notes(&mut self, notes: Vec<Note>) {
+        self.candles.set_notes(notes);
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        self.candles.notes()
+    }
+
+    pub fn set_note_texts(&mut self, shown: bool) {
+        self.candles.set_note_texts(shown);
+    }
+
+    /// Returns true once after notes were edited over the pane.
+    pub fn take_notes_changed(&mut self) -> bool {
+        self.candles.take_notes_changed()
+    }
+
+    /// Interval picker and indicators menu, returns true if the interval changed and klines should be set again.
+    pub fn toolbar_ui(&mut self, ui: &mut Ui) -> bool {
+        let interval = self.interval;
+        ComboBox::from_id_source("context interval")
+            .selected_text(format!("top: {}", self.interval.as_str()))
+            .show_ui(ui, |ui| {
+                [Interval::Minute, Interval::Hour, Interval::Day]
+                    .into_iter()
+                    .for_each(|i| {
+                        ui.selectable_value(&mut self.interval, i, i.as_str());
+                    });
+            });
+
+        ui.menu_button("top indicators", |ui| {
+            if self.indicators.ui(ui) {
+                self.apply_indicators();
+            }
+        });
+
+        interval != self.interval
+    }
+
+    fn apply_indicators(&mut self) {
+        self.indicators.compute(&self.data.closed());
+
+        let mut output = IndicatorOutput::default();
+        self.indicators.outputs().for_each(|o| output.extend(o));
+        self.candles.set_indicators(output);
+    }
+
+    /// Draws the pane with `span` visible in the main panes marked.
+    pub fn ui(&mut self, ui: &mut Ui, span: [f64; 2]) {
+        while self.bounds_sub.try_recv().is_ok() {}
+
+        self.candles.set_span(Some(span));
+        ui.push_id("context pane", |ui| {
+            StripBuilder::new(ui)
+                .size(Size::relative(0.8))
+                .size(Size::remainder())
+                .vertical(|mut strip| {
+                    strip.cell(|ui| {
+                        ui.add(&mut self.candles);
+                    });
+                    strip.cell(|ui| {
+                        ui.add(&self.volume);
+                    });
+                });
+        });
+    }
+}
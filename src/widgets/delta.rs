@@ -0,0 +1,119 @@
+use std::ops::RangeInclusive;
+
+use egui::{
+    plot::{Bar, BarChart, Legend, Line, LinkedAxisGroup, Plot, Value, Values},
+    Color32, Response, Widget,
+};
+
+use crate::netstrat::{
+    axis::Axis,
+    data::Data,
+    delta::{self, Delta},
+};
+
+use super::appearance::plot_text;
+
+/// Panel with taker buy minus taker sell volume bars and the cumulative delta line.
+pub struct DeltaPanel {
+    deltas: Vec<Delta>,
+    width: f64,
+    axes_group: LinkedAxisGroup,
+    axis: Axis,
+}
+
+impl Default for DeltaPanel {
+    fn default() -> Self {
+        Self {
+            deltas: Default::default(),
+            width: Default::default(),
+            axes_group: LinkedAxisGroup::new(false, false),
+            axis: Axis::Time,
+        }
+    }
+}
+
+impl DeltaPanel {
+    pub fn new(axes_group: LinkedAxisGroup) -> Self {
+        Self {
+            axes_group,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_axes_group(&mut self, axes_group: LinkedAxisGroup) {
+        self.axes_group = axes_group;
+    }
+
+    pub fn set_axis(&mut self, axis: Axis) {
+        self.axis = axis;
+    }
+
+    pub fn set_data(&mut self, data: &Data) {
+        self.deltas = delta::deltas(data);
+        self.width = data
+            .vals
+            .first()
+            .map_or(0.0, |k| (k.t_close - k.t_open) as f64 * 0.9);
+    }
+}
+
+impl Widget for &DeltaPanel {
+    fn ui(self, ui: &mut egui::Ui) -> Response {
+        plot_text(ui);
+        let bars = self
+            .deltas
+            .iter()
+            .map(|d| {
+                Bar::new(d.x, d.delta)
+                    .width(self.width)
+                    .fill(match d.delta < 0.0 {
+                        true => Color32::LIGHT_RED.linear_multiply(0.5),
+                        false => Color32::LIGHT_GREEN.linear_multiply(0.5),
+                    })
+            })
+            .collect();
+        let cumulative =
+            Values::from_values_iter(self.deltas.iter().map(|d| Value::new(d.x, d.cumulative)));
+        let deltas = self.deltas.clone();
+        let (axis, label_axis, bar_axis) =
+            (self.axis.clone(), self.axis.clone(), self.axis.clone());
+
+        Plot::new("delta")
+            .link_axis(self.axes_group.clone())
+            .x_axis_formatter(move |v: f64, _: &RangeInclusive<f64>| axis.format(v))
+            .label_formatter(move |name, v| {
+                format!("{name}\n{:.2}\n{}", v.y, label_axis.format(v.x))
+            })
+            .legend(Legend::default())
+            .allow_boxed_zoom(false)
+            .allow_scroll(false)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(
+                    BarChart::new(bars)
+                        .name("delta")
+                        .element_formatter(Box::new(move |b, _| {
+                            match deltas.binary_search_by(|d| d.x.total_cmp(&b.argument)) {
+                                Ok(idx) => tooltip(&deltas[idx], &bar_axis),
+                                Err(_) => bar_axis.describe(b.argument),
+                            }
+                        }))
+                        .vertical(),
+                );
+                plot_ui.line(Line::new(cumulative).name("cumulative delta"));
+            })
+            .response
+    }
+}
+
+fn tooltip(d: &Delta, axis: &Axis) -> String {
+    format!(
+        "buy: {:.8}\nsell: {:.8}\ndelta: {:.8}\ncumulative: {:.8}\n{}",
+        d.buy,
+        d.sell,
+        d.delta,
+        d.cumulative,
+        axis.describe(d.x)
+    )
+}
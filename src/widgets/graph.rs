@@ -1,338 +1,3277 @@
-use std::ffi::OsStr;
-use std::fs::File;
-use std::path::Path;
-use std::time::Duration;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use chrono::{Date, NaiveDateTime, NaiveTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use crossbeam::channel::{unbounded, Receiver, Sender};
 use egui::{
-    plot::LinkedAxisGroup, CentralPanel, ProgressBar, Response, TopBottomPanel, Ui, Widget,
+    plot::LinkedAxisGroup, Align2, Button, CentralPanel, Color32, ComboBox, Context, DragValue,
+    Grid, Id, Key, Label, LayerId, Order, ProgressBar, Rect, Response, RichText, Sense, Spinner,
+    TextEdit, TextStyle, TopBottomPanel, Ui, Widget, Window,
 };
 use egui_extras::{Size, StripBuilder};
 use poll_promise::Promise;
-use tracing::{debug, error, info, trace};
+use quick_error::ResultExt;
+use tracing::{debug, error, info, warn};
 
 use crate::{
+    errors::{Error, Result},
     netstrat::{
+        actions::{Actions, Shortcut},
+        axis::Axis,
         bounds::{Bounds, BoundsSet},
+        cache::Cache,
+        channels::{self, LatestSender},
         data::Data,
-        props::Props,
+        events::{self, Events},
+        indicators::{Header, IndicatorOutput, Indicators, Tone},
+        last_used::LastUsed,
+        launch,
+        manifest::{Manifest, Source, Written},
+        memory::{self, CandlesBudget, Views},
+        notes::{self, Notes},
+        notify::{Finished, Notifier},
+        portfolio,
+        props::{CandlesBand, Props},
+        refresh::{self, Schedule},
+        renko::{self, BrickSize},
+        replay::{self, Replay},
+        selection::Selection,
+        session::Session,
         state::State,
+        view::View,
+    },
+    netstrat::{
+        download::{self, DownloadConfig},
+        export::{
+            self, ChosenFormat, Column, Columns, ConfirmLimits, ExportOptions, Format,
+            IndicatorColumns, XlsxSheet,
+        },
+        expression::Definition,
+        import::{self, Import},
+        influx::{self, Influx},
+        journal::JournalConfig,
+        timezone::Timezone,
+    },
+    network::{
+        webhook::{Payload, Webhook},
+        ws_server::{Broadcaster, Message},
+    },
+    sources::binance::{Client, Interval, Kline},
+    windows::{
+        AppWindow, Backtest, Correlation, DataQuality, Expressions, Journal, Portfolio, Scripts,
+        Statistics, TimeRangeChooser,
     },
-    sources::binance::{errors::ClientError, Client, Kline},
-    windows::{AppWindow, TimeRangeChooser},
 };
 
-use super::{candles::Candles, volume::Volume};
+use super::{
+    candles::{Candles, RangeAction},
+    context::ContextPane,
+    delta::DeltaPanel,
+    indicator_panel::{IndicatorPanel, StudyPanel},
+    minimap::Minimap,
+    repaint::DelayedRepaint,
+    status::Status,
+    time_input::TimeInput,
+    toasts::Toast,
+    volume::{Volume, VolumeKind},
+    x_range,
+};
+
+/// Height of the overview strip under the volume.
+const MINIMAP_HEIGHT: f32 = 40.0;
+/// Export requests waiting for the graph, the chooser waits for room up to the timeout.
+const EXPORT_QUEUE: usize = 4;
+const EXPORT_SEND_TIMEOUT: Duration = Duration::from_millis(100);
+/// Candles shown around a focused time range on each side.
+const FOCUS_PADDING_CANDLES: i64 = 20;
+const PLACEHOLDER_TS_FORMAT: &str = "%Y-%m-%d %H:%M";
+/// Intervals of the quick switch strip, also picked with the number keys in this order.
+const STRIP_INTERVALS: [Interval; 7] = [
+    Interval::Minute,
+    Interval::FiveMinutes,
+    Interval::FifteenMinutes,
+    Interval::Hour,
+    Interval::FourHours,
+    Interval::Day,
+    Interval::Week,
+];
+const STRIP_KEYS: [Key; 7] = [
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+];
+/// Downloads of new props finishing sooner are likely watched, they are shown without a
+/// toast.
+const LOADED_TOAST_AFTER: Duration = Duration::from_secs(2);
+const ACTION_PROPS: &str = "window.props";
+const ACTION_SCRIPTS: &str = "window.scripts";
+const ACTION_EXPRESSIONS: &str = "window.expressions";
+const ACTION_BACKTEST: &str = "window.backtest";
+const ACTION_QUALITY: &str = "window.quality";
+const ACTION_STATS: &str = "window.stats";
+const ACTION_JOURNAL: &str = "window.journal";
+const ACTION_CORRELATION: &str = "window.correlation";
+const ACTION_PORTFOLIO: &str = "window.portfolio";
+/// Bad rows of an import listed in its toast, the rest are only counted.
+const IMPORT_ERRORS_SHOWN: usize = 3;
+/// Colors of event categories in their sorted order, repeated when there are more categories.
+const CATEGORY_COLORS: [Color32; 6] = [
+    Color32::LIGHT_BLUE,
+    Color32::GOLD,
+    Color32::LIGHT_RED,
+    Color32::LIGHT_GREEN,
+    Color32::KHAKI,
+    Color32::from_rgb(200, 130, 255),
+];
 
 #[derive(Default)]
 struct ExportState {
     triggered: bool,
+    format: Format,
+    /// Export over the confirm limits waiting for the user.
+    pending: Option<(Props, Format)>,
+    promise: Option<Promise<Result<Exported>>>,
+    /// Checked symbols of the list being downloaded for an export.
+    batch: Option<Batch>,
+}
+
+/// Symbols exported with the props of the chart, downloaded one after another.
+struct Batch {
+    props: Props,
+    format: Format,
+    promise: Promise<Vec<(String, Result<Vec<Kline>>)>>,
+}
+
+/// Where an export was written.
+enum Exported {
+    File(PathBuf),
+    /// A file of every symbol of a batch.
+    Files(Vec<PathBuf>),
+    Influx(influx::Summary),
+}
+
+/// Download in progress, for the notification once it finishes.
+struct Download {
+    started: Instant,
+    /// Whether new props are loaded rather than the shown range extended.
+    reset: bool,
+    /// Candles loaded before the download started.
+    base: usize,
+    /// Message of the last page which failed.
+    error: Option<String>,
+}
+
+/// Chart to show again from the toast of its finished download.
+#[derive(Debug, Clone)]
+struct Jump {
+    symbol: String,
+    props: Props,
+}
+
+/// What the chart area shows in place of the panes while there are no candles.
+#[derive(Debug, Clone, PartialEq)]
+enum Placeholder {
+    /// Nothing requested yet, e.g. after the symbol changed.
+    Unloaded,
+    Loading,
+    /// The requested range loaded without candles.
+    Empty,
+    Error(String),
+}
+
+/// Where a csv dropped on the app is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DropTarget {
+    /// The chart, while it is empty.
+    Chart,
+    /// The chart after the user agrees to merge the file into the shown candles of its symbol.
+    Merge,
+    NewWindow,
 }
 
 pub struct Graph {
     candles: Candles,
     volume: Volume,
+    indicator_panel: IndicatorPanel,
+    delta_panel: DeltaPanel,
+    study_panels: Vec<StudyPanel>,
+    /// Indicator states shown at the end of the toolbar.
+    headers: Vec<Header>,
+    minimap: Minimap,
+    /// Range the plots are moved to on the next frame, e.g. the one the minimap viewport
+    /// was moved to.
+    pending_range: Option<[f64; 2]>,
+    /// Whether the context pane of a wider interval is shown above the main panes.
+    split: bool,
+    /// Whether only the candles and volume are shown.
+    fullscreen: bool,
+    context_pane: ContextPane,
+    notes: Notes,
+    /// Whether note texts are shown without hovering.
+    note_texts: bool,
+    events: Events,
+    /// Path of the events csv to import.
+    events_path: String,
+    /// Path of the candles csv to import.
+    import_path: String,
+    /// File name of the imported candles shown, cleared once other candles are loaded.
+    imported: Option<String>,
+    /// Manifest of the imported file with whether the file matches its hash.
+    import_manifest: Option<(Manifest, bool)>,
+    /// Whether the imported candles have no known interval, they are drawn as wide as
+    /// the smallest gap between them then.
+    import_interval_unknown: bool,
+    /// Dropped csv of the shown symbol waiting for the user to merge it or open it apart.
+    merge_offer: Option<PathBuf>,
+    /// Candles are shown up to the replay cursor while replaying, downloads of dragged
+    /// ranges and eviction are paused meanwhile.
+    replay: Option<Replay>,
+    /// Time entered to start the replay or jump to.
+    replay_ts: String,
+    /// Candles are shown as of this time in millis, see [`Data::as_of`].
+    as_of: Option<i64>,
+    /// Date and time picked for the as of mode.
+    as_of_date: NaiveDate,
+    as_of_time: TimeInput,
+    /// Whether closes are shown as renko bricks. Indicators are hidden then as they are
+    /// computed over candles.
+    renko: bool,
+    brick_size: BrickSize,
+    /// Whether candles are placed by index leaving out gaps in time between them.
+    compress: bool,
+    axes_group: LinkedAxisGroup,
+    /// Area of the plots on the last drawn frame.
+    plots_rect: Rect,
+    show_delta: bool,
+    snap: bool,
+    indicators: Indicators,
+    /// Whether indicators and scripts also see the forming candle.
+    include_forming: bool,
+    data: Arc<Data>,
     symbol: String,
-    symbol_pub: Sender<String>,
+    symbol_pub: LatestSender<String>,
+    toast_pub: Sender<Toast>,
+    status_pub: Sender<Status>,
+    broadcaster: Broadcaster,
+    budget: CandlesBudget,
+    cache: Cache,
+    notifier: Notifier,
+    confirm_limits: ConfirmLimits,
+    export_options: ExportOptions,
+    /// Timezone pivots and session levels group candles in.
+    timezone: Timezone,
+    /// Offset of the timezone the indicators were last computed in.
+    indicators_utc_offset: i32,
+    download_config: DownloadConfig,
+    influx: Influx,
+    webhook: Webhook,
+    last_used: LastUsed,
+    actions: Actions,
+    download: Option<Download>,
+    /// Message of the last incomplete download, shown in place of the chart when nothing
+    /// could be loaded.
+    load_error: Option<String>,
+    /// Candles count last reported to the budget.
+    reported: usize,
+    /// Whether the last candles are reloaded once the current interval closes.
+    auto_refresh: bool,
+    refresh: Schedule,
+    /// Client of the pages and refreshes, clones share its connections and rate limit.
+    client: Client,
+    /// Repaints the countdowns of the header as seconds pass.
+    repaint: DelayedRepaint,
+    /// Time range of an opened view to show once its candles are loaded.
+    view_range: Option<Bounds>,
+    pasted_view: String,
+    views: Views,
+    /// Visible range for which nothing was left to evict.
+    evicted_up_to: Option<Bounds>,
 
     pub time_range_window: Box<dyn AppWindow>,
+    scripts_window: Scripts,
+    expressions_window: Expressions,
+    backtest_window: Backtest,
+    quality_window: DataQuality,
+    stats_window: Statistics,
+    journal_window: Journal,
+    correlation_window: Correlation,
+    portfolio_window: Portfolio,
 
     klines: Vec<Kline>,
     state: State,
     export_state: ExportState,
-    klines_promise: Option<Promise<Result<Vec<Kline>, ClientError>>>,
-    symbol_sub: Receiver<String>,
+    /// Indicators listed in the export options, shared with the time range window.
+    export_indicators: IndicatorColumns,
+    /// Format picked in the export options, shared with the time range window.
+    chosen_format: ChosenFormat,
+    /// Pages being downloaded.
+    page_promises: Vec<(Bounds, Promise<Result<Vec<Kline>>>)>,
+    refresh_promise: Option<Promise<Result<Vec<Kline>>>>,
+    symbol_sub: Receiver<Selection>,
     props_sub: Receiver<Props>,
-    props_pub: Sender<Props>,
-    export_sub: Receiver<Props>,
+    props_pub: LatestSender<Props>,
+    /// Props of downloads which failed, the chooser shows them again when asked.
+    failed_pub: LatestSender<Props>,
+    export_sub: Receiver<(Props, Format)>,
     drag_sub: Receiver<Bounds>,
+    jump_pub: Sender<Jump>,
+    jump_sub: Receiver<Jump>,
+    /// Whether the window of the graph is to be brought to front.
+    focus_requested: bool,
 }
 
 impl Default for Graph {
     fn default() -> Self {
-        let (s_symbols, r_symbols) = unbounded();
-        let (s_props, r_props) = unbounded();
-        let (s_props1, r_props1) = unbounded();
-        let (s_export, r_export) = unbounded();
-        let (_, r_bounds) = unbounded();
+        let (s_symbols, r_symbols) = channels::latest();
+        let (s_props, r_props) = channels::latest();
+        let (s_props1, r_props1) = channels::latest();
+        let (s_failed, r_failed) = channels::latest();
+        let (s_export, r_export) = channels::with_timeout(EXPORT_QUEUE, EXPORT_SEND_TIMEOUT);
+        let export_indicators = IndicatorColumns::default();
+        let chosen_format = ChosenFormat::default();
+        let (_, r_bounds) = channels::latest();
+        let (s_jump, r_jump) = unbounded();
+        let (s_toasts, _) = unbounded();
+        let (s_status, _) = unbounded();
 
         Self {
             symbol_pub: s_symbols,
+            scripts_window: Scripts::new(false, s_toasts.clone()),
+            expressions_window: Expressions::new(false),
+            backtest_window: Backtest::new(false, s_toasts.clone()),
+            quality_window: DataQuality::new(false),
+            stats_window: Statistics::new(false),
+            journal_window: Journal::new(false, Default::default(), s_toasts.clone()),
+            correlation_window: Correlation::new(false),
+            portfolio_window: Portfolio::new(false),
+            toast_pub: s_toasts,
+            status_pub: s_status,
+            broadcaster: Default::default(),
+            budget: Default::default(),
+            cache: Default::default(),
+            notifier: Default::default(),
+            confirm_limits: Default::default(),
+            export_options: Default::default(),
+            timezone: Default::default(),
+            indicators_utc_offset: 0,
+            download_config: Default::default(),
+            influx: Default::default(),
+            webhook: Default::default(),
+            last_used: Default::default(),
+            actions: Default::default(),
+            download: None,
+            load_error: None,
+            reported: 0,
+            auto_refresh: false,
+            refresh: Default::default(),
+            client: Default::default(),
+            repaint: Default::default(),
+            view_range: None,
+            pasted_view: Default::default(),
+            views: Default::default(),
+            evicted_up_to: None,
             time_range_window: Box::new(TimeRangeChooser::new(
                 false,
-                r_symbols.clone(),
+                r_symbols,
                 s_props,
                 r_props1,
+                r_failed,
                 s_export,
+                export_indicators.clone(),
+                chosen_format.clone(),
+                Default::default(),
+                Default::default(),
                 Props::default(),
             )),
 
-            symbol_sub: r_symbols,
+            symbol_sub: channels::latest().1,
             props_sub: r_props,
             props_pub: s_props1,
+            failed_pub: s_failed,
             export_sub: r_export,
+            export_indicators: export_indicators.clone(),
+            chosen_format,
             drag_sub: r_bounds,
+            jump_pub: s_jump,
+            jump_sub: r_jump,
+            focus_requested: false,
 
             symbol: Default::default(),
             candles: Default::default(),
             volume: Default::default(),
+            indicator_panel: Default::default(),
+            delta_panel: Default::default(),
+            study_panels: Default::default(),
+            headers: Default::default(),
+            minimap: Default::default(),
+            pending_range: None,
+            split: false,
+            fullscreen: false,
+            context_pane: Default::default(),
+            notes: Default::default(),
+            note_texts: false,
+            events: Default::default(),
+            events_path: "events.csv".to_string(),
+            import_path: Default::default(),
+            imported: None,
+            import_manifest: None,
+            import_interval_unknown: false,
+            merge_offer: None,
+            replay: None,
+            replay_ts: Default::default(),
+            as_of: None,
+            as_of_date: Utc::now().date_naive(),
+            as_of_time: TimeInput::new(0, 0, 0),
+            renko: false,
+            brick_size: Default::default(),
+            compress: false,
+            axes_group: LinkedAxisGroup::new(true, false),
+            plots_rect: Rect::NOTHING,
+            show_delta: false,
+            snap: false,
+            indicators: Default::default(),
+            include_forming: false,
+            data: Default::default(),
 
             klines: Default::default(),
             state: Default::default(),
-            klines_promise: Default::default(),
+            page_promises: Default::default(),
+            refresh_promise: Default::default(),
             export_state: Default::default(),
         }
     }
 }
 
 impl Graph {
-    pub fn new(symbol_chan: Receiver<String>) -> Self {
-        let (s_symbols, r_symbols) = unbounded();
-        let (s_props, r_props) = unbounded();
-        let (s_props1, r_props1) = unbounded();
-        let (s_export, r_export) = unbounded();
-        let (s_bounds, r_bounds) = unbounded();
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        symbol_chan: Receiver<Selection>,
+        toast_pub: Sender<Toast>,
+        status_pub: Sender<Status>,
+        broadcaster: Broadcaster,
+        budget: CandlesBudget,
+        cache: Cache,
+        notifier: Notifier,
+        confirm_limits: ConfirmLimits,
+        export_options: ExportOptions,
+        timezone: Timezone,
+        download_config: DownloadConfig,
+        influx: Influx,
+        webhook: Webhook,
+        last_used: LastUsed,
+        band: CandlesBand,
+        journal: JournalConfig,
+        actions: Actions,
+    ) -> Self {
+        Graph::register_actions(&actions);
+        let (s_symbols, r_symbols) = channels::latest();
+        let (s_props, r_props) = channels::latest();
+        let (s_props1, r_props1) = channels::latest();
+        let (s_failed, r_failed) = channels::latest();
+        let (s_export, r_export) = channels::with_timeout(EXPORT_QUEUE, EXPORT_SEND_TIMEOUT);
+        let export_indicators = IndicatorColumns::default();
+        let chosen_format = ChosenFormat::default();
+        let (s_bounds, r_bounds) = channels::latest();
 
         let axes_group = LinkedAxisGroup::new(true, false);
 
         Self {
             symbol_sub: symbol_chan,
             symbol_pub: s_symbols,
+            scripts_window: Scripts::new(false, toast_pub.clone()),
+            backtest_window: Backtest::new(false, toast_pub.clone()),
+            quality_window: DataQuality::new(false),
+            stats_window: Statistics::new(false),
+            journal_window: Journal::new(false, journal, toast_pub.clone()),
+            correlation_window: Correlation::new(false),
+            portfolio_window: Portfolio::new(false),
+            toast_pub,
+            status_pub,
+            broadcaster,
+            budget,
+            cache,
+            notifier,
+            confirm_limits,
+            export_options,
+            indicators_utc_offset: timezone.utc_offset(),
+            timezone,
+            download_config,
+            influx,
+            webhook,
+            last_used,
+            actions: actions.clone(),
             props_sub: r_props,
             props_pub: s_props1,
+            failed_pub: s_failed,
             export_sub: r_export,
+            export_indicators: export_indicators.clone(),
+            chosen_format: chosen_format.clone(),
             drag_sub: r_bounds,
             time_range_window: Box::new(TimeRangeChooser::new(
                 false,
                 r_symbols,
                 s_props,
                 r_props1,
+                r_failed,
                 s_export,
+                export_indicators.clone(),
+                chosen_format.clone(),
+                band,
+                actions,
                 Props::default(),
             )),
             candles: Candles::new(axes_group.clone(), s_bounds),
-            volume: Volume::new(axes_group),
+            volume: Volume::new(axes_group.clone()),
+            indicator_panel: IndicatorPanel::new(axes_group.clone()),
+            delta_panel: DeltaPanel::new(axes_group.clone()),
+            axes_group,
             ..Default::default()
         }
     }
 
-    fn draw(&mut self, ui: &Ui) {
+    fn draw(&mut self, ctx: &Context) {
         info!("drawing data...");
-        let data = Data::new(self.klines.clone());
-        self.volume.set_data(data.clone());
-        self.candles.set_data(data);
-        ui.ctx().request_repaint();
+        self.track_memory();
+        let mut data = Data::new(self.klines.clone());
+        if let Some(ts) = self.as_of {
+            data = data.as_of(ts);
+        }
+        if let Some(replay) = &self.replay {
+            data = data.up_to(replay.cursor());
+        }
+        let data = Arc::new(data);
+        let (shown, axis) = self.chart_data(&data);
+        if &axis != self.candles.axis() {
+            // keep the same time in view as indices shift with loaded data
+            let (_, [from, to]) = self.candles.x_view();
+            let prev = self.candles.axis();
+            if from < to {
+                self.pending_range = Some([from, to].map(|x| axis.to_x(prev.to_ts(x))));
+            }
+        }
+        let interval = Some(self.state.props.interval).filter(|_| !self.import_interval_unknown);
+        self.candles.set_axis(axis.clone());
+        self.candles.set_interval(interval);
+        self.volume.set_axis(axis.clone());
+        self.volume.set_interval(interval);
+        self.delta_panel.set_axis(axis.clone());
+        self.indicator_panel.set_axis(axis);
+        self.volume.set_data(shown.clone());
+        self.candles.set_data(shown.clone());
+        self.delta_panel.set_data(&shown);
+        self.minimap.set_data(&shown);
+        if self.split {
+            self.context_pane.set_klines(&data.vals);
+        }
+        self.data = data;
+        self.apply_events();
+        let indicators_data = self.indicators_data();
+        self.scripts_window.set_data(indicators_data.clone());
+        self.expressions_window.set_data(indicators_data.clone());
+        self.backtest_window.set_data(indicators_data);
+        self.quality_window
+            .set_data(&self.klines, self.state.bounds());
+        self.stats_window.set_data(&self.klines);
+        self.apply_indicators();
+        self.status(Status::Data {
+            interval: self.state.props.interval,
+            range: self
+                .klines
+                .iter()
+                .map(|k| Bounds(k.t_open, k.t_close))
+                .reduce(|a, b| Bounds(a.0.min(b.0), a.1.max(b.1))),
+            candles: self.klines.len(),
+        });
+        ctx.request_repaint();
     }
 
-    fn start_download(&mut self, props: Props, reset_state: bool) {
-        if reset_state {
-            self.klines = vec![];
-            self.state = State::default();
+    /// Candles shown on the chart with what their x stands for, bricks in renko mode.
+    fn chart_data(&self, data: &Arc<Data>) -> (Arc<Data>, Axis) {
+        if !self.renko {
+            return match self.compress {
+                true => {
+                    let (data, axis) = Axis::compress(data);
+                    (Arc::new(data), axis)
+                }
+                false => (data.clone(), Axis::Time),
+            };
         }
 
-        self.state.apply_props(&props);
+        let bricks = match self.brick_size.resolve(data) {
+            Some(size) => renko::bricks(&data.vals, size),
+            None => vec![],
+        };
+        let ranges = bricks.iter().map(|b| [b.t_open, b.t_close]).collect();
 
-        if self.state.loading.pages.len() == 0 {
-            info!("data already downloaded, skipping download");
-            return;
+        (
+            Arc::new(Data::new(renko::to_klines(&bricks))),
+            Axis::Index(Arc::new(ranges)),
+        )
+    }
+
+    /// Brick size controls, returns whether they changed.
+    fn renko_ui(&mut self, ui: &mut Ui) -> bool {
+        let mut atr = matches!(self.brick_size, BrickSize::Atr(_));
+        let mut changed = false;
+        if ui.selectable_value(&mut atr, true, "ATR").clicked() {
+            self.brick_size = BrickSize::default();
+            changed = true;
+        }
+        if ui.selectable_value(&mut atr, false, "absolute").clicked() {
+            let size = BrickSize::default().resolve(&self.data).unwrap_or(1.0);
+            self.brick_size = BrickSize::Absolute(size);
+            changed = true;
         }
+        changed |= match &mut self.brick_size {
+            BrickSize::Atr(period) => ui
+                .add(
+                    DragValue::new(period)
+                        .clamp_range(1..=1000)
+                        .prefix("period: "),
+                )
+                .changed(),
+            BrickSize::Absolute(size) => {
+                let speed = *size * 0.01;
+                ui.add(
+                    DragValue::new(size)
+                        .clamp_range(f64::MIN_POSITIVE..=f64::MAX)
+                        .speed(speed)
+                        .prefix("brick: "),
+                )
+                .changed()
+            }
+        };
 
-        info!("starting data download...");
+        changed
+    }
 
-        self.perform_data_request();
+    fn track_memory(&mut self) {
+        self.budget.track(self.reported, self.klines.len());
+        self.reported = self.klines.len();
+        self.evicted_up_to = None;
     }
 
-    fn perform_data_request(&mut self) {
-        let start_time = self.state.loading.left_edge();
-        let symbol = self.symbol.to_string();
-        let interval = self.state.props.interval.clone();
-        let limit = self.state.loading.pages.page_size();
+    /// Drops candles out of view when the budget is exceeded, they are downloaded again
+    /// once dragged back into view.
+    fn evict(&mut self, ui: &Ui) {
+        let excess = self.budget.excess();
+        if excess == 0
+            || !self.page_promises.is_empty()
+            || self.export_state.triggered
+            || self.replay.is_some()
+            || self.renko
+        {
+            return;
+        }
 
-        debug!("performing request with left edge: {start_time}");
+        let visible = self.candles.visible();
+        if self.evicted_up_to == Some(visible) {
+            return;
+        }
 
-        self.klines_promise = Some(Promise::spawn_async(async move {
-            Client::kline(symbol, interval, start_time, limit).await
-        }));
+        let evicted = memory::evict(&mut self.klines, &self.views, visible, excess);
+        if evicted.is_empty() {
+            self.evicted_up_to = Some(visible);
+            return;
+        }
+
+        let dropped = self.reported - self.klines.len();
+        info!("evicted {dropped} candles of {}: {evicted:?}", self.symbol);
+        self.state.evict(&evicted);
+        self.toast(Toast::info(format!(
+            "memory cap of {} candles exceeded, dropped {dropped} {} candles out of view: {}",
+            self.budget.cap(),
+            self.symbol,
+            evicted
+                .vals()
+                .iter()
+                .map(|b| format!(
+                    "{} - {}",
+                    Data::format_ts(b.0 as f64),
+                    Data::format_ts(b.1 as f64)
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+        self.draw(ui.ctx());
+    }
+
+    /// Ids of enabled indicators.
+    pub fn enabled_indicators(&self) -> Vec<String> {
+        self.indicators.enabled_ids()
     }
 
-    fn handle_events(&mut self) {
-        let drag_wrapped = self.drag_sub.recv_timeout(Duration::from_millis(1));
+    pub fn set_enabled_indicators(&mut self, ids: &[String]) {
+        self.indicators.set_enabled_ids(ids);
+        self.apply_indicators();
+    }
 
-        match drag_wrapped {
-            Ok(bounds) => {
-                info!("got bounds: {bounds:?}");
+    /// X range the wheel input over the plots moves them to.
+    fn wheel_range(&self, ui: &Ui) -> Option<[f64; 2]> {
+        if !ui.rect_contains_pointer(self.plots_rect) {
+            return None;
+        }
 
-                let mut props = self.state.props.clone();
+        let (rect, range) = self.candles.x_view();
+        let input = ui.input();
+        let pos = input.pointer.hover_pos()?;
+        let anchor =
+            range[0] + ((pos.x - rect.left()) / rect.width()) as f64 * (range[1] - range[0]);
 
-                let dt_left = NaiveDateTime::from_timestamp((bounds.0 as f64 / 1000.0) as i64, 0);
-                props.bounds = BoundsSet::new(vec![bounds]);
-                props.date_start = Date::from_utc(dt_left.date(), Utc);
-                props.time_start = dt_left.time();
+        x_range::wheel(
+            range,
+            anchor,
+            rect.width(),
+            input.scroll_delta,
+            input.zoom_delta(),
+            input.modifiers,
+        )
+    }
 
-                let dt_right = NaiveDateTime::from_timestamp((bounds.1 as f64 / 1000.0) as i64, 0);
-                props.bounds = BoundsSet::new(vec![bounds]);
-                props.date_end = Date::from_utc(dt_right.date(), Utc);
-                props.time_end = dt_right.time();
+    fn set_axes_group(&mut self, axes_group: LinkedAxisGroup) {
+        self.candles.set_axes_group(axes_group.clone());
+        self.volume.set_axes_group(axes_group.clone());
+        self.indicator_panel.set_axes_group(axes_group.clone());
+        self.delta_panel.set_axes_group(axes_group.clone());
+        self.study_panels
+            .iter_mut()
+            .for_each(|p| p.set_axes_group(axes_group.clone()));
+        self.axes_group = axes_group;
+    }
 
-                let send_result = self.props_pub.send(props.clone());
-                match send_result {
-                    Ok(_) => {
-                        info!("sent props: {props:?}");
+    /// Main panes, all linked by x.
+    fn panes_ui(&mut self, ui: &mut Ui, next_range: Option<[f64; 2]>) {
+        self.plots_rect = ui.max_rect();
+        let show_indicators = !self.indicator_panel.is_empty();
+        let show_delta = self.show_delta && !self.renko;
+        let sub_panels = show_indicators as usize + show_delta as usize + self.study_panels.len();
+        StripBuilder::new(ui)
+            .size(Size::relative((0.8 - 0.2 * sub_panels as f32).max(0.2)))
+            .size(Size::remainder())
+            .size(Size::exact(MINIMAP_HEIGHT))
+            .sizes(Size::remainder(), sub_panels)
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    ui.add(&mut self.candles);
+                });
+                strip.cell(|ui| {
+                    // candles take the range on the next frame, the rest right away
+                    if let Some(range) = next_range {
+                        self.set_axes_group(LinkedAxisGroup::new(true, false));
+                        x_range::apply(ui, &self.axes_group, range);
+                        self.candles.moved();
                     }
-                    Err(err) => {
-                        error!("failed to send props: {err}");
+                    ui.add(&self.volume);
+                });
+                strip.cell(|ui| {
+                    let (_, view) = self.candles.x_view();
+                    self.pending_range = self.minimap.ui(ui, view);
+                });
+                if show_indicators {
+                    strip.cell(|ui| {
+                        ui.add(&self.indicator_panel);
+                    });
+                }
+                self.study_panels.iter().for_each(|p| {
+                    strip.cell(|ui| {
+                        ui.add(p);
+                    });
+                });
+                if show_delta {
+                    strip.cell(|ui| {
+                        ui.add(&self.delta_panel);
+                    });
+                }
+            });
+    }
+
+    /// Candles and volume filling the ui, the rest of the graph is hidden.
+    fn fullscreen_ui(&mut self, ui: &mut Ui, next_range: Option<[f64; 2]>) {
+        self.plots_rect = ui.max_rect();
+        StripBuilder::new(ui)
+            .size(Size::relative(0.8))
+            .size(Size::remainder())
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    ui.add(&mut self.candles);
+                });
+                strip.cell(|ui| {
+                    if let Some(range) = next_range {
+                        self.set_axes_group(LinkedAxisGroup::new(true, false));
+                        x_range::apply(ui, &self.axes_group, range);
+                        self.candles.moved();
                     }
+                    ui.add(&self.volume);
+                });
+            });
+    }
+
+    /// Shows only the candles and volume plots, the windows of the graph keep their
+    /// visibility for when it is left.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen;
+    }
+
+    pub fn expressions(&self) -> Vec<Definition> {
+        self.expressions_window.definitions()
+    }
+
+    pub fn set_expressions(&mut self, defs: Vec<Definition>) {
+        self.expressions_window.set_definitions(defs);
+        self.apply_indicators();
+    }
+
+    pub fn events(&self) -> &Events {
+        &self.events
+    }
+
+    pub fn set_events(&mut self, events: Events) {
+        self.events = events;
+        self.apply_events();
+    }
+
+    /// Shows events of the shown categories within the data range, the rest are kept for later.
+    fn apply_events(&mut self) {
+        let categories = self.events.categories();
+        let visible: Vec<_> = self
+            .events
+            .visible(self.data.min_x() as i64, self.data.max_x() as i64)
+            .map(|e| {
+                let idx = categories.partition_point(|c| *c < e.category.as_str());
+                (e.clone(), CATEGORY_COLORS[idx % CATEGORY_COLORS.len()])
+            })
+            .collect();
+        self.candles.set_events(visible.clone());
+        self.context_pane.set_events(visible);
+    }
+
+    fn import_events(&mut self) {
+        match events::from_csv(Path::new(&self.events_path)) {
+            Ok(import) => {
+                info!(
+                    "imported {} events from {}, {} bad rows",
+                    import.events.len(),
+                    self.events_path,
+                    import.errors.len()
+                );
+                let mut msg = format!("imported {} events", import.events.len());
+                if !import.errors.is_empty() {
+                    msg = format!(
+                        "{msg}, skipped {} bad rows: {}",
+                        import.errors.len(),
+                        import.errors.join("; ")
+                    );
+                    self.toast(Toast::error(msg));
+                } else {
+                    self.toast(Toast::info(msg));
                 }
+                self.events.add(import.events);
+            }
+            Err(err) => {
+                error!("failed to import events: {}", err.chain());
+                self.toast(Toast::error(err.user_message()));
+            }
+        }
+    }
 
-                self.start_download(props, false);
+    /// Import controls and the legend of categories with their toggles.
+    fn events_ui(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.events_path).desired_width(150.0));
+            if ui
+                .button("import")
+                .on_hover_text("csv with timestamp, label and category columns")
+                .clicked()
+            {
+                self.import_events();
+                changed = true;
+            }
+        });
+        if self.events.is_empty() {
+            return changed;
+        }
+
+        ui.separator();
+        let categories: Vec<String> = self
+            .events
+            .categories()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        categories.iter().enumerate().for_each(|(idx, c)| {
+            let mut shown = self.events.is_shown(c);
+            let text = RichText::new(c).color(CATEGORY_COLORS[idx % CATEGORY_COLORS.len()]);
+            if ui.checkbox(&mut shown, text).changed() {
+                self.events.set_shown(c, shown);
+                changed = true;
             }
-            Err(_) => {}
+        });
+        ui.separator();
+        if ui.button("clear").clicked() {
+            self.events.clear();
+            changed = true;
         }
 
-        let export_wrapped = self.export_sub.recv_timeout(Duration::from_millis(1));
+        changed
+    }
 
-        match export_wrapped {
-            Ok(props) => {
-                info!("got props for export: {props:?}");
+    fn start_replay(&mut self, ui: &Ui) {
+        let data = Data::new(self.klines.clone());
+        let ts = match self.replay_ts.trim().is_empty() {
+            true => data.min_x() as i64,
+            false => match replay::parse_ts(&self.replay_ts) {
+                Some(ts) => ts,
+                None => {
+                    self.toast(Toast::error(format!(
+                        "failed to parse replay start {}, expected YYYY-MM-DD HH:MM:SS",
+                        self.replay_ts
+                    )));
+                    return;
+                }
+            },
+        };
 
-                self.export_state.triggered = true;
+        info!("starting replay of {} at {ts}", self.symbol);
+        self.replay = Some(Replay::new(&data, ts));
+        self.draw(ui.ctx());
+    }
 
-                self.start_download(props, true);
-            }
-            Err(_) => {}
+    fn stop_replay(&mut self, ui: &Ui) {
+        if self.replay.take().is_some() {
+            info!("stopped replay of {}", self.symbol);
+            self.draw(ui.ctx());
+        }
+    }
+
+    /// Start time input of a replay, returns whether it was started.
+    fn replay_start_ui(&mut self, ui: &mut Ui) -> bool {
+        let loaded = self.state.loading.progress() == 1.0 && !self.klines.is_empty();
+        ui.add(
+            TextEdit::singleline(&mut self.replay_ts)
+                .hint_text("YYYY-MM-DD HH:MM:SS")
+                .desired_width(150.0),
+        );
+        let started = ui
+            .add_enabled(loaded, Button::new("start"))
+            .on_hover_text("step through the loaded candles from the entered time or the first one")
+            .clicked();
+        if started {
+            ui.close_menu();
+        }
+
+        started
+    }
+
+    /// Picker of the time the chart is shown as of, returns it once applied.
+    fn as_of_ui(&mut self, ui: &mut Ui) -> Option<i64> {
+        ui.horizontal(|ui| {
+            TimeRangeChooser::date_picker(ui, &mut self.as_of_date, "as_of_date");
+            ui.add(&mut self.as_of_time);
+        });
+        let time = self.as_of_time.get_time();
+        let applied = ui
+            .add_enabled(time.is_some(), Button::new("apply"))
+            .on_hover_text("hide the candles opened later, indicators are computed without them")
+            .clicked();
+        if applied {
+            ui.close_menu();
         }
 
-        let symbol_wrapped = self.symbol_sub.recv_timeout(Duration::from_millis(1));
+        time.filter(|_| applied)
+            .map(|t| self.as_of_date.and_time(t).and_utc().timestamp_millis())
+    }
 
-        match symbol_wrapped {
-            Ok(symbol) => {
-                info!("got symbol: {symbol}");
+    fn set_as_of(&mut self, ctx: &Context, as_of: Option<i64>) {
+        match as_of {
+            Some(ts) => info!("showing {} as of {ts}", self.symbol),
+            None => info!("showing {} up to now", self.symbol),
+        }
+        self.as_of = as_of;
+        self.draw(ctx);
+    }
 
-                self.symbol = symbol.clone();
-                self.symbol_pub.send(symbol).unwrap();
+    /// Controls of the running replay, returns whether the shown candles changed.
+    fn replay_ui(&mut self, ui: &mut Ui) -> bool {
+        let Some(replay) = &mut self.replay else {
+            return false;
+        };
 
-                self.start_download(Props::default(), true);
+        let mut moved = ui.button("⏴").on_hover_text("step back").clicked() && replay.step_back();
+        match replay.is_playing() {
+            true => {
+                if ui.button("⏸").on_hover_text("pause").clicked() {
+                    replay.pause();
+                }
+            }
+            false => {
+                if ui.button("⏵").on_hover_text("play").clicked() {
+                    replay.play();
+                }
+            }
+        }
+        moved |= ui.button("⏵|").on_hover_text("step forward").clicked() && replay.step_forward();
+        ui.add(
+            DragValue::new(&mut replay.speed)
+                .clamp_range(0.1..=50.0)
+                .speed(0.1)
+                .suffix(" candles/s"),
+        );
+        ui.add(
+            TextEdit::singleline(&mut self.replay_ts)
+                .hint_text("YYYY-MM-DD HH:MM:SS")
+                .desired_width(150.0),
+        );
+        if ui.button("jump").clicked() {
+            match replay::parse_ts(&self.replay_ts) {
+                Some(ts) => {
+                    replay.jump_to(&Data::new(self.klines.clone()), ts);
+                    moved = true;
+                }
+                None => self.toast(Toast::error(format!(
+                    "failed to parse replay time {}, expected YYYY-MM-DD HH:MM:SS",
+                    self.replay_ts
+                ))),
             }
-            Err(_) => {}
         }
 
-        let show_wrapped = self.props_sub.recv_timeout(Duration::from_millis(1));
+        moved
+    }
+
+    pub fn notes(&self) -> &Notes {
+        &self.notes
+    }
+
+    pub fn portfolio(&self) -> &portfolio::Portfolio {
+        self.portfolio_window.portfolio()
+    }
+
+    pub fn set_portfolio(&mut self, portfolio: portfolio::Portfolio) {
+        self.portfolio_window.set_portfolio(portfolio);
+    }
+
+    pub fn set_notes(&mut self, notes: Notes) {
+        self.notes = notes;
+        self.journal_window.set_notes(&self.notes);
+        self.show_notes();
+    }
+
+    /// Shows notes of the current symbol in both candle panes.
+    fn show_notes(&mut self) {
+        let notes = self.notes.get(&self.symbol).to_vec();
+        self.candles.set_notes(notes.clone());
+        self.context_pane.set_notes(notes);
+    }
 
-        match show_wrapped {
-            Ok(props) => {
-                info!("got show button pressed: {props:?}");
+    /// Keeps notes edited in either of the panes.
+    fn sync_notes(&mut self) {
+        let mut changed = false;
+        if self.candles.take_notes_changed() {
+            let notes = self.candles.notes().to_vec();
+            self.context_pane.set_notes(notes.clone());
+            self.notes.set(&self.symbol, notes);
+            changed = true;
+        }
+        if self.context_pane.take_notes_changed() {
+            let notes = self.context_pane.notes().to_vec();
+            self.candles.set_notes(notes.clone());
+            self.notes.set(&self.symbol, notes);
+            changed = true;
+        }
+        if changed {
+            self.journal_window.set_notes(&self.notes);
+        }
+    }
 
-                self.start_download(props, true);
+    fn export_notes(&self, extension: &str) {
+        let notes = self.notes.get(&self.symbol);
+        let path = notes::file_name(&self.symbol, extension);
+        let res = match extension {
+            "csv" => notes::to_csv(Path::new(&path), notes),
+            _ => notes::to_json(Path::new(&path), notes),
+        };
+        match res {
+            Ok(abs_path) => {
+                info!("exported notes to file: {abs_path:?}");
+                self.toast(Toast::info(format!(
+                    "exported {} notes to {}",
+                    notes.len(),
+                    abs_path.display()
+                )));
+            }
+            Err(err) => {
+                error!("failed to export notes: {}", err.chain());
+                self.toast(Toast::error(err.user_message()));
             }
-            Err(_) => {}
         }
     }
-}
 
-impl Widget for &mut Graph {
-    fn ui(self, ui: &mut Ui) -> Response {
-        self.handle_events();
+    fn indicators_data(&self) -> Arc<Data> {
+        match self.include_forming {
+            true => self.data.clone(),
+            false => Arc::new(self.data.closed()),
+        }
+    }
 
-        if self.symbol == "" {
-            return ui.label("Select a symbol");
+    fn apply_indicators(&mut self) {
+        self.indicators_utc_offset = self.timezone.utc_offset();
+        self.indicators.set_utc_offset(self.indicators_utc_offset);
+        self.indicators.compute(&self.indicators_data());
+        self.export_indicators
+            .set_listed(self.indicators.enabled_names());
+
+        let mut output = IndicatorOutput::default();
+        if !self.renko {
+            self.indicators
+                .outputs()
+                .chain(self.scripts_window.outputs())
+                .chain(self.expressions_window.outputs())
+                .chain(self.backtest_window.outputs())
+                .chain(self.correlation_window.outputs())
+                .chain(self.portfolio_window.outputs())
+                .for_each(|o| output.extend(o));
         }
 
-        if let Some(promise) = &self.klines_promise {
-            if let Some(res) = promise.ready() {
-                match res {
-                    Ok(data) => {
-                        data.iter().for_each(|k| {
-                            self.klines.push(k.clone());
-                        });
+        let axis = self.candles.axis().clone();
+        let step = self.data.step();
+        output.map_x(|x| axis.slot_x(x, step));
+        self.indicator_panel.set_indicators(&output);
+        self.volume.set_highlights(&output.volume_highlights);
+        self.study_panels = output
+            .sub_panels
+            .iter()
+            .map(|p| StudyPanel::new(p.clone(), self.axes_group.clone(), axis.clone()))
+            .collect();
+        self.headers = output.headers.clone();
+        self.candles.set_indicators(output);
+    }
 
-                        if let Some(_) = self.state.loading.turn_page() {
-                            self.perform_data_request();
-                        } else {
-                            self.klines_promise = None;
-                        }
+    fn start_download(&mut self, props: Props, reset_state: bool) {
+        if reset_state {
+            self.last_used.remember(&self.symbol, &props);
+            self.correlation_window.set_props(&props);
+            self.imported = None;
+            self.import_manifest = None;
+            self.import_interval_unknown = false;
+            self.replay = None;
+            self.klines = vec![];
+            self.state = State::default();
+            self.views = Views::default();
+            self.track_memory();
+        }
 
-                        self.draw(ui);
-                    }
-                    Err(err) => {
-                        error!("failed to get klines data: {err}");
-                        self.state.report_loading_error();
-                        self.klines_promise = None;
+        self.state.apply_props(&props);
+
+        if self.state.loading.pages.is_empty() {
+            info!("data already downloaded, skipping download");
+            return;
+        }
+
+        info!("starting data download...");
+
+        self.load_error = None;
+        self.download = Some(Download {
+            started: Instant::now(),
+            reset: reset_state,
+            base: self.klines.len(),
+            error: None,
+        });
+        self.request_pages();
+    }
+
+    /// Requests pages until the set concurrency is in flight. Pages share the client, which
+    /// waits for the request weight of the exchange to allow them.
+    fn request_pages(&mut self) {
+        while self.state.loading.in_flight() < self.download_config.concurrency() {
+            let Some((page, limit)) = self.state.loading.take_page() else {
+                return;
+            };
+            let symbol = self.symbol.to_string();
+            let props = self.state.props.clone();
+            let cache = self.cache.clone();
+            let client = self.client.clone();
+
+            debug!("performing request with left edge: {}", page.0);
+
+            let promise = Promise::spawn_async(async move {
+                download::page(&client, Some(&cache), &symbol, &props, page, limit).await
+            });
+            self.page_promises.push((page, promise));
+        }
+    }
+
+    /// Merges the pages which finished loading and requests the next ones, a failed page
+    /// doesn't stop the others.
+    fn poll_pages(&mut self, ui: &Ui) {
+        if self.page_promises.is_empty() {
+            return;
+        }
+
+        let mut loaded = false;
+        for (page, promise) in std::mem::take(&mut self.page_promises) {
+            match promise.try_take() {
+                Err(promise) => self.page_promises.push((page, promise)),
+                Ok(Ok(data)) => {
+                    download::insert_page(&mut self.klines, &data);
+                    self.state.loading.page_loaded();
+                    self.status(Status::Fetched(Utc::now().timestamp_millis()));
+                    loaded = true;
+                }
+                Ok(Err(err)) => {
+                    error!("failed to get klines of page {page:?}: {}", err.chain());
+                    self.status(Status::Failed(
+                        Utc::now().timestamp_millis(),
+                        err.user_message(),
+                    ));
+                    self.state.report_loading_error(page);
+                    if let Some(download) = &mut self.download {
+                        download.error = Some(err.user_message());
                     }
                 }
             }
         }
 
-        self.candles
-            .set_enabled(self.state.loading.progress() == 1.0);
-        self.volume
-            .set_enabled(self.state.loading.progress() == 1.0);
+        self.request_pages();
+        if loaded {
+            self.draw(ui.ctx());
+        }
+        if self.page_promises.is_empty() {
+            self.finish_download();
+        }
+    }
 
-        if self.state.loading.progress() == 1.0 && self.export_state.triggered {
-            info!("exporting data...");
-
-            let name = format!(
-                "{}_{}_{}_{:?}.csv",
-                self.symbol,
-                self.state.props.start_time().timestamp(),
-                self.state.props.end_time().timestamp(),
-                self.state.props.interval,
-            );
+    /// Checks the merged candles cover the request. Failed ranges are forgotten to be
+    /// downloaded again once requested.
+    fn finish_download(&mut self) {
+        let failed = self.state.loading.failed();
+        if let Some(failed) = &failed {
+            self.state.evict(failed);
+        }
+        let missing = self.state.props.bounds.subtract(self.state.bounds());
+        let error = self.download.as_mut().and_then(|d| d.error.take());
+        if let Some(missing) = &missing {
+            error!("download incomplete, missing ranges: {missing:?}");
+            self.toast(Toast::error(format!(
+                "failed to download {} of {} pages: {}",
+                failed.map_or(0, |f| f.len()),
+                self.state.loading.pages.len(),
+                error.clone().unwrap_or_default(),
+            )));
+            self.load_error = error.clone();
+            self.failed_pub.send(self.state.props.clone());
+            if self.export_state.triggered {
+                self.export_state.triggered = false;
+                self.toast(Toast::info("export cancelled, request it again to retry"));
+            }
+        }
 
-            let path = Path::new(&name);
-            let f_res = File::create(&path);
-            match f_res {
-                Ok(f) => {
-                    let abs_path = path.canonicalize().unwrap();
-                    info!("Saving to file: {abs_path:?}");
+        self.broadcaster.publish(&Message::Range {
+            symbol: self.symbol.clone(),
+            interval: self.state.props.interval.as_str().to_string(),
+            start: self.state.props.start_time().timestamp_millis(),
+            end: self.state.props.end_time().timestamp_millis(),
+            candles: self.klines.len(),
+        });
+        self.webhook.send(&Payload::Fetch {
+            symbol: self.symbol.clone(),
+            interval: self.state.props.interval.as_str().to_string(),
+            start: self.state.props.start_time().timestamp_millis(),
+            end: self.state.props.end_time().timestamp_millis(),
+            candles: self.klines.len(),
+            error: missing.as_ref().and(error.clone()),
+        });
+        if let Some(range) = self.view_range.take() {
+            let axis = self.candles.axis();
+            self.pending_range = Some([range.0, range.1].map(|ts| axis.to_x(ts as f64)));
+        }
 
-                    let mut wtr = csv::Writer::from_writer(f);
-                    self.klines.iter().for_each(|el| {
-                        wtr.serialize(el).unwrap();
-                    });
-                    if let Some(err) = wtr.flush().err() {
-                        error!("failed to write to file with error: {err}");
-                    } else {
-                        info!("exported to file: {abs_path:?}");
-                        self.export_state.triggered = false;
+        // an export notifies once its file is written
+        let Some(download) = self.download.take() else {
+            return;
+        };
+        if self.export_state.triggered {
+            return;
+        }
+        if missing.is_none() && download.reset && download.started.elapsed() >= LOADED_TOAST_AFTER {
+            self.toast_loaded();
+        }
+        self.notifier.finished(
+            Finished {
+                task: "download",
+                symbol: self.symbol.clone(),
+                range: self.state.props.bounds.hull().unwrap_or_default(),
+                rows: self.klines.len().saturating_sub(download.base),
+                error: missing.and(error),
+            },
+            download.started.elapsed(),
+        );
+    }
+
+    /// Toast of the loaded range with a button showing the chart of it again.
+    fn toast_loaded(&self) {
+        let text = loaded_text(&self.symbol, &self.state.props, self.klines.len());
+        let jump = Jump {
+            symbol: self.symbol.clone(),
+            props: self.state.props.clone(),
+        };
+        let jump_pub = self.jump_pub.clone();
+        self.toast(Toast::info(text).with_button("jump to range", move || {
+            if let Err(err) = jump_pub.send(jump.clone()).context("jump") {
+                error!("{}", Error::from(err).chain());
+            }
+        }));
+    }
+
+    /// Shows the range of the jump fitting the view to it, its chart is loaded again when
+    /// another one is shown meanwhile.
+    fn handle_jumps(&mut self, ctx: &Context) {
+        let Some(jump) = self.jump_sub.try_iter().last() else {
+            return;
+        };
+        info!("jumping to {} {:?}", jump.symbol, jump.props);
+        self.focus_requested = true;
+        let Some(range) = jump.props.bounds.hull() else {
+            return;
+        };
+
+        if jump.symbol == self.symbol && jump.props == self.state.props && self.download.is_none() {
+            let axis = self.candles.axis();
+            self.pending_range = Some([range.0, range.1].map(|ts| axis.to_x(ts as f64)));
+            return;
+        }
+        if jump.symbol != self.symbol {
+            self.clear(ctx);
+        }
+        self.view_range = Some(range);
+        self.open(jump.symbol, jump.props);
+    }
+
+    /// Returns true once after a toast asked to bring the graph to front.
+    pub fn take_focus_request(&mut self) -> bool {
+        std::mem::take(&mut self.focus_requested)
+    }
+
+    /// Starts the export unless it exceeds the limits and waits for confirmation.
+    fn request_export(&mut self, props: Props, format: Format) {
+        if self.export_state.batch.is_some() {
+            self.toast(Toast::info(
+                "checked symbols are being exported, export again once they are written",
+            ));
+            return;
+        }
+        let estimate = export::estimate(&props, format);
+        if self.confirm_limits.exceeded(&estimate) {
+            info!("export of {estimate:?} waits for confirmation");
+            self.export_state.pending = Some((props, format));
+        } else {
+            self.start_export(props, format);
+        }
+    }
+
+    fn start_export(&mut self, props: Props, format: Format) {
+        self.export_state.triggered = true;
+        self.export_state.format = format;
+
+        self.start_download(props, true);
+    }
+
+    /// Summary of the pending export with buttons to start or drop it.
+    fn confirm_export_ui(&mut self, ui: &Ui) {
+        let Some((props, format)) = &self.export_state.pending else {
+            return;
+        };
+
+        let estimate = export::estimate(props, *format);
+        let destination = match self.export_path(props, *format) {
+            Some(path) => std::env::current_dir()
+                .unwrap_or_default()
+                .join(path)
+                .display()
+                .to_string(),
+            None => {
+                let config = self.influx.config();
+                format!("InfluxDB bucket {} at {}", config.bucket, config.url)
+            }
+        };
+        let size = |format| {
+            let mb = export::estimate(props, format).bytes as f64 / (1024.0 * 1024.0);
+            format!("~{mb:.1} MB")
+        };
+        let mut decision = None;
+        Window::new("confirm export")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ui.ctx(), |ui| {
+                Grid::new("confirm_export").num_columns(2).show(ui, |ui| {
+                    ui.label("symbol");
+                    ui.label(&self.symbol);
+                    ui.end_row();
+                    ui.label("interval");
+                    ui.label(props.interval.as_str());
+                    ui.end_row();
+                    ui.label("range");
+                    ui.label(format!(
+                        "{} - {}",
+                        props.start_time().format("%Y-%m-%d %H:%M:%S"),
+                        props.end_time().format("%Y-%m-%d %H:%M:%S")
+                    ));
+                    ui.end_row();
+                    ui.label("rows");
+                    ui.label(format!("~{} in {} requests", estimate.rows, estimate.pages));
+                    ui.end_row();
+                    ui.label("size");
+                    match format {
+                        Format::Csv(_) => {
+                            ui.vertical(|ui| {
+                                [
+                                    (Columns::Basic, "basic columns"),
+                                    (Columns::All, "volumes and trades"),
+                                ]
+                                .into_iter()
+                                .for_each(|(c, name)| {
+                                    let text = format!("{} {name}", size(Format::Csv(c)));
+                                    match Format::Csv(c) == *format {
+                                        true => ui.strong(text),
+                                        false => ui.label(text),
+                                    };
+                                });
+                            });
+                        }
+                        Format::Xlsx { .. } => {
+                            ui.label(format!("{} workbook", size(*format)));
+                        }
+                        _ => {
+                            ui.label(format!("{} line protocol", size(*format)));
+                        }
                     }
-                }
-                Err(err) => {
-                    error!("failed to create file with error: {err}");
-                    self.export_state.triggered = false;
+                    ui.end_row();
+                    if let (Format::Xlsx { .. }, sheets @ 2..) =
+                        (format, export::xlsx_sheets(estimate.rows))
+                    {
+                        ui.label("sheets");
+                        ui.colored_label(
+                            Color32::GOLD,
+                            format!("{sheets}, one per {} candles", export::XLSX_SHEET_ROWS),
+                        );
+                        ui.end_row();
+                    }
+                    ui.label("destination");
+                    ui.label(destination);
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("proceed").clicked() {
+                        decision = Some(true);
+                    }
+                    if ui.button("cancel").clicked() {
+                        decision = Some(false);
+                    }
+                });
+            });
+
+        match decision {
+            Some(true) => {
+                if let Some((props, format)) = self.export_state.pending.take() {
+                    info!("export confirmed");
+                    self.start_export(props, format);
                 }
             }
+            Some(false) => {
+                info!("export cancelled");
+                self.export_state.pending = None;
+            }
+            None => {}
         }
+    }
 
-        TopBottomPanel::top("graph toolbar").show_inside(ui, |ui| {
-            ui.horizontal(|ui| {
-                self.time_range_window.toggle_btn(ui);
-                if self.state.loading.progress() < 1.0 && !self.state.loading.has_error {
-                    ui.add(
-                        ProgressBar::new(self.state.loading.progress())
-                            .show_percentage()
-                            .animate(true),
-                    );
+    /// File an export in the format is written to, none when written to InfluxDB.
+    fn export_path(&self, props: &Props, format: Format) -> Option<PathBuf> {
+        let name = export::file_name(&self.symbol, props, self.export_options.times());
+        format
+            .extension()
+            .map(|ext| PathBuf::from(name).with_extension(ext))
+    }
+
+    /// Writes loaded candles to a file in a thread of its own or to InfluxDB in a task,
+    /// notifying when done.
+    fn export(&mut self) {
+        info!("exporting data...");
+
+        let format = self.export_state.format;
+        let path = self.export_path(&self.state.props, format);
+        let times = self.export_options.times();
+        let symbol = self.symbol.clone();
+        let interval = self.state.props.interval;
+        let klines = self.klines.clone();
+        let manifest = (self.export_options.manifest() && format.manifest()).then(|| {
+            let source = match &self.imported {
+                Some(file) => Source::import(file),
+                None => Source::binance_spot(&Client::default()),
+            };
+            Manifest::new(&symbol, interval, &self.state.props, &klines, source)
+        });
+        let notifier = self.notifier.clone();
+        let started = self
+            .download
+            .as_ref()
+            .map_or_else(Instant::now, |d| d.started);
+        let finished = Finished {
+            task: "export",
+            symbol: self.symbol.clone(),
+            range: self.state.props.bounds.hull().unwrap_or_default(),
+            rows: klines.len(),
+            error: None,
+        };
+        let notify = move |res: &Result<Exported>| {
+            let finished = match res {
+                Ok(Exported::File(_) | Exported::Files(_)) => finished,
+                Ok(Exported::Influx(summary)) => Finished {
+                    rows: summary.points,
+                    error: summary.error.clone(),
+                    ..finished
+                },
+                Err(err) => Finished {
+                    error: Some(err.user_message()),
+                    ..finished
+                },
+            };
+            notifier.finished(finished, started.elapsed());
+        };
+
+        let indicators = match format {
+            Format::Csv(_) | Format::Xlsx { .. } => {
+                let outputs = self.indicators.evaluate(
+                    &Data::new(klines.clone()),
+                    &self.export_indicators.checked(),
+                );
+                export::indicator_columns(&klines, &outputs)
+            }
+            _ => vec![],
+        };
+        self.export_state.promise = Some(match (format, path) {
+            (Format::Csv(columns), Some(path)) => Promise::spawn_thread("export", move || {
+                let res =
+                    export::to_csv_with_indicators(&path, &klines, columns, times, &indicators)
+                        .and_then(|written| Graph::exported_file(written, manifest));
+                notify(&res);
+                res
+            }),
+            (Format::Xlsx { columns, stats }, Some(path)) => {
+                Promise::spawn_thread("export", move || {
+                    let sheet = XlsxSheet {
+                        symbol: &symbol,
+                        klines: &klines,
+                        indicators: &indicators,
+                    };
+                    let res =
+                        export::to_xlsx(&path, &[sheet], columns, times, stats).map(Exported::File);
+                    notify(&res);
+                    res
+                })
+            }
+            (_, Some(path)) => Promise::spawn_thread("export", move || {
+                let res = influx::to_file(&path, &symbol, interval, &klines)
+                    .and_then(|written| Graph::exported_file(written, manifest));
+                notify(&res);
+                res
+            }),
+            (_, None) => {
+                let config = self.influx.config();
+                Promise::spawn_async(async move {
+                    let res = influx::write(&config, &symbol, interval, &klines)
+                        .await
+                        .map(Exported::Influx);
+                    notify(&res);
+                    res
+                })
+            }
+        });
+    }
+
+    /// Downloads the symbols with the props of the chart through the shared client and
+    /// exports them in the chosen format once all are in.
+    pub fn export_symbols(&mut self, symbols: Vec<String>) {
+        let state = &self.export_state;
+        if state.triggered || state.pending.is_some() || state.promise.is_some() {
+            self.toast(Toast::info(
+                "an export is in progress, export again once it is written",
+            ));
+            return;
+        }
+        if state.batch.is_some() {
+            self.toast(Toast::info("checked symbols are being exported"));
+            return;
+        }
+
+        let props = self.state.props.clone();
+        let format = self.chosen_format.get();
+        info!("exporting {} symbols as {}", symbols.len(), format.name());
+        self.toast(Toast::info(format!(
+            "exporting {} symbols as {}",
+            symbols.len(),
+            format.name()
+        )));
+        let client = self.client.clone();
+        let downloaded = props.clone();
+        self.export_state.batch = Some(Batch {
+            props,
+            format,
+            promise: Promise::spawn_async(async move {
+                let mut klines = vec![];
+                for symbol in symbols {
+                    let res = download::download(&client, &symbol, &downloaded).await;
+                    klines.push((symbol, res));
                 }
-            });
+                klines
+            }),
         });
+    }
 
-        CentralPanel::default()
-            .show_inside(ui, |ui| {
-                self.time_range_window.show(ui);
+    /// Writes the downloaded symbols of the batch like [`Graph::export`] does, xlsx as sheets
+    /// of one workbook. Symbols which failed to download are skipped.
+    fn export_batch(
+        &mut self,
+        props: Props,
+        format: Format,
+        downloaded: Vec<(String, Result<Vec<Kline>>)>,
+    ) {
+        let mut loaded = vec![];
+        for (symbol, res) in downloaded {
+            match res {
+                Ok(klines) => loaded.push((symbol, klines)),
+                Err(err) => {
+                    error!("failed to download {symbol} for export: {}", err.chain());
+                    self.toast(Toast::error(format!(
+                        "{symbol} not exported: {}",
+                        err.user_message()
+                    )));
+                }
+            }
+        }
+        if loaded.is_empty() {
+            return;
+        }
 
-                StripBuilder::new(ui)
-                    .size(Size::relative(0.8))
-                    .size(Size::remainder())
-                    .vertical(|mut strip| {
-                        strip.cell(|ui| {
-                            ui.add(&mut self.candles);
+        let times = self.export_options.times();
+        let interval = props.interval;
+        let indicators: Vec<Vec<Column>> = match format {
+            Format::Csv(_) | Format::Xlsx { .. } => {
+                let checked = self.export_indicators.checked();
+                loaded
+                    .iter()
+                    .map(|(_, klines)| {
+                        let outputs = self
+                            .indicators
+                            .evaluate(&Data::new(klines.clone()), &checked);
+                        export::indicator_columns(klines, &outputs)
+                    })
+                    .collect()
+            }
+            _ => vec![vec![]; loaded.len()],
+        };
+        let manifests: Vec<Option<Manifest>> = loaded
+            .iter()
+            .map(|(symbol, klines)| {
+                (self.export_options.manifest() && format.manifest()).then(|| {
+                    let source = Source::binance_spot(&Client::default());
+                    Manifest::new(symbol, interval, &props, klines, source)
+                })
+            })
+            .collect();
+        let notifier = self.notifier.clone();
+        let started = Instant::now();
+        let finished = Finished {
+            task: "export",
+            symbol: loaded
+                .iter()
+                .map(|(symbol, _)| symbol.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            range: props.bounds.hull().unwrap_or_default(),
+            rows: loaded.iter().map(|(_, klines)| klines.len()).sum(),
+            error: None,
+        };
+        let notify = move |res: &Result<Exported>| {
+            let finished = match res {
+                Ok(Exported::Influx(summary)) => Finished {
+                    rows: summary.points,
+                    error: summary.error.clone(),
+                    ..finished
+                },
+                Ok(_) => finished,
+                Err(err) => Finished {
+                    error: Some(err.user_message()),
+                    ..finished
+                },
+            };
+            notifier.finished(finished, started.elapsed());
+        };
+
+        self.export_state.promise = Some(match format {
+            Format::Xlsx { columns, stats } => Promise::spawn_thread("export", move || {
+                let name = format!("{}_symbols", loaded.len());
+                let path =
+                    PathBuf::from(export::file_name(&name, &props, times)).with_extension("xlsx");
+                let sheets: Vec<XlsxSheet> = loaded
+                    .iter()
+                    .zip(&indicators)
+                    .map(|((symbol, klines), indicators)| XlsxSheet {
+                        symbol,
+                        klines,
+                        indicators,
+                    })
+                    .collect();
+                let res =
+                    export::to_xlsx(&path, &sheets, columns, times, stats).map(Exported::File);
+                notify(&res);
+                res
+            }),
+            Format::Influx => {
+                let config = self.influx.config();
+                Promise::spawn_async(async move {
+                    let mut summary = influx::Summary::default();
+                    for (symbol, klines) in &loaded {
+                        let written = influx::write(&config, symbol, interval, klines).await;
+                        let written = match written {
+                            Ok(written) => written,
+                            Err(err) => {
+                                let res = Err(err);
+                                notify(&res);
+                                return res;
+                            }
+                        };
+                        summary.points += written.points;
+                        summary.failed += written.failed;
+                        summary.error = written.error.or(summary.error);
+                    }
+                    let res = Ok(Exported::Influx(summary));
+                    notify(&res);
+                    res
+                })
+            }
+            _ => Promise::spawn_thread("export", move || {
+                let files = loaded.iter().zip(indicators).zip(manifests);
+                let res = files
+                    .map(|(((symbol, klines), indicators), manifest)| {
+                        let path = PathBuf::from(export::file_name(symbol, &props, times))
+                            .with_extension(format.extension().unwrap_or_default());
+                        let written = match format {
+                            Format::Csv(columns) => export::to_csv_with_indicators(
+                                &path,
+                                klines,
+                                columns,
+                                times,
+                                &indicators,
+                            ),
+                            _ => influx::to_file(&path, symbol, interval, klines),
+                        }?;
+                        if let Some(manifest) = manifest {
+                            manifest.of(&written).write(&written.path)?;
+                        }
+                        Ok(written.path)
+                    })
+                    .collect::<Result<Vec<_>>>()
+                    .map(Exported::Files);
+                notify(&res);
+                res
+            }),
+        });
+    }
+
+    /// Export to the written file, with its manifest written next to it when given.
+    fn exported_file(written: Written, manifest: Option<Manifest>) -> Result<Exported> {
+        if let Some(manifest) = manifest {
+            manifest.of(&written).write(&written.path)?;
+        }
+
+        Ok(Exported::File(written.path))
+    }
+
+    /// Shows the symbol downloading candles of the props.
+    pub fn open(&mut self, symbol: String, props: Props) {
+        info!("opening {symbol} with props: {props:?}");
+
+        self.set_symbol(symbol);
+        self.props_pub.send(props.clone());
+        self.start_download(props, true);
+    }
+
+    /// File name of the imported candles while they are shown.
+    pub fn imported(&self) -> Option<&str> {
+        self.imported.as_deref()
+    }
+
+    /// Path input of a candles csv, returns whether it is to be imported.
+    fn file_ui(&mut self, ui: &mut Ui) -> bool {
+        ui.add(
+            TextEdit::singleline(&mut self.import_path)
+                .hint_text("path of a csv")
+                .desired_width(200.0),
+        );
+        let clicked = ui
+            .add_enabled(
+                !self.import_path.trim().is_empty(),
+                Button::new("import csv"),
+            )
+            .on_hover_text(
+                "candles of an export or with t_open, open, high, low, close and volume columns",
+            )
+            .clicked();
+        if clicked {
+            ui.close_menu();
+        }
+
+        clicked
+    }
+
+    /// Opens candles of the csv, merged into the shown ones when of the same symbol and
+    /// interval.
+    pub fn import_file(&mut self, ctx: &Context, path: &Path) {
+        self.import_csv(ctx, path, true);
+    }
+
+    fn import_csv(&mut self, ctx: &Context, path: &Path, merge: bool) {
+        match import::from_csv(path) {
+            Ok(import) => self.open_import(ctx, path, import, merge),
+            Err(err) => {
+                error!("failed to import candles: {}", err.chain());
+                self.toast(Toast::error(err.user_message()));
+            }
+        }
+    }
+
+    /// Target of a dropped file, files named like an export of the shown symbol can be merged.
+    fn drop_target(&self, path: &Path) -> DropTarget {
+        if self.klines.is_empty() && self.page_promises.is_empty() {
+            return DropTarget::Chart;
+        }
+        let symbol = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(import::parse_file_name)
+            .map(|(symbol, _)| symbol);
+        match symbol == Some(self.symbol.clone()) && self.merge_offer.is_none() {
+            true => DropTarget::Merge,
+            false => DropTarget::NewWindow,
+        }
+    }
+
+    /// Opens csv files dropped on the app, in the chart while it is empty and else each in a
+    /// window of its own. A file of the shown symbol is offered to be merged first.
+    fn open_dropped(&mut self, ctx: &Context) {
+        let dropped: Vec<PathBuf> = ctx
+            .input()
+            .raw
+            .dropped_files
+            .iter()
+            .filter_map(|f| f.path.clone())
+            .collect();
+        for path in dropped {
+            info!("dropped file: {path:?}");
+            match self.drop_target(&path) {
+                DropTarget::Chart => self.import_csv(ctx, &path, false),
+                DropTarget::Merge => self.merge_offer = Some(path),
+                DropTarget::NewWindow => self.import_in_new_window(&path),
+            }
+        }
+    }
+
+    fn import_in_new_window(&self, path: &Path) {
+        match launch::spawn_import(path) {
+            Ok(child) => info!("opened {path:?} in process {}", child.id()),
+            Err(err) => {
+                error!("failed to open new window: {}", err.chain());
+                self.toast(Toast::error(err.user_message()));
+            }
+        }
+    }
+
+    /// Dims the app while files are dragged over it.
+    fn drop_overlay(ctx: &Context) {
+        let hovered = ctx.input().raw.hovered_files.len();
+        if hovered == 0 {
+            return;
+        }
+
+        let rect = ctx.input().screen_rect();
+        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("drop overlay")));
+        painter.rect_filled(rect, 0.0, Color32::from_black_alpha(192));
+        painter.text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            match hovered {
+                1 => "drop to open the csv".to_string(),
+                n => format!("drop to open {n} csv files"),
+            },
+            TextStyle::Heading.resolve(&ctx.style()),
+            Color32::WHITE,
+        );
+    }
+
+    /// Asks whether the dropped file of the shown symbol is merged into the chart.
+    fn merge_offer_ui(&mut self, ui: &Ui) {
+        let Some(path) = &self.merge_offer else {
+            return;
+        };
+
+        let mut decision = None;
+        Window::new("merge csv")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ui.ctx(), |ui| {
+                ui.label(format!(
+                    "{} has candles of {}, merge them into the chart?",
+                    path.file_name().unwrap_or_default().to_string_lossy(),
+                    self.symbol
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("merge").clicked() {
+                        decision = Some(Some(DropTarget::Merge));
+                    }
+                    if ui.button("new window").clicked() {
+                        decision = Some(Some(DropTarget::NewWindow));
+                    }
+                    if ui.button("cancel").clicked() {
+                        decision = Some(None);
+                    }
+                });
+            });
+
+        // the outer option is whether the user answered, the inner one where to open
+        let Some(target) = decision else {
+            return;
+        };
+        let Some(path) = self.merge_offer.take() else {
+            return;
+        };
+        match target {
+            Some(DropTarget::Merge) => self.import_csv(ui.ctx(), &path, true),
+            Some(_) => self.import_in_new_window(&path),
+            None => info!("dropped {path:?} not opened"),
+        }
+    }
+
+    /// Shows imported candles labeled with the file name. Candles of the shown symbol and
+    /// interval are merged into the loaded ones the same way as downloaded pages, unless
+    /// `merge` is off.
+    fn open_import(&mut self, ctx: &Context, path: &Path, import: Import, merge: bool) {
+        let label = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |n| n.to_string_lossy().to_string(),
+        );
+        let mut msg = format!("imported {} candles from {label}", import.klines.len());
+        if !import.errors.is_empty() {
+            msg = format!(
+                "{msg}, skipped {} bad rows: {}",
+                import.errors.len(),
+                import.errors[..import.errors.len().min(IMPORT_ERRORS_SHOWN)].join("; ")
+            );
+        }
+        info!("{msg}");
+
+        let (Some(first), Some(last)) = (import.klines.first(), import.klines.last()) else {
+            self.toast(Toast::error(msg));
+            return;
+        };
+        let bounds = Bounds(first.t_open, first.t_close.max(last.t_close));
+        let interval = import.interval().unwrap_or(self.state.props.interval);
+        let manifest = import.manifest.clone().zip(import.checksum_matches());
+        if manifest.as_ref().is_some_and(|(_, matches)| !matches) {
+            warn!("{label} doesn't match the SHA-256 of its manifest");
+        }
+        let symbol = import.symbol.clone().unwrap_or_else(|| {
+            path.file_stem()
+                .map_or_else(|| label.clone(), |s| s.to_string_lossy().to_string())
+        });
+
+        let merge = merge
+            && symbol == self.symbol
+            && interval == self.state.props.interval
+            && !self.klines.is_empty();
+        if merge {
+            download::insert_page(&mut self.klines, &import.klines);
+        } else {
+            let Some(mut props) = self.props_from_bounds(bounds) else {
+                self.toast(Toast::error(format!(
+                    "{label} has candles out of the range of dates"
+                )));
+                return;
+            };
+            props.interval = interval;
+            self.set_symbol(symbol);
+            self.clear(ctx);
+            self.props_pub.send(props.clone());
+            self.state.props = props;
+            self.import_interval_unknown = import.interval().is_none();
+            self.klines = import.klines;
+        }
+        self.state.mark_loaded(bounds);
+        self.imported = Some(label);
+        self.import_manifest = manifest;
+        self.draw(ctx);
+
+        match import.errors.is_empty() {
+            true => self.toast(Toast::info(msg)),
+            false => self.toast(Toast::error(msg)),
+        }
+    }
+
+    /// Badge of the manifest of the imported file with its metadata on hover.
+    fn manifest_ui(&self, ui: &mut Ui) {
+        let Some((m, matches)) = &self.import_manifest else {
+            return;
+        };
+
+        let (color, text) = match matches {
+            true => (Color32::LIGHT_GREEN, "manifest ✔"),
+            false => (Color32::LIGHT_RED, "manifest ✘ hash differs"),
+        };
+        let bounds = |b: [i64; 2]| {
+            format!(
+                "{} - {}",
+                Data::format_ts(b[0] as f64),
+                Data::format_ts(b[1] as f64)
+            )
+        };
+        let rows = [
+            ("file", m.file.clone()),
+            ("symbol", m.symbol.clone()),
+            ("interval", m.interval.clone()),
+            ("requested", bounds(m.requested)),
+            (
+                "effective",
+                m.effective.map_or_else(|| "-".to_string(), bounds),
+            ),
+            ("rows", m.rows.to_string()),
+            ("source", m.source.name.clone()),
+            ("base url", m.source.base_url.clone().unwrap_or_default()),
+            ("app version", m.app_version.clone()),
+            ("exported at", m.exported_at.clone()),
+            ("sha256", m.sha256.clone()),
+        ];
+        ui.colored_label(color, text).on_hover_ui(|ui| {
+            Grid::new("import manifest").show(ui, |ui| {
+                rows.into_iter().for_each(|(name, value)| {
+                    ui.label(name);
+                    ui.label(value);
+                    ui.end_row();
+                });
+            });
+        });
+    }
+
+    /// Millis left until the last candles are reloaded while auto refresh is on.
+    fn refresh_countdown(&self) -> Option<i64> {
+        if !self.auto_refresh || self.symbol.is_empty() {
+            return None;
+        }
+        self.refresh.remaining(Utc::now().timestamp_millis())
+    }
+
+    /// Time left until the forming candle closes and until the next refresh while auto
+    /// refresh is on, clicked to refresh now. Repaints when the shown seconds change, only
+    /// while it is shown.
+    fn countdown_ui(&mut self, ui: &mut Ui) {
+        let Some(refresh_in) = self.refresh_countdown() else {
+            return;
+        };
+        let now = Utc::now().timestamp_millis();
+        let interval = self.state.props.interval.millis();
+        let close_in = self
+            .klines
+            .last()
+            .filter(|_| self.imported.is_none())
+            .map(|k| refresh::close_remaining(k.t_open, interval, now));
+
+        let mut text = format!("refresh {}", refresh::countdown(refresh_in));
+        if let Some(close_in) = close_in {
+            text = format!("close {} · {text}", refresh::countdown(close_in));
+        }
+        let clicked = ui
+            .add(Label::new(RichText::new(text).small().monospace()).sense(Sense::click()))
+            .on_hover_text(
+                "until the candle closes and the last candles reload, click to reload now",
+            )
+            .clicked();
+        if clicked && self.refresh_promise.is_none() {
+            info!("refresh of {} forced", self.symbol);
+            self.refresh.force(now);
+        }
+
+        let next_second = [Some(refresh_in), close_in]
+            .into_iter()
+            .flatten()
+            .map(|millis| (millis - 1).rem_euclid(1000) + 1)
+            .min()
+            .unwrap_or(1000);
+        self.repaint
+            .request(ui.ctx(), Duration::from_millis(next_second as u64));
+    }
+
+    /// Reloads the last candles once the current interval closed, retrying with a growing
+    /// delay on failures. Waits for a running download to finish.
+    fn refresh(&mut self, ui: &Ui) {
+        let now = Utc::now().timestamp_millis();
+        if let Some(promise) = &self.refresh_promise {
+            match promise.ready() {
+                Some(Ok(fresh)) => {
+                    debug!("refreshed {} candles of {}", fresh.len(), self.symbol);
+                    fresh.iter().for_each(|kline| {
+                        self.broadcaster.publish(&Message::Candle {
+                            symbol: self.symbol.clone(),
+                            kline: *kline,
+                        })
+                    });
+                    let interval = self.state.props.interval.as_str();
+                    refresh::newly_closed(&self.klines, fresh)
+                        .into_iter()
+                        .for_each(|kline| {
+                            self.webhook.send(&Payload::Candle {
+                                symbol: self.symbol.clone(),
+                                interval: interval.to_string(),
+                                kline,
+                            })
                         });
-                        strip.cell(|ui| {
-                            ui.add(&self.volume);
+                    refresh::merge(&mut self.klines, fresh);
+                    self.status(Status::Fetched(now));
+                    if let Some(tail) = self.refresh.tail(now) {
+                        self.state.mark_loaded(tail);
+                    }
+                    self.refresh_promise = None;
+                    self.refresh.succeeded(now);
+                    self.draw(ui.ctx());
+                }
+                Some(Err(err)) => {
+                    error!("failed to refresh klines: {}", err.chain());
+                    self.status(Status::Failed(now, err.user_message()));
+                    self.refresh_promise = None;
+                    self.refresh.failed(now);
+                }
+                None => {}
+            }
+            return;
+        }
+
+        if !self.auto_refresh || self.symbol.is_empty() {
+            return;
+        }
+        self.refresh
+            .sync(&self.symbol, self.state.props.interval, now);
+        if !self.refresh.is_due(now) || !self.page_promises.is_empty() {
+            return;
+        }
+        let Some(tail) = self.refresh.tail(now) else {
+            return;
+        };
+
+        info!("refreshing {} candles in {tail:?}", self.symbol);
+        let symbol = self.symbol.to_string();
+        let props = self.state.props.clone();
+        let limit = self.state.props.limit;
+        let client = self.client.clone();
+        self.refresh_promise = Some(Promise::spawn_async(async move {
+            download::page(&client, None, &symbol, &props, tail, limit).await
+        }));
+    }
+
+    /// Moves the plots to the time range with some candles around it.
+    fn focus(&mut self, bounds: Bounds) {
+        let pad = (FOCUS_PADDING_CANDLES * self.state.props.interval.millis())
+            .max((bounds.1 - bounds.0) / 2);
+        let axis = self.candles.axis();
+        self.pending_range = Some([
+            axis.to_x((bounds.0 - pad) as f64),
+            axis.to_x((bounds.1 + pad) as f64),
+        ]);
+    }
+
+    /// Zooms to, summarizes or exports the range selected on the candles.
+    fn range_action(&mut self, action: RangeAction, bounds: Bounds) {
+        info!("{action:?} for selected {bounds:?}");
+        match action {
+            RangeAction::Zoom => {
+                let axis = self.candles.axis();
+                self.pending_range = Some([bounds.0, bounds.1].map(|ts| axis.to_x(ts as f64)));
+            }
+            RangeAction::Stats => self.stats_window.open(),
+            RangeAction::Export => {
+                let [start, end] = [bounds.0, bounds.1].map(|ts| {
+                    DateTime::from_timestamp_millis(ts)
+                        .unwrap_or_default()
+                        .naive_utc()
+                });
+                let props = &self.state.props;
+                let props = Props {
+                    limit: props.limit,
+                    ..Props::new(start, end, props.interval, props.empty_buckets)
+                };
+                self.request_export(props, self.export_state.format);
+            }
+        }
+    }
+
+    /// Drops candles within the ranges and downloads them again.
+    fn refetch(&mut self, ui: &Ui, ranges: BoundsSet) {
+        if !self.page_promises.is_empty() {
+            self.toast(Toast::info("wait for the download to finish to re-fetch"));
+            return;
+        }
+
+        info!("re-fetching ranges: {ranges:?}");
+        self.klines.retain(|k| {
+            let candle = Bounds(k.t_open, k.t_close);
+            ranges.vals().iter().all(|b| candle.intersect(b).is_none())
+        });
+        self.state.evict(&ranges);
+        let mut props = self.state.props.clone();
+        props.bounds = ranges;
+        self.start_download(props, false);
+        self.draw(ui.ctx());
+    }
+
+    fn status(&self, status: Status) {
+        if let Err(err) = self.status_pub.send(status).context("status") {
+            error!("{}", Error::from(err).chain());
+        }
+    }
+
+    fn toast(&self, toast: Toast) {
+        if let Err(err) = self.toast_pub.send(toast).context("toast") {
+            error!("{}", Error::from(err).chain());
+        }
+    }
+
+    fn props_from_bounds(&self, bounds: Bounds) -> Option<Props> {
+        let dt_left = DateTime::from_timestamp_millis(bounds.0);
+        let dt_right = DateTime::from_timestamp_millis(bounds.1);
+        if dt_left.is_none() || dt_right.is_none() {
+            error!("got bounds out of timestamp range: {bounds:?}");
+            return None;
+        }
+        let (dt_left, dt_right) = (dt_left?.naive_utc(), dt_right?.naive_utc());
+
+        let mut props = self.state.props.clone();
+        props.bounds = BoundsSet::new(vec![bounds]);
+        props.date_start = dt_left.date();
+        props.time_start = dt_left.time();
+        props.date_end = dt_right.date();
+        props.time_end = dt_right.time();
+
+        Some(props)
+    }
+
+    fn set_symbol(&mut self, symbol: String) {
+        self.status(Status::Symbol(symbol.clone()));
+        self.symbol = symbol.clone();
+        self.backtest_window.set_symbol(&symbol);
+        self.correlation_window.set_symbol(&symbol);
+        self.portfolio_window.set_symbol(&symbol);
+        self.show_notes();
+        self.broadcaster.publish(&Message::Symbol {
+            symbol: symbol.clone(),
+        });
+        self.symbol_pub.send(symbol);
+    }
+
+    /// Range and interval of the shown candles to load for another symbol, without the
+    /// dragged extensions.
+    fn symbol_props(&self) -> Props {
+        let props = &self.state.props;
+        Props {
+            limit: props.limit,
+            ..Props::new(
+                props.start_time().naive_utc(),
+                props.end_time().naive_utc(),
+                props.interval,
+                props.empty_buckets,
+            )
+        }
+    }
+
+    /// Loads the shown range in `interval` keeping about the same number of candles, the
+    /// props window follows through the props channel.
+    fn switch_interval(&mut self, interval: Interval) {
+        if interval == self.state.props.interval {
+            return;
+        }
+
+        let props = self.symbol_props().with_interval(interval);
+        info!("switching to {} interval: {props:?}", interval.as_str());
+        self.props_pub.send(props.clone());
+        self.start_download(props, true);
+    }
+
+    fn register_actions(actions: &Actions) {
+        STRIP_INTERVALS
+            .into_iter()
+            .zip(STRIP_KEYS)
+            .for_each(|(interval, key)| {
+                actions.register(
+                    &Graph::interval_action(interval),
+                    &format!("interval {}", interval.as_str()),
+                    Some(Shortcut::key(key)),
+                )
+            });
+        [
+            (ACTION_PROPS, "toggle props window"),
+            (ACTION_SCRIPTS, "toggle scripts window"),
+            (ACTION_EXPRESSIONS, "toggle expressions window"),
+            (ACTION_BACKTEST, "toggle backtest window"),
+            (ACTION_QUALITY, "toggle data quality window"),
+            (ACTION_STATS, "toggle statistics window"),
+            (ACTION_JOURNAL, "toggle journal window"),
+            (ACTION_CORRELATION, "toggle correlation window"),
+            (ACTION_PORTFOLIO, "toggle portfolio window"),
+        ]
+        .into_iter()
+        .for_each(|(id, name)| actions.register(id, name, None));
+    }
+
+    fn interval_action(interval: Interval) -> String {
+        format!("interval.{}", interval.as_str())
+    }
+
+    /// Runs the triggered actions of the graph and of its windows.
+    fn handle_actions(&mut self) {
+        // every interval is taken so none is left pending
+        let picked = STRIP_INTERVALS.into_iter().fold(None, |picked, i| {
+            match self.actions.take(&Graph::interval_action(i)) {
+                true => Some(i),
+                false => picked,
+            }
+        });
+        if let Some(interval) = picked {
+            self.switch_interval(interval);
+        }
+
+        let windows: [(&str, &mut dyn AppWindow); 9] = [
+            (ACTION_PROPS, self.time_range_window.as_mut()),
+            (ACTION_SCRIPTS, &mut self.scripts_window),
+            (ACTION_EXPRESSIONS, &mut self.expressions_window),
+            (ACTION_BACKTEST, &mut self.backtest_window),
+            (ACTION_QUALITY, &mut self.quality_window),
+            (ACTION_STATS, &mut self.stats_window),
+            (ACTION_JOURNAL, &mut self.journal_window),
+            (ACTION_CORRELATION, &mut self.correlation_window),
+            (ACTION_PORTFOLIO, &mut self.portfolio_window),
+        ];
+        windows.into_iter().for_each(|(id, w)| {
+            if self.actions.take(id) {
+                w.toggle();
+            }
+        });
+    }
+
+    /// Buttons of the strip intervals with the current one highlighted, returns the clicked one.
+    fn interval_strip_ui(&self, ui: &mut Ui) -> Option<Interval> {
+        let mut picked = None;
+        ui.horizontal(|ui| {
+            STRIP_INTERVALS.into_iter().for_each(|interval| {
+                let shortcut = self
+                    .actions
+                    .get(&Graph::interval_action(interval))
+                    .and_then(|a| a.shortcut);
+                let mut resp =
+                    ui.selectable_label(self.state.props.interval == interval, interval.as_str());
+                if let Some(shortcut) = shortcut {
+                    resp = resp.on_hover_text(format!("key {}", shortcut.label()));
+                }
+                if resp.clicked() {
+                    picked = Some(interval);
+                }
+            });
+        });
+
+        picked
+    }
+
+    /// Drops the candles and downloads of the previous symbol so they never show under
+    /// the new one.
+    fn clear(&mut self, ctx: &Context) {
+        self.page_promises.clear();
+        self.refresh_promise = None;
+        self.download = None;
+        self.load_error = None;
+        self.imported = None;
+        self.import_manifest = None;
+        self.import_interval_unknown = false;
+        self.export_state.triggered = false;
+        self.replay = None;
+        self.candles.clear_selection();
+        self.klines = vec![];
+        let props = self.state.props.clone();
+        self.state = State::default();
+        self.state.props = props;
+        self.views = Views::default();
+        self.draw(ctx);
+    }
+
+    fn placeholder(&self) -> Option<Placeholder> {
+        if !self.klines.is_empty() {
+            return None;
+        }
+        if !self.page_promises.is_empty() {
+            return Some(Placeholder::Loading);
+        }
+        if let Some(err) = &self.load_error {
+            return Some(Placeholder::Error(err.clone()));
+        }
+        if self.state.bounds().is_empty() {
+            return Some(Placeholder::Unloaded);
+        }
+
+        Some(Placeholder::Empty)
+    }
+
+    fn placeholder_ui(&self, ui: &mut Ui, placeholder: Placeholder) {
+        ui.centered_and_justified(|ui| match placeholder {
+            Placeholder::Unloaded => {
+                ui.label(format!("press show in Props to load {}", self.symbol));
+            }
+            Placeholder::Loading => {
+                ui.horizontal_centered(|ui| {
+                    ui.add(Spinner::new());
+                    ui.label("loading…");
+                });
+            }
+            Placeholder::Empty => {
+                let props = &self.state.props;
+                ui.label(format!(
+                    "no data for {} between {} and {}",
+                    self.symbol,
+                    props.start_time().format(PLACEHOLDER_TS_FORMAT),
+                    props.end_time().format(PLACEHOLDER_TS_FORMAT),
+                ));
+            }
+            Placeholder::Error(err) => {
+                ui.colored_label(
+                    Color32::LIGHT_RED,
+                    format!("error: failed to load {}: {err}", self.symbol),
+                );
+            }
+        });
+    }
+
+    /// Current chart as a view to share.
+    fn view(&self) -> View {
+        let (_, [from, to]) = self.candles.x_view();
+        let axis = self.candles.axis();
+        View {
+            symbol: self.symbol.clone(),
+            interval: self.state.props.interval.as_str().to_string(),
+            start: self.state.props.start_time().timestamp_millis(),
+            end: self.state.props.end_time().timestamp_millis(),
+            limit: Some(self.state.props.limit),
+            empty_buckets: self.state.props.empty_buckets,
+            visible: (from < to).then(|| [axis.to_ts(from) as i64, axis.to_ts(to) as i64]),
+            renko: self.renko.then_some(self.brick_size),
+            compress: self.compress,
+            indicators: self.indicators.setups(),
+        }
+    }
+
+    fn detach(&self) {
+        match launch::spawn(&self.view()) {
+            Ok(child) => info!("opened view in process {}", child.id()),
+            Err(err) => {
+                error!("failed to open new window: {}", err.chain());
+                self.toast(Toast::error(err.user_message()));
+            }
+        }
+    }
+
+    /// Opens the chart of a shared view downloading its candles.
+    pub fn open_view(&mut self, s: &str) {
+        match View::decode(s) {
+            Ok(view) => self.show_view(view),
+            Err(err) => {
+                error!("failed to open view: {}", err.chain());
+                self.toast(Toast::error(err.user_message()));
+            }
+        }
+    }
+
+    fn show_view(&mut self, view: View) {
+        let props = match view.props() {
+            Ok(props) if !view.symbol.is_empty() => props,
+            Ok(_) => {
+                self.toast(Toast::error("view has no symbol"));
+                return;
+            }
+            Err(err) => {
+                error!("failed to open view: {}", err.chain());
+                self.toast(Toast::error(err.user_message()));
+                return;
+            }
+        };
+        info!("opening view: {view:?}");
+
+        self.renko = view.renko.is_some();
+        self.brick_size = view.renko.unwrap_or_default();
+        self.compress = view.compress;
+        let failed = self.indicators.set_setups(&view.indicators);
+        if !failed.is_empty() {
+            self.toast(Toast::info(format!(
+                "default params used for {}",
+                failed.join(", ")
+            )));
+        }
+        self.view_range = view.visible.map(|[from, to]| Bounds(from, to));
+
+        self.open(view.symbol, props);
+    }
+
+    /// Adds the chart and the windows of the graph to a session snapshot.
+    pub fn snapshot(&self, session: &mut Session) {
+        self.time_range_window.snapshot(session);
+        self.scripts_window.snapshot(session);
+        self.expressions_window.snapshot(session);
+        self.backtest_window.snapshot(session);
+        self.quality_window.snapshot(session);
+        self.stats_window.snapshot(session);
+        self.journal_window.snapshot(session);
+        self.correlation_window.snapshot(session);
+        self.portfolio_window.snapshot(session);
+        if !self.symbol.is_empty() {
+            session.view = Some(self.view());
+        }
+    }
+
+    /// Reopens the windows and the chart of a session snapshot.
+    pub fn restore(&mut self, session: &Session) {
+        self.time_range_window.restore(session);
+        self.scripts_window.restore(session);
+        self.expressions_window.restore(session);
+        self.backtest_window.restore(session);
+        self.quality_window.restore(session);
+        self.stats_window.restore(session);
+        self.journal_window.restore(session);
+        self.correlation_window.restore(session);
+        self.portfolio_window.restore(session);
+        if let Some(view) = session.view.clone() {
+            self.show_view(view);
+        }
+    }
+
+    fn view_ui(&mut self, ui: &mut Ui) -> Option<String> {
+        if ui
+            .button("copy view")
+            .on_hover_text("copy symbol, time range, chart mode and indicators to share")
+            .clicked()
+        {
+            ui.output().copied_text = self.view().encode();
+            self.toast(Toast::info("view copied to clipboard"));
+            ui.close_menu();
+        }
+        if ui
+            .button("open in new window")
+            .on_hover_text("open the chart in another netstrat window, e.g. on another monitor")
+            .clicked()
+        {
+            self.detach();
+            ui.close_menu();
+        }
+        ui.separator();
+        ui.add(TextEdit::singleline(&mut self.pasted_view).hint_text("paste view"));
+        let open = ui
+            .add_enabled(
+                !self.pasted_view.trim().is_empty(),
+                Button::new("open view"),
+            )
+            .clicked();
+        if !open {
+            return None;
+        }
+        ui.close_menu();
+        Some(std::mem::take(&mut self.pasted_view))
+    }
+
+    /// Handles requests of the other windows, the graph windows drain theirs too.
+    pub fn tick(&mut self, ctx: &Context) {
+        self.handle_events(ctx);
+        self.handle_actions();
+        self.handle_jumps(ctx);
+        Graph::drop_overlay(ctx);
+        self.open_dropped(ctx);
+        self.time_range_window.tick(ctx);
+        self.scripts_window.tick(ctx);
+        self.expressions_window.tick(ctx);
+        self.backtest_window.tick(ctx);
+        self.quality_window.tick(ctx);
+        self.stats_window.tick(ctx);
+        self.journal_window.tick(ctx);
+        if let Some(notes) = self.journal_window.take_edited() {
+            self.notes = notes;
+            self.show_notes();
+        }
+        if self.timezone.utc_offset() != self.indicators_utc_offset {
+            self.apply_indicators();
+        }
+        self.correlation_window.tick(ctx);
+        if let Some((symbol, props)) = self.correlation_window.take_open() {
+            if let Err(err) = self.jump_pub.send(Jump { symbol, props }).context("jump") {
+                error!("{}", Error::from(err).chain());
+            }
+        }
+        if let Some(kline) = self.klines.last().filter(|_| self.imported.is_none()) {
+            self.portfolio_window.set_close(&self.symbol, kline);
+        }
+        self.portfolio_window.tick(ctx);
+    }
+
+    /// Symbols offered to the correlation window.
+    pub fn set_favorites(&mut self, favorites: &BTreeSet<String>) {
+        self.correlation_window.set_favorites(favorites);
+    }
+
+    fn handle_events(&mut self, ctx: &Context) {
+        // called on every frame, so the channels are only polled
+        if let Some(bounds) = self.drag_sub.try_iter().last() {
+            info!("got bounds: {bounds:?}");
+
+            if self.replay.is_some() || self.renko {
+                info!("not on the time axis, skipping download of dragged bounds");
+            } else if let Some(props) = self.props_from_bounds(bounds) {
+                self.props_pub.send(props.clone());
+                info!("sent props: {props:?}");
+
+                self.start_download(props, false);
+            }
+        }
+
+        if let Ok((props, format)) = self.export_sub.try_recv() {
+            info!("got props for export: {props:?}, format: {format:?}");
+            self.request_export(props, format);
+        }
+
+        match self.symbol_sub.try_iter().last() {
+            Some(selection) if selection.is_redundant(&self.symbol) => {
+                debug!("ignoring selection of the shown symbol: {selection:?}");
+            }
+            Some(selection) => {
+                info!("got symbol: {selection:?}");
+
+                let base = self.symbol_props();
+                let remembered =
+                    self.last_used
+                        .props(&selection.symbol, &base, Utc::now().naive_utc());
+                self.set_symbol(selection.symbol);
+                self.clear(ctx);
+                let props = match remembered {
+                    Some(props) => {
+                        info!("using last props of {}: {props:?}", self.symbol);
+                        self.props_pub.send(props.clone());
+                        props
+                    }
+                    None => base,
+                };
+                if selection.force || self.download_config.load_selected() {
+                    self.start_download(props, true);
+                }
+            }
+            None => {}
+        }
+
+        if let Some(props) = self.props_sub.try_iter().last() {
+            info!("got show button pressed: {props:?}");
+
+            self.start_download(props, true);
+        }
+    }
+}
+
+impl Widget for &mut Graph {
+    fn ui(self, ui: &mut Ui) -> Response {
+        if self.symbol.is_empty() {
+            return ui.label("Select a symbol");
+        }
+
+        self.poll_pages(ui);
+
+        self.refresh(ui);
+
+        if !self.renko {
+            self.views.touch(self.candles.visible());
+        }
+        self.indicator_panel.set_visible(self.candles.visible());
+        self.evict(ui);
+
+        self.candles
+            .set_enabled(self.state.loading.progress() == 1.0);
+        self.volume
+            .set_enabled(self.state.loading.progress() == 1.0);
+
+        if self.state.loading.progress() == 1.0 && self.export_state.triggered {
+            self.export_state.triggered = false;
+            self.export();
+        }
+        if let Some(batch) = self.export_state.batch.take() {
+            match batch.promise.try_take() {
+                Ok(downloaded) => self.export_batch(batch.props, batch.format, downloaded),
+                Err(promise) => self.export_state.batch = Some(Batch { promise, ..batch }),
+            }
+        }
+        if let Some(res) = self.export_state.promise.as_ref().and_then(|p| p.ready()) {
+            match res {
+                Ok(Exported::File(abs_path)) => {
+                    info!("exported to file: {abs_path:?}");
+                    self.toast(Toast::info(format!("exported to {}", abs_path.display())));
+                }
+                Ok(Exported::Files(paths)) => {
+                    info!("exported to files: {paths:?}");
+                    let dir = paths.first().and_then(|p| p.parent());
+                    self.toast(Toast::info(format!(
+                        "exported {} files to {}",
+                        paths.len(),
+                        dir.unwrap_or(Path::new("")).display()
+                    )));
+                }
+                Ok(Exported::Influx(summary)) => {
+                    info!("exported to InfluxDB: {summary:?}");
+                    let msg = format!(
+                        "wrote {} points to InfluxDB, {} failed",
+                        summary.points, summary.failed
+                    );
+                    match &summary.error {
+                        Some(err) => self.toast(Toast::error(format!("{msg}: {err}"))),
+                        None => self.toast(Toast::info(msg)),
+                    }
+                }
+                Err(err) => {
+                    error!("failed to export data: {}", err.chain());
+                    self.toast(Toast::error(err.user_message()));
+                }
+            }
+            self.export_state.promise = None;
+        }
+        self.confirm_export_ui(ui);
+        self.merge_offer_ui(ui);
+
+        if self.replay.as_mut().is_some_and(|r| r.tick(Instant::now())) {
+            self.draw(ui.ctx());
+        }
+
+        let mut indicators_changed = false;
+        let mut forming_toggled = false;
+        let mut opened_view = None;
+        let mut import_clicked = false;
+        let (mut replay_started, mut replay_moved, mut replay_stopped) = (false, false, false);
+        let (mut renko_toggled, mut brick_changed, mut compress_toggled) = (false, false, false);
+        let mut interval_picked = None;
+        let mut as_of_picked = None;
+        if !self.fullscreen {
+            TopBottomPanel::top("graph toolbar").show_inside(ui, |ui| {
+                if let Some(interval) = self.interval_strip_ui(ui) {
+                    interval_picked = Some(interval);
+                }
+                ui.horizontal(|ui| {
+                    ui.menu_button("file", |ui| import_clicked = self.file_ui(ui));
+                    self.time_range_window.toggle_btn(ui);
+                    self.scripts_window.toggle_btn(ui);
+                    self.expressions_window.toggle_btn(ui);
+                    self.backtest_window.toggle_btn(ui);
+                    self.quality_window.toggle_btn(ui);
+                    self.stats_window.toggle_btn(ui);
+                    self.journal_window.toggle_btn(ui);
+                    self.correlation_window.toggle_btn(ui);
+                    self.portfolio_window.toggle_btn(ui);
+                    ui.menu_button("indicators", |ui| {
+                        indicators_changed = self.indicators.ui(ui);
+                        ui.separator();
+                        forming_toggled = ui
+                            .checkbox(&mut self.include_forming, "include forming candle")
+                            .changed();
+                    });
+                    let mut events_changed = false;
+                    ui.menu_button("events", |ui| events_changed = self.events_ui(ui));
+                    if events_changed {
+                        self.apply_events();
+                    }
+                    ui.menu_button("notes", |ui| {
+                        ui.label("add notes from the candles context menu");
+                        if ui
+                            .checkbox(&mut self.note_texts, "always show texts")
+                            .changed()
+                        {
+                            self.candles.set_note_texts(self.note_texts);
+                            self.context_pane.set_note_texts(self.note_texts);
+                        }
+                        ui.separator();
+                        if ui.button("export csv").clicked() {
+                            self.export_notes("csv");
+                            ui.close_menu();
+                        }
+                        if ui.button("export json").clicked() {
+                            self.export_notes("json");
+                            ui.close_menu();
+                        }
+                    });
+                    if ui
+                        .checkbox(&mut self.auto_refresh, "auto refresh")
+                        .on_hover_text("reload the last candles when the current interval closes")
+                        .changed()
+                    {
+                        self.refresh.reset();
+                    }
+                    self.countdown_ui(ui);
+                    ui.menu_button("view", |ui| opened_view = self.view_ui(ui));
+                    ui.checkbox(&mut self.show_delta, "delta");
+                    let split_toggled = ui
+                        .checkbox(&mut self.split, "split")
+                        .on_hover_text("show the loaded range in a wider interval above")
+                        .changed();
+                    let interval_changed = self.split && self.context_pane.toolbar_ui(ui);
+                    if self.split && (split_toggled || interval_changed) {
+                        self.context_pane.set_klines(&self.klines);
+                    }
+                    renko_toggled = ui
+                        .checkbox(&mut self.renko, "renko")
+                        .on_hover_text("bricks of closes, indicators are hidden")
+                        .changed();
+                    if self.renko {
+                        brick_changed = self.renko_ui(ui);
+                    } else {
+                        compress_toggled = ui
+                            .checkbox(&mut self.compress, "compress gaps")
+                            .on_hover_text(
+                                "place candles by index leaving out time without trading",
+                            )
+                            .changed();
+                    }
+                    if ui
+                        .checkbox(&mut self.snap, "snap")
+                        .on_hover_text(
+                            "snap crosshair to open, high, low or close, hold alt to disable",
+                        )
+                        .changed()
+                    {
+                        self.candles.set_snap(self.snap);
+                    }
+                    match self.replay.is_some() {
+                        true => {
+                            ui.separator();
+                            replay_moved = self.replay_ui(ui);
+                            replay_stopped = ui.button("stop replay").clicked();
+                            ui.separator();
+                        }
+                        false => {
+                            ui.menu_button("replay", |ui| {
+                                replay_started = self.replay_start_ui(ui)
+                            });
+                        }
+                    }
+                    ui.menu_button("as of", |ui| as_of_picked = self.as_of_ui(ui));
+                    let mut volume_kind = self.volume.kind();
+                    ComboBox::from_id_source("volume kind")
+                        .selected_text(match volume_kind {
+                            VolumeKind::Base => "base volume",
+                            VolumeKind::Quote => "quote volume",
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut volume_kind, VolumeKind::Base, "base volume");
+                            ui.selectable_value(
+                                &mut volume_kind,
+                                VolumeKind::Quote,
+                                "quote volume",
+                            );
                         });
-                    })
+                    if volume_kind != self.volume.kind() {
+                        self.volume.set_kind(volume_kind);
+                    }
+                    self.manifest_ui(ui);
+                    if !self.state.loading.is_finished() {
+                        ui.add(
+                            ProgressBar::new(self.state.loading.progress())
+                                .show_percentage()
+                                .animate(true),
+                        );
+                    }
+                    if let Some(k) = self.candles.hovered().and_then(|h| h.candle) {
+                        ui.label(format!(
+                            "O {} H {} L {} C {}",
+                            k.open, k.high, k.low, k.close
+                        ));
+                    }
+                    self.headers.iter().for_each(|h| {
+                        let color = match h.tone {
+                            Tone::Bullish => Color32::LIGHT_GREEN,
+                            Tone::Bearish => Color32::LIGHT_RED,
+                            Tone::Neutral => Color32::GRAY,
+                        };
+                        ui.colored_label(color, &h.text);
+                    });
+                });
+            });
+        }
+
+        let mut as_of_cleared = false;
+        if let Some(ts) = self.as_of {
+            TopBottomPanel::top("as of banner").show_inside(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!("as of {}", Data::format_ts(ts as f64)))
+                            .strong()
+                            .color(Color32::GOLD),
+                    );
+                    ui.label("later candles are hidden");
+                    as_of_cleared = ui.button("clear").clicked();
+                });
+            });
+        }
+
+        if let Some(ts) = as_of_picked {
+            self.set_as_of(ui.ctx(), Some(ts));
+        } else if as_of_cleared {
+            self.set_as_of(ui.ctx(), None);
+        }
+
+        if renko_toggled || brick_changed || compress_toggled {
+            self.draw(ui.ctx());
+        }
+
+        if let Some(interval) = interval_picked {
+            self.switch_interval(interval);
+        }
+
+        if let Some(view) = opened_view {
+            self.open_view(&view);
+        }
+
+        if import_clicked {
+            let path = PathBuf::from(self.import_path.trim());
+            self.import_csv(ui.ctx(), &path, true);
+        }
+
+        if replay_started {
+            self.start_replay(ui);
+        } else if replay_stopped {
+            self.stop_replay(ui);
+        } else if replay_moved {
+            self.draw(ui.ctx());
+        }
+
+        if forming_toggled {
+            let indicators_data = self.indicators_data();
+            self.scripts_window.set_data(indicators_data.clone());
+            self.expressions_window.set_data(indicators_data.clone());
+            self.backtest_window.set_data(indicators_data);
+        }
+
+        let scripts_changed = self.scripts_window.take_changed();
+        let expressions_changed = self.expressions_window.take_changed();
+        let backtest_changed = self.backtest_window.take_changed();
+        let correlation_changed = self.correlation_window.take_changed();
+        let portfolio_changed = self.portfolio_window.take_changed();
+        if indicators_changed
+            || forming_toggled
+            || scripts_changed
+            || expressions_changed
+            || backtest_changed
+            || correlation_changed
+            || portfolio_changed
+        {
+            self.apply_indicators();
+        }
+
+        if let Some(bounds) = self.quality_window.take_focus() {
+            self.focus(bounds);
+        }
+        if let Some(ranges) = self.quality_window.take_refetch() {
+            self.refetch(ui, ranges);
+        }
+        self.stats_window.set_selection(self.candles.selection());
+        if let Some((action, bounds)) = self.candles.take_range_action() {
+            self.range_action(action, bounds);
+        }
+        self.candles.set_span(
+            self.quality_window
+                .highlight()
+                .map(|b| [b.0 as f64, b.1 as f64]),
+        );
+
+        let next_range = self.wheel_range(ui).or(self.pending_range.take());
+        let resp = CentralPanel::default()
+            .show_inside(ui, |ui| {
+                if self.fullscreen {
+                    self.fullscreen_ui(ui, next_range);
+                    return;
+                }
+
+                self.time_range_window.show(ui);
+                self.scripts_window.show(ui);
+                self.expressions_window.show(ui);
+                self.backtest_window.show(ui);
+                self.quality_window.show(ui);
+                self.stats_window.show(ui);
+                self.journal_window.show(ui);
+                self.correlation_window.show(ui);
+                self.portfolio_window.show(ui);
+
+                if let Some(placeholder) = self.placeholder() {
+                    self.placeholder_ui(ui, placeholder);
+                    return;
+                }
+
+                if !self.split {
+                    self.panes_ui(ui, next_range);
+                    return;
+                }
+
+                let (_, view) = self.candles.x_view();
+                let view = view.map(|x| self.candles.axis().to_ts(x));
+                StripBuilder::new(ui)
+                    .size(Size::relative(0.35))
+                    .size(Size::remainder())
+                    .vertical(|mut strip| {
+                        strip.cell(|ui| self.context_pane.ui(ui, view));
+                        strip.cell(|ui| self.panes_ui(ui, next_range));
+                    });
+            })
+            .response;
+        self.sync_notes();
+
+        resp
+    }
+}
+
+/// Summary of a finished download, e.g. `BTCUSDT 1h 2023-01-01→2023-06-01 loaded (3,624
+/// candles)`. Times are left out of ranges of whole days.
+fn loaded_text(symbol: &str, props: &Props, candles: usize) -> String {
+    let (start, end) = (props.start_time(), props.end_time());
+    let format = match start.time() == NaiveTime::MIN && end.time() == NaiveTime::MIN {
+        true => "%Y-%m-%d",
+        false => "%Y-%m-%d %H:%M",
+    };
+    format!(
+        "{symbol} {} {}→{} loaded ({} candles)",
+        props.interval.as_str(),
+        start.format(format),
+        end.format(format),
+        thousands(candles)
+    )
+}
+
+/// Count with commas between groups of thousands.
+fn thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::new();
+    digits.chars().enumerate().for_each(|(idx, c)| {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    });
+
+    grouped
+}
+
+#[cfg(test)]
+mod graph_tests {
+    use crate::{netstrat::selection::Source, sources::binance::mock};
+
+    use super::*;
+
+    fn graph(symbol_sub: Receiver<Selection>) -> Graph {
+        let download_config = DownloadConfig::default();
+        download_config.set_load_selected(false);
+        Graph::new(
+            symbol_sub,
+            unbounded().0,
+            unbounded().0,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            download_config,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn test_symbol_change_clears_candles() {
+        let (s_symbol, r_symbol) = unbounded();
+        let mut graph = graph(r_symbol);
+        let ctx = Context::default();
+        graph.symbol = "BTCUSDT".to_string();
+        graph.klines = mock::klines(0, 60_000, 10);
+        graph.state.mark_loaded(Bounds(0, 600_000));
+        graph.draw(&ctx);
+        assert_eq!(graph.placeholder(), None);
+
+        s_symbol
+            .send(Selection::new("ETHUSDT", Source::List))
+            .unwrap();
+        graph.tick(&ctx);
+
+        assert_eq!(graph.symbol, "ETHUSDT");
+        assert!(graph.klines.is_empty());
+        assert!(graph.data.vals.is_empty());
+        assert_eq!(graph.placeholder(), Some(Placeholder::Unloaded));
+        assert_eq!(graph.symbol_props().bounds, graph.state.props.bounds);
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut graph = graph(unbounded().1);
+        assert_eq!(graph.placeholder(), Some(Placeholder::Unloaded));
+
+        graph
+            .page_promises
+            .push((Bounds(0, 60_000), Promise::from_ready(Ok(vec![]))));
+        assert_eq!(graph.placeholder(), Some(Placeholder::Loading));
+
+        graph.page_promises.clear();
+        graph.state.mark_loaded(Bounds(0, 60_000));
+        assert_eq!(graph.placeholder(), Some(Placeholder::Empty));
+
+        graph.load_error = Some("Binance doesn't know the symbol".to_string());
+        assert_eq!(
+            graph.placeholder(),
+            Some(Placeholder::Error(
+                "Binance doesn't know the symbol".to_string()
+            ))
+        );
+
+        graph.klines = mock::klines(0, 60_000, 1);
+        assert_eq!(graph.placeholder(), None);
+    }
+
+    #[test]
+    fn test_same_symbol_ignored() {
+        let (s_symbol, r_symbol) = unbounded();
+        let mut graph = graph(r_symbol);
+        let ctx = Context::default();
+        graph.symbol = "BTCUSDT".to_string();
+        graph.klines = mock::klines(0, 60_000, 10);
+        graph.state.mark_loaded(Bounds(0, 600_000));
+
+        s_symbol
+            .send(Selection::new("BTCUSDT", Source::List))
+            .unwrap();
+        graph.tick(&ctx);
+
+        assert_eq!(graph.klines.len(), 10);
+        assert!(s_symbol.is_empty());
+    }
+
+    #[test]
+    fn test_import_opens_and_merges() {
+        let mut graph = graph(unbounded().1);
+        let ctx = Context::default();
+        graph.symbol = "BTCUSDT".to_string();
+        graph.klines = mock::klines(0, 60_000, 10);
+        let all = mock::klines(0, 60_000, 8);
+        let import = |klines: &[Kline]| Import {
+            klines: klines.to_vec(),
+            errors: vec!["line 2: no low".to_string()],
+            ..Default::default()
+        };
+
+        graph.open_import(&ctx, Path::new("dir/ETHUSDT.csv"), import(&all[..5]), true);
+
+        assert_eq!(graph.symbol, "ETHUSDT");
+        assert_eq!(graph.imported(), Some("ETHUSDT.csv"));
+        assert_eq!(graph.klines, all[..5]);
+        assert_eq!(graph.state.props.interval, Interval::Minute);
+        assert_eq!(graph.data.vals.len(), 5);
+        assert_eq!(graph.placeholder(), None);
+
+        graph.open_import(&ctx, Path::new("ETHUSDT.csv"), import(&all[3..]), true);
+
+        assert_eq!(graph.klines, all);
+        assert_eq!(
+            graph.state.bounds(),
+            &BoundsSet::new(vec![Bounds(0, all[7].t_close)])
+        );
+    }
+
+    #[test]
+    fn test_drop_targets() {
+        let mut graph = graph(unbounded().1);
+        let own = Path::new("/tmp/BTCUSDT_0_360_Minute.csv");
+        let other = Path::new("/tmp/ETHUSDT_0_360_Minute.csv");
+        assert_eq!(graph.drop_target(other), DropTarget::Chart);
+
+        graph.symbol = "BTCUSDT".to_string();
+        graph.klines = mock::klines(0, 60_000, 10);
+        assert_eq!(graph.drop_target(own), DropTarget::Merge);
+        assert_eq!(graph.drop_target(other), DropTarget::NewWindow);
+        assert_eq!(
+            graph.drop_target(Path::new("BTCUSDT.csv")),
+            DropTarget::NewWindow
+        );
+
+        graph.merge_offer = Some(own.to_path_buf());
+        assert_eq!(graph.drop_target(own), DropTarget::NewWindow);
+    }
+
+    #[test]
+    fn test_loaded_text() {
+        let day = |d| {
+            chrono::NaiveDate::from_ymd_opt(2023, 1, d)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        };
+        let props = Props::new(day(1), day(31), Interval::Hour, Default::default());
+        assert_eq!(
+            loaded_text("BTCUSDT", &props, 3624),
+            "BTCUSDT 1h 2023-01-01→2023-01-31 loaded (3,624 candles)"
+        );
+
+        let props = Props::new(
+            day(1),
+            day(1) + chrono::Duration::minutes(90),
+            Interval::Minute,
+            Default::default(),
+        );
+        assert_eq!(
+            loaded_text("ETHUSDT", &props, 90),
+            "ETHUSDT 1m 2023-01-01 00:00→2023-01-01 01:30 loaded (90 candles)"
+        );
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(1_050_000), "1,050,000");
+    }
+
+    #[test]
+    fn test_jump_fits_shown_range() {
+        let mut graph = graph(unbounded().1);
+        let ctx = Context::default();
+        graph.symbol = "BTCUSDT".to_string();
+        graph.klines = mock::klines(0, 60_000, 10);
+        graph.state.mark_loaded(Bounds(0, 600_000));
+        graph.draw(&ctx);
+
+        graph
+            .jump_pub
+            .send(Jump {
+                symbol: graph.symbol.clone(),
+                props: graph.state.props.clone(),
             })
-            .response
+            .unwrap();
+        graph.tick(&ctx);
+
+        assert_eq!(graph.symbol, "BTCUSDT");
+        assert!(graph.pending_range.is_some());
+        assert_eq!(graph.klines.len(), 10);
+        assert!(graph.take_focus_request());
+        assert!(!graph.take_focus_request());
     }
 }
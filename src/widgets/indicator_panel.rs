@@ -0,0 +1,394 @@
+use std::ops::RangeInclusive;
+
+use egui::{
+    plot::{
+        Bar, BarChart, HLine, Legend, Line, LineStyle, LinkedAxisGroup, MarkerShape, Plot, Points,
+        Polygon, Text, Value, Values,
+    },
+    Align2, Color32, Response, RichText, Widget,
+};
+
+use crate::netstrat::{
+    axis::Axis,
+    bounds::Bounds,
+    indicators::{
+        math, Band, Divergence, Glyph, Guides, IndicatorOutput, Level, Marker, MarkerKind, Rebase,
+        Series, SubPanel, Tone,
+    },
+};
+
+use super::appearance::plot_text;
+
+/// Panel under the candles showing indicators which don't share the price scale.
+pub struct IndicatorPanel {
+    series: Vec<Series>,
+    guides: Vec<Guides>,
+    markers: Vec<Marker>,
+    divergences: Vec<Divergence>,
+    visible: Bounds,
+    axes_group: LinkedAxisGroup,
+    axis: Axis,
+}
+
+impl Default for IndicatorPanel {
+    fn default() -> Self {
+        Self {
+            series: Default::default(),
+            guides: Default::default(),
+            markers: Default::default(),
+            divergences: Default::default(),
+            visible: Default::default(),
+            axes_group: LinkedAxisGroup::new(false, false),
+            axis: Axis::Time,
+        }
+    }
+}
+
+impl IndicatorPanel {
+    pub fn new(axes_group: LinkedAxisGroup) -> Self {
+        Self {
+            axes_group,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_axes_group(&mut self, axes_group: LinkedAxisGroup) {
+        self.axes_group = axes_group;
+    }
+
+    pub fn set_axis(&mut self, axis: Axis) {
+        self.axis = axis;
+    }
+
+    /// Takes everything drawn in the panel from the output.
+    pub fn set_indicators(&mut self, output: &IndicatorOutput) {
+        self.series = output.panel.clone();
+        self.guides = output.guides.clone();
+        self.markers = output.panel_markers.clone();
+        self.divergences = output.divergences.clone();
+    }
+
+    /// Time range divergences are checked within.
+    pub fn set_visible(&mut self, visible: Bounds) {
+        self.visible = visible;
+    }
+
+    /// X range covered by the series.
+    fn x_range(&self) -> Option<(f64, f64)> {
+        let xs = self
+            .series
+            .iter()
+            .flat_map(|s| s.points.iter().map(|p| p[0]));
+        let (min, max) = xs.fold((f64::MAX, f64::MIN), |(min, max), x| {
+            (min.min(x), max.max(x))
+        });
+
+        (min <= max).then_some((min, max))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+}
+
+impl Widget for &IndicatorPanel {
+    fn ui(self, ui: &mut egui::Ui) -> Response {
+        plot_text(ui);
+        let (x_axis, label_axis) = (self.axis.clone(), self.axis.clone());
+        Plot::new("indicators")
+            .link_axis(self.axes_group.clone())
+            .x_axis_formatter(move |v: f64, _: &RangeInclusive<f64>| x_axis.format(v))
+            .label_formatter(move |name, v| {
+                format!("{name}\n{:.2}\n{}", v.y, label_axis.format(v.x))
+            })
+            .legend(Legend::default())
+            .allow_boxed_zoom(false)
+            .allow_scroll(false)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .show(ui, |plot_ui| {
+                if let Some((x_min, x_max)) = self.x_range() {
+                    self.guides.iter().for_each(|g| {
+                        [(g.upper, g.max), (g.min, g.lower)]
+                            .into_iter()
+                            .for_each(|(from, to)| {
+                                plot_ui.polygon(zone(x_min, x_max, from, to));
+                            });
+                        [g.lower, g.upper].into_iter().for_each(|y| {
+                            plot_ui.hline(
+                                HLine::new(y)
+                                    .style(LineStyle::dashed_loose())
+                                    .color(Color32::GRAY),
+                            );
+                        });
+                    });
+                }
+                self.series.iter().for_each(|s| plot_ui.line(line(s)));
+                [MarkerKind::Buy, MarkerKind::Sell]
+                    .into_iter()
+                    .for_each(|kind| plot_ui.points(dots(&self.markers, kind)));
+
+                let hints: Vec<String> = self
+                    .divergences
+                    .iter()
+                    .filter_map(|d| d.hint(self.visible))
+                    .collect();
+                if !hints.is_empty() {
+                    let bounds = plot_ui.plot_bounds();
+                    plot_ui.text(
+                        Text::new(
+                            Value::new(bounds.min()[0], bounds.max()[1]),
+                            RichText::new(hints.join("\n")).small(),
+                        )
+                        .anchor(Align2::LEFT_TOP)
+                        .color(Color32::YELLOW),
+                    );
+                }
+            })
+            .response
+    }
+}
+
+/// Panel of a single study linked with the candles by x axis.
+pub struct StudyPanel {
+    panel: SubPanel,
+    axes_group: LinkedAxisGroup,
+    axis: Axis,
+}
+
+impl StudyPanel {
+    pub fn new(panel: SubPanel, axes_group: LinkedAxisGroup, axis: Axis) -> Self {
+        Self {
+            panel,
+            axes_group,
+            axis,
+        }
+    }
+
+    pub fn set_axes_group(&mut self, axes_group: LinkedAxisGroup) {
+        self.axes_group = axes_group;
+    }
+}
+
+impl Widget for &StudyPanel {
+    fn ui(self, ui: &mut egui::Ui) -> Response {
+        plot_text(ui);
+        let (x_axis, label_axis) = (self.axis.clone(), self.axis.clone());
+        Plot::new(("study", &self.panel.name))
+            .link_axis(self.axes_group.clone())
+            .x_axis_formatter(move |v: f64, _: &RangeInclusive<f64>| x_axis.format(v))
+            .label_formatter(move |name, v| {
+                format!("{name}\n{:.2}\n{}", v.y, label_axis.format(v.x))
+            })
+            .legend(Legend::default())
+            .allow_boxed_zoom(false)
+            .allow_scroll(false)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .show(ui, |plot_ui| {
+                self.panel.guides.iter().for_each(|y| {
+                    plot_ui.hline(
+                        HLine::new(*y)
+                            .style(LineStyle::dashed_loose())
+                            .color(Color32::GRAY),
+                    );
+                });
+                self.panel
+                    .bars
+                    .iter()
+                    .for_each(|s| plot_ui.bar_chart(bars(s)));
+                let start = plot_ui.plot_bounds().min()[0];
+                self.panel
+                    .series
+                    .iter()
+                    .for_each(|s| match self.panel.rebase {
+                        Some(rebase) => plot_ui.line(line(&Series {
+                            points: math::rebase_returns(&s.points, start, rebase == Rebase::Log),
+                            ..s.clone()
+                        })),
+                        None => plot_ui.line(line(s)),
+                    });
+                self.panel.highlights.iter().for_each(|s| {
+                    plot_ui.line(line(s).color(Color32::LIGHT_RED).width(2.0));
+                });
+
+                if let Some(label) = &self.panel.label {
+                    let bounds = plot_ui.plot_bounds();
+                    plot_ui.text(
+                        Text::new(
+                            Value::new(bounds.min()[0], bounds.max()[1]),
+                            RichText::new(label).small(),
+                        )
+                        .anchor(Align2::LEFT_TOP)
+                        .color(Color32::LIGHT_RED),
+                    );
+                }
+            })
+            .response
+    }
+}
+
+pub fn line(series: &Series) -> Line {
+    let line = Line::new(values(&series.points)).name(&series.name);
+    match series.color {
+        Some([r, g, b]) => line.color(Color32::from_rgb(r, g, b)),
+        None => line,
+    }
+}
+
+/// Small dots of the series.
+pub fn scatter(series: &Series) -> Points {
+    let points = Points::new(values(&series.points))
+        .name(&series.name)
+        .shape(MarkerShape::Circle)
+        .filled(true)
+        .radius(1.5);
+    match series.color {
+        Some([r, g, b]) => points.color(Color32::from_rgb(r, g, b)),
+        None => points,
+    }
+}
+
+/// Bars of the values as wide as the gaps between them, green above zero and red below.
+pub fn bars(series: &Series) -> BarChart {
+    let width = series
+        .points
+        .windows(2)
+        .map(|w| w[1][0] - w[0][0])
+        .fold(f64::MAX, f64::min);
+    let width = match width < f64::MAX {
+        true => width * 0.8,
+        false => 1.0,
+    };
+    let bars = series
+        .points
+        .iter()
+        .map(|[x, y]| {
+            let color = match *y >= 0.0 {
+                true => Color32::LIGHT_GREEN,
+                false => Color32::LIGHT_RED,
+            };
+            Bar::new(*x, *y).width(width).fill(color)
+        })
+        .collect();
+
+    BarChart::new(bars).name(&series.name)
+}
+
+/// Filled area of the band; its edges should be drawn separately with [`line`].
+/// Only convex polygons are filled correctly, so edges sharing x coordinates
+/// are split into a quad per candle.
+pub fn band(band: &Band) -> Vec<Polygon> {
+    let (upper, lower) = (&band.upper.points, &band.lower.points);
+    let aligned = upper.len() == lower.len() && upper.iter().zip(lower).all(|(u, l)| u[0] == l[0]);
+    let polygons: Vec<Vec<[f64; 2]>> = match aligned {
+        true => (1..upper.len())
+            .map(|i| vec![upper[i - 1], upper[i], lower[i], lower[i - 1]])
+            .collect(),
+        false => vec![upper.iter().chain(lower.iter().rev()).copied().collect()],
+    };
+    let color = tone_color(band.tone);
+
+    polygons
+        .iter()
+        .map(|points| {
+            let polygon = Polygon::new(values(points))
+                .name(&band.name)
+                .fill_alpha(0.1)
+                .width(0.0);
+            match color {
+                Some(color) => polygon.color(color),
+                None => polygon,
+            }
+        })
+        .collect()
+}
+
+/// Line of the level with its name at the left end.
+pub fn level(level: &Level) -> (Line, Text) {
+    let color = tone_color(level.tone).unwrap_or(Color32::GRAY);
+    let line = Line::new(values(&[[level.x[0], level.y], [level.x[1], level.y]]))
+        .color(color)
+        .style(LineStyle::dashed_dense());
+    let label = Text::new(
+        Value::new(level.x[0], level.y),
+        RichText::new(&level.name).small(),
+    )
+    .anchor(Align2::LEFT_BOTTOM)
+    .color(color);
+
+    (line, label)
+}
+
+/// Small marks of the glyphs, one item per name so it shows up when hovered.
+pub fn glyphs(glyphs: &[Glyph]) -> Vec<Points> {
+    let mut names: Vec<&str> = vec![];
+    glyphs.iter().for_each(|g| {
+        if !names.contains(&g.name.as_str()) {
+            names.push(&g.name);
+        }
+    });
+
+    names
+        .into_iter()
+        .map(|name| {
+            let marks: Vec<&Glyph> = glyphs.iter().filter(|g| g.name == name).collect();
+            let (shape, color) = match marks[0].tone {
+                Tone::Bullish => (MarkerShape::Up, Color32::LIGHT_GREEN),
+                Tone::Bearish => (MarkerShape::Down, Color32::LIGHT_RED),
+                Tone::Neutral => (MarkerShape::Circle, Color32::GRAY),
+            };
+            Points::new(Values::from_values_iter(
+                marks.iter().map(|g| Value::new(g.point[0], g.point[1])),
+            ))
+            .name(name)
+            .shape(shape)
+            .color(color)
+            .filled(true)
+            .radius(3.0)
+        })
+        .collect()
+}
+
+fn tone_color(tone: Tone) -> Option<Color32> {
+    match tone {
+        Tone::Neutral => None,
+        Tone::Bullish => Some(Color32::LIGHT_GREEN),
+        Tone::Bearish => Some(Color32::LIGHT_RED),
+    }
+}
+
+fn zone(x_min: f64, x_max: f64, from: f64, to: f64) -> Polygon {
+    Polygon::new(values(&[
+        [x_min, from],
+        [x_max, from],
+        [x_max, to],
+        [x_min, to],
+    ]))
+    .color(Color32::GRAY)
+    .fill_alpha(0.1)
+    .width(0.0)
+}
+
+fn dots(markers: &[Marker], kind: MarkerKind) -> Points {
+    let color = match kind {
+        MarkerKind::Buy => Color32::GREEN,
+        MarkerKind::Sell => Color32::RED,
+    };
+
+    Points::new(values(
+        &markers
+            .iter()
+            .filter(|m| m.kind == kind)
+            .map(|m| m.point)
+            .collect::<Vec<_>>(),
+    ))
+    .shape(MarkerShape::Circle)
+    .color(color)
+    .filled(true)
+    .radius(3.0)
+}
+
+fn values(points: &[[f64; 2]]) -> Values {
+    Values::from_values_iter(points.iter().map(|p| Value::new(p[0], p[1])))
+}
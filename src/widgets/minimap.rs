@@ -0,0 +1,188 @@
+use egui::{
+    plot::{Line, Plot, Polygon, Value, Values},
+    Color32, Ui, Vec2,
+};
+
+use crate::netstrat::data::Data;
+
+/// Points of the close line at most, the line is only an overview.
+const MAX_POINTS: usize = 300;
+/// Distance from the viewport edge in points which grabs the edge rather than the viewport.
+const EDGE_POINTS: f64 = 6.0;
+
+/// Part of the viewport being dragged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Grab {
+    /// Whole viewport, `offset` from its start to the pointer.
+    Move {
+        offset: f64,
+    },
+    Start,
+    End,
+}
+
+impl Grab {
+    /// What a press at `x` grabs given the `edge` tolerance, `None` outside the viewport.
+    fn at(view: [f64; 2], x: f64, edge: f64) -> Option<Self> {
+        if (x - view[0]).abs() <= edge {
+            Some(Grab::Start)
+        } else if (x - view[1]).abs() <= edge {
+            Some(Grab::End)
+        } else if view[0] < x && x < view[1] {
+            Some(Grab::Move {
+                offset: x - view[0],
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Viewport with the grabbed part moved to `x`, edges can't cross each other.
+    fn drag(&self, view: [f64; 2], x: f64, min_width: f64) -> [f64; 2] {
+        match self {
+            Grab::Move { offset } => [x - offset, x - offset + view[1] - view[0]],
+            Grab::Start => [x.min(view[1] - min_width), view[1]],
+            Grab::End => [view[0], x.max(view[0] + min_width)],
+        }
+    }
+}
+
+/// Viewport of the same width centered at `x`.
+fn center(view: [f64; 2], x: f64) -> [f64; 2] {
+    let half = (view[1] - view[0]) / 2.0;
+    [x - half, x + half]
+}
+
+/// Last point of every of the equal chunks so that there are `max` points at most.
+fn downsample(points: &[[f64; 2]], max: usize) -> Vec<[f64; 2]> {
+    if max == 0 || points.is_empty() {
+        return vec![];
+    }
+
+    let chunk = points.len().div_ceil(max);
+    points
+        .chunks(chunk)
+        .filter_map(|c| c.last().copied())
+        .collect()
+}
+
+/// Strip with the close line of all the loaded data and the visible range over it.
+#[derive(Default)]
+pub struct Minimap {
+    line: Vec<[f64; 2]>,
+    /// Extent of the line, the viewport is clipped to it so that the plot bounds stay fitted to the line.
+    x: [f64; 2],
+    y: [f64; 2],
+    /// Smallest width of the viewport, a candle.
+    min_width: f64,
+    grab: Option<Grab>,
+}
+
+impl Minimap {
+    pub fn set_data(&mut self, data: &Data) {
+        let closes: Vec<[f64; 2]> = data
+            .vals
+            .iter()
+            .map(|k| [data.center(k), k.close as f64])
+            .collect();
+        self.line = downsample(&closes, MAX_POINTS);
+        self.x = [data.min_x(), data.max_x()];
+        self.y = self.line.iter().fold([f64::MAX, f64::MIN], |y, p| {
+            [y[0].min(p[1]), y[1].max(p[1])]
+        });
+        self.min_width = data
+            .vals
+            .first()
+            .map_or(0.0, |k| (k.t_close - k.t_open) as f64);
+    }
+
+    /// Draws the strip with the `view` range. Returns the range the viewport was dragged or clicked to.
+    pub fn ui(&mut self, ui: &mut Ui, view: [f64; 2]) -> Option<[f64; 2]> {
+        if self.line.is_empty() {
+            return None;
+        }
+
+        let (line, x, y) = (self.line.clone(), self.x, self.y);
+        let resp = Plot::new("minimap")
+            .set_margin_fraction(Vec2::new(0.0, 0.1))
+            .allow_boxed_zoom(false)
+            .allow_drag(false)
+            .allow_scroll(false)
+            .allow_zoom(false)
+            .show_axes([false, false])
+            .show_x(false)
+            .show_y(false)
+            .show(ui, |plot_ui| {
+                plot_ui.line(Line::new(values(&line)).color(Color32::GRAY));
+
+                let (x0, x1) = (view[0].max(x[0]), view[1].min(x[1]));
+                plot_ui.polygon(
+                    Polygon::new(values(&[[x0, y[0]], [x1, y[0]], [x1, y[1]], [x0, y[1]]]))
+                        .color(Color32::LIGHT_BLUE)
+                        .fill_alpha(0.2),
+                );
+
+                // plot x per screen point
+                let start = plot_ui.screen_from_plot(Value::new(view[0], 0.0));
+                let per_point = plot_ui.plot_from_screen(start + Vec2::X).x - view[0];
+                (per_point, plot_ui.pointer_coordinate().map(|p| p.x))
+            });
+        let (per_point, pointer) = resp.inner;
+        let x = pointer?;
+
+        if resp.response.drag_started() {
+            self.grab = Grab::at(view, x, EDGE_POINTS * per_point);
+        }
+        if resp.response.drag_released() {
+            self.grab = None;
+        }
+        if let Some(grab) = self.grab.filter(|_| resp.response.dragged()) {
+            return Some(grab.drag(view, x, self.min_width));
+        }
+        if resp.response.clicked() && Grab::at(view, x, 0.0).is_none() {
+            return Some(center(view, x));
+        }
+
+        None
+    }
+}
+
+fn values(points: &[[f64; 2]]) -> Values {
+    Values::from_values(points.iter().map(|p| Value::new(p[0], p[1])).collect())
+}
+
+#[cfg(test)]
+mod minimap_tests {
+    use super::*;
+
+    #[test]
+    fn test_downsample() {
+        let points: Vec<[f64; 2]> = (0..10).map(|i| [i as f64, i as f64]).collect();
+
+        assert_eq!(downsample(&points, 20).len(), 10);
+        assert_eq!(
+            downsample(&points, 4),
+            vec![[2.0, 2.0], [5.0, 5.0], [8.0, 8.0], [9.0, 9.0]]
+        );
+        assert!(downsample(&points, 0).is_empty());
+    }
+
+    #[test]
+    fn test_grab_and_drag() {
+        let view = [10.0, 20.0];
+
+        assert_eq!(Grab::at(view, 11.0, 2.0), Some(Grab::Start));
+        assert_eq!(Grab::at(view, 19.0, 2.0), Some(Grab::End));
+        assert_eq!(Grab::at(view, 30.0, 2.0), None);
+
+        let grab = Grab::at(view, 15.0, 2.0).unwrap();
+        assert_eq!(grab.drag(view, 25.0, 1.0), [20.0, 30.0]);
+        assert_eq!(Grab::Start.drag(view, 5.0, 1.0), [5.0, 20.0]);
+        assert_eq!(Grab::End.drag(view, 5.0, 1.0), [10.0, 11.0]);
+    }
+
+    #[test]
+    fn test_click_centers() {
+        assert_eq!(center([10.0, 20.0], 50.0), [45.0, 55.0]);
+    }
+}
@@ -1,11 +1,30 @@
+mod appearance;
 mod candles;
+mod context;
+mod delta;
 mod graph;
+mod indicator_panel;
+mod minimap;
+mod palette;
+mod quick_switch;
+mod repaint;
+mod signals;
+mod status;
 mod symbols;
 mod theme;
 mod time_input;
+mod toasts;
 mod volume;
+mod x_range;
 
+pub use self::appearance::{Appearance, PLOT_FONT_RANGE, SCALE_RANGE};
+pub use self::candles::{candle_boxes, copy_json_ui};
 pub use self::graph::Graph;
-pub use self::symbols::Symbols;
+pub use self::palette::Palette;
+pub use self::quick_switch::QuickSwitch;
+pub use self::status::{Status, StatusBar};
+pub use self::symbols::{Symbols, SymbolsFilter, SymbolsState};
 pub use self::theme::Theme;
 pub use self::time_input::TimeInput;
+pub use self::toasts::{Toast, Toasts};
+pub use self::volume::{volume_bars, VolumeKind};
@@ -0,0 +1,108 @@
+//! Command palette opened with ctrl+p listing the actions of the registry.
+
+use egui::{Align2, Context, Key, Layout, TextEdit, Window};
+
+use crate::netstrat::actions::Actions;
+
+/// Actions listed under the box.
+const MATCHES: usize = 12;
+
+#[derive(Default)]
+pub struct Palette {
+    open: bool,
+    /// Whether the box takes focus on the next frame.
+    focus: bool,
+    query: String,
+    /// Action run with enter.
+    highlighted: usize,
+}
+
+impl Palette {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.focus = true;
+        self.query.clear();
+        self.highlighted = 0;
+    }
+
+    /// Draws the box over the app with the matching actions, triggers the one picked with
+    /// enter or a click. Escape closes the box.
+    pub fn show(&mut self, ctx: &Context, actions: &Actions) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = true;
+        Window::new("commands")
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                let resp = ui.add(
+                    TextEdit::singleline(&mut self.query)
+                        .hint_text("command, e.g. settings")
+                        .desired_width(300.0),
+                );
+                if self.focus {
+                    resp.request_focus();
+                    self.focus = false;
+                }
+                if resp.changed() {
+                    self.highlighted = 0;
+                }
+
+                let matches = actions.search(&self.query, MATCHES);
+                let (escape, enter, up, down) = {
+                    let input = ui.input();
+                    (
+                        input.key_pressed(Key::Escape),
+                        input.key_pressed(Key::Enter),
+                        input.key_pressed(Key::ArrowUp),
+                        input.key_pressed(Key::ArrowDown),
+                    )
+                };
+                if down {
+                    self.highlighted = (self.highlighted + 1).min(matches.len().saturating_sub(1));
+                }
+                if up {
+                    self.highlighted = self.highlighted.saturating_sub(1);
+                }
+
+                let mut picked = None;
+                matches.iter().enumerate().for_each(|(idx, action)| {
+                    ui.add_enabled_ui(action.enabled, |ui| {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .selectable_label(idx == self.highlighted, &action.name)
+                                .clicked()
+                            {
+                                picked = Some(action.id.clone());
+                            }
+                            if let Some(shortcut) = action.shortcut {
+                                ui.with_layout(Layout::right_to_left(), |ui| {
+                                    ui.weak(shortcut.label());
+                                });
+                            }
+                        });
+                    });
+                });
+                if matches.is_empty() {
+                    ui.weak("no matches");
+                }
+                if enter && resp.lost_focus() {
+                    picked = picked.or_else(|| matches.get(self.highlighted).map(|a| a.id.clone()));
+                }
+
+                if let Some(id) = picked {
+                    // disabled actions can't run, the box stays open for another pick
+                    open = !actions.trigger(&id);
+                    self.focus = open;
+                }
+                if escape {
+                    open = false;
+                }
+            });
+        self.open = open;
+    }
+}
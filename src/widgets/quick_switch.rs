@@ -0,0 +1,88 @@
+//! Symbol search box opened with `/` over the chart.
+
+use egui::{Key, TextEdit, Ui};
+
+use crate::netstrat::symbol_index::SymbolIndex;
+
+/// Matches listed under the box.
+const MATCHES: usize = 8;
+
+#[derive(Default)]
+pub struct QuickSwitch {
+    open: bool,
+    /// Whether the box takes focus on the next frame.
+    focus: bool,
+    query: String,
+    /// Match picked with enter.
+    highlighted: usize,
+}
+
+impl QuickSwitch {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.focus = true;
+        self.query.clear();
+        self.highlighted = 0;
+    }
+
+    /// Draws the box with its matches, returns the symbol picked with enter or a click.
+    /// Escape closes the box.
+    pub fn ui(&mut self, ui: &mut Ui, index: &SymbolIndex) -> Option<String> {
+        if !self.open {
+            return None;
+        }
+
+        let resp = ui.add(
+            TextEdit::singleline(&mut self.query)
+                .hint_text("symbol, e.g. ethu")
+                .desired_width(200.0),
+        );
+        if self.focus {
+            resp.request_focus();
+            self.focus = false;
+        }
+        // the `/` opening the box may be typed into it as well
+        self.query.retain(|c| c != '/');
+        if resp.changed() {
+            self.highlighted = 0;
+        }
+
+        let matches = index.search(&self.query, MATCHES);
+        let (escape, enter, up, down) = {
+            let input = ui.input();
+            (
+                input.key_pressed(Key::Escape),
+                input.key_pressed(Key::Enter),
+                input.key_pressed(Key::ArrowUp),
+                input.key_pressed(Key::ArrowDown),
+            )
+        };
+        if down {
+            self.highlighted = (self.highlighted + 1).min(matches.len().saturating_sub(1));
+        }
+        if up {
+            self.highlighted = self.highlighted.saturating_sub(1);
+        }
+
+        let mut picked = None;
+        matches.iter().enumerate().for_each(|(idx, symbol)| {
+            if ui
+                .selectable_label(idx == self.highlighted, *symbol)
+                .clicked()
+            {
+                picked = Some(symbol.to_string());
+            }
+        });
+        if matches.is_empty() && !self.query.trim().is_empty() {
+            ui.weak("no matches");
+        }
+        if enter && resp.lost_focus() {
+            picked = picked.or_else(|| matches.get(self.highlighted).map(|s| s.to_string()));
+        }
+
+        if escape || picked.is_some() {
+            self.open = false;
+        }
+        picked
+    }
+}
@@ -0,0 +1,34 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use egui::Context;
+
+/// Repaint requested after a delay from a thread of its own, egui only repaints on input
+/// or when asked for the next frame. At most one is pending, so asking on every frame
+/// repaints once per delay.
+#[derive(Clone, Default)]
+pub struct DelayedRepaint {
+    pending: Arc<AtomicBool>,
+}
+
+impl DelayedRepaint {
+    pub fn request(&self, ctx: &Context, delay: Duration) {
+        if self.pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let ctx = ctx.clone();
+        let pending = self.pending.clone();
+        thread::spawn(move || {
+            thread::sleep(delay);
+            pending.store(false, Ordering::Release);
+            ctx.request_repaint();
+        });
+    }
+}
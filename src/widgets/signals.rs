@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+
+use egui::{
+    plot::{MarkerShape, Points, Value, Values},
+    Color32,
+};
+
+use crate::netstrat::{
+    axis::Axis,
+    data::Data,
+    indicators::{SignalKind, SignalSeries},
+};
+
+/// Signal placed on the plot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placed {
+    pub point: [f64; 2],
+    pub kind: SignalKind,
+    /// Label prefixed with the series name.
+    pub label: String,
+}
+
+/// Signal series by name. Signals are placed on candles once they change
+/// so drawing only picks the visible ones.
+#[derive(Default)]
+pub struct Signals {
+    series: BTreeMap<String, SignalSeries>,
+    /// Signals of all series sorted by x.
+    placed: Vec<Placed>,
+}
+
+impl Signals {
+    /// Adds the series replacing the one of the same name.
+    pub fn add(&mut self, series: SignalSeries) {
+        self.series.insert(series.name.clone(), series);
+    }
+
+    /// Removes the series returning whether there was one.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.series.remove(name).is_some()
+    }
+
+    /// Places signals on the candles on `axis` they fall into, ones outside of the candles
+    /// are skipped.
+    pub fn place(&mut self, data: &Data, axis: &Axis) {
+        let vals = &data.vals;
+        self.placed = self
+            .series
+            .values()
+            .flat_map(|s| s.points.iter().map(move |p| (s, p)))
+            .filter_map(|(s, p)| {
+                let ts = axis.locate(p.ts)?;
+                let k = &vals[data.index_of(ts)?];
+
+                let y = p.price.unwrap_or(match p.kind {
+                    SignalKind::Buy => k.low as f64,
+                    SignalKind::Sell | SignalKind::Neutral => k.high as f64,
+                });
+                Some(Placed {
+                    point: [data.center(k), y],
+                    kind: p.kind,
+                    label: match p.label.is_empty() {
+                        true => s.name.clone(),
+                        false => format!("{}: {}", s.name, p.label),
+                    },
+                })
+            })
+            .collect();
+        self.placed
+            .sort_by(|a, b| a.point[0].total_cmp(&b.point[0]));
+    }
+
+    /// Placed signals within the `x` range.
+    pub fn within(&self, x: [f64; 2]) -> &[Placed] {
+        let from = self.placed.partition_point(|p| p.point[0] < x[0]);
+        let to = self.placed.partition_point(|p| p.point[0] <= x[1]);
+
+        &self.placed[from..to.max(from)]
+    }
+
+    /// Marks of the signals within the `x` range, one item per kind.
+    pub fn points(&self, x: [f64; 2]) -> Vec<Points> {
+        let visible = self.within(x);
+        [SignalKind::Buy, SignalKind::Sell, SignalKind::Neutral]
+            .into_iter()
+            .map(|kind| {
+                let (name, shape, color) = match kind {
+                    SignalKind::Buy => ("buy signal", MarkerShape::Up, Color32::GREEN),
+                    SignalKind::Sell => ("sell signal", MarkerShape::Down, Color32::RED),
+                    SignalKind::Neutral => ("signal", MarkerShape::Circle, Color32::LIGHT_BLUE),
+                };
+                Points::new(Values::from_values(
+                    visible
+                        .iter()
+                        .filter(|p| p.kind == kind)
+                        .map(|p| Value::new(p.point[0], p.point[1]))
+                        .collect(),
+                ))
+                .name(name)
+                .shape(shape)
+                .color(color)
+                .filled(true)
+                .radius(5.0)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod signals_tests {
+    use crate::{netstrat::indicators::SignalPoint, sources::binance::Kline};
+
+    use super::*;
+
+    fn data() -> Data {
+        Data::new(
+            (0..3)
+                .map(|i| Kline {
+                    t_open: i * 10,
+                    t_close: i * 10 + 9,
+                    high: 20.0 + i as f32,
+                    low: 10.0 + i as f32,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn series(name: &str, points: &[(i64, SignalKind, Option<f64>)]) -> SignalSeries {
+        SignalSeries {
+            name: name.to_string(),
+            points: points
+                .iter()
+                .map(|(ts, kind, price)| SignalPoint {
+                    ts: *ts,
+                    kind: *kind,
+                    label: String::new(),
+                    price: *price,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_place() {
+        let mut signals = Signals::default();
+        signals.add(series(
+            "a",
+            &[
+                (25, SignalKind::Sell, None),
+                (3, SignalKind::Buy, None),
+                (12, SignalKind::Neutral, Some(15.0)),
+                (100, SignalKind::Buy, None),
+            ],
+        ));
+
+        signals.place(&data(), &Axis::Time);
+
+        assert_eq!(
+            signals
+                .within([f64::MIN, f64::MAX])
+                .iter()
+                .map(|p| p.point)
+                .collect::<Vec<_>>(),
+            vec![[5.0, 10.0], [15.0, 15.0], [25.0, 22.0]]
+        );
+        assert_eq!(signals.within([10.0, 20.0]).len(), 1);
+        assert_eq!(signals.within([20.0, 10.0]).len(), 0);
+    }
+
+    #[test]
+    fn test_place_on_index_axis() {
+        let mut signals = Signals::default();
+        signals.add(series("a", &[(25, SignalKind::Sell, None)]));
+        let (data, axis) = Axis::compress(&data());
+
+        signals.place(&data, &axis);
+
+        assert_eq!(signals.within([f64::MIN, f64::MAX])[0].point, [2.5, 22.0]);
+    }
+
+    #[test]
+    fn test_series_by_name() {
+        let mut signals = Signals::default();
+        signals.add(series("a", &[(3, SignalKind::Buy, None)]));
+        signals.add(series("b", &[(13, SignalKind::Buy, None)]));
+        signals.add(series("a", &[(23, SignalKind::Sell, None)]));
+        signals.place(&data(), &Axis::Time);
+
+        assert_eq!(
+            signals
+                .within([f64::MIN, f64::MAX])
+                .iter()
+                .map(|p| p.label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "a"]
+        );
+
+        assert!(signals.remove("a"));
+        assert!(!signals.remove("a"));
+        signals.place(&data(), &Axis::Time);
+        assert_eq!(signals.within([f64::MIN, f64::MAX]).len(), 1);
+    }
+}
@@ -0,0 +1,195 @@
+use crossbeam::channel::Receiver;
+use egui::{Color32, Label, RichText, ScrollArea, Sense, Ui, Window};
+
+use crate::{
+    netstrat::{bounds::Bounds, data::Data},
+    network::ws_server::Broadcaster,
+    sources::binance::Interval,
+};
+
+/// Data source all requests go to.
+const SOURCE: &str = "binance";
+/// Failed requests kept for the errors window.
+const MAX_ERRORS: usize = 50;
+
+/// What happened to the shown data, sent by the graph.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    /// Symbol was selected.
+    Symbol(String),
+    /// Shown candles changed.
+    Data {
+        interval: Interval,
+        /// Time range from the first open to the last close.
+        range: Option<Bounds>,
+        candles: usize,
+    },
+    /// Request succeeded at the time in millis.
+    Fetched(i64),
+    /// Request failed at the time in millis.
+    Failed(i64, String),
+}
+
+/// Source, symbol and loaded data of the graph with the outcome of the last request.
+pub struct StatusBar {
+    status_sub: Receiver<Status>,
+    /// Websocket server whose clients are counted while it runs.
+    broadcaster: Broadcaster,
+    symbol: Option<String>,
+    interval: Option<Interval>,
+    range: Option<Bounds>,
+    candles: usize,
+    /// Whether the last request succeeded, `None` before any.
+    ok: Option<bool>,
+    updated: Option<i64>,
+    /// Failed requests, the latest last.
+    errors: Vec<(i64, String)>,
+    errors_visible: bool,
+}
+
+impl StatusBar {
+    pub fn new(status_sub: Receiver<Status>, broadcaster: Broadcaster) -> Self {
+        Self {
+            status_sub,
+            broadcaster,
+            symbol: None,
+            interval: None,
+            range: None,
+            candles: 0,
+            ok: None,
+            updated: None,
+            errors: Default::default(),
+            errors_visible: false,
+        }
+    }
+
+    fn apply(&mut self, status: Status) {
+        match status {
+            Status::Symbol(symbol) => {
+                self.symbol = Some(symbol);
+                self.range = None;
+                self.candles = 0;
+            }
+            Status::Data {
+                interval,
+                range,
+                candles,
+            } => {
+                self.interval = Some(interval);
+                self.range = range;
+                self.candles = candles;
+            }
+            Status::Fetched(ts) => {
+                self.ok = Some(true);
+                self.updated = Some(ts);
+            }
+            Status::Failed(ts, msg) => {
+                self.ok = Some(false);
+                self.errors.push((ts, msg));
+                let excess = self.errors.len().saturating_sub(MAX_ERRORS);
+                self.errors.drain(..excess);
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        self.status_sub
+            .try_iter()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .for_each(|s| self.apply(s));
+
+        let (color, hint) = match self.ok {
+            Some(true) => (Color32::GREEN, "last request succeeded"),
+            Some(false) => (Color32::RED, "last request failed, click to see errors"),
+            None => (Color32::GRAY, "no requests yet"),
+        };
+        if ui
+            .add(Label::new(RichText::new("●").color(color)).sense(Sense::click()))
+            .on_hover_text(hint)
+            .clicked()
+        {
+            self.errors_visible = !self.errors_visible;
+        }
+        ui.label(SOURCE);
+        if self.broadcaster.is_listening() {
+            ui.separator();
+            ui.label(format!("ws {}", self.broadcaster.connections()))
+                .on_hover_text("websocket clients connected");
+        }
+
+        if let Some(symbol) = &self.symbol {
+            ui.separator();
+            match self.interval {
+                Some(interval) => ui.label(format!("{symbol} {}", interval.as_str())),
+                None => ui.label(symbol),
+            };
+        }
+        if let Some(Bounds(start, end)) = self.range {
+            ui.label(format!(
+                "{} - {}",
+                Data::format_ts(start as f64),
+                Data::format_ts(end as f64)
+            ));
+        }
+        if self.symbol.is_some() {
+            ui.label(format!("{} candles", self.candles));
+        }
+        if let Some(ts) = self.updated {
+            ui.separator();
+            ui.label(format!("updated {}", Data::format_ts(ts as f64)));
+        }
+
+        self.errors_ui(ui);
+    }
+
+    fn errors_ui(&mut self, ui: &mut Ui) {
+        let errors = &self.errors;
+        Window::new("request errors")
+            .open(&mut self.errors_visible)
+            .default_width(400.0)
+            .show(ui.ctx(), |ui| {
+                if errors.is_empty() {
+                    ui.label("no failed requests");
+                    return;
+                }
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    errors.iter().rev().for_each(|(ts, msg)| {
+                        ui.label(format!("{} {msg}", Data::format_ts(*ts as f64)));
+                    });
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod status_tests {
+    use crossbeam::channel::unbounded;
+
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let (_, r) = unbounded();
+        let mut bar = StatusBar::new(r, Default::default());
+
+        bar.apply(Status::Symbol("BTCUSDT".to_string()));
+        bar.apply(Status::Data {
+            interval: Interval::Hour,
+            range: Some(Bounds(0, 10)),
+            candles: 2,
+        });
+        bar.apply(Status::Fetched(5));
+        assert_eq!(bar.ok, Some(true));
+        assert_eq!((bar.candles, bar.updated), (2, Some(5)));
+
+        (0..MAX_ERRORS + 1).for_each(|i| bar.apply(Status::Failed(i as i64, "timeout".into())));
+        assert_eq!(bar.ok, Some(false));
+        assert_eq!(bar.errors.len(), MAX_ERRORS);
+        assert_eq!(bar.errors[0].0, 1);
+        assert_eq!(bar.updated, Some(5));
+
+        bar.apply(Status::Symbol("ETHUSDT".to_string()));
+        assert_eq!((bar.range, bar.candles), (None, 0));
+    }
+}
@@ -1,47 +1,469 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
 use crossbeam::channel::{unbounded, Sender};
-use egui::{Label, Layout, Response, ScrollArea, TextEdit, Widget, WidgetText};
+use egui::{Color32, Label, Layout, Response, ScrollArea, TextEdit, Widget, WidgetText};
 use poll_promise::Promise;
+use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
-use crate::sources::binance::{Client, Info, Symbol};
+use crate::{
+    errors::Result,
+    netstrat::{
+        channels::{self, LatestSender},
+        launch,
+        selection::{Selection, Source},
+        symbol_index::SymbolIndex,
+        watchlist::Watchlist,
+    },
+    sources::binance::{Client, Info, Symbol},
+};
+
+use super::toasts::Toast;
+
+const AUTO_REFRESH_PERIOD: Duration = Duration::from_secs(60 * 60);
+/// How long newly listed symbols stay highlighted.
+const NEW_HIGHLIGHT_PERIOD: Duration = Duration::from_secs(30 * 60);
+/// Checked symbols opened in new windows at once above it are confirmed first.
+const OPEN_CONFIRM_ABOVE: usize = 6;
+/// Delay between the instances opened for the checked symbols so their first downloads
+/// don't start at once, each gets a smaller part of the request weight budget.
+const OPEN_STAGGER: Duration = Duration::from_secs(3);
+
+/// Symbols of the spot api grouped by trading type.
+/// Futures symbols live in a separate api which the data source doesn't support yet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Market {
+    #[default]
+    All,
+    Spot,
+    Margin,
+}
+
+impl Market {
+    fn applies(&self, s: &Symbol) -> bool {
+        match self {
+            Market::All => true,
+            Market::Spot => s.is_spot_trading_allowed,
+            Market::Margin => s.is_margin_trading_allowed,
+        }
+    }
+}
 
-#[derive(Default)]
-struct FilterProps {
+/// Filter of the symbols list, everything but the typed value is persisted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SymbolsFilter {
+    #[serde(skip)]
     value: String,
     active_only: bool,
+    /// Match the value against base asset instead of the symbol name.
+    by_base_asset: bool,
+    market: Market,
+}
+
+impl SymbolsFilter {
+    fn matches(&self, s: &Symbol) -> bool {
+        let value = self.value.to_lowercase();
+        let match_value = match self.by_base_asset {
+            true => value.is_empty() || s.base_asset.to_lowercase() == value,
+            false => s.symbol.to_lowercase().contains(value.as_str()),
+        };
+
+        match_value && self.market.applies(s) && (!self.active_only || s.active())
+    }
+}
+
+/// Part of the symbols window persisted between app runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SymbolsState {
+    pub filter: SymbolsFilter,
+    pub favorites: BTreeSet<String>,
+    pub auto_refresh: bool,
+}
+
+/// Result of merging freshly fetched symbols into the current list.
+#[derive(Debug, Default, PartialEq)]
+struct Merged {
+    symbols: Vec<Symbol>,
+    listed: Vec<String>,
+    /// Delisted favorites which are kept in the list.
+    stale: HashSet<String>,
+}
+
+/// Replaces `current` symbols with `fetched` ones keeping delisted favorites as stale.
+/// Fetched order is kept, stale symbols go last.
+fn merge(current: &[Symbol], fetched: Vec<Symbol>, favorites: &BTreeSet<String>) -> Merged {
+    let fetched_names: HashSet<&str> = fetched.iter().map(|s| s.symbol.as_str()).collect();
+    let current_names: HashSet<&str> = current.iter().map(|s| s.symbol.as_str()).collect();
+
+    let listed = match current.is_empty() {
+        // first load lists nothing new
+        true => vec![],
+        false => fetched
+            .iter()
+            .filter(|s| !current_names.contains(s.symbol.as_str()))
+            .map(|s| s.symbol.clone())
+            .collect(),
+    };
+    let delisted_favorites: Vec<Symbol> = current
+        .iter()
+        .filter(|s| !fetched_names.contains(s.symbol.as_str()) && favorites.contains(&s.symbol))
+        .cloned()
+        .collect();
+
+    let stale = delisted_favorites
+        .iter()
+        .map(|s| s.symbol.clone())
+        .collect();
+    let mut symbols = fetched;
+    symbols.extend(delisted_favorites);
+
+    Merged {
+        symbols,
+        listed,
+        stale,
+    }
 }
 
 pub struct Symbols {
     symbols: Vec<Symbol>,
-    filter: FilterProps,
+    /// Symbols of the market filter for the quick switch.
+    index: SymbolIndex,
+    filter: SymbolsFilter,
+    favorites: BTreeSet<String>,
+    auto_refresh: bool,
+    last_refresh: Instant,
+    listed_at: HashMap<String, Instant>,
+    stale: HashSet<String>,
     loading: bool,
     selected_symbol: String,
-    symbols_promise: Option<Promise<Info>>,
-    symbol_pub: Sender<String>,
+    /// Symbols checked for bulk actions, they stay checked while filtered out.
+    checked: BTreeSet<String>,
+    /// Whether opening the checked symbols waits for confirmation.
+    confirm_open: bool,
+    /// Checked symbols to be exported by the graph.
+    export_requested: Option<Vec<String>>,
+    watchlist: Watchlist,
+    symbols_promise: Option<Promise<Result<Info>>>,
+    symbol_pub: LatestSender<Selection>,
+    toast_pub: Sender<Toast>,
 }
 
 impl Default for Symbols {
     fn default() -> Self {
-        let (s, _) = unbounded();
+        let (s, _) = channels::latest();
+        let (s_toasts, _) = unbounded();
         Self {
             symbols: Default::default(),
+            index: Default::default(),
             filter: Default::default(),
+            favorites: Default::default(),
+            auto_refresh: Default::default(),
+            last_refresh: Instant::now(),
+            listed_at: Default::default(),
+            stale: Default::default(),
             loading: Default::default(),
             selected_symbol: Default::default(),
+            checked: Default::default(),
+            confirm_open: false,
+            export_requested: None,
+            watchlist: Default::default(),
             symbols_promise: Default::default(),
             symbol_pub: s,
+            toast_pub: s_toasts,
         }
     }
 }
 
 impl Symbols {
-    pub fn new(symbol_pub: Sender<String>) -> Self {
-        Self {
+    pub fn new(
+        symbol_pub: LatestSender<Selection>,
+        toast_pub: Sender<Toast>,
+        watchlist: Watchlist,
+        state: SymbolsState,
+    ) -> Self {
+        let mut symbols = Self {
             loading: true,
-            symbols_promise: Some(Promise::spawn_async(async { Client::info().await })),
+            filter: state.filter,
+            favorites: state.favorites,
+            auto_refresh: state.auto_refresh,
+            watchlist,
             symbol_pub,
+            toast_pub,
             ..Default::default()
+        };
+        symbols.refresh();
+
+        symbols
+    }
+
+    pub fn favorites(&self) -> &BTreeSet<String> {
+        &self.favorites
+    }
+
+    pub fn state(&self) -> SymbolsState {
+        SymbolsState {
+            filter: self.filter.clone(),
+            favorites: self.favorites.clone(),
+            auto_refresh: self.auto_refresh,
+        }
+    }
+
+    /// Refetches symbols in background keeping the current list until the new one arrives.
+    fn refresh(&mut self) {
+        if self.symbols_promise.is_some() {
+            return;
+        }
+
+        info!("Refreshing symbols...");
+        self.last_refresh = Instant::now();
+        self.symbols_promise = Some(Promise::spawn_async(async {
+            Client::default().info().await
+        }));
+    }
+
+    fn apply(&mut self, fetched: Vec<Symbol>) {
+        let merged = merge(&self.symbols, fetched, &self.favorites);
+        info!(
+            "Got {} symbols, {} newly listed, {} stale.",
+            merged.symbols.len(),
+            merged.listed.len(),
+            merged.stale.len()
+        );
+
+        if !merged.listed.is_empty() {
+            let msg = format!("new symbols listed: {}", merged.listed.join(", "));
+            if let Err(err) = self.toast_pub.send(Toast::info(msg)) {
+                error!("Failed to send toast: {err}.");
+            }
         }
+
+        let now = Instant::now();
+        self.listed_at
+            .retain(|_, at| now.duration_since(*at) < NEW_HIGHLIGHT_PERIOD);
+        merged.listed.into_iter().for_each(|s| {
+            self.listed_at.insert(s, now);
+        });
+        self.symbols = merged.symbols;
+        self.stale = merged.stale;
+        self.reindex();
+    }
+
+    fn reindex(&mut self) {
+        self.index = SymbolIndex::new(
+            self.symbols
+                .iter()
+                .filter(|s| self.filter.market.applies(s))
+                .map(|s| s.symbol.as_str()),
+            &self.favorites,
+        );
+    }
+
+    pub fn index(&self) -> &SymbolIndex {
+        &self.index
+    }
+
+    /// Publishes the symbol picked by name elsewhere than in the list.
+    pub fn select(&mut self, symbol: &str) {
+        match self.symbols.iter().find(|s| s.symbol == symbol).cloned() {
+            Some(s) => self.publish(&s, false),
+            None => error!("Selected unknown symbol {symbol}."),
+        }
+    }
+
+    fn is_new(&self, symbol: &str) -> bool {
+        self.listed_at
+            .get(symbol)
+            .is_some_and(|at| at.elapsed() < NEW_HIGHLIGHT_PERIOD)
+    }
+
+    fn label_text(&self, s: &Symbol) -> WidgetText {
+        let text = WidgetText::from(s.symbol.to_string());
+        if self.stale.contains(&s.symbol) {
+            return WidgetText::from(format!("{} (delisted)", s.symbol))
+                .strikethrough()
+                .weak();
+        }
+        if self.is_new(&s.symbol) {
+            return text.strong().color(Color32::LIGHT_GREEN);
+        }
+
+        match s.active() {
+            true => text.strong(),
+            false => text.strikethrough(),
+        }
+    }
+
+    fn toast(&self, toast: Toast) {
+        if let Err(err) = self.toast_pub.send(toast) {
+            error!("Failed to send toast: {err}.");
+        }
+    }
+
+    /// Adds the checked symbols to favorites.
+    fn favorite_checked(&mut self) {
+        self.favorites.extend(self.checked.iter().cloned());
+        self.reindex();
+    }
+
+    /// Asks the graph to export the checked symbols with the range and format of its
+    /// export options.
+    fn export_checked(&mut self) {
+        info!("Exporting {} symbols.", self.checked.len());
+        self.export_requested = Some(self.checked.iter().cloned().collect());
+    }
+
+    /// Checked symbols to be exported, once.
+    pub fn take_export(&mut self) -> Option<Vec<String>> {
+        self.export_requested.take()
+    }
+
+    /// Adds the checked symbols to the watchlist, returning how many weren't watched yet.
+    fn watch_checked(&self) -> usize {
+        self.watchlist.extend(self.checked.iter().cloned())
+    }
+
+    /// Opens every checked symbol in a new window, [`OPEN_STAGGER`] apart. More than
+    /// [`OPEN_CONFIRM_ABOVE`] wait for confirmation unless `confirmed`, returns whether they
+    /// do.
+    fn open_checked(&mut self, confirmed: bool) -> bool {
+        if !confirmed && self.checked.len() > OPEN_CONFIRM_ABOVE {
+            self.confirm_open = true;
+            return true;
+        }
+
+        self.confirm_open = false;
+        let symbols: Vec<String> = self.checked.iter().cloned().collect();
+        let toast_pub = self.toast_pub.clone();
+        info!("Opening {} symbols in new windows.", symbols.len());
+        std::thread::spawn(move || {
+            symbols.iter().enumerate().for_each(|(i, symbol)| {
+                if i > 0 {
+                    std::thread::sleep(OPEN_STAGGER);
+                }
+                match launch::spawn_symbol(symbol) {
+                    Ok(child) => info!("Opened {symbol} in process {}.", child.id()),
+                    Err(err) => {
+                        error!("Failed to open {symbol} in new window: {}.", err.chain());
+                        if let Err(err) = toast_pub.send(Toast::error(err.user_message())) {
+                            error!("Failed to send toast: {err}.");
+                        }
+                    }
+                }
+            });
+        });
+
+        false
+    }
+
+    /// Bulk actions of the checked symbols.
+    fn checked_ui(&mut self, ui: &mut egui::Ui, filtered: &[Symbol]) {
+        ui.horizontal_wrapped(|ui| {
+            if ui
+                .small_button("check shown")
+                .on_hover_text("check the symbols matching the filter")
+                .clicked()
+            {
+                self.checked
+                    .extend(filtered.iter().map(|s| s.symbol.clone()));
+            }
+            if self.checked.is_empty() {
+                return;
+            }
+
+            let hidden = self
+                .checked
+                .iter()
+                .filter(|c| !filtered.iter().any(|s| &s.symbol == *c))
+                .count();
+            ui.label(
+                WidgetText::from(match hidden {
+                    0 => format!("{} checked", self.checked.len()),
+                    _ => format!("{} checked, {hidden} filtered out", self.checked.len()),
+                })
+                .small(),
+            );
+            if ui
+                .small_button("★ all")
+                .on_hover_text("add the checked symbols to favorites")
+                .clicked()
+            {
+                self.favorite_checked();
+            }
+            if ui
+                .small_button("export")
+                .on_hover_text(
+                    "export the checked symbols with the range and format of the export options",
+                )
+                .clicked()
+            {
+                self.export_checked();
+            }
+            if ui
+                .small_button("watch")
+                .on_hover_text("add the checked symbols to the watchlist")
+                .clicked()
+            {
+                let added = self.watch_checked();
+                self.toast(Toast::info(format!(
+                    "{added} symbols added to the watchlist"
+                )));
+            }
+            if ui
+                .small_button("open")
+                .on_hover_text("open each checked symbol in a new window")
+                .clicked()
+            {
+                self.open_checked(false);
+            }
+            if ui.small_button("clear").clicked() {
+                self.checked.clear();
+                self.confirm_open = false;
+            }
+        });
+
+        if self.confirm_open {
+            ui.horizontal_wrapped(|ui| {
+                ui.colored_label(
+                    Color32::GOLD,
+                    format!("open {} new windows?", self.checked.len()),
+                );
+                if ui.small_button("open").clicked() {
+                    self.open_checked(true);
+                }
+                if ui.small_button("cancel").clicked() {
+                    self.confirm_open = false;
+                }
+            });
+        }
+    }
+
+    /// Sends the symbol unless it is the selected one and the selection isn't forced.
+    fn publish(&mut self, s: &Symbol, force: bool) {
+        if !self.filter.market.applies(s) {
+            info!(
+                "Not publishing symbol {} outside of {:?} market.",
+                s.symbol, self.filter.market
+            );
+            return;
+        }
+
+        let selection = match force {
+            true => Selection::reload(&s.symbol),
+            false => Selection::new(&s.symbol, Source::List),
+        };
+        if selection.is_redundant(&self.selected_symbol) {
+            return;
+        }
+
+        self.symbol_pub.send(selection);
+        info!("Sent symbol: {}.", s.symbol);
+
+        self.selected_symbol = s.symbol.clone();
     }
 }
 
@@ -51,14 +473,26 @@ impl Widget for &mut Symbols {
             if let Some(result) = promise.ready() {
                 self.loading = false;
 
-                self.symbols = result
-                    .symbols
-                    .iter()
-                    .map(|s| -> Symbol { s.clone() })
-                    .collect();
+                match result {
+                    Ok(info) => {
+                        let fetched = info.symbols.to_vec();
+                        self.apply(fetched);
+                    }
+                    Err(err) => {
+                        error!("Failed to get symbols: {}.", err.chain());
+                        if let Err(err) = self.toast_pub.send(Toast::error(err.user_message())) {
+                            error!("Failed to send toast: {err}.");
+                        }
+                    }
+                }
+                self.symbols_promise = None;
             }
         }
 
+        if self.auto_refresh && self.last_refresh.elapsed() >= AUTO_REFRESH_PERIOD {
+            self.refresh();
+        }
+
         if self.loading {
             return ui
                 .centered_and_justified(|ui| {
@@ -69,31 +503,61 @@ impl Widget for &mut Symbols {
 
         ui.with_layout(Layout::top_down(egui::Align::LEFT), |ui| {
             ui.add(
-                TextEdit::singleline(&mut self.filter.value)
-                    .hint_text(WidgetText::from("filter symbols").italics()),
+                TextEdit::singleline(&mut self.filter.value).hint_text(
+                    WidgetText::from(match self.filter.by_base_asset {
+                        true => "base asset, e.g. SOL",
+                        false => "filter symbols",
+                    })
+                    .italics(),
+                ),
             );
 
-            let filtered: Vec<&Symbol> = self
+            let market = self.filter.market;
+            ui.horizontal_wrapped(|ui| {
+                [Market::All, Market::Spot, Market::Margin]
+                    .into_iter()
+                    .for_each(|m| {
+                        ui.selectable_value(
+                            &mut self.filter.market,
+                            m,
+                            format!("{m:?}").to_lowercase(),
+                        );
+                    });
+            });
+            if market != self.filter.market {
+                self.reindex();
+            }
+
+            let filtered: Vec<Symbol> = self
                 .symbols
                 .iter()
-                .filter(|s| {
-                    let match_value = s
-                        .symbol
-                        .to_lowercase()
-                        .contains(self.filter.value.to_lowercase().as_str());
-                    if self.filter.active_only {
-                        return match_value && s.active();
-                    }
-                    match_value
-                })
+                .filter(|s| self.filter.matches(s))
+                .cloned()
                 .collect();
             ui.with_layout(Layout::top_down(egui::Align::RIGHT), |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.auto_refresh, "hourly")
+                        .on_hover_text("refresh symbols every hour");
+                    match self.symbols_promise.is_some() {
+                        true => {
+                            ui.spinner();
+                        }
+                        false => {
+                            if ui.button("⟳").on_hover_text("refresh symbols").clicked() {
+                                self.refresh();
+                            }
+                        }
+                    }
+                });
                 ui.checkbox(&mut self.filter.active_only, "active only");
+                ui.checkbox(&mut self.filter.by_base_asset, "by base asset");
                 ui.add(Label::new(
                     WidgetText::from(format!("{}/{}", filtered.len(), self.symbols.len())).small(),
                 ));
             });
 
+            self.checked_ui(ui, &filtered);
+
             ui.add_space(5f32);
 
             ScrollArea::vertical()
@@ -102,27 +566,45 @@ impl Widget for &mut Symbols {
                 .show(ui, |ui| {
                     ui.with_layout(Layout::top_down(egui::Align::LEFT), |ui| {
                         filtered.iter().for_each(|s| {
-                            let label = ui.selectable_label(
-                                s.symbol == self.selected_symbol,
-                                match s.active() {
-                                    true => WidgetText::from(s.symbol.to_string()).strong(),
-                                    false => WidgetText::from(s.symbol.to_string()).strikethrough(),
-                                },
-                            );
-
-                            if label.clicked() {
-                                let send_result = self.symbol_pub.send(s.symbol.clone());
-                                match send_result {
-                                    Ok(_) => {
-                                        info!("Sent symbol: {}.", s.symbol);
-                                    }
-                                    Err(err) => {
-                                        error!("Failed to send symbol: {err}.");
-                                    }
+                            ui.horizontal(|ui| {
+                                let mut checked = self.checked.contains(&s.symbol);
+                                if ui.checkbox(&mut checked, "").changed() {
+                                    match checked {
+                                        true => self.checked.insert(s.symbol.clone()),
+                                        false => self.checked.remove(&s.symbol),
+                                    };
                                 }
 
-                                self.selected_symbol = s.symbol.clone();
-                            };
+                                let favorite = self.favorites.contains(&s.symbol);
+                                if ui
+                                    .small_button(match favorite {
+                                        true => "★",
+                                        false => "☆",
+                                    })
+                                    .clicked()
+                                {
+                                    match favorite {
+                                        true => self.favorites.remove(&s.symbol),
+                                        false => self.favorites.insert(s.symbol.clone()),
+                                    };
+                                    self.reindex();
+                                }
+
+                                let selected = s.symbol == self.selected_symbol;
+                                let label = ui.selectable_label(selected, self.label_text(s));
+
+                                if label.clicked() {
+                                    self.publish(s, false);
+                                };
+                                if selected
+                                    && ui
+                                        .small_button("↻")
+                                        .on_hover_text(format!("reload {}", s.symbol))
+                                        .clicked()
+                                {
+                                    self.publish(s, true);
+                                }
+                            });
                         });
                     })
                 });
@@ -130,3 +612,237 @@ impl Widget for &mut Symbols {
         .response
     }
 }
+
+#[cfg(test)]
+mod symbols_tests {
+    use super::*;
+
+    fn symbol(name: &str, base: &str, spot: bool, margin: bool) -> Symbol {
+        Symbol {
+            symbol: name.to_string(),
+            status: "TRADING".to_string(),
+            base_asset: base.to_string(),
+            is_spot_trading_allowed: spot,
+            is_margin_trading_allowed: margin,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_by_base_asset() {
+        let filter = SymbolsFilter {
+            value: "sol".to_string(),
+            by_base_asset: true,
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&symbol("SOLUSDT", "SOL", true, false)));
+        assert!(filter.matches(&symbol("SOLBTC", "SOL", true, false)));
+        assert!(!filter.matches(&symbol("SOLOUSDT", "SOLO", true, false)));
+        assert!(!filter.matches(&symbol("USDTSOL", "USDT", true, false)));
+    }
+
+    #[test]
+    fn test_filter_by_market() {
+        let spot_only = symbol("ABCUSDT", "ABC", true, false);
+        let margin = symbol("BTCUSDT", "BTC", true, true);
+        let mut filter = SymbolsFilter::default();
+
+        assert!(filter.matches(&spot_only) && filter.matches(&margin));
+
+        filter.market = Market::Margin;
+        assert!(!filter.matches(&spot_only));
+        assert!(filter.matches(&margin));
+    }
+
+    #[test]
+    fn test_publish_skips_other_market() {
+        let (s, r) = channels::latest();
+        let (s_toasts, _) = unbounded();
+        let mut symbols = Symbols {
+            symbol_pub: s,
+            toast_pub: s_toasts,
+            filter: SymbolsFilter {
+                market: Market::Margin,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        symbols.publish(&symbol("ABCUSDT", "ABC", true, false), false);
+        symbols.publish(&symbol("BTCUSDT", "BTC", true, true), false);
+
+        assert_eq!(
+            r.try_iter().map(|s| s.symbol).collect::<Vec<_>>(),
+            vec!["BTCUSDT"]
+        );
+    }
+
+    #[test]
+    fn test_select_by_name() {
+        let (s, r) = channels::latest();
+        let (s_toasts, _) = unbounded();
+        let mut symbols = Symbols {
+            symbol_pub: s,
+            toast_pub: s_toasts,
+            ..Default::default()
+        };
+        symbols.apply(vec![
+            symbol("ETHUSDT", "ETH", true, true),
+            symbol("ETHBTC", "ETH", true, false),
+        ]);
+
+        assert_eq!(symbols.index().search("ethu", 5), vec!["ETHUSDT"]);
+        symbols.select("ETHUSDT");
+        symbols.select("NOPE");
+        assert_eq!(
+            r.try_iter().map(|s| s.symbol).collect::<Vec<_>>(),
+            vec!["ETHUSDT"]
+        );
+
+        symbols.filter.market = Market::Margin;
+        symbols.reindex();
+        assert!(symbols.index().search("ethb", 5).is_empty());
+    }
+
+    #[test]
+    fn test_publish_skips_selected() {
+        let (s, r) = channels::latest();
+        let (s_toasts, _) = unbounded();
+        let mut symbols = Symbols {
+            symbol_pub: s,
+            toast_pub: s_toasts,
+            ..Default::default()
+        };
+        let btc = symbol("BTCUSDT", "BTC", true, true);
+
+        symbols.publish(&btc, false);
+        assert_eq!(r.try_recv(), Ok(Selection::new("BTCUSDT", Source::List)));
+
+        symbols.publish(&btc, false);
+        assert!(r.try_recv().is_err());
+
+        symbols.publish(&btc, true);
+        assert_eq!(r.try_recv(), Ok(Selection::reload("BTCUSDT")));
+    }
+
+    fn names(symbols: &[Symbol]) -> Vec<&str> {
+        symbols.iter().map(|s| s.symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn test_merge_first_load() {
+        let fetched = vec![symbol("BTCUSDT", "BTC", true, true)];
+
+        let merged = merge(&[], fetched.clone(), &BTreeSet::new());
+
+        assert_eq!(
+            merged,
+            Merged {
+                symbols: fetched,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_listed_and_delisted() {
+        let current = vec![
+            symbol("BTCUSDT", "BTC", true, true),
+            symbol("ABCUSDT", "ABC", true, false),
+            symbol("XYZUSDT", "XYZ", true, false),
+        ];
+        let fetched = vec![
+            symbol("BTCUSDT", "BTC", true, true),
+            symbol("NEWUSDT", "NEW", true, false),
+        ];
+        let favorites = BTreeSet::from(["XYZUSDT".to_string()]);
+
+        let merged = merge(&current, fetched, &favorites);
+
+        assert_eq!(
+            names(&merged.symbols),
+            vec!["BTCUSDT", "NEWUSDT", "XYZUSDT"]
+        );
+        assert_eq!(merged.listed, vec!["NEWUSDT"]);
+        assert_eq!(merged.stale, HashSet::from(["XYZUSDT".to_string()]));
+    }
+
+    #[test]
+    fn test_apply_keeps_filter_and_highlights_new() {
+        let (s_toasts, r_toasts) = unbounded();
+        let mut symbols = Symbols {
+            toast_pub: s_toasts,
+            filter: SymbolsFilter {
+                value: "usdt".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        symbols.apply(vec![symbol("BTCUSDT", "BTC", true, true)]);
+        symbols.apply(vec![
+            symbol("BTCUSDT", "BTC", true, true),
+            symbol("NEWUSDT", "NEW", true, false),
+        ]);
+
+        assert_eq!(symbols.filter.value, "usdt");
+        assert!(symbols.is_new("NEWUSDT"));
+        assert!(!symbols.is_new("BTCUSDT"));
+        assert_eq!(r_toasts.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn test_bulk_actions_of_checked() {
+        let mut symbols = Symbols::default();
+        symbols.apply(vec![
+            symbol("BTCUSDT", "BTC", true, true),
+            symbol("ETHUSDT", "ETH", true, true),
+        ]);
+        symbols.checked = BTreeSet::from(["ETHUSDT".to_string(), "BTCUSDT".to_string()]);
+        // checked symbols stay checked while filtered out
+        symbols.filter.value = "eth".to_string();
+
+        symbols.favorite_checked();
+        assert_eq!(symbols.favorites.len(), 2);
+        assert_eq!(symbols.index().search("btc", 5), vec!["BTCUSDT"]);
+
+        symbols.export_checked();
+        assert_eq!(
+            symbols.take_export(),
+            Some(vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()])
+        );
+        assert_eq!(symbols.take_export(), None);
+
+        symbols.watchlist.extend(["BTCUSDT".to_string()]);
+        assert_eq!(symbols.watch_checked(), 1);
+        assert_eq!(symbols.watchlist.symbols(), vec!["BTCUSDT", "ETHUSDT"]);
+    }
+
+    #[test]
+    fn test_open_many_checked_waits_for_confirmation() {
+        let mut symbols = Symbols {
+            checked: (0..=OPEN_CONFIRM_ABOVE)
+                .map(|i| format!("S{i}USDT"))
+                .collect(),
+            ..Default::default()
+        };
+
+        assert!(symbols.open_checked(false));
+        assert!(symbols.confirm_open);
+    }
+
+    #[test]
+    fn test_persisted_filter_skips_value() {
+        let filter = SymbolsFilter {
+            value: "btc".to_string(),
+            market: Market::Spot,
+            ..Default::default()
+        };
+        let restored: SymbolsFilter =
+            serde_json::from_str(&serde_json::to_string(&filter).unwrap()).unwrap();
+
+        assert_eq!(restored.market, Market::Spot);
+        assert!(restored.value.is_empty());
+    }
+}
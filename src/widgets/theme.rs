@@ -7,6 +7,12 @@ pub struct Theme {
     dark_mode: bool,
 }
 
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Theme {
     pub fn new() -> Self {
         Self { dark_mode: true }
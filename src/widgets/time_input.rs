@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use chrono::{NaiveTime, Utc};
+use chrono::NaiveTime;
 use egui::widgets::{TextEdit, Widget};
 use egui::Color32;
 use tracing::info;
@@ -69,7 +69,7 @@ impl TimeInput {
 
         let time = self.time;
         info!("parsed time: {time}");
-        Some(NaiveTime::from_hms(time.hours, time.minutes, time.seconds))
+        NaiveTime::from_hms_opt(time.hours, time.minutes, time.seconds)
     }
 
     fn parse_val(&mut self) -> Option<Time> {
@@ -82,7 +82,7 @@ impl TimeInput {
             return None;
         }
 
-        Some(Time::new(hours, minutes, seconds)?)
+        Time::new(hours, minutes, seconds)
     }
 }
 
@@ -119,6 +119,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_time_new() {
         let t_valid = Time::new(23, 24, 24);
         let t_invalid = Time::new(32, 32, 32);
@@ -142,3 +143,34 @@ mod tests {
         assert_eq!(t, Time::new(23, 23, 23));
     }
 }
+
+#[cfg(test)]
+mod time_input_prop_tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn parse(val: &str) -> Option<Time> {
+        TimeInput {
+            val: val.to_string(),
+            ..Default::default()
+        }
+        .parse_val()
+    }
+
+    proptest! {
+        #[test]
+        fn parses_valid_time_or_none(val in "\\PC*|[0-9+-]{0,3}(:[0-9+-]{0,3}){0,3}") {
+            if let Some(t) = parse(&val) {
+                prop_assert!(NaiveTime::from_hms_opt(t.hours, t.minutes, t.seconds).is_some());
+            }
+        }
+
+        #[test]
+        fn formatted_time_parses_back(h in 0..30u32, m in 0..70u32, s in 0..70u32) {
+            let parsed = parse(&format!("{h}:{m}:{s}"));
+            prop_assert_eq!(parsed, Time::new(h, m, s));
+            prop_assert_eq!(parsed.is_some(), NaiveTime::from_hms_opt(h, m, s).is_some());
+        }
+    }
+}
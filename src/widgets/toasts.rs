@@ -0,0 +1,137 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crossbeam::channel::Receiver;
+use egui::{Align2, Area, Color32, Context, Frame, Id};
+
+const TOAST_TTL: Duration = Duration::from_secs(5);
+/// Toasts with a button stay longer to be clicked.
+const BUTTON_TOAST_TTL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Error,
+}
+
+/// Button of a toast, clicking it runs the callback and closes the toast.
+#[derive(Clone)]
+pub struct ToastButton {
+    pub label: String,
+    on_click: Arc<dyn Fn() + Send + Sync>,
+}
+
+impl fmt::Debug for ToastButton {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToastButton")
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub kind: ToastKind,
+    pub text: String,
+    pub button: Option<ToastButton>,
+    created: Instant,
+}
+
+impl Toast {
+    pub fn info(text: impl Into<String>) -> Self {
+        Self::new(ToastKind::Info, text.into())
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self::new(ToastKind::Error, text.into())
+    }
+
+    /// Adds a button running `on_click` from the ui thread.
+    pub fn with_button(
+        mut self,
+        label: impl Into<String>,
+        on_click: impl Fn() + Send + Sync + 'static,
+    ) -> Self {
+        self.button = Some(ToastButton {
+            label: label.into(),
+            on_click: Arc::new(on_click),
+        });
+        self
+    }
+
+    fn new(kind: ToastKind, text: String) -> Self {
+        Self {
+            kind,
+            text,
+            button: None,
+            created: Instant::now(),
+        }
+    }
+
+    fn expired(&self) -> bool {
+        let ttl = match self.button {
+            Some(_) => BUTTON_TOAST_TTL,
+            None => TOAST_TTL,
+        };
+        self.created.elapsed() > ttl
+    }
+}
+
+/// Shows short-lived notifications received from other components
+/// in the bottom right corner of the screen.
+pub struct Toasts {
+    toasts: Vec<Toast>,
+    toast_sub: Receiver<Toast>,
+}
+
+impl Toasts {
+    pub fn new(toast_sub: Receiver<Toast>) -> Self {
+        Self {
+            toasts: Default::default(),
+            toast_sub,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        self.toasts.extend(self.toast_sub.try_iter());
+        self.toasts.retain(|t| !t.expired());
+
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        let mut clicked = None;
+        Area::new(Id::new("toasts"))
+            .anchor(Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .show(ctx, |ui| {
+                self.toasts.iter().enumerate().for_each(|(idx, t)| {
+                    Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(
+                                match t.kind {
+                                    ToastKind::Info => ui.visuals().text_color(),
+                                    ToastKind::Error => Color32::LIGHT_RED,
+                                },
+                                &t.text,
+                            );
+                            if let Some(button) = &t.button {
+                                if ui.button(&button.label).clicked() {
+                                    clicked = Some(idx);
+                                }
+                            }
+                        });
+                    });
+                });
+            });
+        if let Some(toast) = clicked.map(|idx| self.toasts.remove(idx)) {
+            if let Some(button) = toast.button {
+                (button.on_click)();
+            }
+        }
+
+        ctx.request_repaint();
+    }
+}
@@ -1,20 +1,59 @@
-use std::ops::RangeInclusive;
+use std::{ops::RangeInclusive, sync::Arc};
 
-use chrono::{DateTime, NaiveDateTime, Utc};
 use egui::{
-    plot::{Bar, BarChart, LinkedAxisGroup, Plot},
-    Color32, FontId, TextFormat, Vec2, Widget,
+    plot::{Bar, BarChart, LinkedAxisGroup, Plot, Text, Value},
+    Align2, Color32, RichText, Vec2, Widget,
 };
-use tracing::debug;
 
-use crate::netstrat::data::Data;
+use crate::{
+    netstrat::{
+        axis::{self, Axis},
+        data::Data,
+        indicators::Series,
+    },
+    sources::binance::{Interval, Kline},
+};
+
+use super::appearance::plot_text;
+
+/// Height the tallest bar is scaled to. The plot follows the linked panes and never re-derives
+/// its y extent, so the bars are rescaled whenever the data changes instead.
+const BARS_HEIGHT: f64 = 1.0;
+/// Extent of the plot above the tallest bar.
+const HEADROOM: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VolumeKind {
+    #[default]
+    Base,
+    Quote,
+}
+
+impl VolumeKind {
+    fn value(&self, k: &Kline) -> f64 {
+        match self {
+            VolumeKind::Base => k.volume as f64,
+            // data without quote volume shows nothing rather than misleading base volume
+            VolumeKind::Quote => k.quote_asset_volume.unwrap_or_default() as f64,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Volume {
-    data: Data,
+    /// Candles shared with the other panes.
+    data: Arc<Data>,
+    /// Bars scaled to [`BARS_HEIGHT`].
     val: Vec<Bar>,
+    kind: VolumeKind,
+    /// Volume of the tallest bar.
+    max_y: f64,
     axes_group: LinkedAxisGroup,
+    axis: Axis,
+    interval: Option<Interval>,
     enabled: bool,
+    /// Candles highlighted by studies, kept to highlight the bars rebuilt with new data.
+    highlights: Vec<Series>,
 }
 
 impl Default for Volume {
@@ -22,8 +61,13 @@ impl Default for Volume {
         Self {
             data: Default::default(),
             val: Default::default(),
+            kind: Default::default(),
+            max_y: 1.0,
             axes_group: LinkedAxisGroup::new(false, false),
+            axis: Axis::Time,
+            interval: Some(Interval::Minute),
             enabled: true,
+            highlights: Default::default(),
         }
     }
 }
@@ -36,35 +80,114 @@ impl Volume {
         }
     }
 
-    pub fn set_data(&mut self, data: Data) {
-        let val = data
-            .vals
-            .iter()
-            .map(|k| {
-                Bar::new((k.t_open + k.t_close) as f64 / 2.0, k.volume as f64)
-                    .width((k.t_open - k.t_close) as f64 * 0.9)
-                    .fill(Color32::LIGHT_GREEN.linear_multiply(0.5))
-            })
-            .collect();
+    pub fn set_axes_group(&mut self, axes_group: LinkedAxisGroup) {
+        self.axes_group = axes_group;
+    }
+
+    pub fn set_axis(&mut self, axis: Axis) {
+        self.axis = axis;
+    }
+
+    /// Interval of the data set next, none when it is unknown.
+    pub fn set_interval(&mut self, interval: Option<Interval>) {
+        self.interval = interval;
+    }
 
+    /// Rebuilds the bars unless the same data is set again.
+    pub fn set_data(&mut self, data: Arc<Data>) {
+        if Arc::ptr_eq(&self.data, &data) {
+            return;
+        }
         self.data = data;
-        self.val = val;
+        self.compute_bars();
+    }
+
+    pub fn kind(&self) -> VolumeKind {
+        self.kind
+    }
+
+    pub fn set_kind(&mut self, kind: VolumeKind) {
+        self.kind = kind;
+        self.compute_bars();
+    }
+
+    /// Highlights the bars of the candles at the x of the series points in their colors.
+    pub fn set_highlights(&mut self, highlights: &[Series]) {
+        self.highlights = highlights.to_vec();
+        self.highlight_bars();
+    }
+
+    fn compute_bars(&mut self) {
+        self.val = volume_bars(
+            &self.data.vals,
+            self.kind,
+            self.axis.step(self.interval, &self.data.vals),
+        );
+        self.highlight_bars();
+        self.max_y = self.val.iter().map(|b| b.value).fold(0.0, f64::max);
+        // without any volume the bars stay flat instead of dividing by zero
+        if self.max_y <= 0.0 {
+            self.max_y = 1.0;
+        }
+        let scale = BARS_HEIGHT / self.max_y;
+        self.val.iter_mut().for_each(|b| b.value *= scale);
     }
 
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
     }
+
+    fn highlight_bars(&mut self) {
+        self.val.iter_mut().for_each(|b| b.fill = bar_fill());
+        self.highlights.iter().for_each(|s| {
+            let fill = highlight_fill(s);
+            s.points.iter().for_each(|p| {
+                if let Ok(idx) = self.val.binary_search_by(|b| b.argument.total_cmp(&p[0])) {
+                    self.val[idx].fill = fill;
+                }
+            });
+        });
+    }
+}
+
+fn bar_fill() -> Color32 {
+    Color32::LIGHT_GREEN.linear_multiply(0.5)
+}
+
+/// Color of the series, gold without one.
+fn highlight_fill(s: &Series) -> Color32 {
+    s.color
+        .map_or(Color32::GOLD, |[r, g, b]| Color32::from_rgb(r, g, b))
+}
+
+/// Bars of the base or quote volume of the candles in their slots of `step` width.
+pub fn volume_bars(klines: &[Kline], kind: VolumeKind, step: f64) -> Vec<Bar> {
+    klines
+        .iter()
+        .map(|k| {
+            let (x, width) = axis::slot(k, step);
+            Bar::new(x, kind.value(k)).width(width).fill(bar_fill())
+        })
+        .collect()
 }
 
 impl Widget for &Volume {
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let (axis, label_axis, bar_axis) =
+            (self.axis.clone(), self.axis.clone(), self.axis.clone());
         ui.add_enabled_ui(self.enabled, |ui| {
-            Plot::new("volume")
+            plot_text(ui);
+            let max_y = self.max_y;
+            // valid bounds set the y extent of a new plot, x is taken from the linked panes
+            Plot::new("volume bars")
                 .link_axis(self.axes_group.clone())
-                .x_axis_formatter(|v: f64, _: &RangeInclusive<f64>| format_ts(v))
-                .label_formatter(|_, v| format!("{}", format_ts(v.x)))
-                .set_margin_fraction(Vec2::new(0.05, 0.5))
-                .include_y(self.data.max_vol())
+                .x_axis_formatter(move |v: f64, _: &RangeInclusive<f64>| axis.format(v))
+                .label_formatter(move |_, v| label_axis.format(v.x))
+                .set_margin_fraction(Vec2::new(0.05, 0.0))
+                .include_x(0.0)
+                .include_x(1.0)
+                .include_y(0.0)
+                .include_y(BARS_HEIGHT + HEADROOM)
                 .allow_scroll(false)
                 .allow_boxed_zoom(false)
                 .allow_drag(false)
@@ -73,21 +196,150 @@ impl Widget for &Volume {
                 .show(ui, |plot_ui| {
                     plot_ui.bar_chart(
                         BarChart::new(self.val.clone())
-                            .element_formatter(Box::new(|b, _| {
-                                format!("{}\n{}", b.value, format_ts(b.argument))
+                            .element_formatter(Box::new(move |b, _| {
+                                let volume = (b.value / BARS_HEIGHT * max_y) as f32;
+                                format!("{volume}\n{}", bar_axis.describe(b.argument))
                             }))
                             .vertical(),
                     );
+                    let bounds = plot_ui.plot_bounds();
+                    let (min, max) = (bounds.min()[0], bounds.max()[0]);
+                    let mut top = BARS_HEIGHT + HEADROOM;
+                    self.highlights.iter().for_each(|s| {
+                        let visible = s
+                            .points
+                            .iter()
+                            .filter(|p| (min..=max).contains(&p[0]))
+                            .count();
+                        plot_ui.text(
+                            Text::new(
+                                Value::new(min, top),
+                                RichText::new(format!("{visible} {}", s.name)).small(),
+                            )
+                            .anchor(Align2::LEFT_TOP)
+                            .color(highlight_fill(s)),
+                        );
+                        top -= HEADROOM / 2.0;
+                    });
                 })
         })
         .response
     }
 }
 
-fn format_ts(ts: f64) -> String {
-    let secs = (ts / 1000f64) as i64;
-    let naive = NaiveDateTime::from_timestamp(secs, 0);
-    let datetime: DateTime<Utc> = DateTime::from_utc(naive, Utc);
+#[cfg(test)]
+mod volume_tests {
+    use crate::{sources::binance::mock, widgets::candle_boxes};
+
+    use super::*;
+
+    #[test]
+    fn test_shared_data_not_rebuilt() {
+        let data = Arc::new(Data::new(mock::klines(0, 10, 5)));
+        let mut volume = Volume::default();
+
+        volume.set_data(data.clone());
+        assert_eq!(volume.val.len(), 5);
+        assert_eq!(Arc::strong_count(&data), 2);
+
+        volume.val.clear();
+        volume.set_data(data.clone());
+        assert!(volume.val.is_empty());
+
+        volume.set_data(Arc::new(Data::new(mock::klines(0, 10, 5))));
+        assert_eq!(volume.val.len(), 5);
+        assert_eq!(Arc::strong_count(&data), 1);
+    }
+
+    #[test]
+    fn test_empty_axis() {
+        let mut volume = Volume::default();
+        assert_eq!(volume.max_y, 1.0);
+
+        volume.set_data(Arc::new(Data::new(mock::klines(0, 10, 5))));
+        volume.set_data(Arc::new(Data::default()));
+        assert!(volume.val.is_empty());
+        assert_eq!(volume.max_y, 1.0);
+    }
+
+    #[test]
+    fn test_bars_aligned_with_candles() {
+        const MINUTE: i64 = 60_000;
+        let mut klines = mock::klines(0, MINUTE, 2);
+        // a gap of two candles and a partial last candle
+        klines.extend(mock::klines(4 * MINUTE, MINUTE, 2));
+        klines[3].t_close = klines[3].t_open + MINUTE / 2 - 1;
+        let step = Axis::Time.step(Some(Interval::Minute), &klines);
+
+        let bars = volume_bars(&klines, VolumeKind::Base, step);
+        let boxes = candle_boxes(&klines, &Axis::Time, step);
+
+        assert_eq!(bars.len(), boxes.len());
+        bars.iter()
+            .zip(&boxes)
+            .zip(&klines)
+            .for_each(|((bar, el), k)| {
+                assert_eq!(bar.argument, el.argument);
+                assert_eq!(bar.bar_width, el.box_width);
+                assert_eq!(bar.argument, k.t_open as f64 + MINUTE as f64 / 2.0);
+                assert_eq!(bar.bar_width, MINUTE as f64 * 0.9);
+            });
+    }
+
+    #[test]
+    fn test_bars_rescaled_on_new_data() {
+        let mut volume = Volume::default();
+        let mut klines = mock::klines(0, 60_000, 3);
+        klines
+            .iter_mut()
+            .zip([10.0, 40.0, 20.0])
+            .for_each(|(k, v)| k.volume = v);
+
+        volume.set_data(Arc::new(Data::new(klines.clone())));
+        assert_eq!(volume.max_y, 40.0);
+        assert_eq!(bar_values(&volume), vec![0.25, 1.0, 0.5]);
+
+        // a new max arrives with appended candles
+        let mut appended = klines.clone();
+        appended.extend(mock::klines(180_000, 60_000, 1));
+        appended[3].volume = 80.0;
+        volume.set_data(Arc::new(Data::new(appended)));
+        assert_eq!(volume.max_y, 80.0);
+        assert_eq!(bar_values(&volume), vec![0.125, 0.5, 0.25, 1.0]);
 
-    datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+        // replaced by much smaller volumes
+        klines.iter_mut().for_each(|k| k.volume /= 1000.0);
+        volume.set_data(Arc::new(Data::new(klines)));
+        assert_eq!(volume.max_y, 0.04f32 as f64);
+        assert_eq!(volume.val.iter().map(|b| b.value).fold(0.0, f64::max), 1.0);
+    }
+
+    #[test]
+    fn test_highlighted_bars() {
+        let mut volume = Volume::default();
+        let mut klines = mock::klines(0, 60_000, 4);
+        // a partial last candle
+        klines[3].t_close = klines[3].t_open + 9_999;
+        let data = Arc::new(Data::new(klines.clone()));
+        volume.set_data(data.clone());
+
+        let centers = [1, 3].map(|i| data.center(&klines[i]));
+        volume.set_highlights(&[Series {
+            name: "anomalies".to_string(),
+            points: centers.iter().map(|x| [*x, 1.0]).collect(),
+            color: Some([255, 215, 0]),
+        }]);
+        let fills: Vec<bool> = volume.val.iter().map(|b| b.fill == Color32::GOLD).collect();
+        assert_eq!(fills, vec![false, true, false, true]);
+
+        // kept on the bars of new data and cleared with the highlights
+        volume.set_data(Arc::new(Data::new(klines)));
+        assert_eq!(volume.val[1].fill, Color32::GOLD);
+        volume.set_highlights(&[]);
+        assert!(volume.val.iter().all(|b| b.fill == bar_fill()));
+    }
+
+    fn bar_values(volume: &Volume) -> Vec<f64> {
+        volume.val.iter().map(|b| b.value).collect()
+    }
 }
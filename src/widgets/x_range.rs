@@ -0,0 +1,134 @@
+//! Wheel navigation over the time axis of linked plots.
+//!
+//! egui 0.18 plots have no api to set their bounds, so a range is applied by drawing
+//! an invisible plot fitted to it into a fresh axes group. Plots linked by x to that group
+//! take the range from it when drawn after.
+
+use egui::{
+    plot::{LinkedAxisGroup, Plot, Points, Value, Values},
+    Color32, Modifiers, Ui, Vec2,
+};
+
+/// Zoom factor per scrolled point.
+const ZOOM_SPEED: f64 = 0.002;
+
+/// X range after the wheel input over a plot `width` points wide showing `range`
+/// with the cursor at `anchor`:
+///
+/// * wheel zooms time around the cursor
+/// * shift+wheel pans time
+/// * ctrl+wheel is zoom of the price pane by egui, the range is kept so that only prices zoom
+///
+/// `None` when there was no input.
+pub fn wheel(
+    range: [f64; 2],
+    anchor: f64,
+    width: f32,
+    scroll: Vec2,
+    zoom: f32,
+    modifiers: Modifiers,
+) -> Option<[f64; 2]> {
+    if zoom != 1.0 || (modifiers.command && scroll != Vec2::ZERO) {
+        return Some(range);
+    }
+    if scroll == Vec2::ZERO || width <= 0.0 {
+        return None;
+    }
+
+    if modifiers.shift {
+        let delta = -(scroll.x + scroll.y) as f64 * (range[1] - range[0]) / width as f64;
+        return Some([range[0] + delta, range[1] + delta]);
+    }
+
+    let factor = (scroll.y as f64 * ZOOM_SPEED).exp();
+    Some(range.map(|x| anchor + (x - anchor) / factor))
+}
+
+/// Draws an invisible plot setting x range of `group` which no plot was drawn to yet.
+pub fn apply(ui: &mut Ui, group: &LinkedAxisGroup, range: [f64; 2]) {
+    Plot::new("x range")
+        .link_axis(group.clone())
+        .min_size(Vec2::ZERO)
+        // degenerate sizes break the screen transform
+        .width(1.0)
+        .height(1.0)
+        .set_margin_fraction(Vec2::ZERO)
+        .allow_boxed_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .allow_zoom(false)
+        .show_background(false)
+        .show_axes([false, false])
+        .show_x(false)
+        .show_y(false)
+        .show(ui, |plot_ui| {
+            plot_ui.points(
+                Points::new(Values::from_values(vec![
+                    Value::new(range[0], 0.0),
+                    Value::new(range[1], 1.0),
+                ]))
+                .color(Color32::TRANSPARENT)
+                .radius(0.0),
+            );
+        });
+}
+
+#[cfg(test)]
+mod x_range_tests {
+    use super::*;
+
+    #[test]
+    fn test_zoom_keeps_anchor() {
+        let range = wheel(
+            [0.0, 100.0],
+            75.0,
+            100.0,
+            Vec2::new(0.0, 500.0),
+            1.0,
+            Modifiers::default(),
+        )
+        .unwrap();
+
+        let factor = 1f64.exp();
+        assert!((range[0] - (75.0 - 75.0 / factor)).abs() < 1e-9);
+        assert!((range[1] - (75.0 + 25.0 / factor)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_shift_pans() {
+        let shift = Modifiers {
+            shift: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            wheel([0.0, 100.0], 50.0, 50.0, Vec2::new(0.0, 10.0), 1.0, shift),
+            Some([-20.0, 80.0])
+        );
+    }
+
+    #[test]
+    fn test_price_zoom_keeps_range() {
+        let ctrl = Modifiers {
+            ctrl: true,
+            command: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            wheel([0.0, 100.0], 50.0, 50.0, Vec2::ZERO, 1.1, ctrl),
+            Some([0.0, 100.0])
+        );
+        assert_eq!(
+            wheel(
+                [0.0, 100.0],
+                50.0,
+                50.0,
+                Vec2::ZERO,
+                1.0,
+                Modifiers::default()
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::{path::Path, sync::Arc};
+
+use crossbeam::channel::Sender;
+use egui::{DragValue, Grid, Ui, Window};
+use quick_error::ResultExt;
+use tracing::{error, info};
+
+use crate::{
+    errors::Error,
+    netstrat::{
+        backtest::{self, Report, Settings, Sizing, Strategy},
+        data::Data,
+        indicators::IndicatorOutput,
+        session::Session,
+    },
+    widgets::Toast,
+};
+
+use super::AppWindow;
+
+/// Runs a strategy over loaded data. Once run, it is re-run every time data changes
+/// and its equity curve and trades are drawn with the indicators.
+pub struct Backtest {
+    visible: bool,
+    strategies: Vec<Box<dyn Strategy>>,
+    selected: usize,
+    settings: Settings,
+    /// Whether the report is drawn and kept up to date with data.
+    enabled: bool,
+    data: Arc<Data>,
+    symbol: String,
+    report: Option<Report>,
+    output: IndicatorOutput,
+    changed: bool,
+    toast_pub: Sender<Toast>,
+}
+
+impl Backtest {
+    pub fn new(visible: bool, toast_pub: Sender<Toast>) -> Self {
+        Self {
+            visible,
+            strategies: backtest::builtin(),
+            selected: 0,
+            settings: Default::default(),
+            enabled: false,
+            data: Default::default(),
+            symbol: Default::default(),
+            report: None,
+            output: Default::default(),
+            changed: false,
+            toast_pub,
+        }
+    }
+
+    /// Re-runs the enabled backtest over the new data.
+    pub fn set_data(&mut self, data: Arc<Data>) {
+        self.data = data;
+        self.run();
+    }
+
+    pub fn set_symbol(&mut self, symbol: &str) {
+        self.symbol = symbol.to_string();
+    }
+
+    /// Equity curve and trades of the enabled backtest.
+    pub fn outputs(&self) -> impl Iterator<Item = &IndicatorOutput> {
+        self.enabled.then_some(&self.output).into_iter()
+    }
+
+    /// Returns true once after the output changed.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn run(&mut self) {
+        self.report = match self.enabled && !self.data.vals.is_empty() {
+            true => Some(backtest::run(
+                self.strategies[self.selected].as_mut(),
+                &self.data,
+                &self.settings,
+            )),
+            false => None,
+        };
+        self.output = self.report.as_ref().map(Report::output).unwrap_or_default();
+        self.changed = true;
+    }
+
+    fn toast(&self, toast: Toast) {
+        if let Err(err) = self.toast_pub.send(toast).context("toast") {
+            error!("{}", Error::from(err).chain());
+        }
+    }
+
+    fn export(&self) {
+        let Some(report) = &self.report else {
+            return;
+        };
+
+        let path = backtest::file_name(&self.symbol, &report.strategy);
+        match backtest::to_csv(Path::new(&path), &report.trades) {
+            Ok(abs_path) => {
+                info!("exported trades to file: {abs_path:?}");
+                self.toast(Toast::info(format!(
+                    "exported {} trades to {}",
+                    report.trades.len(),
+                    abs_path.display()
+                )));
+            }
+            Err(err) => {
+                error!("failed to export trades: {}", err.chain());
+                self.toast(Toast::error(err.user_message()));
+            }
+        }
+    }
+
+    fn settings_ui(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        Grid::new("backtest settings").show(ui, |ui| {
+            ui.label("strategy");
+            ui.horizontal(|ui| {
+                (0..self.strategies.len()).for_each(|idx| {
+                    changed |= ui
+                        .selectable_value(&mut self.selected, idx, self.strategies[idx].name())
+                        .changed();
+                });
+            });
+            ui.end_row();
+
+            ui.label("params");
+            ui.horizontal(|ui| changed |= self.strategies[self.selected].params_ui(ui));
+            ui.end_row();
+
+            ui.label("balance");
+            changed |= ui
+                .add(
+                    DragValue::new(&mut self.settings.balance)
+                        .clamp_range(1.0..=f64::MAX)
+                        .speed(100.0),
+                )
+                .changed();
+            ui.end_row();
+
+            ui.label("fee");
+            changed |= ui
+                .add(
+                    DragValue::new(&mut self.settings.fee_bps)
+                        .clamp_range(0.0..=1000.0)
+                        .speed(0.5)
+                        .suffix(" bps"),
+                )
+                .changed();
+            ui.end_row();
+
+            ui.label("position size");
+            ui.horizontal(|ui| {
+                let mut equity = matches!(self.settings.sizing, Sizing::Equity(_));
+                if ui
+                    .selectable_value(&mut equity, true, "% of equity")
+                    .clicked()
+                {
+                    self.settings.sizing = Sizing::Equity(100.0);
+                    changed = true;
+                }
+                if ui.selectable_value(&mut equity, false, "fixed").clicked() {
+                    self.settings.sizing = Sizing::Fixed(self.settings.balance);
+                    changed = true;
+                }
+                let (size, suffix) = match &mut self.settings.sizing {
+                    Sizing::Equity(pct) => (pct, " %"),
+                    Sizing::Fixed(amount) => (amount, " quote"),
+                };
+                changed |= ui
+                    .add(
+                        DragValue::new(size)
+                            .clamp_range(0.0..=f64::MAX)
+                            .suffix(suffix),
+                    )
+                    .changed();
+            });
+            ui.end_row();
+        });
+
+        changed
+    }
+
+    fn report_ui(&self, ui: &mut Ui) {
+        let Some(report) = &self.report else {
+            ui.label("run to see the results");
+            return;
+        };
+
+        Grid::new("backtest report").show(ui, |ui| {
+            ui.label("final equity");
+            ui.label(
+                report
+                    .final_equity()
+                    .map_or("-".to_string(), |e| format!("{e:.2}")),
+            );
+            ui.end_row();
+            ui.label("trades");
+            ui.label(report.trades.len().to_string());
+            ui.end_row();
+            ui.label("win rate");
+            ui.label(
+                report
+                    .win_rate()
+                    .map_or("-".to_string(), |r| format!("{:.1}%", r * 100.0)),
+            );
+            ui.end_row();
+            ui.label("profit factor");
+            ui.label(
+                report
+                    .profit_factor()
+                    .map_or("-".to_string(), |f| format!("{f:.2}")),
+            );
+            ui.end_row();
+            ui.label("max drawdown");
+            ui.label(format!("{:.2}%", report.max_drawdown() * 100.0));
+            ui.end_row();
+        });
+    }
+}
+
+impl AppWindow for Backtest {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Backtest").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+
+        let mut visible = self.visible;
+        Window::new("backtest")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .default_width(400.0)
+            .show(ui.ctx(), |ui| {
+                let changed = self.settings_ui(ui);
+                ui.separator();
+                let (mut run, mut clear) = (false, false);
+                ui.horizontal(|ui| {
+                    run = ui.button("run").clicked();
+                    clear = ui
+                        .add_enabled(self.enabled, egui::Button::new("clear"))
+                        .clicked();
+                    if ui
+                        .add_enabled(self.report.is_some(), egui::Button::new("export csv"))
+                        .clicked()
+                    {
+                        self.export();
+                    }
+                });
+                if run {
+                    self.enabled = true;
+                }
+                if clear {
+                    self.enabled = false;
+                }
+                if run || clear || (changed && self.enabled) {
+                    self.run();
+                }
+                ui.separator();
+                self.report_ui(ui);
+            });
+        self.visible = visible;
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("backtest", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("backtest").unwrap_or(self.visible);
+    }
+}
+
+#[cfg(test)]
+mod backtest_tests {
+    use crossbeam::channel::unbounded;
+
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_rerun_on_data_change() {
+        let (s_toasts, _) = unbounded();
+        let mut backtest = Backtest::new(false, s_toasts);
+
+        backtest.set_data(Arc::new(Data::new(mock::klines(0, 10, 20))));
+        assert_eq!(backtest.outputs().count(), 0);
+
+        backtest.enabled = true;
+        backtest.set_data(Arc::new(Data::new(mock::klines(0, 10, 50))));
+
+        assert!(backtest.take_changed());
+        let outputs: Vec<&IndicatorOutput> = backtest.outputs().collect();
+        assert_eq!(outputs[0].sub_panels[0].series[0].points.len(), 50);
+    }
+}
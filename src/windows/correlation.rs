@@ -0,0 +1,409 @@
+use std::collections::BTreeSet;
+
+use egui::{Button, Color32, ComboBox, Grid, RichText, ScrollArea, TextEdit, Ui, Window};
+use poll_promise::Promise;
+use tracing::{error, info};
+
+use crate::{
+    errors::Result,
+    netstrat::{
+        correlation::{self, Matrix, MIN_RETURNS},
+        download,
+        indicators::{IndicatorOutput, Series},
+        props::Props,
+        session::Session,
+    },
+    sources::binance::{Client, Interval, Kline},
+};
+
+use super::AppWindow;
+
+/// Symbols correlated at once, each is downloaded in full.
+const MAX_SYMBOLS: usize = 12;
+/// Candles of a symbol beyond which a longer interval has to be picked.
+const MAX_CANDLES: usize = 50_000;
+
+type Fetched = Vec<(String, Result<Vec<Kline>>)>;
+
+/// Correlations of log returns of the picked symbols over the range of the chart. A cell
+/// picked from the matrix loads its first symbol in the chart with closes of the second
+/// drawn over it.
+pub struct Correlation {
+    visible: bool,
+    /// Symbols typed in, separated by commas or spaces.
+    input: String,
+    favorites: BTreeSet<String>,
+    interval: Interval,
+    /// Range of the chart.
+    props: Props,
+    /// Symbol shown in the chart.
+    symbol: String,
+    promise: Option<Promise<Fetched>>,
+    matrix: Option<Matrix>,
+    /// Candles of the symbols of the matrix in the same order.
+    klines: Vec<Vec<Kline>>,
+    /// Messages of the symbols which failed to download.
+    failed: Vec<String>,
+    sort_by_average: bool,
+    /// Positions of the pair drawn on the chart.
+    comparison: Option<(usize, usize)>,
+    open: Option<(String, Props)>,
+    output: IndicatorOutput,
+    changed: bool,
+}
+
+impl Correlation {
+    pub fn new(visible: bool) -> Self {
+        Self {
+            visible,
+            input: Default::default(),
+            favorites: Default::default(),
+            interval: Interval::Hour,
+            props: Default::default(),
+            symbol: Default::default(),
+            promise: None,
+            matrix: None,
+            klines: Default::default(),
+            failed: Default::default(),
+            sort_by_average: false,
+            comparison: None,
+            open: None,
+            output: Default::default(),
+            changed: false,
+        }
+    }
+
+    /// Takes the range of the chart and its interval unless it's aggregated.
+    pub fn set_props(&mut self, props: &Props) {
+        self.props = props.clone();
+        if !props.interval.is_aggregated() {
+            self.interval = props.interval;
+        }
+    }
+
+    pub fn set_favorites(&mut self, favorites: &BTreeSet<String>) {
+        if self.favorites != *favorites {
+            self.favorites = favorites.clone();
+        }
+    }
+
+    /// The comparison is drawn while its first symbol is shown.
+    pub fn set_symbol(&mut self, symbol: &str) {
+        self.symbol = symbol.to_string();
+        self.changed |= self.comparison.is_some();
+    }
+
+    /// Closes of the second symbol of the picked pair while the first one is shown.
+    pub fn outputs(&self) -> impl Iterator<Item = &IndicatorOutput> {
+        let shown = self
+            .comparison
+            .zip(self.matrix.as_ref())
+            .is_some_and(|((a, _), m)| m.symbols[a] == self.symbol);
+        shown.then_some(&self.output).into_iter()
+    }
+
+    /// Returns true once after the output changed.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Returns the symbol to show with the props of the matrix once after a pair was picked.
+    pub fn take_open(&mut self) -> Option<(String, Props)> {
+        self.open.take()
+    }
+
+    /// Typed symbols in upper case without repeats.
+    fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = vec![];
+        self.input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_uppercase())
+            .for_each(|s| {
+                if !symbols.contains(&s) {
+                    symbols.push(s);
+                }
+            });
+        symbols
+    }
+
+    /// Range of the chart at the picked interval.
+    fn fetch_props(&self) -> Props {
+        Props {
+            limit: self.props.limit,
+            ..Props::new(
+                self.props.start_time().naive_utc(),
+                self.props.end_time().naive_utc(),
+                self.interval,
+                self.props.empty_buckets,
+            )
+        }
+    }
+
+    /// Why the symbols can't be fetched.
+    fn invalid(&self, symbols: &[String], props: &Props) -> Option<String> {
+        match symbols.len() {
+            0 | 1 => Some("pick at least 2 symbols".to_string()),
+            n if n > MAX_SYMBOLS => Some(format!("pick at most {MAX_SYMBOLS} symbols")),
+            _ if props.candles() > MAX_CANDLES => Some(format!(
+                "~{} candles per symbol, pick a longer interval",
+                props.candles()
+            )),
+            _ => None,
+        }
+    }
+
+    fn fetch(&mut self, symbols: Vec<String>, props: Props) {
+        info!("fetching closes of {symbols:?} for correlation: {props:?}");
+        self.comparison = None;
+        self.changed = true;
+        self.promise = Some(Promise::spawn_async(async move {
+            let client = Client::default();
+            let mut fetched = vec![];
+            for symbol in symbols {
+                let klines = download::download(&client, &symbol, &props).await;
+                fetched.push((symbol, klines));
+            }
+            fetched
+        }));
+    }
+
+    fn poll(&mut self) {
+        let Some(promise) = self.promise.take() else {
+            return;
+        };
+        let fetched = match promise.try_take() {
+            Ok(fetched) => fetched,
+            Err(promise) => {
+                self.promise = Some(promise);
+                return;
+            }
+        };
+
+        let (mut symbols, mut klines) = (vec![], vec![]);
+        self.failed.clear();
+        fetched
+            .into_iter()
+            .for_each(|(symbol, result)| match result {
+                Ok(k) => {
+                    symbols.push(symbol);
+                    klines.push(k);
+                }
+                Err(err) => {
+                    error!("failed to fetch {symbol} for correlation: {}", err.chain());
+                    self.failed
+                        .push(format!("{symbol}: {}", err.user_message()));
+                }
+            });
+        self.matrix = Some(Matrix::new(symbols, &klines, MIN_RETURNS));
+        self.klines = klines;
+    }
+
+    /// Draws the second symbol of the pair over the first one and asks for the first one to
+    /// be shown.
+    fn compare(&mut self, a: usize, b: usize) {
+        let Some(matrix) = &self.matrix else {
+            return;
+        };
+        let (name_a, name_b) = (&matrix.symbols[a], &matrix.symbols[b]);
+        info!("comparing {name_a} with {name_b}");
+        self.output = IndicatorOutput {
+            overlay: vec![Series {
+                name: format!("{name_b} rebased"),
+                points: correlation::rebased(&self.klines[a], &self.klines[b]),
+                color: None,
+            }],
+            ..Default::default()
+        };
+        self.comparison = Some((a, b));
+        self.changed = true;
+        self.open = Some((name_a.clone(), self.fetch_props()));
+    }
+
+    fn matrix_ui(&mut self, ui: &mut Ui) {
+        let Some(matrix) = &self.matrix else {
+            return;
+        };
+        let order = match self.sort_by_average {
+            true => matrix.by_average(),
+            false => (0..matrix.symbols.len()).collect(),
+        };
+
+        let mut picked = None;
+        ScrollArea::both().max_height(400.0).show(ui, |ui| {
+            Grid::new("correlation matrix").show(ui, |ui| {
+                ui.label("");
+                order.iter().for_each(|j| {
+                    ui.strong(&matrix.symbols[*j]);
+                });
+                ui.strong("average");
+                ui.end_row();
+
+                order.iter().for_each(|i| {
+                    ui.strong(&matrix.symbols[*i]);
+                    order.iter().for_each(|j| {
+                        let cell = matrix.cells[*i][*j];
+                        let Some(value) = cell.value else {
+                            ui.weak("insufficient data").on_hover_text(format!(
+                                "{} candles in common, {} needed",
+                                cell.overlap,
+                                MIN_RETURNS + 1
+                            ));
+                            return;
+                        };
+                        let button = Button::new(format!("{value:.2}")).fill(cell_color(value));
+                        let resp = ui.add_enabled(i != j, button).on_hover_text(format!(
+                            "{} candles in common, click to compare on the chart",
+                            cell.overlap
+                        ));
+                        if resp.clicked() {
+                            picked = Some((*i, *j));
+                        }
+                    });
+                    match matrix.average(*i) {
+                        Some(avg) => ui.label(format!("{avg:.2}")),
+                        None => ui.weak("-"),
+                    };
+                    ui.end_row();
+                });
+            });
+        });
+
+        if let Some((a, b)) = picked {
+            self.compare(a, b);
+        }
+    }
+}
+
+/// Green for positive correlations and red for negative ones, the stronger the more opaque.
+fn cell_color(value: f64) -> Color32 {
+    let alpha = (value.abs().clamp(0.0, 1.0) * 160.0) as u8;
+    match value >= 0.0 {
+        true => Color32::from_rgba_unmultiplied(0, 160, 0, alpha),
+        false => Color32::from_rgba_unmultiplied(200, 0, 0, alpha),
+    }
+}
+
+impl AppWindow for Correlation {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Correlation").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn tick(&mut self, _ctx: &egui::Context) {
+        self.poll();
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+
+        let mut visible = self.visible;
+        Window::new("correlation")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .default_width(500.0)
+            .show(ui.ctx(), |ui| {
+                ui.add(
+                    TextEdit::singleline(&mut self.input)
+                        .hint_text("symbols, e.g. BTCUSDT ETHUSDT")
+                        .desired_width(f32::INFINITY),
+                );
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!self.favorites.is_empty(), Button::new("use favorites"))
+                        .clicked()
+                    {
+                        self.input = self.favorites.iter().cloned().collect::<Vec<_>>().join(" ");
+                    }
+                    if ui
+                        .add_enabled(!self.symbol.is_empty(), Button::new("add shown symbol"))
+                        .clicked()
+                    {
+                        self.input = format!("{} {}", self.input.trim(), self.symbol);
+                    }
+                    ComboBox::from_id_source("correlation interval")
+                        .selected_text(self.interval.as_str())
+                        .show_ui(ui, |ui| {
+                            Interval::ALL
+                                .into_iter()
+                                .filter(|i| !i.is_aggregated())
+                                .for_each(|i| {
+                                    ui.selectable_value(&mut self.interval, i, i.as_str());
+                                });
+                        });
+                });
+
+                let symbols = self.symbols();
+                let props = self.fetch_props();
+                let invalid = self.invalid(&symbols, &props);
+                ui.horizontal(|ui| {
+                    let fetching = self.promise.is_some();
+                    let fetch = ui
+                        .add_enabled(!fetching && invalid.is_none(), Button::new("correlate"))
+                        .on_hover_text("closes over the range of the chart");
+                    if fetch.clicked() {
+                        self.fetch(symbols, props);
+                    }
+                    if fetching {
+                        ui.spinner();
+                    }
+                    ui.checkbox(&mut self.sort_by_average, "sort by average");
+                });
+                if let Some(reason) = invalid {
+                    ui.colored_label(Color32::LIGHT_RED, reason);
+                }
+                self.failed.iter().for_each(|msg| {
+                    ui.label(RichText::new(msg).color(Color32::LIGHT_RED));
+                });
+
+                ui.separator();
+                self.matrix_ui(ui);
+            });
+        self.visible = visible;
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("correlation", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("correlation").unwrap_or(self.visible);
+    }
+}
+
+#[cfg(test)]
+mod correlation_window_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_comparison_shown_with_its_symbol() {
+        let mut window = Correlation::new(true);
+        window.input = "btcusdt, ethusdt ETHUSDT".to_string();
+        assert_eq!(window.symbols(), vec!["BTCUSDT", "ETHUSDT"]);
+
+        window.klines = vec![mock::klines(0, 60_000, 5), mock::klines(0, 60_000, 5)];
+        window.matrix = Some(Matrix::new(window.symbols(), &window.klines, MIN_RETURNS));
+        window.compare(1, 0);
+
+        let (symbol, props) = window.take_open().unwrap();
+        assert_eq!(symbol, "ETHUSDT");
+        assert_eq!(props.interval, Interval::Hour);
+        assert_eq!(window.outputs().count(), 0);
+
+        window.set_symbol("ETHUSDT");
+        assert!(window.take_changed());
+        assert_eq!(
+            window.outputs().next().unwrap().overlay[0].name,
+            "BTCUSDT rebased"
+        );
+    }
+}
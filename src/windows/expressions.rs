@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use egui::{Color32, RichText, TextEdit, TextStyle, Ui, Window};
+
+use crate::{
+    errors::{Error, Result},
+    netstrat::{
+        data::Data,
+        expression::{Definition, Target},
+        indicators::IndicatorOutput,
+        session::Session,
+    },
+};
+
+use super::AppWindow;
+
+struct Entry {
+    def: Definition,
+    result: Option<Result<IndicatorOutput>>,
+}
+
+impl Entry {
+    fn new(def: Definition) -> Self {
+        Self { def, result: None }
+    }
+}
+
+/// Editor of indicators built from expressions. Enabled ones are re-evaluated every time data changes.
+pub struct Expressions {
+    visible: bool,
+    entries: Vec<Entry>,
+    data: Arc<Data>,
+    changed: bool,
+}
+
+impl Expressions {
+    pub fn new(visible: bool) -> Self {
+        Self {
+            visible,
+            entries: vec![],
+            data: Default::default(),
+            changed: false,
+        }
+    }
+
+    /// Re-evaluates enabled expressions over the new data.
+    pub fn set_data(&mut self, data: Arc<Data>) {
+        self.data = data;
+        (0..self.entries.len()).for_each(|idx| self.eval(idx));
+    }
+
+    /// Outputs of enabled expressions which evaluated successfully.
+    pub fn outputs(&self) -> impl Iterator<Item = &IndicatorOutput> {
+        self.entries
+            .iter()
+            .filter(|e| e.def.enabled)
+            .filter_map(|e| e.result.as_ref()?.as_ref().ok())
+    }
+
+    /// Returns true once after any of the outputs changed.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    pub fn definitions(&self) -> Vec<Definition> {
+        self.entries.iter().map(|e| e.def.clone()).collect()
+    }
+
+    pub fn set_definitions(&mut self, defs: Vec<Definition>) {
+        self.entries = defs.into_iter().map(Entry::new).collect();
+        (0..self.entries.len()).for_each(|idx| self.eval(idx));
+    }
+
+    fn eval(&mut self, idx: usize) {
+        let data = &self.data;
+        if let Some(e) = self.entries.get_mut(idx) {
+            e.result = match e.def.enabled && !data.vals.is_empty() {
+                true => Some(e.def.compute(data)),
+                false => None,
+            };
+            self.changed = true;
+        }
+    }
+
+    fn add(&mut self) {
+        let name = (1..)
+            .map(|i| format!("expr_{i}"))
+            .find(|name| self.entries.iter().all(|e| &e.def.name != name))
+            .unwrap_or_default();
+
+        self.entries.push(Entry::new(Definition {
+            name,
+            ..Default::default()
+        }));
+    }
+
+    fn entry_ui(ui: &mut Ui, e: &mut Entry) -> (bool, bool) {
+        let (mut changed, mut remove) = (false, false);
+        ui.horizontal(|ui| {
+            changed |= ui.checkbox(&mut e.def.enabled, "").changed();
+            changed |= ui
+                .add(TextEdit::singleline(&mut e.def.name).desired_width(80.0))
+                .lost_focus();
+            let mut color = Color32::from_rgb(e.def.color[0], e.def.color[1], e.def.color[2]);
+            if ui.color_edit_button_srgba(&mut color).changed() {
+                e.def.color = [color.r(), color.g(), color.b()];
+                changed = true;
+            }
+            changed |= ui
+                .selectable_value(&mut e.def.target, Target::Overlay, "overlay")
+                .changed();
+            changed |= ui
+                .selectable_value(&mut e.def.target, Target::SubPanel, "panel")
+                .changed();
+            remove = ui.small_button("delete").clicked();
+        });
+
+        let resp = ui.add(
+            TextEdit::singleline(&mut e.def.source)
+                .font(TextStyle::Monospace)
+                .desired_width(f32::INFINITY),
+        );
+        changed |= resp.lost_focus();
+
+        if let Some(Err(err)) = &e.result {
+            if let Error::Expression(pos, _) = err {
+                ui.label(
+                    RichText::new(format!("{}^", " ".repeat(*pos)))
+                        .monospace()
+                        .color(Color32::LIGHT_RED),
+                );
+            }
+            ui.colored_label(Color32::LIGHT_RED, err.to_string());
+        }
+
+        (changed, remove)
+    }
+}
+
+impl AppWindow for Expressions {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Expressions").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+
+        let mut visible = self.visible;
+        Window::new("expressions")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .default_width(400.0)
+            .show(ui.ctx(), |ui| {
+                ui.label("series: open, high, low, close, volume; functions: sma(x, n), ema(x, n), abs(x)");
+                let (mut changed, mut removed) = (vec![], None);
+                self.entries.iter_mut().enumerate().for_each(|(idx, e)| {
+                    ui.separator();
+                    let (entry_changed, remove) = Self::entry_ui(ui, e);
+                    if entry_changed {
+                        changed.push(idx);
+                    }
+                    if remove {
+                        removed = Some(idx);
+                    }
+                });
+
+                changed.into_iter().for_each(|idx| self.eval(idx));
+                if let Some(idx) = removed {
+                    self.entries.remove(idx);
+                    self.changed = true;
+                }
+
+                ui.separator();
+                if ui.button("new").clicked() {
+                    self.add();
+                }
+            });
+        self.visible = visible;
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("expressions", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("expressions").unwrap_or(self.visible);
+    }
+}
+
+#[cfg(test)]
+mod expressions_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_reevaluate_on_data_change() {
+        let mut expressions = Expressions::new(false);
+        expressions.set_definitions(vec![
+            Definition {
+                name: "fast".to_string(),
+                source: "ema(close, 5)".to_string(),
+                enabled: true,
+                ..Default::default()
+            },
+            Definition {
+                name: "broken".to_string(),
+                source: "close +".to_string(),
+                enabled: true,
+                ..Default::default()
+            },
+        ]);
+
+        expressions.set_data(Arc::new(Data::new(mock::klines(0, 10, 20))));
+
+        assert!(expressions.take_changed());
+        let outputs: Vec<&IndicatorOutput> = expressions.outputs().collect();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].overlay[0].points.len(), 16);
+
+        expressions.set_data(Arc::new(Data::new(mock::klines(0, 10, 30))));
+
+        let outputs: Vec<&IndicatorOutput> = expressions.outputs().collect();
+        assert_eq!(outputs[0].overlay[0].points.len(), 26);
+        assert_eq!(expressions.definitions()[1].source, "close +");
+    }
+}
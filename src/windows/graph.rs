@@ -1,29 +1,102 @@
+use std::path::Path;
+
 use crossbeam::channel::{Receiver, Sender};
-use egui::{Layout, Ui, Window};
+use egui::{Context, Id, LayerId, Layout, Order, Ui, Window};
 use egui_extras::{Size, StripBuilder};
 
 use super::window::AppWindow;
-use crate::widgets::{Graph, Symbols};
+use crate::{
+    netstrat::{
+        actions::{Actions, Shortcut},
+        cache::Cache,
+        channels::LatestSender,
+        download::DownloadConfig,
+        export::{ConfirmLimits, ExportOptions},
+        influx::Influx,
+        journal::JournalConfig,
+        last_used::LastUsed,
+        memory::CandlesBudget,
+        notify::Notifier,
+        props::{CandlesBand, Props},
+        selection::Selection,
+        session::Session,
+        timezone::Timezone,
+        watchlist::Watchlist,
+    },
+    network::{webhook::Webhook, ws_server::Broadcaster},
+    widgets::{Graph, QuickSwitch, Status, Symbols, SymbolsState, Toast},
+};
+
+const SYMBOLS_STATE_KEY: &str = "symbols";
+const INDICATORS_KEY: &str = "indicators";
+const EXPRESSIONS_KEY: &str = "expressions";
+const NOTES_KEY: &str = "notes";
+const EVENTS_KEY: &str = "events";
+const PORTFOLIO_KEY: &str = "portfolio";
+const ACTION_TOGGLE: &str = "window.graph";
+const ACTION_SWITCH: &str = "symbol.switch";
 
 pub struct SymbolsGraph {
     graph: Graph,
     symbols: Symbols,
+    quick_switch: QuickSwitch,
+    actions: Actions,
     visible: bool,
 }
 
 impl AppWindow for SymbolsGraph {
     fn toggle_btn(&mut self, ui: &mut Ui) {
         if ui.button("graph").clicked() {
-            self.visible = !self.visible
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn tick(&mut self, ctx: &Context) {
+        // dropped files open in the graph
+        if !ctx.input().raw.dropped_files.is_empty() {
+            self.visible = true;
+        }
+        if self.actions.take(ACTION_TOGGLE) {
+            self.toggle();
+        }
+        if self.actions.take(ACTION_SWITCH) {
+            self.visible = true;
+            self.quick_switch.open();
+        }
+        self.graph.set_favorites(self.symbols.favorites());
+        if let Some(symbols) = self.symbols.take_export() {
+            self.graph.export_symbols(symbols);
+        }
+        self.graph.tick(ctx);
+        if self.graph.take_focus_request() {
+            self.visible = true;
+            ctx.move_to_top(LayerId::new(Order::Middle, Id::new("graph")));
         }
     }
 
     fn show(&mut self, ui: &mut Ui) {
-        Window::new("graph")
+        if !self.visible {
+            return;
+        }
+
+        self.graph.set_fullscreen(false);
+        let title = match self.graph.imported() {
+            Some(file) => format!("graph, {file}"),
+            None => "graph".to_string(),
+        };
+        Window::new(title)
+            .id(Id::new("graph"))
             .open(&mut self.visible)
             .min_height(500.0)
             .min_width(700.0)
             .show(ui.ctx(), |ui| {
+                if let Some(symbol) = self.quick_switch.ui(ui, self.symbols.index()) {
+                    self.symbols.select(&symbol);
+                }
                 ui.with_layout(Layout::left_to_right(), |ui| {
                     StripBuilder::new(ui)
                         .size(Size::relative(0.2).at_most(200.0))
@@ -39,14 +112,131 @@ impl AppWindow for SymbolsGraph {
                 })
             });
     }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SYMBOLS_STATE_KEY, &self.symbols.state());
+        eframe::set_value(storage, INDICATORS_KEY, &self.graph.enabled_indicators());
+        eframe::set_value(storage, EXPRESSIONS_KEY, &self.graph.expressions());
+        eframe::set_value(storage, NOTES_KEY, self.graph.notes());
+        eframe::set_value(storage, EVENTS_KEY, self.graph.events());
+        eframe::set_value(storage, PORTFOLIO_KEY, self.graph.portfolio());
+    }
+
+    fn fullscreen(&mut self, ui: &mut Ui) -> bool {
+        self.graph.set_fullscreen(true);
+        ui.add(&mut self.graph);
+        true
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("graph", self.visible);
+        self.graph.snapshot(session);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("graph").unwrap_or(self.visible);
+        self.graph.restore(session);
+    }
 }
 
 impl SymbolsGraph {
-    pub fn new(s: Sender<String>, r: Receiver<String>, visible: bool) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        s: LatestSender<Selection>,
+        r: Receiver<Selection>,
+        toast_pub: Sender<Toast>,
+        status_pub: Sender<Status>,
+        broadcaster: Broadcaster,
+        budget: CandlesBudget,
+        cache: Cache,
+        notifier: Notifier,
+        confirm_limits: ConfirmLimits,
+        export_options: ExportOptions,
+        timezone: Timezone,
+        download_config: DownloadConfig,
+        influx: Influx,
+        webhook: Webhook,
+        last_used: LastUsed,
+        band: CandlesBand,
+        journal: JournalConfig,
+        watchlist: Watchlist,
+        actions: Actions,
+        storage: Option<&dyn eframe::Storage>,
+        visible: bool,
+    ) -> Self {
+        let state = storage
+            .and_then(|s| eframe::get_value::<SymbolsState>(s, SYMBOLS_STATE_KEY))
+            .unwrap_or_default();
+
+        let indicators: Vec<String> = storage
+            .and_then(|s| eframe::get_value(s, INDICATORS_KEY))
+            .unwrap_or_default();
+
+        let mut graph = Graph::new(
+            r,
+            toast_pub.clone(),
+            status_pub,
+            broadcaster,
+            budget,
+            cache,
+            notifier,
+            confirm_limits,
+            export_options,
+            timezone,
+            download_config,
+            influx,
+            webhook,
+            last_used,
+            band,
+            journal,
+            actions.clone(),
+        );
+        graph.set_enabled_indicators(&indicators);
+        graph.set_expressions(
+            storage
+                .and_then(|s| eframe::get_value(s, EXPRESSIONS_KEY))
+                .unwrap_or_default(),
+        );
+        graph.set_events(
+            storage
+                .and_then(|s| eframe::get_value(s, EVENTS_KEY))
+                .unwrap_or_default(),
+        );
+        graph.set_notes(
+            storage
+                .and_then(|s| eframe::get_value(s, NOTES_KEY))
+                .unwrap_or_default(),
+        );
+        graph.set_portfolio(
+            storage
+                .and_then(|s| eframe::get_value(s, PORTFOLIO_KEY))
+                .unwrap_or_default(),
+        );
+
+        actions.register(ACTION_TOGGLE, "toggle graph window", None);
+        actions.register(ACTION_SWITCH, "switch symbol", Some(Shortcut::Text("/")));
+
         Self {
-            graph: Graph::new(r),
-            symbols: Symbols::new(s),
+            graph,
+            symbols: Symbols::new(s, toast_pub, watchlist, state),
+            quick_switch: Default::default(),
+            actions,
             visible,
         }
     }
+
+    /// Shows the symbol downloading candles of the props.
+    pub fn open(&mut self, symbol: String, props: Props) {
+        self.graph.open(symbol, props);
+    }
+
+    /// Shows a view shared from another instance.
+    pub fn open_view(&mut self, view: &str) {
+        self.graph.open_view(view);
+    }
+
+    /// Shows candles of a csv.
+    pub fn import_file(&mut self, ctx: &Context, path: &Path) {
+        self.graph.import_file(ctx, path);
+    }
 }
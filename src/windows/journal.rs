@@ -0,0 +1,321 @@
+use std::path::Path;
+
+use crossbeam::channel::Sender;
+use egui::{Color32, ComboBox, DragValue, Grid, RichText, ScrollArea, TextEdit, Ui, Window};
+use quick_error::ResultExt;
+use tracing::{error, info};
+
+use crate::{
+    errors::Error,
+    netstrat::{
+        data::Data,
+        journal::{self, JournalConfig, Row},
+        notes::{Direction, Notes},
+        session::Session,
+    },
+    widgets::Toast,
+};
+
+use super::AppWindow;
+
+/// Notes of all symbols as a trade journal with the P&L of trades. Notes edited here are
+/// taken by the graph, which shows them on the chart.
+pub struct Journal {
+    visible: bool,
+    config: JournalConfig,
+    notes: Notes,
+    newest_first: bool,
+    /// Symbol and position of the note being edited.
+    editing: Option<(String, usize)>,
+    edited: bool,
+    toast_pub: Sender<Toast>,
+}
+
+impl Journal {
+    pub fn new(visible: bool, config: JournalConfig, toast_pub: Sender<Toast>) -> Self {
+        Self {
+            visible,
+            config,
+            notes: Default::default(),
+            newest_first: true,
+            editing: None,
+            edited: false,
+            toast_pub,
+        }
+    }
+
+    pub fn set_notes(&mut self, notes: &Notes) {
+        self.notes = notes.clone();
+        if let Some((symbol, idx)) = &self.editing {
+            if *idx >= self.notes.get(symbol).len() {
+                self.editing = None;
+            }
+        }
+    }
+
+    /// Returns the notes once after they were edited.
+    pub fn take_edited(&mut self) -> Option<Notes> {
+        std::mem::take(&mut self.edited).then(|| self.notes.clone())
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = journal::rows(&self.notes, self.config.fee_bps());
+        if self.newest_first {
+            rows.reverse();
+        }
+        rows
+    }
+
+    fn toast(&self, toast: Toast) {
+        if let Err(err) = self.toast_pub.send(toast).context("toast") {
+            error!("{}", Error::from(err).chain());
+        }
+    }
+
+    fn export(&self, extension: &str) {
+        let rows = self.rows();
+        let path = journal::file_name(extension);
+        let written = match extension {
+            "csv" => journal::to_csv(Path::new(&path), &rows),
+            _ => journal::to_json(Path::new(&path), &rows),
+        };
+        match written {
+            Ok(abs_path) => {
+                info!("exported journal to file: {abs_path:?}");
+                self.toast(Toast::info(format!(
+                    "exported {} notes to {}",
+                    rows.len(),
+                    abs_path.display()
+                )));
+            }
+            Err(err) => {
+                error!("failed to export journal: {}", err.chain());
+                self.toast(Toast::error(err.user_message()));
+            }
+        }
+    }
+
+    fn rows_ui(&mut self, ui: &mut Ui, rows: &[Row]) {
+        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            Grid::new("journal rows").striped(true).show(ui, |ui| {
+                let arrow = match self.newest_first {
+                    true => "⏷",
+                    false => "⏶",
+                };
+                if ui.button(format!("time {arrow}")).clicked() {
+                    self.newest_first = !self.newest_first;
+                }
+                ["symbol", "side", "entry", "exit", "size", "P&L", "note", ""]
+                    .into_iter()
+                    .for_each(|h| {
+                        ui.strong(h);
+                    });
+                ui.end_row();
+
+                rows.iter().for_each(|r| {
+                    let number = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+                    ui.label(Data::format_ts(r.ts as f64));
+                    ui.label(&r.symbol);
+                    ui.label(r.direction.map(|d| d.as_str()).unwrap_or_default());
+                    ui.label(number(r.entry));
+                    ui.label(number(r.exit));
+                    ui.label(number(r.size));
+                    match r.pnl {
+                        Some(pnl) => ui.colored_label(
+                            match pnl >= 0.0 {
+                                true => Color32::LIGHT_GREEN,
+                                false => Color32::LIGHT_RED,
+                            },
+                            format!("{pnl:.2}"),
+                        ),
+                        None => ui.label(""),
+                    };
+                    ui.label(&r.text);
+                    let editing = self.editing.as_ref() == Some(&(r.symbol.clone(), r.idx));
+                    if ui.selectable_label(editing, "edit").clicked() {
+                        self.editing = match editing {
+                            true => None,
+                            false => Some((r.symbol.clone(), r.idx)),
+                        };
+                    }
+                    ui.end_row();
+                });
+            });
+        });
+    }
+
+    /// Fields of the edited note, a field left unchecked is unknown.
+    fn editor_ui(&mut self, ui: &mut Ui) {
+        let Some((symbol, idx)) = self.editing.clone() else {
+            return;
+        };
+        let Some(note) = self.notes.note_mut(&symbol, idx) else {
+            self.editing = None;
+            return;
+        };
+
+        let mut changed = false;
+        let mut delete = false;
+        ui.label(format!("{symbol} at {}", Data::format_ts(note.ts as f64)));
+        Grid::new("journal editor").show(ui, |ui| {
+            ui.label("side");
+            ComboBox::from_id_source("journal direction")
+                .selected_text(note.direction.map(|d| d.as_str()).unwrap_or("none"))
+                .show_ui(ui, |ui| {
+                    changed |= ui
+                        .selectable_value(&mut note.direction, None, "none")
+                        .changed();
+                    [Direction::Long, Direction::Short]
+                        .into_iter()
+                        .for_each(|d| {
+                            changed |= ui
+                                .selectable_value(&mut note.direction, Some(d), d.as_str())
+                                .changed();
+                        });
+                });
+            ui.end_row();
+
+            let price = note.price;
+            [
+                ("entry", &mut note.entry, price),
+                ("exit", &mut note.exit, price),
+                ("size", &mut note.size, 1.0),
+            ]
+            .into_iter()
+            .for_each(|(name, value, default)| {
+                ui.label(name);
+                changed |= optional_ui(ui, value, default);
+                ui.end_row();
+            });
+
+            ui.label("note");
+            changed |= ui
+                .add(TextEdit::multiline(&mut note.text).desired_rows(2))
+                .changed();
+            ui.end_row();
+        });
+        ui.horizontal(|ui| {
+            if ui.button("done").clicked() {
+                self.editing = None;
+            }
+            delete = ui.button("delete").clicked();
+        });
+
+        if delete {
+            self.notes.remove(&symbol, idx);
+            self.editing = None;
+        }
+        self.edited |= changed || delete;
+    }
+}
+
+/// Checkbox setting whether the value is known with a drag value of it, `default` is taken
+/// once checked.
+fn optional_ui(ui: &mut Ui, value: &mut Option<f64>, default: f64) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        let mut known = value.is_some();
+        if ui.checkbox(&mut known, "").changed() {
+            *value = known.then_some(default);
+            changed = true;
+        }
+        if let Some(v) = value {
+            changed |= ui
+                .add(DragValue::new(v).speed(0.01).clamp_range(0.0..=f64::MAX))
+                .changed();
+        }
+    });
+
+    changed
+}
+
+impl AppWindow for Journal {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Journal").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+
+        let mut visible = self.visible;
+        Window::new("journal")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .default_width(600.0)
+            .show(ui.ctx(), |ui| {
+                let rows = self.rows();
+                if rows.is_empty() {
+                    ui.label("no notes, add them from the candles context menu");
+                    return;
+                }
+                self.rows_ui(ui, &rows);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!("total P&L {:.2}", journal::total_pnl(&rows)))
+                            .strong(),
+                    );
+                    ui.weak(format!(
+                        "fee {} bps per fill, set in settings",
+                        self.config.fee_bps()
+                    ));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("export csv").clicked() {
+                        self.export("csv");
+                    }
+                    if ui.button("export json").clicked() {
+                        self.export("json");
+                    }
+                });
+                if self.editing.is_some() {
+                    ui.separator();
+                    self.editor_ui(ui);
+                }
+            });
+        self.visible = visible;
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("journal", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("journal").unwrap_or(self.visible);
+    }
+}
+
+#[cfg(test)]
+mod journal_window_tests {
+    use crossbeam::channel::unbounded;
+
+    use crate::netstrat::notes::Note;
+
+    use super::*;
+
+    #[test]
+    fn test_edits_taken_once() {
+        let mut notes = Notes::default();
+        notes.set("BTCUSDT", vec![Note::default(), Note::default()]);
+        let mut window = Journal::new(true, Default::default(), unbounded().0);
+        window.set_notes(&notes);
+        assert_eq!(window.take_edited(), None);
+
+        window.editing = Some(("BTCUSDT".to_string(), 1));
+        window.notes.remove("BTCUSDT", 0);
+        window.edited = true;
+        assert_eq!(window.take_edited().unwrap().get("BTCUSDT").len(), 1);
+        assert_eq!(window.take_edited(), None);
+
+        window.set_notes(&window.notes.clone());
+        assert_eq!(window.editing, None);
+    }
+}
@@ -1,7 +1,31 @@
+mod backtest;
+mod correlation;
+mod expressions;
 mod graph;
+mod journal;
+mod network;
+mod portfolio;
+mod quality;
+mod scheduler;
+mod scripts;
+mod settings;
+mod stats;
 mod time_range_chooser;
+mod watchlist;
 mod window;
 
+pub use self::backtest::Backtest;
+pub use self::correlation::Correlation;
+pub use self::expressions::Expressions;
 pub use self::graph::SymbolsGraph;
+pub use self::journal::Journal;
+pub use self::network::Network;
+pub use self::portfolio::Portfolio;
+pub use self::quality::DataQuality;
+pub use self::scheduler::Scheduler;
+pub use self::scripts::Scripts;
+pub use self::settings::Settings;
+pub use self::stats::Statistics;
 pub use self::time_range_chooser::TimeRangeChooser;
+pub use self::watchlist::Watchlist;
 pub use self::window::AppWindow;
@@ -0,0 +1,107 @@
+use chrono::DateTime;
+use egui::{Color32, Context, Grid, RichText, ScrollArea, Ui, Window};
+
+use crate::{
+    netstrat::{actions::Actions, session::Session},
+    network::webhook::{Delivery, Webhook},
+};
+
+use super::AppWindow;
+
+const TS_FORMAT: &str = "%H:%M:%S";
+const ACTION_TOGGLE: &str = "window.network";
+
+/// Log of webhook deliveries, the newest first.
+pub struct Network {
+    visible: bool,
+    webhook: Webhook,
+    actions: Actions,
+}
+
+impl Network {
+    pub fn new(visible: bool, webhook: Webhook, actions: Actions) -> Self {
+        actions.register(ACTION_TOGGLE, "toggle network window", None);
+        Self {
+            visible,
+            webhook,
+            actions,
+        }
+    }
+
+    fn row_ui(ui: &mut Ui, delivery: &Delivery) {
+        ui.label(
+            DateTime::from_timestamp_millis(delivery.at)
+                .map(|dt| dt.format(TS_FORMAT).to_string())
+                .unwrap_or_default(),
+        );
+        ui.label(delivery.kind);
+        ui.label(delivery.attempts.to_string());
+        match &delivery.result {
+            Ok(status) => ui.label(status.to_string()),
+            Err(err) => ui.label(RichText::new(err).color(Color32::RED)),
+        };
+        ui.label(&delivery.url);
+        ui.end_row();
+    }
+}
+
+impl AppWindow for Network {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Network").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn tick(&mut self, _ctx: &Context) {
+        if self.actions.take(ACTION_TOGGLE) {
+            self.toggle();
+        }
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+
+        let mut visible = self.visible;
+        Window::new("network")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .default_width(400.0)
+            .show(ui.ctx(), |ui| {
+                ui.label("webhook deliveries");
+                let log = self.webhook.log();
+                if log.is_empty() {
+                    ui.label("nothing sent yet");
+                    return;
+                }
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    Grid::new("webhook log")
+                        .num_columns(5)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ["time", "event", "attempts", "result", "url"]
+                                .into_iter()
+                                .for_each(|h| {
+                                    ui.strong(h);
+                                });
+                            ui.end_row();
+                            log.iter().for_each(|d| Self::row_ui(ui, d));
+                        });
+                });
+            });
+        self.visible = visible;
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("network", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("network").unwrap_or(self.visible);
+    }
+}
@@ -0,0 +1,600 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{Duration, Instant};
+
+#[allow(deprecated)]
+use chrono::Date;
+use chrono::{DateTime, NaiveDate, Utc};
+use egui::{
+    plot::{Line, Plot, Values},
+    Color32, ComboBox, DragValue, Grid, RichText, ScrollArea, TextEdit, Ui, Vec2, Window,
+};
+use egui_extras::DatePickerButton;
+use poll_promise::Promise;
+use tracing::{error, info};
+
+use crate::{
+    errors::Result,
+    netstrat::{
+        aggregate::EmptyBuckets,
+        data::Data,
+        download,
+        indicators::IndicatorOutput,
+        notes::Direction,
+        portfolio::{self, Fill, Position, Quote, Source},
+        props::Props,
+        session::Session,
+    },
+    sources::binance::{Client, Kline, Ticker},
+};
+
+use super::AppWindow;
+
+/// Prices of the symbols not loaded in the chart are refreshed this often.
+const REFRESH_EVERY: Duration = Duration::from_secs(60);
+/// Symbols without a price are fetched this long after the last request at the earliest.
+const RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// Symbol with the times of the entry and the exit a sparkline is fetched for.
+type SparklineKey = (String, i64, Option<i64>);
+type Sparklines = Vec<(SparklineKey, Result<Vec<Kline>>)>;
+type Tickers = Result<Vec<(String, Ticker)>>;
+
+/// Hypothetical positions marked to the last close loaded in the chart or the last price of
+/// the 24 hour ticker of Binance. Positions of the shown symbol are drawn on the chart.
+pub struct Portfolio {
+    visible: bool,
+    portfolio: portfolio::Portfolio,
+    quotes: BTreeMap<String, Quote>,
+    /// Symbol shown in the chart.
+    symbol: String,
+    tickers_promise: Option<Promise<Tickers>>,
+    requested: Option<Instant>,
+    tickers_error: Option<String>,
+    /// Closes since the entry of the positions.
+    sparklines: BTreeMap<SparklineKey, Vec<[f64; 2]>>,
+    sparklines_promise: Option<Promise<Sparklines>>,
+    show_markers: bool,
+    /// Position being edited.
+    editing: Option<usize>,
+    output: IndicatorOutput,
+    changed: bool,
+}
+
+impl Portfolio {
+    pub fn new(visible: bool) -> Self {
+        Self {
+            visible,
+            portfolio: Default::default(),
+            quotes: Default::default(),
+            symbol: Default::default(),
+            tickers_promise: None,
+            requested: None,
+            tickers_error: None,
+            sparklines: Default::default(),
+            sparklines_promise: None,
+            show_markers: true,
+            editing: None,
+            output: Default::default(),
+            changed: false,
+        }
+    }
+
+    pub fn portfolio(&self) -> &portfolio::Portfolio {
+        &self.portfolio
+    }
+
+    pub fn set_portfolio(&mut self, portfolio: portfolio::Portfolio) {
+        self.portfolio = portfolio;
+        self.editing = None;
+        self.update_output();
+    }
+
+    pub fn set_symbol(&mut self, symbol: &str) {
+        self.symbol = symbol.to_string();
+        self.update_output();
+    }
+
+    /// Marks positions of the symbol to the last loaded candle, prices of the chart stay
+    /// until the ticker refreshes them.
+    pub fn set_close(&mut self, symbol: &str, kline: &Kline) {
+        let quote = Quote {
+            price: kline.close as f64,
+            ts: kline.t_close.min(Utc::now().timestamp_millis()),
+            source: Source::Chart,
+        };
+        if self.quotes.get(symbol) != Some(&quote) {
+            self.quotes.insert(symbol.to_string(), quote);
+        }
+    }
+
+    /// Entries and exits of the positions of the shown symbol.
+    pub fn outputs(&self) -> impl Iterator<Item = &IndicatorOutput> {
+        self.show_markers.then_some(&self.output).into_iter()
+    }
+
+    /// Returns true once after the output changed.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn update_output(&mut self) {
+        let markers = self.portfolio.markers(&self.symbol);
+        if markers != self.output.markers {
+            self.output.markers = markers;
+            self.changed = true;
+        }
+    }
+
+    /// Positions except the edited one, its symbol may be half typed.
+    fn settled(&self) -> impl Iterator<Item = &Position> {
+        self.portfolio
+            .positions
+            .iter()
+            .enumerate()
+            .filter(|(idx, p)| Some(*idx) != self.editing && !p.symbol.is_empty())
+            .map(|(_, p)| p)
+    }
+
+    /// Symbols of the open positions other than the shown one, their prices come from the
+    /// ticker.
+    fn ticker_symbols(&self) -> Vec<String> {
+        let symbols: BTreeSet<&str> = self
+            .settled()
+            .filter(|p| p.exit.is_none() && p.symbol != self.symbol)
+            .map(|p| p.symbol.as_str())
+            .collect();
+        symbols.into_iter().map(String::from).collect()
+    }
+
+    fn refresh_due(&self, symbols: &[String]) -> bool {
+        let Some(requested) = self.requested else {
+            return true;
+        };
+        let missing = symbols
+            .iter()
+            .any(|s| self.quotes.get(s).is_none_or(|q| q.source == Source::Chart));
+
+        requested.elapsed() >= REFRESH_EVERY || (missing && requested.elapsed() >= RETRY_AFTER)
+    }
+
+    fn refresh_tickers(&mut self) {
+        if let Some(promise) = self.tickers_promise.take() {
+            match promise.try_take() {
+                Ok(Ok(tickers)) => {
+                    self.tickers_error = None;
+                    tickers.into_iter().for_each(|(symbol, t)| {
+                        self.quotes.insert(
+                            symbol,
+                            Quote {
+                                price: t.last_price,
+                                ts: t.ts,
+                                source: Source::Ticker,
+                            },
+                        );
+                    });
+                }
+                Ok(Err(err)) => {
+                    error!("failed to refresh portfolio prices: {}", err.chain());
+                    self.tickers_error = Some(err.user_message());
+                }
+                Err(promise) => self.tickers_promise = Some(promise),
+            }
+            return;
+        }
+
+        let symbols = self.ticker_symbols();
+        if symbols.is_empty() || !self.refresh_due(&symbols) {
+            return;
+        }
+        info!("refreshing portfolio prices of {symbols:?}");
+        self.requested = Some(Instant::now());
+        self.tickers_promise = Some(Promise::spawn_async(async move {
+            Client::default().tickers(&symbols).await
+        }));
+    }
+
+    fn sparkline_key(p: &Position) -> SparklineKey {
+        (p.symbol.clone(), p.entry.ts, p.exit.map(|e| e.ts))
+    }
+
+    /// Fetches closes since the entry of the positions without a sparkline, one position at
+    /// a time.
+    fn refresh_sparklines(&mut self) {
+        if let Some(promise) = self.sparklines_promise.take() {
+            match promise.try_take() {
+                Ok(fetched) => fetched.into_iter().for_each(|(key, klines)| {
+                    let points = klines
+                        .unwrap_or_else(|err| {
+                            error!("failed to fetch sparkline of {}: {}", key.0, err.chain());
+                            vec![]
+                        })
+                        .iter()
+                        .map(|k| [k.t_open as f64, k.close as f64])
+                        .collect();
+                    self.sparklines.insert(key, points);
+                }),
+                Err(promise) => self.sparklines_promise = Some(promise),
+            }
+            return;
+        }
+
+        let now = Utc::now();
+        let missing: Vec<(SparklineKey, Props)> = self
+            .settled()
+            .filter(|p| p.entry.ts < now.timestamp_millis())
+            .filter(|p| !self.sparklines.contains_key(&Portfolio::sparkline_key(p)))
+            .filter_map(|p| {
+                let start = DateTime::from_timestamp_millis(p.entry.ts)?;
+                let end = p
+                    .exit
+                    .and_then(|e| DateTime::from_timestamp_millis(e.ts))
+                    .unwrap_or(now);
+                let props = Props::new(
+                    start.naive_utc(),
+                    end.naive_utc(),
+                    p.sparkline_interval(now.timestamp_millis()),
+                    EmptyBuckets::Skip,
+                );
+                Some((Portfolio::sparkline_key(p), props))
+            })
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        self.sparklines_promise = Some(Promise::spawn_async(async move {
+            let client = Client::default();
+            let mut fetched = vec![];
+            for (key, props) in missing {
+                let klines = download::download(&client, &key.0, &props).await;
+                fetched.push((key, klines));
+            }
+            fetched
+        }));
+    }
+
+    /// Opens the editor with a long position of the shown symbol entered now at its price.
+    fn add(&mut self) {
+        let price = self.quotes.get(&self.symbol).map_or(0.0, |q| q.price);
+        self.portfolio.positions.push(Position {
+            symbol: self.symbol.clone(),
+            direction: Direction::Long,
+            size: 1.0,
+            entry: Fill {
+                ts: Utc::now().timestamp_millis(),
+                price,
+            },
+            exit: None,
+        });
+        self.editing = Some(self.portfolio.positions.len() - 1);
+        self.update_output();
+    }
+
+    fn rows_ui(&mut self, ui: &mut Ui) {
+        let mut edit = None;
+        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            Grid::new("portfolio rows").striped(true).show(ui, |ui| {
+                [
+                    "symbol", "side", "size", "entry", "since", "price", "value", "P&L", "", "",
+                ]
+                .into_iter()
+                .for_each(|h| {
+                    ui.strong(h);
+                });
+                ui.end_row();
+
+                self.portfolio
+                    .positions
+                    .iter()
+                    .enumerate()
+                    .for_each(|(idx, p)| {
+                        let quote = self.quotes.get(&p.symbol);
+                        let mark = p.mark(quote);
+                        ui.label(&p.symbol);
+                        ui.label(p.direction.as_str());
+                        ui.label(p.size.to_string());
+                        ui.label(p.entry.price.to_string());
+                        ui.label(Data::format_ts(p.entry.ts as f64));
+                        match (mark, p.exit, quote) {
+                            (Some(price), Some(_), _) => {
+                                ui.label(price.to_string()).on_hover_text("closed");
+                            }
+                            (Some(price), None, Some(q)) => {
+                                let source = match q.source {
+                                    Source::Chart => "last loaded close",
+                                    Source::Ticker => "24h ticker",
+                                };
+                                ui.label(price.to_string()).on_hover_text(format!(
+                                    "{source} at {}",
+                                    Data::format_ts(q.ts as f64)
+                                ));
+                            }
+                            _ => {
+                                ui.weak("-");
+                            }
+                        };
+                        match mark {
+                            Some(price) => {
+                                ui.label(format!("{:.2}", p.value(price)));
+                                pnl_label(ui, p.pnl(price));
+                            }
+                            None => {
+                                ui.weak("-");
+                                ui.weak("-");
+                            }
+                        };
+                        match self.sparklines.get(&Portfolio::sparkline_key(p)) {
+                            Some(points) if points.len() > 1 => {
+                                sparkline_ui(ui, idx, points, p.pnl(points[points.len() - 1][1]))
+                            }
+                            _ => {
+                                ui.weak("");
+                            }
+                        };
+                        if ui
+                            .selectable_label(self.editing == Some(idx), "edit")
+                            .clicked()
+                        {
+                            edit = Some(idx);
+                        }
+                        ui.end_row();
+                    });
+
+                let (value, pnl) = self.portfolio.totals(&self.quotes);
+                ui.strong("total");
+                (0..5).for_each(|_| {
+                    ui.label("");
+                });
+                ui.strong(format!("{value:.2}"));
+                pnl_label(ui, pnl);
+                ui.end_row();
+            });
+        });
+
+        if let Some(idx) = edit {
+            self.editing = match self.editing == Some(idx) {
+                true => None,
+                false => Some(idx),
+            };
+        }
+    }
+
+    /// Fields of the edited position, an exit left unchecked keeps it open.
+    fn editor_ui(&mut self, ui: &mut Ui) {
+        let Some(idx) = self.editing else {
+            return;
+        };
+        let price = self
+            .portfolio
+            .positions
+            .get(idx)
+            .and_then(|p| self.quotes.get(&p.symbol))
+            .map(|q| q.price);
+        let Some(p) = self.portfolio.positions.get_mut(idx) else {
+            self.editing = None;
+            return;
+        };
+
+        let mut delete = false;
+        Grid::new("portfolio editor").show(ui, |ui| {
+            ui.label("symbol");
+            if ui
+                .add(TextEdit::singleline(&mut p.symbol).desired_width(120.0))
+                .changed()
+            {
+                p.symbol = p.symbol.trim().to_uppercase();
+            }
+            ui.end_row();
+
+            ui.label("side");
+            ComboBox::from_id_source("portfolio direction")
+                .selected_text(p.direction.as_str())
+                .show_ui(ui, |ui| {
+                    [Direction::Long, Direction::Short]
+                        .into_iter()
+                        .for_each(|d| {
+                            ui.selectable_value(&mut p.direction, d, d.as_str());
+                        });
+                });
+            ui.end_row();
+
+            ui.label("size");
+            ui.add(
+                DragValue::new(&mut p.size)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f64::MAX),
+            );
+            ui.end_row();
+
+            ui.label("entry");
+            fill_ui(ui, &mut p.entry, "portfolio entry date");
+            ui.end_row();
+
+            ui.label("exit");
+            ui.horizontal(|ui| {
+                let mut closed = p.exit.is_some();
+                if ui.checkbox(&mut closed, "").changed() {
+                    p.exit = closed.then(|| Fill {
+                        ts: Utc::now().timestamp_millis(),
+                        price: price.unwrap_or(p.entry.price),
+                    });
+                }
+                if let Some(exit) = &mut p.exit {
+                    fill_ui(ui, exit, "portfolio exit date");
+                }
+            });
+            ui.end_row();
+        });
+        ui.horizontal(|ui| {
+            if ui.button("done").clicked() {
+                self.editing = None;
+            }
+            delete = ui.button("delete").clicked();
+        });
+
+        if delete {
+            self.portfolio.positions.remove(idx);
+            self.editing = None;
+        }
+        self.update_output();
+    }
+}
+
+fn pnl_label(ui: &mut Ui, pnl: f64) {
+    let color = match pnl >= 0.0 {
+        true => Color32::LIGHT_GREEN,
+        false => Color32::LIGHT_RED,
+    };
+    ui.colored_label(color, format!("{pnl:.2}"));
+}
+
+/// Closes since the entry colored by the P&L at the last of them.
+fn sparkline_ui(ui: &mut Ui, idx: usize, points: &[[f64; 2]], pnl: f64) {
+    let color = match pnl >= 0.0 {
+        true => Color32::LIGHT_GREEN,
+        false => Color32::LIGHT_RED,
+    };
+    let values = Values::from_values(
+        points
+            .iter()
+            .map(|[x, y]| egui::plot::Value::new(*x, *y))
+            .collect(),
+    );
+    Plot::new(("portfolio sparkline", idx))
+        .width(100.0)
+        .height(24.0)
+        .set_margin_fraction(Vec2::new(0.0, 0.1))
+        .allow_boxed_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .allow_zoom(false)
+        .show_axes([false, false])
+        .show_x(false)
+        .show_y(false)
+        .show_background(false)
+        .show(ui, |plot_ui| plot_ui.line(Line::new(values).color(color)));
+}
+
+/// Price and date of a fill, the time of the day is kept when the date changes.
+#[allow(deprecated)]
+fn fill_ui(ui: &mut Ui, fill: &mut Fill, id_source: &str) {
+    ui.horizontal(|ui| {
+        ui.add(
+            DragValue::new(&mut fill.price)
+                .speed(0.01)
+                .clamp_range(0.0..=f64::MAX),
+        );
+        let Some(dt) = DateTime::from_timestamp_millis(fill.ts) else {
+            return;
+        };
+        let mut date = Date::<Utc>::from_utc(dt.date_naive(), Utc);
+        ui.add(DatePickerButton::new(&mut date).id_source(id_source));
+        let picked: NaiveDate = date.naive_utc();
+        if picked != dt.date_naive() {
+            fill.ts = picked.and_time(dt.time()).and_utc().timestamp_millis();
+        }
+    });
+}
+
+impl AppWindow for Portfolio {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Portfolio").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn tick(&mut self, _ctx: &egui::Context) {
+        if !self.visible {
+            return;
+        }
+        self.refresh_tickers();
+        self.refresh_sparklines();
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+
+        let mut visible = self.visible;
+        Window::new("portfolio")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .default_width(700.0)
+            .show(ui.ctx(), |ui| {
+                if self.portfolio.positions.is_empty() {
+                    ui.label("no positions, add one of the shown symbol");
+                } else {
+                    self.rows_ui(ui);
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("add position").clicked() {
+                        self.add();
+                    }
+                    if ui
+                        .checkbox(&mut self.show_markers, "show on chart")
+                        .changed()
+                    {
+                        self.changed = true;
+                    }
+                    if self.tickers_promise.is_some() || self.sparklines_promise.is_some() {
+                        ui.spinner();
+                    }
+                });
+                if let Some(err) = &self.tickers_error {
+                    ui.label(
+                        RichText::new(format!("failed to refresh prices: {err}"))
+                            .color(Color32::LIGHT_RED),
+                    );
+                }
+                if self.editing.is_some() {
+                    ui.separator();
+                    self.editor_ui(ui);
+                }
+            });
+        self.visible = visible;
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("portfolio", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("portfolio").unwrap_or(self.visible);
+    }
+}
+
+#[cfg(test)]
+mod portfolio_window_tests {
+    use super::*;
+
+    #[test]
+    fn test_markers_of_shown_symbol() {
+        let mut window = Portfolio::new(true);
+        window.set_symbol("BTCUSDT");
+        window.quotes.insert(
+            "BTCUSDT".to_string(),
+            Quote {
+                price: 100.0,
+                ts: 0,
+                source: Source::Chart,
+            },
+        );
+        window.add();
+        assert!(window.take_changed());
+        assert_eq!(window.outputs().next().unwrap().markers.len(), 1);
+        assert_eq!(window.portfolio().positions[0].entry.price, 100.0);
+
+        window.set_symbol("ETHUSDT");
+        assert!(window.take_changed());
+        assert!(window.outputs().next().unwrap().markers.is_empty());
+        // the edited symbol may be half typed
+        assert!(window.ticker_symbols().is_empty());
+        window.editing = None;
+        assert_eq!(window.ticker_symbols(), vec!["BTCUSDT"]);
+        assert!(window.refresh_due(&window.ticker_symbols()));
+    }
+}
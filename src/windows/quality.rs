@@ -0,0 +1,214 @@
+use egui::{DragValue, Grid, ScrollArea, Ui, Window};
+
+use crate::{
+    netstrat::{
+        bounds::{Bounds, BoundsSet},
+        quality::{self, Kind, Report},
+        session::Session,
+        stats,
+    },
+    sources::binance::Kline,
+    widgets::copy_json_ui,
+};
+
+use super::AppWindow;
+
+/// Report of gaps, duplicates and anomalies in the loaded candles. A finding picked
+/// from the list is shown on the chart, affected ranges can be downloaded again.
+pub struct DataQuality {
+    visible: bool,
+    z_score: f64,
+    klines: Vec<Kline>,
+    loaded: BoundsSet,
+    report: Report,
+    /// Whether the report is outdated, it is rebuilt only while the window is shown.
+    stale: bool,
+    selected: Option<usize>,
+    focus: Option<Bounds>,
+    refetch: Option<BoundsSet>,
+}
+
+impl DataQuality {
+    pub fn new(visible: bool) -> Self {
+        Self {
+            visible,
+            z_score: 6.0,
+            klines: Default::default(),
+            loaded: Default::default(),
+            report: Default::default(),
+            stale: false,
+            selected: None,
+            focus: None,
+            refetch: None,
+        }
+    }
+
+    /// Candles to check with the ranges they were loaded for.
+    pub fn set_data(&mut self, klines: &[Kline], loaded: &BoundsSet) {
+        self.klines = klines.to_vec();
+        self.loaded = loaded.clone();
+        self.stale = true;
+    }
+
+    /// Time range of the picked finding while the window is shown.
+    pub fn highlight(&self) -> Option<Bounds> {
+        let idx = self.selected.filter(|_| self.visible)?;
+        self.report.findings.get(idx).map(|f| f.bounds)
+    }
+
+    /// Returns the range of the finding once after it was picked.
+    pub fn take_focus(&mut self) -> Option<Bounds> {
+        self.focus.take()
+    }
+
+    /// Returns the ranges to download again once after it was requested.
+    pub fn take_refetch(&mut self) -> Option<BoundsSet> {
+        self.refetch.take()
+    }
+
+    /// Rebuilds the report keeping the picked finding if it is still there.
+    fn check(&mut self) {
+        let selected = self
+            .selected
+            .and_then(|idx| self.report.findings.get(idx).cloned());
+        self.report = quality::check(&self.klines, &self.loaded, self.z_score);
+        self.selected = selected.and_then(|s| self.report.findings.iter().position(|f| *f == s));
+        self.stale = false;
+    }
+
+    fn summary_ui(&mut self, ui: &mut Ui) {
+        Grid::new("data quality summary").show(ui, |ui| {
+            Kind::ALL.iter().for_each(|kind| {
+                ui.label(kind.as_str());
+                ui.label(self.report.count(*kind).to_string());
+                ui.end_row();
+            });
+        });
+    }
+
+    fn findings_ui(&mut self, ui: &mut Ui) {
+        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            self.report
+                .findings
+                .iter()
+                .enumerate()
+                .for_each(|(idx, f)| {
+                    if ui
+                        .selectable_label(self.selected == Some(idx), f.describe())
+                        .clicked()
+                    {
+                        self.selected = Some(idx);
+                        self.focus = Some(f.bounds);
+                    }
+                });
+        });
+    }
+}
+
+impl AppWindow for DataQuality {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Data quality").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+        if self.stale {
+            self.check();
+        }
+
+        let mut visible = self.visible;
+        Window::new("data quality")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .default_width(400.0)
+            .show(ui.ctx(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("extreme return beyond");
+                    if ui
+                        .add(
+                            DragValue::new(&mut self.z_score)
+                                .clamp_range(1.0..=100.0)
+                                .speed(0.1)
+                                .suffix(" std"),
+                        )
+                        .changed()
+                    {
+                        self.check();
+                    }
+                });
+                ui.separator();
+                self.summary_ui(ui);
+                ui.separator();
+                if self.report.findings.is_empty() {
+                    ui.label("no findings");
+                    return;
+                }
+                self.findings_ui(ui);
+                ui.separator();
+                if let Some(b) = self.highlight() {
+                    let klines = stats::within(&self.klines, b);
+                    if !klines.is_empty() {
+                        let text = format!("copy {} candles of the finding as json", klines.len());
+                        if let Some(json) = copy_json_ui(ui, &text, klines) {
+                            ui.output().copied_text = json;
+                        }
+                    }
+                }
+                if ui
+                    .button("re-fetch affected ranges")
+                    .on_hover_text("download candles of the findings again")
+                    .clicked()
+                {
+                    self.refetch = Some(self.report.affected());
+                }
+            });
+        self.visible = visible;
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("data quality", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("data quality").unwrap_or(self.visible);
+    }
+}
+
+#[cfg(test)]
+mod quality_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_highlight_while_shown() {
+        let mut klines = mock::klines(0, 60_000, 10);
+        let loaded = BoundsSet::new(vec![Bounds(0, 600_000 - 1)]);
+        klines.drain(3..5);
+        let mut window = DataQuality::new(true);
+        window.set_data(&klines, &loaded);
+        window.check();
+
+        window.selected = Some(0);
+        assert_eq!(window.highlight(), Some(Bounds(180_000, 300_000 - 1)));
+
+        window.visible = false;
+        assert_eq!(window.highlight(), None);
+
+        window.set_data(&klines, &loaded);
+        window.check();
+        assert_eq!(window.selected, Some(0));
+
+        window.set_data(&mock::klines(0, 60_000, 10), &loaded);
+        window.check();
+        assert_eq!(window.selected, None);
+    }
+}
@@ -0,0 +1,292 @@
+use crossbeam::channel::Sender;
+use egui::{
+    Color32, ComboBox, Context, DragValue, Grid, RichText, ScrollArea, TextEdit, Ui, Window,
+};
+use quick_error::ResultExt;
+use tracing::error;
+
+use crate::{
+    errors::Error,
+    netstrat::{
+        actions::Actions,
+        data::Data,
+        scheduler::{Job, JobFormat, Jobs, Run},
+        session::Session,
+    },
+    sources::binance::Interval,
+    widgets::Toast,
+};
+
+use super::AppWindow;
+
+const JOBS_KEY: &str = "scheduled_jobs";
+const ACTION_TOGGLE: &str = "window.scheduler";
+
+/// Recurring exports appending to files while the app runs, with the history of their runs.
+pub struct Scheduler {
+    visible: bool,
+    jobs: Jobs,
+    /// Job being defined, symbols are comma separated.
+    new_job: Job,
+    new_symbols: String,
+    /// Runs already looked at for failures.
+    seen_runs: usize,
+    actions: Actions,
+    toast_pub: Sender<Toast>,
+}
+
+impl Scheduler {
+    pub fn new(
+        visible: bool,
+        jobs: Jobs,
+        actions: Actions,
+        toast_pub: Sender<Toast>,
+        storage: Option<&dyn eframe::Storage>,
+    ) -> Self {
+        actions.register(ACTION_TOGGLE, "toggle scheduler window", None);
+        if let Some(saved) = storage.and_then(|s| eframe::get_value(s, JOBS_KEY)) {
+            jobs.set_jobs(saved);
+        }
+        Self {
+            visible,
+            seen_runs: jobs.runs(),
+            jobs,
+            new_job: Default::default(),
+            new_symbols: String::new(),
+            actions,
+            toast_pub,
+        }
+    }
+
+    fn toast(&self, toast: Toast) {
+        if let Err(err) = self.toast_pub.send(toast).context("toast") {
+            error!("{}", Error::from(err).chain());
+        }
+    }
+
+    fn jobs_ui(&self, ui: &mut Ui) {
+        let jobs = self.jobs.jobs();
+        if jobs.is_empty() {
+            ui.label("no jobs yet");
+            return;
+        }
+        Grid::new("scheduled jobs").striped(true).show(ui, |ui| {
+            [
+                "on",
+                "id",
+                "symbols",
+                "interval",
+                "format",
+                "every",
+                "synced to",
+                "next run",
+                "",
+            ]
+            .into_iter()
+            .for_each(|h| {
+                ui.strong(h);
+            });
+            ui.end_row();
+
+            jobs.iter().for_each(|job| {
+                let mut enabled = job.enabled;
+                if ui.checkbox(&mut enabled, "").changed() {
+                    self.jobs.set_enabled(job.id, enabled);
+                }
+                ui.label(job.id.to_string());
+                ui.label(job.symbols.join(", "))
+                    .on_hover_text(job.path("SYMBOL").display().to_string());
+                ui.label(&job.interval);
+                ui.label(job.format.name());
+                ui.label(format!("{} min", job.cadence_mins));
+                ui.label(ts(job.synced_to));
+                ui.label(match job.enabled {
+                    true => job.next_run().map_or("now".to_string(), |t| ts(Some(t))),
+                    false => "-".to_string(),
+                });
+                ui.horizontal(|ui| {
+                    let running = self.jobs.is_running(job.id);
+                    if ui
+                        .add_enabled(!running, egui::Button::new("run now"))
+                        .clicked()
+                    {
+                        self.jobs.run_now(job.id);
+                    }
+                    if ui.add_enabled(!running, egui::Button::new("✖")).clicked() {
+                        self.jobs.remove(job.id);
+                    }
+                    if running {
+                        ui.spinner();
+                    }
+                });
+                ui.end_row();
+            });
+        });
+    }
+
+    fn new_job_ui(&mut self, ui: &mut Ui) {
+        Grid::new("new job").num_columns(2).show(ui, |ui| {
+            ui.label("symbols");
+            ui.add(TextEdit::singleline(&mut self.new_symbols).hint_text("BTCUSDT, ETHUSDT"));
+            ui.end_row();
+
+            ui.label("interval");
+            ComboBox::from_id_source("job_interval")
+                .selected_text(&self.new_job.interval)
+                .show_ui(ui, |ui| {
+                    Interval::ALL.into_iter().for_each(|i| {
+                        ui.selectable_value(
+                            &mut self.new_job.interval,
+                            i.as_str().to_string(),
+                            i.as_str(),
+                        );
+                    });
+                });
+            ui.end_row();
+
+            ui.label("format");
+            ComboBox::from_id_source("job_format")
+                .selected_text(self.new_job.format.name())
+                .show_ui(ui, |ui| {
+                    JobFormat::ALL.into_iter().for_each(|f| {
+                        ui.selectable_value(&mut self.new_job.format, f, f.name());
+                    });
+                });
+            ui.end_row();
+
+            ui.label("destination");
+            ui.text_edit_singleline(&mut self.new_job.destination);
+            ui.end_row();
+
+            ui.label("every");
+            ui.add(
+                DragValue::new(&mut self.new_job.cadence_mins)
+                    .clamp_range(1..=7 * 24 * 60)
+                    .suffix(" min"),
+            );
+            ui.end_row();
+        });
+
+        let symbols: Vec<String> = self
+            .new_symbols
+            .split(',')
+            .map(|s| s.trim().to_uppercase())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let valid = !symbols.is_empty() && !self.new_job.destination.trim().is_empty();
+        if ui
+            .add_enabled(valid, egui::Button::new("add job"))
+            .on_disabled_hover_text("symbols and destination are required")
+            .clicked()
+        {
+            self.jobs.add(Job {
+                symbols,
+                ..self.new_job.clone()
+            });
+            self.new_symbols.clear();
+        }
+    }
+
+    fn history_ui(ui: &mut Ui, history: &[Run]) {
+        if history.is_empty() {
+            ui.label("no runs yet");
+            return;
+        }
+        ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+            Grid::new("job history").striped(true).show(ui, |ui| {
+                ["time", "job", "appended", "result"]
+                    .into_iter()
+                    .for_each(|h| {
+                        ui.strong(h);
+                    });
+                ui.end_row();
+
+                history.iter().for_each(|run| {
+                    ui.label(ts(Some(run.at)));
+                    ui.label(run.job.to_string());
+                    ui.label(run.appended.to_string());
+                    match run.succeeded() {
+                        true => ui.label("ok"),
+                        false => ui.label(RichText::new(run.errors.join("\n")).color(Color32::RED)),
+                    };
+                    ui.end_row();
+                });
+            });
+        });
+    }
+}
+
+fn ts(millis: Option<i64>) -> String {
+    millis.map_or("-".to_string(), |t| Data::format_ts(t as f64))
+}
+
+impl AppWindow for Scheduler {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Scheduler").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn tick(&mut self, _ctx: &Context) {
+        if self.actions.take(ACTION_TOGGLE) {
+            self.toggle();
+        }
+
+        let runs = self.jobs.runs();
+        if runs == self.seen_runs {
+            return;
+        }
+        let new = runs - self.seen_runs;
+        self.seen_runs = runs;
+        self.jobs
+            .history()
+            .into_iter()
+            .take(new)
+            .filter(|run| !run.succeeded())
+            .for_each(|run| {
+                self.toast(Toast::error(format!(
+                    "scheduled job {} failed: {}",
+                    run.job,
+                    run.errors.join(", ")
+                )))
+            });
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+
+        let mut visible = self.visible;
+        Window::new("scheduler")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .default_width(500.0)
+            .show(ui.ctx(), |ui| {
+                ui.label("jobs append candles closed since their last run to a file per symbol");
+                self.jobs_ui(ui);
+                ui.separator();
+                ui.collapsing("new job", |ui| self.new_job_ui(ui));
+                ui.separator();
+                ui.label("history");
+                Self::history_ui(ui, &self.jobs.history());
+            });
+        self.visible = visible;
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, JOBS_KEY, &self.jobs.jobs());
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("scheduler", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("scheduler").unwrap_or(self.visible);
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crossbeam::channel::Sender;
+use egui::{Color32, ScrollArea, TextEdit, TextStyle, Ui, Window};
+use quick_error::ResultExt;
+use tracing::{error, info};
+
+use crate::{
+    errors::{Error, Result},
+    netstrat::{
+        data::Data,
+        indicators::IndicatorOutput,
+        scripting::{self, Script, SCRIPTS_DIR, TIME_LIMIT},
+        session::Session,
+    },
+    widgets::Toast,
+};
+
+use super::AppWindow;
+
+const NEW_SCRIPT: &str = r#"// candles: open, high, low, close, volume, time
+// helpers: sma(arr, n), ema(arr, n), rsi(arr, n)
+#{ name: "sma(10)", overlay: sma(close, 10) }
+"#;
+
+struct Entry {
+    script: Script,
+    enabled: bool,
+    result: Option<Result<IndicatorOutput>>,
+}
+
+impl Entry {
+    fn new(script: Script) -> Self {
+        Self {
+            script,
+            enabled: false,
+            result: None,
+        }
+    }
+}
+
+/// Editor of user scripts. Enabled scripts are re-run every time data changes.
+pub struct Scripts {
+    visible: bool,
+    dir: PathBuf,
+    entries: Vec<Entry>,
+    selected: Option<usize>,
+    data: Arc<Data>,
+    changed: bool,
+    toast_pub: Sender<Toast>,
+}
+
+impl Scripts {
+    pub fn new(visible: bool, toast_pub: Sender<Toast>) -> Self {
+        Self::with_dir(visible, Path::new(SCRIPTS_DIR), toast_pub)
+    }
+
+    pub fn with_dir(visible: bool, dir: &Path, toast_pub: Sender<Toast>) -> Self {
+        let entries = match scripting::load(dir) {
+            Ok(scripts) => scripts.into_iter().map(Entry::new).collect(),
+            Err(err) => {
+                error!("failed to load scripts: {}", err.chain());
+                vec![]
+            }
+        };
+
+        Self {
+            visible,
+            dir: dir.to_path_buf(),
+            selected: (!entries.is_empty()).then_some(0),
+            entries,
+            data: Default::default(),
+            changed: false,
+            toast_pub,
+        }
+    }
+
+    /// Re-runs enabled scripts over the new data.
+    pub fn set_data(&mut self, data: Arc<Data>) {
+        self.data = data;
+        (0..self.entries.len()).for_each(|idx| self.run(idx));
+    }
+
+    /// Outputs of enabled scripts which ran successfully.
+    pub fn outputs(&self) -> impl Iterator<Item = &IndicatorOutput> {
+        self.entries
+            .iter()
+            .filter(|e| e.enabled)
+            .filter_map(|e| e.result.as_ref()?.as_ref().ok())
+    }
+
+    /// Returns true once after any of the outputs changed.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn run(&mut self, idx: usize) {
+        let data = &self.data;
+        if let Some(e) = self.entries.get_mut(idx) {
+            e.result = match e.enabled && !data.vals.is_empty() {
+                true => Some(scripting::run(&e.script.source, data, TIME_LIMIT)),
+                false => None,
+            };
+            self.changed = true;
+        }
+    }
+
+    fn toast(&self, toast: Toast) {
+        if let Err(err) = self.toast_pub.send(toast).context("toast") {
+            error!("{}", Error::from(err).chain());
+        }
+    }
+
+    fn add(&mut self) {
+        let name = (1..)
+            .map(|i| format!("script_{i}"))
+            .find(|name| self.entries.iter().all(|e| &e.script.name != name))
+            .unwrap_or_default();
+
+        self.entries.push(Entry::new(Script::new(name, NEW_SCRIPT)));
+        self.selected = Some(self.entries.len() - 1);
+    }
+
+    fn save_script(&self, idx: usize) {
+        if let Some(e) = self.entries.get(idx) {
+            match e.script.save(&self.dir) {
+                Ok(path) => {
+                    info!("saved script to {path:?}");
+                    self.toast(Toast::info(format!("saved {}", path.display())));
+                }
+                Err(err) => {
+                    error!("failed to save script: {}", err.chain());
+                    self.toast(Toast::error(err.user_message()));
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, idx: usize) {
+        if idx >= self.entries.len() {
+            return;
+        }
+
+        let e = self.entries.remove(idx);
+        if let Err(err) = e.script.remove(&self.dir) {
+            error!("failed to remove script: {}", err.chain());
+            self.toast(Toast::error(err.user_message()));
+        }
+
+        self.selected = match self.entries.is_empty() {
+            true => None,
+            false => Some(idx.min(self.entries.len() - 1)),
+        };
+        self.changed = true;
+    }
+
+    fn list_ui(&mut self, ui: &mut Ui) {
+        let mut toggled = None;
+        self.entries.iter_mut().enumerate().for_each(|(idx, e)| {
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut e.enabled, "").changed() {
+                    toggled = Some(idx);
+                }
+                if ui
+                    .selectable_label(self.selected == Some(idx), &e.script.name)
+                    .clicked()
+                {
+                    self.selected = Some(idx);
+                }
+            });
+        });
+
+        if let Some(idx) = toggled {
+            self.run(idx);
+        }
+
+        if ui.button("new").clicked() {
+            self.add();
+        }
+    }
+
+    fn editor_ui(&mut self, ui: &mut Ui, idx: usize) {
+        let (mut run, mut save, mut remove) = (false, false, false);
+        if let Some(e) = self.entries.get_mut(idx) {
+            ui.horizontal(|ui| {
+                ui.label("name");
+                ui.text_edit_singleline(&mut e.script.name);
+            });
+
+            ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                ui.add(
+                    TextEdit::multiline(&mut e.script.source)
+                        .font(TextStyle::Monospace)
+                        .code_editor()
+                        .desired_rows(15)
+                        .desired_width(f32::INFINITY),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                run = ui.button("run").clicked();
+                save = ui.button("save").clicked();
+                remove = ui.button("delete").clicked();
+            });
+
+            if let Some(Err(err)) = &e.result {
+                ui.colored_label(Color32::LIGHT_RED, err.to_string());
+            }
+        }
+
+        if run {
+            self.entries[idx].enabled = true;
+            self.run(idx);
+        }
+        if save {
+            self.save_script(idx);
+        }
+        if remove {
+            self.remove(idx);
+        }
+    }
+}
+
+impl AppWindow for Scripts {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Scripts").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+
+        let mut visible = self.visible;
+        Window::new("scripts")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .default_width(500.0)
+            .show(ui.ctx(), |ui| {
+                ui.horizontal_top(|ui| {
+                    ui.vertical(|ui| self.list_ui(ui));
+                    ui.separator();
+                    ui.vertical(|ui| match self.selected {
+                        Some(idx) => self.editor_ui(ui, idx),
+                        None => {
+                            ui.label("create a script to start");
+                        }
+                    });
+                });
+            });
+        self.visible = visible;
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("scripts", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("scripts").unwrap_or(self.visible);
+    }
+}
+
+#[cfg(test)]
+mod scripts_tests {
+    use crossbeam::channel::unbounded;
+
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_rerun_on_data_change() {
+        let (s_toasts, _) = unbounded();
+        let dir =
+            std::env::temp_dir().join(format!("netstrat_scripts_window_{}", std::process::id()));
+        let mut scripts = Scripts::with_dir(false, &dir, s_toasts);
+
+        scripts.add();
+        scripts.entries[0].enabled = true;
+        scripts.set_data(Arc::new(Data::new(mock::klines(0, 10, 20))));
+
+        assert!(scripts.take_changed());
+        assert!(!scripts.take_changed());
+        let outputs: Vec<&IndicatorOutput> = scripts.outputs().collect();
+        assert_eq!(outputs[0].overlay[0].points.len(), 11);
+
+        scripts.set_data(Arc::new(Data::new(mock::klines(0, 10, 30))));
+
+        let outputs: Vec<&IndicatorOutput> = scripts.outputs().collect();
+        assert_eq!(outputs[0].overlay[0].points.len(), 21);
+    }
+}
@@ -0,0 +1,764 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crossbeam::channel::Sender;
+use egui::{Button, ComboBox, Context, DragValue, Grid, TextEdit, Ui, Window};
+use poll_promise::Promise;
+use quick_error::ResultExt;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{
+    errors::{Error, Result},
+    netstrat::{
+        actions::Actions,
+        cache::{Cache, CacheConfig, CacheStatus},
+        download::{DownloadConfig, CONCURRENCY_RANGE},
+        export::{ConfirmLimits, ExportOptions, TimeFormat},
+        influx::{Influx, InfluxConfig},
+        journal::{JournalConfig, DEFAULT_FEE_BPS},
+        last_used::{LastUsed, LastUsedConfig},
+        logging::{LogConfig, Logging},
+        memory::{CandlesBudget, DEFAULT_CAP},
+        notify::{Notifier, NotifyConfig},
+        props::CandlesBand,
+        session::Session,
+        timezone::{Timezone, UTC_OFFSET_RANGE},
+    },
+    network::{
+        webhook::{Webhook, WebhookConfig},
+        ws_server::{Broadcaster, Server, DEFAULT_PORT},
+    },
+    widgets::{Appearance, Toast, PLOT_FONT_RANGE, SCALE_RANGE},
+};
+
+use super::AppWindow;
+
+const MIN_CANDLES_CAP: usize = 10_000;
+const PRUNE_PERIOD: Duration = Duration::from_secs(5 * 60);
+const SETTINGS_KEY: &str = "settings";
+const MB: u64 = 1024 * 1024;
+const ACTION_TOGGLE: &str = "window.settings";
+
+/// Part of the settings persisted between app runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Persisted {
+    candles_cap: usize,
+    cache: CacheConfig,
+    notify: NotifyConfig,
+    export_confirm: ExportConfirm,
+    export_times: TimeFormat,
+    export_manifest: bool,
+    /// Minutes from UTC of the timezone of the app.
+    utc_offset: i32,
+    appearance: Appearance,
+    download_concurrency: usize,
+    load_selected_symbol: bool,
+    influx: InfluxConfig,
+    webhook: WebhookConfig,
+    last_used: LastUsedConfig,
+    candles_band: Band,
+    journal_fee_bps: f64,
+    log: LogConfig,
+}
+
+/// Export sizes above which a summary is confirmed before the export starts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct ExportConfirm {
+    pages: usize,
+    rows: usize,
+}
+
+impl Default for ExportConfirm {
+    fn default() -> Self {
+        let limits = ConfirmLimits::default();
+        Self {
+            pages: limits.pages(),
+            rows: limits.rows(),
+        }
+    }
+}
+
+/// Candles counts of a request beyond which another interval is suggested.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Band {
+    min: usize,
+    max: usize,
+}
+
+impl Default for Band {
+    fn default() -> Self {
+        let band = CandlesBand::default();
+        Self {
+            min: band.min(),
+            max: band.max(),
+        }
+    }
+}
+
+impl Default for Persisted {
+    fn default() -> Self {
+        Self {
+            candles_cap: DEFAULT_CAP,
+            cache: Default::default(),
+            notify: Default::default(),
+            export_confirm: Default::default(),
+            export_times: Default::default(),
+            export_manifest: false,
+            utc_offset: 0,
+            appearance: Default::default(),
+            download_concurrency: DownloadConfig::default().concurrency(),
+            load_selected_symbol: DownloadConfig::default().load_selected(),
+            influx: Default::default(),
+            webhook: Default::default(),
+            last_used: Default::default(),
+            candles_band: Default::default(),
+            journal_fee_bps: DEFAULT_FEE_BPS,
+            log: Default::default(),
+        }
+    }
+}
+
+pub struct Settings {
+    visible: bool,
+    ws_enabled: bool,
+    ws_port: u16,
+    ws_server: Option<Server>,
+    broadcaster: Broadcaster,
+    budget: CandlesBudget,
+    cache: Cache,
+    notifier: Notifier,
+    confirm_limits: ConfirmLimits,
+    export_options: ExportOptions,
+    timezone: Timezone,
+    download_config: DownloadConfig,
+    influx: Influx,
+    webhook: Webhook,
+    last_used: LastUsed,
+    band: CandlesBand,
+    journal: JournalConfig,
+    logging: Logging,
+    /// Filter being edited, applied on enter.
+    log_filter: String,
+    actions: Actions,
+    appearance: Appearance,
+    /// Appearance being edited, applied on demand.
+    appearance_edit: Appearance,
+    /// Scale of the display used without an override, taken on the first frame.
+    native_scale: Option<f32>,
+    cache_dir: String,
+    cache_status: Option<CacheStatus>,
+    last_prune: Option<Instant>,
+    prune_promise: Option<Promise<Result<CacheStatus>>>,
+    toast_pub: Sender<Toast>,
+}
+
+impl Settings {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        visible: bool,
+        broadcaster: Broadcaster,
+        budget: CandlesBudget,
+        cache: Cache,
+        notifier: Notifier,
+        confirm_limits: ConfirmLimits,
+        export_options: ExportOptions,
+        timezone: Timezone,
+        download_config: DownloadConfig,
+        influx: Influx,
+        webhook: Webhook,
+        last_used: LastUsed,
+        band: CandlesBand,
+        journal: JournalConfig,
+        logging: Logging,
+        actions: Actions,
+        toast_pub: Sender<Toast>,
+        storage: Option<&dyn eframe::Storage>,
+    ) -> Self {
+        let persisted: Persisted = storage
+            .and_then(|s| eframe::get_value(s, SETTINGS_KEY))
+            .unwrap_or_default();
+        budget.set_cap(persisted.candles_cap);
+        cache.set_config(persisted.cache.clone());
+        notifier.set_config(persisted.notify);
+        confirm_limits.set(
+            persisted.export_confirm.pages,
+            persisted.export_confirm.rows,
+        );
+        export_options.set_times(persisted.export_times);
+        export_options.set_manifest(persisted.export_manifest);
+        timezone.set_utc_offset(persisted.utc_offset);
+        download_config.set_concurrency(persisted.download_concurrency);
+        download_config.set_load_selected(persisted.load_selected_symbol);
+        influx.set_config(persisted.influx.clone());
+        webhook.set_config(persisted.webhook.clone());
+        last_used.set_config(persisted.last_used.clone());
+        band.set(persisted.candles_band.min, persisted.candles_band.max);
+        journal.set_fee_bps(persisted.journal_fee_bps);
+        if let Err(err) = logging.set_config(&persisted.log) {
+            error!("failed to apply the saved log config: {}", err.chain());
+        }
+        actions.register(ACTION_TOGGLE, "toggle settings window", None);
+
+        Self {
+            visible,
+            ws_enabled: false,
+            ws_port: DEFAULT_PORT,
+            ws_server: None,
+            broadcaster,
+            budget,
+            cache,
+            notifier,
+            confirm_limits,
+            export_options,
+            timezone,
+            download_config,
+            influx,
+            webhook,
+            last_used,
+            band,
+            journal,
+            log_filter: logging.filter(),
+            logging,
+            actions,
+            appearance: persisted.appearance.clamped(),
+            appearance_edit: persisted.appearance.clamped(),
+            native_scale: None,
+            cache_dir: persisted.cache.dir.display().to_string(),
+            cache_status: None,
+            last_prune: None,
+            prune_promise: None,
+            toast_pub,
+        }
+    }
+
+    /// Draws state of the running services for the status bar.
+    pub fn status_ui(&self, ui: &mut Ui) {
+        ui.label(format!(
+            "candles: {}/{}",
+            self.budget.resident(),
+            self.budget.cap()
+        ));
+        if let Some(server) = &self.ws_server {
+            ui.label(format!(
+                "ws {}: {} connections",
+                server.addr(),
+                self.broadcaster.connections()
+            ));
+        }
+    }
+
+    fn notify_ui(&mut self, ui: &mut Ui) {
+        let mut config = self.notifier.config();
+        ui.checkbox(
+            &mut config.enabled,
+            "notify when downloads and exports finish",
+        );
+        ui.add_enabled_ui(config.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("when taking at least");
+                ui.add(
+                    DragValue::new(&mut config.threshold_secs)
+                        .clamp_range(0..=3600)
+                        .suffix(" s"),
+                );
+            });
+            ui.checkbox(&mut config.sound, "play sound");
+        });
+        if config != self.notifier.config() {
+            self.notifier.set_config(config);
+        }
+    }
+
+    fn appearance_ui(&mut self, ui: &mut Ui) {
+        let edit = &mut self.appearance_edit;
+        ui.horizontal(|ui| {
+            let mut scaled = edit.ui_scale.is_some();
+            if ui.checkbox(&mut scaled, "ui scale").changed() {
+                edit.ui_scale = scaled.then(|| ui.ctx().pixels_per_point());
+            }
+            if let Some(scale) = &mut edit.ui_scale {
+                ui.add(DragValue::new(scale).speed(0.05).clamp_range(SCALE_RANGE));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("plot font size");
+            ui.add(DragValue::new(&mut edit.plot_font_size).clamp_range(PLOT_FONT_RANGE));
+        });
+        ui.group(|ui| self.appearance_edit.preview_ui(ui));
+        ui.horizontal(|ui| {
+            let changed = self.appearance_edit != self.appearance;
+            if ui.add_enabled(changed, Button::new("apply")).clicked() {
+                self.appearance = self.appearance_edit.clamped();
+            }
+            if ui.add_enabled(changed, Button::new("revert")).clicked() {
+                self.appearance_edit = self.appearance;
+            }
+        });
+    }
+
+    fn download_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("pages requested at once");
+            let mut pages = self.download_config.concurrency();
+            if ui
+                .add(DragValue::new(&mut pages).clamp_range(CONCURRENCY_RANGE))
+                .changed()
+            {
+                self.download_config.set_concurrency(pages);
+            }
+        });
+        ui.label("more are faster until the exchange rate limit is hit");
+        let mut load = self.download_config.load_selected();
+        if ui
+            .checkbox(&mut load, "load selected symbols")
+            .on_hover_text("load the range shown for the previous symbol, otherwise press show")
+            .changed()
+        {
+            self.download_config.set_load_selected(load);
+        }
+        let mut remember = self.last_used.enabled();
+        if ui
+            .checkbox(&mut remember, "remember interval and range per symbol")
+            .on_hover_text("a selected symbol starts with the interval and range length it was last loaded with, ending now")
+            .changed()
+        {
+            self.last_used.set_enabled(remember);
+        }
+        let (mut min, mut max) = (self.band.min(), self.band.max());
+        ui.horizontal(|ui| {
+            ui.label("suggest another interval below");
+            ui.add(DragValue::new(&mut min).clamp_range(0..=max));
+            ui.label("or above");
+            ui.add(
+                DragValue::new(&mut max)
+                    .speed(1_000)
+                    .clamp_range(min..=usize::MAX),
+            );
+            ui.label("candles");
+        });
+        if (min, max) != (self.band.min(), self.band.max()) {
+            self.band.set(min, max);
+        }
+    }
+
+    fn journal_ui(&mut self, ui: &mut Ui) {
+        let mut fee_bps = self.journal.fee_bps();
+        ui.horizontal(|ui| {
+            ui.label("fee per fill");
+            ui.add(
+                DragValue::new(&mut fee_bps)
+                    .speed(0.1)
+                    .clamp_range(0.0..=1_000.0)
+                    .suffix(" bps"),
+            );
+        })
+        .response
+        .on_hover_text("charged on entry and exit of journal trades for their P&L");
+        if fee_bps != self.journal.fee_bps() {
+            self.journal.set_fee_bps(fee_bps);
+        }
+    }
+
+    fn timezone_ui(&mut self, ui: &mut Ui) {
+        let mut hours = self.timezone.utc_offset() as f64 / 60.0;
+        ui.add(
+            DragValue::new(&mut hours)
+                .speed(0.25)
+                .clamp_range(
+                    *UTC_OFFSET_RANGE.start() as f64 / 60.0..=*UTC_OFFSET_RANGE.end() as f64 / 60.0,
+                )
+                .max_decimals(2)
+                .prefix("UTC"),
+        );
+        let utc_offset = (hours * 4.0).round() as i32 * 15;
+        if utc_offset != self.timezone.utc_offset() {
+            self.timezone.set_utc_offset(utc_offset);
+        }
+        ui.label("of ISO times in exports and of the days and weeks of pivots and session levels");
+    }
+
+    fn export_ui(&mut self, ui: &mut Ui) {
+        let (mut pages, mut rows) = (self.confirm_limits.pages(), self.confirm_limits.rows());
+        ui.label("confirm exports taking more than");
+        Grid::new("export_confirm").num_columns(2).show(ui, |ui| {
+            ui.add(DragValue::new(&mut pages).clamp_range(0..=usize::MAX));
+            ui.label("requests");
+            ui.end_row();
+            ui.add(
+                DragValue::new(&mut rows)
+                    .speed(1_000)
+                    .clamp_range(0..=usize::MAX),
+            );
+            ui.label("rows");
+            ui.end_row();
+        });
+        if (pages, rows) != (self.confirm_limits.pages(), self.confirm_limits.rows()) {
+            self.confirm_limits.set(pages, rows);
+        }
+
+        ui.separator();
+        let mut times = self.export_options.times();
+        ui.horizontal(|ui| {
+            ui.label("times as");
+            ComboBox::from_id_source("export_times")
+                .selected_text(times.name())
+                .show_ui(ui, |ui| {
+                    TimeFormat::ALL.into_iter().for_each(|format| {
+                        let selected = format.name() == times.name();
+                        if ui.selectable_label(selected, format.name()).clicked() && !selected {
+                            times = format;
+                        }
+                    });
+                });
+        });
+        ui.label("in csv and xlsx files and their names, ISO dates in the timezone of the app");
+        if times != self.export_options.times() {
+            self.export_options.set_times(times);
+        }
+        let mut manifest = self.export_options.manifest();
+        if ui
+            .checkbox(&mut manifest, "write a .meta.json manifest next to files")
+            .on_hover_text(
+                "symbol, interval, bounds, rows, source, app version, time and SHA-256, \
+                 not for xlsx as workbooks can't be hashed as they are written",
+            )
+            .changed()
+        {
+            self.export_options.set_manifest(manifest);
+        }
+
+        ui.separator();
+        ui.label("InfluxDB v2");
+        let mut config = self.influx.config();
+        Grid::new("influx").num_columns(2).show(ui, |ui| {
+            ui.label("url");
+            ui.text_edit_singleline(&mut config.url);
+            ui.end_row();
+            ui.label("org");
+            ui.text_edit_singleline(&mut config.org);
+            ui.end_row();
+            ui.label("bucket");
+            ui.text_edit_singleline(&mut config.bucket);
+            ui.end_row();
+            ui.label("token");
+            ui.add(TextEdit::singleline(&mut config.token).password(true));
+            ui.end_row();
+            ui.label("points per request");
+            ui.add(
+                DragValue::new(&mut config.batch)
+                    .speed(100)
+                    .clamp_range(1..=100_000),
+            );
+            ui.end_row();
+        });
+        if config != self.influx.config() {
+            self.influx.set_config(config);
+        }
+    }
+
+    fn webhook_ui(&mut self, ui: &mut Ui) {
+        let mut config = self.webhook.config();
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut config.enabled, "enabled");
+            ui.label("url");
+            ui.text_edit_singleline(&mut config.url);
+        });
+        if config != self.webhook.config() {
+            self.webhook.set_config(config);
+        }
+        ui.label("posts json when a download finishes and when a candle closes with auto refresh");
+        ui.horizontal(|ui| {
+            if ui.button("send test event").clicked() {
+                self.webhook.send_test();
+            }
+            if let Some(last) = self.webhook.log().first() {
+                match &last.result {
+                    Ok(status) => ui.label(format!("last {}: {status}", last.kind)),
+                    Err(err) => ui.label(format!("last {} failed: {err}", last.kind)),
+                };
+            }
+        });
+    }
+
+    fn logging_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("filter");
+            let resp = ui.add(
+                TextEdit::singleline(&mut self.log_filter)
+                    .hint_text("info,netstrat::sources::binance=debug"),
+            );
+            if resp.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+                if let Err(err) = self.logging.set_filter(&self.log_filter) {
+                    self.toast_error(&err);
+                }
+                self.log_filter = self.logging.filter();
+            }
+        });
+        ui.label(format!("active: {}", self.logging.filter()));
+        if self.logging.from_env() {
+            ui.label("RUST_LOG is set, the filter isn't saved over it");
+        }
+
+        let file = self.logging.file();
+        let mut enabled = file.enabled();
+        if ui
+            .checkbox(&mut enabled, "also write to a log file rotated every 10 MB")
+            .changed()
+        {
+            file.set_enabled(enabled);
+        }
+        let path = file.path();
+        let dir = path.parent().unwrap_or(&path).display().to_string();
+        ui.hyperlink_to(path.display().to_string(), format!("file://{dir}"))
+            .on_hover_text("open the directory of the log files");
+    }
+
+    fn toast_error(&self, err: &Error) {
+        error!("{}", err.chain());
+        if let Err(err) = self
+            .toast_pub
+            .send(Toast::error(err.user_message()))
+            .context("toast")
+        {
+            error!("{}", Error::from(err).chain());
+        }
+    }
+
+    fn toggle_ws_server(&mut self) {
+        if !self.ws_enabled {
+            self.ws_server = None;
+            return;
+        }
+
+        match Server::start(self.ws_port, self.broadcaster.clone()) {
+            Ok(server) => self.ws_server = Some(server),
+            Err(err) => {
+                self.ws_enabled = false;
+                self.toast_error(&err);
+            }
+        }
+    }
+
+    /// Prunes the cache in background and refreshes its status.
+    fn prune(&mut self) {
+        if self.prune_promise.is_some() {
+            return;
+        }
+
+        self.last_prune = Some(Instant::now());
+        let cache = self.cache.clone();
+        self.prune_promise = Some(Promise::spawn_thread("cache prune", move || {
+            cache.prune()?;
+            cache.status()
+        }));
+    }
+
+    fn poll_prune(&mut self) {
+        if let Some(promise) = &self.prune_promise {
+            if let Some(res) = promise.ready() {
+                match res {
+                    Ok(status) => self.cache_status = Some(status.clone()),
+                    Err(err) => self.toast_error(err),
+                }
+                self.prune_promise = None;
+            }
+        }
+
+        if self
+            .last_prune
+            .is_none_or(|at| at.elapsed() >= PRUNE_PERIOD)
+        {
+            self.prune();
+        }
+    }
+
+    fn clear_cache(&mut self, symbol: Option<&str>) {
+        match self.cache.clear(symbol) {
+            Ok(_) => self.prune(),
+            Err(err) => self.toast_error(&err),
+        }
+    }
+
+    fn cache_ui(&mut self, ui: &mut Ui) {
+        let mut config = self.cache.config();
+
+        ui.horizontal(|ui| {
+            ui.label("directory");
+            let resp = ui.add(TextEdit::singleline(&mut self.cache_dir));
+            if resp.lost_focus() && !self.cache_dir.trim().is_empty() {
+                config.dir = PathBuf::from(self.cache_dir.trim());
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("max size, MB");
+            let mut max_mb = config.max_bytes / MB;
+            if ui
+                .add(DragValue::new(&mut max_mb).clamp_range(1..=u64::MAX / MB))
+                .changed()
+            {
+                config.max_bytes = max_mb * MB;
+            }
+        });
+
+        if config != self.cache.config() {
+            self.cache.set_config(config);
+            self.cache_status = None;
+            self.prune();
+        }
+
+        ui.separator();
+        let status = match &self.cache_status {
+            Some(status) => status.clone(),
+            None => {
+                ui.spinner();
+                return;
+            }
+        };
+
+        ui.label(format!(
+            "{:.1} MB in {} pages",
+            status.bytes as f64 / MB as f64,
+            status.entries.values().sum::<usize>()
+        ));
+        let mut clear = None;
+        Grid::new("cache entries").striped(true).show(ui, |ui| {
+            status.entries.iter().for_each(|(symbol, count)| {
+                ui.label(symbol);
+                ui.label(count.to_string());
+                if ui.small_button("clear").clicked() {
+                    clear = Some(symbol.clone());
+                }
+                ui.end_row();
+            });
+        });
+        if let Some(symbol) = clear {
+            self.clear_cache(Some(&symbol));
+        }
+        if !status.entries.is_empty() && ui.button("clear all").clicked() {
+            self.clear_cache(None);
+        }
+    }
+}
+
+impl AppWindow for Settings {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("settings").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn tick(&mut self, ctx: &Context) {
+        if self.actions.take(ACTION_TOGGLE) {
+            self.toggle();
+        }
+        self.poll_prune();
+        let native_scale = *self
+            .native_scale
+            .get_or_insert_with(|| ctx.pixels_per_point());
+        self.appearance.apply(ctx, native_scale);
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+
+        let mut visible = self.visible;
+        Window::new("settings")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .resizable(false)
+            .show(ui.ctx(), |ui| {
+                ui.collapsing("Websocket server", |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.checkbox(&mut self.ws_enabled, "enabled").changed() {
+                            self.toggle_ws_server();
+                        }
+                        ui.label("port");
+                        ui.add_enabled(
+                            self.ws_server.is_none(),
+                            DragValue::new(&mut self.ws_port).clamp_range(1..=u16::MAX),
+                        );
+                    });
+                    ui.label("broadcasts selected symbol, loaded ranges and live candles as json");
+                });
+                ui.collapsing("Memory", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("max candles in memory");
+                        let mut cap = self.budget.cap();
+                        if ui
+                            .add(
+                                DragValue::new(&mut cap)
+                                    .speed(1_000)
+                                    .clamp_range(MIN_CANDLES_CAP..=usize::MAX),
+                            )
+                            .changed()
+                        {
+                            self.budget.set_cap(cap);
+                        }
+                    });
+                    ui.label("candles out of view are dropped when exceeded");
+                });
+                ui.collapsing("Download", |ui| self.download_ui(ui));
+                ui.collapsing("Cache", |ui| self.cache_ui(ui));
+                ui.collapsing("Notifications", |ui| self.notify_ui(ui));
+                ui.collapsing("Timezone", |ui| self.timezone_ui(ui));
+                ui.collapsing("Export", |ui| self.export_ui(ui));
+                ui.collapsing("Webhook", |ui| self.webhook_ui(ui));
+                ui.collapsing("Journal", |ui| self.journal_ui(ui));
+                ui.collapsing("Appearance", |ui| self.appearance_ui(ui));
+                ui.collapsing("Logging", |ui| self.logging_ui(ui));
+            });
+        self.visible = visible;
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(
+            storage,
+            SETTINGS_KEY,
+            &Persisted {
+                candles_cap: self.budget.cap(),
+                cache: self.cache.config(),
+                notify: self.notifier.config(),
+                export_confirm: ExportConfirm {
+                    pages: self.confirm_limits.pages(),
+                    rows: self.confirm_limits.rows(),
+                },
+                export_times: self.export_options.times(),
+                export_manifest: self.export_options.manifest(),
+                utc_offset: self.timezone.utc_offset(),
+                appearance: self.appearance,
+                download_concurrency: self.download_config.concurrency(),
+                load_selected_symbol: self.download_config.load_selected(),
+                influx: self.influx.config(),
+                webhook: self.webhook.config(),
+                last_used: self.last_used.config(),
+                candles_band: Band {
+                    min: self.band.min(),
+                    max: self.band.max(),
+                },
+                journal_fee_bps: self.journal.fee_bps(),
+                log: self.logging.config(),
+            },
+        );
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("settings", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("settings").unwrap_or(self.visible);
+    }
+}
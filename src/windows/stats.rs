@@ -0,0 +1,156 @@
+use egui::{Grid, Ui, Window};
+
+use crate::{
+    netstrat::{
+        bounds::Bounds,
+        data::Data,
+        session::Session,
+        stats::{self, Stats},
+    },
+    sources::binance::Kline,
+};
+
+use super::AppWindow;
+
+/// Summary of the loaded candles or of the range selected on the chart.
+pub struct Statistics {
+    visible: bool,
+    klines: Vec<Kline>,
+    selection: Option<Bounds>,
+    /// Stats of the candles shown, `None` while they are outdated.
+    stats: Option<Option<Stats>>,
+}
+
+impl Statistics {
+    pub fn new(visible: bool) -> Self {
+        Self {
+            visible,
+            klines: Default::default(),
+            selection: None,
+            stats: None,
+        }
+    }
+
+    pub fn set_data(&mut self, klines: &[Kline]) {
+        self.klines = klines.to_vec();
+        self.stats = None;
+    }
+
+    /// Restricts the stats to the candles within the selection.
+    pub fn set_selection(&mut self, selection: Option<Bounds>) {
+        if selection != self.selection {
+            self.selection = selection;
+            self.stats = None;
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.visible = true;
+    }
+
+    fn shown(&self) -> &[Kline] {
+        match self.selection {
+            Some(bounds) => stats::within(&self.klines, bounds),
+            None => &self.klines,
+        }
+    }
+
+    fn stats_ui(ui: &mut Ui, s: &Stats) {
+        let rows = [
+            ("candles", s.candles.to_string()),
+            ("from", Data::format_ts(s.bounds.0 as f64)),
+            ("to", Data::format_ts(s.bounds.1 as f64)),
+            ("open", format!("{:.8}", s.open)),
+            ("close", format!("{:.8}", s.close)),
+            ("change", format!("{:+.2}%", s.change)),
+            ("high", format!("{:.8}", s.high)),
+            ("low", format!("{:.8}", s.low)),
+            ("range", format!("{:.2}%", s.range)),
+            ("up candles", format!("{} of {}", s.up, s.candles)),
+            ("volume", format!("{:.2}", s.volume)),
+            ("mean volume", format!("{:.2}", s.mean_volume())),
+            (
+                "volatility",
+                s.volatility
+                    .map_or_else(|| "-".to_string(), |v| format!("{v:.3}% per candle")),
+            ),
+        ];
+        Grid::new("statistics").striped(true).show(ui, |ui| {
+            rows.into_iter().for_each(|(name, value)| {
+                ui.label(name);
+                ui.label(value);
+                ui.end_row();
+            });
+        });
+    }
+}
+
+impl AppWindow for Statistics {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Statistics").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+        let stats = match self.stats {
+            Some(stats) => stats,
+            None => *self.stats.insert(Stats::of(self.shown())),
+        };
+
+        let mut visible = self.visible;
+        Window::new("statistics")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .resizable(false)
+            .show(ui.ctx(), |ui| {
+                ui.label(match self.selection {
+                    Some(_) => "selected range, shift-drag on the chart to change it",
+                    None => "loaded candles, shift-drag on the chart to select a range",
+                });
+                ui.separator();
+                match stats {
+                    Some(s) => Statistics::stats_ui(ui, &s),
+                    None => {
+                        ui.label("no candles");
+                    }
+                }
+            });
+        self.visible = visible;
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("statistics", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("statistics").unwrap_or(self.visible);
+    }
+}
+
+#[cfg(test)]
+mod stats_window_tests {
+    use crate::sources::binance::mock;
+
+    use super::*;
+
+    #[test]
+    fn test_stats_of_selection() {
+        let mut window = Statistics::new(false);
+        window.set_data(&mock::klines(0, 60_000, 10));
+        assert_eq!(window.shown().len(), 10);
+
+        window.stats = Some(None);
+        window.set_selection(Some(Bounds(120_000, 300_000 - 1)));
+        assert_eq!(window.stats, None);
+        assert_eq!(window.shown().len(), 3);
+        assert_eq!(window.shown()[0].t_open, 120_000);
+    }
+}
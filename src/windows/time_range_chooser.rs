@@ -1,13 +1,21 @@
+use std::time::{Duration, Instant};
+
 use chrono::prelude::*;
-use chrono::{Date, NaiveTime, Utc};
-use crossbeam::channel::{Receiver, Sender};
-use egui::{Ui, Window};
-use tracing::{error, info, warn};
+use chrono::{NaiveDate, NaiveTime};
+use crossbeam::channel::Receiver;
+use egui::{Button, Color32, ComboBox, Context, Ui, Window};
+use egui_extras::DatePickerButton;
+use tracing::{error, info};
 
 use crate::{
     netstrat::{
-        bounds::{Bounds, BoundsSet},
-        props::Props,
+        actions::Actions,
+        aggregate::EmptyBuckets,
+        channels::{LatestSender, TimeoutSender},
+        debounce::Debounce,
+        export::{self, ChosenFormat, Columns, Format, IndicatorColumns},
+        props::{CandlesBand, Props},
+        session::Session,
     },
     sources::binance::Interval,
     widgets::TimeInput,
@@ -15,41 +23,79 @@ use crate::{
 
 use super::AppWindow;
 
+/// Props are published once they stop changing for the delay, so repeated clicks fetch once.
+const PUBLISH_DELAY: Duration = Duration::from_millis(300);
+const ACTION_SHOW: &str = "range.show";
+const ACTION_EXPORT: &str = "range.export";
+
 pub struct TimeRangeChooser {
     symbol: String,
     time_start_input: TimeInput,
     time_end_input: TimeInput,
-    valid: bool,
     visible: bool,
-    date_start: Date<Utc>,
-    date_end: Date<Utc>,
+    date_start: NaiveDate,
+    date_end: NaiveDate,
     interval: Interval,
+    empty_buckets: EmptyBuckets,
     symbol_sub: Receiver<String>,
     props_sub: Receiver<Props>,
-    props_pub: Sender<Props>,
-    export_pub: Sender<Props>,
+    props_pub: LatestSender<Props>,
+    /// Props to show keyed by the symbol, so the same props of another symbol are shown.
+    props_debounce: Debounce<(String, Props)>,
+    /// Props of downloads which failed, showing them again retries.
+    failed_sub: Receiver<Props>,
+    export_pub: TimeoutSender<(Props, Format)>,
+    export_format: Format,
+    /// The export format shared with the graph for exports of the checked symbols.
+    chosen_format: ChosenFormat,
+    /// Indicators of the chart whose values are exported as csv columns.
+    export_indicators: IndicatorColumns,
+    band: CandlesBand,
+    actions: Actions,
+    /// Whether the requested bounds are widened to boundaries of the interval.
+    snap: bool,
+    /// Whether a start time after the end time stands for sessions crossing midnight.
+    overnight: bool,
 }
 
 impl TimeRangeChooser {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         visible: bool,
         symbol_sub: Receiver<String>,
-        props_pub: Sender<Props>,
+        props_pub: LatestSender<Props>,
         props_sub: Receiver<Props>,
-        export_pub: Sender<Props>,
+        failed_sub: Receiver<Props>,
+        export_pub: TimeoutSender<(Props, Format)>,
+        export_indicators: IndicatorColumns,
+        chosen_format: ChosenFormat,
+        band: CandlesBand,
+        actions: Actions,
         props: Props,
     ) -> Self {
+        actions.register(ACTION_SHOW, "load range", None);
+        actions.register(ACTION_EXPORT, "export range", None);
+
         Self {
             symbol: String::new(),
             symbol_sub,
-            valid: true,
             visible,
             props_pub,
+            props_debounce: Debounce::new(PUBLISH_DELAY),
             props_sub,
+            failed_sub,
             export_pub,
+            export_format: chosen_format.get(),
+            chosen_format,
+            export_indicators,
+            band,
+            actions,
+            snap: true,
+            overnight: false,
             date_start: props.date_start,
             date_end: props.date_end,
             interval: props.interval,
+            empty_buckets: props.empty_buckets,
             time_start_input: TimeInput::new(
                 props.time_start.hour(),
                 props.time_start.minute(),
@@ -68,44 +114,148 @@ impl TimeRangeChooser {
     fn parse_props(
         time_start_opt: Option<NaiveTime>,
         time_end_opt: Option<NaiveTime>,
-        date_start: Date<Utc>,
-        date_end: Date<Utc>,
+        date_start: NaiveDate,
+        date_end: NaiveDate,
         interval: Interval,
+        empty_buckets: EmptyBuckets,
     ) -> Option<Props> {
-        let time_start: NaiveTime;
-        match time_start_opt {
-            Some(time) => {
-                time_start = time;
-            }
-            None => {
-                return None;
-            }
+        let time_start = time_start_opt?;
+        let time_end = time_end_opt?;
+
+        Some(Props::new(
+            date_start.and_time(time_start),
+            date_end.and_time(time_end),
+            interval,
+            empty_buckets,
+        ))
+    }
+
+    fn show_range(&mut self, props: &Props) {
+        self.props_debounce
+            .push((self.symbol.clone(), props.clone()), Instant::now());
+    }
+
+    fn export(&self, props: &Props) {
+        match self.export_pub.send((props.clone(), self.export_format)) {
+            Ok(_) => info!("sent props for export: {props:?}"),
+            Err(err) => error!("failed to send props for export: {err}"),
         }
+    }
 
-        let time_end: NaiveTime;
-        match time_end_opt {
-            Some(time) => {
-                time_end = time;
-            }
-            None => {
-                return None;
-            }
+    /// Runs the actions of the palette, which are enabled while the inputs are valid.
+    fn handle_actions(&mut self) {
+        let props = self.props();
+        [ACTION_SHOW, ACTION_EXPORT]
+            .into_iter()
+            .for_each(|id| self.actions.set_enabled(id, props.is_ok()));
+        let Ok(props) = props else {
+            return;
+        };
+        if self.actions.take(ACTION_SHOW) {
+            self.show_range(&props);
         }
-        let mut p = Props {
-            date_start,
-            date_end,
-            time_start,
-            time_end,
+        if self.actions.take(ACTION_EXPORT) {
+            self.export(&props);
+        }
+    }
+
+    /// Whether the inputs are read as sessions crossing midnight.
+    fn is_overnight(&self) -> bool {
+        match (
+            self.time_start_input.get_time(),
+            self.time_end_input.get_time(),
+        ) {
+            (Some(start), Some(end)) => self.overnight && start > end,
+            _ => false,
+        }
+    }
+
+    /// Props of the inputs or why they are invalid, checked on every frame.
+    fn props(&self) -> Result<Props, &'static str> {
+        let props = TimeRangeChooser::parse_props(
+            self.time_start_input.get_time(),
+            self.time_end_input.get_time(),
+            self.date_start,
+            self.date_end,
+            self.interval,
+            self.empty_buckets,
+        )
+        .ok_or("invalid time, use hh:mm:ss")?;
+
+        Props::checked(
+            props.date_start.and_time(props.time_start),
+            props.date_end.and_time(props.time_end),
+            props.interval,
+            props.empty_buckets,
+            self.overnight,
+            self.snap,
+        )
+    }
+
+    /// Interval to use instead when the props have too few or too many candles, with why.
+    fn suggestion(&self, props: &Props) -> Option<(String, Interval)> {
+        let interval = props.suggest_interval(&self.band)?;
+        Some((
+            format!(
+                "this range at {} is ~{} candles, consider {}",
+                props.interval.as_str(),
+                format_count(props.candles()),
+                interval.as_str()
+            ),
             interval,
-            bounds: BoundsSet::new(vec![]),
-            limit: 1000,
-        };
-        p.bounds = BoundsSet::new(vec![Bounds(
-            p.start_time().timestamp_millis(),
-            p.end_time().timestamp_millis(),
-        )]);
+        ))
+    }
+
+    /// Range requested in place of the one typed when snapping moved it.
+    fn effective_range(props: &Props) -> Option<String> {
+        let hull = props.bounds.hull()?;
+        let (start, end) = (props.start_time(), props.end_time());
+        if (hull.0, hull.1) == (start.timestamp_millis(), end.timestamp_millis()) {
+            return None;
+        }
+
+        let [from, to] = [hull.0, hull.1].map(DateTime::from_timestamp_millis);
+        let (from, to) = (from?, to?);
+        let format =
+            match (from.date_naive(), to.date_naive()) == (props.date_start, props.date_end) {
+                true => "%H:%M:%S",
+                false => "%Y-%m-%d %H:%M:%S",
+            };
+        Some(format!(
+            "effective range: {} – {}",
+            from.format(format),
+            to.format(format)
+        ))
+    }
+
+    /// Applies the latest symbol and props sent to the window, shown or not, so it is
+    /// current once opened.
+    fn receive(&mut self) {
+        if let Some(symbol) = self.symbol_sub.try_iter().last() {
+            info!("received symbol: {symbol}");
+            self.symbol = symbol;
+        }
+
+        if let Some(props) = self.props_sub.try_iter().last() {
+            info!("received props: {props:?}");
+            self.unpack_props(&props);
+            self.props_debounce.seen((self.symbol.clone(), props));
+        }
 
-        Some(p)
+        if let Some(props) = self.failed_sub.try_iter().last() {
+            info!("download of props failed, showing them retries: {props:?}");
+            self.props_debounce.forget();
+        }
+    }
+
+    fn publish_props(&mut self, ctx: &Context) {
+        if let Some((_, props)) = self.props_debounce.poll(Instant::now()) {
+            self.props_pub.send(props.clone());
+            info!("sent props for show: {props:?}");
+        }
+        if self.props_debounce.is_pending() {
+            ctx.request_repaint();
+        }
     }
 
     fn unpack_props(&mut self, p: &Props) {
@@ -113,6 +263,8 @@ impl TimeRangeChooser {
 
         self.date_start = p.date_start;
         self.date_end = p.date_end;
+        self.interval = p.interval;
+        self.empty_buckets = p.empty_buckets;
 
         let time_start = p.time_start;
         self.time_start_input =
@@ -123,58 +275,61 @@ impl TimeRangeChooser {
 
         info!("props unpacked and applied");
     }
+
+    /// egui_extras date picker still operates on deprecated `Date<Utc>`,
+    /// so the conversion is kept here at the widget boundary.
+    #[allow(deprecated)]
+    pub fn date_picker(ui: &mut Ui, date: &mut NaiveDate, id_source: &str) {
+        let mut picker_date = Date::<Utc>::from_utc(*date, Utc);
+        ui.add(DatePickerButton::new(&mut picker_date).id_source(id_source));
+        *date = picker_date.naive_utc();
+    }
+}
+
+/// Count rounded to thousands or millions, e.g. `1.05M`.
+fn format_count(n: usize) -> String {
+    match n {
+        0..=9_999 => n.to_string(),
+        10_000..=999_999 => format!("{}k", n / 1000),
+        _ => format!("{:.2}M", n as f64 / 1_000_000.0),
+    }
 }
 
 impl AppWindow for TimeRangeChooser {
     fn toggle_btn(&mut self, ui: &mut Ui) {
         if ui.button("Props").clicked() {
-            self.visible = !self.visible
+            self.toggle()
         }
     }
 
-    fn show(&mut self, ui: &mut Ui) {
-        let symbol_wrapped = self
-            .symbol_sub
-            .recv_timeout(std::time::Duration::from_millis(1));
-
-        match symbol_wrapped {
-            Ok(symbol) => {
-                info!("received symbol: {symbol}");
-                self.symbol = symbol;
-            }
-            Err(_) => {}
-        }
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
 
-        let props_wrapped = self
-            .props_sub
-            .recv_timeout(std::time::Duration::from_millis(1));
+    fn tick(&mut self, ctx: &Context) {
+        self.receive();
+        self.handle_actions();
+        self.publish_props(ctx);
+    }
 
-        match props_wrapped {
-            Ok(props) => {
-                info!("received props: {props:?}");
-                self.unpack_props(&props);
-            }
-            Err(_) => {}
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
         }
 
+        let mut visible = self.visible;
         Window::new(self.symbol.to_string())
-            .open(&mut self.visible)
+            .open(&mut visible)
             .drag_bounds(ui.max_rect())
             .resizable(false)
             .show(ui.ctx(), |ui| {
                 ui.collapsing("Time Period", |ui| {
                     ui.horizontal_wrapped(|ui| {
-                        ui.add(
-                            egui_extras::DatePickerButton::new(&mut self.date_start)
-                                .id_source("datepicker_start"),
-                        );
+                        TimeRangeChooser::date_picker(ui, &mut self.date_start, "datepicker_start");
                         ui.label("Date Start");
                     });
                     ui.horizontal_wrapped(|ui| {
-                        ui.add(
-                            egui_extras::DatePickerButton::new(&mut self.date_end)
-                                .id_source("datepicker_end"),
-                        );
+                        TimeRangeChooser::date_picker(ui, &mut self.date_end, "datepicker_end");
                         ui.label("Date End");
                     });
                     ui.horizontal_wrapped(|ui| {
@@ -185,88 +340,432 @@ impl AppWindow for TimeRangeChooser {
                         ui.add(&mut self.time_end_input);
                         ui.label("Time End");
                     });
+                    ui.checkbox(&mut self.overnight, "overnight sessions")
+                        .on_hover_text("a start time after the end time loads a session ending on the next day for every day");
+                    if self.overnight {
+                        ui.label(match self.is_overnight() {
+                            true => "loading every night from start time to end time of the next day",
+                            false => "start time is before end time, loading a single range",
+                        });
+                    }
+                    ui.checkbox(&mut self.snap, "snap to interval")
+                        .on_hover_text(
+                            "widen the range to whole candles, as the exchange returns them",
+                        );
+                    if let Some(range) = self.props().ok().as_ref().and_then(Self::effective_range)
+                    {
+                        ui.label(range);
+                    }
                 });
                 ui.collapsing("Interval", |ui| {
                     egui::ComboBox::from_label("pick data interval")
                         .selected_text(format!("{:?}", self.interval))
                         .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.interval, Interval::Week, "Week");
                             ui.selectable_value(&mut self.interval, Interval::Day, "Day");
+                            ui.selectable_value(&mut self.interval, Interval::FourHours, "4 Hours");
                             ui.selectable_value(&mut self.interval, Interval::Hour, "Hour");
+                            ui.selectable_value(
+                                &mut self.interval,
+                                Interval::FifteenMinutes,
+                                "15 Minutes",
+                            );
+                            ui.selectable_value(
+                                &mut self.interval,
+                                Interval::FiveMinutes,
+                                "5 Minutes",
+                            );
                             ui.selectable_value(&mut self.interval, Interval::Minute, "Minute");
+                            ui.selectable_value(
+                                &mut self.interval,
+                                Interval::FiveSeconds,
+                                "5 Seconds",
+                            );
+                            ui.selectable_value(&mut self.interval, Interval::Second, "Second");
                         });
+                    if self.interval.is_aggregated() {
+                        ui.label("built from trades, prefer short periods");
+                        let mut fill = self.empty_buckets == EmptyBuckets::CarryForward;
+                        if ui
+                            .checkbox(&mut fill, "fill seconds without trades")
+                            .changed()
+                        {
+                            self.empty_buckets = match fill {
+                                true => EmptyBuckets::CarryForward,
+                                false => EmptyBuckets::Skip,
+                            };
+                        }
+                    }
                 });
 
                 ui.add_space(5f32);
 
+                let props = self.props();
+                let reason = props.as_ref().err().copied().unwrap_or_default();
                 ui.horizontal(|ui| {
-                    if ui.button("show").clicked() {
-                        let props = TimeRangeChooser::parse_props(
-                            self.time_start_input.get_time(),
-                            self.time_end_input.get_time(),
-                            self.date_start,
-                            self.date_end,
-                            self.interval,
-                        );
-                        match props {
-                            Some(props) => {
-                                if props.is_valid() {
-                                    let send_result = self.props_pub.send(props.clone());
-                                    match send_result {
-                                        Ok(_) => {
-                                            info!("sent props for show: {props:?}");
-                                        }
-                                        Err(err) => {
-                                            error!("failed to send props for show: {err}");
-                                        }
-                                    }
-                                } else {
-                                    warn!("invalid props");
-                                    self.valid = false;
-                                }
-                            }
-                            None => {
-                                error!("failed to parse props");
-                                self.valid = false;
-                            }
-                        }
+                    let show = ui
+                        .add_enabled(props.is_ok(), Button::new("show"))
+                        .on_disabled_hover_text(reason);
+                    if let (true, Ok(props)) = (show.clicked(), &props) {
+                        self.show_range(props);
                     }
 
-                    if ui.button("export").clicked() {
-                        let props = TimeRangeChooser::parse_props(
-                            self.time_start_input.get_time(),
-                            self.time_end_input.get_time(),
-                            self.date_start,
-                            self.date_end,
-                            self.interval,
-                        );
-                        match props {
-                            Some(props) => {
-                                if props.is_valid() {
-                                    let send_result = self.export_pub.send(props.clone());
-                                    match send_result {
-                                        Ok(_) => {
-                                            info!("sent props for export: {props:?}");
-                                        }
-                                        Err(err) => {
-                                            error!("failed to send props for export: {err}");
-                                        }
-                                    }
-                                } else {
-                                    warn!("invalid props");
-                                    self.valid = false;
-                                }
-                            }
-                            None => {
-                                error!("failed to parse props");
-                                self.valid = false;
-                            }
-                        }
+                    let export = ui
+                        .add_enabled(props.is_ok(), Button::new("export"))
+                        .on_disabled_hover_text(reason);
+                    if let (true, Ok(props)) = (export.clicked(), &props) {
+                        self.export(props);
+                    }
+
+                    let csv = match self.export_format {
+                        Format::Csv(columns) => Format::Csv(columns),
+                        _ => Format::default(),
+                    };
+                    let xlsx = match self.export_format {
+                        Format::Xlsx { columns, stats } => Format::Xlsx { columns, stats },
+                        _ => Format::Xlsx {
+                            columns: Columns::default(),
+                            stats: false,
+                        },
                     };
+                    ComboBox::from_id_source("export format")
+                        .selected_text(self.export_format.name())
+                        .show_ui(ui, |ui| {
+                            [csv, xlsx, Format::LineProtocol, Format::Influx]
+                                .into_iter()
+                                .for_each(|f| {
+                                    ui.selectable_value(&mut self.export_format, f, f.name());
+                                });
+                        })
+                        .response
+                        .on_hover_text("InfluxDB is set up in settings");
+                    if let Format::Csv(columns) | Format::Xlsx { columns, .. } =
+                        &mut self.export_format
+                    {
+                        let mut all_columns = *columns == Columns::All;
+                        if ui
+                            .checkbox(&mut all_columns, "volumes and trades")
+                            .on_hover_text(
+                                "export quote volume, number of trades and taker buy volumes",
+                            )
+                            .changed()
+                        {
+                            *columns = match all_columns {
+                                true => Columns::All,
+                                false => Columns::Basic,
+                            };
+                        }
+                    }
                 });
+                if let Format::Xlsx { stats, .. } = &mut self.export_format {
+                    ui.checkbox(stats, "stats sheet")
+                        .on_hover_text("add a sheet with the stats of the exported candles");
+                }
+                self.chosen_format.set(self.export_format);
+                let listed = self.export_indicators.listed();
+                let tabular = matches!(self.export_format, Format::Csv(_) | Format::Xlsx { .. });
+                if tabular && !listed.is_empty() {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("indicator columns:");
+                        listed.into_iter().for_each(|(id, name, mut checked)| {
+                            if ui.checkbox(&mut checked, name).changed() {
+                                self.export_indicators.set_checked(&id, checked);
+                            }
+                        });
+                    });
+                }
 
-                if !self.valid {
-                    ui.label("invalid time format or start > end");
+                match (&props, self.export_format) {
+                    (Ok(props), Format::Xlsx { .. })
+                        if props.candles() * 10 > export::XLSX_SHEET_ROWS * 9 =>
+                    {
+                        ui.colored_label(
+                            Color32::GOLD,
+                            format!(
+                                "xlsx sheets hold {} candles, ~{} are split over {} sheets",
+                                export::XLSX_SHEET_ROWS,
+                                props.candles(),
+                                export::xlsx_sheets(props.candles())
+                            ),
+                        );
+                    }
+                    _ => {}
+                }
+                match &props {
+                    Ok(props) if props.bounds.len() > 1 => ui.label(format!(
+                        "~{} candles in {} sessions",
+                        props.candles(),
+                        props.bounds.len()
+                    )),
+                    Ok(props) => ui.label(format!("~{} candles", props.candles())),
+                    Err(reason) => ui.colored_label(Color32::LIGHT_RED, *reason),
+                };
+                if let Some((text, interval)) = props.as_ref().ok().and_then(|p| self.suggestion(p)) {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.colored_label(Color32::GOLD, text);
+                        if ui.button(format!("use {}", interval.as_str())).clicked() {
+                            self.interval = interval;
+                        }
+                    });
                 }
             });
+        self.visible = visible;
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("time range", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("time range").unwrap_or(self.visible);
+    }
+}
+
+#[cfg(test)]
+mod time_range_chooser_tests {
+    use crate::netstrat::{
+        bounds::{Bounds, BoundsSet},
+        channels,
+    };
+
+    use super::*;
+
+    /// Chooser of the default props with the senders of the symbol, props and failed props
+    /// it receives.
+    fn chooser(
+        visible: bool,
+    ) -> (
+        TimeRangeChooser,
+        LatestSender<String>,
+        LatestSender<Props>,
+        LatestSender<Props>,
+    ) {
+        let (s_symbol, r_symbol) = channels::latest();
+        let (s_props, r_props) = channels::latest();
+        let (s_failed, r_failed) = channels::latest();
+        let chooser = TimeRangeChooser::new(
+            visible,
+            r_symbol,
+            channels::latest().0,
+            r_props,
+            r_failed,
+            channels::with_timeout(1, Duration::ZERO).0,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Props::default(),
+        );
+
+        (chooser, s_symbol, s_props, s_failed)
+    }
+
+    #[test]
+    fn test_parse_props() {
+        let date_start = NaiveDate::from_ymd_opt(2021, 12, 31).unwrap();
+        let date_end = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+
+        let props = TimeRangeChooser::parse_props(
+            NaiveTime::from_hms_opt(23, 59, 59),
+            NaiveTime::from_hms_opt(0, 0, 0),
+            date_start,
+            date_end,
+            Interval::Minute,
+            EmptyBuckets::Skip,
+        )
+        .unwrap();
+        assert_eq!(
+            props.bounds,
+            BoundsSet::new(vec![Bounds(1640995199000, 1640995200000)])
+        );
+        assert!(props.is_valid());
+
+        assert_eq!(
+            TimeRangeChooser::parse_props(
+                None,
+                NaiveTime::from_hms_opt(0, 0, 0),
+                date_start,
+                date_end,
+                Interval::Minute,
+                EmptyBuckets::Skip,
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_show_after_symbol_change_or_failure() {
+        let (mut chooser, s_symbol, s_props, s_failed) = chooser(true);
+        let props = chooser.props().unwrap();
+        let show = |chooser: &mut TimeRangeChooser| {
+            chooser.receive();
+            chooser.show_range(&props);
+            chooser
+                .props_debounce
+                .poll(Instant::now() + PUBLISH_DELAY)
+                .map(|(symbol, _)| symbol)
+        };
+
+        s_symbol.send("ETHUSDT".to_string());
+        s_props.send(props.clone());
+        assert_eq!(show(&mut chooser), None);
+
+        s_symbol.send("BTCUSDT".to_string());
+        assert_eq!(show(&mut chooser), Some("BTCUSDT".to_string()));
+        assert_eq!(show(&mut chooser), None);
+
+        s_failed.send(props.clone());
+        assert_eq!(show(&mut chooser), Some("BTCUSDT".to_string()));
+    }
+
+    #[test]
+    fn test_receives_while_hidden() {
+        let (mut chooser, s_symbol, s_props, ..) = chooser(false);
+
+        let date = |d| NaiveDate::from_ymd_opt(2022, 1, d).unwrap();
+        let props = |d| {
+            Props::new(
+                date(d).and_hms_opt(0, 0, 0).unwrap(),
+                date(d + 1).and_hms_opt(12, 30, 0).unwrap(),
+                Interval::Hour,
+                EmptyBuckets::Skip,
+            )
+        };
+        s_symbol.send("ETHUSDT".to_string());
+        s_symbol.send("BTCUSDT".to_string());
+        s_props.send(props(1));
+        s_props.send(props(10));
+
+        chooser.receive();
+
+        assert!(!chooser.visible);
+        assert_eq!(chooser.symbol, "BTCUSDT");
+        assert_eq!((chooser.date_start, chooser.date_end), (date(10), date(11)));
+        assert_eq!(chooser.interval, Interval::Hour);
+        assert_eq!(
+            chooser.time_end_input.get_time(),
+            NaiveTime::from_hms_opt(12, 30, 0)
+        );
+
+        chooser.receive();
+        assert_eq!(chooser.symbol, "BTCUSDT");
+        assert_eq!(chooser.date_start, date(10));
+    }
+
+    #[test]
+    fn test_validity_follows_inputs() {
+        let (mut chooser, ..) = chooser(true);
+        assert!(chooser.props().is_ok());
+
+        chooser.date_start = chooser.date_end + chrono::Duration::days(1);
+        assert_eq!(chooser.props(), Err("start must be before end"));
+
+        chooser.date_start = chooser.date_end - chrono::Duration::days(1);
+        let props = chooser.props().unwrap();
+        assert_eq!(props.date_start, chooser.date_start);
+
+        chooser.time_end_input = TimeInput::new(24, 0, 0);
+        assert_eq!(chooser.props(), Err("invalid time, use hh:mm:ss"));
+    }
+
+    #[test]
+    fn test_snapped_to_interval() {
+        let (mut chooser, ..) = chooser(true);
+        let date = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+        chooser.date_start = date;
+        chooser.date_end = date;
+        chooser.interval = Interval::Hour;
+        chooser.time_start_input = TimeInput::new(10, 7, 0);
+        chooser.time_end_input = TimeInput::new(14, 52, 0);
+        let ts = |h| {
+            date.and_hms_opt(h, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp_millis()
+        };
+
+        let props = chooser.props().unwrap();
+        assert_eq!(props.bounds, BoundsSet::new(vec![Bounds(ts(10), ts(15))]));
+        assert_eq!(
+            TimeRangeChooser::effective_range(&props).as_deref(),
+            Some("effective range: 10:00:00 – 15:00:00")
+        );
+
+        chooser.snap = false;
+        let props = chooser.props().unwrap();
+        assert_eq!(props.start_time().timestamp_millis(), ts(10) + 7 * 60_000);
+        assert_eq!(
+            props.bounds.hull(),
+            Some(Bounds(ts(10) + 7 * 60_000, ts(14) + 52 * 60_000))
+        );
+        assert_eq!(TimeRangeChooser::effective_range(&props), None);
+    }
+
+    #[test]
+    fn test_overnight_sessions() {
+        let (mut chooser, ..) = chooser(true);
+        let date = |d| NaiveDate::from_ymd_opt(2022, 1, d).unwrap();
+        chooser.date_start = date(1);
+        chooser.date_end = date(1);
+        chooser.interval = Interval::Hour;
+        chooser.time_start_input = TimeInput::new(22, 0, 0);
+        chooser.time_end_input = TimeInput::new(6, 0, 0);
+        assert_eq!(
+            chooser.props(),
+            Err("start must be before end, or turn on overnight sessions")
+        );
+
+        chooser.overnight = true;
+        assert_eq!(
+            chooser.props(),
+            Err("overnight sessions end on the next day, pick a later end date")
+        );
+
+        chooser.date_end = date(3);
+        let props = chooser.props().unwrap();
+        let ts = |d, h| {
+            date(d)
+                .and_hms_opt(h, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp_millis()
+        };
+        assert_eq!(
+            props.bounds,
+            BoundsSet::new(vec![
+                Bounds(ts(1, 22), ts(2, 6)),
+                Bounds(ts(2, 22), ts(3, 6))
+            ])
+        );
+        assert_eq!(TimeRangeChooser::effective_range(&props), None);
+
+        chooser.overnight = false;
+        assert_eq!(
+            chooser.props().unwrap().bounds,
+            BoundsSet::new(vec![Bounds(ts(1, 22), ts(3, 6))])
+        );
+    }
+
+    #[test]
+    fn test_suggestion_follows_inputs() {
+        let (mut chooser, ..) = chooser(true);
+        chooser.date_start = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        chooser.date_end = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+        chooser.time_start_input = TimeInput::new(0, 0, 0);
+        chooser.time_end_input = TimeInput::new(0, 0, 0);
+        chooser.interval = Interval::Minute;
+
+        let props = chooser.props().unwrap();
+        assert_eq!(
+            chooser.suggestion(&props),
+            Some((
+                "this range at 1m is ~1.05M candles, consider 15m".to_string(),
+                Interval::FifteenMinutes
+            ))
+        );
+
+        chooser.interval = Interval::FifteenMinutes;
+        assert_eq!(chooser.suggestion(&chooser.props().unwrap()), None);
+        assert_eq!(format_count(12_345), "12k");
     }
 }
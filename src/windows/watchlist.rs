@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Context, Grid, RichText, ScrollArea, Ui, Window};
+use poll_promise::Promise;
+use tracing::{error, info};
+
+use crate::{
+    errors::Result,
+    netstrat::{
+        actions::Actions,
+        channels::LatestSender,
+        data::Data,
+        selection::{Selection, Source},
+        session::Session,
+        watchlist,
+    },
+    sources::binance::{Client, Ticker},
+};
+
+use super::AppWindow;
+
+const WATCHLIST_KEY: &str = "watchlist";
+const ACTION_TOGGLE: &str = "window.watchlist";
+/// Tickers are refreshed this often while the window is shown.
+const REFRESH_EVERY: Duration = Duration::from_secs(60);
+
+type Tickers = Result<Vec<(String, Ticker)>>;
+
+/// Last prices and 24 hour changes of the watched symbols, clicking one shows it on the
+/// chart.
+pub struct Watchlist {
+    visible: bool,
+    watchlist: watchlist::Watchlist,
+    tickers: BTreeMap<String, Ticker>,
+    tickers_promise: Option<Promise<Tickers>>,
+    /// Symbols of the last request and when it was sent.
+    requested: Option<(Vec<String>, Instant)>,
+    tickers_error: Option<String>,
+    symbol_pub: LatestSender<Selection>,
+    actions: Actions,
+}
+
+impl Watchlist {
+    pub fn new(
+        visible: bool,
+        watchlist: watchlist::Watchlist,
+        symbol_pub: LatestSender<Selection>,
+        actions: Actions,
+        storage: Option<&dyn eframe::Storage>,
+    ) -> Self {
+        actions.register(ACTION_TOGGLE, "toggle watchlist window", None);
+        if let Some(saved) = storage.and_then(|s| eframe::get_value(s, WATCHLIST_KEY)) {
+            watchlist.set_symbols(saved);
+        }
+        Self {
+            visible,
+            watchlist,
+            tickers: Default::default(),
+            tickers_promise: None,
+            requested: None,
+            tickers_error: None,
+            symbol_pub,
+            actions,
+        }
+    }
+
+    /// Whether the tickers are stale or the watched symbols changed since the last request.
+    fn refresh_due(&self, symbols: &[String]) -> bool {
+        self.requested
+            .as_ref()
+            .is_none_or(|(requested, at)| requested != symbols || at.elapsed() >= REFRESH_EVERY)
+    }
+
+    fn refresh_tickers(&mut self) {
+        if let Some(promise) = self.tickers_promise.take() {
+            match promise.try_take() {
+                Ok(Ok(tickers)) => {
+                    self.tickers_error = None;
+                    self.tickers = tickers.into_iter().collect();
+                }
+                Ok(Err(err)) => {
+                    error!("failed to refresh watchlist: {}", err.chain());
+                    self.tickers_error = Some(err.user_message());
+                }
+                Err(promise) => self.tickers_promise = Some(promise),
+            }
+            return;
+        }
+
+        let symbols = self.watchlist.symbols();
+        if !self.refresh_due(&symbols) {
+            return;
+        }
+        self.requested = Some((symbols.clone(), Instant::now()));
+        if symbols.is_empty() {
+            self.tickers.clear();
+            return;
+        }
+        info!("refreshing watchlist of {symbols:?}");
+        self.tickers_promise = Some(Promise::spawn_async(async move {
+            Client::default().tickers(&symbols).await
+        }));
+    }
+
+    fn rows_ui(&self, ui: &mut Ui, symbols: &[String]) {
+        ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            Grid::new("watchlist rows").striped(true).show(ui, |ui| {
+                ["symbol", "price", "24h", "at", ""]
+                    .into_iter()
+                    .for_each(|h| {
+                        ui.strong(h);
+                    });
+                ui.end_row();
+
+                symbols.iter().for_each(|symbol| {
+                    if ui
+                        .button(symbol)
+                        .on_hover_text("show on the chart")
+                        .clicked()
+                    {
+                        let selection = Selection::new(symbol, Source::List);
+                        self.symbol_pub.send(selection);
+                    }
+                    match self.tickers.get(symbol) {
+                        Some(t) => {
+                            ui.label(t.last_price.to_string());
+                            let color = match t.price_change_percent >= 0.0 {
+                                true => Color32::LIGHT_GREEN,
+                                false => Color32::LIGHT_RED,
+                            };
+                            ui.colored_label(color, format!("{:+.2}%", t.price_change_percent));
+                            ui.label(Data::format_ts(t.ts as f64));
+                        }
+                        None => {
+                            ui.weak("-");
+                            ui.weak("-");
+                            ui.weak("-");
+                        }
+                    }
+                    if ui
+                        .small_button("✖")
+                        .on_hover_text("stop watching")
+                        .clicked()
+                    {
+                        self.watchlist.remove(symbol);
+                    }
+                    ui.end_row();
+                });
+            });
+        });
+    }
+}
+
+impl AppWindow for Watchlist {
+    fn toggle_btn(&mut self, ui: &mut Ui) {
+        if ui.button("Watchlist").clicked() {
+            self.toggle()
+        }
+    }
+
+    fn toggle(&mut self) {
+        self.visible = !self.visible
+    }
+
+    fn tick(&mut self, _ctx: &Context) {
+        if self.actions.take(ACTION_TOGGLE) {
+            self.toggle();
+        }
+        if self.visible {
+            self.refresh_tickers();
+        }
+    }
+
+    fn show(&mut self, ui: &mut Ui) {
+        if !self.visible {
+            return;
+        }
+
+        let mut visible = self.visible;
+        Window::new("watchlist")
+            .open(&mut visible)
+            .drag_bounds(ui.max_rect())
+            .default_width(400.0)
+            .show(ui.ctx(), |ui| {
+                let symbols = self.watchlist.symbols();
+                match symbols.is_empty() {
+                    true => {
+                        ui.label("no symbols watched, check some in the symbols list to add them");
+                    }
+                    false => self.rows_ui(ui, &symbols),
+                }
+                if self.tickers_promise.is_some() {
+                    ui.spinner();
+                }
+                if let Some(err) = &self.tickers_error {
+                    ui.label(
+                        RichText::new(format!("failed to refresh prices: {err}"))
+                            .color(Color32::LIGHT_RED),
+                    );
+                }
+            });
+        self.visible = visible;
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, WATCHLIST_KEY, &self.watchlist.symbols());
+    }
+
+    fn snapshot(&self, session: &mut Session) {
+        session.set_visible("watchlist", self.visible);
+    }
+
+    fn restore(&mut self, session: &Session) {
+        self.visible = session.visible("watchlist").unwrap_or(self.visible);
+    }
+}
+
+#[cfg(test)]
+mod watchlist_window_tests {
+    use super::*;
+    use crate::netstrat::channels;
+
+    #[test]
+    fn test_refresh_due_when_symbols_change() {
+        let (s, _r) = channels::latest();
+        let watchlist = watchlist::Watchlist::default();
+        let mut window = Watchlist::new(true, watchlist.clone(), s, Actions::default(), None);
+
+        // nothing to fetch while empty
+        window.refresh_tickers();
+        assert!(window.tickers_promise.is_none());
+        assert!(!window.refresh_due(&[]));
+
+        watchlist.extend(["BTCUSDT".to_string()]);
+        assert!(window.refresh_due(&watchlist.symbols()));
+    }
+}
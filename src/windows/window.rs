@@ -1,6 +1,31 @@
-use egui::Ui;
+use egui::{Context, Ui};
+
+use crate::netstrat::session::Session;
 
 pub trait AppWindow {
     fn toggle_btn(&mut self, ui: &mut Ui);
+
+    /// Shows the window when hidden and hides it when shown.
+    fn toggle(&mut self);
+
+    /// Drains channels of the window, called on every frame whether it is shown or not.
+    fn tick(&mut self, _ctx: &Context) {}
+
+    /// Draws the window, returning right away when it is hidden.
     fn show(&mut self, ui: &mut Ui);
+
+    /// Persists window state between app runs.
+    fn save(&mut self, _storage: &mut dyn eframe::Storage) {}
+
+    /// Adds window state to a session snapshot.
+    fn snapshot(&self, _session: &mut Session) {}
+
+    /// Restores window state from a session snapshot.
+    fn restore(&mut self, _session: &Session) {}
+
+    /// Draws the main content filling the ui without the window around it, returns
+    /// whether the window has any.
+    fn fullscreen(&mut self, _ui: &mut Ui) -> bool {
+        false
+    }
 }